@@ -6,6 +6,7 @@ use chrono::{DateTime, Duration, Utc};
 use smartassist_core::types::ApprovalId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 use tokio::sync::{broadcast, RwLock};
 use tracing::{debug, warn};
 
@@ -20,6 +21,9 @@ pub struct ApprovalManager {
     /// Default timeout for approvals.
     default_timeout: Duration,
 
+    /// Outcome applied when a request times out unanswered.
+    timeout_action: TimeoutAction,
+
     /// Approval policy.
     policy: RwLock<ApprovalPolicy>,
 }
@@ -38,6 +42,7 @@ impl ApprovalManager {
             pending: RwLock::new(HashMap::new()),
             response_tx: tx,
             default_timeout: Duration::minutes(5),
+            timeout_action: TimeoutAction::default(),
             policy: RwLock::new(ApprovalPolicy::default()),
         }
     }
@@ -48,6 +53,12 @@ impl ApprovalManager {
         self
     }
 
+    /// Set the outcome applied when a request times out.
+    pub fn with_timeout_action(mut self, action: TimeoutAction) -> Self {
+        self.timeout_action = action;
+        self
+    }
+
     /// Set the approval policy.
     pub async fn set_policy(&self, policy: ApprovalPolicy) {
         let mut p = self.policy.write().await;
@@ -81,6 +92,7 @@ impl ApprovalManager {
                     reason: Some("Auto-approved by policy".to_string()),
                     modifications: None,
                     responded_at: Utc::now(),
+                    auto_resolved: true,
                 }),
             });
         }
@@ -102,6 +114,7 @@ impl ApprovalManager {
                     reason: Some("Denied by policy".to_string()),
                     modifications: None,
                     responded_at: Utc::now(),
+                    auto_resolved: true,
                 }),
             });
         }
@@ -130,6 +143,29 @@ impl ApprovalManager {
         Ok(request)
     }
 
+    /// Request approval and wait for the response.
+    ///
+    /// If no response arrives within `timeout` (or the default timeout), the
+    /// request is resolved with the configured [`TimeoutAction`].
+    pub async fn request_and_wait(
+        &self,
+        session_id: String,
+        tool_name: String,
+        tool_args: serde_json::Value,
+        description: String,
+        timeout: Option<Duration>,
+    ) -> Result<ApprovalResponse> {
+        let request = self
+            .request(session_id, tool_name, tool_args, description)
+            .await?;
+
+        if let Some(response) = request.response {
+            return Ok(response);
+        }
+
+        self.wait_for_response(&request.id, timeout).await
+    }
+
     /// Respond to an approval request.
     pub async fn respond(&self, id: &ApprovalId, response: ApprovalResponse) -> Result<()> {
         let mut pending = self.pending.write().await;
@@ -179,54 +215,76 @@ impl ApprovalManager {
     }
 
     /// Wait for an approval response.
+    ///
+    /// Resolves with the configured [`TimeoutAction`] once the timeout
+    /// elapses without a response.
     pub async fn wait_for_response(
         &self,
         id: &ApprovalId,
         timeout: Option<Duration>,
     ) -> Result<ApprovalResponse> {
         let timeout = timeout.unwrap_or(self.default_timeout);
-        let deadline = Utc::now() + timeout;
+        let deadline = tokio::time::Instant::now() + timeout.to_std().unwrap_or_default();
 
         let mut rx = self.response_tx.subscribe();
 
         loop {
             // Check if already responded
-            if let Some(request) = self.get(id).await {
-                if let Some(response) = request.response {
-                    return Ok(response);
+            match self.get(id).await {
+                Some(request) => {
+                    if let Some(response) = request.response {
+                        return Ok(response);
+                    }
                 }
-            }
-
-            // Check timeout
-            if Utc::now() >= deadline {
-                // Mark as expired
-                let mut pending = self.pending.write().await;
-                if let Some(request) = pending.get_mut(id) {
-                    request.status = ApprovalStatus::Expired;
+                None => {
+                    return Err(AgentError::Internal(format!(
+                        "Approval request not found: {}",
+                        id
+                    )));
                 }
-                return Err(AgentError::ApprovalTimeout(id.to_string()));
             }
 
-            // Wait for event
-            let wait_time = (deadline - Utc::now())
-                .to_std()
-                .unwrap_or(std::time::Duration::from_secs(1));
-
-            match tokio::time::timeout(wait_time, rx.recv()).await {
-                Ok(Ok(ApprovalEvent::Responded { id: event_id, approved: _ })) => {
-                    if &event_id == id {
-                        if let Some(request) = self.get(id).await {
-                            if let Some(response) = request.response {
-                                return Ok(response);
-                            }
-                        }
-                    }
+            // Wait for the matching response event or the deadline
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Ok(ApprovalEvent::Responded { id: event_id, .. })) if &event_id == id => {}
+                Ok(Ok(_)) | Ok(Err(broadcast::error::RecvError::Lagged(_))) => continue,
+                Ok(Err(broadcast::error::RecvError::Closed)) | Err(_) => {
+                    return self.auto_resolve(id).await;
                 }
-                _ => continue,
             }
         }
     }
 
+    /// Resolve a timed-out request with the configured timeout action.
+    async fn auto_resolve(&self, id: &ApprovalId) -> Result<ApprovalResponse> {
+        let mut pending = self.pending.write().await;
+
+        let request = pending
+            .get_mut(id)
+            .ok_or_else(|| AgentError::Internal(format!("Approval request not found: {}", id)))?;
+
+        // A response may have arrived just as the timeout fired
+        if let Some(ref response) = request.response {
+            return Ok(response.clone());
+        }
+
+        let response = self.timeout_action.response();
+        request.status = ApprovalStatus::Expired;
+        request.response = Some(response.clone());
+
+        let _ = self.response_tx.send(ApprovalEvent::Responded {
+            id: id.clone(),
+            approved: response.approved,
+        });
+
+        warn!(
+            "Approval request {} timed out, auto-{}",
+            id,
+            if response.approved { "approved" } else { "denied" }
+        );
+        Ok(response)
+    }
+
     /// Clean up expired requests.
     pub async fn cleanup_expired(&self) {
         let now = Utc::now();
@@ -240,17 +298,47 @@ impl ApprovalManager {
 
         for id in expired {
             if let Some(request) = pending.get_mut(&id) {
+                let response = self.timeout_action.response();
                 request.status = ApprovalStatus::Expired;
+                request.response = Some(response.clone());
+
+                let _ = self.response_tx.send(ApprovalEvent::Responded {
+                    id: id.clone(),
+                    approved: response.approved,
+                });
                 warn!("Approval request {} expired", id);
             }
         }
     }
 
     /// Check if a tool requires approval based on policy (synchronous check).
-    pub fn requires_approval(&self, _tool_name: &str, _tool_args: &serde_json::Value) -> bool {
-        // For now, return false - actual policy check would need async
-        // This is a simplified check; real implementation should be async
-        false
+    ///
+    /// Only reports rules that force approval; returns `false` if the policy
+    /// is currently being updated. Prefer [`ApprovalManager::evaluate`],
+    /// which also accounts for the tool's own answer.
+    pub fn requires_approval(&self, tool_name: &str, tool_args: &serde_json::Value) -> bool {
+        match self.policy.try_read() {
+            Ok(policy) => policy.evaluate(tool_name, tool_args, false).required,
+            Err(_) => false,
+        }
+    }
+
+    /// Decide whether a tool call needs approval.
+    ///
+    /// `tool_requires` is the tool's own answer; policy rules may override it.
+    pub async fn evaluate(
+        &self,
+        tool_name: &str,
+        tool_args: &serde_json::Value,
+        tool_requires: bool,
+    ) -> ApprovalDecision {
+        let policy = self.policy.read().await;
+        let decision = policy.evaluate(tool_name, tool_args, tool_requires);
+        debug!(
+            "Approval decision for '{}': required={} ({})",
+            tool_name, decision.required, decision.reason
+        );
+        decision
     }
 
     /// Subscribe to approval events.
@@ -303,10 +391,35 @@ pub enum ApprovalStatus {
     /// Denied.
     Denied,
 
-    /// Expired without response.
+    /// Expired without response (resolved by the timeout action).
     Expired,
 }
 
+/// Outcome applied to an approval request that times out unanswered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutAction {
+    /// Deny the tool call.
+    #[default]
+    Deny,
+
+    /// Allow the tool call.
+    Allow,
+}
+
+impl TimeoutAction {
+    /// Build the auto-resolved response for this action.
+    fn response(self) -> ApprovalResponse {
+        ApprovalResponse {
+            approved: self == TimeoutAction::Allow,
+            reason: Some("No response before timeout".to_string()),
+            modifications: None,
+            responded_at: Utc::now(),
+            auto_resolved: true,
+        }
+    }
+}
+
 /// Response to an approval request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApprovalResponse {
@@ -321,6 +434,11 @@ pub struct ApprovalResponse {
 
     /// Response timestamp.
     pub responded_at: DateTime<Utc>,
+
+    /// Whether the response was produced automatically (policy or timeout)
+    /// rather than by a person.
+    #[serde(default)]
+    pub auto_resolved: bool,
 }
 
 impl ApprovalResponse {
@@ -331,6 +449,7 @@ impl ApprovalResponse {
             reason: None,
             modifications: None,
             responded_at: Utc::now(),
+            auto_resolved: false,
         }
     }
 
@@ -341,6 +460,7 @@ impl ApprovalResponse {
             reason: Some(reason.into()),
             modifications: None,
             responded_at: Utc::now(),
+            auto_resolved: false,
         }
     }
 
@@ -390,6 +510,110 @@ pub struct ApprovalPolicy {
     /// Pattern-based auto-deny rules.
     #[serde(default)]
     pub auto_deny_patterns: Vec<PolicyPattern>,
+
+    /// Rules deciding whether a tool call needs approval, overriding the
+    /// tool's own answer. The first matching rule wins.
+    #[serde(default)]
+    pub rules: Vec<ApprovalRule>,
+
+    /// Workspace root used by `outside_workspace` rule conditions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<PathBuf>,
+}
+
+/// A rule that forces or waives approval for matching tool calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    /// Rule name, reported when the rule matches.
+    pub name: String,
+
+    /// Tool name pattern (glob, e.g. `bash` or `file_*`).
+    pub tool: String,
+
+    /// Argument patterns (JSON path -> regex). All must match.
+    #[serde(default)]
+    pub arg_patterns: HashMap<String, String>,
+
+    /// Path arguments that must point outside the workspace for the rule
+    /// to match.
+    #[serde(default)]
+    pub outside_workspace: Vec<String>,
+
+    /// What to do when the rule matches.
+    #[serde(default)]
+    pub action: ApprovalRuleAction,
+}
+
+impl ApprovalRule {
+    /// Create a rule that requires approval for matching tools.
+    pub fn require(name: impl Into<String>, tool: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tool: tool.into(),
+            arg_patterns: HashMap::new(),
+            outside_workspace: Vec::new(),
+            action: ApprovalRuleAction::Require,
+        }
+    }
+
+    /// Create a rule that waives approval for matching tools.
+    pub fn allow(name: impl Into<String>, tool: impl Into<String>) -> Self {
+        Self {
+            action: ApprovalRuleAction::Allow,
+            ..Self::require(name, tool)
+        }
+    }
+
+    /// Add an argument pattern.
+    pub fn with_arg_pattern(mut self, path: impl Into<String>, regex: impl Into<String>) -> Self {
+        self.arg_patterns.insert(path.into(), regex.into());
+        self
+    }
+
+    /// Only match when the given path argument is outside the workspace.
+    pub fn outside_workspace(mut self, arg: impl Into<String>) -> Self {
+        self.outside_workspace.push(arg.into());
+        self
+    }
+
+    /// Check whether this rule matches a tool call.
+    pub fn matches(&self, tool: &str, args: &serde_json::Value, workspace: Option<&Path>) -> bool {
+        let tool_matches = glob::Pattern::new(&self.tool)
+            .map(|p| p.matches(tool))
+            .unwrap_or(false);
+
+        tool_matches
+            && args_match(&self.arg_patterns, args)
+            && self
+                .outside_workspace
+                .iter()
+                .all(|arg| path_outside_workspace(args, arg, workspace))
+    }
+}
+
+/// Action taken when an approval rule matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalRuleAction {
+    /// Approval is required.
+    #[default]
+    Require,
+
+    /// Approval is not required.
+    Allow,
+}
+
+/// Outcome of evaluating the approval policy for a tool call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalDecision {
+    /// Whether approval is required.
+    pub required: bool,
+
+    /// Name of the rule that decided, if any.
+    pub matched_rule: Option<String>,
+
+    /// Human-readable explanation of the decision.
+    pub reason: String,
 }
 
 /// A pattern-based policy rule.
@@ -404,8 +628,44 @@ pub struct PolicyPattern {
 }
 
 impl ApprovalPolicy {
+    /// Decide whether a tool call needs approval.
+    ///
+    /// The first matching rule decides; otherwise the tool's own answer
+    /// (`tool_requires`) is used.
+    pub fn evaluate(
+        &self,
+        tool: &str,
+        args: &serde_json::Value,
+        tool_requires: bool,
+    ) -> ApprovalDecision {
+        let workspace = self.workspace.as_deref();
+
+        if let Some(rule) = self.rules.iter().find(|r| r.matches(tool, args, workspace)) {
+            let required = rule.action == ApprovalRuleAction::Require;
+            return ApprovalDecision {
+                required,
+                matched_rule: Some(rule.name.clone()),
+                reason: format!(
+                    "Rule '{}' {} approval",
+                    rule.name,
+                    if required { "requires" } else { "waives" }
+                ),
+            };
+        }
+
+        ApprovalDecision {
+            required: tool_requires,
+            matched_rule: None,
+            reason: if tool_requires {
+                "Tool requires approval".to_string()
+            } else {
+                "Tool does not require approval".to_string()
+            },
+        }
+    }
+
     /// Check if a tool is auto-approved.
-    pub fn is_auto_approved(&self, tool: &str, _args: &serde_json::Value) -> bool {
+    pub fn is_auto_approved(&self, tool: &str, args: &serde_json::Value) -> bool {
         if self.auto_approve.contains(&tool.to_string()) {
             return true;
         }
@@ -413,8 +673,7 @@ impl ApprovalPolicy {
         // Check patterns
         for pattern in &self.auto_approve_patterns {
             if let Ok(re) = regex::Regex::new(&pattern.tool_pattern) {
-                if re.is_match(tool) {
-                    // TODO: Check arg patterns
+                if re.is_match(tool) && args_match(&pattern.arg_patterns, args) {
                     return true;
                 }
             }
@@ -424,7 +683,7 @@ impl ApprovalPolicy {
     }
 
     /// Check if a tool is auto-denied.
-    pub fn is_auto_denied(&self, tool: &str, _args: &serde_json::Value) -> bool {
+    pub fn is_auto_denied(&self, tool: &str, args: &serde_json::Value) -> bool {
         if self.auto_deny.contains(&tool.to_string()) {
             return true;
         }
@@ -432,8 +691,7 @@ impl ApprovalPolicy {
         // Check patterns
         for pattern in &self.auto_deny_patterns {
             if let Ok(re) = regex::Regex::new(&pattern.tool_pattern) {
-                if re.is_match(tool) {
-                    // TODO: Check arg patterns
+                if re.is_match(tool) && args_match(&pattern.arg_patterns, args) {
                     return true;
                 }
             }
@@ -443,9 +701,63 @@ impl ApprovalPolicy {
     }
 }
 
+/// Look up an argument by dotted JSON path (e.g. `options.path`).
+fn arg_value<'a>(args: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(args, |value, key| value.get(key))
+}
+
+/// Check that every argument pattern matches. Missing arguments and invalid
+/// regexes never match.
+fn args_match(patterns: &HashMap<String, String>, args: &serde_json::Value) -> bool {
+    patterns.iter().all(|(path, pattern)| {
+        let Some(value) = arg_value(args, path) else {
+            return false;
+        };
+        let text = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        regex::Regex::new(pattern)
+            .map(|re| re.is_match(&text))
+            .unwrap_or(false)
+    })
+}
+
+/// Check whether a path argument points outside the workspace.
+///
+/// Paths are resolved lexically against the workspace. Without a
+/// workspace every path is treated as outside.
+fn path_outside_workspace(args: &serde_json::Value, arg: &str, workspace: Option<&Path>) -> bool {
+    let Some(path) = arg_value(args, arg).and_then(|v| v.as_str()) else {
+        return false;
+    };
+    let Some(workspace) = workspace else {
+        return true;
+    };
+
+    let resolved = normalize_path(&workspace.join(path));
+    !resolved.starts_with(normalize_path(workspace))
+}
+
+/// Lexically normalize a path, resolving `.` and `..` components.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[tokio::test]
     async fn test_approval_request() {
@@ -509,4 +821,155 @@ mod tests {
 
         assert_eq!(request.status, ApprovalStatus::Approved);
     }
+
+    fn dangerous_rm_policy() -> ApprovalPolicy {
+        ApprovalPolicy {
+            rules: vec![
+                ApprovalRule::require("no-rm", "bash").with_arg_pattern("command", r"\brm\b"),
+                ApprovalRule::require("delete-outside-workspace", "file_delete")
+                    .outside_workspace("path"),
+            ],
+            workspace: Some(PathBuf::from("/home/user/project")),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rule_forces_approval_on_dangerous_command() {
+        let policy = dangerous_rm_policy();
+
+        let decision = policy.evaluate("bash", &serde_json::json!({"command": "rm -rf build"}), false);
+        assert!(decision.required);
+        assert_eq!(decision.matched_rule.as_deref(), Some("no-rm"));
+        assert!(decision.reason.contains("no-rm"));
+    }
+
+    #[test]
+    fn test_rule_allows_benign_command() {
+        let policy = dangerous_rm_policy();
+
+        let decision = policy.evaluate("bash", &serde_json::json!({"command": "ls -la"}), false);
+        assert!(!decision.required);
+        assert!(decision.matched_rule.is_none());
+    }
+
+    #[test]
+    fn test_rule_outside_workspace() {
+        let policy = dangerous_rm_policy();
+
+        let inside = policy.evaluate("file_delete", &serde_json::json!({"path": "src/old.rs"}), false);
+        assert!(!inside.required);
+
+        let escaped = policy.evaluate("file_delete", &serde_json::json!({"path": "../other/file"}), false);
+        assert!(escaped.required);
+        assert_eq!(escaped.matched_rule.as_deref(), Some("delete-outside-workspace"));
+
+        let absolute = policy.evaluate("file_delete", &serde_json::json!({"path": "/etc/passwd"}), false);
+        assert!(absolute.required);
+    }
+
+    #[test]
+    fn test_allow_rule_overrides_tool() {
+        let policy = ApprovalPolicy {
+            rules: vec![ApprovalRule::allow("scratch-writes", "write")
+                .with_arg_pattern("path", r"^/tmp/")],
+            ..Default::default()
+        };
+
+        let decision = policy.evaluate("write", &serde_json::json!({"path": "/tmp/out.txt"}), true);
+        assert!(!decision.required);
+        assert_eq!(decision.matched_rule.as_deref(), Some("scratch-writes"));
+
+        let fallback = policy.evaluate("write", &serde_json::json!({"path": "/etc/hosts"}), true);
+        assert!(fallback.required);
+        assert!(fallback.matched_rule.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_manager_evaluate_uses_policy() {
+        let manager = ApprovalManager::new();
+        manager.set_policy(dangerous_rm_policy()).await;
+
+        let args = serde_json::json!({"command": "rm file.txt"});
+        assert!(manager.evaluate("bash", &args, false).await.required);
+        assert!(manager.requires_approval("bash", &args));
+        assert!(!manager.requires_approval("bash", &serde_json::json!({"command": "pwd"})));
+    }
+
+    #[tokio::test]
+    async fn test_request_auto_denied_after_timeout() {
+        let manager = ApprovalManager::new().with_timeout(Duration::milliseconds(50));
+
+        let started = std::time::Instant::now();
+        let response = manager
+            .request_and_wait(
+                "session1".to_string(),
+                "bash".to_string(),
+                serde_json::json!({"command": "rm -rf build"}),
+                "Delete build output".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!response.approved);
+        assert!(response.auto_resolved);
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+
+        let pending = manager.list_pending("session1").await;
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_action_allow() {
+        let manager = ApprovalManager::new()
+            .with_timeout(Duration::milliseconds(20))
+            .with_timeout_action(TimeoutAction::Allow);
+
+        let request = manager
+            .request(
+                "session1".to_string(),
+                "bash".to_string(),
+                serde_json::json!({"command": "ls"}),
+                "List files".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let response = manager.wait_for_response(&request.id, None).await.unwrap();
+        assert!(response.approved);
+        assert!(response.auto_resolved);
+
+        let updated = manager.get(&request.id).await.unwrap();
+        assert_eq!(updated.status, ApprovalStatus::Expired);
+    }
+
+    #[tokio::test]
+    async fn test_request_answered_before_timeout() {
+        let manager = Arc::new(ApprovalManager::new().with_timeout(Duration::seconds(5)));
+
+        let request = manager
+            .request(
+                "session1".to_string(),
+                "bash".to_string(),
+                serde_json::json!({"command": "ls"}),
+                "List files".to_string(),
+            )
+            .await
+            .unwrap();
+
+        let responder = manager.clone();
+        let id = request.id.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            responder.respond(&id, ApprovalResponse::approve()).await.unwrap();
+        });
+
+        let response = manager.wait_for_response(&request.id, None).await.unwrap();
+        assert!(response.approved);
+        assert!(!response.auto_resolved);
+
+        let updated = manager.get(&request.id).await.unwrap();
+        assert_eq!(updated.status, ApprovalStatus::Approved);
+    }
 }
@@ -34,6 +34,10 @@ pub enum AgentError {
     #[error("Tool not found: {0}")]
     ToolNotFound(String),
 
+    /// Prompt preset not found.
+    #[error("Prompt preset not found: {0}")]
+    PromptNotFound(String),
+
     /// Tool execution error.
     #[error("Tool execution failed: {0}")]
     ToolExecution(String),
@@ -166,6 +170,12 @@ impl From<smartassist_channels::ChannelError> for AgentError {
     }
 }
 
+impl From<smartassist_secrets::SecretError> for AgentError {
+    fn from(e: smartassist_secrets::SecretError) -> Self {
+        Self::Config(e.to_string())
+    }
+}
+
 impl From<smartassist_core::error::SecurityError> for AgentError {
     fn from(e: smartassist_core::error::SecurityError) -> Self {
         Self::ToolExecution(e.to_string())
@@ -12,12 +12,23 @@ pub mod session;
 pub mod tools;
 pub mod providers;
 pub mod approval;
+pub mod prompts;
 
 pub use error::AgentError;
-pub use runtime::{AgentRuntime, RuntimeConfig};
-pub use session::{Session, SessionManager, SessionState};
+pub use runtime::{
+    AgentEvent, AgentRuntime, RuntimeConfig, TurnEvent, TurnOutcome, TurnResult,
+    DEFAULT_EVENT_BUFFER,
+};
+pub use session::{
+    ExportFormat, Session, SessionEncryption, SessionGuard, SessionManager, SessionState,
+    DEFAULT_SESSION_KEY_SECRET,
+};
+pub use prompts::{PromptLibrary, PromptPreset};
 pub use tools::{Tool, ToolContext, ToolExecutor, ToolRegistry};
-pub use approval::{ApprovalManager, ApprovalRequest, ApprovalResponse};
+pub use approval::{
+    ApprovalDecision, ApprovalManager, ApprovalPolicy, ApprovalRequest, ApprovalResponse,
+    ApprovalRule, TimeoutAction,
+};
 
 /// Result type for agent operations.
 pub type Result<T> = std::result::Result<T, AgentError>;
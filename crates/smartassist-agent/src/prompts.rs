@@ -0,0 +1,265 @@
+//! Named system-prompt presets.
+//!
+//! A [`PromptLibrary`] maps names to [`PromptPreset`]s so personas can be
+//! written once and selected by name, from [`AgentRuntime`] or a session.
+//! Preset text can hold `{{variable}}` placeholders, filled from the
+//! caller's variables with the preset's defaults as fallback. The library
+//! is built from values the caller provides, such as a section of a config
+//! file it has already parsed; it never reads files itself.
+//!
+//! [`AgentRuntime`]: crate::runtime::AgentRuntime
+
+use crate::error::AgentError;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use smartassist_core::template::{render, TemplateVars};
+use std::collections::{BTreeMap, HashMap};
+
+/// A reusable system prompt.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptPreset {
+    /// System prompt text, with `{{variable}}` placeholders.
+    pub prompt: String,
+
+    /// Short description for listings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Values for placeholders the caller leaves unset.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: TemplateVars,
+}
+
+impl PromptPreset {
+    /// Create a preset from its prompt text.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the default value of a placeholder.
+    pub fn with_default(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.defaults.insert(name.into(), value.into());
+        self
+    }
+
+    /// Names of the placeholders in the prompt, sorted and without repeats.
+    pub fn variables(&self) -> Vec<String> {
+        match render(&self.prompt, &TemplateVars::new()) {
+            Ok(_) => Vec::new(),
+            Err(undefined) => undefined.0,
+        }
+    }
+
+    /// Fill the prompt's placeholders from `vars`, then from the defaults.
+    ///
+    /// Fails if a placeholder has neither, rather than handing the model a
+    /// prompt with literal braces in it.
+    pub fn render(&self, vars: &TemplateVars) -> Result<String> {
+        let mut merged = self.defaults.clone();
+        merged.extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+        render(&self.prompt, &merged).map_err(|e| AgentError::config(e.to_string()))
+    }
+}
+
+impl From<&str> for PromptPreset {
+    fn from(prompt: &str) -> Self {
+        Self::new(prompt)
+    }
+}
+
+impl From<String> for PromptPreset {
+    fn from(prompt: String) -> Self {
+        Self::new(prompt)
+    }
+}
+
+/// System-prompt presets by name.
+///
+/// Serializes as a map from name to preset, so it can sit in a config
+/// struct.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PromptLibrary {
+    presets: BTreeMap<String, PromptPreset>,
+}
+
+impl PromptLibrary {
+    /// Create an empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a preset, replacing any with the same name.
+    pub fn with_preset(mut self, name: impl Into<String>, preset: impl Into<PromptPreset>) -> Self {
+        self.insert(name, preset);
+        self
+    }
+
+    /// Add a preset, returning the one it replaced.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        preset: impl Into<PromptPreset>,
+    ) -> Option<PromptPreset> {
+        self.presets.insert(name.into(), preset.into())
+    }
+
+    /// Remove a preset.
+    pub fn remove(&mut self, name: &str) -> Option<PromptPreset> {
+        self.presets.remove(name)
+    }
+
+    /// Get a preset by name.
+    pub fn get(&self, name: &str) -> Option<&PromptPreset> {
+        self.presets.get(name)
+    }
+
+    /// Check whether a preset exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.presets.contains_key(name)
+    }
+
+    /// Preset names, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    /// Number of presets.
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Check whether the library is empty.
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Render the preset `name` with `vars`.
+    ///
+    /// Fails with [`AgentError::PromptNotFound`] for an unknown name, and
+    /// with a config error if a placeholder is left without a value.
+    pub fn resolve(&self, name: &str, vars: &TemplateVars) -> Result<String> {
+        let preset = self
+            .get(name)
+            .ok_or_else(|| AgentError::PromptNotFound(name.to_string()))?;
+        preset.render(vars).map_err(|e| match e {
+            AgentError::Config(msg) => {
+                AgentError::config(format!("Prompt preset '{}': {}", name, msg))
+            }
+            other => other,
+        })
+    }
+}
+
+impl<K: Into<String>, P: Into<PromptPreset>> FromIterator<(K, P)> for PromptLibrary {
+    fn from_iter<I: IntoIterator<Item = (K, P)>>(iter: I) -> Self {
+        Self {
+            presets: iter.into_iter().map(|(k, p)| (k.into(), p.into())).collect(),
+        }
+    }
+}
+
+impl From<HashMap<String, PromptPreset>> for PromptLibrary {
+    fn from(presets: HashMap<String, PromptPreset>) -> Self {
+        presets.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(value: Value) -> TemplateVars {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn library() -> PromptLibrary {
+        PromptLibrary::new()
+            .with_preset(
+                "reviewer",
+                PromptPreset::new(
+                    "You review {{language}} code for {{team}}. Keep comments under \
+                     {{max_words}} words.",
+                )
+                .with_description("Code reviewer")
+                .with_default("max_words", 50),
+            )
+            .with_preset("plain", "You are a helpful assistant.")
+    }
+
+    #[test]
+    fn test_resolve_preset_with_variables() {
+        let library = library();
+        let prompt = library
+            .resolve("reviewer", &vars(json!({"language": "Rust", "team": "Platform"})))
+            .unwrap();
+        assert_eq!(
+            prompt,
+            "You review Rust code for Platform. Keep comments under 50 words."
+        );
+
+        // Caller values win over defaults.
+        let prompt = library
+            .resolve(
+                "reviewer",
+                &vars(json!({"language": "Go", "team": "Infra", "max_words": 20})),
+            )
+            .unwrap();
+        assert!(prompt.ends_with("under 20 words."));
+
+        assert_eq!(
+            library.resolve("plain", &TemplateVars::new()).unwrap(),
+            "You are a helpful assistant."
+        );
+        assert_eq!(
+            library.get("reviewer").unwrap().variables(),
+            vec!["language", "max_words", "team"]
+        );
+        assert_eq!(library.names().collect::<Vec<_>>(), vec!["plain", "reviewer"]);
+    }
+
+    #[test]
+    fn test_resolve_unknown_preset_fails() {
+        let err = library().resolve("pirate", &TemplateVars::new()).unwrap_err();
+        assert!(matches!(err, AgentError::PromptNotFound(ref name) if name == "pirate"));
+        assert_eq!(err.to_string(), "Prompt preset not found: pirate");
+    }
+
+    #[test]
+    fn test_resolve_missing_variable_fails() {
+        let err = library()
+            .resolve("reviewer", &vars(json!({"language": "Rust"})))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: Prompt preset 'reviewer': Undefined template variable: team"
+        );
+    }
+
+    #[test]
+    fn test_library_from_config_map() {
+        let library: PromptLibrary = serde_json::from_value(json!({
+            "support": {
+                "prompt": "You support customers of {{product}}.",
+                "defaults": {"product": "SmartAssist"}
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            library.resolve("support", &TemplateVars::new()).unwrap(),
+            "You support customers of SmartAssist."
+        );
+    }
+}
@@ -62,6 +62,7 @@ impl AnthropicProvider {
     /// Build the API request.
     fn build_request(
         &self,
+        model: &str,
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> ApiRequest {
@@ -80,13 +81,13 @@ impl AnthropicProvider {
             .iter()
             .map(|t| ApiTool {
                 name: t.name.clone(),
-                description: t.description.clone(),
+                description: t.model_description(),
                 input_schema: t.input_schema.clone(),
             })
             .collect();
 
         ApiRequest {
-            model: self.model.clone(),
+            model: model.to_string(),
             max_tokens: self.max_tokens,
             messages: api_messages,
             system,
@@ -173,7 +174,16 @@ impl ModelProvider for AnthropicProvider {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<ModelResponse> {
-        let request = self.build_request(messages, tools);
+        self.complete_with_model(&self.model, messages, tools).await
+    }
+
+    async fn complete_with_model(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<ModelResponse> {
+        let request = self.build_request(model, messages, tools);
 
         debug!("Sending request to Anthropic API");
 
@@ -20,7 +20,7 @@ pub mod openrouter;
 pub mod qwen;
 pub mod zhipu;
 
-use crate::Result;
+use crate::{AgentError, Result};
 use async_trait::async_trait;
 use futures::Stream;
 use smartassist_core::types::{Message, MessageContent, TokenUsage, ToolDefinition};
@@ -85,6 +85,27 @@ pub trait ModelProvider: Send + Sync {
         tools: &[ToolDefinition],
     ) -> Result<ModelResponse>;
 
+    /// Generate a response with a model other than the provider's default.
+    ///
+    /// Providers that cannot switch models per request only accept their
+    /// own model.
+    async fn complete_with_model(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<ModelResponse> {
+        if model == self.model() {
+            self.complete(messages, tools).await
+        } else {
+            Err(AgentError::config(format!(
+                "Provider '{}' cannot switch to model '{}'",
+                self.name(),
+                model
+            )))
+        }
+    }
+
     /// Generate a response (streaming).
     fn complete_stream(
         &self,
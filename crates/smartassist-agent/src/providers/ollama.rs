@@ -79,7 +79,12 @@ impl OllamaProvider {
     }
 
     /// Build the API request.
-    fn build_request(&self, messages: &[Message], tools: &[ToolDefinition]) -> ApiRequest {
+    fn build_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> ApiRequest {
         let api_messages: Vec<ApiMessage> = messages
             .iter()
             .map(|m| self.convert_message(m))
@@ -95,7 +100,7 @@ impl OllamaProvider {
                         tool_type: "function".to_string(),
                         function: ApiFunction {
                             name: t.name.clone(),
-                            description: t.description.clone(),
+                            description: t.model_description(),
                             parameters: t.input_schema.clone(),
                         },
                     })
@@ -117,7 +122,7 @@ impl OllamaProvider {
         };
 
         ApiRequest {
-            model: self.model.clone(),
+            model: model.to_string(),
             messages: api_messages,
             tools: api_tools,
             options,
@@ -203,7 +208,16 @@ impl ModelProvider for OllamaProvider {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<ModelResponse> {
-        let request = self.build_request(messages, tools);
+        self.complete_with_model(&self.model, messages, tools).await
+    }
+
+    async fn complete_with_model(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<ModelResponse> {
+        let request = self.build_request(model, messages, tools);
 
         debug!("Sending request to Ollama API: {}", self.base_url);
 
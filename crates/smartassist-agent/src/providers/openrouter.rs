@@ -90,7 +90,12 @@ impl OpenRouterProvider {
     }
 
     /// Build the API request.
-    fn build_request(&self, messages: &[Message], tools: &[ToolDefinition]) -> ApiRequest {
+    fn build_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> ApiRequest {
         let api_messages: Vec<ApiMessage> = messages
             .iter()
             .map(|m| self.convert_message(m))
@@ -106,7 +111,7 @@ impl OpenRouterProvider {
                         tool_type: "function".to_string(),
                         function: ApiFunction {
                             name: t.name.clone(),
-                            description: Some(t.description.clone()),
+                            description: Some(t.model_description()),
                             parameters: t.input_schema.clone(),
                         },
                     })
@@ -115,7 +120,7 @@ impl OpenRouterProvider {
         };
 
         ApiRequest {
-            model: self.model.clone(),
+            model: model.to_string(),
             messages: api_messages,
             max_tokens: Some(self.max_tokens),
             temperature: self.temperature,
@@ -216,7 +221,16 @@ impl ModelProvider for OpenRouterProvider {
         messages: &[Message],
         tools: &[ToolDefinition],
     ) -> Result<ModelResponse> {
-        let request = self.build_request(messages, tools);
+        self.complete_with_model(&self.model, messages, tools).await
+    }
+
+    async fn complete_with_model(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+    ) -> Result<ModelResponse> {
+        let request = self.build_request(model, messages, tools);
 
         debug!("Sending request to OpenRouter API");
 
@@ -1,23 +1,38 @@
 //! Agent runtime for executing conversations.
 
-use crate::approval::ApprovalManager;
+use crate::approval::{ApprovalDecision, ApprovalManager};
+use crate::error::AgentError;
+use crate::prompts::PromptLibrary;
 use crate::providers::{ModelProvider, StreamEvent};
 use crate::session::{Session, SessionManager};
-use crate::tools::{ToolContext, ToolExecutor, ToolRegistry};
+use crate::tools::{
+    canonical_json_hash, ToolCache, ToolContext, ToolExecutor, ToolProgress, ToolRegistry,
+    RANDOM_SEED_KEY,
+};
 use crate::Result;
 use async_stream::stream;
 use futures::Stream;
+use smartassist_core::template::TemplateVars;
 use smartassist_core::types::{
-    AgentConfig, AgentId, Message, SessionKey, ThinkingLevel, TokenUsage,
+    AgentConfig, AgentId, ContentBlock, CostUsage, Message, MessageContent, ModelPricing, Role,
+    SessionKey, ThinkingLevel, TokenUsage,
 };
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
-use tracing::debug;
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Events buffered per subscriber before the oldest are dropped.
+pub const DEFAULT_EVENT_BUFFER: usize = 256;
 
 /// Configuration for the agent runtime.
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
-    /// Maximum turns per request.
+    /// Maximum model calls per request. A model still asking for tools when
+    /// this is reached ends the turn with [`TurnOutcome::MaxIterations`].
     pub max_turns: usize,
 
     /// Maximum output tokens.
@@ -29,6 +44,9 @@ pub struct RuntimeConfig {
     /// Thinking level.
     pub thinking_level: ThinkingLevel,
 
+    /// Model to use instead of the provider's default.
+    pub model: Option<String>,
+
     /// System prompt.
     pub system_prompt: Option<String>,
 
@@ -37,6 +55,22 @@ pub struct RuntimeConfig {
 
     /// Enable tool use.
     pub enable_tools: bool,
+
+    /// Identical tool calls (same tool, same arguments) allowed per request
+    /// before further repeats are refused. Zero disables the check.
+    pub max_repeated_tool_calls: usize,
+
+    /// Pricing by model ID, used to report the cost of each turn.
+    pub pricing: HashMap<String, ModelPricing>,
+
+    /// Seed for the `random` tool, making its output reproducible across
+    /// replays of a run. `None` leaves it cryptographically random.
+    pub random_seed: Option<u64>,
+
+    /// Check tool outputs against their declared output schemas, turning
+    /// a mismatch into an error result. On by default in debug builds, so
+    /// a schema that drifts from its tool shows up in development.
+    pub validate_tool_output: bool,
 }
 
 impl Default for RuntimeConfig {
@@ -46,9 +80,14 @@ impl Default for RuntimeConfig {
             max_output_tokens: 4096,
             temperature: 0.7,
             thinking_level: ThinkingLevel::default(),
+            model: None,
             system_prompt: None,
             stop_sequences: Vec::new(),
             enable_tools: true,
+            max_repeated_tool_calls: 3,
+            pricing: HashMap::new(),
+            random_seed: None,
+            validate_tool_output: cfg!(debug_assertions),
         }
     }
 }
@@ -70,11 +109,20 @@ pub struct AgentRuntime {
     /// Tool executor.
     tool_executor: Arc<ToolExecutor>,
 
+    /// Result cache the tool executor uses, if any.
+    tool_cache: Option<Arc<ToolCache>>,
+
     /// Approval manager.
     approval_manager: Arc<ApprovalManager>,
 
     /// Session manager.
     session_manager: Arc<SessionManager>,
+
+    /// Publishes [`AgentEvent`]s to subscribers.
+    events: broadcast::Sender<AgentEvent>,
+
+    /// System-prompt presets selectable by name.
+    prompts: PromptLibrary,
 }
 
 impl AgentRuntime {
@@ -88,29 +136,128 @@ impl AgentRuntime {
         let tool_executor = Arc::new(ToolExecutor::new(tool_registry.clone()));
         let approval_manager = Arc::new(ApprovalManager::new());
 
-        Self {
+        let mut runtime = Self {
             config,
             runtime_config: RuntimeConfig::default(),
             provider,
             tool_registry,
             tool_executor,
+            tool_cache: None,
             approval_manager,
             session_manager,
-        }
+            events: broadcast::channel(DEFAULT_EVENT_BUFFER).0,
+            prompts: PromptLibrary::new(),
+        };
+        runtime.rebuild_tool_executor();
+        runtime
     }
 
     /// Set the runtime configuration.
     pub fn with_config(mut self, config: RuntimeConfig) -> Self {
         self.runtime_config = config;
+        self.rebuild_tool_executor();
+        self
+    }
+
+    /// Enable result caching for tools marked cacheable.
+    pub fn with_tool_cache(mut self, cache: Arc<ToolCache>) -> Self {
+        self.tool_cache = Some(cache);
+        self.rebuild_tool_executor();
         self
     }
 
+    /// Build the tool executor from the registry, cache and configuration.
+    fn rebuild_tool_executor(&mut self) {
+        let mut executor = ToolExecutor::new(self.tool_registry.clone());
+        if let Some(cache) = &self.tool_cache {
+            executor = executor.with_cache(cache.clone());
+        }
+        if self.runtime_config.validate_tool_output {
+            executor = executor.with_output_validation();
+        }
+        self.tool_executor = Arc::new(executor);
+    }
+
     /// Set the approval manager.
     pub fn with_approval_manager(mut self, manager: Arc<ApprovalManager>) -> Self {
         self.approval_manager = manager;
         self
     }
 
+    /// Set how many events each subscriber can fall behind by.
+    ///
+    /// Subscribers that are further behind lose the oldest events rather
+    /// than slowing the agent down. Existing subscribers are disconnected.
+    pub fn with_event_buffer(mut self, capacity: usize) -> Self {
+        self.events = broadcast::channel(capacity.max(1)).0;
+        self
+    }
+
+    /// Subscribe to the agent's activity across all sessions.
+    ///
+    /// A receiver that falls more than the event buffer behind gets
+    /// [`broadcast::error::RecvError::Lagged`] and resumes with the oldest
+    /// event still buffered.
+    pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
+        self.events.subscribe()
+    }
+
+    /// Set the system-prompt presets that can be selected by name.
+    pub fn with_prompt_library(mut self, library: PromptLibrary) -> Self {
+        self.prompts = library;
+        self
+    }
+
+    /// Use the preset `name`, rendered with `vars`, as the default system
+    /// prompt.
+    ///
+    /// This sets [`RuntimeConfig::system_prompt`], so call it after
+    /// [`with_config`](Self::with_config). Fails if the preset is unknown
+    /// or a placeholder has no value.
+    pub fn with_system_prompt_preset(mut self, name: &str, vars: &TemplateVars) -> Result<Self> {
+        self.runtime_config.system_prompt = Some(self.prompts.resolve(name, vars)?);
+        Ok(self)
+    }
+
+    /// Get the system-prompt presets.
+    pub fn prompt_library(&self) -> &PromptLibrary {
+        &self.prompts
+    }
+
+    /// Give `session` the preset `name`, rendered with `vars`, as its system
+    /// prompt from the next turn on.
+    ///
+    /// The session is left unchanged if the preset is unknown or a
+    /// placeholder has no value. Saving it is left to the caller.
+    pub fn apply_prompt_preset(
+        &self,
+        session: &mut Session,
+        name: &str,
+        vars: &TemplateVars,
+    ) -> Result<()> {
+        let prompt = self.prompts.resolve(name, vars)?;
+        session.set_system_prompt(Some(prompt));
+        Ok(())
+    }
+
+    /// Send an event to subscribers, building it only if there are any.
+    fn publish(&self, event: impl FnOnce() -> AgentEvent) {
+        if self.events.receiver_count() > 0 {
+            // Fails only if every receiver was dropped in the meantime.
+            let _ = self.events.send(event());
+        }
+    }
+
+    /// Publish a progress update from a running tool.
+    fn publish_progress(&self, session: &SessionKey, update: ToolProgress) {
+        self.publish(|| AgentEvent::ToolProgress {
+            session: session.clone(),
+            id: update.tool_use_id,
+            name: update.tool,
+            progress: update.data,
+        });
+    }
+
     /// Get the agent ID.
     pub fn agent_id(&self) -> &AgentId {
         &self.config.id
@@ -127,23 +274,31 @@ impl AgentRuntime {
         session_key: &SessionKey,
         message: &str,
     ) -> Result<String> {
+        let turn = self.process_turn(session_key, message).await?;
+        Ok(turn.assistant_response.unwrap_or_default())
+    }
+
+    /// Process a user message and return the full turn, including tool uses
+    /// and the token usage summed over every model call it took.
+    pub async fn process_turn(
+        &self,
+        session_key: &SessionKey,
+        message: &str,
+    ) -> Result<ConversationTurn> {
+        // Hold the session for the whole turn so concurrent messages for it
+        // run one after another instead of overwriting each other's history.
+        let _guard = self.session_manager.lock(session_key).await;
         let mut session = self
             .session_manager
             .get_or_create(session_key, &self.config.id)
             .await?;
 
-        session.add_user_message(message);
-
-        // Get response from model
-        let response = self.get_model_response(&session).await?;
-
-        // Add assistant response
-        session.add_assistant_message(&response);
+        let turn = self.run_turn(&mut session, message).await?.turn;
 
         // Save session
         self.session_manager.save(&session).await?;
 
-        Ok(response)
+        Ok(turn)
     }
 
     /// Process a message with streaming response.
@@ -156,7 +311,8 @@ impl AgentRuntime {
             // Signal start
             yield Ok(StreamEvent::Start);
 
-            // Get or create session
+            // Get or create session, holding it until the turn is saved
+            let _guard = self.session_manager.lock(&session_key).await;
             let mut session = match self.session_manager.get_or_create(&session_key, &self.config.id).await {
                 Ok(s) => s,
                 Err(e) => {
@@ -165,16 +321,12 @@ impl AgentRuntime {
                 }
             };
 
-            session.add_user_message(&message);
-
             // Get response
-            match self.get_model_response(&session).await {
-                Ok(response) => {
+            match self.run_turn(&mut session, &message).await {
+                Ok(TurnResult { turn, .. }) => {
                     // Stream the response as text deltas
-                    yield Ok(StreamEvent::Text(response.clone()));
-
-                    // Add to session
-                    session.add_assistant_message(&response);
+                    yield Ok(StreamEvent::Text(turn.assistant_response.unwrap_or_default()));
+                    yield Ok(StreamEvent::Usage(turn.token_usage));
 
                     // Save session
                     if let Err(e) = self.session_manager.save(&session).await {
@@ -193,19 +345,294 @@ impl AgentRuntime {
         })
     }
 
-    /// Get a response from the model.
-    async fn get_model_response(&self, session: &Session) -> Result<String> {
-        let messages: Vec<Message> = session.messages.clone();
+    /// Get the model a session's next turn runs on.
+    ///
+    /// The session's override wins over the runtime default; with neither,
+    /// the provider's own model is used.
+    pub fn effective_model<'a>(&'a self, session: &'a Session) -> &'a str {
+        session
+            .model()
+            .or(self.runtime_config.model.as_deref())
+            .unwrap_or_else(|| self.provider.model())
+    }
+
+    /// Get the system prompt for a session's next turn, if any.
+    pub fn effective_system_prompt<'a>(&'a self, session: &'a Session) -> Option<&'a str> {
+        session
+            .system_prompt()
+            .or(self.runtime_config.system_prompt.as_deref())
+    }
+
+    /// Run one user turn against a session.
+    ///
+    /// The user message is appended, then the model is called repeatedly,
+    /// running the tools it asks for, until it answers without tool calls or
+    /// [`RuntimeConfig::max_turns`] calls have been made. Tool calls, their
+    /// results and the final answer are all appended to the session; saving
+    /// it is left to the caller.
+    pub async fn run_turn(&self, session: &mut Session, user_message: &str) -> Result<TurnResult> {
+        self.run_turn_with_events(session, user_message, &mut |_| {}).await
+    }
+
+    /// Like [`run_turn`](Self::run_turn), reporting progress to `on_event`
+    /// as the loop runs.
+    pub async fn run_turn_with_events(
+        &self,
+        session: &mut Session,
+        user_message: &str,
+        on_event: &mut (dyn FnMut(TurnEvent) + Send),
+    ) -> Result<TurnResult> {
+        self.run_turn_with_cancel(session, user_message, on_event, CancellationToken::new())
+            .await
+    }
+
+    /// Like [`run_turn_with_events`](Self::run_turn_with_events), stopping
+    /// with [`AgentError::Cancelled`] once `cancel` is cancelled.
+    ///
+    /// The token is handed to tools through [`ToolContext::cancel`], so a
+    /// long-running tool can stop partway and return what it has done.
+    pub async fn run_turn_with_cancel(
+        &self,
+        session: &mut Session,
+        user_message: &str,
+        on_event: &mut (dyn FnMut(TurnEvent) + Send),
+        cancel: CancellationToken,
+    ) -> Result<TurnResult> {
+        session.add_user_message(user_message);
+        self.publish(|| AgentEvent::TurnStarted {
+            session: session.key.clone(),
+            user_message: user_message.to_string(),
+        });
+
+        let result = match self.tool_loop(session, user_message, on_event, cancel).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.publish(|| AgentEvent::Error {
+                    session: session.key.clone(),
+                    message: e.to_string(),
+                });
+                return Err(e);
+            }
+        };
+        let response = result.turn.assistant_response.as_deref().unwrap_or_default();
+        session.add_assistant_message(response);
+
+        self.publish(|| AgentEvent::TurnEnded {
+            session: session.key.clone(),
+            outcome: result.outcome,
+            usage: result.turn.token_usage.clone(),
+            model_calls: result.turn.model_calls,
+        });
+        Ok(result)
+    }
+
+    /// Call the model until it stops requesting tools or the call limit is
+    /// reached.
+    async fn tool_loop(
+        &self,
+        session: &mut Session,
+        user_message: &str,
+        on_event: &mut (dyn FnMut(TurnEvent) + Send),
+        cancel: CancellationToken,
+    ) -> Result<TurnResult> {
         let tools = if self.runtime_config.enable_tools {
             self.tool_registry.definitions().await
         } else {
             Vec::new()
         };
 
-        let response = self.provider.complete(&messages, &tools).await?;
+        let model = self.effective_model(session).to_string();
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        let mut context = ToolContext {
+            session_id: session.key.as_str().to_string(),
+            agent_id: self.config.id.as_str().to_string(),
+            cancel: cancel.clone(),
+            progress: Some(progress_tx),
+            ..Default::default()
+        };
+        if let Some(seed) = self.runtime_config.random_seed {
+            context
+                .data
+                .insert(RANDOM_SEED_KEY.to_string(), serde_json::json!(seed));
+        }
+        let mut guard = ToolLoopGuard::new(self.runtime_config.max_repeated_tool_calls);
+        let mut turn = ConversationTurn {
+            turn_number: session.messages.iter().filter(|m| m.role == Role::User).count(),
+            user_message: Some(user_message.to_string()),
+            assistant_response: None,
+            tool_uses: Vec::new(),
+            token_usage: TokenUsage::default(),
+            model_calls: 0,
+            cost: None,
+        };
+        let pricing = self.runtime_config.pricing.get(&model);
+        let limit = self.runtime_config.max_turns.max(1);
+
+        for call in 1..=limit {
+            let mut messages: Vec<Message> = Vec::with_capacity(session.messages.len() + 1);
+            if let Some(prompt) = self.effective_system_prompt(session) {
+                messages.push(Message::system(prompt));
+            }
+            messages.extend(session.messages.iter().cloned());
+
+            debug!(
+                "Requesting completion for session {} with model {} (turn {})",
+                session.key.as_str(),
+                model,
+                call
+            );
+            on_event(TurnEvent::ModelCall { iteration: call });
+            let response = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return Err(AgentError::Cancelled),
+                response = self.provider.complete_with_model(&model, &messages, &tools) => {
+                    response?
+                }
+            };
+            session.update_tokens(&response.token_usage);
+            turn.add_usage(&response.token_usage, pricing);
+            self.publish(|| AgentEvent::TokenUsage {
+                session: session.key.clone(),
+                model: model.clone(),
+                usage: response.token_usage.clone(),
+            });
+
+            let blocks = match response.content {
+                MessageContent::Blocks(blocks) if self.runtime_config.enable_tools => blocks,
+                content => return Ok(TurnResult::completed(turn.finish(content.to_text()))),
+            };
+            let tool_uses: Vec<_> = blocks
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::ToolUse { id, name, input } => {
+                        Some((id.clone(), name.clone(), input.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+            if tool_uses.is_empty() {
+                let text = MessageContent::Blocks(blocks).to_text();
+                return Ok(TurnResult::completed(turn.finish(text)));
+            }
+
+            session.add_message(Role::Assistant, blocks);
+            for (id, name, input) in tool_uses {
+                on_event(TurnEvent::ToolStarted {
+                    id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                });
+                self.publish(|| AgentEvent::ToolCalled {
+                    session: session.key.clone(),
+                    id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                });
+                let result = {
+                    let call = self.run_tool_call(&mut guard, &id, &name, input.clone(), &context);
+                    tokio::pin!(call);
+                    loop {
+                        tokio::select! {
+                            result = &mut call => break result,
+                            Some(update) = progress_rx.recv() => {
+                                self.publish_progress(&session.key, update);
+                            }
+                        }
+                    }
+                };
+                while let Ok(update) = progress_rx.try_recv() {
+                    self.publish_progress(&session.key, update);
+                }
+                let output = match &result.output {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                session
+                    .messages
+                    .push(Message::tool_result(&id, output, result.is_error));
+                on_event(TurnEvent::ToolFinished {
+                    id: id.clone(),
+                    name: name.clone(),
+                    result: result.clone(),
+                });
+                self.publish(|| AgentEvent::ToolCompleted {
+                    session: session.key.clone(),
+                    id: id.clone(),
+                    name: name.clone(),
+                    result: result.clone(),
+                });
+                turn.tool_uses.push(ToolUse {
+                    id,
+                    name,
+                    input,
+                    result: Some(result),
+                });
+            }
+        }
+
+        warn!(
+            "Session {} reached the limit of {} model turns",
+            session.key.as_str(),
+            limit
+        );
+        on_event(TurnEvent::MaxIterationsReached { limit });
+        // The last model reply was only tool calls, so there is no answer to
+        // hand back; say why instead of returning empty text.
+        let response = format!(
+            "Stopped after {} model calls without a final answer. The tool results so far \
+             are in the conversation; ask again to continue.",
+            limit
+        );
+        Ok(TurnResult {
+            turn: turn.finish(response),
+            outcome: TurnOutcome::MaxIterations { limit },
+        })
+    }
+
+    /// Run one tool call requested by the model.
+    ///
+    /// Failures are reported back to the model as error results rather than
+    /// ending the request.
+    async fn run_tool_call(
+        &self,
+        guard: &mut ToolLoopGuard,
+        id: &str,
+        name: &str,
+        input: serde_json::Value,
+        context: &ToolContext,
+    ) -> ToolUseResult {
+        if let Some(repeats) = guard.check(name, &input) {
+            warn!("Refusing tool '{}': identical call repeated {} times", name, repeats);
+            return ToolUseResult::error(format!(
+                "Refused: '{}' has already been called {} times with these exact \
+                 arguments. Do not repeat this call; change your approach or answer \
+                 with what you have.",
+                name, repeats
+            ));
+        }
 
-        // Extract text from response
-        Ok(response.content.to_text())
+        match self.tool_approval_decision(name, &input).await {
+            Ok(decision) if decision.required => {
+                return ToolUseResult::error(format!(
+                    "Tool '{}' requires approval and was not run",
+                    name
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => return ToolUseResult::error(e.to_string()),
+        }
+
+        let started = Instant::now();
+        let mut result = match self.execute_tool(id, name, input, context).await {
+            Ok(result) => ToolUseResult {
+                output: result.output,
+                is_error: result.is_error,
+                duration_ms: None,
+            },
+            Err(e) => ToolUseResult::error(e.to_string()),
+        };
+        result.duration_ms = Some(started.elapsed().as_millis() as u64);
+        result
     }
 
     /// Execute a tool use.
@@ -228,13 +655,53 @@ impl AgentRuntime {
         tool_name: &str,
         input: &serde_json::Value,
     ) -> Result<bool> {
+        Ok(self.tool_approval_decision(tool_name, input).await?.required)
+    }
+
+    /// Decide whether a tool requires approval, explaining which rule matched.
+    pub async fn tool_approval_decision(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+    ) -> Result<ApprovalDecision> {
         // Check tool-level approval requirement
         let tool_requires = self.tool_executor.requires_approval(tool_name, input).await?;
 
-        // Check policy-level approval requirement
-        let policy_requires = self.approval_manager.requires_approval(tool_name, input);
+        // Policy rules may override the tool's own answer
+        Ok(self
+            .approval_manager
+            .evaluate(tool_name, input, tool_requires)
+            .await)
+    }
+}
+
+/// Detects a model calling the same tool with the same arguments over and
+/// over within one request.
+struct ToolLoopGuard {
+    /// Identical calls allowed before repeats are refused.
+    max_repeats: usize,
+
+    /// Calls seen so far, by tool name and argument hash.
+    seen: HashMap<(String, String), usize>,
+}
+
+impl ToolLoopGuard {
+    fn new(max_repeats: usize) -> Self {
+        Self {
+            max_repeats,
+            seen: HashMap::new(),
+        }
+    }
 
-        Ok(tool_requires || policy_requires)
+    /// Record a call, returning the number of earlier identical calls if
+    /// this one should be refused.
+    fn check(&mut self, name: &str, input: &serde_json::Value) -> Option<usize> {
+        let count = self
+            .seen
+            .entry((name.to_string(), canonical_json_hash(input)))
+            .or_insert(0);
+        *count += 1;
+        (self.max_repeats > 0 && *count > self.max_repeats).then_some(*count - 1)
     }
 }
 
@@ -253,8 +720,154 @@ pub struct ConversationTurn {
     /// Tool uses in this turn.
     pub tool_uses: Vec<ToolUse>,
 
-    /// Token usage for this turn.
+    /// Token usage for this turn, summed over every model call.
     pub token_usage: TokenUsage,
+
+    /// Number of model calls made during this turn.
+    pub model_calls: usize,
+
+    /// Cost of this turn, if pricing is configured for the model.
+    pub cost: Option<CostUsage>,
+}
+
+impl ConversationTurn {
+    /// Record the usage of one model call.
+    fn add_usage(&mut self, usage: &TokenUsage, pricing: Option<&ModelPricing>) {
+        self.token_usage.add(usage);
+        self.model_calls += 1;
+        self.cost = pricing.map(|p| p.cost(&self.token_usage));
+    }
+
+    fn finish(mut self, response: String) -> Self {
+        self.assistant_response = Some(response);
+        self
+    }
+}
+
+/// How a turn run by [`AgentRuntime::run_turn`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnOutcome {
+    /// The model answered without requesting further tools.
+    Completed,
+
+    /// The model was still requesting tools after `limit` calls. The turn's
+    /// response is a generated note ("Stopped after `limit` model calls
+    /// without a final answer...") explaining the stop, not model output.
+    MaxIterations {
+        /// The configured call limit.
+        limit: usize,
+    },
+}
+
+/// Result of [`AgentRuntime::run_turn`].
+#[derive(Debug, Clone)]
+pub struct TurnResult {
+    /// The turn, with its tool uses, usage and final response.
+    pub turn: ConversationTurn,
+
+    /// Why the tool loop stopped.
+    pub outcome: TurnOutcome,
+}
+
+impl TurnResult {
+    fn completed(turn: ConversationTurn) -> Self {
+        Self {
+            turn,
+            outcome: TurnOutcome::Completed,
+        }
+    }
+
+    /// Whether the turn was cut short by the call limit.
+    pub fn hit_limit(&self) -> bool {
+        matches!(self.outcome, TurnOutcome::MaxIterations { .. })
+    }
+}
+
+/// Progress of the tool loop, reported by
+/// [`AgentRuntime::run_turn_with_events`].
+#[derive(Debug, Clone)]
+pub enum TurnEvent {
+    /// The model is about to be called, starting from iteration 1.
+    ModelCall {
+        iteration: usize,
+    },
+
+    /// A tool requested by the model is about to run.
+    ToolStarted {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+
+    /// A tool finished; its result has been added to the session.
+    ToolFinished {
+        id: String,
+        name: String,
+        result: ToolUseResult,
+    },
+
+    /// The call limit was reached with tools still being requested.
+    MaxIterationsReached {
+        limit: usize,
+    },
+}
+
+/// Agent activity published to [`AgentRuntime::subscribe`] receivers.
+///
+/// Unlike [`TurnEvent`], which reports on one turn to its caller, these
+/// cover every turn the runtime runs and name the session they belong to.
+#[derive(Debug, Clone)]
+pub enum AgentEvent {
+    /// A user message was added and the turn is starting.
+    TurnStarted {
+        session: SessionKey,
+        user_message: String,
+    },
+
+    /// A model call returned, using `usage` tokens.
+    TokenUsage {
+        session: SessionKey,
+        model: String,
+        usage: TokenUsage,
+    },
+
+    /// A tool requested by the model is about to run.
+    ToolCalled {
+        session: SessionKey,
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+
+    /// A running tool reported progress.
+    ToolProgress {
+        session: SessionKey,
+        id: String,
+        name: String,
+        progress: serde_json::Value,
+    },
+
+    /// A tool finished running.
+    ToolCompleted {
+        session: SessionKey,
+        id: String,
+        name: String,
+        result: ToolUseResult,
+    },
+
+    /// The turn ended with the usage summed over its model calls.
+    TurnEnded {
+        session: SessionKey,
+        outcome: TurnOutcome,
+        usage: TokenUsage,
+        model_calls: usize,
+    },
+
+    /// The turn failed; no `TurnEnded` follows.
+    Error {
+        session: SessionKey,
+        message: String,
+    },
 }
 
 /// A tool use in a turn.
@@ -286,6 +899,17 @@ pub struct ToolUseResult {
     pub duration_ms: Option<u64>,
 }
 
+impl ToolUseResult {
+    /// A result reporting an error message.
+    fn error(message: String) -> Self {
+        Self {
+            output: serde_json::Value::String(message),
+            is_error: true,
+            duration_ms: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,4 +920,756 @@ mod tests {
         assert_eq!(config.max_turns, 10);
         assert!(config.enable_tools);
     }
+
+    type Reply = Box<dyn Fn(usize, &[Message]) -> MessageContent + Send + Sync>;
+
+    /// Provider that answers from a script and records each request.
+    ///
+    /// `reply` gives the content of the n-th response (counting from 1)
+    /// from the request messages, and `usage` its token usage.
+    struct ScriptedProvider {
+        reply: Reply,
+        usage: fn(usize) -> TokenUsage,
+        /// Model and system prompt of each request.
+        requests: std::sync::Mutex<Vec<(String, Option<String>)>>,
+    }
+
+    impl ScriptedProvider {
+        fn new<F>(reply: F) -> Self
+        where
+            F: Fn(usize, &[Message]) -> MessageContent + Send + Sync + 'static,
+        {
+            Self {
+                reply: Box::new(reply),
+                usage: |_| TokenUsage::default(),
+                requests: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        /// Always answers "ok".
+        fn answering() -> Self {
+            Self::new(|_, _| MessageContent::Text("ok".to_string()))
+        }
+
+        /// Keeps requesting `echo` with the given arguments, in turn, until
+        /// a call is refused, then gives up with a text answer.
+        fn looping(args: Vec<serde_json::Value>) -> Self {
+            Self::new(move |call, messages| {
+                let refused = messages.iter().any(|m| {
+                    matches!(&m.content, MessageContent::Blocks(blocks) if blocks.iter().any(|b| {
+                        matches!(b, ContentBlock::ToolResult { content, is_error: true, .. }
+                            if content.starts_with("Refused"))
+                    }))
+                });
+                if refused {
+                    return MessageContent::Text("gave up".to_string());
+                }
+                echo_call(call, args[(call - 1) % args.len()].clone())
+            })
+        }
+
+        /// Calls `echo` once, then answers, reporting fixed usage on each
+        /// call.
+        fn metered() -> Self {
+            let mut provider = Self::new(|call, _| match call {
+                1 => echo_call(1, serde_json::json!("ping")),
+                _ => MessageContent::Text("done".to_string()),
+            });
+            provider.usage = |call| match call {
+                1 => TokenUsage {
+                    input: 1_000,
+                    output: 50,
+                    cache_creation: 0,
+                    cache_read: 400,
+                },
+                _ => TokenUsage {
+                    input: 1_200,
+                    output: 150,
+                    cache_creation: 100,
+                    cache_read: 0,
+                },
+            };
+            provider
+        }
+
+        fn requests(&self) -> Vec<(String, Option<String>)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// A request for the `echo` tool.
+    fn echo_call(call: usize, value: serde_json::Value) -> MessageContent {
+        MessageContent::Blocks(vec![ContentBlock::ToolUse {
+            id: format!("call_{}", call),
+            name: "echo".to_string(),
+            input: serde_json::json!({ "value": value }),
+        }])
+    }
+
+    #[async_trait::async_trait]
+    impl ModelProvider for ScriptedProvider {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn model(&self) -> &str {
+            "scripted-model"
+        }
+
+        async fn complete(
+            &self,
+            messages: &[Message],
+            tools: &[smartassist_core::types::ToolDefinition],
+        ) -> Result<crate::providers::ModelResponse> {
+            self.complete_with_model("scripted-model", messages, tools).await
+        }
+
+        async fn complete_with_model(
+            &self,
+            model: &str,
+            messages: &[Message],
+            _tools: &[smartassist_core::types::ToolDefinition],
+        ) -> Result<crate::providers::ModelResponse> {
+            let system = messages
+                .iter()
+                .find(|m| m.role == smartassist_core::types::Role::System)
+                .map(|m| m.content.to_text());
+            let call = {
+                let mut requests = self.requests.lock().unwrap();
+                requests.push((model.to_string(), system));
+                requests.len()
+            };
+
+            Ok(crate::providers::ModelResponse {
+                content: (self.reply)(call, messages),
+                stop_reason: None,
+                token_usage: (self.usage)(call),
+            })
+        }
+
+        fn complete_stream(
+            &self,
+            _messages: &[Message],
+            _tools: &[smartassist_core::types::ToolDefinition],
+        ) -> Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send + '_>> {
+            Box::pin(futures::stream::empty())
+        }
+    }
+
+    /// A runtime around `provider`, with the `echo` tool registered.
+    async fn runtime(
+        dir: &std::path::Path,
+        provider: ScriptedProvider,
+        config: RuntimeConfig,
+    ) -> (AgentRuntime, Arc<ScriptedProvider>) {
+        let provider = Arc::new(provider);
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(crate::tools::EchoTool::new())).await;
+        let runtime = AgentRuntime::new(
+            AgentConfig::default(),
+            provider.clone(),
+            registry,
+            Arc::new(SessionManager::new(dir)),
+        )
+        .with_config(config);
+        (runtime, provider)
+    }
+
+    #[tokio::test]
+    async fn test_session_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, provider) = runtime(
+            dir.path(),
+            ScriptedProvider::answering(),
+            RuntimeConfig {
+                system_prompt: Some("Default prompt".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        let agent_id = runtime.agent_id().clone();
+        let sessions = &runtime.session_manager;
+
+        let key_a = SessionKey::new("agent:a");
+        let mut session_a = sessions.get_or_create(&key_a, &agent_id).await.unwrap();
+        session_a.set_model(Some("model-a".to_string()));
+        session_a.set_system_prompt(Some("You are A.".to_string()));
+        sessions.save(&session_a).await.unwrap();
+
+        let key_b = SessionKey::new("agent:b");
+        let mut session_b = sessions.get_or_create(&key_b, &agent_id).await.unwrap();
+        session_b.set_model(Some("model-b".to_string()));
+        sessions.save(&session_b).await.unwrap();
+
+        let key_c = SessionKey::new("agent:c");
+
+        runtime.process_message(&key_a, "hi").await.unwrap();
+        runtime.process_message(&key_b, "hi").await.unwrap();
+        runtime.process_message(&key_c, "hi").await.unwrap();
+
+        assert_eq!(
+            provider.requests(),
+            [
+                ("model-a".to_string(), Some("You are A.".to_string())),
+                ("model-b".to_string(), Some("Default prompt".to_string())),
+                ("scripted-model".to_string(), Some("Default prompt".to_string())),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_override_applies_from_next_turn() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, provider) = runtime(
+            dir.path(),
+            ScriptedProvider::answering(),
+            RuntimeConfig {
+                model: Some("runtime-model".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        let key = SessionKey::new("agent:a");
+
+        runtime.process_message(&key, "first").await.unwrap();
+
+        let mut session = runtime
+            .session_manager
+            .get_or_create(&key, runtime.agent_id())
+            .await
+            .unwrap();
+        session.set_model(Some("switched".to_string()));
+        session.set_system_prompt(Some("Be terse.".to_string()));
+        runtime.session_manager.save(&session).await.unwrap();
+
+        runtime.process_message(&key, "second").await.unwrap();
+
+        let calls = provider.requests();
+        assert_eq!(calls[0], ("runtime-model".to_string(), None));
+        assert_eq!(calls[1], ("switched".to_string(), Some("Be terse.".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_presets() {
+        let dir = tempfile::tempdir().unwrap();
+        let library = PromptLibrary::new()
+            .with_preset("concise", "Answer in at most {{sentences}} sentences.")
+            .with_preset("reviewer", "You review {{language}} code.");
+        let vars: TemplateVars = [("sentences".to_string(), serde_json::json!(2))].into();
+        let (runtime, provider) =
+            runtime(dir.path(), ScriptedProvider::answering(), RuntimeConfig::default()).await;
+        let runtime = runtime
+            .with_prompt_library(library)
+            .with_system_prompt_preset("concise", &vars)
+            .unwrap();
+
+        let key = SessionKey::new("agent:a");
+        let mut session = runtime
+            .session_manager
+            .get_or_create(&key, runtime.agent_id())
+            .await
+            .unwrap();
+        let err = runtime
+            .apply_prompt_preset(&mut session, "pirate", &TemplateVars::new())
+            .unwrap_err();
+        assert!(matches!(err, crate::AgentError::PromptNotFound(_)));
+        assert!(session.system_prompt().is_none());
+
+        runtime.process_message(&SessionKey::new("agent:b"), "hi").await.unwrap();
+
+        let rust: TemplateVars = [("language".to_string(), serde_json::json!("Rust"))].into();
+        runtime.apply_prompt_preset(&mut session, "reviewer", &rust).unwrap();
+        runtime.session_manager.save(&session).await.unwrap();
+        runtime.process_message(&key, "hi").await.unwrap();
+
+        let calls = provider.requests();
+        assert_eq!(calls[0].1.as_deref(), Some("Answer in at most 2 sentences."));
+        assert_eq!(calls[1].1.as_deref(), Some("You review Rust code."));
+
+        let result = runtime.with_system_prompt_preset("reviewer", &TemplateVars::new());
+        assert!(matches!(result, Err(crate::AgentError::Config(_))));
+    }
+
+    /// Tool results in a session as `(is_error, content)` pairs.
+    fn tool_results(session: &Session) -> Vec<(bool, String)> {
+        session
+            .messages
+            .iter()
+            .filter_map(|m| match &m.content {
+                MessageContent::Blocks(blocks) => blocks.iter().find_map(|b| match b {
+                    ContentBlock::ToolResult {
+                        content, is_error, ..
+                    } => Some((*is_error, content.clone())),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_tool_call_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RuntimeConfig {
+            max_repeated_tool_calls: 2,
+            ..Default::default()
+        };
+        let provider = ScriptedProvider::looping(vec![serde_json::json!("same")]);
+        let (runtime, provider) = runtime(dir.path(), provider, config).await;
+        let key = SessionKey::new("agent:loop");
+
+        let response = runtime.process_message(&key, "go").await.unwrap();
+        assert_eq!(response, "gave up");
+        assert_eq!(provider.requests().len(), 4);
+
+        let session = runtime
+            .session_manager
+            .get_or_create(&key, runtime.agent_id())
+            .await
+            .unwrap();
+        let results = tool_results(&session);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], (false, "same".to_string()));
+        assert_eq!(results[1], (false, "same".to_string()));
+        assert!(results[2].0);
+        assert!(results[2].1.contains("already been called 2 times"));
+        assert_eq!(session.last_message().unwrap().content.to_text(), "gave up");
+    }
+
+    #[tokio::test]
+    async fn test_distinct_tool_calls_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RuntimeConfig {
+            max_turns: 5,
+            max_repeated_tool_calls: 1,
+            ..Default::default()
+        };
+        let args = (0..10).map(|i| serde_json::json!(i)).collect();
+        let provider = ScriptedProvider::looping(args);
+        let (runtime, provider) = runtime(dir.path(), provider, config).await;
+        let key = SessionKey::new("agent:loop");
+
+        runtime.process_message(&key, "go").await.unwrap();
+
+        // Every call differs, so only the turn limit stops the loop.
+        assert_eq!(provider.requests().len(), 5);
+        let session = runtime
+            .session_manager
+            .get_or_create(&key, runtime.agent_id())
+            .await
+            .unwrap();
+        assert!(tool_results(&session).iter().all(|(is_error, _)| !is_error));
+    }
+
+    #[tokio::test]
+    async fn test_turn_sums_usage_across_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, _) =
+            runtime(dir.path(), ScriptedProvider::metered(), RuntimeConfig::default()).await;
+        let key = SessionKey::new("agent:metered");
+
+        let turn = runtime.process_turn(&key, "go").await.unwrap();
+
+        assert_eq!(turn.assistant_response.as_deref(), Some("done"));
+        assert_eq!(turn.turn_number, 1);
+        assert_eq!(turn.model_calls, 2);
+        assert_eq!(turn.token_usage.input, 2_200);
+        assert_eq!(turn.token_usage.output, 200);
+        assert_eq!(turn.token_usage.cache_creation, 100);
+        assert_eq!(turn.token_usage.cache_read, 400);
+        assert_eq!(turn.token_usage.total(), 2_900);
+        assert!(turn.cost.is_none());
+
+        assert_eq!(turn.tool_uses.len(), 1);
+        let result = turn.tool_uses[0].result.as_ref().unwrap();
+        assert_eq!(result.output, serde_json::json!("ping"));
+        assert!(!result.is_error);
+
+        let session = runtime
+            .session_manager
+            .get_or_create(&key, runtime.agent_id())
+            .await
+            .unwrap();
+        assert_eq!(session.total_tokens.total(), 2_900);
+    }
+
+    #[tokio::test]
+    async fn test_turn_cost_from_pricing() {
+        let dir = tempfile::tempdir().unwrap();
+        let pricing = ModelPricing {
+            input_per_1m: 3.0,
+            output_per_1m: 15.0,
+            cache_creation_per_1m: Some(3.75),
+            cache_read_per_1m: Some(0.3),
+        };
+        let config = RuntimeConfig {
+            pricing: HashMap::from([("scripted-model".to_string(), pricing)]),
+            ..Default::default()
+        };
+        let (runtime, _) = runtime(dir.path(), ScriptedProvider::metered(), config).await;
+
+        let turn = runtime
+            .process_turn(&SessionKey::new("agent:metered"), "go")
+            .await
+            .unwrap();
+
+        let cost = turn.cost.unwrap();
+        let input = (2_200.0 * 3.0 + 100.0 * 3.75 + 400.0 * 0.3) / 1_000_000.0;
+        let output = 200.0 * 15.0 / 1_000_000.0;
+        assert!((cost.input_usd - input).abs() < 1e-12);
+        assert!((cost.output_usd - output).abs() < 1e-12);
+        assert!((cost.total_usd - (input + output)).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_run_turn_calls_tool_then_answers() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, _) =
+            runtime(dir.path(), ScriptedProvider::metered(), RuntimeConfig::default()).await;
+        let key = SessionKey::new("agent:metered");
+        let mut session = Session::new(key, runtime.agent_id().clone());
+
+        let mut events = Vec::new();
+        let result = runtime
+            .run_turn_with_events(&mut session, "go", &mut |event| events.push(event))
+            .await
+            .unwrap();
+
+        assert_eq!(result.outcome, TurnOutcome::Completed);
+        assert!(!result.hit_limit());
+        assert_eq!(result.turn.assistant_response.as_deref(), Some("done"));
+        assert_eq!(result.turn.tool_uses.len(), 1);
+        assert_eq!(result.turn.tool_uses[0].name, "echo");
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[0], TurnEvent::ModelCall { iteration: 1 }));
+        assert!(matches!(&events[1], TurnEvent::ToolStarted { name, .. } if name == "echo"));
+        assert!(matches!(
+            &events[2],
+            TurnEvent::ToolFinished { id, result, .. } if id == "call_1" && !result.is_error
+        ));
+        assert!(matches!(events[3], TurnEvent::ModelCall { iteration: 2 }));
+
+        // User message, tool call, tool result, answer.
+        assert_eq!(session.messages.len(), 4);
+        assert_eq!(session.messages[0].content.to_text(), "go");
+        assert_eq!(session.last_message().unwrap().content.to_text(), "done");
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_receives_turn_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, _) =
+            runtime(dir.path(), ScriptedProvider::metered(), RuntimeConfig::default()).await;
+        let mut events = runtime.subscribe();
+        let key = SessionKey::new("agent:observed");
+        let mut session = Session::new(key.clone(), runtime.agent_id().clone());
+
+        runtime.run_turn(&mut session, "go").await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 6, "{:?}", received);
+        assert!(matches!(
+            &received[0],
+            AgentEvent::TurnStarted { session, user_message }
+                if *session == key && user_message == "go"
+        ));
+        assert!(matches!(&received[1], AgentEvent::TokenUsage { .. }));
+        assert!(matches!(&received[2], AgentEvent::ToolCalled { name, .. } if name == "echo"));
+        assert!(matches!(
+            &received[3],
+            AgentEvent::ToolCompleted { id, result, .. } if id == "call_1" && !result.is_error
+        ));
+        assert!(matches!(&received[4], AgentEvent::TokenUsage { .. }));
+        match &received[5] {
+            AgentEvent::TurnEnded { outcome, usage, model_calls, .. } => {
+                assert_eq!(*outcome, TurnOutcome::Completed);
+                assert_eq!(*model_calls, 2);
+                assert_eq!(usage.output, summed_usage(&received).output);
+            }
+            other => panic!("expected TurnEnded, got {:?}", other),
+        }
+    }
+
+    /// Sum of the per-call usage events.
+    fn summed_usage(events: &[AgentEvent]) -> TokenUsage {
+        let mut total = TokenUsage::default();
+        for event in events {
+            if let AgentEvent::TokenUsage { usage, .. } = event {
+                total.add(usage);
+            }
+        }
+        total
+    }
+
+    #[tokio::test]
+    async fn test_full_event_buffer_does_not_stall_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let args = (0..8).map(|i| serde_json::json!(i)).collect();
+        let config = RuntimeConfig {
+            max_turns: 8,
+            ..Default::default()
+        };
+        let (runtime, _) = runtime(dir.path(), ScriptedProvider::looping(args), config).await;
+        let runtime = runtime.with_event_buffer(2);
+        let mut slow = runtime.subscribe();
+        let mut session = Session::new(SessionKey::new("agent:busy"), runtime.agent_id().clone());
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            runtime.run_turn(&mut session, "go"),
+        )
+        .await
+        .expect("turn stalled on a full event buffer")
+        .unwrap();
+        assert!(result.hit_limit());
+
+        // The slow subscriber lost the oldest events and keeps the newest.
+        assert!(matches!(
+            slow.recv().await,
+            Err(broadcast::error::RecvError::Lagged(n)) if n > 0
+        ));
+        assert!(matches!(slow.recv().await, Ok(AgentEvent::ToolCompleted { .. })));
+        assert!(matches!(slow.recv().await, Ok(AgentEvent::TurnEnded { .. })));
+    }
+
+    /// Tool standing in for `echo` that reports the context it was given.
+    struct ContextProbe;
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for ContextProbe {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn definition(&self) -> smartassist_core::types::ToolDefinition {
+            crate::tools::EchoTool::new().definition()
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            _args: serde_json::Value,
+            context: &ToolContext,
+        ) -> Result<smartassist_core::types::ToolResult> {
+            let data = serde_json::json!(context.data);
+            Ok(smartassist_core::types::ToolResult::success(tool_use_id, data))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_checked_against_schema() {
+        /// `echo` declaring string output while returning an object.
+        struct Mislabelled;
+
+        #[async_trait::async_trait]
+        impl crate::tools::Tool for Mislabelled {
+            fn name(&self) -> &str {
+                "echo"
+            }
+
+            fn definition(&self) -> smartassist_core::types::ToolDefinition {
+                let mut definition = crate::tools::EchoTool::new().definition();
+                definition.output_schema = Some(serde_json::json!({"type": "string"}));
+                definition
+            }
+
+            async fn execute(
+                &self,
+                tool_use_id: &str,
+                _args: serde_json::Value,
+                _context: &ToolContext,
+            ) -> Result<smartassist_core::types::ToolResult> {
+                let output = serde_json::json!({"echo": "hi"});
+                Ok(smartassist_core::types::ToolResult::success(tool_use_id, output))
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        for validate in [true, false] {
+            let registry = Arc::new(ToolRegistry::new());
+            registry.register(Arc::new(Mislabelled)).await;
+            let runtime = AgentRuntime::new(
+                AgentConfig::default(),
+                Arc::new(ScriptedProvider::metered()),
+                registry,
+                Arc::new(SessionManager::new(dir.path())),
+            )
+            .with_config(RuntimeConfig {
+                validate_tool_output: validate,
+                ..Default::default()
+            });
+            let mut session = Session::new(SessionKey::new("agent:schema"), AgentId::new("a"));
+
+            let result = runtime.run_turn(&mut session, "go").await.unwrap();
+            let tool_result = result.turn.tool_uses[0].result.as_ref().unwrap();
+            assert_eq!(tool_result.is_error, validate, "{}", tool_result.output);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_random_seed_reaches_tools() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(ContextProbe)).await;
+        let runtime = AgentRuntime::new(
+            AgentConfig::default(),
+            Arc::new(ScriptedProvider::metered()),
+            registry,
+            Arc::new(SessionManager::new(dir.path())),
+        )
+        .with_config(RuntimeConfig {
+            random_seed: Some(1234),
+            ..Default::default()
+        });
+        let mut session = Session::new(SessionKey::new("agent:seeded"), runtime.agent_id().clone());
+
+        let mut outputs = Vec::new();
+        runtime
+            .run_turn_with_events(&mut session, "go", &mut |event| {
+                if let TurnEvent::ToolFinished { result, .. } = event {
+                    outputs.push(result.output);
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(outputs, vec![serde_json::json!({ RANDOM_SEED_KEY: 1234 })]);
+    }
+
+    /// Tool standing in for `echo` that reports progress twice.
+    struct ProgressProbe;
+
+    #[async_trait::async_trait]
+    impl crate::tools::Tool for ProgressProbe {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn definition(&self) -> smartassist_core::types::ToolDefinition {
+            crate::tools::EchoTool::new().definition()
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            _args: serde_json::Value,
+            context: &ToolContext,
+        ) -> Result<smartassist_core::types::ToolResult> {
+            for step in 1..=2 {
+                context.report_progress(tool_use_id, self.name(), serde_json::json!(step));
+                tokio::task::yield_now().await;
+            }
+            let cancelled = context.cancel.is_cancelled();
+            Ok(smartassist_core::types::ToolResult::success(
+                tool_use_id,
+                serde_json::json!({ "cancelled": cancelled }),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_progress_is_published_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(ProgressProbe)).await;
+        let runtime = AgentRuntime::new(
+            AgentConfig::default(),
+            Arc::new(ScriptedProvider::metered()),
+            registry,
+            Arc::new(SessionManager::new(dir.path())),
+        );
+        let mut events = runtime.subscribe();
+        let mut session = Session::new(SessionKey::new("agent:index"), runtime.agent_id().clone());
+
+        runtime.run_turn(&mut session, "go").await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert!(matches!(&received[2], AgentEvent::ToolCalled { .. }));
+        for (event, step) in received[3..5].iter().zip(1..) {
+            assert!(matches!(
+                event,
+                AgentEvent::ToolProgress { id, name, progress, .. }
+                    if id == "call_1" && name == "echo" && *progress == step
+            ));
+        }
+        assert!(matches!(&received[5], AgentEvent::ToolCompleted { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_turn_stops_before_model_call() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runtime, provider) =
+            runtime(dir.path(), ScriptedProvider::metered(), RuntimeConfig::default()).await;
+        let mut session = Session::new(SessionKey::new("agent:abort"), runtime.agent_id().clone());
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = runtime
+            .run_turn_with_cancel(&mut session, "go", &mut |_| {}, cancel)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AgentError::Cancelled));
+        assert!(provider.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_turn_stops_at_max_iterations() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RuntimeConfig {
+            max_turns: 3,
+            ..Default::default()
+        };
+        let args = (0..10).map(|i| serde_json::json!(i)).collect();
+        let provider = ScriptedProvider::looping(args);
+        let (runtime, provider) = runtime(dir.path(), provider, config).await;
+        let key = SessionKey::new("agent:loop");
+        let mut session = Session::new(key, runtime.agent_id().clone());
+
+        let mut events = Vec::new();
+        let result = runtime
+            .run_turn_with_events(&mut session, "go", &mut |event| events.push(event))
+            .await
+            .unwrap();
+
+        assert_eq!(result.outcome, TurnOutcome::MaxIterations { limit: 3 });
+        assert!(result.hit_limit());
+        assert_eq!(result.turn.model_calls, 3);
+        assert_eq!(result.turn.tool_uses.len(), 3);
+        assert_eq!(provider.requests().len(), 3);
+        let response = result.turn.assistant_response.as_deref().unwrap();
+        assert!(response.starts_with("Stopped after 3 model calls without a final answer."));
+        assert_eq!(session.last_assistant_message().unwrap().content.to_text(), response);
+        assert!(matches!(
+            events.last(),
+            Some(TurnEvent::MaxIterationsReached { limit: 3 })
+        ));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TurnEvent::ModelCall { .. }))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_loop_guard_ignores_key_order() {
+        let mut guard = ToolLoopGuard::new(1);
+        assert_eq!(guard.check("read", &serde_json::json!({"a": 1, "b": 2})), None);
+        assert_eq!(guard.check("read", &serde_json::json!({"b": 2, "a": 1})), Some(1));
+        assert_eq!(guard.check("write", &serde_json::json!({"a": 1, "b": 2})), None);
+
+        let mut disabled = ToolLoopGuard::new(0);
+        for _ in 0..5 {
+            assert_eq!(disabled.check("read", &serde_json::json!({})), None);
+        }
+    }
 }
@@ -1,17 +1,28 @@
 //! Session management and persistence.
+//!
+//! Sessions are saved as plaintext JSON unless the [`SessionManager`] is
+//! given a [`SessionEncryption`] key.
 
+use crate::error::AgentError;
 use crate::Result;
 use chrono::{DateTime, Utc};
 use smartassist_core::types::{
     AgentId, ContentBlock, Message, MessageContent, Role, SessionKey, TokenUsage,
 };
+use std::fmt::Write as _;
 use serde::{Deserialize, Serialize};
+use smartassist_secrets::{crypto, SecretStore};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use tokio::fs;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, RwLock};
 use tracing::debug;
+use zeroize::Zeroizing;
+
+/// Secret holding the session encryption key, by default.
+pub const DEFAULT_SESSION_KEY_SECRET: &str = "session-encryption-key";
 
 /// A conversation session with an agent.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,6 +93,17 @@ pub enum SessionState {
     Archived,
 }
 
+/// Format for exporting a session transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Human-readable Markdown with collapsible tool blocks.
+    Markdown,
+
+    /// Full structured messages as JSON.
+    Json,
+}
+
 impl Session {
     /// Create a new session.
     pub fn new(key: SessionKey, agent_id: AgentId) -> Self {
@@ -122,6 +144,26 @@ impl Session {
         self.last_activity = Utc::now();
     }
 
+    /// Get the session's model override.
+    pub fn model(&self) -> Option<&str> {
+        self.metadata.model.as_deref()
+    }
+
+    /// Set or clear the model override, taking effect from the next turn.
+    pub fn set_model(&mut self, model: Option<String>) {
+        self.metadata.model = model;
+    }
+
+    /// Get the session's system prompt override.
+    pub fn system_prompt(&self) -> Option<&str> {
+        self.metadata.system_prompt.as_deref()
+    }
+
+    /// Set or clear the system prompt override, taking effect from the next turn.
+    pub fn set_system_prompt(&mut self, prompt: Option<String>) {
+        self.metadata.system_prompt = prompt;
+    }
+
     /// Get the last message.
     pub fn last_message(&self) -> Option<&Message> {
         self.messages.last()
@@ -165,6 +207,56 @@ impl Session {
         self.state = SessionState::Archived;
     }
 
+    /// Export the conversation transcript.
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Markdown => self.export_markdown(),
+            ExportFormat::Json => {
+                let export = serde_json::json!({
+                    "session_key": self.key.as_str(),
+                    "agent_id": self.agent_id.as_str(),
+                    "created_at": self.created_at,
+                    "last_activity": self.last_activity,
+                    "total_tokens": self.total_tokens,
+                    "messages": self.messages,
+                });
+                serde_json::to_string_pretty(&export).unwrap_or_default()
+            }
+        }
+    }
+
+    /// Render the transcript as Markdown.
+    fn export_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Session {}\n", self.key.as_str());
+        let _ = writeln!(out, "- Agent: {}", self.agent_id.as_str());
+        let _ = writeln!(out, "- Created: {}", self.created_at.to_rfc3339());
+        let _ = writeln!(out, "- Messages: {}", self.messages.len());
+
+        for message in &self.messages {
+            let role = match message.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                Role::System => "System",
+                Role::Tool => "Tool",
+            };
+            let _ = writeln!(out, "\n## {}\n", role);
+
+            match &message.content {
+                MessageContent::Text(text) => {
+                    let _ = writeln!(out, "{}", text);
+                }
+                MessageContent::Blocks(blocks) => {
+                    for block in blocks {
+                        render_markdown_block(&mut out, block);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
     /// Apply compaction to the session's messages.
     ///
     /// Replaces the current message history with the compacted version
@@ -181,7 +273,147 @@ impl Session {
     }
 }
 
+/// Render a content block as Markdown.
+fn render_markdown_block(out: &mut String, block: &ContentBlock) {
+    match block {
+        ContentBlock::Text { text } => {
+            let _ = writeln!(out, "{}\n", text);
+        }
+        ContentBlock::Image { source } => {
+            let _ = writeln!(
+                out,
+                "*[Image: {}, {} bytes base64]*\n",
+                source.media_type,
+                source.data.len()
+            );
+        }
+        ContentBlock::ToolUse { id, name, input } => {
+            let input = serde_json::to_string_pretty(input).unwrap_or_default();
+            let summary = format!(
+                "Tool call: <code>{}</code> ({})",
+                escape_html(name),
+                escape_html(id)
+            );
+            render_details(out, &summary, "json", &input);
+        }
+        ContentBlock::ToolResult {
+            tool_use_id,
+            content,
+            is_error,
+        } => {
+            let label = if *is_error { "Tool error" } else { "Tool result" };
+            let summary = format!("{} ({})", label, escape_html(tool_use_id));
+            render_details(out, &summary, "", content);
+        }
+        ContentBlock::Thinking { thinking } => {
+            render_details(out, "Thinking", "", thinking);
+        }
+    }
+}
+
+/// Escape text placed inside HTML tags so it displays literally.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Render a collapsible `<details>` block around fenced content.
+fn render_details(out: &mut String, summary: &str, lang: &str, body: &str) {
+    // Use a fence longer than any backtick run in the body
+    let longest_run = body
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+
+    let _ = writeln!(out, "<details>\n<summary>{}</summary>\n", summary);
+    let _ = writeln!(out, "{}{}\n{}\n{}\n", fence, lang, body, fence);
+    let _ = writeln!(out, "</details>\n");
+}
+
+/// Key for encrypting session files at rest.
+///
+/// Each save is encrypted with AES-256-GCM under a key derived from this
+/// one with a fresh salt, using the [`smartassist_secrets::crypto`]
+/// primitives.
+pub struct SessionEncryption {
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl SessionEncryption {
+    /// Encrypt with `key`.
+    pub fn new(key: Vec<u8>) -> Self {
+        Self {
+            key: Zeroizing::new(key),
+        }
+    }
+
+    /// Use the key stored in `store` under `name`, generating and storing
+    /// a new one if there is none.
+    pub async fn from_secret_store(store: &dyn SecretStore, name: &str) -> Result<Self> {
+        if store.exists(name).await? {
+            let secret = store.get(name).await?;
+            let key = hex::decode(secret.expose()).map_err(|e| {
+                AgentError::config(format!("Session key '{}' is not valid hex: {}", name, e))
+            })?;
+            return Ok(Self::new(key));
+        }
+
+        let key = crypto::generate_master_key();
+        store.set(name, &hex::encode(&key)).await?;
+        debug!(name, "Generated session encryption key");
+        Ok(Self::new(key))
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<EncryptedSessionFile> {
+        let (encrypted, salt) = crypto::encrypt(&self.key, plaintext)?;
+        Ok(EncryptedSessionFile {
+            encrypted_session: base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                &encrypted,
+            ),
+            salt: hex::encode(&salt),
+        })
+    }
+
+    fn decrypt(&self, file: &EncryptedSessionFile) -> Result<Vec<u8>> {
+        let encrypted = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &file.encrypted_session,
+        )
+        .map_err(|e| AgentError::config(format!("Invalid encrypted session: {}", e)))?;
+        let salt = hex::decode(&file.salt)
+            .map_err(|e| AgentError::config(format!("Invalid encrypted session salt: {}", e)))?;
+        Ok(crypto::decrypt(&self.key, &encrypted, &salt)?)
+    }
+}
+
+impl std::fmt::Debug for SessionEncryption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionEncryption").finish_non_exhaustive()
+    }
+}
+
+/// On-disk form of an encrypted session.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedSessionFile {
+    /// Nonce and AES-256-GCM ciphertext of the session JSON, base64-encoded.
+    encrypted_session: String,
+
+    /// HKDF salt, hex-encoded.
+    salt: String,
+}
+
 /// Manager for session persistence and lifecycle.
+///
+/// [`SessionManager::get_or_create`] and [`SessionManager::save`] do not
+/// lock, so two callers reading, changing and saving the same session can
+/// overwrite each other. Read-modify-write sequences should go through
+/// [`SessionManager::with_session`], or hold [`SessionManager::lock`] for
+/// their duration, which serializes them per session while different
+/// sessions proceed in parallel.
 pub struct SessionManager {
     /// Base directory for session storage.
     base_dir: PathBuf,
@@ -189,8 +421,19 @@ pub struct SessionManager {
     /// In-memory session cache.
     cache: RwLock<HashMap<String, Session>>,
 
+    /// Per-session locks, by cache key.
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+
     /// Maximum messages to keep in memory.
     max_messages: usize,
+
+    /// Encryption for session files, if enabled.
+    encryption: Option<SessionEncryption>,
+}
+
+/// Exclusive access to one session, released on drop.
+pub struct SessionGuard {
+    _guard: OwnedMutexGuard<()>,
 }
 
 impl SessionManager {
@@ -199,10 +442,21 @@ impl SessionManager {
         Self {
             base_dir: base_dir.into(),
             cache: RwLock::new(HashMap::new()),
+            locks: Mutex::new(HashMap::new()),
             max_messages: 100,
+            encryption: None,
         }
     }
 
+    /// Encrypt session files on disk.
+    ///
+    /// Plaintext sessions saved before encryption was enabled still load,
+    /// and are encrypted the next time they are saved.
+    pub fn with_encryption(mut self, encryption: SessionEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
     /// Set the maximum messages per session.
     pub fn with_max_messages(mut self, max: usize) -> Self {
         self.max_messages = max;
@@ -221,11 +475,16 @@ impl SessionManager {
             }
         }
 
-        // Try to load from disk
-        if let Ok(session) = self.load(key).await {
-            let mut cache = self.cache.write().await;
-            cache.insert(cache_key, session.clone());
-            return Ok(session);
+        // Try to load from disk. A missing or unreadable file means a new
+        // session, but one that can't be decrypted must not be replaced.
+        match self.load(key).await {
+            Ok(session) => {
+                let mut cache = self.cache.write().await;
+                cache.insert(cache_key, session.clone());
+                return Ok(session);
+            }
+            Err(AgentError::Io(_) | AgentError::Json(_)) => {}
+            Err(e) => return Err(e),
         }
 
         // Create new session
@@ -238,6 +497,42 @@ impl SessionManager {
         Ok(session)
     }
 
+    /// Wait for exclusive access to a session.
+    ///
+    /// Other callers of `lock` or [`Self::with_session`] for the same key
+    /// wait until the guard is dropped. The lock is not reentrant: calling
+    /// `with_session` for a key while holding its guard never completes.
+    pub async fn lock(&self, key: &SessionKey) -> SessionGuard {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+            // Forget locks nobody holds or waits on.
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(self.cache_key(key)).or_default().clone()
+        };
+
+        SessionGuard {
+            _guard: lock.lock_owned().await,
+        }
+    }
+
+    /// Load or create a session, apply `f` to it and save the result, all
+    /// while holding the session's lock.
+    ///
+    /// New sessions are assigned the agent named in the key.
+    pub async fn with_session<F, T>(&self, key: &SessionKey, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Session) -> T,
+    {
+        let _guard = self.lock(key).await;
+
+        let agent_id = key.agent_id().unwrap_or_else(|| AgentId::new("default"));
+        let mut session = self.get_or_create(key, &agent_id).await?;
+        let output = f(&mut session);
+        self.save(&session).await?;
+
+        Ok(output)
+    }
+
     /// Save a session.
     pub async fn save(&self, session: &Session) -> Result<()> {
         let cache_key = self.cache_key(&session.key);
@@ -255,7 +550,13 @@ impl SessionManager {
         }
 
         let json = serde_json::to_string_pretty(session)?;
-        fs::write(&path, json).await?;
+        match &self.encryption {
+            Some(encryption) => {
+                let file = encryption.encrypt(json.as_bytes())?;
+                fs::write(&path, serde_json::to_string_pretty(&file)?).await?;
+            }
+            None => fs::write(&path, json).await?,
+        }
 
         debug!("Saved session to {:?}", path);
         Ok(())
@@ -265,7 +566,19 @@ impl SessionManager {
     pub async fn load(&self, key: &SessionKey) -> Result<Session> {
         let path = self.session_path(key);
         let content = fs::read_to_string(&path).await?;
-        let session: Session = serde_json::from_str(&content)?;
+        let Ok(file) = serde_json::from_str::<EncryptedSessionFile>(&content) else {
+            let session: Session = serde_json::from_str(&content)?;
+            return Ok(session);
+        };
+
+        let encryption = self.encryption.as_ref().ok_or_else(|| {
+            AgentError::config(format!(
+                "Session {} is encrypted but no encryption key is configured",
+                key
+            ))
+        })?;
+        let json = encryption.decrypt(&file)?;
+        let session: Session = serde_json::from_slice(&json)?;
         Ok(session)
     }
 
@@ -467,4 +780,230 @@ mod tests {
         session.resume();
         assert!(session.is_active());
     }
+
+    #[tokio::test]
+    async fn test_concurrent_appends_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = Arc::new(SessionManager::new(dir.path()));
+        let key = SessionKey::new("agent1:busy");
+
+        let appends: Vec<_> = (0..32)
+            .map(|i| {
+                let manager = manager.clone();
+                let key = key.clone();
+                tokio::spawn(async move {
+                    manager
+                        .with_session(&key, |session| {
+                            session.add_user_message(format!("message {}", i))
+                        })
+                        .await
+                        .unwrap();
+                })
+            })
+            .collect();
+        for append in appends {
+            append.await.unwrap();
+        }
+
+        let session = manager.load(&key).await.unwrap();
+        assert_eq!(session.message_count(), 32);
+        assert_eq!(session.agent_id.as_str(), "agent1");
+        for i in 0..32 {
+            let text = format!("message {}", i);
+            assert!(session.messages.iter().any(|m| m.content.to_text() == text));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_locks_are_per_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(dir.path());
+        let a = SessionKey::new("agent1:a");
+        let b = SessionKey::new("agent1:b");
+
+        let _held = manager.lock(&a).await;
+
+        // Another session is unaffected...
+        let other = manager.with_session(&b, |session| session.add_user_message("hi"));
+        tokio::time::timeout(std::time::Duration::from_secs(1), other)
+            .await
+            .expect("other session blocked")
+            .unwrap();
+
+        // ...while the held one waits.
+        let same = manager.with_session(&a, |session| session.add_user_message("hi"));
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), same)
+            .await
+            .is_err());
+    }
+
+    fn export_session() -> Session {
+        let mut session = Session::new(SessionKey::new("agent1:session1"), AgentId::new("agent1"));
+        session.add_user_message("List the files");
+        session.add_message(
+            Role::Assistant,
+            vec![
+                ContentBlock::Text {
+                    text: "Let me check.".to_string(),
+                },
+                ContentBlock::ToolUse {
+                    id: "call_1".to_string(),
+                    name: "bash".to_string(),
+                    input: serde_json::json!({"command": "ls"}),
+                },
+            ],
+        );
+        session.messages.push(Message::tool_result("call_1", "Cargo.toml\nsrc", false));
+        session.add_assistant_message("There are two entries.");
+        session
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let markdown = export_session().export(ExportFormat::Markdown);
+
+        assert!(markdown.starts_with("# Session agent1:session1\n"));
+        assert!(markdown.contains("- Messages: 4"));
+
+        let headings: Vec<&str> = markdown.lines().filter(|l| l.starts_with("## ")).collect();
+        assert_eq!(headings, vec!["## User", "## Assistant", "## Tool", "## Assistant"]);
+
+        assert!(markdown.contains("<summary>Tool call: <code>bash</code> (call_1)</summary>"));
+        assert!(markdown.contains("```json\n{\n  \"command\": \"ls\"\n}\n```"));
+        assert!(markdown.contains("<summary>Tool result (call_1)</summary>"));
+        assert!(markdown.contains("```\nCargo.toml\nsrc\n```"));
+        assert_eq!(markdown.matches("<details>").count(), 2);
+        assert_eq!(markdown.matches("</details>").count(), 2);
+    }
+
+    #[test]
+    fn test_export_markdown_fence_escaping() {
+        let mut session = Session::new(SessionKey::new("agent1:s"), AgentId::new("agent1"));
+        session
+            .messages
+            .push(Message::tool_result("call_1", "```rust\nfn main() {}\n```", false));
+
+        let markdown = session.export(ExportFormat::Markdown);
+        assert!(markdown.contains("````\n```rust"));
+    }
+
+    #[test]
+    fn test_export_markdown_escapes_summary_html() {
+        let mut session = Session::new(SessionKey::new("agent1:s"), AgentId::new("agent1"));
+        session.add_message(
+            Role::Assistant,
+            vec![ContentBlock::ToolUse {
+                id: "call_<1>".to_string(),
+                name: "</summary><script>x</script>".to_string(),
+                input: serde_json::json!({}),
+            }],
+        );
+
+        let markdown = session.export(ExportFormat::Markdown);
+        assert!(markdown.contains(
+            "<summary>Tool call: <code>&lt;/summary&gt;&lt;script&gt;x&lt;/script&gt;</code> \
+             (call_&lt;1&gt;)</summary>"
+        ));
+        assert!(!markdown.contains("<script>"));
+    }
+
+    #[test]
+    fn test_export_json_round_trip() {
+        let session = export_session();
+        let json = session.export(ExportFormat::Json);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["session_key"], "agent1:session1");
+
+        let messages: Vec<Message> = serde_json::from_value(value["messages"].clone()).unwrap();
+        assert_eq!(messages.len(), session.messages.len());
+        assert_eq!(messages[0].content.to_text(), "List the files");
+
+        match &messages[1].content {
+            MessageContent::Blocks(blocks) => match &blocks[1] {
+                ContentBlock::ToolUse { id, name, input } => {
+                    assert_eq!(id, "call_1");
+                    assert_eq!(name, "bash");
+                    assert_eq!(input["command"], "ls");
+                }
+                other => panic!("unexpected block: {:?}", other),
+            },
+            other => panic!("unexpected content: {:?}", other),
+        }
+        assert_eq!(messages[2].role, Role::Tool);
+    }
+
+    const SECRET_TEXT: &str = "my bank PIN is 4821";
+
+    /// Save a session holding [`SECRET_TEXT`] with `manager`.
+    async fn save_secret_session(manager: &SessionManager) -> SessionKey {
+        let key = SessionKey::new("agent1:private");
+        let mut session = manager.get_or_create(&key, &AgentId::new("agent1")).await.unwrap();
+        session.add_user_message(SECRET_TEXT);
+        manager.save(&session).await.unwrap();
+        key
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = smartassist_secrets::FileSecretStore::new(
+            dir.path().join("secrets"),
+            crypto::generate_master_key(),
+        );
+        let sessions = dir.path().join("sessions");
+
+        let encryption = SessionEncryption::from_secret_store(&store, DEFAULT_SESSION_KEY_SECRET)
+            .await
+            .unwrap();
+        let manager = SessionManager::new(&sessions).with_encryption(encryption);
+        let key = save_secret_session(&manager).await;
+
+        let bytes = std::fs::read(manager.session_path(&key)).unwrap();
+        let on_disk = String::from_utf8_lossy(&bytes);
+        assert!(!on_disk.contains(SECRET_TEXT));
+        assert!(!on_disk.contains("bank"));
+
+        // A new manager has an empty cache and reuses the stored key.
+        let encryption = SessionEncryption::from_secret_store(&store, DEFAULT_SESSION_KEY_SECRET)
+            .await
+            .unwrap();
+        let manager = SessionManager::new(&sessions).with_encryption(encryption);
+        let session = manager.load(&key).await.unwrap();
+        assert_eq!(session.messages[0].content.to_text(), SECRET_TEXT);
+        assert_eq!(manager.list_for_agent(&AgentId::new("agent1")).await.unwrap(), vec![key]);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_session_needs_the_right_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = SessionManager::new(dir.path())
+            .with_encryption(SessionEncryption::new(crypto::generate_master_key()));
+        let key = save_secret_session(&manager).await;
+
+        let plain = SessionManager::new(dir.path());
+        let err = plain.load(&key).await.unwrap_err();
+        assert!(err.to_string().contains("no encryption key"), "{}", err);
+
+        let wrong = SessionManager::new(dir.path())
+            .with_encryption(SessionEncryption::new(crypto::generate_master_key()));
+        assert!(wrong.load(&key).await.is_err());
+        // The session is not replaced by an empty one either.
+        assert!(wrong.get_or_create(&key, &AgentId::new("agent1")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_session_loads_with_encryption_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = save_secret_session(&SessionManager::new(dir.path())).await;
+
+        let manager = SessionManager::new(dir.path())
+            .with_encryption(SessionEncryption::new(crypto::generate_master_key()));
+        let session = manager.load(&key).await.unwrap();
+        assert_eq!(session.messages[0].content.to_text(), SECRET_TEXT);
+
+        manager.save(&session).await.unwrap();
+        let on_disk = std::fs::read_to_string(manager.session_path(&key)).unwrap();
+        assert!(!on_disk.contains(SECRET_TEXT));
+    }
 }
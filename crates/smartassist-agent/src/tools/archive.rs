@@ -1,4 +1,10 @@
 //! Archive manipulation tools (zip, tar, gzip).
+//!
+//! Extraction never writes outside the output directory: entries with
+//! absolute paths or `..` components, entries under a symlink that leads
+//! elsewhere, and symlinks pointing outside are skipped and reported.
+//! [`ExtractLimits`] caps the number of entries and the bytes written, so a
+//! decompression bomb stops extraction instead of filling the disk.
 
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
@@ -8,16 +14,206 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::time::Instant;
 use zip::write::SimpleFileOptions;
 
+/// Bytes an archive may always expand to, whatever its compression ratio.
+const RATIO_FLOOR: u64 = 1024 * 1024;
+
+/// Limits applied when extracting an archive.
+#[derive(Debug, Clone, Copy)]
+pub struct ExtractLimits {
+    /// Maximum number of entries, directories included.
+    pub max_entries: usize,
+
+    /// Maximum total bytes written.
+    pub max_total_size: u64,
+
+    /// Maximum ratio of bytes written to the archive's size. Archives may
+    /// always expand to 1 MiB.
+    pub max_ratio: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_total_size: 1024 * 1024 * 1024,
+            max_ratio: 100,
+        }
+    }
+}
+
+/// State of one extraction: where it writes and how much it may still write.
+struct Extraction {
+    root: PathBuf,
+    limits: ExtractLimits,
+    max_size: u64,
+    entries: usize,
+    written: u64,
+    extracted: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl Extraction {
+    fn new(root: &Path, archive_size: u64, limits: ExtractLimits) -> std::io::Result<Self> {
+        let by_ratio = archive_size.saturating_mul(limits.max_ratio).max(RATIO_FLOOR);
+        Ok(Self {
+            root: root.canonicalize()?,
+            limits,
+            max_size: limits.max_total_size.min(by_ratio),
+            entries: 0,
+            written: 0,
+            extracted: Vec::new(),
+            skipped: Vec::new(),
+        })
+    }
+
+    /// Count an entry, failing once there are too many.
+    fn enter(&mut self) -> std::result::Result<(), String> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(format!("more than {} entries", self.limits.max_entries));
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.skipped.push(format!("{} ({})", name, reason));
+    }
+
+    /// Resolve an entry path inside the output directory.
+    ///
+    /// Returns `None` for paths that are absolute, contain `..`, or whose
+    /// parent directory resolves outside the output directory through a
+    /// symlink. Parent directories are created.
+    fn target(&self, entry: &Path) -> std::io::Result<Option<PathBuf>> {
+        let Some(relative) = normalized(entry) else {
+            return Ok(None);
+        };
+        let path = self.root.join(relative);
+        let Some(parent) = path.parent() else {
+            return Ok(None);
+        };
+        std::fs::create_dir_all(parent)?;
+        if !parent.canonicalize()?.starts_with(&self.root) {
+            return Ok(None);
+        }
+        // Writing through an existing symlink could land anywhere.
+        if path.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()) {
+            return Ok(None);
+        }
+        Ok(Some(path))
+    }
+
+    /// Check whether a symlink at `path` pointing to `target` resolves
+    /// inside the output directory.
+    ///
+    /// The target is followed from the link's real parent directory, so
+    /// links extracted earlier are taken into account. A `..` after a named
+    /// component is refused, since that name could be a link as well.
+    fn link_stays_inside(&self, path: &Path, target: &Path) -> std::io::Result<bool> {
+        let Some(parent) = path.parent() else {
+            return Ok(false);
+        };
+        let mut resolved = parent.canonicalize()?;
+        let mut named = false;
+        for component in target.components() {
+            match component {
+                Component::Normal(part) => {
+                    resolved.push(part);
+                    named = true;
+                }
+                Component::CurDir => {}
+                Component::ParentDir if !named => {
+                    resolved.pop();
+                }
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Ok(false)
+                }
+            }
+        }
+        Ok(resolved.starts_with(&self.root))
+    }
+
+    /// Copy an entry's contents to `path`, within the size limit.
+    ///
+    /// Returns an error message if the limit was hit; the partial file is
+    /// removed.
+    fn write(
+        &mut self,
+        reader: &mut dyn Read,
+        path: &Path,
+    ) -> crate::Result<std::result::Result<(), String>> {
+        let remaining = self.max_size - self.written;
+        let mut limited = reader.take(remaining + 1);
+        let copied = std::io::copy(&mut limited, &mut File::create(path)?)?;
+        if copied > remaining {
+            std::fs::remove_file(path)?;
+            return Ok(Err(format!("archive expands to more than {} bytes", self.max_size)));
+        }
+        self.written += copied;
+        self.extracted.push(path.to_string_lossy().to_string());
+        Ok(Ok(()))
+    }
+
+    fn into_result(
+        self,
+        tool_use_id: &str,
+        outcome: std::result::Result<(), String>,
+    ) -> ToolResult {
+        match outcome {
+            Ok(()) => ToolResult::success(
+                tool_use_id,
+                json!({
+                    "output_dir": self.root.to_string_lossy(),
+                    "files_extracted": self.extracted.len(),
+                    "files": self.extracted,
+                    "skipped": self.skipped,
+                }),
+            ),
+            Err(reason) => ToolResult::error(
+                tool_use_id,
+                format!(
+                    "Extraction stopped after {} files: {}",
+                    self.extracted.len(),
+                    reason
+                ),
+            ),
+        }
+    }
+}
+
+/// Strip `.` components, rejecting absolute paths and `..`.
+fn normalized(path: &Path) -> Option<PathBuf> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
 /// Tool for creating and extracting zip archives.
-pub struct ZipTool;
+pub struct ZipTool {
+    limits: ExtractLimits,
+}
 
 impl ZipTool {
     pub fn new() -> Self {
-        Self
+        Self {
+            limits: ExtractLimits::default(),
+        }
+    }
+
+    /// Set the limits applied when extracting.
+    pub fn with_limits(mut self, limits: ExtractLimits) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
@@ -73,6 +269,7 @@ impl Tool for ZipTool {
                 },
                 "required": ["operation", "archive"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -165,35 +362,13 @@ impl Tool for ZipTool {
                 std::fs::create_dir_all(&output_dir)?;
 
                 let file = File::open(&archive_path)?;
+                let archive_size = file.metadata()?.len();
                 let mut archive = zip::ZipArchive::new(file)
                     .map_err(|e| crate::error::AgentError::tool_execution(format!("Zip error: {}", e)))?;
-                let mut extracted = Vec::new();
-
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i)
-                        .map_err(|e| crate::error::AgentError::tool_execution(format!("Zip error: {}", e)))?;
-                    let outpath = output_dir.join(file.mangled_name());
+                let mut extraction = Extraction::new(&output_dir, archive_size, self.limits)?;
+                let outcome = extract_zip(&mut archive, &mut extraction)?;
 
-                    if file.name().ends_with('/') {
-                        std::fs::create_dir_all(&outpath)?;
-                    } else {
-                        if let Some(p) = outpath.parent() {
-                            std::fs::create_dir_all(p)?;
-                        }
-                        let mut outfile = File::create(&outpath)?;
-                        std::io::copy(&mut file, &mut outfile)?;
-                        extracted.push(outpath.to_string_lossy().to_string());
-                    }
-                }
-
-                Ok(ToolResult::success(
-                    tool_use_id,
-                    json!({
-                        "output_dir": output_dir.to_string_lossy(),
-                        "files_extracted": extracted.len(),
-                        "files": extracted
-                    }),
-                ).with_duration(start.elapsed()))
+                Ok(extraction.into_result(tool_use_id, outcome).with_duration(start.elapsed()))
             }
             "list" => {
                 let file = File::open(&archive_path)?;
@@ -224,6 +399,86 @@ impl Tool for ZipTool {
     }
 }
 
+/// Extract every entry of a zip archive that stays inside the output
+/// directory.
+fn extract_zip(
+    archive: &mut zip::ZipArchive<File>,
+    extraction: &mut Extraction,
+) -> crate::Result<std::result::Result<(), String>> {
+    if archive.len() > extraction.limits.max_entries {
+        return Ok(Err(format!("more than {} entries", extraction.limits.max_entries)));
+    }
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)
+            .map_err(|e| crate::error::AgentError::tool_execution(format!("Zip error: {}", e)))?;
+        if let Err(reason) = extraction.enter() {
+            return Ok(Err(reason));
+        }
+        let name = file.name().to_string();
+        let Some(outpath) = extraction.target(Path::new(&name))? else {
+            extraction.skip(&name, "outside the output directory");
+            continue;
+        };
+
+        if file.is_dir() {
+            std::fs::create_dir_all(&outpath)?;
+        } else if file.is_symlink() {
+            // Symlinks are not recreated; their target is checked so that a
+            // link out of the directory is reported rather than written.
+            let mut target = String::new();
+            (&mut file).take(4096).read_to_string(&mut target)?;
+            if !extraction.link_stays_inside(&outpath, Path::new(&target))? {
+                extraction.skip(&name, "symlink pointing outside the output directory");
+                continue;
+            }
+            extraction.skip(&name, "symlink");
+        } else if let Err(reason) = extraction.write(&mut file, &outpath)? {
+            return Ok(Err(reason));
+        }
+    }
+    Ok(Ok(()))
+}
+
+/// Extract every entry of a tar archive that stays inside the output
+/// directory.
+fn extract_tar<R: Read>(
+    archive: &mut tar::Archive<R>,
+    extraction: &mut Extraction,
+) -> crate::Result<std::result::Result<(), String>> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if let Err(reason) = extraction.enter() {
+            return Ok(Err(reason));
+        }
+        let path = entry.path()?.to_path_buf();
+        let name = path.to_string_lossy().to_string();
+        let Some(outpath) = extraction.target(&path)? else {
+            extraction.skip(&name, "outside the output directory");
+            continue;
+        };
+
+        let kind = entry.header().entry_type();
+        if kind.is_dir() {
+            std::fs::create_dir_all(&outpath)?;
+        } else if kind.is_symlink() {
+            let target = entry.link_name()?.map(|t| t.to_path_buf()).unwrap_or_default();
+            if !extraction.link_stays_inside(&outpath, &target)? {
+                extraction.skip(&name, "symlink pointing outside the output directory");
+                continue;
+            }
+            entry.unpack(&outpath)?;
+            extraction.extracted.push(outpath.to_string_lossy().to_string());
+        } else if kind.is_file() || kind.is_contiguous() {
+            if let Err(reason) = extraction.write(&mut entry, &outpath)? {
+                return Ok(Err(reason));
+            }
+        } else {
+            extraction.skip(&name, "unsupported entry type");
+        }
+    }
+    Ok(Ok(()))
+}
+
 #[derive(Debug, Serialize)]
 struct ArchiveEntry {
     name: String,
@@ -233,11 +488,21 @@ struct ArchiveEntry {
 }
 
 /// Tool for creating and extracting tar archives.
-pub struct TarTool;
+pub struct TarTool {
+    limits: ExtractLimits,
+}
 
 impl TarTool {
     pub fn new() -> Self {
-        Self
+        Self {
+            limits: ExtractLimits::default(),
+        }
+    }
+
+    /// Set the limits applied when extracting.
+    pub fn with_limits(mut self, limits: ExtractLimits) -> Self {
+        self.limits = limits;
+        self
     }
 }
 
@@ -300,6 +565,7 @@ impl Tool for TarTool {
                 },
                 "required": ["operation", "archive"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -430,39 +696,17 @@ impl Tool for TarTool {
                 std::fs::create_dir_all(&output_dir)?;
 
                 let file = File::open(&archive_path)?;
-                let mut extracted = Vec::new();
+                let archive_size = file.metadata()?.len();
+                let mut extraction = Extraction::new(&output_dir, archive_size, self.limits)?;
 
-                if use_gzip {
+                let outcome = if use_gzip {
                     let decoder = flate2::read::GzDecoder::new(file);
-                    let mut ar = tar::Archive::new(decoder);
-
-                    for entry in ar.entries()? {
-                        let mut entry = entry?;
-                        let path = entry.path()?.to_path_buf();
-                        let outpath = output_dir.join(&path);
-                        entry.unpack(&outpath)?;
-                        extracted.push(outpath.to_string_lossy().to_string());
-                    }
+                    extract_tar(&mut tar::Archive::new(decoder), &mut extraction)?
                 } else {
-                    let mut ar = tar::Archive::new(file);
+                    extract_tar(&mut tar::Archive::new(file), &mut extraction)?
+                };
 
-                    for entry in ar.entries()? {
-                        let mut entry = entry?;
-                        let path = entry.path()?.to_path_buf();
-                        let outpath = output_dir.join(&path);
-                        entry.unpack(&outpath)?;
-                        extracted.push(outpath.to_string_lossy().to_string());
-                    }
-                }
-
-                Ok(ToolResult::success(
-                    tool_use_id,
-                    json!({
-                        "output_dir": output_dir.to_string_lossy(),
-                        "files_extracted": extracted.len(),
-                        "files": extracted
-                    }),
-                ).with_duration(start.elapsed()))
+                Ok(extraction.into_result(tool_use_id, outcome).with_duration(start.elapsed()))
             }
             "list" => {
                 let file = File::open(&archive_path)?;
@@ -689,4 +933,222 @@ mod tests {
         assert!(!result.is_error);
         assert!(extract_dir.join("test.txt").exists());
     }
+
+    fn context_in(temp: &TempDir) -> ToolContext {
+        ToolContext {
+            cwd: temp.path().to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, data) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// Append an entry with a raw name, bypassing the tar crate's checks.
+    fn append_raw(
+        builder: &mut tar::Builder<File>,
+        name: &str,
+        kind: tar::EntryType,
+        link: Option<&str>,
+        data: &[u8],
+    ) {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(kind);
+        if let Some(link) = link {
+            header.set_link_name(link).unwrap();
+        }
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_zip_slip_entry_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        write_zip(
+            &temp.path().join("evil.zip"),
+            &[("../../evil.txt", b"pwned"), ("ok/fine.txt", b"fine")],
+        );
+        std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+
+        let result = ZipTool::new()
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "evil.zip", "path": "a/b/out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{:?}", result.output);
+        assert_eq!(result.output["files_extracted"], 1);
+        assert!(result.output["skipped"][0].as_str().unwrap().starts_with("../../evil.txt"));
+        assert!(temp.path().join("a/b/out/ok/fine.txt").exists());
+        assert!(!temp.path().join("a/evil.txt").exists());
+        assert!(!temp.path().join("evil.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_zip_bomb_is_stopped() {
+        let temp = TempDir::new().unwrap();
+        // 8 MiB of zeros deflates to a few KiB, far above the allowed ratio.
+        let zeros = vec![0u8; 8 * 1024 * 1024];
+        write_zip(&temp.path().join("bomb.zip"), &[("zeros.bin", &zeros)]);
+
+        let result = ZipTool::new()
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "bomb.zip", "path": "out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("expands to more than"), "{}", message);
+        assert!(!temp.path().join("out/zeros.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn test_zip_entry_count_limit() {
+        let temp = TempDir::new().unwrap();
+        let entries: Vec<(String, &[u8])> =
+            (0..5).map(|i| (format!("f{}.txt", i), &b"x"[..])).collect();
+        let entries: Vec<(&str, &[u8])> = entries.iter().map(|(n, d)| (n.as_str(), *d)).collect();
+        write_zip(&temp.path().join("many.zip"), &entries);
+
+        let tool = ZipTool::new().with_limits(ExtractLimits {
+            max_entries: 3,
+            ..Default::default()
+        });
+        let result = tool
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "many.zip", "path": "out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("more than 3 entries"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_tar_traversal_and_symlinks_are_refused() {
+        let temp = TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(File::create(temp.path().join("evil.tar")).unwrap());
+        append_raw(&mut builder, "../evil.txt", tar::EntryType::Regular, None, b"pwned");
+        append_raw(&mut builder, "escape", tar::EntryType::Symlink, Some("../.."), b"");
+        append_raw(&mut builder, "escape/evil.txt", tar::EntryType::Regular, None, b"pwned");
+        append_raw(&mut builder, "docs/link", tar::EntryType::Symlink, Some("../readme"), b"");
+        append_raw(&mut builder, "readme", tar::EntryType::Regular, None, b"hello");
+        builder.finish().unwrap();
+        drop(builder);
+
+        let result = TarTool::new()
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "evil.tar", "path": "out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{:?}", result.output);
+        let skipped = result.output["skipped"].as_array().unwrap();
+        assert_eq!(skipped.len(), 2, "{:?}", skipped);
+        assert!(!temp.path().join("evil.txt").exists());
+        assert!(!temp.path().join("out/escape").is_symlink());
+        // `escape/evil.txt` became a plain directory and file inside `out`.
+        assert!(temp.path().join("out/escape/evil.txt").is_file());
+        assert_eq!(std::fs::read_to_string(temp.path().join("out/docs/link")).unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_tar_gz_bomb_is_stopped() {
+        let temp = TempDir::new().unwrap();
+        let file = File::create(temp.path().join("bomb.tar.gz")).unwrap();
+        let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::best());
+        let mut builder = tar::Builder::new(encoder);
+        let zeros = vec![0u8; 8 * 1024 * 1024];
+        let mut header = tar::Header::new_gnu();
+        header.set_size(zeros.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "zeros.bin", &zeros[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let result = TarTool::new()
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "bomb.tar.gz", "path": "out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("expands to more than"), "{}", message);
+        assert!(!temp.path().join("out/zeros.bin").exists());
+    }
+
+    #[tokio::test]
+    async fn test_tar_symlink_chain_cannot_escape() {
+        let temp = TempDir::new().unwrap();
+        let mut builder = tar::Builder::new(File::create(temp.path().join("chain.tar")).unwrap());
+        // `d` is the output directory itself, so `d/..` is its parent.
+        append_raw(&mut builder, "d", tar::EntryType::Symlink, Some("."), b"");
+        append_raw(&mut builder, "d/up", tar::EntryType::Symlink, Some("../outside"), b"");
+        builder.finish().unwrap();
+        drop(builder);
+
+        let result = TarTool::new()
+            .execute(
+                "test",
+                json!({"operation": "extract", "archive": "chain.tar", "path": "out"}),
+                &context_in(&temp),
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{:?}", result.output);
+        let skipped = result.output["skipped"].as_array().unwrap();
+        assert_eq!(skipped.len(), 1, "{:?}", skipped);
+        assert!(skipped[0].as_str().unwrap().starts_with("d/up"));
+        assert!(temp.path().join("out/d").is_symlink());
+        assert!(!temp.path().join("out/up").is_symlink());
+    }
+
+    #[test]
+    fn test_link_stays_inside() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("a/b")).unwrap();
+        let extraction = Extraction::new(temp.path(), 0, ExtractLimits::default()).unwrap();
+        let inside = |path: &str, target: &str| {
+            extraction
+                .link_stays_inside(&temp.path().join(path), Path::new(target))
+                .unwrap()
+        };
+
+        assert!(inside("a/link", "../b"));
+        assert!(inside("link", "./b/c"));
+        assert!(!inside("link", "../b"));
+        assert!(!inside("a/b/link", "../../../etc"));
+        assert!(!inside("link", "/etc/passwd"));
+        assert!(!inside("a/link", "b/../../.."));
+    }
 }
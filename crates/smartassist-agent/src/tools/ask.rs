@@ -111,6 +111,7 @@ impl Tool for AskUserTool {
                 },
                 "required": ["questions"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -289,6 +290,7 @@ impl Tool for ConfirmTool {
                 },
                 "required": ["message"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -68,6 +68,7 @@ impl Tool for CronTool {
                 },
                 "required": ["action"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -214,6 +215,7 @@ impl Tool for GatewayTool {
                 },
                 "required": ["action"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -339,6 +341,7 @@ impl Tool for NodesTool {
                 },
                 "required": ["action"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
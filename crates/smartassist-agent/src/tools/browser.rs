@@ -597,6 +597,7 @@ fn browser_tool_definition() -> ToolDefinition {
             },
             "required": ["action"]
         }),
+        output_schema: None,
         execution: ToolExecutionConfig::default(),
     }
 }
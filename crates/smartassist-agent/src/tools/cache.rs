@@ -0,0 +1,235 @@
+//! Result cache for pure tools.
+//!
+//! Results are keyed by tool name and a hash of the canonicalized arguments,
+//! so argument key order does not affect cache hits. Only tools whose
+//! definition marks them `cacheable` are ever cached.
+
+use sha2::{Digest, Sha256};
+use smartassist_core::types::ToolResult;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Configuration for the tool result cache.
+#[derive(Debug, Clone)]
+pub struct ToolCacheConfig {
+    /// How long a cached result stays valid.
+    pub ttl: Duration,
+
+    /// Maximum number of cached results.
+    pub max_entries: usize,
+}
+
+impl Default for ToolCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            max_entries: 1000,
+        }
+    }
+}
+
+/// A cached tool result.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    result: ToolResult,
+    inserted_at: Instant,
+}
+
+/// TTL- and size-bounded cache of tool results.
+pub struct ToolCache {
+    config: ToolCacheConfig,
+    entries: Mutex<HashMap<(String, String), CacheEntry>>,
+}
+
+impl Default for ToolCache {
+    fn default() -> Self {
+        Self::new(ToolCacheConfig::default())
+    }
+}
+
+impl ToolCache {
+    /// Create a new cache.
+    pub fn new(config: ToolCacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Look up a cached result.
+    ///
+    /// The returned result carries the caller's `tool_use_id`.
+    pub async fn get(
+        &self,
+        tool_name: &str,
+        args: &serde_json::Value,
+        tool_use_id: &str,
+    ) -> Option<ToolResult> {
+        let key = (tool_name.to_string(), canonical_json_hash(args));
+        let mut entries = self.entries.lock().await;
+
+        match entries.get(&key) {
+            Some(entry) if entry.inserted_at.elapsed() < self.config.ttl => Some(ToolResult {
+                tool_use_id: tool_use_id.to_string(),
+                ..entry.result.clone()
+            }),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a result, evicting the oldest entry if the cache is full.
+    pub async fn insert(&self, tool_name: &str, args: &serde_json::Value, result: ToolResult) {
+        if self.config.max_entries == 0 {
+            return;
+        }
+
+        let key = (tool_name.to_string(), canonical_json_hash(args));
+        let mut entries = self.entries.lock().await;
+
+        let ttl = self.config.ttl;
+        entries.retain(|_, entry| entry.inserted_at.elapsed() < ttl);
+
+        if !entries.contains_key(&key) && entries.len() >= self.config.max_entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                result,
+                inserted_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Number of cached results (including not yet evicted expired ones).
+    pub async fn len(&self) -> usize {
+        self.entries.lock().await.len()
+    }
+
+    /// Whether the cache is empty.
+    pub async fn is_empty(&self) -> bool {
+        self.entries.lock().await.is_empty()
+    }
+
+    /// Remove all cached results.
+    pub async fn clear(&self) {
+        self.entries.lock().await.clear();
+    }
+}
+
+/// Hash a JSON value independent of object key order.
+pub fn canonical_json_hash(value: &serde_json::Value) -> String {
+    let mut canonical = String::new();
+    write_canonical(value, &mut canonical);
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Serialize a JSON value with object keys sorted.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        serde_json::Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonical_hash_ignores_key_order() {
+        let a = json!({"input": "hello", "algorithm": "sha256", "nested": {"x": 1, "y": [1, 2]}});
+        let b = json!({"nested": {"y": [1, 2], "x": 1}, "algorithm": "sha256", "input": "hello"});
+        assert_eq!(canonical_json_hash(&a), canonical_json_hash(&b));
+
+        let c = json!({"input": "hello", "algorithm": "md5"});
+        assert_ne!(canonical_json_hash(&a), canonical_json_hash(&c));
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit() {
+        let cache = ToolCache::default();
+        let args = json!({"input": "hello"});
+
+        cache
+            .insert("base64", &args, ToolResult::success("use1", json!("aGVsbG8=")))
+            .await;
+
+        let hit = cache.get("base64", &args, "use2").await.unwrap();
+        assert_eq!(hit.tool_use_id, "use2");
+        assert_eq!(hit.output, json!("aGVsbG8="));
+
+        assert!(cache.get("hex", &args, "use3").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_ttl_expiry() {
+        let cache = ToolCache::new(ToolCacheConfig {
+            ttl: Duration::from_millis(20),
+            max_entries: 10,
+        });
+        let args = json!({"input": "hello"});
+
+        cache
+            .insert("base64", &args, ToolResult::success("use1", json!("aGVsbG8=")))
+            .await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        assert!(cache.get("base64", &args, "use2").await.is_none());
+        assert!(cache.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_cache_size_cap() {
+        let cache = ToolCache::new(ToolCacheConfig {
+            ttl: Duration::from_secs(60),
+            max_entries: 2,
+        });
+
+        for i in 0..3 {
+            cache
+                .insert("echo", &json!({"n": i}), ToolResult::success("id", json!(i)))
+                .await;
+        }
+
+        assert_eq!(cache.len().await, 2);
+        assert!(cache.get("echo", &json!({"n": 0}), "id").await.is_none());
+        assert!(cache.get("echo", &json!({"n": 2}), "id").await.is_some());
+    }
+}
@@ -126,6 +126,7 @@ impl Tool for CanvasTool {
                 },
                 "required": ["action", "node"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -68,6 +68,7 @@ impl Tool for TelegramActionsTool {
                 },
                 "required": ["action", "chat_id"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -275,6 +276,7 @@ impl Tool for DiscordActionsTool {
                 },
                 "required": ["action"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -461,6 +463,7 @@ impl Tool for SlackActionsTool {
                 },
                 "required": ["action", "channel"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -1,4 +1,8 @@
 //! File checksum and integrity tools.
+//!
+//! Files are hashed as a stream of fixed-size reads, so memory use does not
+//! grow with the file. The hashing helpers here are shared with the `hash`
+//! tool.
 
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
@@ -6,9 +10,160 @@ use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
 use serde::Deserialize;
 use serde_json::json;
-use sha2::{Digest, Sha256};
+use sha2::{Digest, Sha256, Sha512};
 use std::path::Path;
 use std::time::Instant;
+use tokio::io::AsyncReadExt;
+
+/// Bytes read from a file per hasher update, bounding memory use
+/// regardless of the file size.
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A hash algorithm supported by the hashing tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Names accepted by [`HashAlgorithm::parse`], for tool schemas.
+    pub(crate) const NAMES: [&'static str; 5] = ["md5", "sha1", "sha256", "sha512", "blake3"];
+
+    /// Parse an algorithm name, ignoring case.
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "md5" => Some(Self::Md5),
+            "sha1" => Some(Self::Sha1),
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            "blake3" => Some(Self::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Error message for a name [`HashAlgorithm::parse`] rejected.
+    pub(crate) fn unknown(name: &str) -> String {
+        format!(
+            "Unknown algorithm: {}. Use md5, sha1, sha256, sha512, or blake3",
+            name
+        )
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+            Self::Blake3 => "blake3",
+        }
+    }
+
+    /// Whether collisions are infeasible to construct.
+    ///
+    /// MD5 and SHA-1 are broken: they still catch accidental corruption but
+    /// must not be relied on against tampering.
+    pub(crate) fn is_cryptographic(&self) -> bool {
+        !matches!(self, Self::Md5 | Self::Sha1)
+    }
+
+    /// Warning to show alongside a digest from a broken algorithm.
+    pub(crate) fn warning(&self) -> Option<String> {
+        (!self.is_cryptographic()).then(|| {
+            format!(
+                "{} is not collision resistant; use it only to detect accidental \
+                 corruption, not tampering",
+                self.as_str().to_uppercase()
+            )
+        })
+    }
+
+    pub(crate) fn hasher(&self) -> Hasher {
+        match self {
+            Self::Md5 => Hasher::Md5(md5::Context::new()),
+            Self::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+            Self::Sha512 => Hasher::Sha512(Sha512::new()),
+            Self::Blake3 => Hasher::Blake3(Box::default()),
+        }
+    }
+}
+
+/// Incremental state of a [`HashAlgorithm`].
+pub(crate) enum Hasher {
+    Md5(md5::Context),
+    Sha1(sha1::Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Md5(context) => context.consume(data),
+            Self::Sha1(hasher) => hasher.update(data),
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    /// Finish hashing and return the digest as lowercase hex.
+    pub(crate) fn finalize_hex(self) -> String {
+        match self {
+            Self::Md5(context) => hex::encode(context.compute().0),
+            Self::Sha1(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha512(hasher) => hex::encode(hasher.finalize()),
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Hash `data` in one call.
+pub(crate) fn hash_bytes(algorithm: HashAlgorithm, data: &[u8]) -> String {
+    let mut hasher = algorithm.hasher();
+    hasher.update(data);
+    hasher.finalize_hex()
+}
+
+/// Hash everything `reader` yields, returning the digest and byte count.
+pub(crate) async fn hash_reader<R>(
+    algorithm: HashAlgorithm,
+    mut reader: R,
+) -> std::io::Result<(String, u64)>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let mut hasher = algorithm.hasher();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    let mut total = 0u64;
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        total += read as u64;
+    }
+    Ok((hasher.finalize_hex(), total))
+}
+
+/// Hash a file without loading it into memory.
+pub(crate) async fn hash_file(
+    algorithm: HashAlgorithm,
+    path: &Path,
+) -> std::io::Result<(String, u64)> {
+    let file = tokio::fs::File::open(path).await?;
+    hash_reader(algorithm, file).await
+}
 
 /// Tool for computing file checksums.
 pub struct FileChecksumTool;
@@ -43,7 +198,8 @@ impl Tool for FileChecksumTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "file_checksum".to_string(),
-            description: "Compute checksum/hash of a file".to_string(),
+            description: "Compute checksum/hash of a file, streaming it so any size works"
+                .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -53,12 +209,14 @@ impl Tool for FileChecksumTool {
                     },
                     "algorithm": {
                         "type": "string",
-                        "enum": ["md5", "sha1", "sha256", "sha512"],
-                        "description": "Hash algorithm (default: sha256)"
+                        "enum": HashAlgorithm::NAMES,
+                        "description": "Hash algorithm (default: sha256). md5 and sha1 are not \
+                                        cryptographically secure"
                     }
                 },
                 "required": ["path"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -90,41 +248,13 @@ impl Tool for FileChecksumTool {
             ));
         }
 
-        let content = tokio::fs::read(&file_path)
-            .await
-            .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read file: {}", e)))?;
-
-        let hash = match algorithm.to_lowercase().as_str() {
-            "md5" => {
-                let digest = md5::compute(&content);
-                hex::encode(digest.0)
-            }
-            "sha1" => {
-                use sha1::Digest as Sha1Digest;
-                let mut hasher = sha1::Sha1::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha256" => {
-                let mut hasher = Sha256::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha512" => {
-                use sha2::Sha512;
-                let mut hasher = Sha512::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            _ => {
-                return Ok(ToolResult::error(
-                    tool_use_id,
-                    format!("Unknown algorithm: {}. Use md5, sha1, sha256, or sha512", algorithm),
-                ));
-            }
+        let Some(hash_algorithm) = HashAlgorithm::parse(&algorithm) else {
+            return Ok(ToolResult::error(tool_use_id, HashAlgorithm::unknown(&algorithm)));
         };
 
-        let size = content.len();
+        let (hash, size) = hash_file(hash_algorithm, &file_path)
+            .await
+            .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read file: {}", e)))?;
 
         Ok(ToolResult::success(
             tool_use_id,
@@ -132,7 +262,9 @@ impl Tool for FileChecksumTool {
                 "path": file_path.to_string_lossy(),
                 "algorithm": algorithm,
                 "hash": hash,
-                "size": size
+                "size": size,
+                "cryptographic": hash_algorithm.is_cryptographic(),
+                "warning": hash_algorithm.warning()
             }),
         ).with_duration(start.elapsed()))
     }
@@ -187,12 +319,14 @@ impl Tool for FileVerifyTool {
                     },
                     "algorithm": {
                         "type": "string",
-                        "enum": ["md5", "sha1", "sha256", "sha512"],
-                        "description": "Hash algorithm (default: sha256)"
+                        "enum": HashAlgorithm::NAMES,
+                        "description": "Hash algorithm (default: sha256). md5 and sha1 are not \
+                                        cryptographically secure"
                     }
                 },
                 "required": ["path", "expected"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -224,40 +358,14 @@ impl Tool for FileVerifyTool {
             ));
         }
 
-        let content = tokio::fs::read(&file_path)
+        let Some(hash_algorithm) = HashAlgorithm::parse(&algorithm) else {
+            return Ok(ToolResult::error(tool_use_id, HashAlgorithm::unknown(&algorithm)));
+        };
+
+        let (actual_hash, _) = hash_file(hash_algorithm, &file_path)
             .await
             .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read file: {}", e)))?;
 
-        let actual_hash = match algorithm.to_lowercase().as_str() {
-            "md5" => {
-                let digest = md5::compute(&content);
-                hex::encode(digest.0)
-            }
-            "sha1" => {
-                use sha1::Digest as Sha1Digest;
-                let mut hasher = sha1::Sha1::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha256" => {
-                let mut hasher = Sha256::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha512" => {
-                use sha2::Sha512;
-                let mut hasher = Sha512::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            _ => {
-                return Ok(ToolResult::error(
-                    tool_use_id,
-                    format!("Unknown algorithm: {}. Use md5, sha1, sha256, or sha512", algorithm),
-                ));
-            }
-        };
-
         let expected = args.expected.to_lowercase();
         let matches = actual_hash == expected;
 
@@ -268,7 +376,9 @@ impl Tool for FileVerifyTool {
                 "algorithm": algorithm,
                 "expected": expected,
                 "actual": actual_hash,
-                "matches": matches
+                "matches": matches,
+                "cryptographic": hash_algorithm.is_cryptographic(),
+                "warning": hash_algorithm.warning()
             }),
         ).with_duration(start.elapsed()))
     }
@@ -305,6 +415,76 @@ mod tests {
         assert!(output["hash"].as_str().is_some());
     }
 
+    #[tokio::test]
+    async fn test_large_file_digest() {
+        let temp = TempDir::new().unwrap();
+        // Not a multiple of the buffer size, so the last read is partial.
+        let content: Vec<u8> = (0..3 * 1024 * 1024 + 17).map(|i| (i % 251) as u8).collect();
+        std::fs::write(temp.path().join("large.bin"), &content).unwrap();
+        let context = ToolContext {
+            cwd: temp.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let result = FileChecksumTool::new()
+            .execute("test", json!({"path": "large.bin"}), &context)
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert_eq!(
+            result.output["hash"],
+            "fe2aaf82bfa2ffec207a0c6fa7ce7d4af268d67e2672fdaec675f3f9b65d0854"
+        );
+        assert_eq!(result.output["size"], content.len());
+        assert_eq!(result.output["cryptographic"], true);
+        assert!(result.output["warning"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_hash_reader_never_buffers_whole_input() {
+        // 32 MiB of zeros generated on the fly; only one buffer exists at a time.
+        let reader = tokio::io::repeat(0).take(32 * 1024 * 1024);
+        let (hash, size) = hash_reader(HashAlgorithm::Sha256, reader).await.unwrap();
+        assert_eq!(hash, "83ee47245398adee79bd9c0a8bc57b821e92aba10f5f9ade8a5d1fae4d8c4302");
+        assert_eq!(size, 32 * 1024 * 1024);
+    }
+
+    #[tokio::test]
+    async fn test_streaming_matches_oneshot() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("data.bin");
+        let n = HASH_BUFFER_SIZE;
+
+        for len in [0, 1, n - 1, n, n + 1, 3 * n + 5] {
+            let content: Vec<u8> = (0..len).map(|i| (i * 7 % 256) as u8).collect();
+            std::fs::write(&path, &content).unwrap();
+            for name in HashAlgorithm::NAMES {
+                let algorithm = HashAlgorithm::parse(name).unwrap();
+                let (streamed, size) = hash_file(algorithm, &path).await.unwrap();
+                assert_eq!(streamed, hash_bytes(algorithm, &content), "{} at {}", name, len);
+                assert_eq!(size, len as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_digests_and_weak_algorithms() {
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Md5, b"hello"),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Blake3, b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert!(!HashAlgorithm::Md5.is_cryptographic());
+        assert!(HashAlgorithm::Md5.warning().unwrap().starts_with("MD5 is not"));
+        assert!(HashAlgorithm::Blake3.is_cryptographic());
+        assert_eq!(HashAlgorithm::parse("SHA512"), Some(HashAlgorithm::Sha512));
+        assert_eq!(HashAlgorithm::parse("crc32"), None);
+    }
+
     #[tokio::test]
     async fn test_file_verify_match() {
         let temp = TempDir::new().unwrap();
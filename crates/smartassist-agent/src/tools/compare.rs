@@ -29,8 +29,50 @@ struct CompareArgs {
     a: serde_json::Value,
     /// Second value
     b: serde_json::Value,
-    /// Comparison operator
-    operator: String,
+    /// Comparison operator (value mode only)
+    #[serde(default)]
+    operator: Option<String>,
+    /// How `a` and `b` are compared
+    #[serde(default)]
+    mode: CompareMode,
+}
+
+/// How [`CompareTool`] interprets its operands.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CompareMode {
+    /// Compare the values directly with `operator`.
+    #[default]
+    Value,
+    /// Structural diff of two JSON documents.
+    Json,
+    /// Structural diff of two YAML documents.
+    Yaml,
+}
+
+impl CompareMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Value => "value",
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+        }
+    }
+
+    /// Parse an operand as a document.
+    ///
+    /// Strings hold the document text; any other value is taken as an
+    /// already-parsed document.
+    fn parse(self, value: serde_json::Value) -> std::result::Result<serde_json::Value, String> {
+        let serde_json::Value::String(text) = value else {
+            return Ok(value);
+        };
+        match self {
+            Self::Value => Ok(serde_json::Value::String(text)),
+            Self::Json => serde_json::from_str(&text).map_err(|e| format!("Invalid JSON: {}", e)),
+            Self::Yaml => serde_yaml::from_str(&text).map_err(|e| format!("Invalid YAML: {}", e)),
+        }
+    }
 }
 
 #[async_trait]
@@ -42,7 +84,9 @@ impl Tool for CompareTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "compare".to_string(),
-            description: "Compare two values with various operators".to_string(),
+            description: "Compare two values with various operators, or diff two JSON/YAML \
+                documents structurally"
+                .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -55,11 +99,19 @@ impl Tool for CompareTool {
                     "operator": {
                         "type": "string",
                         "enum": ["eq", "ne", "lt", "le", "gt", "ge", "contains", "starts_with", "ends_with"],
-                        "description": "Comparison operator"
+                        "description": "Comparison operator (required in value mode)"
+                    },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["value", "json", "yaml"],
+                        "description": "value compares with the operator; json and yaml parse \
+                            string operands as documents and report added, removed and changed \
+                            paths, ignoring key order and formatting"
                     }
                 },
-                "required": ["a", "b", "operator"]
+                "required": ["a", "b"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -77,7 +129,17 @@ impl Tool for CompareTool {
         let start = Instant::now();
         let args: CompareArgs = serde_json::from_value(args)?;
 
-        let result = match args.operator.as_str() {
+        if args.mode != CompareMode::Value {
+            return Ok(compare_documents(tool_use_id, args)?.with_duration(start.elapsed()));
+        }
+        let Some(operator) = args.operator else {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                "operator is required in value mode".to_string(),
+            ));
+        };
+
+        let result = match operator.as_str() {
             "eq" => args.a == args.b,
             "ne" => args.a != args.b,
             "lt" | "le" | "gt" | "ge" => {
@@ -85,7 +147,7 @@ impl Tool for CompareTool {
                 let a = args.a.as_f64();
                 let b = args.b.as_f64();
                 match (a, b) {
-                    (Some(a), Some(b)) => match args.operator.as_str() {
+                    (Some(a), Some(b)) => match operator.as_str() {
                         "lt" => a < b,
                         "le" => a <= b,
                         "gt" => a > b,
@@ -118,7 +180,7 @@ impl Tool for CompareTool {
             _ => {
                 return Ok(ToolResult::error(
                     tool_use_id,
-                    format!("Unknown operator: {}", args.operator),
+                    format!("Unknown operator: {}", operator),
                 ));
             }
         };
@@ -127,7 +189,7 @@ impl Tool for CompareTool {
             tool_use_id,
             json!({
                 "result": result,
-                "operator": args.operator,
+                "operator": operator,
                 "a": args.a,
                 "b": args.b
             }),
@@ -135,6 +197,103 @@ impl Tool for CompareTool {
     }
 }
 
+/// Diff two documents and report whether they are equal.
+fn compare_documents(tool_use_id: &str, args: CompareArgs) -> Result<ToolResult> {
+    let parsed = args
+        .mode
+        .parse(args.a)
+        .and_then(|a| Ok((a, args.mode.parse(args.b)?)));
+    let (a, b) = match parsed {
+        Ok(documents) => documents,
+        Err(e) => return Ok(ToolResult::error(tool_use_id, e)),
+    };
+
+    let mut diff = DocumentDiff::default();
+    diff.compare("$", &a, &b);
+
+    Ok(ToolResult::success(
+        tool_use_id,
+        json!({
+            "equal": diff.is_empty(),
+            "mode": args.mode.as_str(),
+            "added": diff.added,
+            "removed": diff.removed,
+            "changed": diff.changed
+        }),
+    ))
+}
+
+/// Paths that differ between two documents.
+///
+/// Object keys are matched by name, so key order never matters. Arrays are
+/// compared by index. Numbers are equal when their numeric values are, so
+/// `1` equals `1.0`; no other coercion is done, so `"1"` differs from `1`
+/// and `null` differs from a missing key.
+#[derive(Debug, Default)]
+struct DocumentDiff {
+    /// Paths only in `b`, with their values.
+    added: Vec<serde_json::Value>,
+    /// Paths only in `a`, with their values.
+    removed: Vec<serde_json::Value>,
+    /// Paths in both whose values differ.
+    changed: Vec<serde_json::Value>,
+}
+
+impl DocumentDiff {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn compare(&mut self, path: &str, a: &serde_json::Value, b: &serde_json::Value) {
+        use serde_json::Value;
+
+        match (a, b) {
+            (Value::Object(a), Value::Object(b)) => {
+                for (key, value) in a {
+                    let child = format!("{}.{}", path, key);
+                    match b.get(key) {
+                        Some(other) => self.compare(&child, value, other),
+                        None => self.removed.push(json!({ "path": child, "value": value })),
+                    }
+                }
+                for (key, value) in b {
+                    if !a.contains_key(key) {
+                        let child = format!("{}.{}", path, key);
+                        self.added.push(json!({ "path": child, "value": value }));
+                    }
+                }
+            }
+            (Value::Array(a), Value::Array(b)) => {
+                for (i, value) in a.iter().enumerate() {
+                    let child = format!("{}[{}]", path, i);
+                    match b.get(i) {
+                        Some(other) => self.compare(&child, value, other),
+                        None => self.removed.push(json!({ "path": child, "value": value })),
+                    }
+                }
+                for (i, value) in b.iter().enumerate().skip(a.len()) {
+                    let child = format!("{}[{}]", path, i);
+                    self.added.push(json!({ "path": child, "value": value }));
+                }
+            }
+            (Value::Number(x), Value::Number(y)) if numbers_equal(x, y) => {}
+            _ if a == b => {}
+            _ => self.changed.push(json!({ "path": path, "from": a, "to": b })),
+        }
+    }
+}
+
+/// Compare numbers by value, exactly for integers.
+fn numbers_equal(a: &serde_json::Number, b: &serde_json::Number) -> bool {
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64()) {
+        return x == y;
+    }
+    if let (Some(x), Some(y)) = (a.as_u64(), b.as_u64()) {
+        return x == y;
+    }
+    a.as_f64() == b.as_f64()
+}
+
 fn value_to_string(value: &serde_json::Value) -> String {
     match value {
         serde_json::Value::String(s) => s.clone(),
@@ -193,6 +352,7 @@ impl Tool for AssertTool {
                 },
                 "required": ["condition"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -278,6 +438,7 @@ impl Tool for MatchTool {
                 },
                 "required": ["value", "pattern"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -375,6 +536,7 @@ impl Tool for VersionCompareTool {
                 },
                 "required": ["version_a", "version_b"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -505,6 +667,85 @@ mod tests {
         assert!(output["result"].as_bool().unwrap());
     }
 
+    /// Run `compare` in a document mode and return its output.
+    async fn diff_documents(
+        mode: &str,
+        a: serde_json::Value,
+        b: serde_json::Value,
+    ) -> serde_json::Value {
+        let result = CompareTool::new()
+            .execute("test", json!({ "a": a, "b": b, "mode": mode }), &ToolContext::default())
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        result.output
+    }
+
+    #[tokio::test]
+    async fn test_compare_json_ignores_key_order() {
+        let a = r#"{"name": "app", "deps": {"serde": "1", "tokio": "1"}, "ports": [80, 443]}"#;
+        let b = r#"{
+            "ports": [80, 443],
+            "deps": {"tokio": "1", "serde": "1"},
+            "name": "app"
+        }"#;
+
+        let output = diff_documents("json", json!(a), json!(b)).await;
+
+        assert_eq!(output["equal"], json!(true));
+        assert_eq!(output["changed"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_compare_json_reports_nested_changes() {
+        let a = r#"{"server": {"port": 80, "tls": {"enabled": false}}, "tags": ["a", "b"]}"#;
+        let b = r#"{"server": {"port": 80, "tls": {"enabled": true}, "host": "x"}, "tags": ["a"]}"#;
+
+        let output = diff_documents("json", json!(a), json!(b)).await;
+
+        assert_eq!(output["equal"], json!(false));
+        assert_eq!(
+            output["changed"],
+            json!([{ "path": "$.server.tls.enabled", "from": false, "to": true }])
+        );
+        assert_eq!(output["added"], json!([{ "path": "$.server.host", "value": "x" }]));
+        assert_eq!(output["removed"], json!([{ "path": "$.tags[1]", "value": "b" }]));
+    }
+
+    #[tokio::test]
+    async fn test_compare_yaml_against_structured_value() {
+        let yaml = "name: app\nlimits:\n  cpu: 2\n  memory: 512\n";
+        let value = json!({"limits": {"memory": 512, "cpu": 2.0}, "name": "app"});
+
+        let output = diff_documents("yaml", json!(yaml), value).await;
+
+        assert_eq!(output["equal"], json!(true), "{}", output);
+        assert_eq!(output["mode"], json!("yaml"));
+    }
+
+    #[tokio::test]
+    async fn test_compare_documents_do_not_coerce_types() {
+        let output = diff_documents("json", json!({"n": 1, "v": null}), json!({"n": "1"})).await;
+
+        assert_eq!(output["equal"], json!(false));
+        assert_eq!(output["changed"], json!([{ "path": "$.n", "from": 1, "to": "1" }]));
+        assert_eq!(output["removed"], json!([{ "path": "$.v", "value": null }]));
+    }
+
+    #[tokio::test]
+    async fn test_compare_invalid_document() {
+        let result = CompareTool::new()
+            .execute(
+                "test",
+                json!({ "a": "{", "b": "{}", "mode": "json" }),
+                &ToolContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.output.as_str().unwrap().starts_with("Invalid JSON"));
+    }
+
     #[tokio::test]
     async fn test_assert_pass() {
         let tool = AssertTool::new();
@@ -159,6 +159,7 @@ impl Tool for ContextAddTool {
                 },
                 "required": ["content"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -275,6 +276,7 @@ impl Tool for ContextGetTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -370,6 +372,7 @@ impl Tool for ContextClearTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -40,6 +40,7 @@ impl Tool for SystemInfoTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -127,6 +128,7 @@ impl Tool for HealthCheckTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -245,6 +247,7 @@ impl Tool for DiagnosticTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -1,13 +1,15 @@
 //! Diff tools for comparing text and files.
 //!
 //! Provides tools for generating and viewing diffs between
-//! text content or files.
+//! text content or files. Diffs are computed per line (as a unified
+//! diff), per word or per character; binary content is only compared
+//! for equality.
 
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
-use similar::{ChangeTag, TextDiff};
+use similar::{ChangeTag, DiffTag, TextDiff};
 use std::path::PathBuf;
 use std::time::Instant;
 use tracing::debug;
@@ -36,7 +38,9 @@ impl Tool for DiffTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "diff".to_string(),
-            description: "Generate a diff between two pieces of text or files.".to_string(),
+            description: "Generate a line (unified), word or character diff between two pieces \
+                          of text or files, with counts of added, removed and changed units."
+                .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -56,13 +60,21 @@ impl Tool for DiffTool {
                         "type": "string",
                         "description": "Path to the new file (alternative to new_text)"
                     },
+                    "mode": {
+                        "type": "string",
+                        "enum": ["line", "word", "char"],
+                        "default": "line",
+                        "description": "Diff granularity: a unified line diff, or the new text \
+                                        with [-removed-] and {+added+} words or characters"
+                    },
                     "context_lines": {
                         "type": "integer",
                         "default": 3,
-                        "description": "Number of context lines around changes"
+                        "description": "Number of context lines around changes (line mode)"
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -75,99 +87,95 @@ impl Tool for DiffTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        // Get old content
-        let old_content = if let Some(old_text) = args.get("old_text").and_then(|v| v.as_str()) {
-            old_text.to_string()
-        } else if let Some(old_file) = args.get("old_file").and_then(|v| v.as_str()) {
-            let path = resolve_path(old_file, &ctx.cwd);
-            tokio::fs::read_to_string(&path)
-                .await
-                .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read old file: {}", e)))?
-        } else {
+        let Some(old) = read_side(&args, "old", ctx).await? else {
             return Ok(ToolResult::error(
                 tool_use_id,
                 "Either old_text or old_file must be provided",
             ));
         };
-
-        // Get new content
-        let new_content = if let Some(new_text) = args.get("new_text").and_then(|v| v.as_str()) {
-            new_text.to_string()
-        } else if let Some(new_file) = args.get("new_file").and_then(|v| v.as_str()) {
-            let path = resolve_path(new_file, &ctx.cwd);
-            tokio::fs::read_to_string(&path)
-                .await
-                .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read new file: {}", e)))?
-        } else {
+        let Some(new) = read_side(&args, "new", ctx).await? else {
             return Ok(ToolResult::error(
                 tool_use_id,
                 "Either new_text or new_file must be provided",
             ));
         };
 
+        let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("line");
+        let unit = match mode {
+            "line" => "line",
+            "word" => "word",
+            "char" => "char",
+            other => {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    format!("Unknown mode '{}'; expected line, word or char", other),
+                ));
+            }
+        };
+
         let context_lines = args
             .get("context_lines")
             .and_then(|v| v.as_u64())
             .unwrap_or(3) as usize;
 
-        // Generate diff
-        let diff = TextDiff::from_lines(&old_content, &new_content);
-
-        // Build unified diff output
-        let mut unified_diff = String::new();
-        let mut additions = 0;
-        let mut deletions = 0;
-        let mut changes: Vec<serde_json::Value> = Vec::new();
-
-        for (idx, group) in diff.grouped_ops(context_lines).iter().enumerate() {
-            if idx > 0 {
-                unified_diff.push_str("...\n");
+        let (old_text, new_text) = match (old.text(), new.text()) {
+            (Some(old_text), Some(new_text)) => (old_text, new_text),
+            _ => {
+                let differ = old.bytes != new.bytes;
+                debug!("Diff of binary content: differ={}", differ);
+                return Ok(ToolResult::success(
+                    tool_use_id,
+                    serde_json::json!({
+                        "binary": true,
+                        "diff": if differ { "Binary files differ" } else { "" },
+                        "mode": mode,
+                        "has_changes": differ,
+                    }),
+                )
+                .with_duration(start.elapsed()));
             }
+        };
 
-            for op in group {
-                for change in diff.iter_changes(op) {
-                    let tag = match change.tag() {
-                        ChangeTag::Delete => {
-                            deletions += 1;
-                            "-"
-                        }
-                        ChangeTag::Insert => {
-                            additions += 1;
-                            "+"
-                        }
-                        ChangeTag::Equal => " ",
-                    };
-
-                    let line_content = change.value();
-                    unified_diff.push_str(&format!("{}{}", tag, line_content));
-
-                    if change.tag() != ChangeTag::Equal {
-                        changes.push(serde_json::json!({
-                            "type": if change.tag() == ChangeTag::Insert { "add" } else { "delete" },
-                            "old_line": change.old_index().map(|i| i + 1),
-                            "new_line": change.new_index().map(|i| i + 1),
-                            "content": line_content.trim_end(),
-                        }));
-                    }
-                }
-            }
-        }
+        let diff = match mode {
+            "word" => TextDiff::from_words(old_text, new_text),
+            "char" => TextDiff::from_chars(old_text, new_text),
+            _ => TextDiff::from_lines(old_text, new_text),
+        };
+        let stats = DiffStats::of(&diff, mode == "word");
+
+        let (output, changes) = if mode == "line" {
+            let unified = diff
+                .unified_diff()
+                .context_radius(context_lines)
+                .header(&old.label, &new.label)
+                .to_string();
+            (unified, line_changes(&diff))
+        } else {
+            inline_diff(&diff)
+        };
 
         let duration = start.elapsed();
 
         debug!(
-            "Generated diff: {} additions, {} deletions",
-            additions, deletions
+            "Generated {} diff: {} added, {} removed, {} changed",
+            mode, stats.added, stats.removed, stats.changed
         );
 
         Ok(ToolResult::success(
             tool_use_id,
             serde_json::json!({
-                "diff": unified_diff,
-                "additions": additions,
-                "deletions": deletions,
+                "diff": output,
+                "mode": mode,
+                "additions": stats.added + stats.changed,
+                "deletions": stats.removed + stats.changed,
+                "stats": {
+                    "added": stats.added,
+                    "removed": stats.removed,
+                    "changed": stats.changed,
+                    "unit": unit,
+                },
                 "changes": changes,
-                "has_changes": additions > 0 || deletions > 0,
+                "has_changes": stats.total() > 0,
             }),
         )
         .with_duration(duration))
@@ -230,6 +238,7 @@ impl Tool for PatchTool {
                 },
                 "required": ["search", "replace"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -332,6 +341,151 @@ impl Tool for PatchTool {
     }
 }
 
+/// Bytes scanned for a NUL byte when deciding whether content is binary.
+const BINARY_SNIFF_LEN: usize = 8000;
+
+/// One side of a diff.
+struct Side {
+    bytes: Vec<u8>,
+    /// Name shown in the unified diff header.
+    label: String,
+}
+
+impl Side {
+    /// The content as text, or `None` if it looks binary.
+    fn text(&self) -> Option<&str> {
+        let sniff = &self.bytes[..self.bytes.len().min(BINARY_SNIFF_LEN)];
+        if sniff.contains(&0) {
+            return None;
+        }
+        std::str::from_utf8(&self.bytes).ok()
+    }
+}
+
+/// Read the `{side}_text` or `{side}_file` argument.
+async fn read_side(
+    args: &serde_json::Value,
+    side: &str,
+    ctx: &ToolContext,
+) -> Result<Option<Side>> {
+    if let Some(text) = args.get(format!("{}_text", side)).and_then(|v| v.as_str()) {
+        return Ok(Some(Side {
+            bytes: text.as_bytes().to_vec(),
+            label: side.to_string(),
+        }));
+    }
+    let Some(file) = args.get(format!("{}_file", side)).and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let bytes = tokio::fs::read(resolve_path(file, &ctx.cwd)).await.map_err(|e| {
+        crate::error::AgentError::tool_execution(format!("Failed to read {} file: {}", side, e))
+    })?;
+    Ok(Some(Side {
+        bytes,
+        label: file.to_string(),
+    }))
+}
+
+/// Counts of the units (lines, words or characters) a diff touches.
+///
+/// A run of removed units next to a run of added ones counts as changed
+/// units, pairwise, with the surplus counted as added or removed.
+#[derive(Debug, Default, PartialEq)]
+struct DiffStats {
+    added: usize,
+    removed: usize,
+    changed: usize,
+}
+
+impl DiffStats {
+    /// Count the changes in `diff`, ignoring whitespace tokens if `words`.
+    fn of<'a>(diff: &TextDiff<'a, 'a, '_, str>, words: bool) -> Self {
+        let mut stats = Self::default();
+        let (mut run_removed, mut run_added) = (0, 0);
+
+        for op in diff.ops() {
+            if op.tag() == DiffTag::Equal {
+                stats.flush(&mut run_removed, &mut run_added);
+                continue;
+            }
+            for change in diff.iter_changes(op) {
+                if words && change.value().trim().is_empty() {
+                    continue;
+                }
+                match change.tag() {
+                    ChangeTag::Delete => run_removed += 1,
+                    ChangeTag::Insert => run_added += 1,
+                    ChangeTag::Equal => {}
+                }
+            }
+        }
+        stats.flush(&mut run_removed, &mut run_added);
+        stats
+    }
+
+    fn flush(&mut self, removed: &mut usize, added: &mut usize) {
+        let changed = (*removed).min(*added);
+        self.changed += changed;
+        self.removed += *removed - changed;
+        self.added += *added - changed;
+        *removed = 0;
+        *added = 0;
+    }
+
+    fn total(&self) -> usize {
+        self.added + self.removed + self.changed
+    }
+}
+
+/// Added and deleted lines with their line numbers.
+fn line_changes<'a>(diff: &TextDiff<'a, 'a, '_, str>) -> Vec<serde_json::Value> {
+    diff.iter_all_changes()
+        .filter(|change| change.tag() != ChangeTag::Equal)
+        .map(|change| {
+            serde_json::json!({
+                "type": if change.tag() == ChangeTag::Insert { "add" } else { "delete" },
+                "old_line": change.old_index().map(|i| i + 1),
+                "new_line": change.new_index().map(|i| i + 1),
+                "content": change.value().trim_end(),
+            })
+        })
+        .collect()
+}
+
+/// The new text with removals marked `[-...-]` and insertions `{+...+}`,
+/// and the marked runs.
+fn inline_diff<'a>(diff: &TextDiff<'a, 'a, '_, str>) -> (String, Vec<serde_json::Value>) {
+    let mut output = String::new();
+    let mut runs: Vec<(ChangeTag, String)> = Vec::new();
+
+    for change in diff.iter_all_changes() {
+        match runs.last_mut() {
+            Some((tag, text)) if *tag == change.tag() => text.push_str(change.value()),
+            _ => runs.push((change.tag(), change.value().to_string())),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (tag, text) in runs {
+        let kind = match tag {
+            ChangeTag::Equal => {
+                output.push_str(&text);
+                continue;
+            }
+            ChangeTag::Delete => {
+                output.push_str(&format!("[-{}-]", text));
+                "delete"
+            }
+            ChangeTag::Insert => {
+                output.push_str(&format!("{{+{}+}}", text));
+                "add"
+            }
+        };
+        changes.push(serde_json::json!({"type": kind, "content": text}));
+    }
+    (output, changes)
+}
+
 /// Resolve a path relative to the working directory.
 fn resolve_path(path: &str, cwd: &std::path::Path) -> PathBuf {
     let p = std::path::Path::new(path);
@@ -400,6 +554,129 @@ mod tests {
         assert!(!result.output.get("has_changes").and_then(|v| v.as_bool()).unwrap_or(true));
     }
 
+    #[tokio::test]
+    async fn test_unified_diff_context() {
+        let tool = DiffTool::new();
+        let ctx = ToolContext::default();
+        let old: String = (1..=10).map(|i| format!("line{}\n", i)).collect();
+        let new = old.replace("line5\n", "five\n");
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"old_text": old, "new_text": new, "context_lines": 1}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.output["diff"],
+            "--- old\n+++ new\n@@ -4,3 +4,3 @@\n line4\n-line5\n+five\n line6\n"
+        );
+        assert_eq!(result.output["stats"]["changed"], 1);
+        assert_eq!(result.output["stats"]["added"], 0);
+        assert_eq!(result.output["stats"]["removed"], 0);
+        assert_eq!(result.output["changes"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_word_diff_of_single_word() {
+        let tool = DiffTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({
+                    "old_text": "the quick brown fox jumps",
+                    "new_text": "the quick red fox jumps",
+                    "mode": "word"
+                }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output["diff"], "the quick [-brown-]{+red+} fox jumps");
+        assert_eq!(
+            result.output["stats"],
+            serde_json::json!({"added": 0, "removed": 0, "changed": 1, "unit": "word"})
+        );
+        assert_eq!(result.output["changes"][0]["content"], "brown");
+        assert_eq!(result.output["changes"][1]["content"], "red");
+    }
+
+    #[tokio::test]
+    async fn test_char_diff() {
+        let tool = DiffTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"old_text": "colour", "new_text": "color!", "mode": "char"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output["diff"], "colo[-u-]r{+!+}");
+        assert_eq!(result.output["stats"]["added"], 1);
+        assert_eq!(result.output["stats"]["removed"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_binary_files_differ() {
+        let tool = DiffTool::new();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), [0x89, b'P', b'N', b'G', 0, 1, 2]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), [0x89, b'P', b'N', b'G', 0, 1, 3]).unwrap();
+        let ctx = ToolContext {
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"old_file": "a.bin", "new_file": "b.bin"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.output["binary"], true);
+        assert_eq!(result.output["diff"], "Binary files differ");
+        assert_eq!(result.output["has_changes"], true);
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"old_file": "a.bin", "new_file": "a.bin"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["has_changes"], false);
+    }
+
+    #[tokio::test]
+    async fn test_diff_unknown_mode() {
+        let tool = DiffTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"old_text": "a", "new_text": "b", "mode": "sentence"}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
     #[tokio::test]
     async fn test_patch_preview() {
         let tool = PatchTool::new();
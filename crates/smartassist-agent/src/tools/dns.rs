@@ -0,0 +1,492 @@
+//! Minimal DNS client used by the `dns_lookup` tool.
+//!
+//! Sends one query to a recursive resolver over UDP, retrying over TCP if
+//! the answer is truncated. Queries set the EDNS `DO` bit and the `AD` bit,
+//! so a validating resolver reports whether it authenticated the answer
+//! with DNSSEC. That report is only as trustworthy as the path to the
+//! resolver; the answer itself is not validated here.
+
+use serde::Serialize;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+/// UDP payload size advertised with EDNS.
+const EDNS_PAYLOAD_SIZE: u16 = 1232;
+
+/// Port resolvers listen on unless told otherwise.
+const DNS_PORT: u16 = 53;
+
+/// Header flag bits.
+const FLAG_QR: u16 = 0x8000;
+const FLAG_TC: u16 = 0x0200;
+const FLAG_RD: u16 = 0x0100;
+const FLAG_AD: u16 = 0x0020;
+
+/// Record types the lookup understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RecordType {
+    A,
+    Aaaa,
+    Mx,
+    Txt,
+    Cname,
+    Ns,
+    Soa,
+}
+
+impl RecordType {
+    /// All supported types, in the order they're documented.
+    pub(crate) const ALL: [RecordType; 7] = [
+        Self::A,
+        Self::Aaaa,
+        Self::Mx,
+        Self::Txt,
+        Self::Cname,
+        Self::Ns,
+        Self::Soa,
+    ];
+
+    /// Parse a type name such as `"MX"`, ignoring case.
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str().eq_ignore_ascii_case(name))
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+            Self::Mx => "MX",
+            Self::Txt => "TXT",
+            Self::Cname => "CNAME",
+            Self::Ns => "NS",
+            Self::Soa => "SOA",
+        }
+    }
+
+    fn code(self) -> u16 {
+        match self {
+            Self::A => 1,
+            Self::Ns => 2,
+            Self::Cname => 5,
+            Self::Soa => 6,
+            Self::Mx => 15,
+            Self::Txt => 16,
+            Self::Aaaa => 28,
+        }
+    }
+
+    fn from_code(code: u16) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.code() == code)
+    }
+}
+
+/// One record from the answer section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct DnsRecord {
+    /// Owner name, without the trailing dot.
+    pub name: String,
+
+    /// Record type name.
+    #[serde(rename = "type")]
+    pub record_type: &'static str,
+
+    /// Time to live in seconds.
+    pub ttl: u32,
+
+    /// Record data in presentation form, e.g. `10 mail.example.com` for MX.
+    pub value: String,
+}
+
+/// A resolver's answer to one query.
+#[derive(Debug, Clone)]
+pub(crate) struct DnsAnswer {
+    /// Response code; 0 is success, 3 is a name that doesn't exist.
+    pub rcode: u8,
+
+    /// Whether the resolver set the `AD` (authenticated data) flag.
+    pub authenticated: bool,
+
+    /// Answer records of supported types, including CNAMEs the resolver
+    /// followed.
+    pub records: Vec<DnsRecord>,
+}
+
+impl DnsAnswer {
+    /// Describe a failing response code.
+    pub(crate) fn rcode_error(&self) -> Option<&'static str> {
+        match self.rcode {
+            0 => None,
+            1 => Some("the resolver rejected the query as malformed"),
+            2 => Some("the resolver failed (SERVFAIL), possibly a DNSSEC validation failure"),
+            3 => Some("the name does not exist (NXDOMAIN)"),
+            5 => Some("the resolver refused the query"),
+            _ => Some("the resolver returned an error"),
+        }
+    }
+}
+
+/// Parse a resolver address: an IP, optionally with a port
+/// (`192.0.2.1`, `192.0.2.1:5353`, `2001:db8::1`, `[2001:db8::1]:5353`).
+pub(crate) fn parse_resolver(address: &str) -> Option<SocketAddr> {
+    if let Ok(addr) = address.parse::<SocketAddr>() {
+        return Some(addr);
+    }
+    let ip = address.trim_start_matches('[').trim_end_matches(']');
+    ip.parse().ok().map(|ip| SocketAddr::new(ip, DNS_PORT))
+}
+
+/// First nameserver in `/etc/resolv.conf`, if any.
+pub(crate) fn system_resolver() -> Option<SocketAddr> {
+    let conf = std::fs::read_to_string("/etc/resolv.conf").ok()?;
+    conf.lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .find_map(|rest| {
+            // Drop an IPv6 zone index, which SocketAddr can't represent.
+            let ip = rest.trim().split('%').next()?;
+            parse_resolver(ip)
+        })
+}
+
+/// Ask `server` for `name`'s records of type `record_type`.
+pub(crate) async fn query(
+    server: SocketAddr,
+    name: &str,
+    record_type: RecordType,
+    wait: Duration,
+) -> Result<DnsAnswer, String> {
+    let id: u16 = rand::random();
+    let message = build_query(id, name, record_type)?;
+
+    let udp = async {
+        let response = query_udp(server, &message, id).await?;
+        if read_u16(&response, 2)? & FLAG_TC != 0 {
+            return Ok::<_, String>(None);
+        }
+        parse_response(&response, id).map(Some)
+    };
+    let answer = timeout(wait, udp)
+        .await
+        .map_err(|_| format!("no answer from {} within {:?}", server, wait))??;
+    if let Some(answer) = answer {
+        return Ok(answer);
+    }
+
+    let tcp = async {
+        let response = query_tcp(server, &message).await?;
+        parse_response(&response, id)
+    };
+    timeout(wait, tcp)
+        .await
+        .map_err(|_| format!("no answer from {} over TCP within {:?}", server, wait))?
+}
+
+async fn query_udp(server: SocketAddr, message: &[u8], id: u16) -> Result<Vec<u8>, String> {
+    let bind: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await.map_err(|e| e.to_string())?;
+    socket.connect(server).await.map_err(|e| e.to_string())?;
+    socket.send(message).await.map_err(|e| e.to_string())?;
+
+    let mut buf = vec![0u8; 65535];
+    loop {
+        let len = socket.recv(&mut buf).await.map_err(|e| e.to_string())?;
+        // Ignore stray datagrams that don't answer this query.
+        if len >= 12 && u16::from_be_bytes([buf[0], buf[1]]) == id {
+            buf.truncate(len);
+            return Ok(buf);
+        }
+    }
+}
+
+async fn query_tcp(server: SocketAddr, message: &[u8]) -> Result<Vec<u8>, String> {
+    let mut stream = TcpStream::connect(server).await.map_err(|e| e.to_string())?;
+    let mut framed = (message.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(message);
+    stream.write_all(&framed).await.map_err(|e| e.to_string())?;
+
+    let len = stream.read_u16().await.map_err(|e| e.to_string())?;
+    let mut response = vec![0u8; len as usize];
+    stream.read_exact(&mut response).await.map_err(|e| e.to_string())?;
+    Ok(response)
+}
+
+/// Encode a query with one question and an EDNS OPT record.
+fn build_query(id: u16, name: &str, record_type: RecordType) -> Result<Vec<u8>, String> {
+    let mut message = Vec::with_capacity(64);
+    message.extend_from_slice(&id.to_be_bytes());
+    message.extend_from_slice(&(FLAG_RD | FLAG_AD).to_be_bytes());
+    // One question, no answer or authority records, one additional (OPT).
+    for count in [1u16, 0, 0, 1] {
+        message.extend_from_slice(&count.to_be_bytes());
+    }
+
+    let name = name.trim_end_matches('.');
+    if name.len() > 253 {
+        return Err(format!("name is too long: {}", name));
+    }
+    for label in name.split('.').filter(|label| !name.is_empty() || !label.is_empty()) {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("invalid label '{}' in {}", label, name));
+        }
+        message.push(label.len() as u8);
+        message.extend_from_slice(label.as_bytes());
+    }
+    message.push(0);
+    message.extend_from_slice(&record_type.code().to_be_bytes());
+    message.extend_from_slice(&1u16.to_be_bytes());
+
+    // OPT: root name, type 41, class = payload size, TTL carries the DO bit.
+    message.push(0);
+    message.extend_from_slice(&41u16.to_be_bytes());
+    message.extend_from_slice(&EDNS_PAYLOAD_SIZE.to_be_bytes());
+    message.extend_from_slice(&[0, 0, 0x80, 0]);
+    message.extend_from_slice(&0u16.to_be_bytes());
+    Ok(message)
+}
+
+/// Decode a response to the query with `id`.
+fn parse_response(message: &[u8], id: u16) -> Result<DnsAnswer, String> {
+    if read_u16(message, 0)? != id {
+        return Err("response does not match the query".to_string());
+    }
+    let flags = read_u16(message, 2)?;
+    if flags & FLAG_QR == 0 {
+        return Err("resolver sent a query instead of a response".to_string());
+    }
+    let questions = read_u16(message, 4)?;
+    let answers = read_u16(message, 6)?;
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        read_name(message, &mut pos)?;
+        pos += 4;
+    }
+
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        let name = read_name(message, &mut pos)?;
+        let code = read_u16(message, pos)?;
+        let ttl = read_u32(message, pos + 4)?;
+        let len = read_u16(message, pos + 8)? as usize;
+        pos += 10;
+        let end = pos + len;
+        if end > message.len() {
+            return Err("truncated record data".to_string());
+        }
+        // Skip types we don't present, such as RRSIG.
+        if let Some(record_type) = RecordType::from_code(code) {
+            let value = read_rdata(message, pos, end, record_type)?;
+            records.push(DnsRecord {
+                name,
+                record_type: record_type.as_str(),
+                ttl,
+                value,
+            });
+        }
+        pos = end;
+    }
+
+    Ok(DnsAnswer {
+        rcode: (flags & 0x000f) as u8,
+        authenticated: flags & FLAG_AD != 0,
+        records,
+    })
+}
+
+/// Render record data at `message[start..end]` in presentation form.
+fn read_rdata(
+    message: &[u8],
+    start: usize,
+    end: usize,
+    record_type: RecordType,
+) -> Result<String, String> {
+    let data = &message[start..end];
+    let mut pos = start;
+    let value = match record_type {
+        RecordType::A => {
+            let octets: [u8; 4] = data.try_into().map_err(|_| "bad A record length")?;
+            Ipv4Addr::from(octets).to_string()
+        }
+        RecordType::Aaaa => {
+            let octets: [u8; 16] = data.try_into().map_err(|_| "bad AAAA record length")?;
+            Ipv6Addr::from(octets).to_string()
+        }
+        RecordType::Cname | RecordType::Ns => read_name(message, &mut pos)?,
+        RecordType::Mx => {
+            let preference = read_u16(message, pos)?;
+            pos += 2;
+            format!("{} {}", preference, read_name(message, &mut pos)?)
+        }
+        RecordType::Txt => {
+            // One or more length-prefixed strings, joined as one value.
+            let mut text = Vec::new();
+            let mut i = 0;
+            while i < data.len() {
+                let len = data[i] as usize;
+                let chunk = data.get(i + 1..i + 1 + len).ok_or("truncated TXT string")?;
+                text.extend_from_slice(chunk);
+                i += 1 + len;
+            }
+            String::from_utf8_lossy(&text).into_owned()
+        }
+        RecordType::Soa => {
+            let primary = read_name(message, &mut pos)?;
+            let mailbox = read_name(message, &mut pos)?;
+            let mut numbers = Vec::with_capacity(5);
+            for i in 0..5 {
+                numbers.push(read_u32(message, pos + i * 4)?.to_string());
+            }
+            format!("{} {} {}", primary, mailbox, numbers.join(" "))
+        }
+    };
+    Ok(value)
+}
+
+/// Longest name on the wire, in bytes.
+const MAX_NAME_LENGTH: usize = 255;
+
+/// Read a possibly compressed name at `*pos`, advancing past it.
+fn read_name(message: &[u8], pos: &mut usize) -> Result<String, String> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut cursor = *pos;
+    let mut jumped = false;
+    let mut length = 1;
+
+    // Each pointer must land before where the previous one did, so the
+    // targets only ever decrease and the loop ends.
+    let mut limit = *pos;
+    loop {
+        let len = *message.get(cursor).ok_or("truncated name")? as usize;
+        if len & 0xc0 == 0xc0 {
+            let target = (read_u16(message, cursor)? & 0x3fff) as usize;
+            if target >= limit {
+                return Err("invalid name compression pointer".to_string());
+            }
+            limit = target;
+            if !jumped {
+                *pos = cursor + 2;
+                jumped = true;
+            }
+            cursor = target;
+            continue;
+        }
+        if len == 0 {
+            if !jumped {
+                *pos = cursor + 1;
+            }
+            break;
+        }
+        length += 1 + len;
+        if length > MAX_NAME_LENGTH {
+            return Err("name is too long".to_string());
+        }
+        let label = message
+            .get(cursor + 1..cursor + 1 + len)
+            .ok_or("truncated name")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        cursor += 1 + len;
+    }
+    Ok(labels.join("."))
+}
+
+fn read_u16(message: &[u8], pos: usize) -> Result<u16, String> {
+    match message.get(pos..pos + 2) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err("truncated DNS message".to_string()),
+    }
+}
+
+fn read_u32(message: &[u8], pos: usize) -> Result<u32, String> {
+    match message.get(pos..pos + 4) {
+        Some(bytes) => Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err("truncated DNS message".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_query_sets_dnssec_bits() {
+        let query = build_query(0xabcd, "example.com.", RecordType::Mx).unwrap();
+        assert_eq!(&query[..4], &[0xab, 0xcd, 0x01, 0x20]);
+        assert_eq!(&query[12..25], b"\x07example\x03com\x00");
+        assert_eq!(&query[25..29], &[0, 15, 0, 1]);
+        // OPT record with the DO bit.
+        assert_eq!(&query[29..], &[0, 0, 41, 0x04, 0xd0, 0, 0, 0x80, 0, 0, 0]);
+
+        assert!(build_query(1, "a..b", RecordType::A).is_err());
+        assert!(build_query(1, &"x".repeat(64), RecordType::A).is_err());
+    }
+
+    #[test]
+    fn test_parse_compressed_soa() {
+        let mut message = vec![0x12, 0x34, 0x81, 0xa0, 0, 1, 0, 1, 0, 0, 0, 0];
+        message.extend_from_slice(b"\x07example\x03com\x00\x00\x06\x00\x01");
+        // Answer: name -> offset 12, SOA, TTL 60.
+        message.extend_from_slice(&[0xc0, 12, 0, 6, 0, 1, 0, 0, 0, 60]);
+        let mut rdata = b"\x02ns\xc0\x0c\x0ahostmaster\xc0\x0c".to_vec();
+        for n in [2024010101u32, 7200, 3600, 1209600, 300] {
+            rdata.extend_from_slice(&n.to_be_bytes());
+        }
+        message.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        message.extend_from_slice(&rdata);
+
+        let answer = parse_response(&message, 0x1234).unwrap();
+        assert!(answer.authenticated);
+        assert_eq!(answer.rcode_error(), None);
+        assert_eq!(
+            answer.records,
+            vec![DnsRecord {
+                name: "example.com".to_string(),
+                record_type: "SOA",
+                ttl: 60,
+                value: "ns.example.com hostmaster.example.com 2024010101 7200 3600 1209600 300"
+                    .to_string(),
+            }]
+        );
+
+        assert!(parse_response(&message, 0x9999).is_err());
+        assert!(parse_response(&message[..message.len() - 3], 0x1234).is_err());
+    }
+
+    #[test]
+    fn test_looping_compression_pointer_is_rejected() {
+        // A label followed by a pointer back to the label itself.
+        let mut message = vec![0; 12];
+        message.extend_from_slice(b"\x01a\xc0\x0c");
+        let mut pos = 12;
+        assert!(read_name(&message, &mut pos).is_err());
+
+        // Two names pointing at each other.
+        let mut message = vec![0; 12];
+        message.extend_from_slice(b"\x01a\xc0\x10\x01b\xc0\x0c");
+        let mut pos = 16;
+        assert!(read_name(&message, &mut pos).is_err());
+
+        // A chain of backward pointers is still fine.
+        let mut message = vec![0; 12];
+        message.extend_from_slice(b"\x03com\x00\x07example\xc0\x0c\x03www\xc0\x11");
+        let mut pos = 27;
+        assert_eq!(read_name(&message, &mut pos).unwrap(), "www.example.com");
+        assert_eq!(pos, 33);
+    }
+
+    #[test]
+    fn test_parse_resolver() {
+        assert_eq!(parse_resolver("192.0.2.1"), Some("192.0.2.1:53".parse().unwrap()));
+        assert_eq!(parse_resolver("192.0.2.1:5353"), Some("192.0.2.1:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("2001:db8::1"), Some("[2001:db8::1]:53".parse().unwrap()));
+        assert_eq!(parse_resolver("[::1]:5353"), Some("[::1]:5353".parse().unwrap()));
+        assert_eq!(parse_resolver("dns.example"), None);
+    }
+}
@@ -1,8 +1,10 @@
 //! Encoding and hashing tools.
 //!
 //! Provides tools for encoding/decoding data (base64, hex)
-//! and computing hashes (MD5, SHA256).
+//! and computing hashes (SHA-256/512, BLAKE3, and the non-cryptographic
+//! MD5 and SHA-1).
 
+use super::checksum::{hash_bytes, hash_file, HashAlgorithm};
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
@@ -56,7 +58,11 @@ impl Tool for Base64Tool {
                 },
                 "required": ["input"]
             }),
-            execution: ToolExecutionConfig::default(),
+            output_schema: None,
+            execution: ToolExecutionConfig {
+                cacheable: true,
+                ..Default::default()
+            },
         }
     }
 
@@ -185,7 +191,11 @@ impl Tool for HexTool {
                 },
                 "required": ["input"]
             }),
-            execution: ToolExecutionConfig::default(),
+            output_schema: None,
+            execution: ToolExecutionConfig {
+                cacheable: true,
+                ..Default::default()
+            },
         }
     }
 
@@ -285,7 +295,9 @@ impl Tool for HashTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "hash".to_string(),
-            description: "Compute cryptographic hash of input data.".to_string(),
+            description: "Compute the hash of a string or file. Files are streamed, so any size \
+                          works. md5 and sha1 are not cryptographically secure."
+                .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -295,7 +307,7 @@ impl Tool for HashTool {
                     },
                     "algorithm": {
                         "type": "string",
-                        "enum": ["md5", "sha1", "sha256", "sha512"],
+                        "enum": HashAlgorithm::NAMES,
                         "default": "sha256",
                         "description": "Hash algorithm to use"
                     },
@@ -305,6 +317,8 @@ impl Tool for HashTool {
                     }
                 }
             }),
+            output_schema: None,
+            // Not cacheable: a file's contents can change between calls.
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -315,19 +329,19 @@ impl Tool for HashTool {
         args: serde_json::Value,
         ctx: &ToolContext,
     ) -> Result<ToolResult> {
-        use sha2::{Sha256, Sha512, Digest};
-        use sha1::Sha1;
-
         let start = Instant::now();
 
         let algorithm = args
             .get("algorithm")
             .and_then(|v| v.as_str())
             .unwrap_or("sha256");
+        let Some(hash_algorithm) = HashAlgorithm::parse(algorithm) else {
+            return Ok(ToolResult::error(tool_use_id, HashAlgorithm::unknown(algorithm)));
+        };
 
-        // Get content either from input or file
-        let content = if let Some(input) = args.get("input").and_then(|v| v.as_str()) {
-            input.as_bytes().to_vec()
+        // Hash either the input or the file, streamed from disk
+        let (hash, input_bytes) = if let Some(input) = args.get("input").and_then(|v| v.as_str()) {
+            (hash_bytes(hash_algorithm, input.as_bytes()), input.len() as u64)
         } else if let Some(file) = args.get("file").and_then(|v| v.as_str()) {
             let path = if std::path::Path::new(file).is_absolute() {
                 std::path::PathBuf::from(file)
@@ -335,7 +349,7 @@ impl Tool for HashTool {
                 ctx.cwd.join(file)
             };
 
-            tokio::fs::read(&path)
+            hash_file(hash_algorithm, &path)
                 .await
                 .map_err(|e| crate::error::AgentError::tool_execution(format!("Failed to read file: {}", e)))?
         } else {
@@ -345,34 +359,6 @@ impl Tool for HashTool {
             ));
         };
 
-        let hash = match algorithm {
-            "md5" => {
-                let digest = md5::compute(&content);
-                hex::encode(digest.0)
-            }
-            "sha1" => {
-                let mut hasher = Sha1::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha256" => {
-                let mut hasher = Sha256::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            "sha512" => {
-                let mut hasher = Sha512::new();
-                hasher.update(&content);
-                hex::encode(hasher.finalize())
-            }
-            _ => {
-                return Ok(ToolResult::error(
-                    tool_use_id,
-                    format!("Unknown algorithm: {}", algorithm),
-                ));
-            }
-        };
-
         let duration = start.elapsed();
 
         debug!("Hash {}: {} chars", algorithm, hash.len());
@@ -382,7 +368,9 @@ impl Tool for HashTool {
             serde_json::json!({
                 "hash": hash,
                 "algorithm": algorithm,
-                "input_bytes": content.len(),
+                "input_bytes": input_bytes,
+                "cryptographic": hash_algorithm.is_cryptographic(),
+                "warning": hash_algorithm.warning(),
             }),
         )
         .with_duration(duration))
@@ -434,7 +422,11 @@ impl Tool for UrlEncodeTool {
                 },
                 "required": ["input"]
             }),
-            execution: ToolExecutionConfig::default(),
+            output_schema: None,
+            execution: ToolExecutionConfig {
+                cacheable: true,
+                ..Default::default()
+            },
         }
     }
 
@@ -520,6 +512,7 @@ mod tests {
     fn test_hash_tool_creation() {
         let tool = HashTool::new();
         assert_eq!(tool.name(), "hash");
+        assert!(!tool.definition().execution.cacheable);
     }
 
     #[test]
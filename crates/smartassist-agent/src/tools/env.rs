@@ -55,6 +55,7 @@ impl Tool for EnvGetTool {
                 },
                 "required": ["name"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -72,10 +73,9 @@ impl Tool for EnvGetTool {
         let start = Instant::now();
         let args: EnvGetArgs = serde_json::from_value(args)?;
 
-        // First check the context env, then fall back to system env
+        // Only the context env is visible; it has already been filtered
         let value = context.env.get(&args.name)
             .cloned()
-            .or_else(|| std::env::var(&args.name).ok())
             .or(args.default);
 
         match value {
@@ -147,6 +147,7 @@ impl Tool for EnvListTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -165,18 +166,10 @@ impl Tool for EnvListTool {
         let args: EnvListArgs = serde_json::from_value(args)?;
         let include_values = args.include_values.unwrap_or(false);
 
-        // Collect from both context and system env
         let mut vars: Vec<_> = context.env.iter()
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
-        // Add system env vars not in context
-        for (k, v) in std::env::vars() {
-            if !context.env.contains_key(&k) {
-                vars.push((k, v));
-            }
-        }
-
         // Filter by prefix if provided
         if let Some(ref prefix) = args.prefix {
             vars.retain(|(k, _)| k.starts_with(prefix));
@@ -246,6 +239,7 @@ impl Tool for EnvCheckTool {
                 },
                 "required": ["names"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -265,7 +259,7 @@ impl Tool for EnvCheckTool {
 
         let results: Vec<_> = args.names.iter()
             .map(|name| {
-                let exists = context.env.contains_key(name) || std::env::var(name).is_ok();
+                let exists = context.env.contains_key(name);
                 json!({
                     "name": name,
                     "exists": exists
@@ -0,0 +1,158 @@
+//! Environment variable filtering for tool contexts.
+//!
+//! Tools run commands on the host, so the variables they see are limited to
+//! an allowlist. A denylist of name patterns is applied on top and always
+//! wins, so credentials such as `GITHUB_TOKEN` stay out even when the
+//! allowlist is broad.
+
+use std::collections::HashMap;
+
+/// Variables passed to tools by default.
+const DEFAULT_ALLOW: &[&str] = &["PATH", "HOME", "LANG"];
+
+/// Name patterns that are never passed to tools by default.
+const DEFAULT_DENY: &[&str] = &["*_TOKEN", "*_KEY", "*_SECRET", "*_PASSWORD"];
+
+/// Allowlist and denylist of environment variable names.
+///
+/// Patterns match whole names; `*` matches any run of characters. Deny
+/// patterns ignore case, so `*_TOKEN` also keeps out `github_token`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvFilter {
+    /// Patterns a variable must match to be passed.
+    allow: Vec<String>,
+
+    /// Patterns that exclude a variable even if allowed, in upper case.
+    deny: Vec<String>,
+}
+
+impl Default for EnvFilter {
+    fn default() -> Self {
+        Self::new(DEFAULT_ALLOW.iter().copied(), DEFAULT_DENY.iter().copied())
+    }
+}
+
+impl EnvFilter {
+    /// Create a filter from allow and deny patterns.
+    pub fn new(
+        allow: impl IntoIterator<Item = impl Into<String>>,
+        deny: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            allow: allow.into_iter().map(Into::into).collect(),
+            deny: deny.into_iter().map(|p| p.into().to_ascii_uppercase()).collect(),
+        }
+    }
+
+    /// Allow every variable except those matching the default denylist.
+    pub fn allow_all() -> Self {
+        Self::new(["*"], DEFAULT_DENY.iter().copied())
+    }
+
+    /// Add an allow pattern.
+    pub fn allow(mut self, pattern: impl Into<String>) -> Self {
+        self.allow.push(pattern.into());
+        self
+    }
+
+    /// Add a deny pattern.
+    pub fn deny(mut self, pattern: impl Into<String>) -> Self {
+        self.deny.push(pattern.into().to_ascii_uppercase());
+        self
+    }
+
+    /// Check whether a variable may be passed to tools.
+    pub fn permits(&self, name: &str) -> bool {
+        let upper = name.to_ascii_uppercase();
+        self.allow.iter().any(|p| glob_match(p, name))
+            && !self.deny.iter().any(|p| glob_match(p, &upper))
+    }
+
+    /// Keep only the permitted variables.
+    pub fn apply(&self, vars: impl IntoIterator<Item = (String, String)>) -> HashMap<String, String> {
+        vars.into_iter()
+            .filter(|(name, _)| self.permits(name))
+            .collect()
+    }
+
+    /// Get the permitted variables of the current process.
+    pub fn process_env(&self) -> HashMap<String, String> {
+        self.apply(std::env::vars())
+    }
+}
+
+/// Match a name against a pattern where `*` matches any run of characters.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard: the whole name must match.
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("PATH", "PATH"));
+        assert!(!glob_match("PATH", "PATHEXT"));
+        assert!(glob_match("*_TOKEN", "GITHUB_TOKEN"));
+        assert!(!glob_match("*_TOKEN", "TOKEN"));
+        assert!(glob_match("LC_*", "LC_ALL"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("A*B*C", "AxxBxxC"));
+        assert!(!glob_match("A*B*C", "AxxCxxB"));
+    }
+
+    #[test]
+    fn test_default_filter() {
+        let filter = EnvFilter::default();
+        assert!(filter.permits("PATH"));
+        assert!(filter.permits("HOME"));
+        assert!(!filter.permits("SHELL"));
+        assert!(!filter.permits("AWS_SECRET"));
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow() {
+        let filter = EnvFilter::allow_all().deny("DATABASE_URL");
+        let vars = [
+            ("EDITOR", "vim"),
+            ("OPENAI_API_KEY", "sk-test"),
+            ("SLACK_BOT_TOKEN", "xoxb"),
+            ("DATABASE_URL", "postgres://"),
+        ];
+
+        let env = filter.apply(vars.map(|(k, v)| (k.to_string(), v.to_string())));
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["EDITOR"], "vim");
+    }
+
+    #[test]
+    fn test_deny_ignores_case() {
+        let filter = EnvFilter::allow_all().deny("database_url");
+        assert!(!filter.permits("github_token"));
+        assert!(!filter.permits("Aws_Secret"));
+        assert!(!filter.permits("DATABASE_URL"));
+        assert!(filter.permits("editor"));
+
+        // Allow patterns still match case exactly.
+        assert!(!EnvFilter::default().permits("path"));
+    }
+}
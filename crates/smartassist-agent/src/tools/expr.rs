@@ -0,0 +1,870 @@
+//! Exact arithmetic expressions for [`CalcTool`](super::CalcTool).
+//!
+//! Numbers are arbitrary-precision rationals, so `0.1 + 0.2` is exactly
+//! `0.3` and `30!` keeps every digit. A number may carry a unit (`3 GB`,
+//! `12 in`); quantities of the same dimension add, subtract and divide to a
+//! plain ratio, and `<expr> to <unit>` converts between units.
+//!
+//! Only arithmetic is understood: numbers, units, `+ - * / % ^ !`,
+//! parentheses and a few functions. Anything else is rejected, as are
+//! results too large to hold, deep nesting and division by zero.
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+/// Longest expression accepted, in bytes.
+const MAX_LENGTH: usize = 1_000;
+
+/// Deepest nesting of parentheses, functions and unary operators.
+const MAX_DEPTH: usize = 64;
+
+/// Most bits in the numerator and denominator of any value.
+const MAX_BITS: u64 = 1 << 20;
+
+/// Largest argument to `!`.
+const MAX_FACTORIAL: u64 = 10_000;
+
+/// Decimal digits computed for a square root that is not exact.
+const SQRT_DIGITS: u32 = 30;
+
+/// Physical dimension of a unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Data,
+    Length,
+    Mass,
+    Time,
+}
+
+/// A unit: its symbol, other spellings, dimension and size in the
+/// dimension's base unit (bytes, metres, grams, seconds) as a fraction.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Unit {
+    symbol: &'static str,
+    aliases: &'static [&'static str],
+    dimension: Dimension,
+    factor: (u64, u64),
+}
+
+impl Unit {
+    /// Symbol used in results, e.g. `MiB`.
+    pub fn symbol(&self) -> &'static str {
+        self.symbol
+    }
+
+    fn factor(&self) -> BigRational {
+        BigRational::new(self.factor.0.into(), self.factor.1.into())
+    }
+
+    /// Look a unit up by symbol or alias.
+    ///
+    /// Other units also match ignoring case, but data units only match
+    /// exactly since case tells bits from bytes (`Mb`, `MB`).
+    fn find(name: &str) -> Option<&'static Unit> {
+        let matches = |unit: &&Unit, eq: &dyn Fn(&str) -> bool| {
+            eq(unit.symbol) || unit.aliases.iter().any(|alias| eq(alias))
+        };
+        UNITS.iter().find(|unit| matches(unit, &|s| s == name)).or_else(|| {
+            UNITS.iter().find(|unit| {
+                unit.dimension != Dimension::Data
+                    && matches(unit, &|s| s.eq_ignore_ascii_case(name))
+            })
+        })
+    }
+}
+
+macro_rules! unit {
+    ($symbol:literal, [$($alias:literal),*], $dimension:ident, $num:expr) => {
+        unit!($symbol, [$($alias),*], $dimension, $num, 1)
+    };
+    ($symbol:literal, [$($alias:literal),*], $dimension:ident, $num:expr, $den:expr) => {
+        Unit {
+            symbol: $symbol,
+            aliases: &[$($alias),*],
+            dimension: Dimension::$dimension,
+            factor: ($num, $den),
+        }
+    };
+}
+
+/// Known units. Decimal prefixes are powers of 1000 (`GB`, `Gb`), binary
+/// prefixes powers of 1024 (`GiB`).
+static UNITS: &[Unit] = &[
+    unit!("B", ["byte", "bytes"], Data, 1),
+    unit!("bit", ["bits"], Data, 1, 8),
+    unit!("Kb", ["kb", "kbit"], Data, 125),
+    unit!("Mb", ["Mbit"], Data, 125_000),
+    unit!("Gb", ["Gbit"], Data, 125_000_000),
+    unit!("Tb", ["Tbit"], Data, 125_000_000_000),
+    unit!("KB", ["kB"], Data, 1_000),
+    unit!("MB", [], Data, 1_000_000),
+    unit!("GB", [], Data, 1_000_000_000),
+    unit!("TB", [], Data, 1_000_000_000_000),
+    unit!("PB", [], Data, 1_000_000_000_000_000),
+    unit!("KiB", [], Data, 1 << 10),
+    unit!("MiB", [], Data, 1 << 20),
+    unit!("GiB", [], Data, 1 << 30),
+    unit!("TiB", [], Data, 1 << 40),
+    unit!("PiB", [], Data, 1 << 50),
+    unit!("mm", [], Length, 1, 1_000),
+    unit!("cm", [], Length, 1, 100),
+    unit!("m", ["meter", "meters", "metre", "metres"], Length, 1),
+    unit!("km", [], Length, 1_000),
+    unit!("in", ["inch", "inches"], Length, 254, 10_000),
+    unit!("ft", ["foot", "feet"], Length, 3_048, 10_000),
+    unit!("yd", ["yard", "yards"], Length, 9_144, 10_000),
+    unit!("mi", ["mile", "miles"], Length, 1_609_344, 1_000),
+    unit!("mg", [], Mass, 1, 1_000),
+    unit!("g", ["gram", "grams"], Mass, 1),
+    unit!("kg", [], Mass, 1_000),
+    unit!("t", ["tonne", "tonnes"], Mass, 1_000_000),
+    unit!("oz", ["ounce", "ounces"], Mass, 28_349_523_125, 1_000_000_000),
+    unit!("lb", ["lbs", "pound", "pounds"], Mass, 45_359_237, 100_000),
+    unit!("ms", [], Time, 1, 1_000),
+    unit!("s", ["sec", "secs", "second", "seconds"], Time, 1),
+    unit!("min", ["mins", "minute", "minutes"], Time, 60),
+    unit!("h", ["hr", "hrs", "hour", "hours"], Time, 3_600),
+    unit!("d", ["day", "days"], Time, 86_400),
+    unit!("week", ["weeks"], Time, 604_800),
+];
+
+/// Evaluated value of an expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    /// Value in the base unit of its dimension, or a plain number.
+    base: BigRational,
+    /// Unit the value is shown in, if it has one.
+    unit: Option<&'static Unit>,
+    /// False when a square root was rounded along the way.
+    exact: bool,
+}
+
+impl Quantity {
+    fn plain(value: BigRational) -> Self {
+        Self {
+            base: value,
+            unit: None,
+            exact: true,
+        }
+    }
+
+    /// Value in [`unit`](Self::unit).
+    pub fn value(&self) -> BigRational {
+        match self.unit {
+            Some(unit) => &self.base / unit.factor(),
+            None => self.base.clone(),
+        }
+    }
+
+    /// Unit of the value, `None` for a plain number.
+    pub fn unit(&self) -> Option<&'static Unit> {
+        self.unit
+    }
+
+    /// Whether the value is exact.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    fn dimension(&self) -> Option<Dimension> {
+        self.unit.map(|unit| unit.dimension)
+    }
+}
+
+/// Evaluate `expression`.
+///
+/// Errors are messages for the user.
+pub fn evaluate(expression: &str) -> Result<Quantity, String> {
+    if expression.len() > MAX_LENGTH {
+        return Err(format!("Expression is longer than {} characters", MAX_LENGTH));
+    }
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("Expression is empty".to_string());
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.conversion()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(token) => Err(format!("Unexpected {}", token.describe())),
+    }
+}
+
+/// Format `value` as a decimal with at most `digits` fractional digits.
+///
+/// Returns the text and whether it is exact.
+pub fn to_decimal(value: &BigRational, digits: u32) -> (String, bool) {
+    if value.is_integer() {
+        return (value.to_integer().to_string(), true);
+    }
+    let scale = BigInt::from(10u32).pow(digits);
+    let scaled = value.abs() * BigRational::from_integer(scale.clone());
+    let exact = scaled.is_integer();
+    let rounded = scaled.round().to_integer();
+
+    let (whole, fraction) = (&rounded / &scale, &rounded % &scale);
+    let mut text = whole.to_string();
+    if !fraction.is_zero() {
+        let fraction = format!("{:0>width$}", fraction.to_string(), width = digits as usize);
+        text.push('.');
+        text.push_str(fraction.trim_end_matches('0'));
+    }
+    if value.is_negative() && !rounded.is_zero() {
+        text.insert(0, '-');
+    }
+    (text, exact)
+}
+
+/// Nearest `f64`, if the value is in range.
+pub fn to_f64(value: &BigRational) -> Option<f64> {
+    let approx = value.to_f64()?;
+    approx.is_finite().then_some(approx)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(BigRational),
+    Name(String),
+    Op(char),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Self::Number(n) => format!("number {}", n),
+            Self::Name(name) => format!("'{}'", name),
+            Self::Op(op) => format!("'{}'", op),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            let (number, end) = number(&chars, i)?;
+            tokens.push(Token::Number(number));
+            i = end;
+        } else if c.is_alphabetic() {
+            let end = (i..chars.len())
+                .find(|&j| !(chars[j].is_alphanumeric() || chars[j] == '_'))
+                .unwrap_or(chars.len());
+            tokens.push(Token::Name(chars[i..end].iter().collect()));
+            i = end;
+        } else {
+            let op = match c {
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    i += 1;
+                    '^'
+                }
+                '×' => '*',
+                '÷' => '/',
+                '−' => '-',
+                '+' | '-' | '*' | '/' | '%' | '^' | '!' | '(' | ')' | ',' => c,
+                _ => return Err(format!("Unexpected character '{}'", c)),
+            };
+            tokens.push(Token::Op(op));
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parse a decimal number starting at `start`: digits (with optional `_`
+/// separators), an optional fraction and an optional exponent.
+fn number(chars: &[char], start: usize) -> Result<(BigRational, usize), String> {
+    let mut i = start;
+    let mut digits = String::new();
+    let mut scale: i64 = 0;
+    let mut seen_point = false;
+    while let Some(&c) = chars.get(i) {
+        match c {
+            '0'..='9' => {
+                digits.push(c);
+                if seen_point {
+                    scale += 1;
+                }
+            }
+            '_' => {}
+            '.' if !seen_point => seen_point = true,
+            _ => break,
+        }
+        i += 1;
+    }
+
+    // An exponent only when digits follow, so `2e` stays a number and a name.
+    let mut exponent: i64 = 0;
+    if matches!(chars.get(i), Some('e' | 'E')) {
+        let sign_len = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+        let first = i + 1 + sign_len;
+        if chars.get(first).is_some_and(char::is_ascii_digit) {
+            let end = (first..chars.len())
+                .find(|&j| !chars[j].is_ascii_digit())
+                .unwrap_or(chars.len());
+            let text: String = chars[first..end].iter().collect();
+            exponent = text
+                .parse::<i64>()
+                .ok()
+                .filter(|e| *e <= 100_000)
+                .ok_or_else(|| "Number exponent is too large".to_string())?;
+            if chars[i + 1] == '-' {
+                exponent = -exponent;
+            }
+            i = end;
+        }
+    }
+
+    let mantissa: BigInt = digits
+        .parse()
+        .map_err(|_| format!("Invalid number '{}'", chars[start..i].iter().collect::<String>()))?;
+    let power = exponent - scale;
+    let ten = BigInt::from(10u32).pow(power.unsigned_abs() as u32);
+    let value = if power >= 0 {
+        BigRational::from_integer(mantissa * ten)
+    } else {
+        BigRational::new(mantissa, ten)
+    };
+    Ok((value, i))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), String> {
+        if self.eat(op) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => Err(format!("Expected '{}' but found {}", op, token.describe())),
+            None => Err(format!("Expected '{}' at the end", op)),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    /// `sum [to|as unit]`
+    fn conversion(&mut self) -> Result<Quantity, String> {
+        let value = self.sum()?;
+        let keyword = matches!(self.peek(), Some(Token::Name(n)) if n == "to" || n == "as");
+        if !keyword {
+            return Ok(value);
+        }
+        self.pos += 1;
+        let unit = match self.next() {
+            Some(Token::Name(name)) => {
+                Unit::find(&name).ok_or_else(|| format!("Unknown unit '{}'", name))?
+            }
+            _ => return Err("Expected a unit to convert to".to_string()),
+        };
+        if value.dimension() != Some(unit.dimension) {
+            return Err(format!("Cannot convert {} to {}", describe(&value), unit.symbol));
+        }
+        Ok(Quantity {
+            unit: Some(unit),
+            ..value
+        })
+    }
+
+    /// `term {(+|-) term}`
+    fn sum(&mut self) -> Result<Quantity, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value = add(value, self.term()?, false)?;
+            } else if self.eat('-') {
+                value = add(value, self.term()?, true)?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// `unary {(*|/|%) unary}`
+    fn term(&mut self) -> Result<Quantity, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value = multiply(value, self.unary()?)?;
+            } else if self.eat('/') {
+                value = divide(value, self.unary()?)?;
+            } else if self.eat('%') {
+                value = remainder(value, self.unary()?)?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// `(-|+) unary | power`
+    fn unary(&mut self) -> Result<Quantity, String> {
+        if self.eat('-') {
+            self.enter()?;
+            let value = self.unary()?;
+            self.depth -= 1;
+            return Ok(Quantity {
+                base: -value.base,
+                ..value
+            });
+        }
+        if self.eat('+') {
+            self.enter()?;
+            let value = self.unary();
+            self.depth -= 1;
+            return value;
+        }
+        self.power()
+    }
+
+    /// `postfix [^ unary]`, right associative.
+    fn power(&mut self) -> Result<Quantity, String> {
+        let base = self.postfix()?;
+        if !self.eat('^') {
+            return Ok(base);
+        }
+        self.enter()?;
+        let exponent = self.unary()?;
+        self.depth -= 1;
+        pow(base, exponent)
+    }
+
+    /// `primary {!}`
+    fn postfix(&mut self) -> Result<Quantity, String> {
+        let mut value = self.primary()?;
+        while self.eat('!') {
+            value = factorial(value)?;
+        }
+        Ok(value)
+    }
+
+    /// A number with an optional unit, a bare unit, a parenthesized
+    /// expression or a function call.
+    fn primary(&mut self) -> Result<Quantity, String> {
+        match self.next() {
+            Some(Token::Number(n)) => {
+                let unit = match self.peek() {
+                    Some(Token::Name(name)) if name != "to" && name != "as" => {
+                        let unit = Unit::find(name)
+                            .ok_or_else(|| format!("Unknown unit '{}'", name))?;
+                        self.pos += 1;
+                        Some(unit)
+                    }
+                    _ => None,
+                };
+                Ok(with_unit(n, unit))
+            }
+            Some(Token::Op('(')) => {
+                self.enter()?;
+                let value = self.conversion()?;
+                self.expect(')')?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            Some(Token::Name(name)) => {
+                if self.peek() == Some(&Token::Op('(')) {
+                    return self.call(&name);
+                }
+                match Unit::find(&name) {
+                    Some(unit) => Ok(with_unit(BigRational::one(), Some(unit))),
+                    None => Err(format!("Unknown name '{}'", name)),
+                }
+            }
+            Some(token) => Err(format!("Unexpected {}", token.describe())),
+            None => Err("Expression ends unexpectedly".to_string()),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Quantity, String> {
+        const FUNCTIONS: &[&str] = &["abs", "floor", "ceil", "round", "sqrt", "min", "max"];
+        if !FUNCTIONS.contains(&name) {
+            return Err(format!("Unknown function '{}'", name));
+        }
+        self.expect('(')?;
+        self.enter()?;
+        let mut args = vec![self.conversion()?];
+        while self.eat(',') {
+            args.push(self.conversion()?);
+        }
+        self.expect(')')?;
+        self.depth -= 1;
+
+        let arity = |n: usize| {
+            if args.len() == n {
+                Ok(())
+            } else {
+                Err(format!("{}() takes {} argument(s)", name, n))
+            }
+        };
+        match name {
+            "abs" => {
+                arity(1)?;
+                let value = args.remove(0);
+                Ok(Quantity {
+                    base: value.base.abs(),
+                    ..value
+                })
+            }
+            "floor" | "ceil" | "round" => {
+                arity(1)?;
+                let value = args.remove(0);
+                let shown = value.value();
+                let rounded = match name {
+                    "floor" => shown.floor(),
+                    "ceil" => shown.ceil(),
+                    _ => shown.round(),
+                };
+                Ok(with_unit(rounded, value.unit))
+            }
+            "sqrt" => {
+                arity(1)?;
+                sqrt(args.remove(0))
+            }
+            "min" | "max" => {
+                let mut best = args.remove(0);
+                for value in args {
+                    same_dimension(&best, &value, name)?;
+                    let better = if name == "min" {
+                        value.base < best.base
+                    } else {
+                        value.base > best.base
+                    };
+                    if better {
+                        best = value;
+                    }
+                }
+                Ok(best)
+            }
+            _ => Err(format!("Unknown function '{}'", name)),
+        }
+    }
+}
+
+/// `value` in `unit`, converted to the base unit.
+fn with_unit(value: BigRational, unit: Option<&'static Unit>) -> Quantity {
+    match unit {
+        Some(unit) => Quantity {
+            base: value * unit.factor(),
+            unit: Some(unit),
+            exact: true,
+        },
+        None => Quantity::plain(value),
+    }
+}
+
+fn describe(value: &Quantity) -> String {
+    match value.unit {
+        Some(unit) => unit.symbol.to_string(),
+        None => "a plain number".to_string(),
+    }
+}
+
+fn same_dimension(a: &Quantity, b: &Quantity, op: &str) -> Result<(), String> {
+    if a.dimension() == b.dimension() {
+        return Ok(());
+    }
+    Err(format!("Cannot {} {} and {}", op, describe(a), describe(b)))
+}
+
+/// Fail when `value` has grown too large to keep computing with.
+fn checked(value: Quantity) -> Result<Quantity, String> {
+    if value.base.numer().bits() + value.base.denom().bits() > MAX_BITS {
+        return Err("Result is too large".to_string());
+    }
+    Ok(value)
+}
+
+fn add(a: Quantity, b: Quantity, subtract: bool) -> Result<Quantity, String> {
+    same_dimension(&a, &b, if subtract { "subtract" } else { "add" })?;
+    let base = if subtract { a.base - b.base } else { a.base + b.base };
+    checked(Quantity {
+        base,
+        unit: a.unit.or(b.unit),
+        exact: a.exact && b.exact,
+    })
+}
+
+fn multiply(a: Quantity, b: Quantity) -> Result<Quantity, String> {
+    if let (Some(x), Some(y)) = (a.unit, b.unit) {
+        return Err(format!("Cannot multiply {} by {}", x.symbol, y.symbol));
+    }
+    checked(Quantity {
+        base: a.base * b.base,
+        unit: a.unit.or(b.unit),
+        exact: a.exact && b.exact,
+    })
+}
+
+fn divide(a: Quantity, b: Quantity) -> Result<Quantity, String> {
+    if b.base.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    let unit = match (a.unit, b.unit) {
+        (Some(x), Some(y)) if x.dimension == y.dimension => None,
+        (Some(_), None) => a.unit,
+        (None, None) => None,
+        _ => return Err(format!("Cannot divide {} by {}", describe(&a), describe(&b))),
+    };
+    checked(Quantity {
+        base: a.base / b.base,
+        unit,
+        exact: a.exact && b.exact,
+    })
+}
+
+fn remainder(a: Quantity, b: Quantity) -> Result<Quantity, String> {
+    same_dimension(&a, &b, "take the remainder of")?;
+    if b.base.is_zero() {
+        return Err("Division by zero".to_string());
+    }
+    checked(Quantity {
+        base: a.base % b.base,
+        unit: a.unit.or(b.unit),
+        exact: a.exact && b.exact,
+    })
+}
+
+fn pow(base: Quantity, exponent: Quantity) -> Result<Quantity, String> {
+    if base.unit.is_some() || exponent.unit.is_some() {
+        return Err("Powers of quantities with units are not supported".to_string());
+    }
+    if !exponent.base.is_integer() {
+        return Err("Exponent must be a whole number".to_string());
+    }
+    let exp = exponent.base.to_integer();
+    let exact = base.exact && exponent.exact;
+
+    // Powers of 0, 1 and -1 stay small however large the exponent is.
+    if base.base.is_zero() || base.base.abs().is_one() {
+        if exp.is_negative() && base.base.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+        let value = if exp.is_zero() || (base.base.is_negative() && (&exp % 2u32).is_zero()) {
+            BigRational::one()
+        } else {
+            base.base
+        };
+        return Ok(Quantity {
+            base: value,
+            unit: None,
+            exact,
+        });
+    }
+
+    let magnitude = exp.abs().to_u32().filter(|e| {
+        let bits = base.base.numer().bits() + base.base.denom().bits();
+        bits.saturating_mul(u64::from(*e)) <= MAX_BITS
+    });
+    let Some(magnitude) = magnitude else {
+        return Err("Result is too large".to_string());
+    };
+
+    let numer = base.base.numer().pow(magnitude);
+    let denom = base.base.denom().pow(magnitude);
+    let value = if exp.is_negative() {
+        BigRational::new(denom, numer)
+    } else {
+        BigRational::new(numer, denom)
+    };
+    checked(Quantity {
+        base: value,
+        unit: None,
+        exact,
+    })
+}
+
+fn factorial(value: Quantity) -> Result<Quantity, String> {
+    let n = value
+        .base
+        .is_integer()
+        .then(|| value.base.to_integer().to_u64())
+        .flatten()
+        .filter(|n| value.unit.is_none() && *n <= MAX_FACTORIAL)
+        .ok_or_else(|| {
+            format!("Factorial needs a whole number from 0 to {}", MAX_FACTORIAL)
+        })?;
+    let product = (2..=n).fold(BigInt::one(), |acc, k| acc * k);
+    checked(Quantity {
+        base: BigRational::from_integer(product),
+        ..value
+    })
+}
+
+fn sqrt(value: Quantity) -> Result<Quantity, String> {
+    if value.unit.is_some() {
+        return Err("Square roots of quantities with units are not supported".to_string());
+    }
+    if value.base.is_negative() {
+        return Err("Cannot take the square root of a negative number".to_string());
+    }
+    let (numer, denom) = (value.base.numer(), value.base.denom());
+    let (root_n, root_d) = (numer.sqrt(), denom.sqrt());
+    if &(&root_n * &root_n) == numer && &(&root_d * &root_d) == denom {
+        return Ok(Quantity::plain(BigRational::new(root_n, root_d)));
+    }
+
+    // sqrt(n/d) = sqrt(n * d) / d, scaled to keep SQRT_DIGITS digits.
+    let scale = BigInt::from(10u32).pow(SQRT_DIGITS);
+    let root = (numer * denom * &scale * &scale).sqrt();
+    checked(Quantity {
+        base: BigRational::new(root, denom * scale),
+        unit: None,
+        exact: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str) -> (String, Option<&'static str>) {
+        let value = evaluate(expr).unwrap_or_else(|e| panic!("{}: {}", expr, e));
+        (to_decimal(&value.value(), 20).0, value.unit().map(Unit::symbol))
+    }
+
+    fn error(expr: &str) -> String {
+        evaluate(expr).unwrap_err()
+    }
+
+    #[test]
+    fn test_precedence_and_exact_decimals() {
+        assert_eq!(eval("1 + 2 * 3").0, "7");
+        assert_eq!(eval("(1 + 2) * 3").0, "9");
+        assert_eq!(eval("2 ^ 3 ^ 2").0, "512");
+        assert_eq!(eval("-2 ^ 2").0, "-4");
+        assert_eq!(eval("2 ** -2").0, "0.25");
+        assert_eq!(eval("7 % 4 + 1_000").0, "1003");
+        assert_eq!(eval("1.5e3 / 4").0, "375");
+
+        // f64 gives 0.30000000000000004 and 0.9999999999999999.
+        assert_eq!(eval("0.1 + 0.2").0, "0.3");
+        assert_eq!(eval("0.1 * 3 - 0.3").0, "0");
+        assert_eq!(eval("1 - 0.9").0, "0.1");
+    }
+
+    #[test]
+    fn test_powers_of_zero_and_one_with_huge_exponents() {
+        assert_eq!(eval("1 ^ (10 ^ 10)").0, "1");
+        assert_eq!(eval("0 ^ (10 ^ 10)").0, "0");
+        assert_eq!(eval("0 ^ 0").0, "1");
+        assert_eq!(eval("(-1) ^ (10 ^ 10)").0, "1");
+        assert_eq!(eval("(-1) ^ (10 ^ 10 + 1)").0, "-1");
+        assert_eq!(eval("1 ^ -(10 ^ 10)").0, "1");
+    }
+
+    #[test]
+    fn test_large_integer_factorial() {
+        assert_eq!(eval("30!").0, "265252859812191058636308480000000");
+        assert_eq!(eval("25! / 23!").0, "600");
+        let digits = eval("1000!").0;
+        assert_eq!(digits.len(), 2568);
+        assert!(digits.starts_with("402387260077"));
+    }
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(eval("3 GB / 128 MB"), ("23.4375".to_string(), None));
+        assert_eq!(eval("1 GiB to MiB"), ("1024".to_string(), Some("MiB")));
+        assert_eq!(eval("1.5 GB + 500 MB"), ("2".to_string(), Some("GB")));
+        assert_eq!(eval("12 in to cm"), ("30.48".to_string(), Some("cm")));
+        assert_eq!(eval("90 minutes as h"), ("1.5".to_string(), Some("h")));
+        assert_eq!(eval("2 * 3 kg"), ("6".to_string(), Some("kg")));
+        assert_eq!(eval("10 GB / GiB").0, "9.31322574615478515625");
+        assert_eq!(eval("100 Mb to MB"), ("12.5".to_string(), Some("MB")));
+        assert_eq!(eval("1 Gbit to Mb"), ("1000".to_string(), Some("Mb")));
+        assert_eq!(eval("2 HOURS to MIN"), ("120".to_string(), Some("min")));
+
+        assert_eq!(error("3 GB + 2 km"), "Cannot add GB and km");
+        assert_eq!(error("3 GB * 2 MB"), "Cannot multiply GB by MB");
+        assert_eq!(error("1 h to MB"), "Cannot convert h to MB");
+        assert_eq!(error("5 parsecs"), "Unknown unit 'parsecs'");
+        assert_eq!(error("5 mB"), "Unknown unit 'mB'");
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("sqrt(2.25)").0, "1.5");
+        let root = evaluate("sqrt(2)").unwrap();
+        assert!(!root.is_exact());
+        assert_eq!(to_decimal(&root.value(), 10), ("1.4142135624".to_string(), false));
+        assert_eq!(eval("max(1, 7, 3) - min(4, -2)").0, "9");
+        assert_eq!(eval("round(2.5) + floor(-1.5) + abs(-3)").0, "4");
+        assert_eq!(eval("floor(1.75 GB)"), ("1".to_string(), Some("GB")));
+        assert_eq!(error("sqrt(-1)"), "Cannot take the square root of a negative number");
+    }
+
+    #[test]
+    fn test_clean_errors() {
+        assert_eq!(error("1 / 0"), "Division by zero");
+        assert_eq!(error("5 % (2 - 2)"), "Division by zero");
+        assert_eq!(error("0 ^ -1"), "Division by zero");
+        assert_eq!(error("0 ^ -(10 ^ 10)"), "Division by zero");
+        assert_eq!(error("10 ^ 10 ^ 10"), "Result is too large");
+        assert_eq!(error("99999999!"), "Factorial needs a whole number from 0 to 10000");
+        assert_eq!(error("2.5!"), "Factorial needs a whole number from 0 to 10000");
+        assert_eq!(error("2 ^ 0.5"), "Exponent must be a whole number");
+        assert_eq!(error("1e999999"), "Number exponent is too large");
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(error(&nested), "Expression is nested too deeply");
+        assert_eq!(error(&"1+".repeat(600)), "Expression is longer than 1000 characters");
+    }
+
+    #[test]
+    fn test_rejects_non_arithmetic() {
+        assert_eq!(error("system(1)"), "Unknown function 'system'");
+        assert_eq!(error("exec(\"ls\")"), "Unexpected character '\"'");
+        assert_eq!(error("x + 1"), "Unknown name 'x'");
+        assert_eq!(error("1; 2"), "Unexpected character ';'");
+        assert_eq!(error("2 3"), "Unexpected number 3");
+        assert_eq!(error("(1 + 2"), "Expected ')' at the end");
+        assert_eq!(error(""), "Expression is empty");
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        let third = BigRational::new(1.into(), 3.into());
+        assert_eq!(to_decimal(&third, 5), ("0.33333".to_string(), false));
+        assert_eq!(to_decimal(&-third, 2), ("-0.33".to_string(), false));
+        let tiny = BigRational::new((-1).into(), 1000.into());
+        assert_eq!(to_decimal(&tiny, 2), ("0".to_string(), false));
+        assert_eq!(to_f64(&BigRational::new(1.into(), 4.into())), Some(0.25));
+    }
+}
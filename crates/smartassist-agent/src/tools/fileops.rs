@@ -63,6 +63,7 @@ impl Tool for FileCopyTool {
                 },
                 "required": ["source", "destination"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -181,6 +182,7 @@ impl Tool for FileMoveTool {
                 },
                 "required": ["source", "destination"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -299,6 +301,7 @@ impl Tool for FileStatTool {
                 },
                 "required": ["path"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -418,6 +421,7 @@ impl Tool for FileDeleteTool {
                 },
                 "required": ["path"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
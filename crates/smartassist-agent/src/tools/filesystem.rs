@@ -11,8 +11,14 @@ use crate::error::AgentError;
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
-use regex::Regex;
-use std::path::PathBuf;
+use futures::StreamExt;
+use regex::{Regex, RegexBuilder};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
 use std::time::Instant;
 use tracing::debug;
 
@@ -47,6 +53,7 @@ impl Tool for ReadTool {
                 },
                 "required": ["path"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -95,6 +102,7 @@ impl Tool for ReadTool {
                 "content": numbered.join("\n"),
                 "lines": lines.len(),
                 "path": full_path.to_string_lossy(),
+                "sha256": content_sha256(&content),
             }))
             .with_duration(duration),
         )
@@ -132,6 +140,7 @@ impl Tool for WriteTool {
                 },
                 "required": ["path", "content"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -241,10 +250,16 @@ impl Tool for EditTool {
                     "replace_all": {
                         "type": "boolean",
                         "description": "Replace all occurrences instead of requiring unique match"
+                    },
+                    "expected_sha256": {
+                        "type": "string",
+                        "description": "SHA-256 of the file as last read (from 'read'); the edit \
+                                        fails with a conflict if the file has changed since"
                     }
                 },
                 "required": ["path", "old_string", "new_string"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -277,7 +292,11 @@ impl Tool for EditTool {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let expected_sha256 = args.get("expected_sha256").and_then(|v| v.as_str());
+
+        // Edit the file a symlink points at rather than replacing the link.
         let full_path = resolve_path(path, &context.cwd);
+        let full_path = tokio::fs::canonicalize(&full_path).await.unwrap_or(full_path);
 
         // Read file
         let content = tokio::fs::read_to_string(&full_path).await.map_err(|e| {
@@ -291,6 +310,16 @@ impl Tool for EditTool {
             ));
         }
 
+        let current_sha256 = content_sha256(&content);
+        if let Some(expected) = expected_sha256 {
+            if !expected.eq_ignore_ascii_case(&current_sha256) {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    conflict_message(path, expected, &current_sha256),
+                ));
+            }
+        }
+
         // Count occurrences
         let count = content.matches(old_string).count();
 
@@ -318,16 +347,33 @@ impl Tool for EditTool {
             content.replacen(old_string, new_string, 1)
         };
 
-        // Write back
-        tokio::fs::write(&full_path, &new_content).await.map_err(|e| {
+        // Write back atomically
+        let write_error = |e: std::io::Error| {
             AgentError::tool_execution(format!("Failed to write file '{}': {}", path, e))
+        };
+        let staged = StagedWrite::new(&full_path, new_content.as_bytes())
+            .await
+            .map_err(write_error)?;
+
+        // Another writer may have changed the file while the edit was staged.
+        let latest = tokio::fs::read_to_string(&full_path).await.map_err(|e| {
+            AgentError::tool_execution(format!("Failed to read file '{}': {}", path, e))
         })?;
+        if latest != content {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                conflict_message(path, &current_sha256, &content_sha256(&latest)),
+            ));
+        }
+
+        staged.commit().await.map_err(write_error)?;
 
         let duration = start.elapsed();
         Ok(
             ToolResult::success(tool_use_id, serde_json::json!({
                 "path": full_path.to_string_lossy(),
                 "replacements": if replace_all { count } else { 1 },
+                "sha256": content_sha256(&new_content),
             }))
             .with_duration(duration),
         )
@@ -342,6 +388,75 @@ impl Tool for EditTool {
     }
 }
 
+/// Hex-encoded SHA-256 of file contents, used as an edit precondition.
+fn content_sha256(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+fn conflict_message(path: &str, expected: &str, found: &str) -> String {
+    format!(
+        "Conflict: '{}' changed since it was read (expected sha256 {}, found {}). \
+         Read the file again before editing.",
+        path, expected, found
+    )
+}
+
+/// New file contents written beside the target and renamed over it.
+///
+/// Readers see either the old or the new contents, never a partial write.
+/// Until [`StagedWrite::commit`] the target is untouched, so a crash leaves
+/// at most a stray temp file; dropping an uncommitted write removes it.
+struct StagedWrite {
+    temp: PathBuf,
+    target: PathBuf,
+    committed: bool,
+}
+
+impl StagedWrite {
+    /// Write and sync `contents` to a temp file next to `target`, copying
+    /// the target's permissions if it exists.
+    async fn new(target: &Path, contents: &[u8]) -> std::io::Result<Self> {
+        use tokio::io::AsyncWriteExt;
+
+        let name = target.file_name().unwrap_or_default().to_string_lossy();
+        let temp = target.with_file_name(format!(
+            ".{}.{}.tmp",
+            name,
+            uuid::Uuid::new_v4().simple()
+        ));
+        let staged = Self {
+            temp,
+            target: target.to_path_buf(),
+            committed: false,
+        };
+
+        let mut file = tokio::fs::File::create(&staged.temp).await?;
+        file.write_all(contents).await?;
+        file.sync_all().await?;
+
+        if let Ok(metadata) = tokio::fs::metadata(target).await {
+            tokio::fs::set_permissions(&staged.temp, metadata.permissions()).await?;
+        }
+
+        Ok(staged)
+    }
+
+    /// Move the staged contents into place.
+    async fn commit(mut self) -> std::io::Result<()> {
+        tokio::fs::rename(&self.temp, &self.target).await?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for StagedWrite {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.temp);
+        }
+    }
+}
+
 /// Glob tool - Find files matching a pattern.
 pub struct GlobTool {
     /// Maximum results to return.
@@ -384,13 +499,28 @@ impl Tool for GlobTool {
                         "type": "string",
                         "description": "The glob pattern to match files"
                     },
+                    "patterns": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Additional glob patterns; a path matching any is returned"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Glob patterns to leave out; excluded directories are \
+                                        not searched"
+                    },
                     "path": {
                         "type": "string",
                         "description": "Base directory to search from (defaults to cwd)"
+                    },
+                    "max_results": {
+                        "type": "integer",
+                        "description": "Stop after this many matches"
                     }
-                },
-                "required": ["pattern"]
+                }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -403,10 +533,7 @@ impl Tool for GlobTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let pattern = args
-            .get("pattern")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| AgentError::tool_execution("Missing 'pattern' argument"))?;
+        let pattern = args.get("pattern").and_then(|v| v.as_str());
 
         let base_path = args
             .get("path")
@@ -414,26 +541,39 @@ impl Tool for GlobTool {
             .map(|p| resolve_path(p, &context.cwd))
             .unwrap_or_else(|| context.cwd.clone());
 
-        // Construct full pattern
-        let full_pattern = base_path.join(pattern);
-        let pattern_str = full_pattern.to_string_lossy();
+        let include = pattern
+            .into_iter()
+            .chain(string_list(&args, "patterns"))
+            .collect::<Vec<_>>();
+        if include.is_empty() {
+            return Err(AgentError::tool_execution("Missing 'pattern' argument"));
+        }
+        let matcher = GlobMatcher::new(&base_path, &include, &string_list(&args, "exclude"))?;
+
+        let limit = args
+            .get("max_results")
+            .and_then(|v| v.as_u64())
+            .map_or(self.max_results, |v| (v as usize).min(self.max_results));
 
-        debug!("Glob pattern: {}", pattern_str);
+        debug!("Glob patterns {:?} under {}", include, base_path.display());
 
-        // Execute glob
-        let entries: Vec<String> = glob::glob(&pattern_str)
-            .map_err(|e| AgentError::tool_execution(format!("Invalid glob pattern: {}", e)))?
-            .filter_map(|entry| entry.ok())
-            .take(self.max_results)
+        let walk = GlobWalk::new(matcher, limit);
+        let mut paths = tokio::task::spawn_blocking(move || walk.run(walk_threads()))
+            .await
+            .map_err(|e| AgentError::tool_execution(format!("File walk failed: {}", e)))?;
+        paths.sort();
+
+        let entries: Vec<String> = paths
+            .iter()
             .map(|path| {
                 path.strip_prefix(&base_path)
-                    .unwrap_or(&path)
+                    .unwrap_or(path)
                     .to_string_lossy()
                     .to_string()
             })
             .collect();
 
-        let truncated = entries.len() >= self.max_results;
+        let truncated = entries.len() >= limit;
 
         let duration = start.elapsed();
         Ok(
@@ -452,6 +592,313 @@ impl Tool for GlobTool {
     }
 }
 
+/// Upper bound on the threads a [`GlobTool`] walk uses.
+const MAX_WALK_THREADS: usize = 8;
+
+fn walk_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get().min(MAX_WALK_THREADS))
+}
+
+/// Read an optional array-of-strings argument.
+fn string_list<'a>(args: &'a serde_json::Value, key: &str) -> Vec<&'a str> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// Include and exclude patterns, anchored at the search root.
+///
+/// Patterns may be absolute or start with `..`. Each include pattern is
+/// walked from the directory its literal prefix names, so `../lib/*.rs`
+/// reads only `../lib`. As with shell globs, `*` does not cross a `/`.
+struct GlobMatcher {
+    include: Vec<IncludePattern>,
+    exclude: Vec<glob::Pattern>,
+}
+
+/// An include pattern, split at its first wildcard component.
+struct IncludePattern {
+    /// Directory named by the components before the first wildcard.
+    root: PathBuf,
+    /// The components after `root`; `None` stands for `**`.
+    parts: Vec<Option<glob::Pattern>>,
+    /// The whole pattern, matched against full paths.
+    full: glob::Pattern,
+}
+
+impl GlobMatcher {
+    const OPTIONS: glob::MatchOptions = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+
+    fn new(base: &Path, include: &[&str], exclude: &[&str]) -> Result<Self> {
+        let include = include
+            .iter()
+            .map(|pattern| IncludePattern::new(base, pattern))
+            .collect::<Result<Vec<_>>>()?;
+        let exclude = exclude
+            .iter()
+            .map(|pattern| {
+                let (root, parts) = anchor(base, pattern, false);
+                compile(&root, &parts)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { include, exclude })
+    }
+
+    /// Directories the walk starts from, none inside another.
+    fn roots(&self) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = self.include.iter().map(|p| p.root.clone()).collect();
+        roots.sort();
+        roots.dedup_by(|later, earlier| later.starts_with(earlier));
+        roots
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        self.include
+            .iter()
+            .any(|p| p.full.matches_path_with(path, Self::OPTIONS))
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude
+            .iter()
+            .any(|p| p.matches_path_with(path, Self::OPTIONS))
+    }
+
+    /// Check whether anything under the directory `dir` could match.
+    fn may_contain(&self, dir: &Path) -> bool {
+        self.include.iter().any(|pattern| pattern.may_contain(dir))
+    }
+}
+
+impl IncludePattern {
+    fn new(base: &Path, pattern: &str) -> Result<Self> {
+        let (root, parts) = anchor(base, pattern, true);
+        let full = compile(&root, &parts)?;
+        let parts = parts
+            .iter()
+            .map(|part| match part.as_str() {
+                "**" => Ok(None),
+                part => glob::Pattern::new(part).map(Some).map_err(invalid_pattern),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { root, parts, full })
+    }
+
+    fn may_contain(&self, dir: &Path) -> bool {
+        let Ok(relative) = dir.strip_prefix(&self.root) else {
+            return false;
+        };
+        for (i, component) in relative.components().enumerate() {
+            let name = component.as_os_str().to_string_lossy();
+            match self.parts.get(i) {
+                Some(None) => return true,
+                // A directory can only stand for a component that is not
+                // the last one.
+                Some(Some(part)) if i + 1 < self.parts.len() => {
+                    if !part.matches_with(&name, GlobMatcher::OPTIONS) {
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+/// Resolve `pattern` against `base`, removing `.` and `..`, and split it
+/// into the literal directory it starts in and the components after that.
+///
+/// With `keep_last`, the final component always goes to the second half,
+/// so the directory is one to walk rather than the match itself.
+fn anchor(base: &Path, pattern: &str, keep_last: bool) -> (PathBuf, Vec<String>) {
+    let joined = base.join(pattern);
+    let mut components: Vec<std::path::Component> = Vec::new();
+    for component in joined.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if matches!(components.last(), Some(std::path::Component::Normal(_))) {
+                    components.pop();
+                }
+            }
+            component => components.push(component),
+        }
+    }
+
+    let is_wild = |c: &std::path::Component| {
+        c.as_os_str().to_string_lossy().contains(['*', '?', '['])
+    };
+    let mut literal = components.iter().take_while(|c| !is_wild(c)).count();
+    if keep_last {
+        literal = literal.min(components.len().saturating_sub(1));
+    }
+    let root: PathBuf = components[..literal].iter().collect();
+    let parts = components[literal..]
+        .iter()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    (root, parts)
+}
+
+/// Build a pattern matching `parts` under the literal directory `root`.
+fn compile(root: &Path, parts: &[String]) -> Result<glob::Pattern> {
+    let mut text = glob::Pattern::escape(&root.to_string_lossy());
+    for part in parts {
+        if !text.ends_with(std::path::MAIN_SEPARATOR) {
+            text.push(std::path::MAIN_SEPARATOR);
+        }
+        text.push_str(part);
+    }
+    glob::Pattern::new(&text).map_err(invalid_pattern)
+}
+
+fn invalid_pattern(e: glob::PatternError) -> AgentError {
+    AgentError::tool_execution(format!("Invalid glob pattern: {}", e))
+}
+
+/// Directories waiting to be read by a [`GlobWalk`].
+#[derive(Default)]
+struct WalkQueue {
+    dirs: Vec<PathBuf>,
+    /// Directories queued or being read; the walk ends when this is zero.
+    active: usize,
+}
+
+/// A directory walk shared by several threads, starting from the
+/// matcher's roots and skipping directories no pattern can reach.
+///
+/// Symlinks are followed, and each directory is read at most once by its
+/// canonical path, so links back up the tree cannot loop. Once `limit`
+/// paths have been found the walk stops; which paths those are depends on
+/// thread scheduling.
+struct GlobWalk {
+    matcher: GlobMatcher,
+    limit: usize,
+    queue: Mutex<WalkQueue>,
+    /// Signalled when directories are queued or the walk ends.
+    ready: Condvar,
+    /// Canonical paths of directories already queued.
+    visited: Mutex<HashSet<PathBuf>>,
+    found: Mutex<Vec<PathBuf>>,
+    done: AtomicBool,
+}
+
+impl GlobWalk {
+    fn new(matcher: GlobMatcher, limit: usize) -> Self {
+        Self {
+            matcher,
+            limit,
+            queue: Mutex::new(WalkQueue::default()),
+            ready: Condvar::new(),
+            visited: Mutex::new(HashSet::new()),
+            found: Mutex::new(Vec::new()),
+            done: AtomicBool::new(limit == 0),
+        }
+    }
+
+    /// Walk the tree on `threads` threads and return the matching paths.
+    fn run(self, threads: usize) -> Vec<PathBuf> {
+        for root in self.matcher.roots() {
+            self.enqueue(root);
+        }
+        std::thread::scope(|scope| {
+            for _ in 0..threads.max(1) {
+                scope.spawn(|| self.work());
+            }
+        });
+        self.found.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn work(&self) {
+        while let Some(dir) = self.next_dir() {
+            self.read_dir(&dir);
+
+            let mut queue = lock(&self.queue);
+            queue.active -= 1;
+            if queue.active == 0 {
+                self.ready.notify_all();
+            }
+        }
+    }
+
+    fn next_dir(&self) -> Option<PathBuf> {
+        let mut queue = lock(&self.queue);
+        loop {
+            if self.done.load(Ordering::Relaxed) {
+                return None;
+            }
+            if let Some(dir) = queue.dirs.pop() {
+                return Some(dir);
+            }
+            if queue.active == 0 {
+                return None;
+            }
+            queue = self.ready.wait(queue).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    fn enqueue(&self, dir: PathBuf) {
+        let key = std::fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone());
+        if !lock(&self.visited).insert(key) {
+            return;
+        }
+
+        let mut queue = lock(&self.queue);
+        queue.dirs.push(dir);
+        queue.active += 1;
+        self.ready.notify_one();
+    }
+
+    fn read_dir(&self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            if self.done.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let path = entry.path();
+            if self.matcher.is_excluded(&path) {
+                continue;
+            }
+
+            let is_dir = path.is_dir();
+            if self.matcher.is_included(&path) {
+                self.record(path.clone());
+            }
+            if is_dir && self.matcher.may_contain(&path) {
+                self.enqueue(path);
+            }
+        }
+    }
+
+    fn record(&self, path: PathBuf) {
+        let mut found = lock(&self.found);
+        if found.len() < self.limit {
+            found.push(path);
+        }
+        if found.len() >= self.limit {
+            self.done.store(true, Ordering::Relaxed);
+            // Take the queue lock so no worker misses the wakeup between
+            // checking `done` and waiting.
+            let _queue = lock(&self.queue);
+            self.ready.notify_all();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 /// Grep tool - Search file contents with regex.
 pub struct GrepTool {
     /// Maximum results to return.
@@ -492,6 +939,9 @@ impl GrepTool {
     }
 }
 
+/// Number of files [`GrepTool`] searches at once.
+const SEARCH_CONCURRENCY: usize = 16;
+
 #[derive(Debug, Clone, serde::Serialize)]
 struct GrepMatch {
     file: String,
@@ -512,7 +962,9 @@ impl Tool for GrepTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "grep".to_string(),
-            description: "Search file contents using regex pattern".to_string(),
+            description: "Search file contents using regex pattern, skipping hidden and \
+                          gitignored files"
+                .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
@@ -532,17 +984,38 @@ impl Tool for GrepTool {
                         "type": "boolean",
                         "description": "Case-insensitive search"
                     },
+                    "fixed_strings": {
+                        "type": "boolean",
+                        "description": "Treat the pattern as a literal string, not a regex"
+                    },
                     "context": {
                         "type": "integer",
                         "description": "Lines of context before and after match"
                     },
+                    "context_before": {
+                        "type": "integer",
+                        "description": "Lines of context before match (overrides 'context')"
+                    },
+                    "context_after": {
+                        "type": "integer",
+                        "description": "Lines of context after match (overrides 'context')"
+                    },
                     "files_only": {
                         "type": "boolean",
                         "description": "Only return file names, not matches"
+                    },
+                    "hidden": {
+                        "type": "boolean",
+                        "description": "Search hidden files and directories"
+                    },
+                    "no_ignore": {
+                        "type": "boolean",
+                        "description": "Search files excluded by .gitignore"
                     }
                 },
                 "required": ["pattern"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -565,24 +1038,45 @@ impl Tool for GrepTool {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        let context_lines = args
-            .get("context")
-            .and_then(|v| v.as_u64())
-            .map(|v| v as usize);
+        let fixed_strings = args
+            .get("fixed_strings")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let context_arg = |key: &str| args.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+        let context_lines = context_arg("context");
+        let before = context_arg("context_before")
+            .or(context_lines)
+            .unwrap_or(self.default_context_before);
+        let after = context_arg("context_after")
+            .or(context_lines)
+            .unwrap_or(self.default_context_after);
 
         let files_only = args
             .get("files_only")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
+        let hidden = args
+            .get("hidden")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let no_ignore = args
+            .get("no_ignore")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
         // Build regex
-        let regex_pattern = if case_insensitive {
-            format!("(?i){}", pattern)
+        let regex_pattern = if fixed_strings {
+            regex::escape(pattern)
         } else {
             pattern.to_string()
         };
 
-        let regex = Regex::new(&regex_pattern)
+        let regex = RegexBuilder::new(&regex_pattern)
+            .case_insensitive(case_insensitive)
+            .build()
             .map_err(|e| AgentError::tool_execution(format!("Invalid regex: {}", e)))?;
 
         // Get files to search
@@ -595,98 +1089,55 @@ impl Tool for GrepTool {
         let file_glob = args
             .get("glob")
             .and_then(|v| v.as_str())
-            .unwrap_or("**/*");
+            .map(glob::Pattern::new)
+            .transpose()
+            .map_err(|e| AgentError::tool_execution(format!("Invalid glob: {}", e)))?;
 
-        // Handle the case where base_path is a file vs directory
         let files: Vec<PathBuf> = if base_path.is_file() {
-            // If path is a file, just search that file
             vec![base_path.clone()]
         } else {
-            // If path is a directory, use glob
-            let glob_pattern = base_path.join(file_glob);
-            glob::glob(&glob_pattern.to_string_lossy())
-                .map_err(|e| AgentError::tool_execution(format!("Invalid glob: {}", e)))?
-                .filter_map(|e| e.ok())
-                .filter(|p| p.is_file())
-                .collect()
-        };
-
-        let mut matches: Vec<GrepMatch> = Vec::new();
-        let mut files_with_matches: Vec<String> = Vec::new();
-
-        for file_path in files {
-            if matches.len() >= self.max_results {
-                break;
-            }
-
-            // Try to read as text
-            let content = match tokio::fs::read_to_string(&file_path).await {
-                Ok(c) => c,
-                Err(_) => continue, // Skip binary files
+            let walk = WalkOptions {
+                hidden,
+                gitignore: !no_ignore,
+                glob: file_glob,
             };
+            let root = base_path.clone();
+            tokio::task::spawn_blocking(move || walk.files(&root))
+                .await
+                .map_err(|e| AgentError::tool_execution(format!("File walk failed: {}", e)))?
+        };
 
-            let lines: Vec<&str> = content.lines().collect();
-            let mut file_has_match = false;
-
-            for (line_idx, line) in lines.iter().enumerate() {
-                if regex.is_match(line) {
-                    file_has_match = true;
-
-                    if !files_only {
-                        let rel_path = file_path
-                            .strip_prefix(&context.cwd)
-                            .unwrap_or(&file_path)
-                            .to_string_lossy()
-                            .to_string();
-
-                        let ctx = context_lines.unwrap_or(self.default_context_before);
-
-                        let context_before = if ctx > 0 {
-                            let start = line_idx.saturating_sub(ctx);
-                            Some(
-                                lines[start..line_idx]
-                                    .iter()
-                                    .map(|s| s.to_string())
-                                    .collect(),
-                            )
-                        } else {
-                            None
-                        };
-
-                        let context_after = if ctx > 0 {
-                            let end = (line_idx + 1 + ctx).min(lines.len());
-                            Some(
-                                lines[line_idx + 1..end]
-                                    .iter()
-                                    .map(|s| s.to_string())
-                                    .collect(),
-                            )
-                        } else {
-                            None
-                        };
-
-                        matches.push(GrepMatch {
-                            file: rel_path,
-                            line: line_idx + 1, // 1-indexed
-                            content: line.to_string(),
-                            context_before,
-                            context_after,
-                        });
-
-                        if matches.len() >= self.max_results {
-                            break;
-                        }
-                    }
-                }
-            }
-
-            if file_has_match {
+        // Search files concurrently, consuming results in path order so the
+        // output is stable across runs.
+        let per_file_limit = if files_only { 1 } else { self.max_results };
+        let mut searches = futures::stream::iter(files)
+            .map(|file_path| {
                 let rel_path = file_path
                     .strip_prefix(&context.cwd)
                     .unwrap_or(&file_path)
                     .to_string_lossy()
                     .to_string();
-                files_with_matches.push(rel_path);
+                let regex = regex.clone();
+                tokio::task::spawn_blocking(move || {
+                    search_file(&file_path, rel_path, &regex, before, after, per_file_limit)
+                })
+            })
+            .buffered(SEARCH_CONCURRENCY);
+
+        let mut matches: Vec<GrepMatch> = Vec::new();
+        let mut files_with_matches: Vec<String> = Vec::new();
+
+        while let Some(joined) = searches.next().await {
+            let Ok(found) = joined else { continue };
+            let Some(first) = found.first() else { continue };
+            files_with_matches.push(first.file.clone());
+
+            if !files_only {
+                let room = self.max_results - matches.len();
+                matches.extend(found.into_iter().take(room));
+                if matches.len() >= self.max_results {
+                    break;
+                }
             }
         }
 
@@ -718,6 +1169,215 @@ impl Tool for GrepTool {
     }
 }
 
+/// Search one file, returning up to `limit` matches.
+///
+/// Files that cannot be read as UTF-8 text are skipped.
+fn search_file(
+    path: &Path,
+    file: String,
+    regex: &Regex,
+    before: usize,
+    after: usize,
+    limit: usize,
+) -> Vec<GrepMatch> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+    let context = |range: &[&str]| range.iter().map(|s| s.to_string()).collect();
+
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .take(limit)
+        .map(|(idx, line)| GrepMatch {
+            file: file.clone(),
+            line: idx + 1, // 1-indexed
+            content: line.to_string(),
+            context_before: (before > 0)
+                .then(|| context(&lines[idx.saturating_sub(before)..idx])),
+            context_after: (after > 0)
+                .then(|| context(&lines[idx + 1..(idx + 1 + after).min(lines.len())])),
+        })
+        .collect()
+}
+
+/// Which files a [`GrepTool`] directory search visits.
+struct WalkOptions {
+    /// Include names starting with a dot.
+    hidden: bool,
+
+    /// Skip paths excluded by `.gitignore` files.
+    gitignore: bool,
+
+    /// Only include files whose path below the root matches.
+    glob: Option<glob::Pattern>,
+}
+
+impl WalkOptions {
+    /// Collect the files below `root`, sorted by path.
+    fn files(&self, root: &Path) -> Vec<PathBuf> {
+        let inherited = if self.gitignore {
+            IgnoreRule::load_ancestors(root)
+        } else {
+            Vec::new()
+        };
+        let mut files = Vec::new();
+        self.walk(root, root, &inherited, &mut files);
+        files.sort();
+        files
+    }
+
+    fn walk(&self, root: &Path, dir: &Path, inherited: &[IgnoreRule], files: &mut Vec<PathBuf>) {
+        let local = if self.gitignore {
+            IgnoreRule::load(dir)
+        } else {
+            Vec::new()
+        };
+        let rules: Cow<'_, [IgnoreRule]> = if local.is_empty() {
+            Cow::Borrowed(inherited)
+        } else {
+            Cow::Owned([inherited, &local].concat())
+        };
+
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // Symlinked directories are not followed, to avoid cycles.
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let is_dir = file_type.is_dir();
+
+            if name == ".git" || (!self.hidden && name.starts_with('.')) {
+                continue;
+            }
+            if is_ignored(&rules, &path, is_dir) {
+                continue;
+            }
+
+            if is_dir {
+                self.walk(root, &path, &rules, files);
+            } else if path.is_file()
+                && self.glob.as_ref().is_none_or(|glob| {
+                    glob.matches_path(path.strip_prefix(root).unwrap_or(&path))
+                })
+            {
+                files.push(path);
+            }
+        }
+    }
+}
+
+/// A single `.gitignore` pattern.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    /// Directory containing the `.gitignore` file.
+    base: PathBuf,
+
+    pattern: glob::Pattern,
+
+    /// `!pattern`: re-include a path excluded by an earlier rule.
+    negated: bool,
+
+    /// `pattern/`: only match directories.
+    dir_only: bool,
+
+    /// The pattern contains a slash, so it matches the path relative to
+    /// `base` rather than the file name at any depth.
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// Load the rules of `dir/.gitignore`, if present.
+    fn load(dir: &Path) -> Vec<Self> {
+        std::fs::read_to_string(dir.join(".gitignore"))
+            .map(|content| content.lines().filter_map(|l| Self::parse(dir, l)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Load the rules of the directories above `dir`, up to the enclosing
+    /// repository root (the nearest directory with a `.git` entry), from
+    /// the outermost in. Outside a repository there are none, as git would
+    /// not apply them either.
+    fn load_ancestors(dir: &Path) -> Vec<Self> {
+        if dir.join(".git").exists() {
+            return Vec::new();
+        }
+        let mut ancestors = Vec::new();
+        for ancestor in dir.ancestors().skip(1) {
+            ancestors.push(ancestor);
+            if ancestor.join(".git").exists() {
+                return ancestors.iter().rev().flat_map(|a| Self::load(a)).collect();
+            }
+        }
+        Vec::new()
+    }
+
+    fn parse(base: &Path, line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+
+        Some(Self {
+            base: base.to_path_buf(),
+            pattern: glob::Pattern::new(line).ok()?,
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(&self.base) else {
+            return false;
+        };
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        if self.anchored {
+            self.pattern.matches_path_with(relative, options)
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options))
+        }
+    }
+}
+
+/// Check whether the last rule matching `path` excludes it.
+fn is_ignored(rules: &[IgnoreRule], path: &Path, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .find(|rule| rule.matches(path, is_dir))
+        .is_some_and(|rule| !rule.negated)
+}
+
 /// Resolve a path relative to the working directory.
 fn resolve_path(path: &str, cwd: &std::path::Path) -> PathBuf {
     let p = std::path::Path::new(path);
@@ -814,6 +1474,109 @@ mod tests {
         assert_eq!(content, "Hello, Rust!");
     }
 
+    #[tokio::test]
+    async fn test_edit_with_expected_hash() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("script.sh");
+        std::fs::write(&file_path, "echo hello\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&file_path, std::fs::Permissions::from_mode(0o750)).unwrap();
+        }
+
+        let context = ToolContext {
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let read = ReadTool
+            .execute("read-1", serde_json::json!({ "path": "script.sh" }), &context)
+            .await
+            .unwrap();
+        let sha256 = read.output["sha256"].as_str().unwrap().to_string();
+
+        let result = EditTool::new()
+            .execute(
+                "edit-1",
+                serde_json::json!({
+                    "path": "script.sh",
+                    "old_string": "hello",
+                    "new_string": "world",
+                    "expected_sha256": sha256
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "{:?}", result.output);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "echo world\n");
+        assert_eq!(result.output["sha256"], content_sha256("echo world\n"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&file_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o750);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edit_conflict_on_hash_mismatch() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("notes.txt");
+        std::fs::write(&file_path, "draft one").unwrap();
+        let stale = content_sha256("draft one");
+
+        // Someone else edits the file after it was read.
+        std::fs::write(&file_path, "draft two").unwrap();
+
+        let context = ToolContext {
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let result = EditTool::new()
+            .execute(
+                "edit-1",
+                serde_json::json!({
+                    "path": "notes.txt",
+                    "old_string": "draft",
+                    "new_string": "final",
+                    "expected_sha256": stale
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        assert!(result.output.as_str().unwrap().starts_with("Conflict"));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "draft two");
+    }
+
+    #[tokio::test]
+    async fn test_staged_write_is_atomic() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.toml");
+        std::fs::write(&file_path, "old").unwrap();
+        let entries = || std::fs::read_dir(dir.path()).unwrap().count();
+
+        // A write that never commits (e.g. the process dies first) leaves
+        // the target intact.
+        let staged = StagedWrite::new(&file_path, b"new").await.unwrap();
+        assert_eq!(staged.temp.parent(), file_path.parent());
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "old");
+        assert_eq!(entries(), 2);
+        drop(staged);
+        assert_eq!(entries(), 1);
+
+        let staged = StagedWrite::new(&file_path, b"new").await.unwrap();
+        staged.commit().await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "new");
+        assert_eq!(entries(), 1);
+    }
+
     #[tokio::test]
     async fn test_glob_tool() {
         let dir = tempdir().unwrap();
@@ -842,6 +1605,159 @@ mod tests {
         assert_eq!(count, 3);
     }
 
+    #[tokio::test]
+    async fn test_glob_multiple_patterns() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/bin")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        for file in [
+            "Cargo.toml",
+            "README.md",
+            "src/lib.rs",
+            "src/bin/main.rs",
+            "src/bin/main_test.rs",
+            "target/debug/build.rs",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let context = ToolContext {
+            cwd: root.to_path_buf(),
+            ..Default::default()
+        };
+
+        let result = GlobTool::new()
+            .execute(
+                "test-1",
+                serde_json::json!({
+                    "pattern": "**/*.rs",
+                    "patterns": ["*.toml"],
+                    "exclude": ["target", "**/*_test.rs"]
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        let matches: Vec<String> = result.output["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m.as_str().unwrap().replace('\\', "/"))
+            .collect();
+        assert_eq!(matches, ["Cargo.toml", "src/bin/main.rs", "src/lib.rs"]);
+        assert_eq!(result.output["truncated"], false);
+    }
+
+    #[tokio::test]
+    async fn test_glob_result_cap() {
+        let dir = tempdir().unwrap();
+        let mut nested = dir.path().to_path_buf();
+        for depth in 0..20 {
+            nested.push(format!("d{}", depth));
+            std::fs::create_dir(&nested).unwrap();
+            std::fs::write(nested.join("file.txt"), "").unwrap();
+        }
+
+        let context = ToolContext {
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let result = GlobTool::new()
+            .execute(
+                "test-1",
+                serde_json::json!({ "pattern": "**/*.txt", "max_results": 3 }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["count"], 3);
+        assert_eq!(result.output["truncated"], true);
+
+        // The walk stops at the cap instead of reading the rest of the tree.
+        let matcher = GlobMatcher::new(dir.path(), &["**/*.txt"], &[]).unwrap();
+        let walk = GlobWalk::new(matcher, 1);
+        walk.enqueue(dir.path().to_path_buf());
+        walk.work();
+        assert_eq!(lock(&walk.found).len(), 1);
+        assert!(lock(&walk.visited).len() <= 3);
+    }
+
+    #[tokio::test]
+    async fn test_glob_outside_the_base_path() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::create_dir_all(root.join("lib/nested")).unwrap();
+        std::fs::write(root.join("lib/a.rs"), "").unwrap();
+        std::fs::write(root.join("lib/nested/b.rs"), "").unwrap();
+
+        let context = ToolContext {
+            cwd: root.join("app"),
+            ..Default::default()
+        };
+        let tool = GlobTool::new();
+        for (pattern, count) in [
+            ("../lib/*.rs".to_string(), 1),
+            ("../lib/**/*.rs".to_string(), 2),
+            (format!("{}/**/*.rs", root.join("lib").display()), 2),
+        ] {
+            let result = tool
+                .execute("test-1", serde_json::json!({ "pattern": pattern }), &context)
+                .await
+                .unwrap();
+            assert_eq!(result.output["count"], count, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_glob_walk_skips_unreachable_dirs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for sub in ["src/tools", "target/debug", "docs"] {
+            std::fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join("src/tools/mod.rs"), "").unwrap();
+
+        let matcher = GlobMatcher::new(root, &["src/*/*.rs", "*.toml"], &[]).unwrap();
+        assert_eq!(matcher.roots(), [root.to_path_buf()]);
+        assert!(matcher.may_contain(&root.join("src")));
+        assert!(matcher.may_contain(&root.join("src/tools")));
+        assert!(!matcher.may_contain(&root.join("src/tools/deeper")));
+        assert!(!matcher.may_contain(&root.join("target")));
+
+        let walk = GlobWalk::new(matcher, 10);
+        assert_eq!(walk.run(1), [root.join("src/tools/mod.rs")]);
+
+        let matcher = GlobMatcher::new(root, &["src/**/*.rs"], &[]).unwrap();
+        assert_eq!(matcher.roots(), [root.join("src")]);
+        assert!(matcher.may_contain(&root.join("src/tools/deeper/still")));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_glob_symlink_loop_terminates() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir(root.join("a")).unwrap();
+        std::fs::write(root.join("a/file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(root, root.join("a/loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("a"), root.join("a/self")).unwrap();
+
+        let context = ToolContext {
+            cwd: root.to_path_buf(),
+            ..Default::default()
+        };
+        let result = GlobTool::new()
+            .execute("test-1", serde_json::json!({ "pattern": "**/*.txt" }), &context)
+            .await
+            .unwrap();
+
+        assert_eq!(result.output["matches"], serde_json::json!(["a/file.txt"]));
+    }
+
     #[tokio::test]
     async fn test_grep_tool() {
         let dir = tempdir().unwrap();
@@ -869,4 +1785,153 @@ mod tests {
         let count = result.output.get("count").and_then(|v| v.as_u64()).unwrap();
         assert_eq!(count, 2);
     }
+
+    #[tokio::test]
+    async fn test_grep_regex_with_context() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("lib.rs"),
+            "use std::io;\n\nfn parse(input: &str) {}\n// end\nFN Render() {}\n",
+        )
+        .unwrap();
+
+        let context = ToolContext {
+            cwd: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let tool = GrepTool::new();
+        let result = tool
+            .execute(
+                "test-1",
+                serde_json::json!({
+                    "pattern": r"^fn \w+\(",
+                    "case_insensitive": true,
+                    "context_before": 2,
+                    "context_after": 1
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        let matches = result.output["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0]["file"], "lib.rs");
+        assert_eq!(matches[0]["line"], 3);
+        assert_eq!(matches[0]["content"], "fn parse(input: &str) {}");
+        assert_eq!(matches[0]["context_before"], serde_json::json!(["use std::io;", ""]));
+        assert_eq!(matches[0]["context_after"], serde_json::json!(["// end"]));
+        assert_eq!(matches[1]["line"], 5);
+        assert_eq!(matches[1]["context_after"], serde_json::json!([]));
+
+        // A fixed string matches literally, not as a regex.
+        let result = tool
+            .execute(
+                "test-2",
+                serde_json::json!({ "pattern": "&str)", "fixed_strings": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["count"], 1);
+        assert!(result.output["matches"][0].get("context_before").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_grep_subdirectory_applies_parent_gitignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("src/target")).unwrap();
+        std::fs::create_dir_all(root.join("src/generated")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n/src/generated\n").unwrap();
+        for file in ["src/lib.rs", "src/target/out.rs", "src/generated/out.rs"] {
+            std::fs::write(root.join(file), "needle\n").unwrap();
+        }
+
+        let context = ToolContext {
+            cwd: root.to_path_buf(),
+            ..Default::default()
+        };
+        let args = |no_ignore: bool| {
+            serde_json::json!({
+                "pattern": "needle",
+                "path": "src",
+                "files_only": true,
+                "no_ignore": no_ignore
+            })
+        };
+
+        let tool = GrepTool::new();
+        let result = tool.execute("test-1", args(false), &context).await.unwrap();
+        let files = result.output["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].as_str().unwrap().replace('\\', "/"), "src/lib.rs");
+
+        let result = tool.execute("test-2", args(true), &context).await.unwrap();
+        assert_eq!(result.output["files"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_grep_respects_gitignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/generated")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n*.log\n!keep.log\n").unwrap();
+        std::fs::write(root.join("src/.gitignore"), "/generated\n").unwrap();
+        for file in [
+            "src/main.rs",
+            "src/generated/out.rs",
+            "target/debug/build.rs",
+            "debug.log",
+            "keep.log",
+            ".env",
+        ] {
+            std::fs::write(root.join(file), "needle\n").unwrap();
+        }
+
+        let context = ToolContext {
+            cwd: root.to_path_buf(),
+            ..Default::default()
+        };
+
+        let tool = GrepTool::new();
+        let files = |output: &serde_json::Value| -> Vec<String> {
+            output["files"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f.as_str().unwrap().replace('\\', "/"))
+                .collect()
+        };
+
+        let result = tool
+            .execute(
+                "test-1",
+                serde_json::json!({ "pattern": "needle", "files_only": true }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(files(&result.output), ["keep.log", "src/main.rs"]);
+
+        let result = tool
+            .execute(
+                "test-2",
+                serde_json::json!({
+                    "pattern": "needle",
+                    "files_only": true,
+                    "hidden": true,
+                    "no_ignore": true
+                }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["count"], 6);
+        assert!(files(&result.output).contains(&".env".to_string()));
+    }
 }
@@ -7,12 +7,63 @@ use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::time::Instant;
 use tokio::process::Command;
 use tracing::debug;
 
+/// Resolve the repository a git tool call targets and check that git
+/// can use it.
+///
+/// The path comes from `repo_path` (or the older `path`), relative to the
+/// working directory. Bare repositories are accepted unless the tool needs
+/// a work tree.
+async fn open_repo(
+    args: &serde_json::Value,
+    ctx: &ToolContext,
+    needs_work_tree: bool,
+) -> std::result::Result<PathBuf, String> {
+    let path = args
+        .get("repo_path")
+        .or_else(|| args.get("path"))
+        .and_then(|v| v.as_str())
+        .map(|p| ctx.cwd.join(p))
+        .unwrap_or_else(|| ctx.cwd.clone());
+
+    if !path.is_dir() {
+        return Err(format!("Repository path does not exist: {}", path.display()));
+    }
+
+    match is_bare_repo(&path).await {
+        Some(true) if needs_work_tree => Err(format!(
+            "{} is a bare repository; this operation needs a work tree",
+            path.display()
+        )),
+        Some(_) => Ok(path),
+        None => Err(format!("Not a git repository: {}", path.display())),
+    }
+}
+
+/// Ask git whether `path` is a bare repository, or `None` if it is not a
+/// repository at all.
+async fn is_bare_repo(path: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--is-bare-repository"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
 /// Tool for getting git status.
 pub struct GitStatusTool;
 
@@ -42,7 +93,7 @@ impl Tool for GitStatusTool {
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "path": {
+                    "repo_path": {
                         "type": "string",
                         "description": "Repository path (defaults to current directory)"
                     },
@@ -53,6 +104,7 @@ impl Tool for GitStatusTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -65,11 +117,10 @@ impl Tool for GitStatusTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let path = args
-            .get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from)
-            .unwrap_or_else(|| ctx.cwd.clone());
+        let path = match open_repo(&args, ctx, true).await {
+            Ok(path) => path,
+            Err(message) => return Ok(ToolResult::error(tool_use_id, message)),
+        };
 
         let short = args
             .get("short")
@@ -99,7 +150,7 @@ impl Tool for GitStatusTool {
         if !output.status.success() {
             return Ok(ToolResult::error(
                 tool_use_id,
-                format!("git status failed: {}", stderr),
+                format!("git status failed: {}", stderr.trim()),
             ));
         }
 
@@ -199,7 +250,7 @@ impl Tool for GitLogTool {
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "path": {
+                    "repo_path": {
                         "type": "string",
                         "description": "Repository path (defaults to current directory)"
                     },
@@ -224,6 +275,7 @@ impl Tool for GitLogTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -236,11 +288,10 @@ impl Tool for GitLogTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let path = args
-            .get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from)
-            .unwrap_or_else(|| ctx.cwd.clone());
+        let path = match open_repo(&args, ctx, false).await {
+            Ok(path) => path,
+            Err(message) => return Ok(ToolResult::error(tool_use_id, message)),
+        };
 
         let count = args
             .get("count")
@@ -280,7 +331,7 @@ impl Tool for GitLogTool {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Ok(ToolResult::error(
                 tool_use_id,
-                format!("git log failed: {}", stderr),
+                format!("git log failed: {}", stderr.trim()),
             ));
         }
 
@@ -349,7 +400,7 @@ impl Tool for GitDiffTool {
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "path": {
+                    "repo_path": {
                         "type": "string",
                         "description": "Repository path (defaults to current directory)"
                     },
@@ -369,6 +420,7 @@ impl Tool for GitDiffTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -381,11 +433,10 @@ impl Tool for GitDiffTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let path = args
-            .get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from)
-            .unwrap_or_else(|| ctx.cwd.clone());
+        let path = match open_repo(&args, ctx, true).await {
+            Ok(path) => path,
+            Err(message) => return Ok(ToolResult::error(tool_use_id, message)),
+        };
 
         let staged = args
             .get("staged")
@@ -427,7 +478,7 @@ impl Tool for GitDiffTool {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Ok(ToolResult::error(
                 tool_use_id,
-                format!("git diff failed: {}", stderr),
+                format!("git diff failed: {}", stderr.trim()),
             ));
         }
 
@@ -494,7 +545,7 @@ impl Tool for GitBranchTool {
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
-                    "path": {
+                    "repo_path": {
                         "type": "string",
                         "description": "Repository path (defaults to current directory)"
                     },
@@ -510,6 +561,7 @@ impl Tool for GitBranchTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -522,11 +574,10 @@ impl Tool for GitBranchTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let path = args
-            .get("path")
-            .and_then(|v| v.as_str())
-            .map(PathBuf::from)
-            .unwrap_or_else(|| ctx.cwd.clone());
+        let path = match open_repo(&args, ctx, false).await {
+            Ok(path) => path,
+            Err(message) => return Ok(ToolResult::error(tool_use_id, message)),
+        };
 
         let all = args
             .get("all")
@@ -562,7 +613,7 @@ impl Tool for GitBranchTool {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Ok(ToolResult::error(
                 tool_use_id,
-                format!("git branch failed: {}", stderr),
+                format!("git branch failed: {}", stderr.trim()),
             ));
         }
 
@@ -618,6 +669,114 @@ impl Tool for GitBranchTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::tempdir;
+
+    /// Run git in `dir`, panicking on failure.
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    /// Create a repository with two commits on `main` and one uncommitted change.
+    fn temp_repo() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        git(dir.path(), &["init", "-q", "-b", "main"]);
+        std::fs::write(dir.path().join("README.md"), "hello\n").unwrap();
+        git(dir.path(), &["add", "README.md"]);
+        git(dir.path(), &["commit", "-q", "-m", "Initial commit"]);
+        std::fs::write(dir.path().join("README.md"), "hello world\n").unwrap();
+        git(dir.path(), &["commit", "-q", "-am", "Expand readme"]);
+        std::fs::write(dir.path().join("notes.txt"), "todo\n").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_tools_on_repo_path() {
+        let repo = temp_repo();
+        let ctx = ToolContext::default();
+        let args = serde_json::json!({ "repo_path": repo.path() });
+
+        let status = GitStatusTool::new()
+            .execute("status", args.clone(), &ctx)
+            .await
+            .unwrap();
+        assert!(!status.is_error, "{:?}", status.output);
+        assert_eq!(status.output["branch"], "main");
+        assert_eq!(status.output["untracked"], serde_json::json!(["notes.txt"]));
+
+        let log = GitLogTool::new().execute("log", args.clone(), &ctx).await.unwrap();
+        assert_eq!(log.output["count"], 2);
+        assert_eq!(log.output["commits"][0]["message"], "Expand readme");
+
+        let diff = GitDiffTool::new().execute("diff", args.clone(), &ctx).await.unwrap();
+        assert_eq!(diff.output["has_changes"], false);
+
+        // A relative repo_path resolves against the working directory.
+        let ctx = ToolContext {
+            cwd: repo.path().parent().unwrap().to_path_buf(),
+            ..Default::default()
+        };
+        let name = repo.path().file_name().unwrap().to_str().unwrap();
+        let branch = GitBranchTool::new()
+            .execute("branch", serde_json::json!({ "repo_path": name }), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(branch.output["current"], "main");
+    }
+
+    #[tokio::test]
+    async fn test_bare_repo() {
+        let repo = temp_repo();
+        let dir = tempdir().unwrap();
+        let bare = dir.path().join("mirror.git");
+        git(
+            dir.path(),
+            &["clone", "-q", "--bare", repo.path().to_str().unwrap(), "mirror.git"],
+        );
+
+        let ctx = ToolContext::default();
+        let args = serde_json::json!({ "repo_path": bare });
+
+        let log = GitLogTool::new().execute("log", args.clone(), &ctx).await.unwrap();
+        assert!(!log.is_error, "{:?}", log.output);
+        assert_eq!(log.output["count"], 2);
+
+        let branch = GitBranchTool::new()
+            .execute("branch", args.clone(), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(branch.output["current"], "main");
+
+        let status = GitStatusTool::new().execute("status", args, &ctx).await.unwrap();
+        assert!(status.is_error);
+        assert!(status.output.as_str().unwrap().contains("bare repository"));
+    }
+
+    #[tokio::test]
+    async fn test_not_a_repo() {
+        let dir = tempdir().unwrap();
+        let ctx = ToolContext::default();
+        let args = serde_json::json!({ "repo_path": dir.path() });
+
+        let result = GitLogTool::new().execute("log", args, &ctx).await.unwrap();
+        assert!(result.is_error);
+        assert_eq!(
+            result.output,
+            format!("Not a git repository: {}", dir.path().display())
+        );
+
+        let missing = serde_json::json!({ "repo_path": dir.path().join("missing") });
+        let result = GitStatusTool::new().execute("status", missing, &ctx).await.unwrap();
+        assert!(result.output.as_str().unwrap().starts_with("Repository path does not exist"));
+    }
 
     #[test]
     fn test_git_status_tool_creation() {
@@ -7,11 +7,20 @@ use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, To
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::time::{Duration, Instant};
 
+/// Maximum redirects followed for one request.
+const MAX_REDIRECTS: usize = 10;
+
 /// Tool for making HTTP requests.
+///
+/// By default requests to loopback, private, link-local and other
+/// internal addresses are refused, including via redirects, so the tool
+/// cannot reach cloud metadata endpoints or services on the host's network.
 pub struct HttpRequestTool {
     client: reqwest::Client,
+    allow_private: bool,
 }
 
 impl HttpRequestTool {
@@ -20,7 +29,184 @@ impl HttpRequestTool {
             .timeout(Duration::from_secs(30))
             .build()
             .unwrap_or_default();
-        Self { client }
+        Self {
+            client,
+            allow_private: false,
+        }
+    }
+
+    /// Allow requests to internal addresses.
+    pub fn allow_private_networks(mut self, allow: bool) -> Self {
+        self.allow_private = allow;
+        self
+    }
+
+    /// Send a request, following redirects itself so every hop's resolved
+    /// addresses can be checked.
+    ///
+    /// Each hop connects only to the addresses that were checked, so a
+    /// second DNS answer cannot point the request somewhere else. Credentials
+    /// are dropped once a redirect leaves the original origin.
+    async fn send_guarded(
+        &self,
+        mut method: reqwest::Method,
+        mut url: url::Url,
+        headers: &HashMap<String, String>,
+        mut body: Option<serde_json::Value>,
+        timeout: Duration,
+    ) -> std::result::Result<reqwest::Response, String> {
+        let mut headers = headers.clone();
+        for _ in 0..=MAX_REDIRECTS {
+            let host = url.host_str().ok_or("URL has no host")?.to_string();
+            let addrs = resolve_public(&url).await?;
+            let client = reqwest::Client::builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .resolve_to_addrs(&host, &addrs)
+                .build()
+                .map_err(|e| e.to_string())?;
+
+            let request =
+                build_request(&client, method.clone(), url.clone(), &headers, body.as_ref());
+            let response = request
+                .timeout(timeout)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok());
+            let Some(location) = location.filter(|_| status.is_redirection()) else {
+                return Ok(response);
+            };
+
+            let next = url.join(location).map_err(|e| format!("Invalid redirect: {}", e))?;
+            if !same_origin(&url, &next) {
+                strip_credentials(&mut headers);
+            }
+            url = next;
+            // 307 and 308 repeat the request as is; other redirects become GET.
+            if !matches!(status.as_u16(), 307 | 308) {
+                method = reqwest::Method::GET;
+                body = None;
+            }
+        }
+
+        Err(format!("Too many redirects (max {})", MAX_REDIRECTS))
+    }
+}
+
+/// Build a request with the given headers and JSON body.
+fn build_request(
+    client: &reqwest::Client,
+    method: reqwest::Method,
+    url: url::Url,
+    headers: &HashMap<String, String>,
+    body: Option<&serde_json::Value>,
+) -> reqwest::RequestBuilder {
+    let mut request = client.request(method, url);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    request
+}
+
+/// Headers that carry credentials for the origin they were set for.
+const CREDENTIAL_HEADERS: [&str; 3] = ["authorization", "cookie", "proxy-authorization"];
+
+/// Check whether two URLs share scheme, host and port.
+fn same_origin(a: &url::Url, b: &url::Url) -> bool {
+    a.scheme() == b.scheme()
+        && a.host_str() == b.host_str()
+        && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// Remove the headers that must not follow a redirect to another origin.
+fn strip_credentials(headers: &mut HashMap<String, String>) {
+    headers.retain(|name, _| !CREDENTIAL_HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h)));
+}
+
+/// Resolve a URL's host, refusing it if any address is internal.
+async fn resolve_public(url: &url::Url) -> std::result::Result<Vec<SocketAddr>, String> {
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = match url.host() {
+        Some(url::Host::Ipv4(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(url::Host::Ipv6(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(url::Host::Domain(domain)) => tokio::net::lookup_host((domain, port))
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", domain, e))?
+            .collect(),
+        None => return Err("URL has no host".to_string()),
+    };
+
+    match addrs.iter().find(|addr| is_internal(addr.ip())) {
+        Some(addr) => Err(format!(
+            "Blocked request to internal address {} for {}",
+            addr.ip(),
+            url.host_str().unwrap_or_default()
+        )),
+        None if addrs.is_empty() => Err(format!("No addresses found for {}", url)),
+        None => Ok(addrs),
+    }
+}
+
+/// Check whether an address is loopback, private, link-local or otherwise
+/// not publicly routable.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || a == 0
+                || a >= 240
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = embedded_ipv4(ip) {
+                return is_internal(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // Unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// The IPv4 address an IPv6 address stands for, for the forms that reach
+/// an IPv4 host: IPv4-mapped `::ffff:a.b.c.d`, IPv4-compatible `::a.b.c.d`,
+/// NAT64 `64:ff9b::a.b.c.d` and 6to4 `2002:aabb:ccdd::/48`.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let s = ip.segments();
+    let from = |hi: u16, lo: u16| {
+        let [a, b] = hi.to_be_bytes();
+        let [c, d] = lo.to_be_bytes();
+        Ipv4Addr::new(a, b, c, d)
+    };
+    match s {
+        [0, 0, 0, 0, 0, 0xffff, hi, lo] => Some(from(hi, lo)),
+        // `::` and `::1` are checked as IPv6 addresses.
+        [0, 0, 0, 0, 0, 0, hi, lo] if !ip.is_unspecified() && !ip.is_loopback() => {
+            Some(from(hi, lo))
+        }
+        [0x64, 0xff9b, 0, 0, 0, 0, hi, lo] => Some(from(hi, lo)),
+        [0x2002, hi, lo, ..] => Some(from(hi, lo)),
+        _ => None,
     }
 }
 
@@ -93,6 +279,7 @@ impl Tool for HttpRequestTool {
                 },
                 "required": ["method", "url"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -113,14 +300,13 @@ impl Tool for HttpRequestTool {
         let method = args.method.to_uppercase();
         let timeout = Duration::from_secs(args.timeout_secs.unwrap_or(30));
 
-        // Build the request
-        let mut request = match method.as_str() {
-            "GET" => self.client.get(&args.url),
-            "POST" => self.client.post(&args.url),
-            "PUT" => self.client.put(&args.url),
-            "PATCH" => self.client.patch(&args.url),
-            "DELETE" => self.client.delete(&args.url),
-            "HEAD" => self.client.head(&args.url),
+        let method = match method.as_str() {
+            "GET" => reqwest::Method::GET,
+            "POST" => reqwest::Method::POST,
+            "PUT" => reqwest::Method::PUT,
+            "PATCH" => reqwest::Method::PATCH,
+            "DELETE" => reqwest::Method::DELETE,
+            "HEAD" => reqwest::Method::HEAD,
             _ => {
                 return Ok(ToolResult::error(
                     tool_use_id,
@@ -129,23 +315,30 @@ impl Tool for HttpRequestTool {
             }
         };
 
-        // Set timeout
-        request = request.timeout(timeout);
-
-        // Add headers
-        if let Some(headers) = args.headers {
-            for (key, value) in headers {
-                request = request.header(&key, &value);
+        let url = match url::Url::parse(&args.url) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => url,
+            Ok(_) => {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    "Only HTTP and HTTPS URLs are supported",
+                ));
             }
-        }
-
-        // Add body
-        if let Some(body) = args.body {
-            request = request.json(&body);
-        }
+            Err(e) => return Ok(ToolResult::error(tool_use_id, format!("Invalid URL: {}", e))),
+        };
+        let headers = args.headers.unwrap_or_default();
 
         // Execute request
-        match request.send().await {
+        let sent = if self.allow_private {
+            build_request(&self.client, method, url, &headers, args.body.as_ref())
+                .timeout(timeout)
+                .send()
+                .await
+                .map_err(|e| e.to_string())
+        } else {
+            self.send_guarded(method, url, &headers, args.body, timeout).await
+        };
+
+        match sent {
             Ok(response) => {
                 let status = response.status();
                 let status_code = status.as_u16();
@@ -232,6 +425,7 @@ impl Tool for UrlParseTool {
                 },
                 "required": ["url"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -342,6 +536,7 @@ impl Tool for UrlBuildTool {
                 },
                 "required": ["base"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -400,6 +595,119 @@ impl Tool for UrlBuildTool {
 mod tests {
     use super::*;
 
+    async fn request(tool: &HttpRequestTool, url: &str) -> ToolResult {
+        tool.execute(
+            "test",
+            json!({ "method": "GET", "url": url }),
+            &ToolContext::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_internal_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::127.0.0.1",
+            "::10.0.0.1",
+            "64:ff9b::169.254.169.254",
+            "64:ff9b::7f00:1",
+            "2002:a9fe:a9fe::1",
+            "2002:c0a8:0101::",
+        ] {
+            assert!(is_internal(ip.parse().unwrap()), "{} should be internal", ip);
+        }
+        for ip in [
+            "93.184.216.34",
+            "1.1.1.1",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "64:ff9b::5db8:d822",
+            "2002:5db8:d822::1",
+        ] {
+            assert!(!is_internal(ip.parse().unwrap()), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn test_credentials_dropped_on_cross_origin_redirect() {
+        let url = |s: &str| url::Url::parse(s).unwrap();
+        let origin = url("https://api.example.com/");
+        assert!(same_origin(&origin, &url("https://api.example.com:443/b")));
+        for other in [
+            "http://api.example.com/a",
+            "https://evil.example.net/a",
+            "https://api.example.com:8443/a",
+        ] {
+            assert!(!same_origin(&origin, &url(other)), "{}", other);
+        }
+
+        let mut headers: HashMap<String, String> = [
+            ("Authorization", "Bearer secret"),
+            ("cookie", "session=1"),
+            ("Proxy-Authorization", "Basic x"),
+            ("Accept", "application/json"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        strip_credentials(&mut headers);
+        assert_eq!(headers.keys().collect::<Vec<_>>(), ["Accept"]);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_and_loopback_blocked() {
+        let tool = HttpRequestTool::new();
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://localhost:8080/admin",
+            "http://[::1]/",
+            "http://[::ffff:169.254.169.254]/",
+        ] {
+            let result = request(&tool, url).await;
+            assert!(result.is_error, "{} should be blocked", url);
+            assert!(result.output.as_str().unwrap().contains("internal address"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_address_passes_guard() {
+        let url = url::Url::parse("https://93.184.216.34/").unwrap();
+        let addrs = resolve_public(&url).await.unwrap();
+        assert_eq!(addrs, ["93.184.216.34:443".parse().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_private_networks_opt_in() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true})))
+            .mount(&server)
+            .await;
+        let url = format!("{}/status", server.uri());
+
+        assert!(request(&HttpRequestTool::new(), &url).await.is_error);
+
+        let result = request(&HttpRequestTool::new().allow_private_networks(true), &url).await;
+        assert!(!result.is_error);
+        assert_eq!(result.output["body"]["ok"], true);
+    }
+
     #[tokio::test]
     async fn test_url_parse() {
         let tool = UrlParseTool::new();
@@ -50,6 +50,14 @@ impl Tool for JsonQueryTool {
                 },
                 "required": ["json", "path"]
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "result": {"description": "Value found at the path"},
+                    "path": {"type": "string"}
+                },
+                "required": ["result", "path"]
+            })),
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -251,6 +259,7 @@ impl Tool for JsonTransformTool {
                 },
                 "required": ["json"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -448,6 +457,7 @@ impl Tool for YamlTool {
                 },
                 "required": ["input"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
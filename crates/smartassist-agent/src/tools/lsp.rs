@@ -384,6 +384,7 @@ impl Tool for LspTool {
                 },
                 "required": ["operation", "filePath", "line", "character"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
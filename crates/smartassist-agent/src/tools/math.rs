@@ -3,15 +3,24 @@
 //! Provides tools for mathematical calculations,
 //! random number generation, and UUID generation.
 
+use super::expr;
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
-use rand::Rng;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use std::time::Instant;
 use tracing::debug;
 
-/// Tool for basic math calculations.
+/// Fractional digits shown for expression results unless `precision` is
+/// given.
+const EXPRESSION_DIGITS: u32 = 20;
+
+/// Tool for math calculations.
+///
+/// Either applies one `operation` to `a` and `b` as floats, or evaluates
+/// an `expression` exactly, with optional units (see [`expr`]).
 pub struct CalcTool;
 
 impl CalcTool {
@@ -35,10 +44,19 @@ impl Tool for CalcTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "calc".to_string(),
-            description: "Perform basic mathematical calculations.".to_string(),
+            description: "Perform mathematical calculations: one operation on two numbers, or an \
+                          exact expression such as \"30!\", \"0.1 + 0.2\" or \"3 GB / 128 MB\"."
+                .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
+                    "expression": {
+                        "type": "string",
+                        "description": "Expression evaluated exactly: numbers with optional units \
+                                        (B, KB..PB, KiB..PiB, mm..km, in, ft, mi, g, kg, lb, \
+                                        s, min, h, d...), + - * / % ^ !, parentheses, \
+                                        abs/floor/ceil/round/sqrt/min/max and 'to <unit>'"
+                    },
                     "operation": {
                         "type": "string",
                         "enum": ["add", "subtract", "multiply", "divide", "power", "sqrt", "abs", "round", "floor", "ceil", "mod", "min", "max"],
@@ -55,11 +73,30 @@ impl Tool for CalcTool {
                     "precision": {
                         "type": "integer",
                         "default": 10,
-                        "description": "Decimal precision for rounding"
+                        "description": "Decimal precision for rounding, or digits shown for an \
+                                        expression result (default 20)"
                     }
-                },
-                "required": ["operation", "a"]
+                }
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "result": {
+                        "type": ["number", "string"],
+                        "description": "Number for an operation; exact decimal text for an \
+                                        expression"
+                    },
+                    "operation": {"type": "string"},
+                    "a": {"type": "number"},
+                    "b": {"type": ["number", "null"]},
+                    "expression": {"type": "string"},
+                    "exact": {"type": "boolean"},
+                    "fraction": {"type": ["string", "null"]},
+                    "value": {"type": ["number", "null"]},
+                    "unit": {"type": ["string", "null"]}
+                },
+                "required": ["result"]
+            })),
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -72,6 +109,34 @@ impl Tool for CalcTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
+        if let Some(expression) = args.get("expression").and_then(|v| v.as_str()) {
+            let digits = args
+                .get("precision")
+                .and_then(|v| v.as_u64())
+                .map_or(EXPRESSION_DIGITS, |p| p.min(1_000) as u32);
+            let value = match expr::evaluate(expression) {
+                Ok(value) => value,
+                Err(message) => return Ok(ToolResult::error(tool_use_id, message)),
+            };
+            let shown = value.value();
+            let (result, exact) = expr::to_decimal(&shown, digits);
+
+            debug!("Calc: {} = {}", expression, result);
+
+            return Ok(ToolResult::success(
+                tool_use_id,
+                serde_json::json!({
+                    "expression": expression,
+                    "result": result,
+                    "exact": exact && value.is_exact(),
+                    "fraction": (!shown.is_integer()).then(|| shown.to_string()),
+                    "value": expr::to_f64(&shown),
+                    "unit": value.unit().map(expr::Unit::symbol),
+                }),
+            )
+            .with_duration(start.elapsed()));
+        }
+
         let operation = args
             .get("operation")
             .and_then(|v| v.as_str())
@@ -128,6 +193,9 @@ impl Tool for CalcTool {
             "ceil" => a.ceil(),
             "mod" => {
                 let b = b.ok_or_else(|| crate::error::AgentError::tool_execution("b is required for mod"))?;
+                if b == 0.0 {
+                    return Ok(ToolResult::error(tool_use_id, "Division by zero"));
+                }
                 a % b
             }
             "min" => {
@@ -146,6 +214,13 @@ impl Tool for CalcTool {
             }
         };
 
+        if !result.is_finite() {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                "Result overflows a 64-bit float; use 'expression' for exact arithmetic",
+            ));
+        }
+
         let duration = start.elapsed();
 
         debug!("Calc: {} = {}", operation, result);
@@ -167,7 +242,16 @@ impl Tool for CalcTool {
     }
 }
 
+/// Key in [`ToolContext::data`] holding the run-wide random seed.
+pub const RANDOM_SEED_KEY: &str = "random_seed";
+
 /// Tool for generating random values.
+///
+/// Output comes from the thread's cryptographically secure generator
+/// unless a seed is given, either as the `seed` argument or run-wide under
+/// [`RANDOM_SEED_KEY`] in the context. A `seed` argument replays exactly;
+/// a run-wide seed is combined with the tool call ID so that each call in
+/// a run differs but a replay of the run repeats every value.
 pub struct RandomTool;
 
 impl RandomTool {
@@ -231,9 +315,25 @@ impl Tool for RandomTool {
                         "type": "integer",
                         "default": 1,
                         "description": "Number of values to generate"
+                    },
+                    "seed": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Seed for reproducible output; the same seed and \
+                                        arguments always give the same result"
                     }
                 }
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "result": {"description": "Generated value, or a list of them when count > 1"},
+                    "type": {"type": "string"},
+                    "count": {"type": "integer"},
+                    "seed": {"type": "integer", "description": "Seed used, when seeded"}
+                },
+                "required": ["result", "type", "count"]
+            })),
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -242,7 +342,7 @@ impl Tool for RandomTool {
         &self,
         tool_use_id: &str,
         args: serde_json::Value,
-        _ctx: &ToolContext,
+        ctx: &ToolContext,
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
@@ -256,7 +356,26 @@ impl Tool for RandomTool {
             .and_then(|v| v.as_u64())
             .unwrap_or(1) as usize;
 
-        let mut rng = rand::thread_rng();
+        let seed = match args.get("seed") {
+            None | Some(serde_json::Value::Null) => ctx
+                .data
+                .get(RANDOM_SEED_KEY)
+                .and_then(|v| v.as_u64())
+                .map(|seed| call_seed(seed, tool_use_id)),
+            Some(v) => match v.as_u64() {
+                Some(seed) => Some(seed),
+                None => {
+                    return Ok(ToolResult::error(
+                        tool_use_id,
+                        "seed must be a non-negative integer",
+                    ))
+                }
+            },
+        };
+        let mut rng: Box<dyn RngCore> = match seed {
+            Some(seed) => Box::new(ChaCha20Rng::seed_from_u64(seed)),
+            None => Box::new(rand::thread_rng()),
+        };
 
         let result: serde_json::Value = match value_type {
             "integer" => {
@@ -293,14 +412,14 @@ impl Tool for RandomTool {
                     _ => "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789".chars().collect(),
                 };
 
-                let generate_string = |rng: &mut rand::rngs::ThreadRng, len: usize| -> String {
+                let generate_string = |rng: &mut dyn RngCore, len: usize| -> String {
                     (0..len).map(|_| chars[rng.gen_range(0..chars.len())]).collect()
                 };
 
                 if count == 1 {
-                    serde_json::json!(generate_string(&mut rng, length))
+                    serde_json::json!(generate_string(&mut *rng, length))
                 } else {
-                    let values: Vec<String> = (0..count).map(|_| generate_string(&mut rng, length)).collect();
+                    let values: Vec<String> = (0..count).map(|_| generate_string(&mut *rng, length)).collect();
                     serde_json::json!(values)
                 }
             }
@@ -353,15 +472,15 @@ impl Tool for RandomTool {
 
         debug!("Random {}: generated", value_type);
 
-        Ok(ToolResult::success(
-            tool_use_id,
-            serde_json::json!({
-                "result": result,
-                "type": value_type,
-                "count": count,
-            }),
-        )
-        .with_duration(duration))
+        let mut output = serde_json::json!({
+            "result": result,
+            "type": value_type,
+            "count": count,
+        });
+        if let Some(seed) = seed {
+            output["seed"] = serde_json::json!(seed);
+        }
+        Ok(ToolResult::success(tool_use_id, output).with_duration(duration))
     }
 
     fn group(&self) -> ToolGroup {
@@ -369,6 +488,16 @@ impl Tool for RandomTool {
     }
 }
 
+/// Seed for one call under a run-wide `seed`, mixing in the call ID with
+/// FNV-1a so that the result is stable across builds and platforms.
+fn call_seed(seed: u64, tool_use_id: &str) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    seed.to_le_bytes()
+        .iter()
+        .chain(tool_use_id.as_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(*byte)).wrapping_mul(PRIME))
+}
+
 /// Tool for generating UUIDs.
 pub struct UuidTool;
 
@@ -416,6 +545,19 @@ impl Tool for UuidTool {
                     }
                 }
             }),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "result": {
+                        "type": ["string", "array"],
+                        "items": {"type": "string"}
+                    },
+                    "version": {"type": "string"},
+                    "format": {"type": "string"},
+                    "count": {"type": "integer"}
+                },
+                "required": ["result", "version", "format", "count"]
+            })),
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -428,7 +570,7 @@ impl Tool for UuidTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let _version = args
+        let version = args
             .get("version")
             .and_then(|v| v.as_str())
             .unwrap_or("v4");
@@ -451,14 +593,17 @@ impl Tool for UuidTool {
             }
         };
 
-        let generate_uuid = || -> uuid::Uuid {
-            // Always use v4 for now (v7 requires newer uuid crate version)
-            uuid::Uuid::new_v4()
+        let generate_uuid: fn() -> uuid::Uuid = match version {
+            "v4" => uuid::Uuid::new_v4,
+            "v7" => uuid::Uuid::now_v7,
+            _ => {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    format!("Unsupported UUID version '{}', expected v4 or v7", version),
+                ));
+            }
         };
 
-        // Note: v7 requested but using v4 as fallback
-        let actual_version = "v4";
-
         let result: serde_json::Value = if count == 1 {
             serde_json::json!(format_uuid(generate_uuid()))
         } else {
@@ -468,13 +613,13 @@ impl Tool for UuidTool {
 
         let duration = start.elapsed();
 
-        debug!("UUID {}: generated {} UUIDs", actual_version, count);
+        debug!("UUID {}: generated {} UUIDs", version, count);
 
         Ok(ToolResult::success(
             tool_use_id,
             serde_json::json!({
                 "result": result,
-                "version": actual_version,
+                "version": version,
                 "format": format,
                 "count": count,
             }),
@@ -509,6 +654,40 @@ mod tests {
         assert_eq!(tool.name(), "uuid");
     }
 
+    #[tokio::test]
+    async fn test_uuid_versions() {
+        let tool = UuidTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"count": 2}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output["version"], "v4");
+        let id = result.output["result"][0].as_str().unwrap();
+        assert_eq!(uuid::Uuid::parse_str(id).unwrap().get_version_num(), 4);
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"version": "v7", "count": 50}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output["version"], "v7");
+        let ids: Vec<&str> = result.output["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(smartassist_core::id::uuid_v7_timestamp(ids[0]).is_some());
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"version": "v1"}), &ctx)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
     #[tokio::test]
     async fn test_calc_add() {
         let tool = CalcTool::new();
@@ -579,6 +758,82 @@ mod tests {
         assert!(result.is_error);
     }
 
+    #[tokio::test]
+    async fn test_calc_expression_is_exact() {
+        let tool = CalcTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"expression": "0.1 + 0.2"}), &ctx)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.output["result"], "0.3");
+        assert_eq!(result.output["exact"], true);
+        assert_eq!(result.output["fraction"], "3/10");
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"expression": "25!"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output["result"], "15511210043330985984000000");
+        assert!(result.output["fraction"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_calc_expression_with_units() {
+        let tool = CalcTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute("test_id", serde_json::json!({"expression": "1.5 GiB to MiB"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output["result"], "1536");
+        assert_eq!(result.output["unit"], "MiB");
+        assert_eq!(result.output["value"], 1536.0);
+    }
+
+    #[tokio::test]
+    async fn test_calc_expression_errors() {
+        let tool = CalcTool::new();
+        let ctx = ToolContext::default();
+
+        for expression in ["1 / (2 - 2)", "2 ^ 99999999", "__import__('os')"] {
+            let result = tool
+                .execute("test_id", serde_json::json!({"expression": expression}), &ctx)
+                .await
+                .unwrap();
+            assert!(result.is_error, "{}", expression);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_calc_float_overflow_is_error() {
+        let tool = CalcTool::new();
+        let ctx = ToolContext::default();
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"operation": "power", "a": 10, "b": 400}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+
+        let result = tool
+            .execute(
+                "test_id",
+                serde_json::json!({"operation": "mod", "a": 10, "b": 0}),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
     #[tokio::test]
     async fn test_random_integer() {
         let tool = RandomTool::new();
@@ -647,6 +902,70 @@ mod tests {
         assert!(["a", "b", "c"].contains(&value));
     }
 
+    async fn random(id: &str, args: serde_json::Value, ctx: &ToolContext) -> serde_json::Value {
+        let result = RandomTool::new().execute(id, args, ctx).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        result.output
+    }
+
+    #[tokio::test]
+    async fn test_random_same_seed_same_sequence() {
+        let ctx = ToolContext::default();
+        let cases = [
+            serde_json::json!({"type": "integer", "max": 1_000_000, "count": 8, "seed": 42}),
+            serde_json::json!({"type": "float", "count": 4, "seed": 42}),
+            serde_json::json!({"type": "string", "length": 24, "seed": 42}),
+            serde_json::json!({"type": "shuffle", "items": [1, 2, 3, 4, 5, 6, 7, 8], "seed": 42}),
+            serde_json::json!({"type": "bytes", "length": 32, "seed": 42}),
+        ];
+        for args in cases {
+            let first = random("a", args.clone(), &ctx).await;
+            let again = random("b", args.clone(), &ctx).await;
+            assert_eq!(first["result"], again["result"], "{}", args);
+            assert_eq!(first["seed"], 42);
+        }
+
+        let bytes = |seed: u64| serde_json::json!({"type": "bytes", "length": 32, "seed": seed});
+        assert_ne!(
+            random("a", bytes(42), &ctx).await["result"],
+            random("a", bytes(43), &ctx).await["result"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_random_unseeded_calls_differ() {
+        let ctx = ToolContext::default();
+        let args = serde_json::json!({"type": "bytes", "length": 32});
+
+        let first = random("a", args.clone(), &ctx).await;
+        let second = random("a", args, &ctx).await;
+        assert_ne!(first["result"], second["result"]);
+        assert!(first.get("seed").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_random_run_seed_from_context() {
+        let mut ctx = ToolContext::default();
+        ctx.data.insert(RANDOM_SEED_KEY.to_string(), serde_json::json!(7));
+        let args = serde_json::json!({"type": "integer", "max": 1_000_000, "count": 8});
+
+        // Replaying a call repeats it; another call in the run differs.
+        let first = random("call_1", args.clone(), &ctx).await;
+        assert_eq!(random("call_1", args.clone(), &ctx).await, first);
+        assert_ne!(random("call_2", args.clone(), &ctx).await["result"], first["result"]);
+        assert_eq!(first["seed"], call_seed(7, "call_1"));
+
+        // An explicit seed takes precedence.
+        let mut seeded = args.clone();
+        seeded["seed"] = serde_json::json!(99);
+        assert_eq!(random("call_1", seeded, &ctx).await["seed"], 99);
+
+        let mut invalid = args;
+        invalid["seed"] = serde_json::json!(-1);
+        let result = RandomTool::new().execute("call_1", invalid, &ctx).await.unwrap();
+        assert!(result.is_error);
+    }
+
     #[tokio::test]
     async fn test_uuid_v4() {
         let tool = UuidTool::new();
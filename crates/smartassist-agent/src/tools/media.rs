@@ -3,11 +3,13 @@
 //! - [`ImageTool`] - Analyze images with vision models
 //! - [`TtsTool`] - Text to speech conversion
 
+use super::tts::{self, Audio, TtsBackend, TtsConfig, DEFAULT_CHUNK_CHARS};
 use super::{Tool, ToolContext};
 use crate::error::AgentError;
 use crate::Result;
 use async_trait::async_trait;
 use base64::Engine;
+use futures::StreamExt;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
 use std::path::Path;
 use std::sync::Arc;
@@ -86,6 +88,7 @@ impl Tool for ImageTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -186,16 +189,17 @@ impl Tool for ImageTool {
     }
 }
 
-/// TTS tool - Text to speech conversion.
+/// TTS tool - Text to speech through a [`TtsBackend`].
 pub struct TtsTool {
-    /// Default voice to use.
-    default_voice: String,
-    /// HTTP client for API requests.
-    client: reqwest::Client,
-    /// OpenAI API key for TTS calls.
-    api_key: Option<String>,
-    /// Base URL for the TTS API.
-    base_url: String,
+    /// Backend to synthesize with, or why speech is unavailable.
+    backend: std::result::Result<Arc<dyn TtsBackend>, String>,
+    /// Voice used when a request names none; the backend's first voice
+    /// otherwise.
+    default_voice: Option<String>,
+    /// Chunk size for streamed synthesis, in characters.
+    chunk_chars: usize,
+    /// Configuration the deprecated builder methods change.
+    legacy: TtsConfig,
 }
 
 impl Default for TtsTool {
@@ -205,33 +209,118 @@ impl Default for TtsTool {
 }
 
 impl TtsTool {
+    /// Create a TTS tool configured from the environment.
+    ///
+    /// See [`TtsConfig::from_env`].
     pub fn new() -> Self {
-        let api_key = std::env::var("OPENAI_API_KEY").ok();
+        match TtsConfig::from_env() {
+            Ok(config) => Self::from_config(&config),
+            Err(reason) => Self::disabled(reason),
+        }
+    }
+
+    /// Create a TTS tool from configuration.
+    ///
+    /// A missing API key leaves the tool disabled, reporting what to set.
+    pub fn from_config(config: &TtsConfig) -> Self {
+        let backend = config.backend(tts::default_client()).map(Arc::from);
         Self {
-            default_voice: "alloy".to_string(),
-            client: reqwest::Client::new(),
-            api_key,
-            base_url: "https://api.openai.com".to_string(),
+            backend,
+            default_voice: config.voice.clone(),
+            chunk_chars: config.chunk_chars.unwrap_or(DEFAULT_CHUNK_CHARS),
+            legacy: config.clone(),
         }
     }
 
-    /// Set the default voice.
-    pub fn with_default_voice(mut self, voice: impl Into<String>) -> Self {
-        self.default_voice = voice.into();
-        self
+    /// Create a TTS tool using `backend`.
+    pub fn with_backend(backend: Arc<dyn TtsBackend>) -> Self {
+        Self {
+            backend: Ok(backend),
+            ..Self::disabled(String::new())
+        }
     }
 
-    /// Set the API key for TTS requests.
+    fn disabled(reason: String) -> Self {
+        Self {
+            backend: Err(reason),
+            default_voice: None,
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            legacy: TtsConfig::default(),
+        }
+    }
+
+    /// Set the API key for TTS requests, switching to the OpenAI backend.
+    #[deprecated(note = "use `TtsTool::with_backend` with an `OpenAiTts`")]
     pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
-        self.api_key = Some(key.into());
+        self.legacy.backend = tts::TtsProvider::OpenAi;
+        self.legacy.api_key = Some(key.into());
+        self.backend = self.legacy.backend(tts::default_client()).map(Arc::from);
         self
     }
 
-    /// Set the base URL for the TTS API.
+    /// Set the base URL for the TTS API, switching to the OpenAI backend.
+    #[deprecated(note = "use `TtsTool::with_backend` with an `OpenAiTts`")]
     pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
-        self.base_url = url.into();
+        self.legacy.backend = tts::TtsProvider::OpenAi;
+        self.legacy.endpoint = Some(url.into());
+        self.backend = self.legacy.backend(tts::default_client()).map(Arc::from);
+        self
+    }
+
+    /// Set the default voice.
+    pub fn with_default_voice(mut self, voice: impl Into<String>) -> Self {
+        self.default_voice = Some(voice.into());
         self
     }
+
+    /// Set the chunk size for streamed synthesis, in characters.
+    pub fn with_chunk_chars(mut self, chars: usize) -> Self {
+        self.chunk_chars = chars;
+        self
+    }
+
+    /// Whether a backend is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.backend.is_ok()
+    }
+
+    /// Synthesize `text` chunk by chunk, writing each chunk to its own
+    /// numbered file as soon as it is ready.
+    async fn stream_to_files(
+        &self,
+        backend: &dyn TtsBackend,
+        text: &str,
+        voice: &str,
+        speed: f64,
+        base: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let stem = Path::new(base).with_extension("");
+        let mut chunks = Vec::new();
+        let mut stream = backend.synthesize_stream(text, voice, speed, self.chunk_chars);
+        while let Some(audio) = stream.next().await {
+            let audio = audio?;
+            let path = format!(
+                "{}-{:03}.{}",
+                stem.display(),
+                chunks.len() + 1,
+                audio.format.extension()
+            );
+            write_audio(&path, &audio).await?;
+            chunks.push(serde_json::json!({
+                "index": chunks.len(),
+                "output": path,
+                "bytes": audio.bytes.len(),
+            }));
+        }
+        Ok(chunks)
+    }
+}
+
+/// Write synthesized audio to `path`.
+async fn write_audio(path: &str, audio: &Audio) -> Result<()> {
+    tokio::fs::write(path, &audio.bytes).await.map_err(|e| {
+        AgentError::tool_execution(format!("Failed to write audio to '{}': {}", path, e))
+    })
 }
 
 #[async_trait]
@@ -241,6 +330,14 @@ impl Tool for TtsTool {
     }
 
     fn definition(&self) -> ToolDefinition {
+        let mut voice = serde_json::json!({
+            "type": "string",
+            "description": "Voice to use"
+        });
+        if let Ok(backend) = &self.backend {
+            voice["enum"] = serde_json::json!(backend.voices());
+        }
+
         ToolDefinition {
             name: "tts".to_string(),
             description: "Convert text to speech audio. Generates audio files from text."
@@ -252,11 +349,7 @@ impl Tool for TtsTool {
                         "type": "string",
                         "description": "Text to convert to speech"
                     },
-                    "voice": {
-                        "type": "string",
-                        "enum": ["alloy", "echo", "fable", "onyx", "nova", "shimmer"],
-                        "description": "Voice to use"
-                    },
+                    "voice": voice,
                     "output": {
                         "type": "string",
                         "description": "Output file path (optional)"
@@ -264,10 +357,16 @@ impl Tool for TtsTool {
                     "speed": {
                         "type": "number",
                         "description": "Speech speed (0.25 to 4.0, default 1.0)"
+                    },
+                    "stream": {
+                        "type": "boolean",
+                        "description": "Synthesize long text in chunks, each written to a \
+                                        numbered file as soon as it is ready (default false)"
                     }
                 },
                 "required": ["text"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -285,20 +384,10 @@ impl Tool for TtsTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| AgentError::tool_execution("Missing 'text' argument"))?;
 
-        let voice = args
-            .get("voice")
-            .and_then(|v| v.as_str())
-            .unwrap_or(&self.default_voice);
-
+        let requested_voice = args.get("voice").and_then(|v| v.as_str());
         let speed = args.get("speed").and_then(|v| v.as_f64()).unwrap_or(1.0);
         let output = args.get("output").and_then(|v| v.as_str());
-
-        debug!(
-            "TTS: {} chars, voice={}, speed={}",
-            text.len(),
-            voice,
-            speed
-        );
+        let stream = args.get("stream").and_then(|v| v.as_bool()).unwrap_or(false);
 
         // Validate speed range.
         if !(0.25..=4.0).contains(&speed) {
@@ -307,77 +396,99 @@ impl Tool for TtsTool {
             ));
         }
 
-        let output_path = output
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| format!("/tmp/tts_{}.mp3", uuid::Uuid::new_v4()));
-
-        let api_key = match &self.api_key {
-            Some(key) => key.clone(),
-            None => {
-                // No API key configured -- return informational result.
+        let backend = match &self.backend {
+            Ok(backend) => backend,
+            Err(reason) => {
+                // No backend configured -- return informational result.
                 let result = serde_json::json!({
                     "text_length": text.len(),
-                    "voice": voice,
+                    "voice": requested_voice.or(self.default_voice.as_deref()),
                     "generated": false,
-                    "message": "TTS API key not configured. Set OPENAI_API_KEY."
+                    "message": reason
                 });
                 let duration = start.elapsed();
                 return Ok(ToolResult::success(tool_use_id, result).with_duration(duration));
             }
         };
 
-        // Call the OpenAI TTS API.
-        let url = format!("{}/v1/audio/speech", self.base_url);
-        let body = serde_json::json!({
-            "model": "tts-1",
-            "input": text,
-            "voice": voice,
-            "speed": speed
-        });
-
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| {
-                AgentError::tool_execution(format!("TTS API request failed: {}", e))
+        let voices = backend.voices();
+        let voice = requested_voice
+            .or(self.default_voice.as_deref())
+            .or(voices.first().map(String::as_str))
+            .ok_or_else(|| {
+                AgentError::tool_execution(format!(
+                    "The {} TTS backend has no voices configured",
+                    backend.name()
+                ))
             })?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_body = response.text().await.unwrap_or_default();
+        if !voices.iter().any(|v| v == voice) {
             return Err(AgentError::tool_execution(format!(
-                "TTS API returned {}: {}",
-                status, error_body
+                "Voice '{}' is not supported by the {} TTS backend. Available voices: {}",
+                voice,
+                backend.name(),
+                voices.join(", ")
             )));
         }
 
-        let audio_bytes = response.bytes().await.map_err(|e| {
-            AgentError::tool_execution(format!("Failed to read TTS response body: {}", e))
-        })?;
-        let byte_count = audio_bytes.len();
+        debug!(
+            "TTS via {}: {} chars, voice={}, speed={}, stream={}",
+            backend.name(),
+            text.len(),
+            voice,
+            speed,
+            stream
+        );
 
-        // Write the audio bytes to the output file.
-        tokio::fs::write(&output_path, &audio_bytes)
-            .await
-            .map_err(|e| {
-                AgentError::tool_execution(format!(
-                    "Failed to write audio to '{}': {}",
-                    output_path, e
-                ))
-            })?;
+        if stream {
+            let base = output
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| format!("/tmp/tts_{}", uuid::Uuid::new_v4()));
+            let chunks = self
+                .stream_to_files(backend.as_ref(), text, voice, speed, &base)
+                .await?;
+            let bytes: u64 = chunks.iter().filter_map(|c| c["bytes"].as_u64()).sum();
+
+            let result = serde_json::json!({
+                "text_length": text.len(),
+                "voice": voice,
+                "speed": speed,
+                "backend": backend.name(),
+                "generated": true,
+                "streamed": true,
+                "chunks": chunks,
+                "bytes": bytes
+            });
+            let duration = start.elapsed();
+            return Ok(ToolResult::success(tool_use_id, result).with_duration(duration));
+        }
+
+        let chars = text.chars().count();
+        if chars > backend.max_chars() {
+            return Err(AgentError::tool_execution(format!(
+                "Text is {} characters but the {} TTS backend accepts at most {} at once; \
+                 set 'stream' to synthesize it in chunks",
+                chars,
+                backend.name(),
+                backend.max_chars()
+            )));
+        }
+
+        let audio = backend.synthesize(text, voice, speed).await?;
+        let output_path = output.map(|s| s.to_string()).unwrap_or_else(|| {
+            format!("/tmp/tts_{}.{}", uuid::Uuid::new_v4(), audio.format.extension())
+        });
+        write_audio(&output_path, &audio).await?;
 
         let result = serde_json::json!({
             "text_length": text.len(),
             "voice": voice,
             "speed": speed,
+            "backend": backend.name(),
+            "format": audio.format,
+            "mime_type": audio.format.mime_type(),
             "output": output_path,
             "generated": true,
-            "bytes": byte_count
+            "bytes": audio.bytes.len()
         });
 
         let duration = start.elapsed();
@@ -412,21 +523,170 @@ mod tests {
         assert_eq!(tool.name(), "tts");
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn test_tts_tool_with_api_key() {
+        let tool = TtsTool::new().with_api_key("test-key-123");
+        assert!(tool.is_enabled());
+        assert_eq!(tool.legacy.api_key.as_deref(), Some("test-key-123"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_tts_tool_with_base_url() {
+        let tool = TtsTool::new()
+            .with_base_url("https://custom.api.example.com")
+            .with_api_key("test-key-123");
+        assert!(tool.is_enabled());
+        assert_eq!(
+            tool.legacy.endpoint.as_deref(),
+            Some("https://custom.api.example.com")
+        );
+    }
+
     #[test]
     fn test_tts_tool_custom_voice() {
         let tool = TtsTool::new().with_default_voice("nova");
-        assert_eq!(tool.default_voice, "nova");
+        assert_eq!(tool.default_voice.as_deref(), Some("nova"));
     }
 
     #[test]
-    fn test_tts_tool_with_api_key() {
-        let tool = TtsTool::new().with_api_key("test-key-123");
-        assert_eq!(tool.api_key, Some("test-key-123".to_string()));
+    fn test_tts_tool_from_config() {
+        let config = TtsConfig {
+            backend: tts::TtsProvider::Local,
+            voices: vec!["en-us".to_string(), "fr".to_string()],
+            chunk_chars: Some(120),
+            ..Default::default()
+        };
+        let tool = TtsTool::from_config(&config);
+        assert!(tool.is_enabled());
+        assert_eq!(tool.chunk_chars, 120);
+        let schema = tool.definition().input_schema;
+        assert_eq!(schema["properties"]["voice"]["enum"], serde_json::json!(["en-us", "fr"]));
+
+        let tool = TtsTool::from_config(&TtsConfig::default());
+        assert!(!tool.is_enabled());
+        assert!(tool.definition().input_schema["properties"]["voice"]["enum"].is_null());
     }
 
-    #[test]
-    fn test_tts_tool_with_base_url() {
-        let tool = TtsTool::new().with_base_url("https://custom.api.example.com");
-        assert_eq!(tool.base_url, "https://custom.api.example.com");
+    /// Backend returning `[<voice>|<text>]` as audio for each call.
+    struct MockTts {
+        voices: Vec<String>,
+    }
+
+    impl MockTts {
+        fn tool() -> TtsTool {
+            TtsTool::with_backend(Arc::new(MockTts {
+                voices: vec!["low".to_string(), "high".to_string()],
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl TtsBackend for MockTts {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        fn voices(&self) -> &[String] {
+            &self.voices
+        }
+
+        fn max_chars(&self) -> usize {
+            40
+        }
+
+        async fn synthesize(&self, text: &str, voice: &str, _speed: f64) -> Result<Audio> {
+            let bytes = format!("[{}|{}]", voice, text);
+            Ok(Audio::new(bytes, tts::AudioFormat::Wav))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tts_writes_backend_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.wav");
+        let args = serde_json::json!({
+            "text": "Hello there.",
+            "output": path.to_str().unwrap()
+        });
+
+        let result = MockTts::tool()
+            .execute("t1", args, &ToolContext::default())
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert_eq!(result.output["backend"], "mock");
+        assert_eq!(result.output["voice"], "low");
+        assert_eq!(result.output["format"], "wav");
+        assert_eq!(result.output["mime_type"], "audio/wav");
+        assert_eq!(std::fs::read(&path).unwrap(), b"[low|Hello there.]");
+    }
+
+    #[tokio::test]
+    async fn test_tts_rejects_unsupported_voice() {
+        let args = serde_json::json!({ "text": "Hi", "voice": "alloy" });
+        let err = MockTts::tool()
+            .execute("t1", args, &ToolContext::default())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Voice 'alloy' is not supported by the mock"), "{}", err);
+        assert!(err.contains("Available voices: low, high"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_tts_streams_chunks_to_numbered_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("story.wav");
+        let text = "The first sentence is here. A second follows it. Then a third.";
+        let args = serde_json::json!({
+            "text": text,
+            "voice": "high",
+            "output": base.to_str().unwrap(),
+            "stream": true
+        });
+
+        let tool = MockTts::tool().with_chunk_chars(30);
+        let result = tool.execute("t1", args, &ToolContext::default()).await.unwrap();
+
+        let chunks = result.output["chunks"].as_array().unwrap();
+        let expected = [
+            "[high|The first sentence is here.]",
+            "[high|A second follows it.]",
+            "[high|Then a third.]",
+        ];
+        assert_eq!(chunks.len(), expected.len());
+        for (i, (chunk, audio)) in chunks.iter().zip(expected).enumerate() {
+            let path = dir.path().join(format!("story-{:03}.wav", i + 1));
+            assert_eq!(chunk["index"], i);
+            assert_eq!(chunk["output"], path.to_str().unwrap());
+            assert_eq!(std::fs::read(&path).unwrap(), audio.as_bytes());
+        }
+        let total: usize = expected.iter().map(|a| a.len()).sum();
+        assert_eq!(result.output["bytes"], total);
+        assert_eq!(result.output["streamed"], true);
+    }
+
+    #[tokio::test]
+    async fn test_tts_long_text_needs_stream() {
+        let args = serde_json::json!({ "text": "word ".repeat(20) });
+        let err = MockTts::tool()
+            .execute("t1", args, &ToolContext::default())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("accepts at most 40"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_tts_disabled_is_informational() {
+        let tool = TtsTool::from_config(&TtsConfig::default());
+        let args = serde_json::json!({ "text": "Hi", "voice": "nova" });
+        let result = tool.execute("t1", args, &ToolContext::default()).await.unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.output["generated"], false);
+        assert!(result.output["message"].as_str().unwrap().contains("OPENAI_API_KEY"));
     }
 }
@@ -11,8 +11,11 @@ use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
 use smartassist_memory::{EmbeddingProvider, MemoryEntry, VectorStore};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
 // ---------------------------------------------------------------------------
@@ -99,6 +102,7 @@ impl Tool for MemorySearchTool {
                 },
                 "required": ["query"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -289,6 +293,7 @@ impl Tool for MemoryGetTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -479,6 +484,7 @@ impl Tool for MemoryStoreTool {
                 },
                 "required": ["content"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -575,13 +581,58 @@ impl Tool for MemoryStoreTool {
 // MemoryIndexTool
 // ---------------------------------------------------------------------------
 
-/// Memory index tool - Index a file's content for semantic search.
+/// Progress of a [`MemoryIndexTool`] run, reported after each file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexProgress {
+    /// File just processed.
+    pub path: PathBuf,
+
+    /// Files processed so far, including skipped ones.
+    pub files_processed: usize,
+
+    /// Files found under the indexed path.
+    pub files_total: usize,
+
+    /// Chunks stored so far.
+    pub chunks_created: usize,
+
+    /// Estimated time left, from the bytes processed so far.
+    pub estimated_remaining: Option<Duration>,
+}
+
+/// Outcome of a [`MemoryIndexTool`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSummary {
+    /// Files whose chunks were stored.
+    pub files_indexed: usize,
+
+    /// Files in a directory that could not be read as text.
+    pub files_skipped: usize,
+
+    /// Files found under the indexed path.
+    pub files_total: usize,
+
+    /// Chunks stored.
+    pub chunks_created: usize,
+
+    /// Whether the run was cancelled before every file was processed.
+    pub cancelled: bool,
+}
+
+/// Memory index tool - Index a file's or directory's content for semantic
+/// search.
+///
+/// Each file's chunks are stored as soon as the file is processed, so a
+/// cancelled run keeps what it indexed.
 pub struct MemoryIndexTool {
     /// Vector store for persistence.
     store: Option<Arc<dyn VectorStore>>,
 
     /// Embedding provider for generating vector embeddings.
     embedding_provider: Option<Arc<dyn EmbeddingProvider>>,
+
+    /// Channel for progress of runs started through [`Tool::execute`].
+    progress: Option<mpsc::UnboundedSender<IndexProgress>>,
 }
 
 impl Default for MemoryIndexTool {
@@ -596,6 +647,7 @@ impl MemoryIndexTool {
         Self {
             store: None,
             embedding_provider: None,
+            progress: None,
         }
     }
 
@@ -610,6 +662,100 @@ impl MemoryIndexTool {
         self.embedding_provider = Some(provider);
         self
     }
+
+    /// Send progress of every run started through [`Tool::execute`] to
+    /// `sender`.
+    pub fn with_progress_sender(mut self, sender: mpsc::UnboundedSender<IndexProgress>) -> Self {
+        self.progress = Some(sender);
+        self
+    }
+
+    /// Index the file or directory at `path`, calling `on_progress` after
+    /// each file.
+    ///
+    /// Directories are walked recursively, skipping hidden entries and
+    /// files that aren't UTF-8 text. Cancelling `cancel` stops the run
+    /// before the next file; files already indexed stay in the store.
+    pub async fn index_with_progress(
+        &self,
+        path: &Path,
+        chunk_size: usize,
+        on_progress: &mut (dyn FnMut(IndexProgress) + Send),
+        cancel: &CancellationToken,
+    ) -> Result<IndexSummary> {
+        let provider = self
+            .embedding_provider
+            .clone()
+            .ok_or_else(|| AgentError::tool_execution("Embedding provider not configured"))?;
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| AgentError::tool_execution("Memory store not configured"))?;
+
+        let metadata = tokio::fs::metadata(path).await.ok();
+        let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+        let files = if is_dir {
+            let root = path.to_path_buf();
+            tokio::task::spawn_blocking(move || collect_files(&root))
+                .await
+                .map_err(|e| AgentError::Internal(e.to_string()))?
+        } else {
+            vec![(path.to_path_buf(), metadata.map_or(0, |m| m.len()))]
+        };
+        let bytes_total: u64 = files.iter().map(|(_, size)| size).sum();
+
+        let start = Instant::now();
+        let mut bytes_done = 0;
+        let mut summary = IndexSummary {
+            files_indexed: 0,
+            files_skipped: 0,
+            files_total: files.len(),
+            chunks_created: 0,
+            cancelled: false,
+        };
+
+        for (file, size) in files {
+            if cancel.is_cancelled() {
+                summary.cancelled = true;
+                break;
+            }
+
+            match tokio::fs::read_to_string(&file).await {
+                Ok(content) => {
+                    let indexed =
+                        index_file(&*provider, &*store, &file, &content, chunk_size).await?;
+                    summary.files_indexed += 1;
+                    summary.chunks_created += indexed;
+                }
+                Err(e) if is_dir => {
+                    debug!("Memory index: skipping {}: {}", file.display(), e);
+                    summary.files_skipped += 1;
+                }
+                Err(e) => {
+                    return Err(AgentError::tool_execution(format!(
+                        "Failed to read file '{}': {}",
+                        file.display(),
+                        e
+                    )))
+                }
+            }
+
+            bytes_done += size;
+            let elapsed = start.elapsed();
+            let estimated_remaining = (bytes_done > 0).then(|| {
+                elapsed.mul_f64((bytes_total - bytes_done) as f64 / bytes_done as f64)
+            });
+            on_progress(IndexProgress {
+                path: file,
+                files_processed: summary.files_indexed + summary.files_skipped,
+                files_total: summary.files_total,
+                chunks_created: summary.chunks_created,
+                estimated_remaining,
+            });
+        }
+
+        Ok(summary)
+    }
 }
 
 #[async_trait]
@@ -621,14 +767,15 @@ impl Tool for MemoryIndexTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "memory_index".to_string(),
-            description: "Index a file's content into the memory store for semantic search"
+            description: "Index a file's or directory's content into the memory store for \
+                semantic search"
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "path": {
                         "type": "string",
-                        "description": "The file path to index"
+                        "description": "The file or directory path to index"
                     },
                     "chunk_size": {
                         "type": "integer",
@@ -637,6 +784,7 @@ impl Tool for MemoryIndexTool {
                 },
                 "required": ["path"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -645,31 +793,25 @@ impl Tool for MemoryIndexTool {
         &self,
         tool_use_id: &str,
         args: serde_json::Value,
-        _context: &ToolContext,
+        context: &ToolContext,
     ) -> Result<ToolResult> {
         let start = Instant::now();
 
-        let provider = match &self.embedding_provider {
-            Some(p) => p.clone(),
-            None => {
-                let duration = start.elapsed();
-                return Ok(
-                    ToolResult::error(tool_use_id, "Embedding provider not configured")
-                        .with_duration(duration),
-                );
-            }
-        };
+        if self.embedding_provider.is_none() {
+            let duration = start.elapsed();
+            return Ok(
+                ToolResult::error(tool_use_id, "Embedding provider not configured")
+                    .with_duration(duration),
+            );
+        }
 
-        let store = match &self.store {
-            Some(s) => s.clone(),
-            None => {
-                let duration = start.elapsed();
-                return Ok(
-                    ToolResult::error(tool_use_id, "Memory store not configured")
-                        .with_duration(duration),
-                );
-            }
-        };
+        if self.store.is_none() {
+            let duration = start.elapsed();
+            return Ok(
+                ToolResult::error(tool_use_id, "Memory store not configured")
+                    .with_duration(duration),
+            );
+        }
 
         let path = args
             .get("path")
@@ -683,42 +825,40 @@ impl Tool for MemoryIndexTool {
 
         debug!("Memory index: path='{}', chunk_size={}", path, chunk_size);
 
-        // Read file content
-        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
-            AgentError::tool_execution(format!("Failed to read file '{}': {}", path, e))
-        })?;
-
-        // Split into overlapping chunks (overlap of 200 chars)
-        let overlap = 200;
-        let chunks = chunk_text(&content, chunk_size, overlap);
-        let chunk_count = chunks.len();
-
-        // Generate embeddings for all chunks
-        let chunk_strings: Vec<String> = chunks.iter().map(|s| s.to_string()).collect();
-        let embeddings = provider.embed(&chunk_strings).await.map_err(|e| {
-            AgentError::tool_execution(format!("Failed to generate embeddings: {}", e))
-        })?;
-
-        // Store each chunk with metadata
-        let mut entries = Vec::with_capacity(chunk_count);
-        for (i, (chunk, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
-            let entry = MemoryEntry::new(chunk, embedding)
-                .with_metadata("path", serde_json::Value::String(path.to_string()))
-                .with_metadata("chunk_index", serde_json::json!(i));
-            entries.push(entry);
-        }
-
-        store.insert_batch(entries).await.map_err(|e| {
-            AgentError::tool_execution(format!("Failed to store indexed chunks: {}", e))
-        })?;
+        let progress = self.progress.clone();
+        let mut on_progress = |event: IndexProgress| {
+            context.report_progress(
+                tool_use_id,
+                self.name(),
+                serde_json::json!({
+                    "path": event.path,
+                    "files_processed": event.files_processed,
+                    "files_total": event.files_total,
+                    "chunks_created": event.chunks_created,
+                    "estimated_remaining_secs": event.estimated_remaining.map(|d| d.as_secs_f64()),
+                }),
+            );
+            if let Some(sender) = &progress {
+                let _ = sender.send(event);
+            }
+        };
+        let summary = self
+            .index_with_progress(Path::new(path), chunk_size, &mut on_progress, &context.cancel)
+            .await?;
 
         let duration = start.elapsed();
         Ok(
             ToolResult::success(tool_use_id, serde_json::json!({
                 "path": path,
-                "chunks_indexed": chunk_count,
+                "files_indexed": summary.files_indexed,
+                "files_skipped": summary.files_skipped,
+                "chunks_indexed": summary.chunks_created,
                 "chunk_size": chunk_size,
-                "message": format!("Indexed {} chunks from {}", chunk_count, path),
+                "cancelled": summary.cancelled,
+                "message": format!(
+                    "Indexed {} chunks from {} file(s) in {}",
+                    summary.chunks_created, summary.files_indexed, path
+                ),
             }))
             .with_duration(duration),
         )
@@ -733,6 +873,71 @@ impl Tool for MemoryIndexTool {
 // Helper functions
 // ---------------------------------------------------------------------------
 
+/// Split one file into chunks, embed them and store them, returning the
+/// number of chunks stored.
+async fn index_file(
+    provider: &dyn EmbeddingProvider,
+    store: &dyn VectorStore,
+    path: &Path,
+    content: &str,
+    chunk_size: usize,
+) -> Result<usize> {
+    // Split into overlapping chunks (overlap of 200 chars)
+    let overlap = 200;
+    let chunks = chunk_text(content, chunk_size, overlap);
+    if chunks.is_empty() {
+        return Ok(0);
+    }
+    let chunk_count = chunks.len();
+
+    let embeddings = provider.embed(&chunks).await.map_err(|e| {
+        AgentError::tool_execution(format!("Failed to generate embeddings: {}", e))
+    })?;
+
+    // Store each chunk with metadata
+    let path = path.to_string_lossy();
+    let mut entries = Vec::with_capacity(chunk_count);
+    for (i, (chunk, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
+        let entry = MemoryEntry::new(chunk, embedding)
+            .with_metadata("path", serde_json::Value::String(path.to_string()))
+            .with_metadata("chunk_index", serde_json::json!(i));
+        entries.push(entry);
+    }
+
+    store.insert_batch(entries).await.map_err(|e| {
+        AgentError::tool_execution(format!("Failed to store indexed chunks: {}", e))
+    })?;
+    Ok(chunk_count)
+}
+
+/// Files under `root` with their sizes, in path order, skipping hidden
+/// entries.
+fn collect_files(root: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                files.push((entry.path(), size));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
 /// Generate a simple embedding for a query (fallback when no provider is configured).
 /// In production, use a real embedding model via the EmbeddingProvider trait.
 fn generate_simple_embedding(text: &str) -> Vec<f32> {
@@ -967,6 +1172,168 @@ mod tests {
         assert!(result.is_error);
     }
 
+    /// Embeds texts with [`generate_simple_embedding`].
+    struct SimpleEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingProvider for SimpleEmbeddings {
+        fn dimension(&self) -> usize {
+            128
+        }
+
+        async fn embed(&self, texts: &[String]) -> smartassist_memory::Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|text| generate_simple_embedding(text)).collect())
+        }
+    }
+
+    /// A directory of three text files, one binary file and a hidden one.
+    fn index_fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "short note").unwrap();
+        // 2500 chars in 1000-char chunks overlapping by 200: four chunks.
+        std::fs::write(dir.path().join("b.txt"), "b".repeat(2500)).unwrap();
+        std::fs::write(dir.path().join("bin.dat"), [0xff, 0xfe, 0x00]).unwrap();
+        std::fs::create_dir_all(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("nested/c.md"), "# Notes").unwrap();
+        std::fs::create_dir_all(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".git/config"), "[core]").unwrap();
+        dir
+    }
+
+    fn index_tool(store: Arc<MemoryVectorStore>) -> MemoryIndexTool {
+        MemoryIndexTool::new()
+            .with_store(store)
+            .with_embedding_provider(Arc::new(SimpleEmbeddings))
+    }
+
+    #[tokio::test]
+    async fn test_memory_index_reports_progress_per_file() {
+        let dir = index_fixture();
+        let store = Arc::new(MemoryVectorStore::new());
+        let tool = index_tool(store.clone());
+
+        let mut events = Vec::new();
+        let cancel = CancellationToken::new();
+        let summary = tool
+            .index_with_progress(dir.path(), 1000, &mut |e| events.push(e), &cancel)
+            .await
+            .unwrap();
+
+        let names: Vec<String> = events
+            .iter()
+            .map(|e| e.path.strip_prefix(dir.path()).unwrap().display().to_string())
+            .collect();
+        assert_eq!(names, vec!["a.txt", "b.txt", "bin.dat", "nested/c.md"]);
+        let processed: Vec<usize> = events.iter().map(|e| e.files_processed).collect();
+        assert_eq!(processed, vec![1, 2, 3, 4]);
+        let chunks: Vec<usize> = events.iter().map(|e| e.chunks_created).collect();
+        assert_eq!(chunks, vec![1, 5, 5, 6]);
+        assert!(events.iter().all(|e| e.files_total == 4));
+        assert!(events[0].estimated_remaining.is_some());
+        assert_eq!(events[3].estimated_remaining, Some(Duration::ZERO));
+
+        assert_eq!(
+            summary,
+            IndexSummary {
+                files_indexed: 3,
+                files_skipped: 1,
+                files_total: 4,
+                chunks_created: 6,
+                cancelled: false,
+            }
+        );
+        assert_eq!(store.count().await.unwrap(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_memory_index_execute_sends_progress() {
+        let dir = index_fixture();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let tool = index_tool(Arc::new(MemoryVectorStore::new())).with_progress_sender(tx);
+
+        let args = serde_json::json!({ "path": dir.path().to_str().unwrap() });
+        let result = tool.execute("id", args, &ToolContext::default()).await.unwrap();
+
+        assert!(!result.is_error, "{}", result.output);
+        assert_eq!(result.output["files_indexed"], 3);
+        assert_eq!(result.output["chunks_indexed"], 6);
+        let mut received = 0;
+        while let Ok(event) = rx.try_recv() {
+            received += 1;
+            assert_eq!(event.files_processed, received);
+        }
+        assert_eq!(received, 4);
+    }
+
+    #[tokio::test]
+    async fn test_memory_index_execute_uses_turn_context() {
+        let dir = index_fixture();
+        let tool = index_tool(Arc::new(MemoryVectorStore::new()));
+        let args = serde_json::json!({ "path": dir.path().to_str().unwrap() });
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let context = ToolContext {
+            progress: Some(tx),
+            ..Default::default()
+        };
+        let result = tool.execute("id", args.clone(), &context).await.unwrap();
+        assert_eq!(result.output["cancelled"], false);
+        let mut received = 0;
+        while let Ok(update) = rx.try_recv() {
+            received += 1;
+            assert_eq!(update.tool_use_id, "id");
+            assert_eq!(update.tool, "memory_index");
+            assert_eq!(update.data["files_processed"], received);
+            assert_eq!(update.data["files_total"], 4);
+        }
+        assert_eq!(received, 4);
+
+        // A turn aborted before the tool runs stops it at the first file.
+        let context = ToolContext::default();
+        context.cancel.cancel();
+        let result = tool.execute("id", args, &context).await.unwrap();
+        assert_eq!(result.output["cancelled"], true);
+        assert_eq!(result.output["files_indexed"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_memory_index_cancel_keeps_partial_index() {
+        let dir = index_fixture();
+        let store = Arc::new(MemoryVectorStore::new());
+        let tool = index_tool(store.clone());
+
+        let cancel = CancellationToken::new();
+        let mut events = 0;
+        let mut on_progress = |e: IndexProgress| {
+            events += 1;
+            if e.files_processed == 2 {
+                cancel.cancel();
+            }
+        };
+        let summary = tool
+            .index_with_progress(dir.path(), 1000, &mut on_progress, &cancel)
+            .await
+            .unwrap();
+
+        assert_eq!(events, 2);
+        assert!(summary.cancelled);
+        assert_eq!(summary.files_indexed, 2);
+        assert_eq!(summary.chunks_created, 5);
+
+        // Both finished files stay searchable, nothing after them was stored.
+        assert_eq!(store.count().await.unwrap(), 5);
+        let query = generate_simple_embedding("short note");
+        let paths: std::collections::HashSet<String> = store
+            .search(&query, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(entry, _)| entry.metadata["path"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|p| p.ends_with("a.txt") || p.ends_with("b.txt")));
+    }
+
     #[tokio::test]
     async fn test_memory_get_path_lookup() {
         let store = Arc::new(MemoryVectorStore::new());
@@ -124,6 +124,7 @@ impl Tool for MessageTool {
                 },
                 "required": ["text"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -253,6 +254,7 @@ impl Tool for SessionsSpawnTool {
                 },
                 "required": ["prompt"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -325,6 +327,7 @@ impl Tool for SessionsSendTool {
                 },
                 "required": ["session_id", "message"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -389,6 +392,7 @@ impl Tool for SessionsListTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -456,6 +460,7 @@ impl Tool for SessionsHistoryTool {
                 },
                 "required": ["session_id"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -523,6 +528,7 @@ impl Tool for SessionStatusTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
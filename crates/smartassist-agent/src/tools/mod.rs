@@ -10,6 +10,7 @@ mod archive;
 mod ask;
 mod automation;
 mod browser;
+mod cache;
 mod canvas;
 mod channel_actions;
 mod checksum;
@@ -17,8 +18,11 @@ mod compare;
 mod context;
 mod diagnostic;
 mod diff;
+mod dns;
 mod encoding;
 mod env;
+mod env_filter;
+mod expr;
 mod fileops;
 mod filesystem;
 mod git;
@@ -33,20 +37,23 @@ mod network;
 mod notebook;
 mod plan;
 mod process;
+mod search;
 mod skill;
 mod string;
 mod system;
 mod tasks;
 mod template;
 mod time;
+mod tts;
 mod util;
 mod validate;
 mod web;
 
-pub use archive::{TarTool, ZipTool};
+pub use archive::{ExtractLimits, TarTool, ZipTool};
 pub use ask::{AskUserTool, ConfirmTool};
 pub use automation::{CronTool, GatewayTool, NodesTool};
 pub use browser::BrowserTool;
+pub use cache::{canonical_json_hash, ToolCache, ToolCacheConfig};
 pub use canvas::CanvasTool;
 pub use channel_actions::{DiscordActionsTool, SlackActionsTool, TelegramActionsTool};
 pub use checksum::{FileChecksumTool, FileVerifyTool};
@@ -56,15 +63,19 @@ pub use diagnostic::{DiagnosticTool, HealthCheckTool, SystemInfoTool};
 pub use diff::{DiffTool, PatchTool};
 pub use encoding::{Base64Tool, HashTool, HexTool, UrlEncodeTool};
 pub use env::{EnvCheckTool, EnvGetTool, EnvListTool};
+pub use env_filter::EnvFilter;
 pub use fileops::{FileCopyTool, FileDeleteTool, FileMoveTool, FileStatTool};
 pub use filesystem::{EditTool, GlobTool, GrepTool, ReadTool, WriteTool};
 pub use git::{GitBranchTool, GitDiffTool, GitLogTool, GitStatusTool};
 pub use http::{HttpRequestTool, UrlBuildTool, UrlParseTool};
 pub use json::{JsonQueryTool, JsonTransformTool, YamlTool};
 pub use lsp::LspTool;
-pub use math::{CalcTool, RandomTool, UuidTool};
+pub use math::{CalcTool, RandomTool, UuidTool, RANDOM_SEED_KEY};
 pub use media::{ImageTool, TtsTool};
-pub use memory::{MemoryGetTool, MemoryIndexTool, MemorySearchTool, MemoryStoreTool};
+pub use memory::{
+    IndexProgress, IndexSummary, MemoryGetTool, MemoryIndexTool, MemorySearchTool,
+    MemoryStoreTool,
+};
 pub use messaging::{
     MessageTool, SessionStatusTool, SessionsHistoryTool, SessionsListTool, SessionsSendTool,
     SessionsSpawnTool,
@@ -73,15 +84,23 @@ pub use network::{DnsLookupTool, HttpPingTool, NetInfoTool, PortCheckTool};
 pub use notebook::NotebookEditTool;
 pub use plan::{EnterPlanModeTool, ExitPlanModeTool, PlanState, SharedPlanState};
 pub use process::{ProcessInfoTool, ProcessListTool};
+pub use search::{
+    normalize_results, BraveSearch, GenericSearch, SearchBackend, SearchProvider, SearchResult,
+    SerpApiSearch, WebSearchConfig,
+};
 pub use skill::{Skill, SkillListTool, SkillRegistry, SkillTool, SharedSkillRegistry};
 pub use string::{CaseTool, ReplaceTool, SplitJoinTool, TrimPadTool};
 pub use system::BashTool;
 pub use tasks::{TaskCreateTool, TaskGetTool, TaskListTool, TaskStore, TaskUpdateTool};
 pub use template::{FormatTool, TemplateTool};
 pub use time::{DateCalcTool, DateParseTool, NowTool};
+pub use tts::{
+    split_text, Audio, AudioFormat, AudioStream, LocalTts, OpenAiTts, TtsBackend, TtsConfig,
+    TtsProvider, DEFAULT_CHUNK_CHARS,
+};
 pub use util::{EchoTool, SleepTool, TempDirTool, TempFileTool};
 pub use validate::{IsEmptyTool, ValidateTool};
-pub use web::{WebFetchTool, WebSearchTool};
+pub use web::{FetchPolicy, WebFetchTool, WebSearchTool};
 
 // Plugin adapter (bridges plugin SDK tools into the agent runtime)
 // Note: PluginToolAdapter is defined inline below, not in a submodule.
@@ -89,14 +108,17 @@ pub use web::{WebFetchTool, WebSearchTool};
 use crate::error::AgentError;
 use crate::Result;
 use async_trait::async_trait;
-use smartassist_core::safety::SafetyLayer;
+use smartassist_core::safety::{SafetyLayer, StreamingLeakScanner};
 use smartassist_core::types::{ToolDefinition, ToolGroup, ToolResult};
+use smartassist_core::types::SandboxProfile as SandboxLevel;
 use smartassist_sandbox::{CommandExecutor, ExecutionContext, SandboxProfile};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::RwLock;
-use tracing::debug;
+use tokio::sync::{mpsc, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 
 /// Adapter that wraps a plugin tool to implement the agent `Tool` trait.
 ///
@@ -185,7 +207,7 @@ pub struct ToolContext {
     /// Working directory.
     pub cwd: std::path::PathBuf,
 
-    /// Environment variables.
+    /// Environment variables, filtered by [`EnvFilter::default`] unless set.
     pub env: HashMap<String, String>,
 
     /// Session ID.
@@ -199,17 +221,52 @@ pub struct ToolContext {
 
     /// Additional context data.
     pub data: HashMap<String, serde_json::Value>,
+
+    /// Cancelled when the turn running the tool is aborted.
+    pub cancel: CancellationToken,
+
+    /// Receives progress updates from long-running tools, if set.
+    pub progress: Option<mpsc::UnboundedSender<ToolProgress>>,
+}
+
+impl ToolContext {
+    /// Report progress of the tool call `tool_use_id`, if anyone listens.
+    pub fn report_progress(&self, tool_use_id: &str, tool: &str, data: serde_json::Value) {
+        if let Some(sender) = &self.progress {
+            // The receiver goes away once the turn ends.
+            let _ = sender.send(ToolProgress {
+                tool_use_id: tool_use_id.to_string(),
+                tool: tool.to_string(),
+                data,
+            });
+        }
+    }
+}
+
+/// A progress update from a running tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolProgress {
+    /// Tool use ID of the call reporting progress.
+    pub tool_use_id: String,
+
+    /// Tool name.
+    pub tool: String,
+
+    /// Tool-specific progress details.
+    pub data: serde_json::Value,
 }
 
 impl Default for ToolContext {
     fn default() -> Self {
         Self {
             cwd: std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("/")),
-            env: std::env::vars().collect(),
+            env: EnvFilter::default().process_env(),
             session_id: String::new(),
             agent_id: String::new(),
             sandbox_profile: SandboxProfile::standard(),
             data: HashMap::new(),
+            cancel: CancellationToken::new(),
+            progress: None,
         }
     }
 }
@@ -221,6 +278,12 @@ pub struct ToolRegistry {
 
     /// Tool groups.
     groups: RwLock<HashMap<ToolGroup, Vec<String>>>,
+
+    /// Groups whose tools are hidden and cannot be executed.
+    disabled_groups: RwLock<HashSet<ToolGroup>>,
+
+    /// File the disabled groups are saved to, if any.
+    group_state_path: Option<PathBuf>,
 }
 
 impl Default for ToolRegistry {
@@ -235,9 +298,28 @@ impl ToolRegistry {
         Self {
             tools: RwLock::new(HashMap::new()),
             groups: RwLock::new(HashMap::new()),
+            disabled_groups: RwLock::new(HashSet::new()),
+            group_state_path: None,
         }
     }
 
+    /// Save the set of disabled groups to a file, loading any state already
+    /// there.
+    ///
+    /// The file holds only the disabled groups, so groups added later start
+    /// enabled. Registries built with the same path start with the same
+    /// groups disabled.
+    pub fn with_group_state(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            let disabled: Vec<ToolGroup> = serde_json::from_str(&content)?;
+            *self.disabled_groups.get_mut() = disabled.into_iter().collect();
+        }
+        self.group_state_path = Some(path);
+        Ok(self)
+    }
+
     /// Create a registry with default tools.
     pub async fn with_defaults() -> Self {
         let registry = Self::new();
@@ -254,7 +336,7 @@ impl ToolRegistry {
 
         // Web tools
         registry.register(Arc::new(WebFetchTool::new())).await;
-        registry.register(Arc::new(WebSearchTool::new())).await;
+        registry.register(Arc::new(WebSearchTool::from_env())).await;
 
         // Messaging tools
         registry.register(Arc::new(MessageTool::new())).await;
@@ -447,9 +529,43 @@ impl ToolRegistry {
     }
 
     /// Get a tool by name.
+    ///
+    /// Tools in disabled groups are not returned.
     pub async fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
         let tools = self.tools.read().await;
-        tools.get(name).cloned()
+        let tool = tools.get(name)?;
+        if self.disabled_groups.read().await.contains(&tool.group()) {
+            return None;
+        }
+        Some(tool.clone())
+    }
+
+    /// Enable or disable every tool in a group.
+    ///
+    /// Disabled tools stay registered but are left out of definitions and
+    /// cannot be executed.
+    pub async fn set_group_enabled(&self, group: ToolGroup, enabled: bool) -> Result<()> {
+        let mut disabled = self.disabled_groups.write().await;
+        let changed = if enabled {
+            disabled.remove(&group)
+        } else {
+            disabled.insert(group)
+        };
+
+        if let (true, Some(path)) = (changed, &self.group_state_path) {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut groups: Vec<ToolGroup> = disabled.iter().copied().collect();
+            groups.sort_by_key(|g| format!("{:?}", g));
+            tokio::fs::write(path, serde_json::to_string_pretty(&groups)?).await?;
+        }
+        Ok(())
+    }
+
+    /// Check whether a group's tools are enabled.
+    pub async fn is_group_enabled(&self, group: ToolGroup) -> bool {
+        !self.disabled_groups.read().await.contains(&group)
     }
 
     /// List all tool names.
@@ -464,18 +580,24 @@ impl ToolRegistry {
         groups.get(&group).cloned().unwrap_or_default()
     }
 
-    /// Get all tool definitions.
+    /// Get all tool definitions, excluding disabled groups.
     pub async fn definitions(&self) -> Vec<ToolDefinition> {
         let tools = self.tools.read().await;
-        tools.values().map(|t| t.definition()).collect()
+        let disabled = self.disabled_groups.read().await;
+        tools
+            .values()
+            .filter(|t| !disabled.contains(&t.group()))
+            .map(|t| t.definition())
+            .collect()
     }
 
-    /// Get tool definitions for specific groups.
+    /// Get tool definitions for specific groups, excluding disabled groups.
     pub async fn definitions_for_groups(&self, target_groups: &[ToolGroup]) -> Vec<ToolDefinition> {
         let tools = self.tools.read().await;
+        let disabled = self.disabled_groups.read().await;
         tools
             .values()
-            .filter(|t| target_groups.contains(&t.group()))
+            .filter(|t| target_groups.contains(&t.group()) && !disabled.contains(&t.group()))
             .map(|t| t.definition())
             .collect()
     }
@@ -498,6 +620,22 @@ impl ToolRegistry {
     }
 }
 
+/// What a before-execution hook decided about a tool call.
+#[derive(Debug, Clone)]
+pub enum ToolHookAction {
+    /// Run the tool with these arguments, possibly rewritten.
+    Continue(serde_json::Value),
+
+    /// Skip the tool and return this result instead.
+    Respond(ToolResult),
+}
+
+/// Hook run before a tool executes, given the tool name and arguments.
+pub type BeforeToolHook = Arc<dyn Fn(&str, serde_json::Value) -> ToolHookAction + Send + Sync>;
+
+/// Hook run after a tool executes, given the tool name and result.
+pub type AfterToolHook = Arc<dyn Fn(&str, ToolResult) -> ToolResult + Send + Sync>;
+
 /// Tool executor with sandbox support and safety layer.
 pub struct ToolExecutor {
     /// Tool registry.
@@ -511,6 +649,21 @@ pub struct ToolExecutor {
 
     /// Safety layer for input/output validation.
     safety: Option<SafetyLayer>,
+
+    /// Result cache for cacheable tools.
+    cache: Option<Arc<ToolCache>>,
+
+    /// Environment variables tools may see.
+    env_filter: EnvFilter,
+
+    /// Hooks run before each call, in registration order.
+    before_hooks: Vec<BeforeToolHook>,
+
+    /// Hooks run after each call, in registration order.
+    after_hooks: Vec<AfterToolHook>,
+
+    /// Whether outputs are checked against the tool's output schema.
+    validate_output: bool,
 }
 
 impl ToolExecutor {
@@ -521,6 +674,11 @@ impl ToolExecutor {
             default_context: ToolContext::default(),
             command_executor: None,
             safety: None,
+            cache: None,
+            env_filter: EnvFilter::default(),
+            before_hooks: Vec::new(),
+            after_hooks: Vec::new(),
+            validate_output: false,
         }
     }
 
@@ -530,11 +688,21 @@ impl ToolExecutor {
         self
     }
 
+    /// Set which environment variables tools may see.
+    ///
+    /// The default context is refilled from the process environment, and
+    /// the filter is also applied to contexts passed to [`Self::execute`].
+    pub fn with_env_filter(mut self, filter: EnvFilter) -> Self {
+        self.default_context.env = filter.process_env();
+        self.env_filter = filter;
+        self
+    }
+
     /// Set up command executor with sandbox.
     pub fn with_sandbox(mut self, profile: SandboxProfile) -> Self {
         let exec_context = ExecutionContext::new(&self.default_context.cwd)
             .with_profile(profile)
-            .with_envs(self.default_context.env.clone());
+            .with_clean_env(self.env_filter.apply(self.default_context.env.clone()));
         self.command_executor = Some(CommandExecutor::new(exec_context));
         self
     }
@@ -545,6 +713,53 @@ impl ToolExecutor {
         self
     }
 
+    /// Create a scanner for redacting leaks from streamed tool output.
+    ///
+    /// Returns `None` unless a safety layer with leak detection is enabled.
+    pub fn output_scanner(&self) -> Option<StreamingLeakScanner> {
+        self.safety.as_ref().and_then(SafetyLayer::output_scanner)
+    }
+
+    /// Enable result caching for tools marked cacheable.
+    pub fn with_cache(mut self, cache: Arc<ToolCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Check successful outputs against the tool's declared output schema.
+    ///
+    /// A mismatching output is logged and replaced by an error result that
+    /// lists each mismatch. Tools without an output schema are unaffected.
+    pub fn with_output_validation(mut self) -> Self {
+        self.validate_output = true;
+        self
+    }
+
+    /// Add a hook that runs before each tool call.
+    ///
+    /// Before-hooks run ahead of the safety input check and may rewrite the
+    /// arguments or answer in place of the tool. A result a hook answers
+    /// with still goes through the safety output check.
+    pub fn add_before<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str, serde_json::Value) -> ToolHookAction + Send + Sync + 'static,
+    {
+        self.before_hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Add a hook that runs after each tool call.
+    ///
+    /// After-hooks run once the safety output check has passed, including
+    /// for cached results and results supplied by a before-hook.
+    pub fn add_after<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&str, ToolResult) -> ToolResult + Send + Sync + 'static,
+    {
+        self.after_hooks.push(Arc::new(hook));
+        self
+    }
+
     /// Execute a tool by name.
     pub async fn execute(
         &self,
@@ -552,6 +767,21 @@ impl ToolExecutor {
         name: &str,
         args: serde_json::Value,
         context: Option<&ToolContext>,
+    ) -> Result<ToolResult> {
+        let result = self.execute_unhooked(tool_use_id, name, args, context).await?;
+        Ok(self
+            .after_hooks
+            .iter()
+            .fold(result, |result, hook| hook(name, result)))
+    }
+
+    /// Execute a tool, running before-hooks but not after-hooks.
+    async fn execute_unhooked(
+        &self,
+        tool_use_id: &str,
+        name: &str,
+        mut args: serde_json::Value,
+        context: Option<&ToolContext>,
     ) -> Result<ToolResult> {
         let tool = self
             .registry
@@ -559,28 +789,92 @@ impl ToolExecutor {
             .await
             .ok_or_else(|| AgentError::ToolNotFound(name.to_string()))?;
 
-        let ctx = context.unwrap_or(&self.default_context);
+        let mut ctx = self.filter_context(context.unwrap_or(&self.default_context));
+        let declared = tool.definition().execution.sandbox_profile;
+        if let Some(profile) = declared.and_then(|level| tighten(&ctx.sandbox_profile, level)) {
+            ctx.to_mut().sandbox_profile = profile;
+        }
+        let ctx = ctx.as_ref();
+
+        for hook in &self.before_hooks {
+            match hook(name, args) {
+                ToolHookAction::Continue(next) => args = next,
+                ToolHookAction::Respond(result) => return self.check_output(name, result),
+            }
+        }
 
         // Pre-execution: validate and scan args
         if let Some(ref safety) = self.safety {
             safety.check_input(name, &args)?;
         }
 
+        // Side-effecting tools (anything needing approval) are never cached
+        let cache = self.cache.as_ref().filter(|_| {
+            tool.definition().execution.cacheable && !tool.requires_approval(&args)
+        });
+
+        if let Some(cache) = cache {
+            if let Some(cached) = cache.get(name, &args, tool_use_id).await {
+                debug!("Tool '{}' result served from cache", name);
+                return Ok(cached);
+            }
+        }
+
         debug!("Executing tool '{}' with args: {:?}", name, args);
-        let result = tool.execute(tool_use_id, args, ctx).await?;
+        let cache_args = cache.map(|_| args.clone());
+        let mut result = tool.execute(tool_use_id, args, ctx).await?;
+
+        if self.validate_output && !result.is_error {
+            let violations = tool.definition().output_violations(&result.output);
+            if !violations.is_empty() {
+                warn!("Tool '{}' output does not match its schema: {:?}", name, violations);
+                result = ToolResult {
+                    output: serde_json::Value::String(format!(
+                        "Output of tool '{}' does not match its schema: {}",
+                        name,
+                        violations.join("; ")
+                    )),
+                    is_error: true,
+                    ..result
+                };
+            }
+        }
 
-        // Post-execution: scan output for leaks, wrap in XML
-        if let Some(ref safety) = self.safety {
-            let cleaned_output = safety.check_output(name, &result.output)?;
-            return Ok(ToolResult {
-                output: cleaned_output,
-                ..result
-            });
+        let result = self.check_output(name, result)?;
+
+        if let (Some(cache), Some(args)) = (cache, cache_args) {
+            if !result.is_error {
+                cache.insert(name, &args, result.clone()).await;
+            }
         }
 
         Ok(result)
     }
 
+    /// Post-execution: scan output for leaks, wrap in XML.
+    fn check_output(&self, name: &str, result: ToolResult) -> Result<ToolResult> {
+        let Some(ref safety) = self.safety else {
+            return Ok(result);
+        };
+        let cleaned_output = safety.check_output(name, &result.output)?;
+        Ok(ToolResult {
+            output: cleaned_output,
+            ..result
+        })
+    }
+
+    /// Strip variables the filter does not permit, copying the context only
+    /// when something has to be removed.
+    fn filter_context<'a>(&self, context: &'a ToolContext) -> std::borrow::Cow<'a, ToolContext> {
+        if context.env.keys().all(|name| self.env_filter.permits(name)) {
+            return std::borrow::Cow::Borrowed(context);
+        }
+        std::borrow::Cow::Owned(ToolContext {
+            env: self.env_filter.apply(context.env.clone()),
+            ..context.clone()
+        })
+    }
+
     /// Check if a tool requires approval.
     pub async fn requires_approval(&self, name: &str, args: &serde_json::Value) -> Result<bool> {
         let tool = self
@@ -592,22 +886,61 @@ impl ToolExecutor {
         Ok(tool.requires_approval(args))
     }
 
-    /// Execute a shell command (with sandboxing).
+    /// Sandbox profile for commands run on behalf of a tool.
+    ///
+    /// The profile the executor was set up with by [`Self::with_sandbox`],
+    /// or the tool's declared profile where that is stricter. A tool can
+    /// tighten its sandbox but never loosen it, so a plugin declaring
+    /// [`SandboxLevel::None`] still runs under the configured profile.
+    pub async fn command_profile(&self, tool_name: &str) -> Result<SandboxProfile> {
+        let configured = match &self.command_executor {
+            Some(executor) => &executor.context().profile,
+            None => &self.default_context.sandbox_profile,
+        };
+        Ok(self
+            .declared_profile(tool_name)
+            .await?
+            .and_then(|level| tighten(configured, level))
+            .unwrap_or_else(|| configured.clone()))
+    }
+
+    /// The sandbox level a tool declares in its execution config.
+    async fn declared_profile(&self, tool_name: &str) -> Result<Option<SandboxLevel>> {
+        let tool = self
+            .registry
+            .get(tool_name)
+            .await
+            .ok_or_else(|| AgentError::ToolNotFound(tool_name.to_string()))?;
+        Ok(tool.definition().execution.sandbox_profile)
+    }
+
+    /// Execute a shell command on behalf of a tool (with sandboxing).
+    ///
+    /// The command runs under [`Self::command_profile`] for the tool.
     pub async fn execute_command(
         &self,
+        tool_name: &str,
         command: &str,
     ) -> Result<smartassist_core::types::ExecutionResult> {
         let executor = self
             .command_executor
             .as_ref()
             .ok_or_else(|| AgentError::config("Command executor not configured"))?;
+        let declared = self.declared_profile(tool_name).await?;
+        let tightened = declared.and_then(|level| tighten(&executor.context().profile, level));
 
         let start = Instant::now();
-        let output = executor.execute(command).await?;
+        let output = match tightened {
+            Some(profile) => {
+                let context = executor.context().clone().with_profile(profile);
+                CommandExecutor::new(context).execute(command).await?
+            }
+            None => executor.execute(command).await?,
+        };
         let duration_ms = start.elapsed().as_millis() as u64;
 
         Ok(smartassist_core::types::ExecutionResult {
-            exit_code: output.exit_code,
+            exit_code: output.exit_code(),
             stdout: output.stdout,
             stderr: output.stderr,
             duration_ms,
@@ -616,6 +949,30 @@ impl ToolExecutor {
     }
 }
 
+/// How much an isolation level allows, from `Strict` up to `None`.
+fn looseness(level: SandboxLevel) -> u8 {
+    match level {
+        SandboxLevel::Strict => 0,
+        SandboxLevel::Standard => 1,
+        SandboxLevel::Trusted => 2,
+        SandboxLevel::None => 3,
+    }
+}
+
+/// The profile for `declared` if it is stricter than `configured`.
+///
+/// Built-in profiles are ranked by the level they come from. A custom
+/// profile ranks with `Standard`, so only `Strict` can tighten it.
+fn tighten(configured: &SandboxProfile, declared: SandboxLevel) -> Option<SandboxProfile> {
+    let current = match configured.name.as_str() {
+        "minimal" => SandboxLevel::Strict,
+        "relaxed" => SandboxLevel::Trusted,
+        "none" => SandboxLevel::None,
+        _ => SandboxLevel::Standard,
+    };
+    (looseness(declared) < looseness(current)).then(|| declared.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -632,6 +989,113 @@ mod tests {
         assert!(tools.contains(&"read".to_string()));
     }
 
+    async fn echo_and_fetch(registry: ToolRegistry) -> Arc<ToolRegistry> {
+        registry.register(Arc::new(EchoTool::new())).await;
+        registry.register(Arc::new(WebFetchTool::new())).await;
+        Arc::new(registry)
+    }
+
+    fn definition_names(definitions: Vec<ToolDefinition>) -> Vec<String> {
+        definitions.into_iter().map(|d| d.name).collect()
+    }
+
+    #[tokio::test]
+    async fn test_disabled_group_hidden_and_blocked() {
+        let registry = echo_and_fetch(ToolRegistry::new()).await;
+        registry.set_group_enabled(ToolGroup::Web, false).await.unwrap();
+        assert!(!registry.is_group_enabled(ToolGroup::Web).await);
+
+        assert_eq!(definition_names(registry.definitions().await), ["echo"]);
+        assert!(registry
+            .definitions_for_groups(&[ToolGroup::Web])
+            .await
+            .is_empty());
+
+        let executor = ToolExecutor::new(registry.clone());
+        let err = executor
+            .execute("id", "web_fetch", serde_json::json!({"url": "https://example.com"}), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AgentError::ToolNotFound(name) if name == "web_fetch"));
+        assert!(executor
+            .execute("id", "echo", serde_json::json!({"value": "hi"}), None)
+            .await
+            .is_ok());
+
+        registry.set_group_enabled(ToolGroup::Web, true).await.unwrap();
+        assert_eq!(registry.definitions().await.len(), 2);
+        assert!(registry.get("web_fetch").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_group_state_survives_rebuild() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool-groups.json");
+
+        let registry = echo_and_fetch(ToolRegistry::new().with_group_state(&path).unwrap()).await;
+        registry.set_group_enabled(ToolGroup::Web, false).await.unwrap();
+        drop(registry);
+
+        let rebuilt = echo_and_fetch(ToolRegistry::new().with_group_state(&path).unwrap()).await;
+        assert!(!rebuilt.is_group_enabled(ToolGroup::Web).await);
+        assert!(rebuilt.get("web_fetch").await.is_none());
+        assert_eq!(definition_names(rebuilt.definitions().await), ["echo"]);
+    }
+
+    #[tokio::test]
+    async fn test_executor_hooks() {
+        let registry = echo_and_fetch(ToolRegistry::new()).await;
+        let executor = ToolExecutor::new(registry)
+            .add_before(|name, mut args| {
+                if name == "echo" {
+                    args["value"] = serde_json::json!("rewritten");
+                }
+                ToolHookAction::Continue(args)
+            })
+            .add_before(|name, args| match name {
+                "web_fetch" => ToolHookAction::Respond(ToolResult::error("id", "offline")),
+                _ => ToolHookAction::Continue(args),
+            })
+            .add_after(|name, mut result| {
+                result.output = serde_json::json!({ "tool": name, "output": result.output });
+                result
+            });
+
+        let result = executor
+            .execute("id", "echo", serde_json::json!({"value": "original"}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.output, serde_json::json!({"tool": "echo", "output": "rewritten"}));
+
+        let result = executor
+            .execute("id", "web_fetch", serde_json::json!({"url": "https://example.com"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert_eq!(result.output["tool"], "web_fetch");
+    }
+
+    #[tokio::test]
+    async fn test_hook_response_passes_safety_output_check() {
+        let registry = echo_and_fetch(ToolRegistry::new()).await;
+        let executor = ToolExecutor::new(registry)
+            .with_safety(SafetyLayer::default())
+            .add_before(|_, _| {
+                ToolHookAction::Respond(ToolResult::success(
+                    "id",
+                    serde_json::json!("Found key: sk-abcdefghijklmnopqrstuvwx"),
+                ))
+            });
+
+        let result = executor
+            .execute("id", "echo", serde_json::json!({"value": "unused"}), None)
+            .await
+            .unwrap();
+        let output = result.output.as_str().unwrap();
+        assert!(!output.contains("sk-abcdefghijklmnopqrstuvwx"));
+        assert!(output.starts_with("<tool_output"));
+    }
+
     #[tokio::test]
     async fn test_registry_with_defaults() {
         let registry = ToolRegistry::with_defaults().await;
@@ -809,4 +1273,422 @@ mod tests {
         // Total: 101 tools
         assert_eq!(tools.len(), 101);
     }
+
+    /// Counts executions so cache hits can be observed.
+    struct CountingTool {
+        calls: std::sync::atomic::AtomicUsize,
+        cacheable: bool,
+    }
+
+    #[async_trait]
+    impl Tool for CountingTool {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "counting".to_string(),
+                description: "Counts calls".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                output_schema: None,
+                execution: smartassist_core::types::ToolExecutionConfig {
+                    cacheable: self.cacheable,
+                    ..Default::default()
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            _args: serde_json::Value,
+            _context: &ToolContext,
+        ) -> Result<ToolResult> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            Ok(ToolResult::success(tool_use_id, serde_json::json!(n)))
+        }
+    }
+
+    async fn counting_executor(
+        cacheable: bool,
+        ttl: std::time::Duration,
+    ) -> (ToolExecutor, Arc<CountingTool>) {
+        let tool = Arc::new(CountingTool {
+            calls: std::sync::atomic::AtomicUsize::new(0),
+            cacheable,
+        });
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(tool.clone()).await;
+
+        let cache = Arc::new(ToolCache::new(ToolCacheConfig {
+            ttl,
+            max_entries: 10,
+        }));
+        (ToolExecutor::new(registry).with_cache(cache), tool)
+    }
+
+    #[tokio::test]
+    async fn test_executor_cache_hit() {
+        let (executor, tool) =
+            counting_executor(true, std::time::Duration::from_secs(60)).await;
+        let args = serde_json::json!({"a": 1, "b": 2});
+
+        let first = executor.execute("id1", "counting", args, None).await.unwrap();
+        let second = executor
+            .execute("id2", "counting", serde_json::json!({"b": 2, "a": 1}), None)
+            .await
+            .unwrap();
+
+        assert_eq!(tool.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first.output, second.output);
+        assert_eq!(second.tool_use_id, "id2");
+    }
+
+    #[tokio::test]
+    async fn test_executor_cache_ttl_expiry() {
+        let (executor, tool) =
+            counting_executor(true, std::time::Duration::from_millis(20)).await;
+
+        executor
+            .execute("id1", "counting", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+        let second = executor
+            .execute("id2", "counting", serde_json::json!({}), None)
+            .await
+            .unwrap();
+
+        assert_eq!(tool.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(second.output, serde_json::json!(2));
+    }
+
+    #[tokio::test]
+    async fn test_executor_skips_non_cacheable_tools() {
+        let (executor, tool) =
+            counting_executor(false, std::time::Duration::from_secs(60)).await;
+
+        for id in ["id1", "id2"] {
+            executor
+                .execute(id, "counting", serde_json::json!({}), None)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(tool.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Variables the `env_list` tool sees, by name.
+    async fn env_list(executor: &ToolExecutor, context: &ToolContext) -> HashMap<String, String> {
+        let result = executor
+            .execute(
+                "id",
+                "env_list",
+                serde_json::json!({ "include_values": true }),
+                Some(context),
+            )
+            .await
+            .unwrap();
+        result.output["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| {
+                let field = |key: &str| v[key].as_str().unwrap().to_string();
+                (field("name"), field("value"))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_executor_filters_context_env() {
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(EnvListTool::new())).await;
+        let context = ToolContext {
+            env: HashMap::from([
+                ("PATH".to_string(), "/usr/bin".to_string()),
+                ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+                ("EDITOR".to_string(), "vim".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let executor = ToolExecutor::new(registry.clone());
+        let vars = env_list(&executor, &context).await;
+        assert_eq!(vars["PATH"], "/usr/bin");
+        assert!(!vars.contains_key("GITHUB_TOKEN"));
+        assert!(!vars.contains_key("EDITOR"));
+
+        let executor = ToolExecutor::new(registry).with_env_filter(EnvFilter::allow_all());
+        let vars = env_list(&executor, &context).await;
+        assert_eq!(vars["EDITOR"], "vim");
+        assert!(!vars.contains_key("GITHUB_TOKEN"));
+    }
+
+    /// Tool that returns its arguments and declares an integer `n` output.
+    struct ShapedTool;
+
+    #[async_trait]
+    impl Tool for ShapedTool {
+        fn name(&self) -> &str {
+            "shaped"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "shaped".to_string(),
+                description: "Returns its arguments".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                output_schema: Some(serde_json::json!({
+                    "type": "object",
+                    "properties": {"n": {"type": "integer"}},
+                    "required": ["n"]
+                })),
+                execution: Default::default(),
+            }
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            args: serde_json::Value,
+            _context: &ToolContext,
+        ) -> Result<ToolResult> {
+            Ok(ToolResult::success(tool_use_id, args))
+        }
+    }
+
+    async fn shaped_executor() -> ToolExecutor {
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(ShapedTool)).await;
+        registry.register(Arc::new(CalcTool::new())).await;
+        registry.register(Arc::new(JsonQueryTool::new())).await;
+        ToolExecutor::new(registry).with_output_validation()
+    }
+
+    #[tokio::test]
+    async fn test_output_validation_accepts_conforming_output() {
+        let executor = shaped_executor().await;
+
+        let result = executor
+            .execute("id", "shaped", serde_json::json!({"n": 3}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.output, serde_json::json!({"n": 3}));
+
+        // Built-in tools produce what they declare.
+        let calc = serde_json::json!({"operation": "add", "a": 2, "b": 3});
+        let result = executor.execute("id", "calc", calc, None).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+        let query = serde_json::json!({"json": "{\"a\": [1, 2]}", "path": ".a[1]"});
+        let result = executor.execute("id", "json_query", query, None).await.unwrap();
+        assert!(!result.is_error, "{}", result.output);
+    }
+
+    #[tokio::test]
+    async fn test_output_validation_flags_mismatch() {
+        let executor = shaped_executor().await;
+
+        let result = executor
+            .execute("id", "shaped", serde_json::json!({"n": "three"}), None)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("'shaped' does not match its schema"), "{}", message);
+        assert!(message.contains("$.n: expected integer, got string"), "{}", message);
+
+        // Without validation the output is passed through untouched.
+        let registry = Arc::new(ToolRegistry::new());
+        registry.register(Arc::new(ShapedTool)).await;
+        let result = ToolExecutor::new(registry)
+            .execute("id", "shaped", serde_json::json!({"n": "three"}), None)
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+    }
+
+    /// Tool that reports the sandbox profile it was run under.
+    struct ProfiledTool {
+        name: &'static str,
+        profile: Option<smartassist_core::types::SandboxProfile>,
+    }
+
+    #[async_trait]
+    impl Tool for ProfiledTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: self.name.to_string(),
+                description: "Reports its sandbox profile".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                output_schema: None,
+                execution: smartassist_core::types::ToolExecutionConfig {
+                    sandbox_profile: self.profile,
+                    ..Default::default()
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            _args: serde_json::Value,
+            context: &ToolContext,
+        ) -> Result<ToolResult> {
+            let profile = context.sandbox_profile.name.clone();
+            Ok(ToolResult::success(tool_use_id, serde_json::json!(profile)))
+        }
+    }
+
+    async fn profiled_executor() -> ToolExecutor {
+        let registry = Arc::new(ToolRegistry::new());
+        registry
+            .register(Arc::new(ProfiledTool {
+                name: "offline",
+                profile: Some(smartassist_core::types::SandboxProfile::Strict),
+            }))
+            .await;
+        registry
+            .register(Arc::new(ProfiledTool {
+                name: "plain",
+                profile: None,
+            }))
+            .await;
+        registry
+            .register(Arc::new(ProfiledTool {
+                name: "trusted",
+                profile: Some(smartassist_core::types::SandboxProfile::Trusted),
+            }))
+            .await;
+        registry
+            .register(Arc::new(PluginToolAdapter::new(Arc::new(UnsandboxedPluginTool))))
+            .await;
+        let context = ToolContext {
+            env: HashMap::from([
+                ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+                ("MARKER".to_string(), "visible".to_string()),
+            ]),
+            ..Default::default()
+        };
+        ToolExecutor::new(registry)
+            .with_env_filter(EnvFilter::allow_all())
+            .with_context(context)
+            .with_sandbox(SandboxProfile::standard())
+    }
+
+    #[tokio::test]
+    async fn test_tool_declared_sandbox_profile() {
+        let executor = profiled_executor().await;
+
+        let strict = executor.command_profile("offline").await.unwrap();
+        assert_eq!(strict.name, "minimal");
+        assert!(!strict.network.enabled);
+        let default = executor.command_profile("plain").await.unwrap();
+        assert_eq!(default.name, "standard");
+        assert!(default.network.enabled);
+        assert!(executor.command_profile("missing").await.is_err());
+
+        let result = executor
+            .execute("id", "offline", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.output, serde_json::json!("minimal"));
+    }
+
+    /// Plugin tool that asks to run without a sandbox.
+    struct UnsandboxedPluginTool;
+
+    #[async_trait]
+    impl smartassist_plugin_sdk::PluginTool for UnsandboxedPluginTool {
+        fn name(&self) -> &str {
+            "unsandboxed"
+        }
+
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "unsandboxed".to_string(),
+                description: "Declares no sandbox".to_string(),
+                input_schema: serde_json::json!({"type": "object"}),
+                output_schema: None,
+                execution: smartassist_core::types::ToolExecutionConfig {
+                    sandbox_profile: Some(smartassist_core::types::SandboxProfile::None),
+                    ..Default::default()
+                },
+            }
+        }
+
+        async fn execute(
+            &self,
+            tool_use_id: &str,
+            _args: serde_json::Value,
+            _ctx: &smartassist_plugin_sdk::ToolExecutionContext,
+        ) -> smartassist_plugin_sdk::Result<ToolResult> {
+            Ok(ToolResult::success(tool_use_id, serde_json::json!("ran")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_declared_profile_cannot_loosen() {
+        let executor = profiled_executor().await;
+
+        for name in ["unsandboxed", "trusted"] {
+            let profile = executor.command_profile(name).await.unwrap();
+            assert_eq!(profile.name, "standard", "{}", name);
+            assert!(profile.drop_capabilities, "{}", name);
+        }
+        for name in ["unsandboxed", "trusted"] {
+            let result = executor
+                .execute("id", name, serde_json::json!({}), None)
+                .await
+                .unwrap();
+            assert!(!result.is_error, "{}", name);
+        }
+        let result = executor
+            .execute("id", "trusted", serde_json::json!({}), None)
+            .await
+            .unwrap();
+        assert_eq!(result.output, serde_json::json!("standard"));
+    }
+
+    #[tokio::test]
+    async fn test_network_disabled_profile_blocks_network_command() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let executor = profiled_executor().await;
+        let command = format!(
+            "bash -c '(exec 3<>/dev/tcp/127.0.0.1/{}) 2>/dev/null {}'",
+            port, "&& echo connected || echo blocked"
+        );
+
+        // The default profile allows localhost; the tool's strict one has no network.
+        let plain = executor.execute_command("plain", &command).await.unwrap();
+        assert_eq!(plain.stdout.trim(), "connected");
+        let offline = executor.execute_command("offline", &command).await.unwrap();
+        assert_ne!(offline.stdout.trim(), "connected", "{}", offline.stderr);
+    }
+
+    #[tokio::test]
+    async fn test_execute_command_uses_tool_profile() {
+        let executor = profiled_executor().await;
+        let command = "echo ${MARKER:-unset}";
+
+        // The strict profile only passes an allow-list of variables through.
+        let offline = executor.execute_command("offline", command).await.unwrap();
+        assert_eq!(offline.stdout.trim(), "unset");
+        let plain = executor.execute_command("plain", command).await.unwrap();
+        assert_eq!(plain.stdout.trim(), "visible");
+    }
+
+    #[test]
+    fn test_default_context_env_is_filtered() {
+        let filter = EnvFilter::default();
+        let context = ToolContext::default();
+        assert!(context.env.keys().all(|name| filter.permits(name)));
+    }
 }
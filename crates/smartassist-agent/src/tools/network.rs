@@ -1,18 +1,25 @@
-//! Network utility tools (DNS lookup, connectivity checks).
+//! Network utility tools (DNS lookup, connectivity checks, port scans).
 
+use crate::tools::dns::{self, DnsRecord, RecordType};
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::net::ToSocketAddrs;
+use std::net::{IpAddr, ToSocketAddrs};
 use std::time::{Duration, Instant};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tokio::time::timeout;
 
 /// Tool for DNS lookups.
+///
+/// Queries a recursive resolver directly, so any record type can be asked
+/// for and the resolver's DNSSEC verdict is reported. A and AAAA lookups
+/// without an explicit resolver go through the system resolver library
+/// instead, which also consults `/etc/hosts`.
 pub struct DnsLookupTool;
 
 impl DnsLookupTool {
@@ -34,13 +41,55 @@ struct DnsLookupArgs {
     /// Record type (default: A)
     #[serde(default)]
     record_type: Option<String>,
+    /// Resolver to query instead of the system one
+    #[serde(default)]
+    resolver: Option<String>,
+    /// Timeout in seconds (default: 5)
+    #[serde(default = "default_dns_timeout")]
+    timeout_secs: u64,
+}
+
+fn default_dns_timeout() -> u64 {
+    5
 }
 
 #[derive(Debug, Serialize)]
 struct DnsResult {
     hostname: String,
-    addresses: Vec<String>,
     record_type: String,
+    records: Vec<DnsRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    addresses: Vec<String>,
+    /// Whether the resolver authenticated the answer with DNSSEC.
+    authenticated: bool,
+    /// Resolver that answered, or `system` for the resolver library.
+    resolver: String,
+}
+
+impl DnsLookupTool {
+    /// Look up A or AAAA records with the system resolver library.
+    fn lookup_system(hostname: &str, record_type: RecordType) -> std::io::Result<Vec<DnsRecord>> {
+        // Use port 0 to just get addresses without connecting
+        let addrs = format!("{}:0", hostname).to_socket_addrs()?;
+        let mut records: Vec<DnsRecord> = Vec::new();
+        for addr in addrs {
+            let ip = addr.ip();
+            let wanted = match record_type {
+                RecordType::A => ip.is_ipv4(),
+                _ => ip.is_ipv6(),
+            };
+            let value = ip.to_string();
+            if wanted && !records.iter().any(|r| r.value == value) {
+                records.push(DnsRecord {
+                    name: hostname.to_string(),
+                    record_type: record_type.as_str(),
+                    ttl: 0,
+                    value,
+                });
+            }
+        }
+        Ok(records)
+    }
 }
 
 #[async_trait]
@@ -50,9 +99,12 @@ impl Tool for DnsLookupTool {
     }
 
     fn definition(&self) -> ToolDefinition {
+        let record_types: Vec<&str> = RecordType::ALL.iter().map(|t| t.as_str()).collect();
         ToolDefinition {
             name: "dns_lookup".to_string(),
-            description: "Look up DNS records for a hostname".to_string(),
+            description: "Look up DNS records for a hostname, reporting whether the answer \
+                was DNSSEC-authenticated"
+                .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -62,12 +114,22 @@ impl Tool for DnsLookupTool {
                     },
                     "record_type": {
                         "type": "string",
-                        "enum": ["A", "AAAA"],
+                        "enum": record_types,
                         "description": "Record type (default: A)"
+                    },
+                    "resolver": {
+                        "type": "string",
+                        "description": "Resolver IP, optionally with port, to query \
+                            instead of the system resolver (e.g. 192.0.2.53:5353)"
+                    },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "description": "Timeout in seconds (default: 5)"
                     }
                 },
                 "required": ["hostname"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -84,51 +146,113 @@ impl Tool for DnsLookupTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
         let args: DnsLookupArgs = serde_json::from_value(args)?;
-        let record_type = args.record_type.unwrap_or_else(|| "A".to_string());
-
-        // Use port 0 to just get addresses without connecting
-        let lookup = format!("{}:0", args.hostname);
-
-        match lookup.to_socket_addrs() {
-            Ok(addrs) => {
-                let addresses: Vec<String> = addrs
-                    .filter_map(|addr| {
-                        let ip = addr.ip();
-                        match record_type.as_str() {
-                            "A" if ip.is_ipv4() => Some(ip.to_string()),
-                            "AAAA" if ip.is_ipv6() => Some(ip.to_string()),
-                            "A" | "AAAA" => None,
-                            _ => Some(ip.to_string()),
-                        }
-                    })
-                    .collect();
+        let type_name = args.record_type.as_deref().unwrap_or("A");
+        let Some(record_type) = RecordType::parse(type_name) else {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                format!("Unsupported record type: {}", type_name),
+            ));
+        };
 
-                if addresses.is_empty() {
-                    Ok(ToolResult::error(
+        // Without a resolver, addresses come from the system resolver
+        // library, which also knows /etc/hosts, search domains and the like.
+        let address_lookup = matches!(record_type, RecordType::A | RecordType::Aaaa);
+        let (records, authenticated, resolver) = if address_lookup && args.resolver.is_none() {
+            match Self::lookup_system(&args.hostname, record_type) {
+                Ok(records) => (records, false, "system".to_string()),
+                Err(e) => {
+                    return Ok(ToolResult::error(
                         tool_use_id,
-                        format!("No {} records found for {}", record_type, args.hostname),
+                        format!("DNS lookup failed for {}: {}", args.hostname, e),
                     ))
-                } else {
-                    let result = DnsResult {
-                        hostname: args.hostname,
-                        addresses,
-                        record_type,
-                    };
-                    Ok(ToolResult::success(
+                }
+            }
+        } else {
+            let server = match &args.resolver {
+                Some(address) => match dns::parse_resolver(address) {
+                    Some(server) => server,
+                    None => {
+                        return Ok(ToolResult::error(
+                            tool_use_id,
+                            format!("Invalid resolver address: {}", address),
+                        ))
+                    }
+                },
+                None => match dns::system_resolver() {
+                    Some(server) => server,
+                    None => {
+                        return Ok(ToolResult::error(
+                            tool_use_id,
+                            "No resolver configured; pass one in 'resolver'",
+                        ))
+                    }
+                },
+            };
+            let wait = Duration::from_secs(args.timeout_secs.max(1));
+            match dns::query(server, &args.hostname, record_type, wait).await {
+                Ok(answer) => {
+                    if let Some(reason) = answer.rcode_error() {
+                        return Ok(ToolResult::error(
+                            tool_use_id,
+                            format!("DNS lookup failed for {}: {}", args.hostname, reason),
+                        ));
+                    }
+                    (answer.records, answer.authenticated, server.to_string())
+                }
+                Err(e) => {
+                    return Ok(ToolResult::error(
                         tool_use_id,
-                        json!(result),
-                    ).with_duration(start.elapsed()))
+                        format!("DNS lookup failed for {}: {}", args.hostname, e),
+                    ))
                 }
             }
-            Err(e) => Ok(ToolResult::error(
+        };
+
+        // CNAMEs the resolver followed don't count as answers on their own.
+        if !records.iter().any(|r| r.record_type == record_type.as_str()) {
+            return Ok(ToolResult::error(
                 tool_use_id,
-                format!("DNS lookup failed for {}: {}", args.hostname, e),
-            )),
+                format!("No {} records found for {}", record_type.as_str(), args.hostname),
+            ));
         }
+
+        let addresses = if address_lookup {
+            records
+                .iter()
+                .filter(|r| r.record_type == record_type.as_str())
+                .map(|r| r.value.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let result = DnsResult {
+            hostname: args.hostname,
+            record_type: record_type.as_str().to_string(),
+            records,
+            addresses,
+            authenticated,
+            resolver,
+        };
+        Ok(ToolResult::success(tool_use_id, json!(result)).with_duration(start.elapsed()))
     }
 }
 
+/// Most ports a single `port_check` call will probe.
+const MAX_SCAN_PORTS: usize = 1024;
+
+/// Most connections a scan keeps in flight at once.
+const MAX_SCAN_CONCURRENCY: usize = 64;
+
+const DEFAULT_SCAN_CONCURRENCY: usize = 16;
+
 /// Tool for checking TCP port connectivity.
+///
+/// Checks one port, or a list or range of ports on one host. Ports are
+/// probed by a bounded pool of connections, each with its own timeout, and
+/// reported as open, closed (the host refused the connection) or filtered
+/// (no answer within the timeout, or the network refused to route it). The
+/// port count and concurrency are capped so a call stays a connectivity
+/// check rather than a sweep.
 pub struct PortCheckTool;
 
 impl PortCheckTool {
@@ -148,21 +272,154 @@ struct PortCheckArgs {
     /// Host to check
     host: String,
     /// Port to check
-    port: u16,
-    /// Timeout in seconds (default: 5)
     #[serde(default)]
-    timeout_secs: Option<u64>,
+    port: Option<u16>,
+    /// Ports to scan instead of a single port
+    #[serde(default)]
+    ports: Option<PortList>,
+    /// Timeout per connection in seconds (default: 5, or 1 for a scan)
+    #[serde(default)]
+    timeout_secs: Option<f64>,
+    /// Connections in flight at once while scanning (default: 16)
+    #[serde(default)]
+    concurrency: Option<usize>,
+}
+
+/// Ports given either as a spec such as `"22,80,8000-8010"` or as a list.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PortList {
+    Spec(String),
+    List(Vec<u16>),
+}
+
+impl PortList {
+    /// Expand into sorted, distinct ports, rejecting port 0, reversed
+    /// ranges and lists longer than [`MAX_SCAN_PORTS`].
+    fn expand(&self) -> std::result::Result<Vec<u16>, String> {
+        let mut ports = Vec::new();
+        match self {
+            PortList::List(list) => ports.extend_from_slice(list),
+            PortList::Spec(spec) => {
+                for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                    let parse = |s: &str| {
+                        s.trim()
+                            .parse::<u16>()
+                            .map_err(|_| format!("Invalid port: {}", s.trim()))
+                    };
+                    match item.split_once('-') {
+                        Some((low, high)) => {
+                            let (low, high) = (parse(low)?, parse(high)?);
+                            if low > high {
+                                return Err(format!("Invalid port range: {}", item));
+                            }
+                            if usize::from(high - low) >= MAX_SCAN_PORTS {
+                                return Err(too_many_ports());
+                            }
+                            ports.extend(low..=high);
+                        }
+                        None => ports.push(parse(item)?),
+                    }
+                    if ports.len() > MAX_SCAN_PORTS {
+                        return Err(too_many_ports());
+                    }
+                }
+            }
+        }
+
+        if ports.contains(&0) {
+            return Err("Invalid port: 0".to_string());
+        }
+        ports.sort_unstable();
+        ports.dedup();
+        if ports.is_empty() {
+            return Err("No ports given".to_string());
+        }
+        if ports.len() > MAX_SCAN_PORTS {
+            return Err(too_many_ports());
+        }
+        Ok(ports)
+    }
+}
+
+fn too_many_ports() -> String {
+    format!("Too many ports: at most {} can be checked at once", MAX_SCAN_PORTS)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PortState {
+    Open,
+    Closed,
+    Filtered,
+}
+
+#[derive(Debug, Serialize)]
+struct PortProbe {
+    port: u16,
+    state: PortState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct PortCheckResult {
     host: String,
+    address: String,
     port: u16,
     open: bool,
+    state: PortState,
     response_time_ms: Option<u64>,
     error: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+struct PortScanResult {
+    host: String,
+    address: String,
+    ports: Vec<PortProbe>,
+    open: Vec<u16>,
+    closed: usize,
+    filtered: usize,
+    concurrency: usize,
+    timeout_ms: u64,
+}
+
+/// Try one TCP connection to `ip:port`.
+async fn probe_port(ip: IpAddr, port: u16, wait: Duration) -> PortProbe {
+    let start = Instant::now();
+    let (state, error) = match timeout(wait, TcpStream::connect((ip, port))).await {
+        Ok(Ok(_stream)) => (PortState::Open, None),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+            (PortState::Closed, Some(e.to_string()))
+        }
+        Ok(Err(e)) => (PortState::Filtered, Some(e.to_string())),
+        Err(_) => (PortState::Filtered, Some("Connection timeout".to_string())),
+    };
+    PortProbe {
+        port,
+        state,
+        response_time_ms: (state == PortState::Open).then(|| start.elapsed().as_millis() as u64),
+        error,
+    }
+}
+
+/// Probe `port` on each of `ips` in turn until one is open, as connecting
+/// by host name does, returning the address of the last probe.
+async fn probe_addresses(ips: &[IpAddr], port: u16, wait: Duration) -> (IpAddr, PortProbe) {
+    let mut last = None;
+    for &ip in ips {
+        let probe = probe_port(ip, port, wait).await;
+        if probe.state == PortState::Open {
+            return (ip, probe);
+        }
+        last = Some((ip, probe));
+    }
+    last.expect("at least one address")
+}
+
 #[async_trait]
 impl Tool for PortCheckTool {
     fn name(&self) -> &str {
@@ -172,7 +429,11 @@ impl Tool for PortCheckTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "port_check".to_string(),
-            description: "Check if a TCP port is open on a host".to_string(),
+            description: format!(
+                "Check if TCP ports are open on a host. Pass 'port' for one port or \
+                 'ports' for up to {} ports, each reported open, closed or filtered",
+                MAX_SCAN_PORTS
+            ),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -184,13 +445,30 @@ impl Tool for PortCheckTool {
                         "type": "integer",
                         "description": "Port number to check"
                     },
+                    "ports": {
+                        "oneOf": [
+                            {"type": "string"},
+                            {"type": "array", "items": {"type": "integer"}}
+                        ],
+                        "description": "Ports to scan, as a list or a spec such as \
+                            \"22,80,8000-8010\""
+                    },
                     "timeout_secs": {
+                        "type": "number",
+                        "description": "Timeout per connection in seconds \
+                            (default: 5, or 1 when scanning)"
+                    },
+                    "concurrency": {
                         "type": "integer",
-                        "description": "Timeout in seconds (default: 5)"
+                        "description": format!(
+                            "Connections in flight at once while scanning (default: {}, max: {})",
+                            DEFAULT_SCAN_CONCURRENCY, MAX_SCAN_CONCURRENCY
+                        )
                     }
                 },
-                "required": ["host", "port"]
+                "required": ["host"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -207,41 +485,95 @@ impl Tool for PortCheckTool {
     ) -> Result<ToolResult> {
         let start = Instant::now();
         let args: PortCheckArgs = serde_json::from_value(args)?;
-        let timeout_duration = Duration::from_secs(args.timeout_secs.unwrap_or(5));
 
-        let addr = format!("{}:{}", args.host, args.port);
+        let (ports, scan) = match (&args.ports, args.port) {
+            (Some(list), None) => match list.expand() {
+                Ok(ports) => (ports, true),
+                Err(e) => return Ok(ToolResult::error(tool_use_id, e)),
+            },
+            (None, Some(0)) => return Ok(ToolResult::error(tool_use_id, "Invalid port: 0")),
+            (None, Some(port)) => (vec![port], false),
+            (Some(_), Some(_)) => {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    "Pass either 'port' or 'ports', not both",
+                ))
+            }
+            (None, None) => {
+                return Ok(ToolResult::error(tool_use_id, "Missing 'port' or 'ports'"))
+            }
+        };
 
-        let result = match timeout(timeout_duration, TcpStream::connect(&addr)).await {
-            Ok(Ok(_stream)) => {
-                let elapsed = start.elapsed().as_millis() as u64;
-                PortCheckResult {
-                    host: args.host,
-                    port: args.port,
-                    open: true,
-                    response_time_ms: Some(elapsed),
-                    error: None,
-                }
+        let default_timeout = if scan { 1.0 } else { 5.0 };
+        let wait = Duration::from_secs_f64(
+            args.timeout_secs
+                .filter(|t| t.is_finite())
+                .unwrap_or(default_timeout)
+                .clamp(0.05, 60.0),
+        );
+
+        let mut ips: Vec<IpAddr> = match tokio::net::lookup_host((args.host.as_str(), 0)).await {
+            Ok(addrs) => addrs.map(|addr| addr.ip()).collect(),
+            Err(e) => {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    format!("Failed to resolve {}: {}", args.host, e),
+                ))
             }
-            Ok(Err(e)) => PortCheckResult {
-                host: args.host,
-                port: args.port,
-                open: false,
-                response_time_ms: None,
-                error: Some(e.to_string()),
-            },
-            Err(_) => PortCheckResult {
-                host: args.host,
-                port: args.port,
-                open: false,
-                response_time_ms: None,
-                error: Some("Connection timeout".to_string()),
-            },
         };
+        let mut seen = std::collections::HashSet::new();
+        ips.retain(|ip| seen.insert(*ip));
+        if ips.is_empty() {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                format!("No address found for {}", args.host),
+            ));
+        }
 
-        Ok(ToolResult::success(
-            tool_use_id,
-            json!(result),
-        ).with_duration(start.elapsed()))
+        if !scan {
+            let (ip, probe) = probe_addresses(&ips, ports[0], wait).await;
+            let result = PortCheckResult {
+                host: args.host,
+                address: ip.to_string(),
+                port: probe.port,
+                open: probe.state == PortState::Open,
+                state: probe.state,
+                response_time_ms: probe.response_time_ms,
+                error: probe.error,
+            };
+            return Ok(ToolResult::success(tool_use_id, json!(result))
+                .with_duration(start.elapsed()));
+        }
+
+        // Scan one address so every probe targets the same host.
+        let ip = ips[0];
+        let concurrency = args
+            .concurrency
+            .unwrap_or(DEFAULT_SCAN_CONCURRENCY)
+            .clamp(1, MAX_SCAN_CONCURRENCY);
+        let mut probes: Vec<PortProbe> = stream::iter(ports)
+            .map(|port| probe_port(ip, port, wait))
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+        probes.sort_unstable_by_key(|p| p.port);
+
+        let count = |state| probes.iter().filter(|p| p.state == state).count();
+        let result = PortScanResult {
+            host: args.host,
+            address: ip.to_string(),
+            open: probes
+                .iter()
+                .filter(|p| p.state == PortState::Open)
+                .map(|p| p.port)
+                .collect(),
+            closed: count(PortState::Closed),
+            filtered: count(PortState::Filtered),
+            ports: probes,
+            concurrency,
+            timeout_ms: wait.as_millis() as u64,
+        };
+        Ok(ToolResult::success(tool_use_id, json!(result)).with_duration(start.elapsed()))
     }
 }
 
@@ -310,6 +642,7 @@ impl Tool for HttpPingTool {
                 },
                 "required": ["url"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -463,6 +796,7 @@ impl Tool for NetInfoTool {
                 "properties": {},
                 "additionalProperties": false
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -514,6 +848,167 @@ impl Tool for NetInfoTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Encode a name in DNS wire form.
+    fn wire_name(name: &str) -> Vec<u8> {
+        let mut out = Vec::new();
+        for label in name.split('.') {
+            out.push(label.len() as u8);
+            out.extend_from_slice(label.as_bytes());
+        }
+        out.push(0);
+        out
+    }
+
+    /// Start a resolver on localhost that answers every query with
+    /// `answers` (record type and data), returning its address and a count
+    /// of the queries it received.
+    async fn stub_resolver(
+        rcode: u8,
+        authenticated: bool,
+        answers: Vec<(u16, Vec<u8>)>,
+    ) -> (SocketAddr, Arc<AtomicUsize>) {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let queries = Arc::new(AtomicUsize::new(0));
+        let counter = queries.clone();
+
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let query = &buf[..len];
+
+                // Question: name labels, then type and class.
+                let mut end = 12;
+                while query[end] != 0 {
+                    end += 1 + query[end] as usize;
+                }
+                end += 5;
+
+                let flags = 0x8180 | u16::from(rcode) | if authenticated { 0x0020 } else { 0 };
+                let mut response = query[..2].to_vec();
+                response.extend_from_slice(&flags.to_be_bytes());
+                response.extend_from_slice(&[0, 1, 0, answers.len() as u8, 0, 0, 0, 0]);
+                response.extend_from_slice(&query[12..end]);
+                for (record_type, data) in &answers {
+                    response.extend_from_slice(&[0xc0, 12]);
+                    response.extend_from_slice(&record_type.to_be_bytes());
+                    response.extend_from_slice(&[0, 1, 0, 0, 1, 44]);
+                    response.extend_from_slice(&(data.len() as u16).to_be_bytes());
+                    response.extend_from_slice(data);
+                }
+                socket.send_to(&response, peer).await.unwrap();
+            }
+        });
+
+        (addr, queries)
+    }
+
+    async fn lookup(args: serde_json::Value) -> ToolResult {
+        DnsLookupTool::new()
+            .execute("test", args, &ToolContext::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_dns_lookup_mx_records() {
+        let mx = |preference: u8, host: &str| {
+            let mut data = vec![0, preference];
+            data.extend(wire_name(host));
+            (15, data)
+        };
+        let (addr, queries) =
+            stub_resolver(0, true, vec![mx(10, "mx1.example.com"), mx(20, "mx2.example.com")])
+                .await;
+
+        let result = lookup(json!({
+            "hostname": "example.com",
+            "record_type": "MX",
+            "resolver": addr.to_string(),
+        }))
+        .await;
+
+        assert!(!result.is_error, "{}", result.output);
+        let values: Vec<&str> = result.output["records"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["value"].as_str().unwrap())
+            .collect();
+        assert_eq!(values, vec!["10 mx1.example.com", "20 mx2.example.com"]);
+        assert_eq!(result.output["records"][0]["type"], "MX");
+        assert_eq!(result.output["records"][0]["ttl"], 300);
+        assert_eq!(result.output["authenticated"], true);
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dns_lookup_txt_records() {
+        let (addr, _) = stub_resolver(
+            0,
+            false,
+            vec![
+                (16, b"\x0bv=spf1 -all".to_vec()),
+                // Long TXT values are split into several strings.
+                (16, b"\x03abc\x03def".to_vec()),
+            ],
+        )
+        .await;
+
+        let result = lookup(json!({
+            "hostname": "example.com",
+            "record_type": "txt",
+            "resolver": addr.to_string(),
+        }))
+        .await;
+
+        assert!(!result.is_error, "{}", result.output);
+        assert_eq!(result.output["record_type"], "TXT");
+        assert_eq!(result.output["records"][0]["value"], "v=spf1 -all");
+        assert_eq!(result.output["records"][1]["value"], "abcdef");
+        assert_eq!(result.output["authenticated"], false);
+    }
+
+    #[tokio::test]
+    async fn test_dns_lookup_uses_resolver_override() {
+        // A split-horizon name only the internal resolver knows.
+        let (addr, queries) = stub_resolver(0, false, vec![(1, vec![10, 1, 2, 3])]).await;
+        let result = lookup(json!({
+            "hostname": "intranet.corp.example",
+            "resolver": addr.to_string(),
+        }))
+        .await;
+
+        assert!(!result.is_error, "{}", result.output);
+        assert_eq!(result.output["addresses"], json!(["10.1.2.3"]));
+        assert_eq!(result.output["resolver"], addr.to_string());
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+
+        // The override's answer stands, even where the system resolver
+        // would have found the name.
+        let (addr, queries) = stub_resolver(3, false, Vec::new()).await;
+        let result = lookup(json!({
+            "hostname": "localhost",
+            "resolver": addr.to_string(),
+        }))
+        .await;
+        assert!(result.is_error);
+        assert!(result.output.as_str().unwrap().contains("NXDOMAIN"));
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dns_lookup_rejects_bad_arguments() {
+        let result = lookup(json!({"hostname": "example.com", "record_type": "SRV"})).await;
+        assert!(result.is_error);
+        let result = lookup(json!({"hostname": "example.com", "resolver": "dns.example"})).await;
+        assert!(result.is_error);
+    }
 
     #[tokio::test]
     async fn test_dns_lookup_localhost() {
@@ -528,8 +1023,9 @@ mod tests {
             &context,
         ).await.unwrap();
 
-        // localhost should resolve
+        // localhost should resolve, through the resolver library
         assert!(!result.is_error);
+        assert_eq!(result.output["resolver"], "system");
     }
 
     #[tokio::test]
@@ -567,6 +1063,129 @@ mod tests {
         assert!(!result.is_error);
     }
 
+    /// A port on localhost that nothing listens on.
+    fn closed_port() -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    async fn port_check(args: serde_json::Value) -> ToolResult {
+        PortCheckTool::new()
+            .execute("test", args, &ToolContext::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_port_check_open_listener() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let result = port_check(json!({"host": "127.0.0.1", "port": port})).await;
+        assert!(!result.is_error, "{}", result.output);
+        assert_eq!(result.output["open"], true);
+        assert_eq!(result.output["state"], "open");
+        assert!(result.output["response_time_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_port_check_tries_every_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Nothing listens on 127.0.0.2, so the first address is refused.
+        let ips = ["127.0.0.2".parse().unwrap(), "127.0.0.1".parse().unwrap()];
+        let (ip, probe) = probe_addresses(&ips, port, Duration::from_secs(2)).await;
+        assert_eq!(probe.state, PortState::Open);
+        assert_eq!(ip, ips[1]);
+
+        let result = port_check(json!({"host": "localhost", "port": port})).await;
+        assert_eq!(result.output["open"], true, "{}", result.output);
+        assert_eq!(result.output["address"], "127.0.0.1");
+    }
+
+    #[tokio::test]
+    async fn test_port_scan_reports_open_and_closed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().port();
+        let closed = closed_port();
+
+        let start = Instant::now();
+        let result = port_check(json!({
+            "host": "127.0.0.1",
+            "ports": [closed, open],
+            "timeout_secs": 2,
+            "concurrency": 4,
+        }))
+        .await;
+
+        // A refused connection is reported straight away, not after the timeout.
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(!result.is_error, "{}", result.output);
+        let ports = result.output["ports"].as_array().unwrap();
+        assert_eq!(ports.len(), 2);
+        let state = |port: u16| {
+            ports.iter().find(|p| p["port"] == port).unwrap()["state"].as_str().unwrap()
+        };
+        assert_eq!(state(open), "open");
+        assert_eq!(state(closed), "closed");
+        assert_eq!(result.output["open"], json!([open]));
+        assert_eq!(result.output["closed"], 1);
+        assert_eq!(result.output["filtered"], 0);
+        assert_eq!(result.output["timeout_ms"], 2000);
+    }
+
+    #[tokio::test]
+    async fn test_port_scan_range_with_bounded_concurrency() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().port();
+        let (low, high) = (open.saturating_sub(5).max(1), open.saturating_add(5));
+
+        let result = port_check(json!({
+            "host": "127.0.0.1",
+            "ports": format!("{}-{}", low, high),
+            "concurrency": 1000,
+        }))
+        .await;
+
+        assert!(!result.is_error, "{}", result.output);
+        assert_eq!(result.output["concurrency"], MAX_SCAN_CONCURRENCY);
+        let scanned: Vec<u64> = result.output["ports"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["port"].as_u64().unwrap())
+            .collect();
+        assert_eq!(scanned, (low..=high).map(u64::from).collect::<Vec<_>>());
+        assert!(result.output["open"].as_array().unwrap().contains(&json!(open)));
+    }
+
+    #[test]
+    fn test_port_list_expand() {
+        let spec = |s: &str| PortList::Spec(s.to_string()).expand();
+        assert_eq!(spec("443, 20-22,80,22").unwrap(), vec![20, 21, 22, 80, 443]);
+        assert_eq!(PortList::List(vec![8080, 80, 80]).expand().unwrap(), vec![80, 8080]);
+        assert!(spec("10-5").is_err());
+        assert!(spec("0").is_err());
+        assert!(spec("http").is_err());
+        assert!(spec("").is_err());
+        assert!(spec(&format!("1-{}", MAX_SCAN_PORTS)).is_ok());
+        assert!(spec(&format!("1-{}", MAX_SCAN_PORTS + 1))
+            .unwrap_err()
+            .starts_with("Too many ports"));
+        assert!(spec("1-65535").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_port_check_rejects_bad_arguments() {
+        let result = port_check(json!({"host": "127.0.0.1"})).await;
+        assert!(result.is_error);
+        let result = port_check(json!({"host": "127.0.0.1", "port": 80, "ports": [80]})).await;
+        assert!(result.is_error);
+        let result = port_check(json!({"host": "127.0.0.1", "ports": "1-5000"})).await;
+        assert!(result.is_error);
+    }
+
     #[tokio::test]
     async fn test_net_info() {
         let tool = NetInfoTool::new();
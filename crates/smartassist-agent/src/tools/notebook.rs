@@ -76,6 +76,7 @@ impl Tool for NotebookEditTool {
                 },
                 "required": ["notebook_path", "new_source"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
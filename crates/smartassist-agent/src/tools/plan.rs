@@ -60,6 +60,7 @@ impl Tool for EnterPlanModeTool {
                 "type": "object",
                 "properties": {},
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -155,6 +156,7 @@ impl Tool for ExitPlanModeTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
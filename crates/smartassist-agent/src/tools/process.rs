@@ -63,6 +63,7 @@ impl Tool for ProcessListTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -164,6 +165,7 @@ impl Tool for ProcessInfoTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -0,0 +1,591 @@
+//! Web search backends for [`WebSearchTool`](super::WebSearchTool).
+//!
+//! - [`BraveSearch`] - Brave Search API
+//! - [`SerpApiSearch`] - SerpAPI (Google results)
+//! - [`GenericSearch`] - Any endpoint taking `q`/`num` and a bearer token
+//!
+//! Backends return raw results; the tool normalizes and caps them with
+//! [`normalize_results`] so every backend produces the same output.
+
+use crate::error::AgentError;
+use crate::Result;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Brave Search API endpoint.
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+/// SerpAPI endpoint.
+const SERPAPI_ENDPOINT: &str = "https://serpapi.com/search.json";
+
+/// Longest snippet kept in a normalized result, in characters.
+const MAX_SNIPPET_CHARS: usize = 300;
+
+/// A single search result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Page title.
+    pub title: String,
+    /// Page URL.
+    pub url: String,
+    /// Short excerpt of the page.
+    pub snippet: String,
+}
+
+impl SearchResult {
+    /// Create a result.
+    pub fn new(
+        title: impl Into<String>,
+        url: impl Into<String>,
+        snippet: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            url: url.into(),
+            snippet: snippet.into(),
+        }
+    }
+}
+
+/// A web search provider.
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    /// Provider name, reported in the tool output.
+    fn name(&self) -> &str;
+
+    /// Search for `query`, returning up to about `count` results.
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>>;
+}
+
+/// Supported search providers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchProvider {
+    /// Brave Search API.
+    #[default]
+    Brave,
+    /// SerpAPI.
+    SerpApi,
+    /// A generic endpoint; requires `endpoint`.
+    Generic,
+}
+
+impl SearchProvider {
+    /// Parse a provider name as used in configuration.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "brave" => Some(Self::Brave),
+            "serpapi" => Some(Self::SerpApi),
+            "generic" => Some(Self::Generic),
+            _ => None,
+        }
+    }
+
+    /// Provider name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Brave => "brave",
+            Self::SerpApi => "serpapi",
+            Self::Generic => "generic",
+        }
+    }
+
+    /// Provider-specific environment variable holding the API key.
+    fn key_env_var(&self) -> Option<&'static str> {
+        match self {
+            Self::Brave => Some("BRAVE_API_KEY"),
+            Self::SerpApi => Some("SERPAPI_API_KEY"),
+            Self::Generic => None,
+        }
+    }
+}
+
+/// Web search configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebSearchConfig {
+    /// Provider to query.
+    #[serde(default)]
+    pub provider: SearchProvider,
+
+    /// API key for the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// Endpoint override; required for the generic provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// Most results returned per search.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+}
+
+impl WebSearchConfig {
+    /// Read the configuration from the environment.
+    ///
+    /// `SMARTASSIST_SEARCH_PROVIDER` picks the provider; without it, the
+    /// generic provider is used when `SMARTASSIST_SEARCH_ENDPOINT` is set and
+    /// Brave otherwise. The key comes from `SMARTASSIST_SEARCH_API_KEY`, or
+    /// the provider's own variable (`BRAVE_API_KEY`, `SERPAPI_API_KEY`).
+    pub fn from_env() -> std::result::Result<Self, String> {
+        use smartassist_core::env::{get_usize, get_var};
+
+        let endpoint = get_var("SMARTASSIST_SEARCH_ENDPOINT");
+        let provider = match get_var("SMARTASSIST_SEARCH_PROVIDER") {
+            Some(name) => SearchProvider::parse(&name).ok_or_else(|| {
+                format!(
+                    "Web search is disabled: unknown provider '{}' in \
+                     SMARTASSIST_SEARCH_PROVIDER (expected brave, serpapi or generic).",
+                    name
+                )
+            })?,
+            None if endpoint.is_some() => SearchProvider::Generic,
+            None => SearchProvider::Brave,
+        };
+        let api_key = get_var("SMARTASSIST_SEARCH_API_KEY")
+            .or_else(|| provider.key_env_var().and_then(get_var));
+
+        Ok(Self {
+            provider,
+            api_key,
+            endpoint,
+            max_results: get_usize("SMARTASSIST_SEARCH_MAX_RESULTS"),
+        })
+    }
+
+    /// Build the configured backend.
+    ///
+    /// Fails with a message for the user when a required setting is missing.
+    pub fn backend(&self, client: Client) -> std::result::Result<Box<dyn SearchBackend>, String> {
+        let Some(api_key) = self.api_key.clone().filter(|k| !k.is_empty()) else {
+            let env_hint = match self.provider.key_env_var() {
+                Some(var) => format!("SMARTASSIST_SEARCH_API_KEY or {}", var),
+                None => "SMARTASSIST_SEARCH_API_KEY".to_string(),
+            };
+            return Err(format!(
+                "Web search is disabled: no API key for the {} provider. Set {}.",
+                self.provider.as_str(),
+                env_hint
+            ));
+        };
+
+        Ok(match self.provider {
+            SearchProvider::Brave => Box::new(BraveSearch {
+                client,
+                api_key,
+                endpoint: self.endpoint.clone().unwrap_or_else(|| BRAVE_ENDPOINT.to_string()),
+            }),
+            SearchProvider::SerpApi => Box::new(SerpApiSearch {
+                client,
+                api_key,
+                endpoint: self.endpoint.clone().unwrap_or_else(|| SERPAPI_ENDPOINT.to_string()),
+            }),
+            SearchProvider::Generic => {
+                let endpoint = self.endpoint.clone().ok_or_else(|| {
+                    "Web search is disabled: the generic provider needs \
+                     SMARTASSIST_SEARCH_ENDPOINT."
+                        .to_string()
+                })?;
+                Box::new(GenericSearch {
+                    client,
+                    api_key,
+                    endpoint,
+                })
+            }
+        })
+    }
+}
+
+/// Client used by backends built from configuration.
+pub(crate) fn default_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Brave Search API backend.
+pub struct BraveSearch {
+    client: Client,
+    api_key: String,
+    endpoint: String,
+}
+
+impl BraveSearch {
+    /// Create a backend for the public Brave endpoint.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: default_client(),
+            api_key: api_key.into(),
+            endpoint: BRAVE_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Set the endpoint.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+}
+
+#[async_trait]
+impl SearchBackend for BraveSearch {
+    fn name(&self) -> &str {
+        "brave"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        // Brave returns at most 20 results per request.
+        let request = self
+            .client
+            .get(&self.endpoint)
+            .query(&[("q", query), ("count", &count.min(20).to_string())])
+            .header("Accept", "application/json")
+            .header("X-Subscription-Token", &self.api_key);
+        let body = send(request).await?;
+        Ok(parse_results(&body["web"]["results"], &["url"], &["description"]))
+    }
+}
+
+/// SerpAPI backend, returning Google results.
+pub struct SerpApiSearch {
+    client: Client,
+    api_key: String,
+    endpoint: String,
+}
+
+impl SerpApiSearch {
+    /// Create a backend for the public SerpAPI endpoint.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: default_client(),
+            api_key: api_key.into(),
+            endpoint: SERPAPI_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Set the endpoint.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+}
+
+#[async_trait]
+impl SearchBackend for SerpApiSearch {
+    fn name(&self) -> &str {
+        "serpapi"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let request = self.client.get(&self.endpoint).query(&[
+            ("engine", "google"),
+            ("q", query),
+            ("num", &count.to_string()),
+            ("api_key", &self.api_key),
+        ]);
+        let body = send(request).await?;
+        Ok(parse_results(&body["organic_results"], &["link"], &["snippet"]))
+    }
+}
+
+/// Backend for a search API taking `q` and `num` parameters and a bearer
+/// token, returning `results`, `items` or `organic` arrays.
+pub struct GenericSearch {
+    client: Client,
+    api_key: String,
+    endpoint: String,
+}
+
+impl GenericSearch {
+    /// Create a backend for `endpoint`.
+    pub fn new(endpoint: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            client: default_client(),
+            api_key: api_key.into(),
+            endpoint: endpoint.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchBackend for GenericSearch {
+    fn name(&self) -> &str {
+        "generic"
+    }
+
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let request = self
+            .client
+            .get(&self.endpoint)
+            .query(&[("q", query), ("num", &count.to_string())])
+            .bearer_auth(&self.api_key);
+        let body = send(request).await?;
+        let items = ["results", "items", "organic"]
+            .iter()
+            .find_map(|key| body.get(*key))
+            .unwrap_or(&Value::Null);
+        Ok(parse_results(items, &["url", "link"], &["snippet", "description"]))
+    }
+}
+
+/// Send a search request and parse the JSON response.
+async fn send(request: RequestBuilder) -> Result<Value> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AgentError::tool_execution(format!("Search request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AgentError::tool_execution(format!(
+            "Search API error: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| AgentError::tool_execution(format!("Failed to parse search response: {}", e)))
+}
+
+/// Read results from a JSON array of objects with a `title`, a URL and a
+/// snippet.
+///
+/// Each item's URL and snippet come from the first of the given fields it
+/// has, so items using different field names can share an array.
+fn parse_results(items: &Value, url_fields: &[&str], snippet_fields: &[&str]) -> Vec<SearchResult> {
+    items
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let field = |names: &[&str]| {
+                        names.iter().find_map(|name| item.get(*name)?.as_str())
+                    };
+                    Some(SearchResult::new(
+                        item.get("title")?.as_str()?,
+                        field(url_fields)?,
+                        field(snippet_fields).unwrap_or_default(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Clean up results from any backend and keep at most `max`.
+///
+/// Markup and entities are removed from titles and snippets, whitespace is
+/// collapsed and snippets are shortened. Results without a title or an
+/// http(s) URL, and repeats of a URL already seen, are dropped.
+pub fn normalize_results(results: Vec<SearchResult>, max: usize) -> Vec<SearchResult> {
+    let mut seen = HashSet::new();
+    results
+        .into_iter()
+        .filter_map(|result| {
+            let url = result.url.trim().to_string();
+            let title = clean_text(&result.title);
+            let web = url.starts_with("https://") || url.starts_with("http://");
+            if title.is_empty() || !web || !seen.insert(url.clone()) {
+                return None;
+            }
+            Some(SearchResult {
+                title,
+                url,
+                snippet: truncate(&clean_text(&result.snippet), MAX_SNIPPET_CHARS),
+            })
+        })
+        .take(max)
+        .collect()
+}
+
+/// Strip tags and common entities and collapse whitespace.
+fn clean_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    let decoded = plain
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&");
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Shorten `text` to at most `max` characters, ending with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_normalize_results() {
+        let results = vec![
+            SearchResult::new(
+                " <b>Rust</b>  &amp; Cargo ",
+                "https://rust-lang.org ",
+                "A <strong>language</strong>\n empowering",
+            ),
+            SearchResult::new("Duplicate", "https://rust-lang.org", "again"),
+            SearchResult::new("", "https://empty-title.example", "x"),
+            SearchResult::new("Not web", "javascript:alert(1)", "x"),
+            SearchResult::new("Long", "http://long.example", "word ".repeat(100)),
+            SearchResult::new("Over the cap", "https://capped.example", ""),
+        ];
+
+        let normalized = normalize_results(results, 2);
+
+        assert_eq!(normalized.len(), 2);
+        assert_eq!(
+            normalized[0],
+            SearchResult::new("Rust & Cargo", "https://rust-lang.org", "A language empowering")
+        );
+        assert_eq!(normalized[1].title, "Long");
+        assert_eq!(normalized[1].snippet.chars().count(), MAX_SNIPPET_CHARS);
+        assert!(normalized[1].snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn test_missing_api_key_disables() {
+        let config = WebSearchConfig {
+            provider: SearchProvider::SerpApi,
+            ..Default::default()
+        };
+        let err = config.backend(default_client()).err().unwrap();
+        assert!(err.contains("no API key for the serpapi provider"), "{}", err);
+        assert!(err.contains("SERPAPI_API_KEY"), "{}", err);
+
+        let config = WebSearchConfig {
+            provider: SearchProvider::Generic,
+            api_key: Some("k".to_string()),
+            ..Default::default()
+        };
+        let err = config.backend(default_client()).err().unwrap();
+        assert!(err.contains("SMARTASSIST_SEARCH_ENDPOINT"), "{}", err);
+    }
+
+    #[test]
+    fn test_config_selects_provider() {
+        let config: WebSearchConfig =
+            serde_json::from_value(serde_json::json!({ "provider": "serpapi", "api_key": "k" }))
+                .unwrap();
+        assert_eq!(config.backend(default_client()).unwrap().name(), "serpapi");
+
+        let config = WebSearchConfig {
+            api_key: Some("k".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.backend(default_client()).unwrap().name(), "brave");
+        assert_eq!(SearchProvider::parse("Generic"), Some(SearchProvider::Generic));
+        assert_eq!(SearchProvider::parse("bing"), None);
+    }
+
+    #[tokio::test]
+    async fn test_brave_backend() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("q", "rust"))
+            .and(query_param("count", "5"))
+            .and(header("X-Subscription-Token", "brave-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "web": { "results": [
+                    {
+                        "title": "Rust",
+                        "url": "https://rust-lang.org",
+                        "description": "A language"
+                    },
+                    { "title": "No URL" }
+                ]}
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = BraveSearch::new("brave-key").with_endpoint(server.uri());
+        let results = backend.search("rust", 5).await.unwrap();
+        assert_eq!(
+            results,
+            vec![SearchResult::new("Rust", "https://rust-lang.org", "A language")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serpapi_backend() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("q", "rust"))
+            .and(query_param("api_key", "serp-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "organic_results": [
+                    { "title": "Rust", "link": "https://rust-lang.org", "snippet": "A language" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = SerpApiSearch::new("serp-key").with_endpoint(server.uri());
+        let results = backend.search("rust", 3).await.unwrap();
+        assert_eq!(
+            results,
+            vec![SearchResult::new("Rust", "https://rust-lang.org", "A language")]
+        );
+
+        let failing = SerpApiSearch::new("bad-key").with_endpoint(server.uri());
+        let err = failing.search("rust", 3).await.unwrap_err().to_string();
+        assert!(err.contains("Search API error: 404"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_generic_backend_falls_back_per_field() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("q", "rust"))
+            .and(header("Authorization", "Bearer generic-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "items": [
+                    {
+                        "title": "Rust",
+                        "url": "https://rust-lang.org",
+                        "description": "A language"
+                    },
+                    { "title": "Book", "link": "https://doc.rust-lang.org/book", "snippet": "Read" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let backend = GenericSearch::new(server.uri(), "generic-key");
+        let results = backend.search("rust", 5).await.unwrap();
+        assert_eq!(
+            results,
+            vec![
+                SearchResult::new("Rust", "https://rust-lang.org", "A language"),
+                SearchResult::new("Book", "https://doc.rust-lang.org/book", "Read"),
+            ]
+        );
+    }
+}
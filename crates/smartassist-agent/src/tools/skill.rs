@@ -170,6 +170,7 @@ impl Tool for SkillTool {
                 },
                 "required": ["skill"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -274,6 +275,7 @@ impl Tool for SkillListTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -51,6 +51,7 @@ impl Tool for CaseTool {
                 },
                 "required": ["input", "to"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -259,6 +260,7 @@ impl Tool for SplitJoinTool {
                 },
                 "required": ["operation"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -418,6 +420,7 @@ impl Tool for ReplaceTool {
                 },
                 "required": ["input", "pattern", "replacement"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -591,6 +594,7 @@ impl Tool for TrimPadTool {
                 },
                 "required": ["input", "operation"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
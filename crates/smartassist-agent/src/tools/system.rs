@@ -15,6 +15,9 @@ use tracing::warn;
 /// Shell metacharacters that indicate potential command injection in paths/arguments.
 const SHELL_METACHARACTERS: &[char] = &['`', '$', '|', '&', ';', '\n', '\r', '\0'];
 
+/// Default limit on output captured from one command, across stdout and stderr.
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024; // 1 MB
+
 /// Bash tool - Execute shell commands with sandboxing.
 pub struct BashTool {
     /// Allowed commands (regex patterns).
@@ -25,6 +28,9 @@ pub struct BashTool {
 
     /// Compiled blocked regexes.
     blocked_regexes: Vec<Regex>,
+
+    /// Maximum output bytes captured per command.
+    max_output_bytes: usize,
 }
 
 impl Default for BashTool {
@@ -70,9 +76,18 @@ impl BashTool {
             allowed_patterns: vec![],
             blocked_patterns,
             blocked_regexes,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
         }
     }
 
+    /// Set the maximum output bytes captured per command.
+    ///
+    /// Longer output is cut off with a truncation marker.
+    pub fn with_max_output(mut self, bytes: usize) -> Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
     /// Add an allowed pattern.
     pub fn allow(mut self, pattern: impl Into<String>) -> Self {
         self.allowed_patterns.push(pattern.into());
@@ -180,6 +195,7 @@ impl Tool for BashTool {
                 },
                 "required": ["command"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -215,9 +231,9 @@ impl Tool for BashTool {
         // Set up execution context
         let exec_context = ExecutionContext::new(&cwd)
             .with_profile(context.sandbox_profile.clone())
-            .with_envs(context.env.clone());
+            .with_clean_env(context.env.clone());
 
-        let executor = CommandExecutor::new(exec_context);
+        let executor = CommandExecutor::new(exec_context).with_max_output_size(self.max_output_bytes);
 
         // Get timeout (default 120s, max 600s)
         let timeout = args
@@ -233,8 +249,10 @@ impl Tool for BashTool {
         let result_output = serde_json::json!({
             "stdout": output.stdout,
             "stderr": output.stderr,
-            "exit_code": output.exit_code,
+            "exit_code": output.exit_code(),
+            "status": output.status,
             "timed_out": output.timed_out,
+            "truncated": output.truncated,
             "duration_ms": duration.as_millis() as u64,
         });
 
@@ -267,6 +285,56 @@ impl Tool for BashTool {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_bash_sees_only_context_env() {
+        let tool = BashTool::new();
+        let mut context = ToolContext::default();
+        context.env.insert("GREETING".to_string(), "hello".to_string());
+
+        let result = tool
+            .execute(
+                "test",
+                serde_json::json!({ "command": "echo \"$GREETING ${CARGO_PKG_NAME:-unset}\"" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.output["stdout"].as_str().unwrap().trim(), "hello unset");
+    }
+
+    #[tokio::test]
+    async fn test_bash_output_cap() {
+        let tool = BashTool::new().with_max_output(4096);
+        let context = ToolContext::default();
+
+        let result = tool
+            .execute(
+                "test",
+                serde_json::json!({ "command": "yes | head -c 1000000" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        let stdout = result.output["stdout"].as_str().unwrap();
+        assert_eq!(result.output["truncated"], true);
+        assert!(stdout.len() < 4200);
+        assert!(stdout.contains("[Output truncated at 4096 bytes]"));
+        assert!(result.output["duration_ms"].is_u64());
+
+        let result = tool
+            .execute(
+                "test",
+                serde_json::json!({ "command": "seq 1 500; exit 3" }),
+                &context,
+            )
+            .await
+            .unwrap();
+        let expected: String = (1..=500).map(|n| format!("{}\n", n)).collect();
+        assert_eq!(result.output["stdout"], expected);
+        assert_eq!(result.output["truncated"], false);
+        assert_eq!(result.output["exit_code"], 3);
+    }
+
     #[test]
     fn test_bash_tool_creation() {
         let tool = BashTool::new();
@@ -158,6 +158,7 @@ impl Tool for TaskCreateTool {
                 },
                 "required": ["subject", "description"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -227,6 +228,7 @@ impl Tool for TaskListTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -311,6 +313,7 @@ impl Tool for TaskUpdateTool {
                 },
                 "required": ["task_id"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -403,6 +406,7 @@ impl Tool for TaskGetTool {
                 },
                 "required": ["task_id"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -4,10 +4,8 @@ use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
-use regex::Regex;
 use serde::Deserialize;
 use serde_json::json;
-use std::collections::HashMap;
 use std::time::Instant;
 
 /// Tool for simple template string substitution.
@@ -30,7 +28,7 @@ struct TemplateArgs {
     /// Template string with {{variable}} placeholders
     template: String,
     /// Variables to substitute
-    variables: HashMap<String, serde_json::Value>,
+    variables: smartassist_core::template::TemplateVars,
 }
 
 #[async_trait]
@@ -57,6 +55,7 @@ impl Tool for TemplateTool {
                 },
                 "required": ["template", "variables"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -74,36 +73,15 @@ impl Tool for TemplateTool {
         let start = Instant::now();
         let args: TemplateArgs = serde_json::from_value(args)?;
 
-        let re = Regex::new(r"\{\{(\w+)\}\}")
-            .map_err(|e| crate::error::AgentError::tool_execution(format!("Regex error: {}", e)))?;
-
-        let mut result = args.template.clone();
-        let mut substitutions = Vec::new();
-        let mut missing = Vec::new();
-
-        for cap in re.captures_iter(&args.template) {
-            let var_name = &cap[1];
-            if let Some(value) = args.variables.get(var_name) {
-                let value_str = match value {
-                    serde_json::Value::String(s) => s.clone(),
-                    serde_json::Value::Number(n) => n.to_string(),
-                    serde_json::Value::Bool(b) => b.to_string(),
-                    serde_json::Value::Null => "null".to_string(),
-                    _ => serde_json::to_string(value).unwrap_or_default(),
-                };
-                result = result.replace(&format!("{{{{{}}}}}", var_name), &value_str);
-                substitutions.push(var_name.to_string());
-            } else {
-                missing.push(var_name.to_string());
-            }
-        }
+        // Same substitution providers apply to templated messages.
+        let filled = smartassist_core::template::fill_template(&args.template, &args.variables);
 
         Ok(ToolResult::success(
             tool_use_id,
             json!({
-                "result": result,
-                "substitutions": substitutions,
-                "missing": missing
+                "result": filled.text,
+                "substitutions": filled.substituted,
+                "missing": filled.missing
             }),
         ).with_duration(start.elapsed()))
     }
@@ -163,6 +141,7 @@ impl Tool for FormatTool {
                 },
                 "required": ["value", "format_type"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -50,6 +50,7 @@ impl Tool for NowTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -164,6 +165,7 @@ impl Tool for DateParseTool {
                 },
                 "required": ["input"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -333,6 +335,7 @@ impl Tool for DateCalcTool {
                 },
                 "required": ["date", "operation"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
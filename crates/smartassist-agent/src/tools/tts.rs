@@ -0,0 +1,677 @@
+//! Text to speech backends for [`TtsTool`](super::TtsTool).
+//!
+//! - [`OpenAiTts`] - OpenAI speech API (MP3)
+//! - [`LocalTts`] - A local espeak-compatible engine (WAV)
+//!
+//! Long text can be synthesized in chunks with
+//! [`TtsBackend::synthesize_stream`], which splits it at sentence
+//! boundaries so the first chunk can be played while the rest is produced.
+
+use crate::error::AgentError;
+use crate::Result;
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// OpenAI API base URL.
+const OPENAI_ENDPOINT: &str = "https://api.openai.com";
+
+/// Voices offered by the OpenAI speech API.
+const OPENAI_VOICES: &[&str] = &["alloy", "echo", "fable", "onyx", "nova", "shimmer"];
+
+/// Longest input the OpenAI speech API accepts, in characters.
+const OPENAI_MAX_CHARS: usize = 4096;
+
+/// Local engine run when none is configured.
+const LOCAL_PROGRAM: &str = "espeak-ng";
+
+/// Voices assumed for the local engine when none are configured.
+const LOCAL_VOICES: &[&str] = &["en", "en-us", "en-gb", "de", "es", "fr"];
+
+/// Speaking rate of the local engine at speed 1.0, in words per minute.
+const LOCAL_BASE_WPM: f64 = 175.0;
+
+/// Default chunk size for streaming synthesis, in characters.
+///
+/// Small enough that the first chunk comes back quickly.
+pub const DEFAULT_CHUNK_CHARS: usize = 400;
+
+/// Encoding of synthesized audio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    /// MPEG layer 3.
+    Mp3,
+    /// RIFF WAVE.
+    Wav,
+}
+
+impl AudioFormat {
+    /// File extension, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Wav => "wav",
+        }
+    }
+
+    /// MIME type.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "audio/mpeg",
+            Self::Wav => "audio/wav",
+        }
+    }
+}
+
+/// Synthesized audio.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audio {
+    /// Encoded audio data.
+    pub bytes: Vec<u8>,
+    /// Encoding of `bytes`.
+    pub format: AudioFormat,
+}
+
+impl Audio {
+    /// Create audio from encoded bytes.
+    pub fn new(bytes: impl Into<Vec<u8>>, format: AudioFormat) -> Self {
+        Self {
+            bytes: bytes.into(),
+            format,
+        }
+    }
+}
+
+/// Audio chunks produced by [`TtsBackend::synthesize_stream`], in order.
+pub type AudioStream<'a> = BoxStream<'a, Result<Audio>>;
+
+/// A text to speech engine.
+#[async_trait]
+pub trait TtsBackend: Send + Sync {
+    /// Backend name, reported in the tool output.
+    fn name(&self) -> &str;
+
+    /// Voices the backend can speak with; the first is the default.
+    fn voices(&self) -> &[String];
+
+    /// Longest text accepted by a single [`synthesize`](Self::synthesize)
+    /// call, in characters.
+    fn max_chars(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Synthesize `text` with `voice` at `speed` (1.0 is normal).
+    async fn synthesize(&self, text: &str, voice: &str, speed: f64) -> Result<Audio>;
+
+    /// Synthesize `text` as a sequence of chunks of about `chunk_chars`
+    /// characters, split at sentence boundaries.
+    ///
+    /// Each chunk is synthesized when the stream is polled for it, so the
+    /// first one is ready without waiting for the rest.
+    fn synthesize_stream<'a>(
+        &'a self,
+        text: &'a str,
+        voice: &'a str,
+        speed: f64,
+        chunk_chars: usize,
+    ) -> AudioStream<'a> {
+        let chunks = split_text(text, chunk_chars.min(self.max_chars()));
+        stream::iter(chunks)
+            .then(move |chunk| async move { self.synthesize(&chunk, voice, speed).await })
+            .boxed()
+    }
+}
+
+/// Supported text to speech backends.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsProvider {
+    /// OpenAI speech API.
+    #[default]
+    OpenAi,
+    /// Local espeak-compatible engine.
+    Local,
+}
+
+impl TtsProvider {
+    /// Parse a backend name as used in configuration.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "openai" => Some(Self::OpenAi),
+            "local" | "espeak" => Some(Self::Local),
+            _ => None,
+        }
+    }
+
+    /// Backend name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::Local => "local",
+        }
+    }
+}
+
+/// Text to speech configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TtsConfig {
+    /// Backend to synthesize with.
+    #[serde(default)]
+    pub backend: TtsProvider,
+
+    /// API key for the OpenAI backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+
+    /// API base URL override for the OpenAI backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// Program run by the local backend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Voices installed for the local backend.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub voices: Vec<String>,
+
+    /// Voice used when a request does not name one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+
+    /// Chunk size for streaming synthesis, in characters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_chars: Option<usize>,
+}
+
+impl TtsConfig {
+    /// Read the configuration from the environment.
+    ///
+    /// `SMARTASSIST_TTS_BACKEND` picks the backend (default `openai`). The
+    /// OpenAI key comes from `SMARTASSIST_TTS_API_KEY` or `OPENAI_API_KEY`;
+    /// the local engine is set with `SMARTASSIST_TTS_COMMAND` and its voices
+    /// with a comma separated `SMARTASSIST_TTS_VOICES`.
+    pub fn from_env() -> std::result::Result<Self, String> {
+        use smartassist_core::env::{get_usize, get_var};
+
+        let backend = match get_var("SMARTASSIST_TTS_BACKEND") {
+            Some(name) => TtsProvider::parse(&name).ok_or_else(|| {
+                format!(
+                    "Text to speech is disabled: unknown backend '{}' in \
+                     SMARTASSIST_TTS_BACKEND (expected openai or local).",
+                    name
+                )
+            })?,
+            None => TtsProvider::OpenAi,
+        };
+        let voices = get_var("SMARTASSIST_TTS_VOICES")
+            .map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            backend,
+            api_key: get_var("SMARTASSIST_TTS_API_KEY").or_else(|| get_var("OPENAI_API_KEY")),
+            endpoint: get_var("SMARTASSIST_TTS_ENDPOINT"),
+            command: get_var("SMARTASSIST_TTS_COMMAND"),
+            voices,
+            voice: get_var("SMARTASSIST_TTS_VOICE"),
+            chunk_chars: get_usize("SMARTASSIST_TTS_CHUNK_CHARS"),
+        })
+    }
+
+    /// Build the configured backend.
+    ///
+    /// Fails with a message for the user when a required setting is missing.
+    pub fn backend(&self, client: Client) -> std::result::Result<Box<dyn TtsBackend>, String> {
+        match self.backend {
+            TtsProvider::OpenAi => {
+                let Some(api_key) = self.api_key.clone().filter(|k| !k.is_empty()) else {
+                    return Err("Text to speech is disabled: no API key for the openai backend. \
+                                Set SMARTASSIST_TTS_API_KEY or OPENAI_API_KEY."
+                        .to_string());
+                };
+                let mut backend = OpenAiTts::new(api_key);
+                backend.client = client;
+                if let Some(endpoint) = &self.endpoint {
+                    backend = backend.with_base_url(endpoint.clone());
+                }
+                Ok(Box::new(backend))
+            }
+            TtsProvider::Local => {
+                let mut backend = LocalTts::new();
+                if let Some(command) = &self.command {
+                    backend = backend.with_program(command.clone());
+                }
+                if !self.voices.is_empty() {
+                    backend = backend.with_voices(self.voices.clone());
+                }
+                Ok(Box::new(backend))
+            }
+        }
+    }
+}
+
+/// Client used by backends built from configuration.
+pub(crate) fn default_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap_or_default()
+}
+
+/// OpenAI speech API backend.
+pub struct OpenAiTts {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    model: String,
+    voices: Vec<String>,
+}
+
+impl OpenAiTts {
+    /// Create a backend for the public OpenAI API.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: default_client(),
+            api_key: api_key.into(),
+            base_url: OPENAI_ENDPOINT.to_string(),
+            model: "tts-1".to_string(),
+            voices: OPENAI_VOICES.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    /// Set the API base URL.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    /// Set the speech model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+}
+
+#[async_trait]
+impl TtsBackend for OpenAiTts {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn voices(&self) -> &[String] {
+        &self.voices
+    }
+
+    fn max_chars(&self) -> usize {
+        OPENAI_MAX_CHARS
+    }
+
+    async fn synthesize(&self, text: &str, voice: &str, speed: f64) -> Result<Audio> {
+        let url = format!("{}/v1/audio/speech", self.base_url.trim_end_matches('/'));
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": text,
+            "voice": voice,
+            "speed": speed,
+            "response_format": "mp3"
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.api_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AgentError::tool_execution(format!("TTS API request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_body = response.text().await.unwrap_or_default();
+            return Err(AgentError::tool_execution(format!(
+                "TTS API returned {}: {}",
+                status, error_body
+            )));
+        }
+
+        let bytes = response.bytes().await.map_err(|e| {
+            AgentError::tool_execution(format!("Failed to read TTS response body: {}", e))
+        })?;
+        Ok(Audio::new(bytes.to_vec(), AudioFormat::Mp3))
+    }
+}
+
+/// Local engine backend, for espeak-ng or a program taking the same
+/// arguments (`--stdout`, `--stdin`, `-v <voice>`, `-s <wpm>`).
+pub struct LocalTts {
+    program: String,
+    voices: Vec<String>,
+}
+
+impl Default for LocalTts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalTts {
+    /// Create a backend running espeak-ng.
+    pub fn new() -> Self {
+        Self {
+            program: LOCAL_PROGRAM.to_string(),
+            voices: LOCAL_VOICES.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    /// Set the program to run.
+    pub fn with_program(mut self, program: impl Into<String>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Set the installed voices.
+    pub fn with_voices(mut self, voices: Vec<String>) -> Self {
+        self.voices = voices;
+        self
+    }
+}
+
+/// Words per minute for the local engine at `speed`, within espeak's range.
+fn local_wpm(speed: f64) -> u32 {
+    (LOCAL_BASE_WPM * speed).round().clamp(80.0, 450.0) as u32
+}
+
+#[async_trait]
+impl TtsBackend for LocalTts {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn voices(&self) -> &[String] {
+        &self.voices
+    }
+
+    async fn synthesize(&self, text: &str, voice: &str, speed: f64) -> Result<Audio> {
+        // Text goes through stdin so that it is never parsed as options.
+        let mut child = tokio::process::Command::new(&self.program)
+            .args(["--stdout", "--stdin", "-v", voice, "-s"])
+            .arg(local_wpm(speed).to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                AgentError::tool_execution(format!("Failed to run '{}': {}", self.program, e))
+            })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).await?;
+        }
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            return Err(AgentError::tool_execution(format!(
+                "'{}' exited with {}: {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(Audio::new(output.stdout, AudioFormat::Wav))
+    }
+}
+
+/// Split `text` into chunks of at most `max` characters.
+///
+/// Chunks end at sentence boundaries where possible, then at whitespace;
+/// only a single word longer than `max` is cut mid-word. Whitespace
+/// between chunks is dropped and empty chunks are never produced.
+pub fn split_text(text: &str, max: usize) -> Vec<String> {
+    let max = max.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for sentence in sentences(text) {
+        if current.is_empty() || len(&current) + 1 + len(sentence) <= max {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(sentence);
+        } else {
+            chunks.push(std::mem::take(&mut current));
+            current.push_str(sentence);
+        }
+        // A sentence too long for one chunk is split at whitespace.
+        if len(&current) > max {
+            let mut pieces = split_words(&current, max);
+            current = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Trimmed sentences of `text`, each ending after `.`, `!`, `?` or a
+/// newline that is followed by whitespace.
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = i + c.len_utf8();
+        let at_break = match chars.peek() {
+            Some((_, next)) => next.is_whitespace(),
+            None => true,
+        };
+        if matches!(c, '.' | '!' | '?' | '\n') && at_break {
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+    }
+    sentences.push(&text[start..]);
+    sentences
+        .into_iter()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Split `text` at whitespace into pieces of at most `max` characters.
+fn split_words(text: &str, max: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && len(&current) + 1 + len(word) > max {
+            pieces.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while len(&current) > max {
+            let cut = current.char_indices().nth(max).map_or(current.len(), |(i, _)| i);
+            let rest = current.split_off(cut);
+            pieces.push(std::mem::replace(&mut current, rest));
+        }
+    }
+    if !current.is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Length in characters.
+fn len(text: &str) -> usize {
+    text.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Backend returning `<voice>:<text>` as audio and recording each call.
+    struct EchoBackend {
+        voices: Vec<String>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl TtsBackend for EchoBackend {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn voices(&self) -> &[String] {
+            &self.voices
+        }
+
+        fn max_chars(&self) -> usize {
+            20
+        }
+
+        async fn synthesize(&self, text: &str, voice: &str, _speed: f64) -> Result<Audio> {
+            self.calls.lock().unwrap().push(text.to_string());
+            Ok(Audio::new(format!("{}:{}", voice, text), AudioFormat::Wav))
+        }
+    }
+
+    #[test]
+    fn test_split_text_at_sentences() {
+        let text = "One. Two!  Three?\nFour is longer. Pi is 3.14 today.";
+        assert_eq!(
+            split_text(text, 17),
+            vec!["One. Two! Three?", "Four is longer.", "Pi is 3.14 today."]
+        );
+        assert_eq!(split_text(text, 1000).len(), 1);
+        assert!(split_text("  \n ", 10).is_empty());
+    }
+
+    #[test]
+    fn test_split_text_long_sentence() {
+        let chunks = split_text("alpha beta gamma delta. Épsilonépsilon", 11);
+        assert_eq!(
+            chunks,
+            vec!["alpha beta", "gamma", "delta.", "Épsilonépsi", "lon"]
+        );
+        assert!(chunks.iter().all(|c| c.chars().count() <= 11));
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_chunks_in_order() {
+        let backend = EchoBackend {
+            voices: vec!["v".to_string()],
+            calls: Mutex::new(Vec::new()),
+        };
+        let text = "First sentence. Second one. Third sentence goes here.";
+
+        // The backend's 20 character limit caps the requested chunk size.
+        let chunks: Vec<Audio> = backend
+            .synthesize_stream(text, "v", 1.0, 100)
+            .map(|audio| audio.unwrap())
+            .collect()
+            .await;
+
+        let bodies: Vec<String> = chunks
+            .iter()
+            .map(|a| String::from_utf8(a.bytes.clone()).unwrap())
+            .collect();
+        assert_eq!(
+            bodies,
+            vec!["v:First sentence.", "v:Second one.", "v:Third sentence goes", "v:here."]
+        );
+        assert!(chunks.iter().all(|a| a.format == AudioFormat::Wav));
+        assert_eq!(backend.calls.lock().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stream_synthesizes_lazily() {
+        let backend = EchoBackend {
+            voices: vec!["v".to_string()],
+            calls: Mutex::new(Vec::new()),
+        };
+        let mut stream = backend.synthesize_stream("One. Two. Three.", "v", 1.0, 5);
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first.bytes, b"v:One.");
+        assert_eq!(*backend.calls.lock().unwrap(), vec!["One."]);
+    }
+
+    #[test]
+    fn test_config_selects_backend() {
+        let config: TtsConfig = serde_json::from_value(serde_json::json!({
+            "backend": "local",
+            "voices": ["en-us", "fr"]
+        }))
+        .unwrap();
+        let backend = config.backend(default_client()).unwrap();
+        assert_eq!(backend.name(), "local");
+        assert_eq!(backend.voices(), ["en-us", "fr"]);
+
+        let config = TtsConfig {
+            api_key: Some("k".to_string()),
+            ..Default::default()
+        };
+        let backend = config.backend(default_client()).unwrap();
+        assert_eq!(backend.name(), "openai");
+        assert_eq!(backend.voices()[0], "alloy");
+
+        assert_eq!(TtsProvider::parse("OpenAI"), Some(TtsProvider::OpenAi));
+        assert_eq!(TtsProvider::parse("polly"), None);
+    }
+
+    #[test]
+    fn test_missing_api_key_disables() {
+        let err = TtsConfig::default().backend(default_client()).err().unwrap();
+        assert!(err.contains("no API key for the openai backend"), "{}", err);
+        assert!(err.contains("OPENAI_API_KEY"), "{}", err);
+    }
+
+    #[test]
+    fn test_local_wpm() {
+        assert_eq!(local_wpm(1.0), 175);
+        assert_eq!(local_wpm(2.0), 350);
+        assert_eq!(local_wpm(0.25), 80);
+        assert_eq!(local_wpm(4.0), 450);
+    }
+
+    #[tokio::test]
+    async fn test_openai_backend() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/audio/speech"))
+            .and(header("Authorization", "Bearer tts-key"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "tts-1",
+                "input": "Hello",
+                "voice": "nova"
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"ID3audio".to_vec()))
+            .mount(&server)
+            .await;
+
+        let backend = OpenAiTts::new("tts-key").with_base_url(server.uri());
+        let audio = backend.synthesize("Hello", "nova", 1.0).await.unwrap();
+        assert_eq!(audio, Audio::new(b"ID3audio".to_vec(), AudioFormat::Mp3));
+
+        let err = backend.synthesize("Hello", "echo", 1.0).await.unwrap_err();
+        assert!(err.to_string().contains("TTS API returned 404"), "{}", err);
+    }
+}
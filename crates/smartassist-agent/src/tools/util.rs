@@ -56,6 +56,7 @@ impl Tool for SleepTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -153,6 +154,7 @@ impl Tool for TempFileTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -241,6 +243,7 @@ impl Tool for TempDirTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -320,6 +323,7 @@ impl Tool for EchoTool {
                 },
                 "required": ["value"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
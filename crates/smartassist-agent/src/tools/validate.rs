@@ -2,20 +2,162 @@
 //!
 //! Provides tools for validating data formats like
 //! email addresses, URLs, JSON, and more.
+//!
+//! Each format is a [`FormatValidator`]; callers can add their own with
+//! [`ValidateTool::with_validator`].
 
 use crate::tools::{Tool, ToolContext};
 use crate::Result;
 use async_trait::async_trait;
-use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
+use smartassist_core::types::{
+    schema_violations, ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult,
+};
+use std::sync::Arc;
 use std::time::Instant;
 use tracing::debug;
 
+/// Outcome of checking an input against a format.
+#[derive(Debug, Clone)]
+pub struct Validation {
+    /// Whether the input matches the format.
+    pub valid: bool,
+
+    /// Why the input is or is not valid.
+    pub message: String,
+
+    /// Parts of the input worth reporting, such as a URL's host.
+    pub details: Option<serde_json::Value>,
+}
+
+/// A named format `validate` can check inputs against.
+pub trait FormatValidator: Send + Sync {
+    /// Name passed as the `format` argument.
+    fn name(&self) -> &str;
+
+    /// Check `input`.
+    ///
+    /// `args` holds all tool arguments, for formats that take parameters of
+    /// their own. Unusable parameters are an error rather than an invalid
+    /// input.
+    fn validate(
+        &self,
+        input: &str,
+        args: &serde_json::Value,
+    ) -> std::result::Result<Validation, String>;
+}
+
+/// Check function of a built-in format: validity, message, and details.
+type CheckFn = fn(&str) -> (bool, String, Option<serde_json::Value>);
+
+/// A built-in format checked by a plain function.
+struct BuiltinFormat {
+    name: &'static str,
+    check: CheckFn,
+}
+
+impl FormatValidator for BuiltinFormat {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn validate(
+        &self,
+        input: &str,
+        _args: &serde_json::Value,
+    ) -> std::result::Result<Validation, String> {
+        let (valid, message, details) = (self.check)(input);
+        Ok(Validation {
+            valid,
+            message,
+            details,
+        })
+    }
+}
+
+/// JSON input checked against the JSON Schema in the `schema` argument.
+///
+/// Supports the keywords of [`schema_violations`].
+struct JsonSchemaFormat;
+
+impl FormatValidator for JsonSchemaFormat {
+    fn name(&self) -> &str {
+        "json_schema"
+    }
+
+    fn validate(
+        &self,
+        input: &str,
+        args: &serde_json::Value,
+    ) -> std::result::Result<Validation, String> {
+        let schema = args
+            .get("schema")
+            .filter(|schema| schema.is_object() || schema.is_boolean())
+            .ok_or("The json_schema format requires a schema object in 'schema'")?;
+
+        let value: serde_json::Value = match serde_json::from_str(input) {
+            Ok(value) => value,
+            Err(e) => {
+                return Ok(Validation {
+                    valid: false,
+                    message: format!("Invalid JSON: {}", e),
+                    details: None,
+                })
+            }
+        };
+
+        let violations = schema_violations(schema, &value);
+        let message = match violations.first() {
+            None => "Matches the schema".to_string(),
+            Some(first) => format!("Does not match the schema: {}", first),
+        };
+        Ok(Validation {
+            valid: violations.is_empty(),
+            message,
+            details: Some(serde_json::json!({ "violations": violations })),
+        })
+    }
+}
+
 /// Tool for validating various data formats.
-pub struct ValidateTool;
+pub struct ValidateTool {
+    validators: Vec<Arc<dyn FormatValidator>>,
+}
 
 impl ValidateTool {
     pub fn new() -> Self {
-        Self
+        let builtin: [(&'static str, CheckFn); 13] = [
+            ("email", validate_email),
+            ("url", validate_url),
+            ("json", validate_json),
+            ("uuid", validate_uuid),
+            ("ip", validate_ip),
+            ("ipv4", validate_ipv4),
+            ("ipv6", validate_ipv6),
+            ("semver", validate_semver),
+            ("date", validate_date),
+            ("iso8601", validate_iso8601),
+            ("base64", validate_base64),
+            ("hex", validate_hex),
+            ("phone", validate_phone),
+        ];
+        let mut validators: Vec<Arc<dyn FormatValidator>> = builtin
+            .into_iter()
+            .map(|(name, check)| Arc::new(BuiltinFormat { name, check }) as _)
+            .collect();
+        validators.push(Arc::new(JsonSchemaFormat));
+        Self { validators }
+    }
+
+    /// Add a format, replacing any existing one with the same name.
+    pub fn with_validator(mut self, validator: impl FormatValidator + 'static) -> Self {
+        self.validators.retain(|v| v.name() != validator.name());
+        self.validators.push(Arc::new(validator));
+        self
+    }
+
+    /// Names of the supported formats.
+    pub fn formats(&self) -> Vec<&str> {
+        self.validators.iter().map(|v| v.name()).collect()
     }
 }
 
@@ -34,7 +176,8 @@ impl Tool for ValidateTool {
     fn definition(&self) -> ToolDefinition {
         ToolDefinition {
             name: "validate".to_string(),
-            description: "Validate data against common formats (email, URL, JSON, etc.)."
+            description: "Validate data against common formats (email, URL, JSON, etc.) or a \
+                JSON Schema."
                 .to_string(),
             input_schema: serde_json::json!({
                 "type": "object",
@@ -45,12 +188,17 @@ impl Tool for ValidateTool {
                     },
                     "format": {
                         "type": "string",
-                        "enum": ["email", "url", "json", "uuid", "ip", "ipv4", "ipv6", "semver", "date", "base64", "hex", "phone"],
+                        "enum": self.formats(),
                         "description": "Format to validate against"
+                    },
+                    "schema": {
+                        "type": "object",
+                        "description": "JSON Schema the input must match (json_schema format)"
                     }
                 },
                 "required": ["input", "format"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -73,20 +221,23 @@ impl Tool for ValidateTool {
             .and_then(|v| v.as_str())
             .ok_or_else(|| crate::error::AgentError::tool_execution("format is required"))?;
 
-        let (valid, message, details) = match format {
-            "email" => validate_email(input),
-            "url" => validate_url(input),
-            "json" => validate_json(input),
-            "uuid" => validate_uuid(input),
-            "ip" => validate_ip(input),
-            "ipv4" => validate_ipv4(input),
-            "ipv6" => validate_ipv6(input),
-            "semver" => validate_semver(input),
-            "date" => validate_date(input),
-            "base64" => validate_base64(input),
-            "hex" => validate_hex(input),
-            "phone" => validate_phone(input),
-            _ => (false, format!("Unknown format: {}", format), None),
+        let Some(validator) = self.validators.iter().find(|v| v.name() == format) else {
+            return Ok(ToolResult::error(
+                tool_use_id,
+                format!(
+                    "Unknown format '{}'; expected one of: {}",
+                    format,
+                    self.formats().join(", ")
+                ),
+            ));
+        };
+        let Validation {
+            valid,
+            message,
+            details,
+        } = match validator.validate(input, &args) {
+            Ok(validation) => validation,
+            Err(e) => return Ok(ToolResult::error(tool_use_id, e)),
         };
 
         let duration = start.elapsed();
@@ -277,6 +428,20 @@ fn validate_date(input: &str) -> (bool, String, Option<serde_json::Value>) {
     (false, "Invalid date format".to_string(), None)
 }
 
+fn validate_iso8601(input: &str) -> (bool, String, Option<serde_json::Value>) {
+    let kind = if chrono::DateTime::parse_from_rfc3339(input).is_ok() {
+        "date-time"
+    } else if chrono::NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S%.f").is_ok() {
+        "local date-time"
+    } else if chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d").is_ok() {
+        "date"
+    } else {
+        return (false, "Not an ISO 8601 date or date-time".to_string(), None);
+    };
+    let details = serde_json::json!({ "kind": kind });
+    (true, format!("Valid ISO 8601 {}", kind), Some(details))
+}
+
 fn validate_base64(input: &str) -> (bool, String, Option<serde_json::Value>) {
     use base64::{engine::general_purpose, Engine};
 
@@ -373,6 +538,7 @@ impl Tool for IsEmptyTool {
                 },
                 "required": ["value"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -465,6 +631,141 @@ mod tests {
         assert_eq!(tool.name(), "validate");
     }
 
+    /// Run `validate` and return its result.
+    async fn validate(args: serde_json::Value) -> ToolResult {
+        ValidateTool::new()
+            .execute("test_id", args, &ToolContext::default())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_each_format_accepts_and_rejects() {
+        let cases = [
+            ("email", "ada@example.org", "ada@"),
+            ("url", "https://example.com:8080/a?b=1", "not a url"),
+            ("uuid", "550e8400-e29b-41d4-a716-446655440000", "550e8400-e29b"),
+            ("ip", "::1", "1.2.3"),
+            ("ipv4", "10.0.0.1", "256.0.0.1"),
+            ("ipv6", "2001:db8::1", "10.0.0.1"),
+            ("iso8601", "2024-02-29", "2023-02-29"),
+            ("iso8601", "2024-05-01T12:30:00Z", "01/05/2024"),
+            ("iso8601", "2024-05-01T12:30:00.250", "2024-05-01 12:30"),
+        ];
+
+        for (format, valid, invalid) in cases {
+            let result = validate(serde_json::json!({"input": valid, "format": format})).await;
+            assert!(!result.is_error);
+            assert_eq!(result.output["valid"], true, "{} {}", format, valid);
+
+            let result = validate(serde_json::json!({"input": invalid, "format": format})).await;
+            assert!(!result.is_error);
+            assert_eq!(result.output["valid"], false, "{} {}", format, invalid);
+            assert!(result.output["message"].as_str().is_some_and(|m| !m.is_empty()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_json_schema() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "tags": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["name"]
+        });
+
+        let result = validate(serde_json::json!({
+            "input": r#"{"name": "demo", "tags": ["a"]}"#,
+            "format": "json_schema",
+            "schema": schema,
+        }))
+        .await;
+        assert_eq!(result.output["valid"], true);
+
+        let result = validate(serde_json::json!({
+            "input": r#"{"tags": ["a", 2]}"#,
+            "format": "json_schema",
+            "schema": schema,
+        }))
+        .await;
+        assert_eq!(result.output["valid"], false);
+        assert_eq!(
+            result.output["details"]["violations"],
+            serde_json::json!([
+                "$: missing required property 'name'",
+                "$.tags[1]: expected string, got number"
+            ])
+        );
+
+        let result = validate(serde_json::json!({
+            "input": "{",
+            "format": "json_schema",
+            "schema": schema,
+        }))
+        .await;
+        assert_eq!(result.output["valid"], false);
+
+        // Without a schema the arguments, not the input, are wrong.
+        let result = validate(serde_json::json!({"input": "{}", "format": "json_schema"})).await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_format_is_an_error() {
+        let result = validate(serde_json::json!({"input": "x", "format": "postcode"})).await;
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("Unknown format 'postcode'"), "{}", message);
+        assert!(message.contains("json_schema"), "{}", message);
+    }
+
+    /// Accepts only even numbers.
+    struct EvenFormat;
+
+    impl FormatValidator for EvenFormat {
+        fn name(&self) -> &str {
+            "even"
+        }
+
+        fn validate(
+            &self,
+            input: &str,
+            _args: &serde_json::Value,
+        ) -> std::result::Result<Validation, String> {
+            let n: i64 = input.parse().map_err(|_| "not a number".to_string())?;
+            Ok(Validation {
+                valid: n % 2 == 0,
+                message: format!("{} is {}", n, if n % 2 == 0 { "even" } else { "odd" }),
+                details: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_format() {
+        let tool = ValidateTool::new().with_validator(EvenFormat);
+        assert!(tool.formats().contains(&"even"));
+        let schema = tool.definition().input_schema;
+        assert!(schema["properties"]["format"]["enum"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("even")));
+
+        let ctx = ToolContext::default();
+        let result = tool
+            .execute("id", serde_json::json!({"input": "4", "format": "even"}), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(result.output["valid"], true);
+        let result = tool
+            .execute("id", serde_json::json!({"input": "x", "format": "even"}), &ctx)
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
     #[test]
     fn test_is_empty_tool_creation() {
         let tool = IsEmptyTool::new();
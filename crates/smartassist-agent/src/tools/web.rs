@@ -3,15 +3,132 @@
 //! - [`WebFetchTool`] - Fetch and extract web content
 //! - [`WebSearchTool`] - Search the web
 
+use super::search::{self, normalize_results, SearchBackend, SearchProvider, WebSearchConfig};
 use super::{Tool, ToolContext};
 use crate::error::AgentError;
 use crate::Result;
 use async_trait::async_trait;
 use smartassist_core::types::{ToolDefinition, ToolExecutionConfig, ToolGroup, ToolResult};
 use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tracing::debug;
 
+/// How long a host's robots.txt is reused before being fetched again.
+const ROBOTS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Most redirects followed for one fetch.
+const MAX_REDIRECTS: usize = 10;
+
+/// Which URLs [`WebFetchTool`] may request.
+#[derive(Debug, Clone, Default)]
+pub struct FetchPolicy {
+    /// Domains that may be fetched, including their subdomains. Empty
+    /// allows every domain that is not blocked.
+    pub allowed_domains: Vec<String>,
+
+    /// Domains that are never fetched, including their subdomains.
+    pub blocked_domains: Vec<String>,
+
+    /// Check the host's robots.txt before fetching.
+    pub respect_robots: bool,
+}
+
+impl FetchPolicy {
+    /// Check whether a host passes the domain lists.
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        let matches = |domain: &String| {
+            let domain = domain.trim_matches('.').to_ascii_lowercase();
+            host == domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        };
+
+        !self.blocked_domains.iter().any(matches)
+            && (self.allowed_domains.is_empty() || self.allowed_domains.iter().any(matches))
+    }
+}
+
+/// Allow and disallow rules from a robots.txt that apply to one user agent.
+#[derive(Debug, Clone, Default)]
+struct RobotsRules {
+    /// `(allow, path prefix)` pairs.
+    rules: Vec<(bool, String)>,
+}
+
+impl RobotsRules {
+    /// Rules that refuse every path, used when robots.txt is unreachable.
+    fn disallow_all() -> Self {
+        Self {
+            rules: vec![(false, "/".to_string())],
+        }
+    }
+
+    /// Parse the groups of a robots.txt that apply to `agent`.
+    ///
+    /// Groups naming the agent take precedence over `*` groups. Paths are
+    /// matched as plain prefixes.
+    fn parse(body: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        // Whether the current group names this agent, or `*`.
+        let (mut names_agent, mut names_any) = (false, false);
+        let mut in_rules = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        (names_agent, names_any) = (false, false);
+                        in_rules = false;
+                    }
+                    let value = value.to_ascii_lowercase();
+                    names_agent |= value == agent;
+                    names_any |= value == "*";
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty Disallow allows everything.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (field == "allow", value.to_string());
+                    if names_agent {
+                        specific.push(rule);
+                    } else if names_any {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if specific.is_empty() { wildcard } else { specific },
+        }
+    }
+
+    /// Check a path against the rules; the longest match wins and Allow
+    /// wins a tie.
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, prefix)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(allow, prefix)| (prefix.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
 /// Web fetch tool - Fetch and extract content from a URL.
 pub struct WebFetchTool {
     /// HTTP client.
@@ -22,6 +139,10 @@ pub struct WebFetchTool {
     timeout: Duration,
     /// User agent string.
     user_agent: String,
+    /// Which URLs may be fetched.
+    policy: FetchPolicy,
+    /// Parsed robots.txt by origin, with the time it was fetched.
+    robots: Mutex<HashMap<String, (Instant, RobotsRules)>>,
 }
 
 impl Default for WebFetchTool {
@@ -41,6 +162,40 @@ impl WebFetchTool {
             max_content_length: 1024 * 1024, // 1 MB
             timeout: Duration::from_secs(30),
             user_agent: "SmartAssist/1.0".to_string(),
+            policy: FetchPolicy::default(),
+            robots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a web fetch tool that only fetches URLs allowed by `policy`.
+    ///
+    /// Redirects to a domain the policy does not allow are not followed.
+    /// When robots.txt is respected, redirects are followed by the tool
+    /// itself so that each target's robots.txt is checked first.
+    pub fn with_policy(policy: FetchPolicy) -> Self {
+        let redirect_policy = policy.clone();
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                let allowed = attempt
+                    .url()
+                    .host_str()
+                    .is_some_and(|host| redirect_policy.allows_host(host));
+                if !allowed || redirect_policy.respect_robots {
+                    attempt.stop()
+                } else if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            client,
+            policy,
+            ..Self::new()
         }
     }
 
@@ -62,6 +217,64 @@ impl WebFetchTool {
         self
     }
 
+    /// Check a URL against the policy, returning why it may not be fetched.
+    async fn check_policy(&self, url: &url::Url) -> Option<String> {
+        let host = url.host_str().unwrap_or_default();
+        if !self.policy.allows_host(host) {
+            return Some(format!("Fetching from '{}' is not allowed by policy", host));
+        }
+        if !self.policy.respect_robots {
+            return None;
+        }
+
+        let origin = url.origin().ascii_serialization();
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+
+        let rules = self.robots_rules(&origin).await;
+        (!rules.allows(&path)).then(|| format!("Fetching '{}' is disallowed by robots.txt", url))
+    }
+
+    /// Get the robots.txt rules for an origin, fetching them if not cached.
+    async fn robots_rules(&self, origin: &str) -> RobotsRules {
+        {
+            let cache = self.robots.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((fetched_at, rules)) = cache.get(origin) {
+                if fetched_at.elapsed() < ROBOTS_CACHE_TTL {
+                    return rules.clone();
+                }
+            }
+        }
+
+        let agent = self.user_agent.split('/').next().unwrap_or_default();
+        let response = self
+            .client
+            .get(format!("{}/robots.txt", origin))
+            .header("User-Agent", &self.user_agent)
+            .timeout(self.timeout)
+            .send()
+            .await;
+
+        // A missing robots.txt allows everything; an unreachable one
+        // allows nothing.
+        let rules = match response {
+            Ok(response) if response.status().is_success() => match response.text().await {
+                Ok(body) => RobotsRules::parse(&body, agent),
+                Err(_) => RobotsRules::disallow_all(),
+            },
+            Ok(response) if response.status().is_client_error() => RobotsRules::default(),
+            _ => RobotsRules::disallow_all(),
+        };
+        debug!("Fetched robots.txt for {}: {} rules", origin, rules.rules.len());
+
+        let mut cache = self.robots.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(origin.to_string(), (Instant::now(), rules.clone()));
+        rules
+    }
+
     /// Extract text content from HTML.
     fn extract_text(&self, html: &str) -> String {
         // Simple HTML to text conversion
@@ -136,7 +349,11 @@ impl Tool for WebFetchTool {
                 },
                 "required": ["url"]
             }),
-            execution: ToolExecutionConfig::default(),
+            output_schema: None,
+            execution: ToolExecutionConfig {
+                cacheable: true,
+                ..Default::default()
+            },
         }
     }
 
@@ -172,15 +389,51 @@ impl Tool for WebFetchTool {
             ));
         }
 
-        // Make request
-        let response = self
-            .client
-            .get(url)
-            .header("User-Agent", &self.user_agent)
-            .timeout(self.timeout)
-            .send()
-            .await
-            .map_err(|e| AgentError::tool_execution(format!("Request failed: {}", e)))?;
+        if let Some(reason) = self.check_policy(&parsed_url).await {
+            return Ok(ToolResult::error(tool_use_id, reason));
+        }
+
+        // Make request, checking each redirect target when the client
+        // leaves redirects to us.
+        let mut target = parsed_url;
+        let mut redirects = 0;
+        let response = loop {
+            let response = self
+                .client
+                .get(target.clone())
+                .header("User-Agent", &self.user_agent)
+                .timeout(self.timeout)
+                .send()
+                .await
+                .map_err(|e| AgentError::tool_execution(format!("Request failed: {}", e)))?;
+            if !self.policy.respect_robots || !response.status().is_redirection() {
+                break response;
+            }
+            let next = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|location| target.join(location).ok());
+            let Some(next) = next else {
+                break response;
+            };
+
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Ok(ToolResult::error(tool_use_id, "Too many redirects"));
+            }
+            if next.scheme() != "http" && next.scheme() != "https" {
+                return Ok(ToolResult::error(
+                    tool_use_id,
+                    format!("Refusing to follow redirect to '{}'", next),
+                ));
+            }
+            if let Some(reason) = self.check_policy(&next).await {
+                return Ok(ToolResult::error(tool_use_id, reason));
+            }
+            debug!("Following redirect to {}", next);
+            target = next;
+        };
 
         let status = response.status();
         let content_type = response
@@ -279,16 +532,14 @@ impl Tool for WebFetchTool {
     }
 }
 
-/// Web search tool - Search the web using a search API.
+/// Web search tool - Search the web through a [`SearchBackend`].
 pub struct WebSearchTool {
-    /// HTTP client.
-    client: Client,
-    /// Search API endpoint.
-    api_endpoint: Option<String>,
-    /// API key for search service.
-    api_key: Option<String>,
+    /// Backend to query, or why search is unavailable.
+    backend: std::result::Result<Arc<dyn SearchBackend>, String>,
     /// Maximum results to return.
     max_results: usize,
+    /// Generic endpoint settings made with the deprecated builder methods.
+    legacy: WebSearchConfig,
 }
 
 impl Default for WebSearchTool {
@@ -298,43 +549,86 @@ impl Default for WebSearchTool {
 }
 
 impl WebSearchTool {
-    /// Create a new web search tool.
+    /// Create a web search tool with no backend; searches report that search
+    /// is not configured.
     pub fn new() -> Self {
         Self {
-            client: Client::builder()
-                .timeout(Duration::from_secs(30))
-                .build()
-                .unwrap_or_default(),
-            api_endpoint: None,
-            api_key: None,
+            backend: Err("Web search is not configured. Set SMARTASSIST_SEARCH_PROVIDER \
+                          and SMARTASSIST_SEARCH_API_KEY environment variables."
+                .to_string()),
             max_results: 10,
+            legacy: WebSearchConfig {
+                provider: SearchProvider::Generic,
+                ..WebSearchConfig::default()
+            },
         }
     }
 
     /// Set the search API endpoint.
+    ///
+    /// Together with [`with_api_key`](Self::with_api_key) this configures a
+    /// [`GenericSearch`](search::GenericSearch) backend.
+    #[deprecated(note = "use `WebSearchTool::with_backend` with a `GenericSearch`")]
     pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
-        self.api_endpoint = Some(endpoint.into());
+        self.legacy.endpoint = Some(endpoint.into());
+        self.backend = self.legacy.backend(search::default_client()).map(Arc::from);
         self
     }
 
-    /// Set the API key.
+    /// Set the API key of the endpoint set with
+    /// [`with_endpoint`](Self::with_endpoint).
+    #[deprecated(note = "use `WebSearchTool::with_backend` with a `GenericSearch`")]
     pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
-        self.api_key = Some(key.into());
+        self.legacy.api_key = Some(key.into());
+        self.backend = self.legacy.backend(search::default_client()).map(Arc::from);
         self
     }
 
+    /// Create a web search tool using `backend`.
+    pub fn with_backend(backend: Arc<dyn SearchBackend>) -> Self {
+        Self {
+            backend: Ok(backend),
+            ..Self::new()
+        }
+    }
+
+    /// Create a web search tool from configuration.
+    ///
+    /// A missing API key leaves the tool disabled, reporting what to set.
+    pub fn from_config(config: &WebSearchConfig) -> Self {
+        let tool = Self {
+            backend: config.backend(search::default_client()).map(Arc::from),
+            ..Self::new()
+        };
+        match config.max_results {
+            Some(max) => tool.with_max_results(max),
+            None => tool,
+        }
+    }
+
+    /// Create a web search tool configured from the environment.
+    ///
+    /// See [`WebSearchConfig::from_env`].
+    pub fn from_env() -> Self {
+        match WebSearchConfig::from_env() {
+            Ok(config) => Self::from_config(&config),
+            Err(reason) => Self {
+                backend: Err(reason),
+                ..Self::new()
+            },
+        }
+    }
+
     /// Set maximum results.
     pub fn with_max_results(mut self, max: usize) -> Self {
         self.max_results = max;
         self
     }
-}
 
-#[derive(Debug, Clone, serde::Serialize)]
-struct SearchResult {
-    title: String,
-    url: String,
-    snippet: String,
+    /// Whether a backend is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.backend.is_ok()
+    }
 }
 
 #[async_trait]
@@ -361,6 +655,7 @@ impl Tool for WebSearchTool {
                 },
                 "required": ["query"]
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }
@@ -385,75 +680,33 @@ impl Tool for WebSearchTool {
             .unwrap_or(self.max_results)
             .min(self.max_results);
 
-        debug!("Web search: {} (max {} results)", query, num_results);
-
-        // Check if search API is configured
-        let (endpoint, api_key) = match (&self.api_endpoint, &self.api_key) {
-            (Some(e), Some(k)) => (e.clone(), k.clone()),
-            _ => {
-                return Ok(ToolResult::error(
-                    tool_use_id,
-                    "Web search is not configured. Set SMARTASSIST_SEARCH_API_KEY and SMARTASSIST_SEARCH_ENDPOINT environment variables.",
-                ));
-            }
+        let backend = match &self.backend {
+            Ok(backend) => backend,
+            Err(reason) => return Ok(ToolResult::error(tool_use_id, reason.clone())),
         };
 
-        // Make search request (assuming a generic search API format)
-        let response = self
-            .client
-            .get(&endpoint)
-            .query(&[
-                ("q", query),
-                ("num", &num_results.to_string()),
-            ])
-            .header("Authorization", format!("Bearer {}", api_key))
-            .send()
-            .await
-            .map_err(|e| AgentError::tool_execution(format!("Search request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Ok(ToolResult::error(
-                tool_use_id,
-                format!("Search API error: {}", response.status()),
-            ));
-        }
+        debug!(
+            "Web search via {}: {} (max {} results)",
+            backend.name(),
+            query,
+            num_results
+        );
 
-        let body: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| AgentError::tool_execution(format!("Failed to parse search response: {}", e)))?;
-
-        // Parse results (format depends on the search API)
-        let results: Vec<SearchResult> = body
-            .get("results")
-            .or_else(|| body.get("items"))
-            .or_else(|| body.get("organic"))
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|item| {
-                        Some(SearchResult {
-                            title: item.get("title")?.as_str()?.to_string(),
-                            url: item.get("url")
-                                .or_else(|| item.get("link"))
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string())?,
-                            snippet: item.get("snippet")
-                                .or_else(|| item.get("description"))
-                                .and_then(|v| v.as_str())
-                                .map(|s| s.to_string())
-                                .unwrap_or_default(),
-                        })
-                    })
-                    .take(num_results)
-                    .collect()
-            })
-            .unwrap_or_default();
+        // Backend failures such as HTTP errors go back to the model as an
+        // error result, not as a failed tool call.
+        let results = match backend.search(query, num_results).await {
+            Ok(results) => normalize_results(results, num_results),
+            Err(AgentError::ToolExecution(message)) => {
+                return Ok(ToolResult::error(tool_use_id, message));
+            }
+            Err(e) => return Err(e),
+        };
 
         let duration = start.elapsed();
         Ok(
             ToolResult::success(tool_use_id, serde_json::json!({
                 "query": query,
+                "provider": backend.name(),
                 "results": results,
                 "count": results.len(),
             }))
@@ -476,10 +729,280 @@ mod tests {
         assert_eq!(tool.name(), "web_fetch");
     }
 
+    fn fetch_args(url: String) -> serde_json::Value {
+        serde_json::json!({ "url": url, "extract": "html" })
+    }
+
+    #[test]
+    fn test_policy_domains() {
+        let policy = FetchPolicy {
+            allowed_domains: vec!["example.com".to_string()],
+            blocked_domains: vec!["ads.example.com".to_string()],
+            respect_robots: false,
+        };
+        assert!(policy.allows_host("example.com"));
+        assert!(policy.allows_host("docs.Example.com"));
+        assert!(!policy.allows_host("ads.example.com"));
+        assert!(!policy.allows_host("tracker.ads.example.com"));
+        assert!(!policy.allows_host("notexample.com"));
+        assert!(FetchPolicy::default().allows_host("anything.org"));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_domain_rejected() {
+        let tool = WebFetchTool::with_policy(FetchPolicy {
+            blocked_domains: vec!["example.com".to_string()],
+            ..Default::default()
+        });
+
+        let result = tool
+            .execute(
+                "id",
+                fetch_args("https://www.example.com/page".to_string()),
+                &ToolContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.output.as_str().unwrap().contains("not allowed by policy"));
+    }
+
+    #[tokio::test]
+    async fn test_allowlisted_domain_fetched() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/page"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("hello"))
+            .mount(&server)
+            .await;
+        let url = format!("{}/page", server.uri());
+
+        let allowed = WebFetchTool::with_policy(FetchPolicy {
+            allowed_domains: vec!["127.0.0.1".to_string()],
+            ..Default::default()
+        });
+        let result = allowed
+            .execute("id", fetch_args(url.clone()), &ToolContext::default())
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert_eq!(result.output["content"], "hello");
+
+        let elsewhere = WebFetchTool::with_policy(FetchPolicy {
+            allowed_domains: vec!["example.org".to_string()],
+            ..Default::default()
+        });
+        let result = elsewhere
+            .execute("id", fetch_args(url), &ToolContext::default())
+            .await
+            .unwrap();
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_robots_checked_after_redirect_to_other_host() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let target = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "User-agent: *\nDisallow: /private\n",
+            ))
+            .mount(&target)
+            .await;
+        for page in ["/private", "/public"] {
+            Mock::given(method("GET"))
+                .and(path(page))
+                .respond_with(ResponseTemplate::new(200).set_body_string("reached"))
+                .mount(&target)
+                .await;
+        }
+
+        // The first server has no robots.txt and redirects elsewhere.
+        let origin = MockServer::start().await;
+        for page in ["private", "public"] {
+            Mock::given(method("GET"))
+                .and(path(format!("/{}", page)))
+                .respond_with(
+                    ResponseTemplate::new(302)
+                        .insert_header("location", format!("{}/{}", target.uri(), page).as_str()),
+                )
+                .mount(&origin)
+                .await;
+        }
+
+        let tool = WebFetchTool::with_policy(FetchPolicy {
+            respect_robots: true,
+            ..Default::default()
+        });
+        let context = ToolContext::default();
+        let fetch = |page: &str| fetch_args(format!("{}/{}", origin.uri(), page));
+
+        let blocked = tool.execute("id", fetch("private"), &context).await.unwrap();
+        assert!(blocked.is_error);
+        assert!(blocked.output.as_str().unwrap().contains("robots.txt"), "{}", blocked.output);
+        let public = tool.execute("id", fetch("public"), &context).await.unwrap();
+        assert!(!public.is_error, "{}", public.output);
+        assert_eq!(public.output["content"], "reached");
+    }
+
+    #[tokio::test]
+    async fn test_robots_disallowed_path() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/robots.txt"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "User-agent: *\nDisallow: /private\nAllow: /private/press\n",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+        for page in ["/public", "/private/press"] {
+            Mock::given(method("GET"))
+                .and(path(page))
+                .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+                .mount(&server)
+                .await;
+        }
+
+        let tool = WebFetchTool::with_policy(FetchPolicy {
+            respect_robots: true,
+            ..Default::default()
+        });
+        let context = ToolContext::default();
+        let fetch = |page: &str| fetch_args(format!("{}{}", server.uri(), page));
+
+        let blocked = tool.execute("id", fetch("/private/report"), &context).await.unwrap();
+        assert!(blocked.is_error);
+        assert!(blocked.output.as_str().unwrap().contains("robots.txt"));
+        let public = tool.execute("id", fetch("/public"), &context).await.unwrap();
+        assert!(!public.is_error);
+        let press = tool.execute("id", fetch("/private/press"), &context).await.unwrap();
+        assert!(!press.is_error);
+    }
+
+    #[test]
+    fn test_robots_agent_groups() {
+        let body = "User-agent: *\nDisallow: /\n\nUser-agent: SmartAssist\nDisallow: /tmp\n";
+        let rules = RobotsRules::parse(body, "SmartAssist");
+        assert!(rules.allows("/docs"));
+        assert!(!rules.allows("/tmp/file"));
+
+        let others = RobotsRules::parse(body, "OtherBot");
+        assert!(!others.allows("/docs"));
+        assert!(RobotsRules::parse("User-agent: *\nDisallow:\n", "bot").allows("/"));
+    }
+
     #[test]
     fn test_web_search_tool_creation() {
         let tool = WebSearchTool::new();
         assert_eq!(tool.name(), "web_search");
+        assert!(!tool.is_enabled());
+    }
+
+    /// Backend returning fixed results.
+    struct FixedBackend(Vec<search::SearchResult>);
+
+    #[async_trait]
+    impl SearchBackend for FixedBackend {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        async fn search(&self, _query: &str, _count: usize) -> Result<Vec<search::SearchResult>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_web_search_normalizes_backend_results() {
+        use search::SearchResult;
+
+        let backend = FixedBackend(vec![
+            SearchResult::new("<b>Rust</b>", "https://rust-lang.org", "A  language &amp; tools"),
+            SearchResult::new("Rust again", "https://rust-lang.org", "duplicate"),
+            SearchResult::new("Book", "https://doc.rust-lang.org/book", "The book"),
+            SearchResult::new("Crates", "https://crates.io", "Packages"),
+        ]);
+        let tool = WebSearchTool::with_backend(Arc::new(backend)).with_max_results(5);
+
+        let result = tool
+            .execute(
+                "id",
+                serde_json::json!({ "query": "rust", "num_results": 2 }),
+                &ToolContext::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error);
+        assert_eq!(
+            result.output,
+            serde_json::json!({
+                "query": "rust",
+                "provider": "fixed",
+                "count": 2,
+                "results": [
+                    {
+                        "title": "Rust",
+                        "url": "https://rust-lang.org",
+                        "snippet": "A language & tools"
+                    },
+                    {
+                        "title": "Book",
+                        "url": "https://doc.rust-lang.org/book",
+                        "snippet": "The book"
+                    }
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_search_disabled_without_key() {
+        let tool = WebSearchTool::from_config(&WebSearchConfig::default());
+        assert!(!tool.is_enabled());
+
+        let result = tool
+            .execute("id", serde_json::json!({ "query": "rust" }), &ToolContext::default())
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        let message = result.output.as_str().unwrap();
+        assert!(message.contains("no API key for the brave provider"), "{}", message);
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_web_search_http_error_is_tool_error() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+
+        let tool = WebSearchTool::new().with_endpoint(server.uri());
+        assert!(!tool.is_enabled());
+        let tool = tool.with_api_key("key");
+        assert!(tool.is_enabled());
+
+        let result = tool
+            .execute("id", serde_json::json!({ "query": "rust" }), &ToolContext::default())
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert_eq!(result.output, "Search API error: 503 Service Unavailable");
     }
 
     #[test]
@@ -1,12 +1,21 @@
 //! Attachment handling for messages.
+//!
+//! When no usable MIME type is supplied, it is sniffed from the content's
+//! magic bytes, then guessed from the filename. Files opened by path are
+//! the exception: their extension is trusted when it is known, and the
+//! content is only sniffed when it is missing or unrecognized.
 
 use crate::error::ChannelError;
 use crate::Result;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
+/// MIME type used when content cannot be identified.
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
 /// An attachment to a message.
 #[derive(Debug, Clone)]
 pub struct Attachment {
@@ -89,7 +98,10 @@ impl Attachment {
     ) -> Self {
         let bytes = bytes.into();
         let size = bytes.len();
-        let mime_type_str = mime_type.into();
+        let mut mime_type_str = mime_type.into();
+        if is_unspecified(&mime_type_str) {
+            mime_type_str = sniff_mime_type(&bytes).unwrap_or(OCTET_STREAM).to_string();
+        }
 
         Self {
             attachment_type: Self::detect_type(&mime_type_str),
@@ -119,8 +131,11 @@ impl Attachment {
             .unwrap_or_else(|| "file".to_string());
 
         let mime_type = mime_guess::from_path(&path)
-            .first_or_octet_stream()
-            .to_string();
+            .first()
+            .map(|m| m.to_string())
+            .filter(|m| !is_unspecified(m))
+            .or_else(|| sniff_file(&path).map(str::to_string))
+            .unwrap_or_else(|| OCTET_STREAM.to_string());
 
         let size = std::fs::metadata(&path).ok().map(|m| m.len() as usize);
 
@@ -246,6 +261,101 @@ impl Attachment {
     }
 }
 
+/// Whether a supplied MIME type carries no information.
+fn is_unspecified(mime_type: &str) -> bool {
+    let mime_type = mime_type.trim();
+    mime_type.is_empty() || mime_type.eq_ignore_ascii_case(OCTET_STREAM)
+}
+
+/// Infer a MIME type from the leading magic bytes of a file.
+///
+/// Recognizes common image, video, audio, and PDF formats. Returns `None`
+/// when the content is not recognized.
+pub fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| data.get(offset..offset + magic.len()) == Some(magic);
+
+    if at(0, b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if at(0, b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if at(0, b"GIF87a") || at(0, b"GIF89a") {
+        Some("image/gif")
+    } else if at(0, b"BM") && is_bmp_header(data) {
+        Some("image/bmp")
+    } else if at(0, b"%PDF-") {
+        Some("application/pdf")
+    } else if at(0, b"RIFF") {
+        if at(8, b"WEBP") {
+            Some("image/webp")
+        } else if at(8, b"WAVE") {
+            Some("audio/wav")
+        } else if at(8, b"AVI ") {
+            Some("video/x-msvideo")
+        } else {
+            None
+        }
+    } else if at(4, b"ftyp") {
+        // ISO base media: the major brand says what the container holds.
+        match data.get(8..12)? {
+            b"M4A " | b"M4B " => Some("audio/mp4"),
+            b"heic" | b"heix" | b"mif1" => Some("image/heic"),
+            b"avif" => Some("image/avif"),
+            b"qt  " => Some("video/quicktime"),
+            _ => Some("video/mp4"),
+        }
+    } else if at(0, b"\x1a\x45\xdf\xa3") {
+        Some("video/webm")
+    } else if at(0, b"OggS") {
+        Some("audio/ogg")
+    } else if at(0, b"fLaC") {
+        Some("audio/flac")
+    } else if at(0, b"ID3") || is_mp3_frame(data) {
+        Some("audio/mpeg")
+    } else {
+        None
+    }
+}
+
+/// Check the fixed fields of a BMP file header: the reserved words are
+/// zero and the DIB header has one of the sizes in use.
+fn is_bmp_header(data: &[u8]) -> bool {
+    let Some(&[a, b, c, d]) = data.get(14..18) else {
+        return false;
+    };
+    let dib_size = u32::from_le_bytes([a, b, c, d]);
+    data[6..10] == [0; 4] && matches!(dib_size, 12 | 16 | 40 | 52 | 56 | 64 | 108 | 124)
+}
+
+/// Check for an MPEG audio layer III frame header.
+///
+/// Only layer III is accepted; the sync bits alone also match the UTF-16
+/// byte order mark `FF FE`, which reads as a layer I header.
+fn is_mp3_frame(data: &[u8]) -> bool {
+    let [0xff, b1, b2, ..] = *data else {
+        return false;
+    };
+    let version = (b1 >> 3) & 0b11;
+    let layer = (b1 >> 1) & 0b11;
+    let bitrate = b2 >> 4;
+    let sample_rate = (b2 >> 2) & 0b11;
+    b1 & 0xe0 == 0xe0
+        && version != 0b01
+        && layer == 0b01
+        && !matches!(bitrate, 0 | 0xf)
+        && sample_rate != 0b11
+}
+
+/// Sniff the MIME type of a file from its first bytes.
+fn sniff_file(path: &Path) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(32);
+    std::fs::File::open(path)
+        .ok()?
+        .take(32)
+        .read_to_end(&mut head)
+        .ok()?;
+    sniff_mime_type(&head)
+}
+
 /// Builder for attachments.
 #[derive(Debug, Default)]
 pub struct AttachmentBuilder {
@@ -318,11 +428,20 @@ impl AttachmentBuilder {
             .ok_or_else(|| ChannelError::Attachment("Source is required".to_string()))?;
 
         let filename = self.filename.unwrap_or_else(|| "file".to_string());
-        let mime_type = self.mime_type.unwrap_or_else(|| {
-            mime_guess::from_path(&filename)
-                .first_or_octet_stream()
-                .to_string()
-        });
+        let sniffed = match &source {
+            AttachmentSource::Bytes(b) => sniff_mime_type(b),
+            AttachmentSource::Path(p) => sniff_file(p),
+            _ => None,
+        };
+        let mime_type = self
+            .mime_type
+            .filter(|m| !is_unspecified(m))
+            .or_else(|| sniffed.map(str::to_string))
+            .unwrap_or_else(|| {
+                mime_guess::from_path(&filename)
+                    .first_or_octet_stream()
+                    .to_string()
+            });
 
         let attachment_type = self
             .attachment_type
@@ -394,4 +513,84 @@ mod tests {
         assert_eq!(attachment.caption, Some("A test image".to_string()));
         assert!(attachment.spoiler);
     }
+
+    #[test]
+    fn test_sniff_known_headers() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        let jpeg = b"\xff\xd8\xff\xe0\x00\x10JFIF\x00";
+        let mp4 = b"\x00\x00\x00\x20ftypisom\x00\x00\x02\x00";
+        let pdf = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3";
+
+        assert_eq!(sniff_mime_type(png), Some("image/png"));
+        assert_eq!(sniff_mime_type(jpeg), Some("image/jpeg"));
+        assert_eq!(sniff_mime_type(mp4), Some("video/mp4"));
+        assert_eq!(sniff_mime_type(pdf), Some("application/pdf"));
+        assert_eq!(sniff_mime_type(b"RIFF\x24\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime_type(b"\x00\x00\x00\x20ftypM4A "), Some("audio/mp4"));
+        assert_eq!(sniff_mime_type(b"ID3\x04\x00"), Some("audio/mpeg"));
+        assert_eq!(sniff_mime_type(b"OggS\x00\x02"), Some("audio/ogg"));
+        assert_eq!(sniff_mime_type(b"hello world"), None);
+        assert_eq!(sniff_mime_type(b""), None);
+    }
+
+    #[test]
+    fn test_sniff_rejects_lookalike_text() {
+        let mp3 = b"\xff\xfb\x90\x64\x00\x00";
+        let bmp = b"BM\x36\x00\x0c\x00\x00\x00\x00\x00\x36\x00\x00\x00\x28\x00\x00\x00";
+        assert_eq!(sniff_mime_type(mp3), Some("audio/mpeg"));
+        assert_eq!(sniff_mime_type(bmp), Some("image/bmp"));
+
+        // UTF-16 text with a byte order mark, and text starting with "BM".
+        assert_eq!(sniff_mime_type(b"\xff\xfeh\x00i\x00"), None);
+        assert_eq!(sniff_mime_type(b"BMW owners club meeting notes"), None);
+    }
+
+    #[test]
+    fn test_from_path_trusts_known_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes.txt");
+        std::fs::write(&notes, b"\x89PNG\r\n\x1a\n is how a PNG starts").unwrap();
+        assert_eq!(Attachment::from_path(&notes).unwrap().mime_type, "text/plain");
+
+        for name in ["image", "image.unknownext"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, b"\x89PNG\r\n\x1a\n\x00").unwrap();
+            assert_eq!(Attachment::from_path(&path).unwrap().mime_type, "image/png");
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_sniffs_missing_mime_type() {
+        let cases: [(&[u8], &str, AttachmentType); 5] = [
+            (b"\x89PNG\r\n\x1a\n\x00", "image/png", AttachmentType::Image),
+            (b"\xff\xd8\xff\xdb\x00", "image/jpeg", AttachmentType::Image),
+            (b"\x00\x00\x00\x18ftypmp42", "video/mp4", AttachmentType::Video),
+            (b"%PDF-1.4\n", "application/pdf", AttachmentType::Document),
+            (b"plain bytes", OCTET_STREAM, AttachmentType::Document),
+        ];
+        for (data, mime_type, attachment_type) in cases {
+            let attachment = Attachment::from_bytes(data.to_vec(), "upload", "");
+            assert_eq!(attachment.mime_type, mime_type);
+            assert_eq!(attachment.attachment_type, attachment_type);
+        }
+
+        // A generic type is treated as missing; a specific one is kept.
+        let png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let attachment = Attachment::from_bytes(png.clone(), "upload", OCTET_STREAM);
+        assert_eq!(attachment.mime_type, "image/png");
+        let attachment = Attachment::from_bytes(png, "upload", "image/x-custom");
+        assert_eq!(attachment.mime_type, "image/x-custom");
+    }
+
+    #[test]
+    fn test_builder_prefers_content_over_filename() {
+        let attachment = AttachmentBuilder::new()
+            .filename("clip.bin")
+            .bytes(b"\x00\x00\x00\x20ftypisom".to_vec())
+            .build()
+            .unwrap();
+
+        assert_eq!(attachment.mime_type, "video/mp4");
+        assert!(attachment.is_video());
+    }
 }
@@ -0,0 +1,418 @@
+//! Inbound and outbound message deduplication.
+//!
+//! Webhook retries and overlapping polls can hand the manager the same
+//! message more than once. [`DedupCache`] remembers recently seen message
+//! keys for a time window so repeats can be dropped before they reach an
+//! agent. The cache is bounded: once full, the oldest keys are forgotten
+//! first, even if they are still inside the window.
+//!
+//! Retried sends have the mirror-image problem. [`SendCache`] remembers the
+//! result of each send that carried an idempotency key, under the same
+//! window and bound, and hands it back instead of sending again. The same
+//! [`ResultCache`] also remembers targets resolved from human-friendly
+//! identifiers ([`TargetCache`]).
+
+use crate::traits::SendResult;
+use crate::Result;
+use smartassist_core::types::{InboundMessage, MessageId, MessageTarget};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Deduplication settings.
+#[derive(Debug, Clone)]
+pub struct DedupConfig {
+    /// How long a message key is remembered.
+    pub window: Duration,
+
+    /// Maximum number of keys remembered at once.
+    pub capacity: usize,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(600), // 10 minutes
+            capacity: 10_000,
+        }
+    }
+}
+
+/// Identity of an inbound message across channels.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MessageKey {
+    channel: String,
+    account_id: String,
+    id: MessageId,
+}
+
+impl MessageKey {
+    fn of(message: &InboundMessage) -> Self {
+        Self {
+            channel: message.channel.clone(),
+            account_id: message.account_id.clone(),
+            id: message.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct DedupState {
+    /// When each remembered key was first seen.
+    seen: HashMap<MessageKey, Instant>,
+
+    /// Remembered keys, oldest first.
+    order: VecDeque<(MessageKey, Instant)>,
+}
+
+/// Bounded, time-windowed set of recently seen inbound messages.
+#[derive(Debug)]
+pub struct DedupCache {
+    config: DedupConfig,
+    state: Mutex<DedupState>,
+}
+
+impl Default for DedupCache {
+    fn default() -> Self {
+        Self::new(DedupConfig::default())
+    }
+}
+
+impl DedupCache {
+    /// Create a cache with the given settings.
+    pub fn new(config: DedupConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(DedupState::default()),
+        }
+    }
+
+    /// Record a message, returning `true` if it has not been seen within
+    /// the window.
+    pub fn check(&self, message: &InboundMessage) -> bool {
+        self.check_at(MessageKey::of(message), Instant::now())
+    }
+
+    /// Number of keys currently remembered.
+    pub fn len(&self) -> usize {
+        self.lock().seen.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn check_at(&self, key: MessageKey, now: Instant) -> bool {
+        if self.config.capacity == 0 {
+            return true;
+        }
+
+        let mut state = self.lock();
+
+        // Forget keys that have aged out of the window.
+        while let Some((_, seen_at)) = state.order.front() {
+            if now.duration_since(*seen_at) < self.config.window {
+                break;
+            }
+            if let Some((old, _)) = state.order.pop_front() {
+                state.seen.remove(&old);
+            }
+        }
+
+        if state.seen.contains_key(&key) {
+            return false;
+        }
+
+        while state.order.len() >= self.config.capacity {
+            if let Some((old, _)) = state.order.pop_front() {
+                state.seen.remove(&old);
+            }
+        }
+
+        state.seen.insert(key.clone(), now);
+        state.order.push_back((key, now));
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DedupState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Cache key: channel instance and a caller-chosen key.
+type CacheKey = (String, String);
+
+/// A cached result, filled in once the operation succeeds.
+type Slot<T> = Arc<OnceCell<T>>;
+
+#[derive(Debug)]
+struct CacheState<T> {
+    /// Result slot for each remembered key.
+    slots: HashMap<CacheKey, Slot<T>>,
+
+    /// Remembered keys, oldest first.
+    order: VecDeque<(CacheKey, Instant)>,
+}
+
+impl<T> Default for CacheState<T> {
+    fn default() -> Self {
+        Self {
+            slots: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+}
+
+/// Bounded, time-windowed record of successful results by channel and key.
+#[derive(Debug)]
+pub struct ResultCache<T> {
+    config: DedupConfig,
+    state: Mutex<CacheState<T>>,
+}
+
+/// Results of sends made with an idempotency key.
+pub type SendCache = ResultCache<SendResult>;
+
+/// Targets resolved from human-friendly identifiers.
+pub type TargetCache = ResultCache<MessageTarget>;
+
+impl<T: Clone> Default for ResultCache<T> {
+    fn default() -> Self {
+        Self::new(DedupConfig::default())
+    }
+}
+
+impl<T: Clone> ResultCache<T> {
+    /// Create a cache with the given settings.
+    pub fn new(config: DedupConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Run `op` unless it already succeeded for the same key on this
+    /// channel within the window, in which case its result is returned
+    /// instead.
+    ///
+    /// Concurrent calls with the same key wait for the first to finish. A
+    /// failure is not remembered, so the next call tries again.
+    pub async fn get_or_try_insert<F, Fut>(&self, channel_id: &str, key: &str, op: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let slot = self.slot_at((channel_id.to_string(), key.to_string()), Instant::now());
+        slot.get_or_try_init(op).await.cloned()
+    }
+
+    /// Forget every key remembered for `channel_id`.
+    pub fn forget_channel(&self, channel_id: &str) {
+        let mut state = self.lock();
+        state.slots.retain(|(channel, _), _| channel != channel_id);
+        state.order.retain(|((channel, _), _)| channel != channel_id);
+    }
+
+    /// Number of keys currently remembered.
+    pub fn len(&self) -> usize {
+        self.lock().slots.len()
+    }
+
+    /// Whether no keys are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot_at(&self, key: CacheKey, now: Instant) -> Slot<T> {
+        if self.config.capacity == 0 {
+            return Slot::default();
+        }
+
+        let mut state = self.lock();
+
+        // Forget keys that have aged out of the window.
+        while let Some((_, stored_at)) = state.order.front() {
+            if now.duration_since(*stored_at) < self.config.window {
+                break;
+            }
+            if let Some((old, _)) = state.order.pop_front() {
+                state.slots.remove(&old);
+            }
+        }
+
+        if let Some(slot) = state.slots.get(&key) {
+            return slot.clone();
+        }
+
+        while state.order.len() >= self.config.capacity {
+            if let Some((old, _)) = state.order.pop_front() {
+                state.slots.remove(&old);
+            }
+        }
+
+        let slot = Slot::default();
+        state.slots.insert(key.clone(), slot.clone());
+        state.order.push_back((key, now));
+        slot
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SendCache {
+    /// Run `send` unless a send with the same key already succeeded on
+    /// this channel within the window, in which case its result is
+    /// returned instead.
+    ///
+    /// Concurrent calls with the same key wait for the first to finish. A
+    /// failed send is not remembered, so the next call tries again.
+    pub async fn get_or_send<F, Fut>(
+        &self,
+        channel_id: &str,
+        key: &str,
+        send: F,
+    ) -> Result<SendResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SendResult>>,
+    {
+        self.get_or_try_insert(channel_id, key, send).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(channel: &str, account_id: &str, id: &str) -> InboundMessage {
+        InboundMessage {
+            id: MessageId::new(id),
+            channel: channel.to_string(),
+            account_id: account_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_drops_repeats() {
+        let cache = DedupCache::default();
+        let msg = message("whatsapp", "acct", "m1");
+
+        assert!(cache.check(&msg));
+        assert!(!cache.check(&msg));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_key_includes_channel_and_account() {
+        let cache = DedupCache::default();
+
+        assert!(cache.check(&message("whatsapp", "acct", "m1")));
+        assert!(cache.check(&message("whatsapp", "acct", "m2")));
+        assert!(cache.check(&message("whatsapp", "other", "m1")));
+        assert!(cache.check(&message("imessage", "acct", "m1")));
+        assert_eq!(cache.len(), 4);
+    }
+
+    #[test]
+    fn test_window_expiry() {
+        let cache = DedupCache::new(DedupConfig {
+            window: Duration::from_secs(60),
+            capacity: 10,
+        });
+        let key = MessageKey::of(&message("whatsapp", "acct", "m1"));
+        let start = Instant::now();
+
+        assert!(cache.check_at(key.clone(), start));
+        assert!(!cache.check_at(key.clone(), start + Duration::from_secs(59)));
+        assert!(cache.check_at(key, start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let cache = DedupCache::new(DedupConfig {
+            window: Duration::from_secs(60),
+            capacity: 2,
+        });
+
+        assert!(cache.check(&message("whatsapp", "acct", "m1")));
+        assert!(cache.check(&message("whatsapp", "acct", "m2")));
+        assert!(cache.check(&message("whatsapp", "acct", "m3")));
+        assert_eq!(cache.len(), 2);
+
+        assert!(!cache.check(&message("whatsapp", "acct", "m3")));
+        assert!(cache.check(&message("whatsapp", "acct", "m1")));
+    }
+
+    #[tokio::test]
+    async fn test_send_cache_reuses_result() {
+        let cache = SendCache::default();
+        let sends = std::sync::atomic::AtomicU32::new(0);
+        let send = || async {
+            let n = sends.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(SendResult::new(format!("m{}", n)))
+        };
+
+        let first = cache.get_or_send("wa", "k1", send).await.unwrap();
+        let again = cache.get_or_send("wa", "k1", send).await.unwrap();
+        let other = cache.get_or_send("tg", "k1", send).await.unwrap();
+
+        assert_eq!(first.message_id, "m0");
+        assert_eq!(again.message_id, "m0");
+        assert_eq!(other.message_id, "m1");
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_send_cache_retries_failures() {
+        let cache = SendCache::default();
+
+        let err = cache
+            .get_or_send("wa", "k1", || async { Err(crate::ChannelError::Timeout) })
+            .await;
+        assert!(err.is_err());
+
+        let sent = cache
+            .get_or_send("wa", "k1", || async { Ok(SendResult::new("m1")) })
+            .await
+            .unwrap();
+        assert_eq!(sent.message_id, "m1");
+    }
+
+    #[test]
+    fn test_send_cache_window_expiry() {
+        let cache = SendCache::new(DedupConfig {
+            window: Duration::from_secs(60),
+            capacity: 10,
+        });
+        let key = ("wa".to_string(), "k1".to_string());
+        let start = Instant::now();
+
+        let slot = cache.slot_at(key.clone(), start);
+        let within = cache.slot_at(key.clone(), start + Duration::from_secs(59));
+        let after = cache.slot_at(key, start + Duration::from_secs(60));
+        assert!(Arc::ptr_eq(&slot, &within));
+        assert!(!Arc::ptr_eq(&slot, &after));
+    }
+
+    #[tokio::test]
+    async fn test_forget_channel() {
+        let cache = TargetCache::default();
+        let resolve = |id: &'static str| move || async move { Ok(MessageTarget::new(id)) };
+        cache.get_or_try_insert("wa", "+1 555", resolve("1555")).await.unwrap();
+        cache.get_or_try_insert("tg", "@bob", resolve("42")).await.unwrap();
+
+        cache.forget_channel("wa");
+        assert_eq!(cache.len(), 1);
+
+        let target = cache.get_or_try_insert("wa", "+1 555", resolve("other")).await.unwrap();
+        assert_eq!(target.chat_id, "other");
+        let target = cache.get_or_try_insert("tg", "@bob", resolve("other")).await.unwrap();
+        assert_eq!(target.chat_id, "42");
+    }
+}
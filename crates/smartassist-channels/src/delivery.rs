@@ -476,6 +476,149 @@ pub struct QueueStats {
     pub cancelled: usize,
 }
 
+/// Closing fence appended to a chunk that ends inside a code block.
+const FENCE_CLOSE: &str = "\n```";
+
+/// Smallest limit for which code blocks are closed and reopened across chunks.
+const MIN_FENCED_LIMIT: usize = 32;
+
+/// Split a message into messages whose text fits within `max_len` characters.
+///
+/// Text is broken on paragraph breaks, then line breaks, then whitespace,
+/// and only mid-word when nothing else fits. A code block spanning two
+/// chunks is closed at the end of the first and reopened, with its info
+/// string, at the start of the next. The first message keeps the media,
+/// mentions and reply target; the rest carry text only. An idempotency key
+/// is kept on the first message and suffixed with the chunk index on the
+/// rest, so each chunk has a key of its own.
+pub fn split_message(message: &OutboundMessage, max_len: usize) -> Vec<OutboundMessage> {
+    if message.text.chars().count() <= max_len {
+        return vec![message.clone()];
+    }
+
+    split_text(&message.text, max_len)
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| {
+            if i == 0 {
+                OutboundMessage {
+                    text,
+                    ..message.clone()
+                }
+            } else {
+                OutboundMessage {
+                    target: message.target.clone(),
+                    text,
+                    media: vec![],
+                    mentions: vec![],
+                    reply_to: None,
+                    options: message.options.clone(),
+                    idempotency_key: message
+                        .idempotency_key
+                        .as_ref()
+                        .map(|key| format!("{}:{}", key, i)),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Split text into chunks of at most `max_len` characters.
+///
+/// See [`split_message`] for where breaks are placed.
+pub fn split_text(text: &str, max_len: usize) -> Vec<String> {
+    let max_len = max_len.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text.to_string();
+    // Bytes at the start of `rest` that reopen a code block.
+    let mut reopened = 0;
+
+    while rest.chars().count() > max_len {
+        let fenced = max_len >= MIN_FENCED_LIMIT && rest.contains("```");
+        let budget = if fenced {
+            max_len - FENCE_CLOSE.len()
+        } else {
+            max_len
+        };
+
+        let window = &rest[..byte_offset(&rest, budget)];
+        let (cut, skip) = break_point(window, reopened);
+        let mut chunk = rest[..cut].trim_end().to_string();
+        let mut next = rest[cut + skip..].to_string();
+        reopened = 0;
+
+        if fenced {
+            if let Some(opener) = open_fence(&chunk) {
+                // A long info string could leave no room for content.
+                if opener.len() < budget / 2 {
+                    next = format!("{}\n{}", opener, next);
+                    reopened = opener.len() + 1;
+                    chunk.push_str(FENCE_CLOSE);
+                }
+            }
+        }
+
+        if !chunk.trim().is_empty() {
+            chunks.push(chunk);
+        }
+        rest = next;
+    }
+
+    if !rest.trim().is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Byte offset of the character at index `chars`, or the end of the text.
+fn byte_offset(text: &str, chars: usize) -> usize {
+    text.char_indices().nth(chars).map_or(text.len(), |(i, _)| i)
+}
+
+/// Pick where to end a chunk within `window`, after at least `min` bytes.
+///
+/// Returns the chunk end and the length of the separator to drop.
+fn break_point(window: &str, min: usize) -> (usize, usize) {
+    let candidates = window.get(min..).unwrap_or("");
+    let half = window.len() / 2;
+
+    if let Some(i) = candidates
+        .rfind("\n\n")
+        .map(|i| i + min)
+        .filter(|&i| i > min && i >= half)
+    {
+        return (i, 2);
+    }
+    if let Some(i) = candidates.rfind('\n').map(|i| i + min).filter(|&i| i > min) {
+        return (i, 1);
+    }
+    if let Some((i, c)) = candidates
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace())
+        .map(|(i, c)| (i + min, c))
+        .filter(|&(i, _)| i > min)
+    {
+        return (i, c.len_utf8());
+    }
+    (window.len(), 0)
+}
+
+/// The opening fence line of a code block left open at the end of `text`.
+fn open_fence(text: &str) -> Option<&str> {
+    let mut open = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            open = match open {
+                Some(_) => None,
+                None => Some(trimmed.trim_end()),
+            };
+        }
+    }
+    open
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,4 +670,112 @@ mod tests {
         let stats = queue.stats().await;
         assert_eq!(stats.pending, 2);
     }
+
+    /// Check that chunks fit and that no code block is left open.
+    fn assert_well_formed(chunks: &[String], max_len: usize) {
+        for chunk in chunks {
+            assert!(chunk.chars().count() <= max_len, "chunk too long: {}", chunk.len());
+            assert!(open_fence(chunk).is_none(), "unclosed fence in {:?}", chunk);
+        }
+    }
+
+    #[test]
+    fn test_short_message_unchanged() {
+        let message = OutboundMessage {
+            text: "Hello".to_string(),
+            reply_to: Some("m1".to_string()),
+            ..Default::default()
+        };
+
+        let split = split_message(&message, 4096);
+        assert_eq!(split.len(), 1);
+        assert_eq!(split[0].text, "Hello");
+        assert_eq!(split[0].reply_to.as_deref(), Some("m1"));
+    }
+
+    #[test]
+    fn test_split_on_paragraphs() {
+        let paragraph = "word ".repeat(300).trim_end().to_string();
+        let text = vec![paragraph.clone(); 10].join("\n\n");
+        assert!(text.len() > 4096 * 3);
+
+        let chunks = split_text(&text, 4096);
+        assert_well_formed(&chunks, 4096);
+        assert_eq!(chunks.len(), 5);
+        for chunk in &chunks {
+            assert!(chunk.split("\n\n").all(|p| p == paragraph));
+        }
+        assert_eq!(chunks.join("\n\n"), text);
+    }
+
+    #[test]
+    fn test_split_on_words() {
+        let text = "lorem ipsum ".repeat(1000);
+
+        let chunks = split_text(&text, 4096);
+        assert_well_formed(&chunks, 4096);
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|c| c.ends_with("lorem") || c.ends_with("ipsum")));
+        assert_eq!(chunks.join(" "), text);
+    }
+
+    #[test]
+    fn test_split_without_breaks() {
+        let text = "é".repeat(10);
+
+        let chunks = split_text(&text, 4);
+        assert_eq!(chunks, ["éééé", "éééé", "éé"]);
+    }
+
+    #[test]
+    fn test_code_block_reopened() {
+        let code: String = (0..400).map(|i| format!("let x{} = {};\n", i, i)).collect();
+        let text = format!("Here you go:\n\n```rust\n{}```\nDone.", code);
+
+        let chunks = split_text(&text, 4096);
+        assert!(chunks.len() > 1);
+        assert_well_formed(&chunks, 4096);
+        assert!(chunks[0].ends_with("\n```"));
+        assert!(chunks[1].starts_with("```rust\n"));
+        assert!(chunks.last().unwrap().ends_with("Done."));
+
+        // Every line of code survives in order.
+        let lines: Vec<_> = chunks
+            .iter()
+            .flat_map(|c| c.lines())
+            .filter(|l| l.starts_with("let "))
+            .collect();
+        assert_eq!(lines, code.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_split_message_keeps_metadata_on_first() {
+        let message = OutboundMessage {
+            text: "a ".repeat(5000),
+            reply_to: Some("m1".to_string()),
+            ..Default::default()
+        };
+
+        let split = split_message(&message, 4096);
+        assert_eq!(split.len(), 3);
+        assert_eq!(split[0].reply_to.as_deref(), Some("m1"));
+        assert!(split[1..].iter().all(|m| m.reply_to.is_none()));
+        assert!(split.iter().all(|m| m.target.chat_id == message.target.chat_id));
+    }
+
+    #[test]
+    fn test_split_message_derives_chunk_keys() {
+        let message = OutboundMessage {
+            text: "a ".repeat(5000),
+            idempotency_key: Some("reply-7".to_string()),
+            ..Default::default()
+        };
+
+        let keys: Vec<_> = split_message(&message, 4096)
+            .into_iter()
+            .map(|m| m.idempotency_key.unwrap())
+            .collect();
+        assert_eq!(keys, ["reply-7", "reply-7:1", "reply-7:2"]);
+    }
 }
@@ -3,6 +3,7 @@
 #![cfg(feature = "discord")]
 
 use crate::attachment::Attachment;
+use crate::delivery::split_message;
 use crate::error::ChannelError;
 use crate::traits::{
     Channel, ChannelConfig, ChannelLifecycle, ChannelReceiver, ChannelSender, MessageHandler,
@@ -26,6 +27,9 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{info, warn};
 
+/// Longest message content Discord accepts, in characters.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
 /// Discord channel implementation.
 pub struct DiscordChannel {
     /// Bot token.
@@ -253,8 +257,8 @@ impl Channel for DiscordChannel {
                 native_commands: true,
             },
             limits: ChannelLimits {
-                text_max_length: 2000,
-                caption_max_length: 2000,
+                text_max_length: MAX_MESSAGE_LENGTH,
+                caption_max_length: MAX_MESSAGE_LENGTH,
                 messages_per_second: 5.0,
                 messages_per_minute: 300,
             },
@@ -262,6 +266,38 @@ impl Channel for DiscordChannel {
     }
 }
 
+/// Send one message whose content fits within [`MAX_MESSAGE_LENGTH`].
+async fn send_part(
+    http: &Http,
+    channel_id: ChannelId,
+    message: &OutboundMessage,
+    files: Vec<CreateAttachment>,
+) -> Result<SendResult> {
+    let mut builder = CreateMessage::new();
+    if !message.text.is_empty() {
+        builder = builder.content(&message.text);
+    }
+
+    // Set reply reference
+    if let Some(ref reply_to) = message.reply_to {
+        if let Ok(msg_id) = reply_to.parse::<u64>() {
+            let reference = (channel_id, serenity::all::MessageId::new(msg_id));
+            builder = builder.reference_message(reference);
+        }
+    }
+
+    for file in files {
+        builder = builder.add_file(file);
+    }
+
+    let sent = channel_id
+        .send_message(http, builder)
+        .await
+        .map_err(|e| ChannelError::channel("discord", e.to_string()))?;
+
+    Ok(SendResult::new(sent.id.to_string()))
+}
+
 #[async_trait]
 impl ChannelSender for DiscordChannel {
     async fn send(&self, message: OutboundMessage) -> Result<SendResult> {
@@ -278,21 +314,14 @@ impl ChannelSender for DiscordChannel {
                 .map_err(|e| ChannelError::InvalidMessage(e.to_string()))?,
         );
 
-        let mut builder = CreateMessage::new().content(&message.text);
-
-        // Set reply reference
-        if let Some(ref reply_to) = message.reply_to {
-            if let Ok(msg_id) = reply_to.parse::<u64>() {
-                builder = builder.reference_message((channel_id, serenity::all::MessageId::new(msg_id)));
-            }
+        // Discord rejects long content outright, so send it as several
+        // messages, reporting the last one.
+        let mut result = SendResult::new(String::new());
+        for part in split_message(&message, MAX_MESSAGE_LENGTH) {
+            result = send_part(http, channel_id, &part, Vec::new()).await?;
         }
 
-        let sent = channel_id
-            .send_message(http, builder)
-            .await
-            .map_err(|e| ChannelError::channel("discord", e.to_string()))?;
-
-        Ok(SendResult::new(sent.id.to_string()))
+        Ok(result)
     }
 
     async fn send_with_attachments(
@@ -333,21 +362,13 @@ impl ChannelSender for DiscordChannel {
             }
         }
 
-        let mut builder = CreateMessage::new();
-        if !message.text.is_empty() {
-            builder = builder.content(&message.text);
-        }
-
-        for file in files {
-            builder = builder.add_file(file);
+        // Files go with the first part of the text.
+        let mut result = SendResult::new(String::new());
+        for part in split_message(&message, MAX_MESSAGE_LENGTH) {
+            result = send_part(http, channel_id, &part, std::mem::take(&mut files)).await?;
         }
 
-        let sent = channel_id
-            .send_message(http, builder)
-            .await
-            .map_err(|e| ChannelError::channel("discord", e.to_string()))?;
-
-        Ok(SendResult::new(sent.id.to_string()))
+        Ok(result)
     }
 
     async fn edit(&self, message: &MessageRef, new_content: &str) -> Result<()> {
@@ -483,7 +504,7 @@ impl ChannelSender for DiscordChannel {
     }
 
     fn max_message_length(&self) -> usize {
-        2000
+        MAX_MESSAGE_LENGTH
     }
 }
 
@@ -701,4 +722,39 @@ mod tests {
         assert!(caps.chat_types.contains(&ChatType::Direct));
         assert!(caps.chat_types.contains(&ChatType::Group));
     }
+
+    #[test]
+    fn test_split_reopens_code_block() {
+        let code: String = (0..250).map(|i| format!("let line_{} = {};\n", i, i)).collect();
+        let text = format!("Here it is:\n```rust\n{}```\n\nThat's all.", code);
+        assert!(text.chars().count() > 2 * MAX_MESSAGE_LENGTH);
+        let message = OutboundMessage {
+            text,
+            reply_to: Some("42".to_string()),
+            ..Default::default()
+        };
+
+        let parts = split_message(&message, MAX_MESSAGE_LENGTH);
+
+        assert!(parts.len() >= 3);
+        assert!(parts[0].text.contains("```rust\n"));
+        assert_eq!(parts[0].reply_to.as_deref(), Some("42"));
+        for (i, part) in parts.iter().enumerate() {
+            assert!(part.text.chars().count() <= MAX_MESSAGE_LENGTH);
+            // Every part opens and closes its own fences.
+            let fences = part.text.lines().filter(|l| l.trim_start().starts_with("```")).count();
+            assert_eq!(fences % 2, 0, "unbalanced fences in part {}: {:?}", i, part.text);
+            if i > 0 {
+                assert!(part.reply_to.is_none());
+            }
+        }
+
+        // Parts continuing the block reopen it with its language.
+        assert!(parts[1].text.starts_with("```rust\n"));
+        let joined: String = parts.iter().map(|p| p.text.as_str()).collect();
+        for i in 0..250 {
+            assert!(joined.contains(&format!("let line_{} = {};", i, i)));
+        }
+        assert!(parts.last().unwrap().text.ends_with("That's all."));
+    }
 }
@@ -1,5 +1,6 @@
 //! Channel error types.
 
+use crate::traits::ChannelFeature;
 use std::io;
 use thiserror::Error;
 
@@ -74,6 +75,26 @@ pub enum ChannelError {
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
 
+    /// The channel does not support the requested feature.
+    #[error("Channel {channel} does not support {feature}")]
+    Unsupported {
+        /// Channel instance ID.
+        channel: String,
+        /// Feature that was requested.
+        feature: ChannelFeature,
+    },
+
+    /// A human-friendly identifier could not be resolved to a target.
+    #[error("Cannot resolve '{identifier}' on {channel}: {reason}")]
+    UnresolvedTarget {
+        /// Channel instance ID.
+        channel: String,
+        /// Identifier that was given.
+        identifier: String,
+        /// Why it could not be resolved.
+        reason: String,
+    },
+
     /// Channel-specific error.
     #[error("Channel error ({channel}): {message}")]
     Channel {
@@ -121,6 +142,27 @@ impl ChannelError {
         Self::RateLimit { retry_after_secs }
     }
 
+    /// Create an unsupported feature error.
+    pub fn unsupported(channel: impl Into<String>, feature: ChannelFeature) -> Self {
+        Self::Unsupported {
+            channel: channel.into(),
+            feature,
+        }
+    }
+
+    /// Create an unresolved target error.
+    pub fn unresolved(
+        channel: impl Into<String>,
+        identifier: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self::UnresolvedTarget {
+            channel: channel.into(),
+            identifier: identifier.into(),
+            reason: reason.into(),
+        }
+    }
+
     /// Check if this error is retriable.
     pub fn is_retriable(&self) -> bool {
         matches!(
@@ -10,6 +10,10 @@ pub mod delivery;
 pub mod attachment;
 pub mod registry;
 pub mod manager;
+pub mod metrics;
+pub mod dedup;
+pub mod offset;
+pub mod upload;
 
 #[cfg(feature = "telegram")]
 pub mod telegram;
@@ -36,12 +40,22 @@ pub mod whatsapp;
 pub mod line;
 
 pub use error::ChannelError;
-pub use traits::{Channel, ChannelConfig, ChannelReceiver, ChannelSender, ChannelLifecycle, MessageHandler, MessageRef, SendResult};
+pub use traits::{Channel, ChannelConfig, ChannelFeature, ChannelReceiver, ChannelSender, ChannelLifecycle, MessageHandler, MessageRef, SendResult};
 pub use routing::{Router, RouteMatch, RouteRule};
-pub use delivery::{DeliveryQueue, DeliveryStatus, DeliveryResult};
+pub use delivery::{split_message, DeliveryQueue, DeliveryStatus, DeliveryResult};
 pub use attachment::{Attachment, AttachmentType};
 pub use registry::{ChannelRegistry, RegisteredChannel};
-pub use manager::{ChannelManager, ChannelManagerBuilder, ManagerStatus, ManagerMessageHandler};
+pub use manager::{
+    ChannelManager, ChannelManagerBuilder, ManagerMessageHandler, ManagerStatus, RetryConfig,
+    UnsupportedPolicy,
+};
+pub use dedup::{DedupCache, DedupConfig, ResultCache, SendCache, TargetCache};
+pub use metrics::{ChannelMetrics, ChannelMetricsSnapshot, LatencyHistogram};
+pub use offset::{FileOffsetStore, MemoryOffsetStore, OffsetStore, PollOffset};
+pub use upload::{
+    ChunkedUploader, FileUploadStore, MemoryUploadStore, UploadSession, UploadStore,
+    DEFAULT_UPLOAD_CHUNK_SIZE,
+};
 
 /// Result type for channel operations.
 pub type Result<T> = std::result::Result<T, ChannelError>;
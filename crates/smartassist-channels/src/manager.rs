@@ -2,25 +2,47 @@
 //!
 //! The ChannelManager provides a unified interface for:
 //! - Managing channel lifecycle (connect, disconnect)
-//! - Routing inbound messages to agents
+//! - Routing inbound messages to agents, dropping duplicates
 //! - Delivering outbound messages via the delivery pipeline
+//! - Sending each idempotency key at most once
+//! - Retrying transient send failures with backoff
+//! - Checking channel capabilities before edits, reactions and the like
 //! - Health monitoring and status reporting
 
-use crate::delivery::{DeliveryConfig, DeliveryQueue};
+use crate::dedup::{DedupCache, DedupConfig, SendCache, TargetCache};
+use crate::delivery::{split_message, DeliveryConfig, DeliveryQueue};
 use crate::error::ChannelError;
+use crate::metrics::{ChannelMetrics, ChannelMetricsSnapshot};
 use crate::registry::{ChannelRegistry, RegistryStats};
 use crate::routing::{RouteMatch, RouteRule, Router};
-use crate::traits::{Channel, ChannelConfig, ChannelFactory, SendResult};
+use crate::traits::{Channel, ChannelConfig, ChannelFactory, ChannelFeature, MessageRef, SendResult};
 use crate::Result;
 use async_trait::async_trait;
+use futures::StreamExt;
 use smartassist_core::types::{
-    AgentId, ChannelHealth, InboundMessage, MessageTarget, OutboundMessage,
+    AgentId, ChannelHealth, HealthStatus, InboundMessage, MessageTarget, OutboundMessage,
 };
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tracing::{debug, error, info, warn};
 
+/// How long a channel's health check may take before it counts as unhealthy.
+const DEFAULT_PRESENCE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many channel health checks presence reporting runs at once.
+const PRESENCE_CONCURRENCY: usize = 8;
+
+/// How long, and for how many identifiers, resolved targets are remembered.
+fn default_target_cache_config() -> DedupConfig {
+    DedupConfig {
+        window: Duration::from_secs(3600),
+        capacity: 10_000,
+    }
+}
+
 /// The central manager for all messaging channels.
 pub struct ChannelManager {
     /// Channel registry for managing instances.
@@ -43,6 +65,121 @@ pub struct ChannelManager {
 
     /// Shutdown signal.
     shutdown: Arc<RwLock<Option<mpsc::Sender<()>>>>,
+
+    /// Metrics aggregated across all channels.
+    metrics: Arc<ChannelMetrics>,
+
+    /// Per-channel metrics by instance ID.
+    channel_metrics: Arc<RwLock<HashMap<String, Arc<ChannelMetrics>>>>,
+
+    /// Retry policy for direct sends.
+    retry_config: RetryConfig,
+
+    /// Sends waiting out a backoff delay before their next attempt.
+    retry_queued: Arc<AtomicUsize>,
+
+    /// Retry attempts currently in flight.
+    retrying: Arc<AtomicUsize>,
+
+    /// Recently seen inbound messages.
+    dedup: Arc<DedupCache>,
+
+    /// Results of recent sends made with an idempotency key.
+    sent: Arc<SendCache>,
+
+    /// Targets resolved from human-friendly identifiers.
+    targets: Arc<TargetCache>,
+
+    /// Per-channel time limit for presence health checks.
+    presence_timeout: Duration,
+
+    /// What to do with operations a channel doesn't support.
+    unsupported_policy: UnsupportedPolicy,
+}
+
+/// What [`ChannelManager`] does with an operation the target channel's
+/// capabilities rule out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsupportedPolicy {
+    /// Fail with [`ChannelError::Unsupported`] without calling the channel.
+    #[default]
+    Reject,
+
+    /// Fall back to a plain message where one conveys the same thing: a
+    /// reaction is sent as the emoji in reply to the message, and an edit
+    /// as a reply with the new content. Typing indicators are skipped.
+    /// Deletes and removing reactions are still rejected.
+    Degrade,
+}
+
+/// Retry policy for transient send failures.
+///
+/// Only errors for which [`ChannelError::is_retriable`] holds are retried;
+/// anything else is returned to the caller immediately.
+#[derive(Debug, Clone)]
+pub struct RetryConfig {
+    /// Maximum retries after the first attempt.
+    pub max_retries: u32,
+
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+
+    /// Upper bound for any single delay, including server hints.
+    pub max_delay: Duration,
+
+    /// Delay multiplier applied after each retry.
+    pub multiplier: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Disable retries.
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1) after `error`.
+    ///
+    /// The error's own hint (e.g. a rate limit's retry-after) wins when it
+    /// is longer than the computed backoff.
+    fn delay_for(&self, retry: u32, error: &ChannelError) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let seconds = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        // A backoff too large (or a multiplier too odd) to represent is
+        // capped like any other long delay.
+        let backoff = Duration::try_from_secs_f64(seconds).unwrap_or(self.max_delay);
+        let delay = error.retry_delay().map_or(backoff, |hint| hint.max(backoff));
+        delay.min(self.max_delay)
+    }
+}
+
+/// Increments a counter for as long as it is alive.
+struct CountGuard(Arc<AtomicUsize>);
+
+impl CountGuard {
+    fn new(counter: &Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter.clone())
+    }
+}
+
+impl Drop for CountGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 /// Handler for processing routed messages.
@@ -62,6 +199,15 @@ impl Default for ChannelManager {
     }
 }
 
+impl std::fmt::Debug for ChannelManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChannelManager")
+            .field("presence_timeout", &self.presence_timeout)
+            .field("unsupported_policy", &self.unsupported_policy)
+            .finish_non_exhaustive()
+    }
+}
+
 impl ChannelManager {
     /// Create a new channel manager.
     pub fn new() -> Self {
@@ -75,6 +221,16 @@ impl ChannelManager {
             message_handler: Arc::new(RwLock::new(None)),
             running: Arc::new(RwLock::new(false)),
             shutdown: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(ChannelMetrics::new()),
+            channel_metrics: Arc::new(RwLock::new(HashMap::new())),
+            retry_config: RetryConfig::default(),
+            retry_queued: Arc::new(AtomicUsize::new(0)),
+            retrying: Arc::new(AtomicUsize::new(0)),
+            dedup: Arc::new(DedupCache::default()),
+            sent: Arc::new(SendCache::default()),
+            targets: Arc::new(TargetCache::new(default_target_cache_config())),
+            presence_timeout: DEFAULT_PRESENCE_TIMEOUT,
+            unsupported_policy: UnsupportedPolicy::default(),
         }
     }
 
@@ -94,9 +250,57 @@ impl ChannelManager {
             message_handler: Arc::new(RwLock::new(None)),
             running: Arc::new(RwLock::new(false)),
             shutdown: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(ChannelMetrics::new()),
+            channel_metrics: Arc::new(RwLock::new(HashMap::new())),
+            retry_config: RetryConfig::default(),
+            retry_queued: Arc::new(AtomicUsize::new(0)),
+            retrying: Arc::new(AtomicUsize::new(0)),
+            dedup: Arc::new(DedupCache::default()),
+            sent: Arc::new(SendCache::default()),
+            targets: Arc::new(TargetCache::new(default_target_cache_config())),
+            presence_timeout: DEFAULT_PRESENCE_TIMEOUT,
+            unsupported_policy: UnsupportedPolicy::default(),
         }
     }
 
+    /// Set the retry policy for direct sends.
+    pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Set the inbound deduplication window and capacity.
+    pub fn with_dedup_config(mut self, config: DedupConfig) -> Self {
+        self.dedup = Arc::new(DedupCache::new(config));
+        self
+    }
+
+    /// Set how long, and for how many keys, idempotent send results are
+    /// remembered.
+    pub fn with_idempotency_config(mut self, config: DedupConfig) -> Self {
+        self.sent = Arc::new(SendCache::new(config));
+        self
+    }
+
+    /// Set how long, and for how many identifiers, resolved targets are
+    /// remembered.
+    pub fn with_target_cache_config(mut self, config: DedupConfig) -> Self {
+        self.targets = Arc::new(TargetCache::new(config));
+        self
+    }
+
+    /// Set the per-channel time limit for presence health checks.
+    pub fn with_presence_timeout(mut self, timeout: Duration) -> Self {
+        self.presence_timeout = timeout;
+        self
+    }
+
+    /// Set what happens to operations a channel doesn't support.
+    pub fn with_unsupported_policy(mut self, policy: UnsupportedPolicy) -> Self {
+        self.unsupported_policy = policy;
+        self
+    }
+
     /// Get the channel registry.
     pub fn registry(&self) -> &Arc<ChannelRegistry> {
         &self.registry
@@ -142,7 +346,9 @@ impl ChannelManager {
 
     /// Unregister a channel.
     pub async fn remove_channel(&self, instance_id: &str) -> Result<()> {
-        self.registry.unregister(instance_id).await
+        self.registry.unregister(instance_id).await?;
+        self.targets.forget_channel(instance_id);
+        Ok(())
     }
 
     // --- Routing ---
@@ -187,6 +393,11 @@ impl ChannelManager {
     // --- Sending Messages ---
 
     /// Send a message through a specific channel.
+    ///
+    /// Text longer than the channel's limit is sent as several messages;
+    /// see [`ChannelManager::send_chunked`]. A message with an
+    /// idempotency key that was already sent through this channel within
+    /// the window is not sent again; the earlier result is returned.
     pub async fn send(
         &self,
         channel_id: &str,
@@ -196,7 +407,14 @@ impl ChannelManager {
             ChannelError::not_found(channel_id)
         })?;
 
-        channel.send(message).await
+        let Some(key) = message.idempotency_key.clone() else {
+            return self.send_chunked(channel_id, channel, message).await;
+        };
+        self.sent
+            .get_or_send(channel_id, &key, || {
+                self.send_chunked(channel_id, channel, message)
+            })
+            .await
     }
 
     /// Send a message to a specific target (auto-selects channel).
@@ -219,8 +437,9 @@ impl ChannelManager {
                         mentions: vec![],
                         reply_to: None,
                         options: Default::default(),
+                        idempotency_key: None,
                     };
-                    return channel.send(message).await;
+                    return self.send_chunked(&instance_id, channel, message).await;
                 }
             }
         }
@@ -231,6 +450,257 @@ impl ChannelManager {
         )))
     }
 
+    /// Send through a channel, splitting text that exceeds its length limit.
+    ///
+    /// Chunks are sent in order and the first failure stops the rest. The
+    /// result is that of the last chunk, with the IDs of every chunk under
+    /// the `chunk_message_ids` metadata key when more than one was sent.
+    async fn send_chunked(
+        &self,
+        channel_id: &str,
+        channel: Arc<dyn Channel>,
+        message: OutboundMessage,
+    ) -> Result<SendResult> {
+        let max_len = channel.capabilities().limits.text_max_length;
+        let chunks = split_message(&message, max_len);
+        if chunks.len() == 1 {
+            return self.send_with_metrics(channel_id, channel, message).await;
+        }
+
+        debug!(
+            "Splitting {} byte message for {} into {} chunks",
+            message.text.len(),
+            channel_id,
+            chunks.len()
+        );
+
+        let mut ids = Vec::with_capacity(chunks.len());
+        let mut last = None;
+        for chunk in chunks {
+            let result = self.send_with_metrics(channel_id, channel.clone(), chunk).await?;
+            ids.push(serde_json::Value::String(result.message_id.clone()));
+            last = Some(result);
+        }
+
+        let mut result = last.expect("split_message returns at least one chunk");
+        result
+            .metadata
+            .insert("chunk_message_ids".to_string(), serde_json::Value::Array(ids));
+        Ok(result)
+    }
+
+    /// Send through a channel, retrying transient failures.
+    ///
+    /// Every attempt is recorded in the metrics. The last error is returned
+    /// once the error is permanent or the retries are exhausted.
+    async fn send_with_metrics(
+        &self,
+        channel_id: &str,
+        channel: Arc<dyn Channel>,
+        message: OutboundMessage,
+    ) -> Result<SendResult> {
+        let metrics = Self::metrics_entry(&self.channel_metrics, channel_id).await;
+        let bytes = message.text.len();
+        let mut retry = 0;
+
+        loop {
+            let started = Instant::now();
+            let result = {
+                let _in_flight = (retry > 0).then(|| CountGuard::new(&self.retrying));
+                channel.send(message.clone()).await
+            };
+            let latency = started.elapsed();
+
+            let error = match result {
+                Ok(sent) => {
+                    metrics.record_send(bytes, latency);
+                    self.metrics.record_send(bytes, latency);
+                    return Ok(sent);
+                }
+                Err(e) => {
+                    metrics.record_send_failure(latency);
+                    self.metrics.record_send_failure(latency);
+                    e
+                }
+            };
+
+            if !error.is_retriable() || retry >= self.retry_config.max_retries {
+                if retry > 0 {
+                    error!(
+                        "Send via {} failed after {} attempts: {}",
+                        channel_id,
+                        retry + 1,
+                        error
+                    );
+                }
+                return Err(error);
+            }
+
+            retry += 1;
+            let delay = self.retry_config.delay_for(retry, &error);
+            warn!(
+                "Send via {} failed (attempt {}), retrying in {:?}: {}",
+                channel_id, retry, delay, error
+            );
+
+            let _queued = CountGuard::new(&self.retry_queued);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Get or create the metrics entry for a channel.
+    async fn metrics_entry(
+        channel_metrics: &RwLock<HashMap<String, Arc<ChannelMetrics>>>,
+        channel_id: &str,
+    ) -> Arc<ChannelMetrics> {
+        if let Some(metrics) = channel_metrics.read().await.get(channel_id) {
+            return metrics.clone();
+        }
+
+        channel_metrics
+            .write()
+            .await
+            .entry(channel_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    // --- Message Actions ---
+
+    /// Edit a message sent through a channel.
+    pub async fn edit(
+        &self,
+        channel_id: &str,
+        message: &MessageRef,
+        new_content: &str,
+    ) -> Result<()> {
+        let channel = self.get_or_not_found(channel_id).await?;
+        if self.check_feature(channel_id, &*channel, ChannelFeature::Edits)? {
+            return channel.edit(message, new_content).await;
+        }
+        self.send_reply(channel_id, channel, message, new_content).await
+    }
+
+    /// Delete a message sent through a channel.
+    pub async fn delete(&self, channel_id: &str, message: &MessageRef) -> Result<()> {
+        let channel = self.get_or_not_found(channel_id).await?;
+        if !channel.supports(ChannelFeature::Deletes) {
+            return Err(ChannelError::unsupported(channel_id, ChannelFeature::Deletes));
+        }
+        channel.delete(message).await
+    }
+
+    /// Add a reaction to a message.
+    pub async fn react(&self, channel_id: &str, message: &MessageRef, emoji: &str) -> Result<()> {
+        let channel = self.get_or_not_found(channel_id).await?;
+        if self.check_feature(channel_id, &*channel, ChannelFeature::Reactions)? {
+            return channel.react(message, emoji).await;
+        }
+        self.send_reply(channel_id, channel, message, emoji).await
+    }
+
+    /// Remove a reaction from a message.
+    pub async fn unreact(
+        &self,
+        channel_id: &str,
+        message: &MessageRef,
+        emoji: &str,
+    ) -> Result<()> {
+        let channel = self.get_or_not_found(channel_id).await?;
+        if !channel.supports(ChannelFeature::Reactions) {
+            return Err(ChannelError::unsupported(channel_id, ChannelFeature::Reactions));
+        }
+        channel.unreact(message, emoji).await
+    }
+
+    /// Show a typing indicator in a chat.
+    pub async fn send_typing(&self, channel_id: &str, target: &MessageTarget) -> Result<()> {
+        let channel = self.get_or_not_found(channel_id).await?;
+        if self.check_feature(channel_id, &*channel, ChannelFeature::TypingIndicators)? {
+            return channel.send_typing(target).await;
+        }
+        Ok(())
+    }
+
+    // --- Target Resolution ---
+
+    /// Resolve a human-friendly identifier, such as `@username` or a phone
+    /// number, to a target on a channel.
+    ///
+    /// Resolutions are cached per channel. Failures are not, so an
+    /// identifier that could not be resolved is looked up again next time.
+    pub async fn resolve_target(
+        &self,
+        channel_id: &str,
+        identifier: &str,
+    ) -> Result<MessageTarget> {
+        let identifier = identifier.trim();
+        if identifier.is_empty() {
+            return Err(ChannelError::unresolved(channel_id, identifier, "identifier is empty"));
+        }
+        let channel = self.get_or_not_found(channel_id).await?;
+        self.targets
+            .get_or_try_insert(channel_id, identifier, || async move {
+                let target = channel.resolve_target(identifier).await?;
+                debug!("Resolved {} on {} to {}", identifier, channel_id, target.chat_id);
+                Ok(target)
+            })
+            .await
+    }
+
+    async fn get_or_not_found(&self, channel_id: &str) -> Result<Arc<dyn Channel>> {
+        self.registry
+            .get(channel_id)
+            .await
+            .ok_or_else(|| ChannelError::not_found(channel_id))
+    }
+
+    /// Check whether `channel` supports `feature`.
+    ///
+    /// Returns `Ok(false)` when it doesn't but the policy allows degrading,
+    /// and an error when the operation should be rejected.
+    fn check_feature(
+        &self,
+        channel_id: &str,
+        channel: &dyn Channel,
+        feature: ChannelFeature,
+    ) -> Result<bool> {
+        if channel.supports(feature) {
+            return Ok(true);
+        }
+        match self.unsupported_policy {
+            UnsupportedPolicy::Reject => Err(ChannelError::unsupported(channel_id, feature)),
+            UnsupportedPolicy::Degrade => {
+                debug!("{} does not support {}, degrading", channel_id, feature);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Send `text` in reply to `message`, in the same chat and thread.
+    async fn send_reply(
+        &self,
+        channel_id: &str,
+        channel: Arc<dyn Channel>,
+        message: &MessageRef,
+        text: &str,
+    ) -> Result<()> {
+        let reply = OutboundMessage {
+            target: MessageTarget {
+                chat_id: message.chat_id.clone(),
+                thread_id: message.thread_id.clone(),
+            },
+            text: text.to_string(),
+            media: vec![],
+            mentions: vec![],
+            reply_to: Some(message.message_id.clone()),
+            options: Default::default(),
+            idempotency_key: None,
+        };
+        self.send_chunked(channel_id, channel, reply).await?;
+        Ok(())
+    }
+
     /// Queue a message for delivery.
     pub async fn queue_message(
         &self,
@@ -318,6 +788,9 @@ impl ChannelManager {
         let inbound_tx = self.inbound_tx.clone();
         let router = self.router.clone();
         let handler = self.message_handler.clone();
+        let metrics = self.metrics.clone();
+        let channel_metrics = self.channel_metrics.clone();
+        let dedup = self.dedup.clone();
 
         tokio::spawn(async move {
             info!("Starting message receive loop");
@@ -341,8 +814,19 @@ impl ChannelManager {
                                 // Try to receive a message
                                 match channel.try_receive().await {
                                     Ok(Some(message)) => {
+                                        if !dedup.check(&message) {
+                                            debug!("Dropping duplicate message from channel {}: {:?}", id, message.id);
+                                            continue;
+                                        }
+
                                         debug!("Received message from channel {}: {:?}", id, message.id);
 
+                                        let bytes = message.text.len();
+                                        metrics.record_receive(bytes);
+                                        Self::metrics_entry(&channel_metrics, &id)
+                                            .await
+                                            .record_receive(bytes);
+
                                         // Broadcast to subscribers
                                         if let Err(e) = inbound_tx.send(message.clone()) {
                                             debug!("No subscribers for inbound messages: {}", e);
@@ -423,11 +907,62 @@ impl ChannelManager {
         self.registry.health_check().await
     }
 
+    /// Get a snapshot of every channel's health for presence reporting.
+    ///
+    /// Up to eight channels are checked at a time. One that does not answer
+    /// within the presence timeout is reported as unhealthy instead of
+    /// holding up the others.
+    pub async fn presence(&self) -> HashMap<String, ChannelHealth> {
+        let mut channels = Vec::new();
+        for id in self.registry.list().await {
+            if let Some(channel) = self.registry.get(&id).await {
+                channels.push((id, channel));
+            }
+        }
+
+        let timeout = self.presence_timeout;
+        let checks: Vec<_> = channels
+            .into_iter()
+            .map(|(id, channel)| async move {
+                let error = match tokio::time::timeout(timeout, channel.health()).await {
+                    Ok(Ok(health)) => return (id, health),
+                    Ok(Err(e)) => e.to_string(),
+                    Err(_) => format!("Health check timed out after {:?}", timeout),
+                };
+                let health = ChannelHealth {
+                    status: HealthStatus::Unhealthy,
+                    error: Some(error),
+                    ..Default::default()
+                };
+                (id, health)
+            })
+            .collect();
+
+        futures::stream::iter(checks)
+            .buffer_unordered(PRESENCE_CONCURRENCY)
+            .collect()
+            .await
+    }
+
     /// Get registry statistics.
     pub async fn stats(&self) -> RegistryStats {
         self.registry.stats().await
     }
 
+    /// Get metrics aggregated across all channels.
+    pub fn metrics(&self) -> ChannelMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Get metrics for a specific channel.
+    pub async fn channel_metrics(&self, instance_id: &str) -> Option<ChannelMetricsSnapshot> {
+        self.channel_metrics
+            .read()
+            .await
+            .get(instance_id)
+            .map(|m| m.snapshot())
+    }
+
     /// Get manager status.
     pub async fn status(&self) -> ManagerStatus {
         let stats = self.registry.stats().await;
@@ -440,6 +975,9 @@ impl ChannelManager {
             channels_enabled: stats.enabled,
             queue_pending: queue_stats.pending,
             queue_delivered: queue_stats.delivered,
+            retry_queued: self.retry_queued.load(Ordering::Relaxed),
+            retrying: self.retrying.load(Ordering::Relaxed),
+            metrics: self.metrics.snapshot(),
         }
     }
 }
@@ -464,6 +1002,15 @@ pub struct ManagerStatus {
 
     /// Delivered messages.
     pub queue_delivered: usize,
+
+    /// Sends waiting to be retried after a transient failure.
+    pub retry_queued: usize,
+
+    /// Retry attempts currently in flight.
+    pub retrying: usize,
+
+    /// Throughput and latency metrics across all channels.
+    pub metrics: ChannelMetricsSnapshot,
 }
 
 /// Builder for creating a ChannelManager with configuration.
@@ -471,6 +1018,12 @@ pub struct ChannelManagerBuilder {
     default_agent: Option<AgentId>,
     rules: Vec<RouteRule>,
     delivery_config: DeliveryConfig,
+    retry_config: RetryConfig,
+    dedup_config: DedupConfig,
+    idempotency_config: DedupConfig,
+    target_cache_config: DedupConfig,
+    presence_timeout: Duration,
+    unsupported_policy: UnsupportedPolicy,
 }
 
 impl Default for ChannelManagerBuilder {
@@ -486,6 +1039,12 @@ impl ChannelManagerBuilder {
             default_agent: None,
             rules: Vec::new(),
             delivery_config: DeliveryConfig::default(),
+            retry_config: RetryConfig::default(),
+            dedup_config: DedupConfig::default(),
+            idempotency_config: DedupConfig::default(),
+            target_cache_config: default_target_cache_config(),
+            presence_timeout: DEFAULT_PRESENCE_TIMEOUT,
+            unsupported_policy: UnsupportedPolicy::default(),
         }
     }
 
@@ -513,6 +1072,44 @@ impl ChannelManagerBuilder {
         self
     }
 
+    /// Set the retry policy for direct sends.
+    pub fn retry_config(mut self, config: RetryConfig) -> Self {
+        self.retry_config = config;
+        self
+    }
+
+    /// Set the inbound deduplication window and capacity.
+    pub fn dedup_config(mut self, config: DedupConfig) -> Self {
+        self.dedup_config = config;
+        self
+    }
+
+    /// Set how long, and for how many keys, idempotent send results are
+    /// remembered.
+    pub fn idempotency_config(mut self, config: DedupConfig) -> Self {
+        self.idempotency_config = config;
+        self
+    }
+
+    /// Set how long, and for how many identifiers, resolved targets are
+    /// remembered.
+    pub fn target_cache_config(mut self, config: DedupConfig) -> Self {
+        self.target_cache_config = config;
+        self
+    }
+
+    /// Set the per-channel time limit for presence health checks.
+    pub fn presence_timeout(mut self, timeout: Duration) -> Self {
+        self.presence_timeout = timeout;
+        self
+    }
+
+    /// Set what happens to operations a channel doesn't support.
+    pub fn unsupported_policy(mut self, policy: UnsupportedPolicy) -> Self {
+        self.unsupported_policy = policy;
+        self
+    }
+
     /// Build the channel manager.
     pub fn build(self) -> ChannelManager {
         let mut router = Router::new();
@@ -530,12 +1127,343 @@ impl ChannelManagerBuilder {
             router,
             Arc::new(DeliveryQueue::new(self.delivery_config)),
         )
+        .with_retry_config(self.retry_config)
+        .with_dedup_config(self.dedup_config)
+        .with_idempotency_config(self.idempotency_config)
+        .with_target_cache_config(self.target_cache_config)
+        .with_presence_timeout(self.presence_timeout)
+        .with_unsupported_policy(self.unsupported_policy)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::attachment::Attachment;
+    use crate::traits::{ChannelLifecycle, ChannelReceiver, ChannelSender, MessageHandler, MessageRef};
+    use smartassist_core::types::{ChannelCapabilities, ChannelFeatures, ChannelHealth};
+    use smartassist_core::types::MessageId;
+    use std::collections::VecDeque;
+    use std::sync::atomic::AtomicU32;
+
+    /// In-memory channel that accepts (or rejects) every send.
+    #[derive(Debug)]
+    struct FakeChannel {
+        id: String,
+        fail_sends: bool,
+        /// Sends left to time out before the channel recovers.
+        timeouts_left: AtomicU32,
+        /// Messages handed out by `try_receive`, oldest first.
+        inbound: std::sync::Mutex<VecDeque<InboundMessage>>,
+        /// Status reported by `health`, or `None` to fail the check.
+        health: Option<HealthStatus>,
+        /// How long `health` takes to answer.
+        health_delay: Duration,
+        /// Features reported by `capabilities`.
+        features: ChannelFeatures,
+        /// Edits, reactions and sends that reached the channel, in order.
+        actions: std::sync::Mutex<Vec<String>>,
+        /// Targets for friendly identifiers; empty to pass identifiers
+        /// through as chat IDs.
+        directory: HashMap<String, MessageTarget>,
+        /// Calls to `resolve_target`.
+        lookups: AtomicU32,
+    }
+
+    impl FakeChannel {
+        fn new(id: &str, fail_sends: bool) -> Self {
+            Self {
+                id: id.to_string(),
+                fail_sends,
+                timeouts_left: AtomicU32::new(0),
+                inbound: std::sync::Mutex::new(VecDeque::new()),
+                health: Some(HealthStatus::Unknown),
+                health_delay: Duration::ZERO,
+                features: ChannelFeatures::default(),
+                actions: std::sync::Mutex::new(Vec::new()),
+                directory: HashMap::new(),
+                lookups: AtomicU32::new(0),
+            }
+        }
+
+        /// A channel resolving the identifiers in `directory`.
+        fn with_directory(id: &str, directory: Vec<(&str, MessageTarget)>) -> Self {
+            Self {
+                directory: directory
+                    .into_iter()
+                    .map(|(name, target)| (name.to_string(), target))
+                    .collect(),
+                ..Self::new(id, false)
+            }
+        }
+
+        /// A channel reporting `features`.
+        fn with_features(id: &str, features: ChannelFeatures) -> Self {
+            Self {
+                features,
+                ..Self::new(id, false)
+            }
+        }
+
+        fn record(&self, action: String) {
+            self.actions.lock().unwrap().push(action);
+        }
+
+        /// A channel whose health check answers `health` after `delay`.
+        fn with_health(id: &str, health: Option<HealthStatus>, delay: Duration) -> Self {
+            Self {
+                health,
+                health_delay: delay,
+                ..Self::new(id, false)
+            }
+        }
+
+        /// A channel that receives `messages` in order.
+        fn with_inbound(id: &str, messages: Vec<InboundMessage>) -> Self {
+            Self {
+                inbound: std::sync::Mutex::new(messages.into()),
+                ..Self::new(id, false)
+            }
+        }
+
+        /// A channel whose first `timeouts` sends time out.
+        fn flaky(id: &str, timeouts: u32) -> Self {
+            Self {
+                timeouts_left: AtomicU32::new(timeouts),
+                ..Self::new(id, false)
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Channel for FakeChannel {
+        fn channel_type(&self) -> &str {
+            "fake"
+        }
+
+        fn instance_id(&self) -> &str {
+            &self.id
+        }
+
+        fn capabilities(&self) -> ChannelCapabilities {
+            ChannelCapabilities {
+                features: self.features.clone(),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ChannelSender for FakeChannel {
+        async fn send(&self, message: OutboundMessage) -> Result<SendResult> {
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+            if self.fail_sends {
+                return Err(ChannelError::Internal("send rejected".to_string()));
+            }
+            let timed_out = self
+                .timeouts_left
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok();
+            if timed_out {
+                return Err(ChannelError::Timeout);
+            }
+            let reply_to = message.reply_to.as_deref().unwrap_or("-");
+            self.record(format!("send {} re {}", message.text, reply_to));
+            Ok(SendResult::with_chat("msg", message.target.chat_id))
+        }
+
+        async fn send_with_attachments(
+            &self,
+            message: OutboundMessage,
+            _attachments: Vec<Attachment>,
+        ) -> Result<SendResult> {
+            self.send(message).await
+        }
+
+        async fn edit(&self, message: &MessageRef, new_content: &str) -> Result<()> {
+            self.record(format!("edit {} {}", message.message_id, new_content));
+            Ok(())
+        }
+
+        async fn delete(&self, _message: &MessageRef) -> Result<()> {
+            Ok(())
+        }
+
+        async fn react(&self, message: &MessageRef, emoji: &str) -> Result<()> {
+            self.record(format!("react {} {}", message.message_id, emoji));
+            Ok(())
+        }
+
+        async fn unreact(&self, _message: &MessageRef, _emoji: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_typing(&self, _target: &MessageTarget) -> Result<()> {
+            Ok(())
+        }
+
+        async fn resolve_target(&self, identifier: &str) -> Result<MessageTarget> {
+            self.lookups.fetch_add(1, Ordering::Relaxed);
+            if self.directory.is_empty() {
+                return Ok(MessageTarget::new(identifier));
+            }
+            self.directory
+                .get(identifier)
+                .cloned()
+                .ok_or_else(|| ChannelError::unresolved(&self.id, identifier, "no such user"))
+        }
+    }
+
+    #[async_trait]
+    impl ChannelReceiver for FakeChannel {
+        async fn start_receiving(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn stop_receiving(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&self) -> Result<InboundMessage> {
+            Err(ChannelError::Internal("no messages".to_string()))
+        }
+
+        async fn try_receive(&self) -> Result<Option<InboundMessage>> {
+            Ok(self.inbound.lock().unwrap().pop_front())
+        }
+
+        fn set_handler(&self, _handler: Box<dyn MessageHandler>) {}
+    }
+
+    #[async_trait]
+    impl ChannelLifecycle for FakeChannel {
+        async fn connect(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn disconnect(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        async fn health(&self) -> Result<ChannelHealth> {
+            tokio::time::sleep(self.health_delay).await;
+            match self.health {
+                Some(status) => Ok(ChannelHealth {
+                    status,
+                    ..Default::default()
+                }),
+                None => Err(ChannelError::Internal("probe failed".to_string())),
+            }
+        }
+    }
+
+    fn outbound(text: &str) -> OutboundMessage {
+        OutboundMessage {
+            target: MessageTarget::new("chat1"),
+            text: text.to_string(),
+            media: vec![],
+            mentions: vec![],
+            reply_to: None,
+            options: Default::default(),
+            idempotency_key: None,
+        }
+    }
+
+    async fn manager_with(id: &str, fail_sends: bool) -> ChannelManager {
+        let manager = ChannelManager::new();
+        manager
+            .register_channel(
+                ChannelConfig::new("fake", id, "account"),
+                Arc::new(FakeChannel::new(id, fail_sends)),
+            )
+            .await
+            .unwrap();
+        manager
+    }
+
+    async fn flaky_manager(id: &str, timeouts: u32, retry_config: RetryConfig) -> ChannelManager {
+        let manager = ChannelManager::new().with_retry_config(retry_config);
+        manager
+            .register_channel(
+                ChannelConfig::new("fake", id, "account"),
+                Arc::new(FakeChannel::flaky(id, timeouts)),
+            )
+            .await
+            .unwrap();
+        manager
+    }
+
+    /// Handler recording the IDs of the messages it is given.
+    #[derive(Default)]
+    struct RecordingHandler {
+        handled: tokio::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ManagerMessageHandler for RecordingHandler {
+        async fn handle_message(&self, message: InboundMessage, _route: RouteMatch) -> Result<()> {
+            self.handled.lock().await.push(message.id.as_str().to_string());
+            Ok(())
+        }
+    }
+
+    fn inbound(id: &str) -> InboundMessage {
+        InboundMessage {
+            id: MessageId::new(id),
+            channel: "fake".to_string(),
+            account_id: "account".to_string(),
+            text: "hi".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Features of the WhatsApp Cloud API channel: reactions but no edits.
+    fn whatsapp_features() -> ChannelFeatures {
+        ChannelFeatures {
+            reactions: true,
+            read_receipts: true,
+            mentions: true,
+            ..Default::default()
+        }
+    }
+
+    /// Features of the Slack channel: edits, deletes, reactions, threads.
+    fn slack_features() -> ChannelFeatures {
+        ChannelFeatures {
+            reactions: true,
+            threads: true,
+            edits: true,
+            deletes: true,
+            mentions: true,
+            ..Default::default()
+        }
+    }
+
+    async fn register_with_features(
+        manager: &ChannelManager,
+        id: &str,
+        features: ChannelFeatures,
+    ) -> Arc<FakeChannel> {
+        let channel = Arc::new(FakeChannel::with_features(id, features));
+        manager
+            .register_channel(ChannelConfig::new("fake", id, "account"), channel.clone())
+            .await
+            .unwrap();
+        channel
+    }
+
+    fn fast_retries(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            multiplier: 2.0,
+        }
+    }
 
     #[tokio::test]
     async fn test_manager_creation() {
@@ -562,5 +1490,430 @@ mod tests {
         assert!(!status.running);
         assert_eq!(status.channels_total, 0);
         assert_eq!(status.queue_pending, 0);
+        assert_eq!(status.metrics, ChannelMetricsSnapshot::default());
+    }
+
+    #[tokio::test]
+    async fn test_send_records_metrics() {
+        let manager = manager_with("fake1", false).await;
+
+        for _ in 0..5 {
+            manager.send("fake1", outbound("hello")).await.unwrap();
+        }
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 5);
+        assert_eq!(metrics.bytes_sent, 25);
+        assert_eq!(metrics.send_failures, 0);
+        assert_eq!(metrics.latency_samples, 5);
+        assert!(metrics.send_latency_p50_ms.unwrap() >= 2);
+        assert!(metrics.send_latency_p95_ms.is_some());
+
+        let status = manager.status().await;
+        assert_eq!(status.metrics.messages_sent, 5);
+    }
+
+    #[tokio::test]
+    async fn test_send_failure_records_metrics() {
+        let manager = manager_with("fake1", true).await;
+
+        for _ in 0..3 {
+            assert!(manager.send("fake1", outbound("hello")).await.is_err());
+        }
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 0);
+        assert_eq!(metrics.send_failures, 3);
+        assert_eq!(metrics.bytes_sent, 0);
+        assert_eq!(metrics.latency_samples, 3);
+        assert_eq!(manager.metrics().send_failures, 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_splits_long_messages() {
+        let manager = manager_with("fake1", false).await;
+        let text = "hello world\n".repeat(1000);
+
+        let result = manager.send("fake1", outbound(&text)).await.unwrap();
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 3);
+        assert!(metrics.bytes_sent <= text.len() as u64);
+        assert_eq!(result.metadata["chunk_message_ids"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_idempotency_key_delivers_once() {
+        let manager = manager_with("fake1", false).await;
+        let message = OutboundMessage {
+            idempotency_key: Some("reply-1".to_string()),
+            ..outbound("hello")
+        };
+
+        let first = manager.send("fake1", message.clone()).await.unwrap();
+        let second = manager.send("fake1", message).await.unwrap();
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 1);
+        assert_eq!(first.message_id, second.message_id);
+        assert_eq!(first.timestamp, second.timestamp);
+
+        // Unkeyed and differently keyed messages are still sent.
+        manager.send("fake1", outbound("hello")).await.unwrap();
+        let other = OutboundMessage {
+            idempotency_key: Some("reply-2".to_string()),
+            ..outbound("hello")
+        };
+        manager.send("fake1", other).await.unwrap();
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 3);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_sends_with_same_key_deliver_once() {
+        let manager = Arc::new(manager_with("fake1", false).await);
+        let message = OutboundMessage {
+            idempotency_key: Some("reply-1".to_string()),
+            ..outbound("hello")
+        };
+
+        let sends: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                let message = message.clone();
+                tokio::spawn(async move { manager.send("fake1", message).await.unwrap() })
+            })
+            .collect();
+        let mut timestamps = Vec::new();
+        for send in sends {
+            timestamps.push(send.await.unwrap().timestamp);
+        }
+
+        assert!(timestamps.windows(2).all(|w| w[0] == w[1]));
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_keyed_send_can_be_retried() {
+        let manager = flaky_manager("fake1", 1, RetryConfig::disabled()).await;
+        let message = OutboundMessage {
+            idempotency_key: Some("reply-1".to_string()),
+            ..outbound("hello")
+        };
+
+        assert!(manager.send("fake1", message.clone()).await.is_err());
+        assert!(manager.send("fake1", message).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_to_records_metrics() {
+        let manager = manager_with("fake1", false).await;
+
+        manager
+            .send_to("fake", MessageTarget::new("chat1"), "hey")
+            .await
+            .unwrap();
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 1);
+        assert_eq!(metrics.bytes_sent, 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_transient_failures() {
+        let manager = flaky_manager("fake1", 2, fast_retries(3)).await;
+
+        let sent = manager.send("fake1", outbound("hello")).await.unwrap();
+        assert_eq!(sent.message_id, "msg");
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 1);
+        assert_eq!(metrics.send_failures, 2);
+
+        let status = manager.status().await;
+        assert_eq!(status.retry_queued, 0);
+        assert_eq!(status.retrying, 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_after_retries_exhausted() {
+        let manager = flaky_manager("fake1", 5, fast_retries(2)).await;
+
+        let err = manager.send("fake1", outbound("hello")).await.unwrap_err();
+        assert!(matches!(err, ChannelError::Timeout));
+
+        let metrics = manager.channel_metrics("fake1").await.unwrap();
+        assert_eq!(metrics.messages_sent, 0);
+        assert_eq!(metrics.send_failures, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retries_disabled() {
+        let manager = flaky_manager("fake1", 1, RetryConfig::disabled()).await;
+
+        assert!(manager.send("fake1", outbound("hello")).await.is_err());
+        assert!(manager.send("fake1", outbound("hello")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_queued_retries() {
+        let retry_config = RetryConfig {
+            initial_delay: Duration::from_millis(200),
+            max_delay: Duration::from_millis(200),
+            ..fast_retries(1)
+        };
+        let manager = Arc::new(flaky_manager("fake1", 1, retry_config).await);
+
+        let sender = manager.clone();
+        let send = tokio::spawn(async move { sender.send("fake1", outbound("hello")).await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let status = manager.status().await;
+        assert_eq!(status.retry_queued, 1);
+        assert_eq!(status.retrying, 0);
+
+        send.await.unwrap().unwrap();
+        assert_eq!(manager.status().await.retry_queued, 0);
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        let config = RetryConfig {
+            max_retries: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+        };
+        let io = ChannelError::Io(std::io::Error::other("reset"));
+
+        assert_eq!(config.delay_for(1, &io), Duration::from_millis(100));
+        assert_eq!(config.delay_for(3, &io), Duration::from_millis(400));
+        assert_eq!(config.delay_for(10, &io), Duration::from_secs(2));
+        assert_eq!(
+            config.delay_for(1, &ChannelError::rate_limit(1)),
+            Duration::from_secs(1)
+        );
+
+        // Huge exponents and nonsensical multipliers don't panic.
+        assert_eq!(config.delay_for(u32::MAX, &io), Duration::from_secs(2));
+        for multiplier in [f64::MAX, f64::NAN, -2.0] {
+            let config = RetryConfig { multiplier, ..config.clone() };
+            assert!(config.delay_for(5000, &io) <= Duration::from_secs(2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_inbound_messages_dropped() {
+        let manager = ChannelManagerBuilder::new()
+            .default_agent(AgentId::new("agent"))
+            .build();
+        let messages = vec![inbound("m1"), inbound("m1"), inbound("m2"), inbound("m2")];
+        let channel = Arc::new(FakeChannel::with_inbound("fake1", messages));
+        manager
+            .register_channel(ChannelConfig::new("fake", "fake1", "account"), channel.clone())
+            .await
+            .unwrap();
+        let handler = Arc::new(RecordingHandler::default());
+        manager.set_message_handler(handler.clone()).await;
+
+        manager.start().await.unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !channel.inbound.lock().unwrap().is_empty() {
+            assert!(Instant::now() < deadline, "messages were not received");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        manager.stop().await.unwrap();
+
+        assert_eq!(*handler.handled.lock().await, ["m1", "m2"]);
+        assert_eq!(manager.metrics().messages_received, 2);
+    }
+
+    #[tokio::test]
+    async fn test_presence_reports_mixed_health() {
+        let manager = ChannelManager::new().with_presence_timeout(Duration::from_millis(100));
+        let channels = [
+            ("up", Some(HealthStatus::Healthy), Duration::ZERO),
+            ("slow", Some(HealthStatus::Degraded), Duration::from_millis(20)),
+            ("broken", None, Duration::ZERO),
+            ("hung", Some(HealthStatus::Healthy), Duration::from_secs(60)),
+        ];
+        for (id, health, delay) in channels {
+            manager
+                .register_channel(
+                    ChannelConfig::new("fake", id, "account"),
+                    Arc::new(FakeChannel::with_health(id, health, delay)),
+                )
+                .await
+                .unwrap();
+        }
+
+        let started = Instant::now();
+        let presence = manager.presence().await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        assert_eq!(presence.len(), 4);
+        assert_eq!(presence["up"].status, HealthStatus::Healthy);
+        assert_eq!(presence["slow"].status, HealthStatus::Degraded);
+        assert_eq!(presence["broken"].status, HealthStatus::Unhealthy);
+        assert!(presence["broken"].error.as_deref().unwrap().contains("probe failed"));
+        assert_eq!(presence["hung"].status, HealthStatus::Unhealthy);
+        assert!(presence["hung"].error.as_deref().unwrap().contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_presence_limits_concurrent_checks() {
+        let manager = ChannelManager::new();
+        let delay = Duration::from_millis(40);
+        for i in 0..(PRESENCE_CONCURRENCY * 2 + 1) {
+            let id = format!("fake{}", i);
+            manager
+                .register_channel(
+                    ChannelConfig::new("fake", &id, "account"),
+                    Arc::new(FakeChannel::with_health(&id, Some(HealthStatus::Healthy), delay)),
+                )
+                .await
+                .unwrap();
+        }
+
+        let started = Instant::now();
+        let presence = manager.presence().await;
+
+        // Seventeen checks, eight at a time, take at least three rounds.
+        assert!(started.elapsed() >= delay * 3);
+        assert_eq!(presence.len(), PRESENCE_CONCURRENCY * 2 + 1);
+        assert!(presence.values().all(|h| h.status == HealthStatus::Healthy));
+    }
+
+    #[tokio::test]
+    async fn test_edit_rejected_when_channel_lacks_edits() {
+        let manager = ChannelManager::new();
+        let whatsapp = register_with_features(&manager, "whatsapp", whatsapp_features()).await;
+        let slack = register_with_features(&manager, "slack", slack_features()).await;
+        let message = MessageRef::new("m1", "chat1");
+
+        let err = manager.edit("whatsapp", &message, "fixed").await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ChannelError::Unsupported { channel, feature: ChannelFeature::Edits }
+                    if channel == "whatsapp"
+            ),
+            "{:?}",
+            err
+        );
+        assert_eq!(err.to_string(), "Channel whatsapp does not support edits");
+        assert!(whatsapp.actions.lock().unwrap().is_empty());
+
+        manager.edit("slack", &message, "fixed").await.unwrap();
+        assert_eq!(*slack.actions.lock().unwrap(), vec!["edit m1 fixed"]);
+
+        let err = manager.delete("whatsapp", &message).await.unwrap_err();
+        assert!(matches!(err, ChannelError::Unsupported { feature: ChannelFeature::Deletes, .. }));
+        let err = manager.edit("missing", &message, "fixed").await.unwrap_err();
+        assert!(matches!(err, ChannelError::NotFound(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_degrade_policy_falls_back_to_replies() {
+        let manager = ChannelManager::new().with_unsupported_policy(UnsupportedPolicy::Degrade);
+        let plain = register_with_features(&manager, "plain", ChannelFeatures::default()).await;
+        let message = MessageRef::new("m1", "chat1");
+
+        manager.react("plain", &message, "👍").await.unwrap();
+        manager.edit("plain", &message, "fixed").await.unwrap();
+        manager.send_typing("plain", &MessageTarget::new("chat1")).await.unwrap();
+        assert_eq!(
+            *plain.actions.lock().unwrap(),
+            vec!["send 👍 re m1", "send fixed re m1"]
+        );
+
+        // Nothing stands in for removing a message or a reaction.
+        let err = manager.delete("plain", &message).await.unwrap_err();
+        assert!(matches!(err, ChannelError::Unsupported { feature: ChannelFeature::Deletes, .. }));
+        let err = manager.unreact("plain", &message, "👍").await.unwrap_err();
+        assert!(matches!(
+            err,
+            ChannelError::Unsupported { feature: ChannelFeature::Reactions, .. }
+        ));
+    }
+
+    async fn register_directory(
+        manager: &ChannelManager,
+        id: &str,
+        directory: Vec<(&str, MessageTarget)>,
+    ) -> Arc<FakeChannel> {
+        let channel = Arc::new(FakeChannel::with_directory(id, directory));
+        manager
+            .register_channel(ChannelConfig::new("fake", id, "account"), channel.clone())
+            .await
+            .unwrap();
+        channel
+    }
+
+    #[tokio::test]
+    async fn test_resolve_target_maps_friendly_identifiers() {
+        let manager = ChannelManager::new();
+        let telegram = register_directory(
+            &manager,
+            "telegram",
+            vec![
+                ("@alice", MessageTarget::new("1001")),
+                ("@ops_team", MessageTarget::with_thread("-1002003", "17")),
+            ],
+        )
+        .await;
+        register_directory(
+            &manager,
+            "whatsapp",
+            vec![("+1 555 010 0199", MessageTarget::new("15550100199"))],
+        )
+        .await;
+
+        let alice = manager.resolve_target("telegram", " @alice ").await.unwrap();
+        assert_eq!(alice.chat_id, "1001");
+        let ops = manager.resolve_target("telegram", "@ops_team").await.unwrap();
+        assert_eq!((ops.chat_id.as_str(), ops.thread_id.as_deref()), ("-1002003", Some("17")));
+        let phone = manager.resolve_target("whatsapp", "+1 555 010 0199").await.unwrap();
+        assert_eq!(phone.chat_id, "15550100199");
+
+        // Repeats are answered from the cache.
+        manager.resolve_target("telegram", "@alice").await.unwrap();
+        assert_eq!(telegram.lookups.load(Ordering::Relaxed), 2);
+
+        // Channels without a directory take identifiers as chat IDs.
+        register_with_features(&manager, "plain", ChannelFeatures::default()).await;
+        let raw = manager.resolve_target("plain", "C024BE91L").await.unwrap();
+        assert_eq!(raw.chat_id, "C024BE91L");
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_identifier_is_an_error() {
+        let manager = ChannelManager::new();
+        let telegram =
+            register_directory(&manager, "telegram", vec![("@alice", MessageTarget::new("1"))])
+                .await;
+
+        let err = manager.resolve_target("telegram", "@nobody").await.unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                ChannelError::UnresolvedTarget { channel, identifier, .. }
+                    if channel == "telegram" && identifier == "@nobody"
+            ),
+            "{:?}",
+            err
+        );
+        assert_eq!(err.to_string(), "Cannot resolve '@nobody' on telegram: no such user");
+
+        // Failures are not cached.
+        manager.resolve_target("telegram", "@nobody").await.unwrap_err();
+        assert_eq!(telegram.lookups.load(Ordering::Relaxed), 2);
+
+        let err = manager.resolve_target("telegram", "  ").await.unwrap_err();
+        assert!(matches!(err, ChannelError::UnresolvedTarget { .. }), "{:?}", err);
+        assert_eq!(telegram.lookups.load(Ordering::Relaxed), 2);
+        let err = manager.resolve_target("missing", "@alice").await.unwrap_err();
+        assert!(matches!(err, ChannelError::NotFound(_)), "{:?}", err);
     }
 }
@@ -0,0 +1,227 @@
+//! Channel throughput and latency metrics.
+//!
+//! Counters are plain atomics and send latency is recorded into a fixed
+//! bucket histogram, so updating metrics on the send/receive path never
+//! takes a lock.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (in milliseconds) of the latency histogram buckets.
+///
+/// Samples above the last bound fall into an overflow bucket whose upper
+/// bound is the largest latency observed so far.
+const LATENCY_BUCKETS_MS: [u64; 14] = [
+    1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000, 2_000, 5_000, 10_000, 30_000,
+];
+
+/// Lock-free histogram of latency samples.
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    /// Sample counts per bucket, plus one overflow bucket.
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+
+    /// Total number of samples.
+    count: AtomicU64,
+
+    /// Largest sample observed, in milliseconds.
+    max_ms: AtomicU64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    /// Create an empty histogram.
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            max_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a latency sample.
+    pub fn record(&self, latency: Duration) {
+        let ms = latency.as_millis().min(u64::MAX as u128) as u64;
+        let index = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max_ms.fetch_max(ms, Ordering::Relaxed);
+    }
+
+    /// Number of recorded samples.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Estimate the given percentile (0.0..=1.0) in milliseconds.
+    ///
+    /// Returns the upper bound of the bucket containing the requested rank,
+    /// capped at the largest observed sample. Returns `None` when empty.
+    pub fn percentile_ms(&self, quantile: f64) -> Option<u64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+
+        let quantile = quantile.clamp(0.0, 1.0);
+        let rank = ((quantile * count as f64).ceil() as u64).max(1);
+        let max_ms = self.max_ms.load(Ordering::Relaxed);
+
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                let bound = LATENCY_BUCKETS_MS.get(index).copied().unwrap_or(max_ms);
+                return Some(bound.min(max_ms));
+            }
+        }
+
+        Some(max_ms)
+    }
+}
+
+/// Metrics for a single channel (or an aggregate of channels).
+#[derive(Debug, Default)]
+pub struct ChannelMetrics {
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+    send_failures: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    send_latency: LatencyHistogram,
+}
+
+impl ChannelMetrics {
+    /// Create a new, zeroed metrics set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a successful send.
+    pub fn record_send(&self, bytes: usize, latency: Duration) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.send_latency.record(latency);
+    }
+
+    /// Record a failed send.
+    pub fn record_send_failure(&self, latency: Duration) {
+        self.send_failures.fetch_add(1, Ordering::Relaxed);
+        self.send_latency.record(latency);
+    }
+
+    /// Record a received message.
+    pub fn record_receive(&self, bytes: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Get the send latency histogram.
+    pub fn send_latency(&self) -> &LatencyHistogram {
+        &self.send_latency
+    }
+
+    /// Take a point-in-time snapshot of the metrics.
+    pub fn snapshot(&self) -> ChannelMetricsSnapshot {
+        ChannelMetricsSnapshot {
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+            send_failures: self.send_failures.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            latency_samples: self.send_latency.count(),
+            send_latency_p50_ms: self.send_latency.percentile_ms(0.50),
+            send_latency_p95_ms: self.send_latency.percentile_ms(0.95),
+        }
+    }
+}
+
+/// Point-in-time view of channel metrics.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMetricsSnapshot {
+    /// Messages sent successfully.
+    pub messages_sent: u64,
+
+    /// Messages received.
+    pub messages_received: u64,
+
+    /// Failed send attempts.
+    pub send_failures: u64,
+
+    /// Bytes of text sent.
+    pub bytes_sent: u64,
+
+    /// Bytes of text received.
+    pub bytes_received: u64,
+
+    /// Number of send latency samples recorded.
+    pub latency_samples: u64,
+
+    /// Median send latency in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_latency_p50_ms: Option<u64>,
+
+    /// 95th percentile send latency in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_latency_p95_ms: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_histogram() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.count(), 0);
+        assert_eq!(histogram.percentile_ms(0.5), None);
+    }
+
+    #[test]
+    fn test_histogram_percentiles() {
+        let histogram = LatencyHistogram::new();
+        for _ in 0..90 {
+            histogram.record(Duration::from_millis(3));
+        }
+        for _ in 0..10 {
+            histogram.record(Duration::from_millis(150));
+        }
+
+        assert_eq!(histogram.count(), 100);
+        assert_eq!(histogram.percentile_ms(0.50), Some(5));
+        assert_eq!(histogram.percentile_ms(0.95), Some(150));
+    }
+
+    #[test]
+    fn test_histogram_overflow_uses_max() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(Duration::from_secs(45));
+        assert_eq!(histogram.percentile_ms(0.95), Some(45_000));
+    }
+
+    #[test]
+    fn test_metrics_snapshot() {
+        let metrics = ChannelMetrics::new();
+        metrics.record_send(10, Duration::from_millis(4));
+        metrics.record_send_failure(Duration::from_millis(8));
+        metrics.record_receive(7);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.messages_sent, 1);
+        assert_eq!(snapshot.send_failures, 1);
+        assert_eq!(snapshot.messages_received, 1);
+        assert_eq!(snapshot.bytes_sent, 10);
+        assert_eq!(snapshot.bytes_received, 7);
+        assert_eq!(snapshot.latency_samples, 2);
+    }
+}
@@ -0,0 +1,238 @@
+//! Persisted offsets for long-polling channels.
+//!
+//! Long-polling APIs such as Telegram's `getUpdates` number their updates
+//! and drop the ones below the offset passed on the next call. Saving the
+//! offset after each processed batch lets a restarted channel carry on
+//! where it stopped: updates handled before the restart are not handled
+//! again, and ones that arrived while it was down are still fetched.
+
+use crate::error::ChannelError;
+use crate::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Storage for polling offsets, keyed by channel instance.
+#[async_trait]
+pub trait OffsetStore: Send + Sync {
+    /// Load the saved offset for `key`, if any.
+    async fn load(&self, key: &str) -> Result<Option<i64>>;
+
+    /// Save the offset for `key`.
+    async fn save(&self, key: &str, offset: i64) -> Result<()>;
+}
+
+/// Offsets kept in memory only, lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryOffsetStore {
+    offsets: Mutex<HashMap<String, i64>>,
+}
+
+#[async_trait]
+impl OffsetStore for MemoryOffsetStore {
+    async fn load(&self, key: &str) -> Result<Option<i64>> {
+        Ok(self.offsets.lock().unwrap().get(key).copied())
+    }
+
+    async fn save(&self, key: &str, offset: i64) -> Result<()> {
+        self.offsets.lock().unwrap().insert(key.to_string(), offset);
+        Ok(())
+    }
+}
+
+/// Offsets stored as one small file per key in a directory.
+#[derive(Debug, Clone)]
+pub struct FileOffsetStore {
+    dir: PathBuf,
+}
+
+impl FileOffsetStore {
+    /// Store offsets in `dir`, created on first save.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store offsets in the SmartAssist channels directory.
+    pub fn default_location() -> Result<Self> {
+        let dir = smartassist_core::paths::channels_dir()
+            .map_err(|e| ChannelError::Config(e.to_string()))?;
+        Ok(Self::new(dir))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.offset", name))
+    }
+}
+
+#[async_trait]
+impl OffsetStore for FileOffsetStore {
+    async fn load(&self, key: &str) -> Result<Option<i64>> {
+        let path = self.path(key);
+        let text = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        text.trim().parse().map(Some).map_err(|e| {
+            ChannelError::Internal(format!("Invalid offset in {}: {}", path.display(), e))
+        })
+    }
+
+    async fn save(&self, key: &str, offset: i64) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Write then rename, so a crash never leaves a truncated file.
+        let path = self.path(key);
+        let tmp = path.with_extension("offset.tmp");
+        tokio::fs::write(&tmp, offset.to_string()).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+}
+
+/// Tracks the next update to fetch for one channel instance.
+///
+/// Call [`commit`](Self::commit) with the last update id of a batch once
+/// every update in it has been handled. Updates are handled at least once:
+/// a crash between handling a batch and committing it replays that batch.
+pub struct PollOffset {
+    store: Arc<dyn OffsetStore>,
+    key: String,
+    next: Option<i64>,
+}
+
+impl PollOffset {
+    /// Resume from the offset saved in `store` under `key`.
+    pub async fn resume(store: Arc<dyn OffsetStore>, key: impl Into<String>) -> Result<Self> {
+        let key = key.into();
+        let next = store.load(&key).await?;
+        Ok(Self { store, key, next })
+    }
+
+    /// Offset to request updates from, or `None` to start with the oldest
+    /// update the server still holds.
+    pub fn next(&self) -> Option<i64> {
+        self.next
+    }
+
+    /// Check whether update `id` has not been handled yet.
+    ///
+    /// Servers may redeliver updates that were already committed; those
+    /// should be skipped.
+    pub fn is_pending(&self, id: i64) -> bool {
+        match self.next {
+            Some(next) => id >= next,
+            None => true,
+        }
+    }
+
+    /// Record that every update up to and including `last_id` has been
+    /// handled, and save the new offset.
+    pub async fn commit(&mut self, last_id: i64) -> Result<()> {
+        if !self.is_pending(last_id) {
+            return Ok(());
+        }
+        self.store.save(&self.key, last_id + 1).await?;
+        self.next = Some(last_id + 1);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for PollOffset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PollOffset")
+            .field("key", &self.key)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Server side of a long-polling API: updates at or above the offset.
+    fn poll(queue: &[i64], offset: Option<i64>) -> Vec<i64> {
+        let start = offset.unwrap_or(i64::MIN);
+        queue.iter().copied().filter(|id| *id >= start).collect()
+    }
+
+    /// Handle one batch the way a polling loop does.
+    async fn handle_batch(offset: &mut PollOffset, batch: &[i64], handled: &mut Vec<i64>) {
+        handled.extend(batch.iter().copied().filter(|id| offset.is_pending(*id)));
+        if let Some(last) = batch.last() {
+            offset.commit(*last).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart_resumes_from_persisted_offset() {
+        let tmp = TempDir::new().unwrap();
+        let store: Arc<dyn OffsetStore> = Arc::new(FileOffsetStore::new(tmp.path()));
+        let mut handled = Vec::new();
+
+        let mut queue = vec![10, 11, 12];
+        let mut offset = PollOffset::resume(store.clone(), "telegram:main").await.unwrap();
+        assert_eq!(offset.next(), None);
+        let batch = poll(&queue, offset.next());
+        handle_batch(&mut offset, &batch, &mut handled).await;
+        drop(offset);
+
+        // Updates keep arriving while the channel is down.
+        queue.extend([13, 14]);
+
+        let mut offset = PollOffset::resume(store, "telegram:main").await.unwrap();
+        assert_eq!(offset.next(), Some(13));
+        let batch = poll(&queue, offset.next());
+        assert_eq!(batch, vec![13, 14]);
+        handle_batch(&mut offset, &batch, &mut handled).await;
+
+        assert_eq!(handled, vec![10, 11, 12, 13, 14]);
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_updates_are_skipped() {
+        let store: Arc<dyn OffsetStore> = Arc::new(MemoryOffsetStore::default());
+        let mut offset = PollOffset::resume(store.clone(), "bot").await.unwrap();
+        let mut handled = Vec::new();
+
+        handle_batch(&mut offset, &[5, 6], &mut handled).await;
+        // A server that ignored the offset sends 6 again along with new ones.
+        handle_batch(&mut offset, &[6, 7, 9], &mut handled).await;
+        // An old batch never moves the offset back.
+        handle_batch(&mut offset, &[4], &mut handled).await;
+
+        assert_eq!(handled, vec![5, 6, 7, 9]);
+        assert_eq!(offset.next(), Some(10));
+        assert_eq!(store.load("bot").await.unwrap(), Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_file_store_keys_are_separate() {
+        let tmp = TempDir::new().unwrap();
+        let store = FileOffsetStore::new(tmp.path().join("offsets"));
+
+        assert_eq!(store.load("a").await.unwrap(), None);
+        store.save("a", 42).await.unwrap();
+        store.save("b/../c", 7).await.unwrap();
+        store.save("a", 43).await.unwrap();
+
+        assert_eq!(store.load("a").await.unwrap(), Some(43));
+        assert_eq!(store.load("b/../c").await.unwrap(), Some(7));
+        assert!(tmp.path().join("offsets").join("b____c.offset").exists());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_rejects_corrupt_offset() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("bot.offset"), "not a number").unwrap();
+
+        let err = FileOffsetStore::new(tmp.path()).load("bot").await.unwrap_err();
+        assert!(err.to_string().contains("Invalid offset"), "{}", err);
+    }
+}
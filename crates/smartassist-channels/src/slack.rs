@@ -3,7 +3,8 @@
 //! This module provides a Slack channel adapter that supports:
 //! - Socket Mode for real-time event reception (requires app token)
 //! - Web API for sending messages
-//! - File uploads and downloads
+//! - File uploads and downloads, optionally sending large files in
+//!   resumable chunks
 //! - Thread replies
 //! - Reactions
 
@@ -15,6 +16,9 @@ use crate::traits::{
     Channel, ChannelConfig, ChannelLifecycle, ChannelReceiver, ChannelSender, MessageHandler,
     MessageRef, SendResult,
 };
+use crate::upload::{
+    ChunkedUploader, FileUploadStore, MemoryUploadStore, UploadSession, UploadStore,
+};
 use crate::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -67,6 +71,12 @@ pub struct SlackChannel {
 
     /// Shutdown signal.
     shutdown: Arc<RwLock<Option<tokio::sync::oneshot::Sender<()>>>>,
+
+    /// Sends files larger than one chunk, resuming interrupted uploads.
+    uploader: ChunkedUploader,
+
+    /// Whether files larger than one chunk go through `uploader`.
+    chunked_uploads: bool,
 }
 
 impl std::fmt::Debug for SlackChannel {
@@ -98,16 +108,70 @@ impl SlackChannel {
             message_rx: Arc::new(RwLock::new(rx)),
             handler: Arc::new(RwLock::new(None)),
             shutdown: Arc::new(RwLock::new(None)),
+            uploader: ChunkedUploader::new(Arc::new(MemoryUploadStore::default())),
+            chunked_uploads: false,
         }
     }
 
     /// Create from configuration.
+    ///
+    /// The `chunked_uploads` option turns on chunked uploads, and upload
+    /// sessions are saved under the `upload_dir` option if set, so a large
+    /// upload cut off by a restart resumes where it stopped.
     pub fn from_config(config: ChannelConfig, bot_token: String, app_token: Option<String>) -> Self {
+        let chunked_uploads = config.options.get("chunked_uploads").and_then(|v| v.as_bool());
+        let upload_dir = config.options.get("upload_dir").and_then(|v| v.as_str());
+        let upload_store: Option<Arc<dyn UploadStore>> =
+            upload_dir.map(|dir| Arc::new(FileUploadStore::new(dir)) as _);
         let mut channel = Self::new(bot_token, app_token, config.instance_id);
         channel.workspace_id = config.options.get("workspace_id")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
-        channel
+        if let Some(store) = upload_store {
+            channel = channel.with_upload_store(store);
+        }
+        channel.with_chunked_uploads(chunked_uploads.unwrap_or(false))
+    }
+
+    /// Send files larger than one chunk in pieces.
+    ///
+    /// Off by default: each file then goes to its upload URL in a single
+    /// request, which is all Slack documents. Only turn this on when the
+    /// upload endpoint is known to accept `Content-Range` chunks.
+    pub fn with_chunked_uploads(mut self, enabled: bool) -> Self {
+        self.chunked_uploads = enabled;
+        self
+    }
+
+    /// Save upload sessions in `store`.
+    ///
+    /// With chunked uploads on, the session is saved after each chunk, so
+    /// resending the same file to the same conversation continues an
+    /// interrupted upload. Sessions are kept in memory by default.
+    pub fn with_upload_store(mut self, store: Arc<dyn UploadStore>) -> Self {
+        self.uploader = ChunkedUploader::new(store).with_chunk_size(self.uploader.chunk_size());
+        self
+    }
+
+    /// Set the size above which files are sent in resumable chunks.
+    pub fn with_upload_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.uploader = self.uploader.with_chunk_size(chunk_size);
+        self
+    }
+
+    /// Key identifying an upload of one file to one conversation.
+    ///
+    /// It includes a digest of the content, so a different file with the
+    /// same name and size never resumes another's upload.
+    fn upload_key(&self, channel_id: &SlackChannelId, filename: &str, content: &[u8]) -> String {
+        use sha2::{Digest, Sha256};
+        format!(
+            "slack:{}:{}:{}:{:x}",
+            self.instance_id,
+            channel_id,
+            filename,
+            Sha256::digest(content)
+        )
     }
 
     /// Convert Slack message event to InboundMessage.
@@ -591,34 +655,88 @@ impl ChannelSender for SlackChannel {
                 }
             };
 
-            // Step 1: Get upload URL
-            let upload_url_req = SlackApiFilesGetUploadUrlExternalRequest::new(
-                filename.clone(),
-                content.len(),
-            );
-            let upload_url_resp = session
-                .get_upload_url_external(&upload_url_req)
-                .await
-                .map_err(|e| ChannelError::channel("slack", format!("Failed to get upload URL: {}", e)))?;
+            // A large file an earlier attempt started keeps its upload URL.
+            let key = self.upload_key(&channel_id, &filename, &content);
+            let chunked = self.chunked_uploads && content.len() > self.uploader.chunk_size();
+            let pending = if chunked {
+                self.uploader.pending(&key, content.len() as u64).await?
+            } else {
+                None
+            };
 
-            debug!("Got upload URL for file '{}': file_id={}", filename, upload_url_resp.file_id);
+            let resumed = match pending {
+                Some(pending) => {
+                    debug!(
+                        "Resuming upload of '{}' at byte {} of {}",
+                        filename, pending.offset, pending.size
+                    );
+                    let file_id = pending.file_id.clone().ok_or_else(|| {
+                        let message = format!("Saved upload of '{}' has no file ID", filename);
+                        ChannelError::channel("slack", message)
+                    })?;
+                    // A rejected session has expired; start over with a new URL.
+                    self.uploader
+                        .resume(&key, pending, &content, &content_type)
+                        .await?
+                        .map(|_| SlackFileId::new(file_id))
+                }
+                None => None,
+            };
 
-            // Step 2: Upload file content to the URL
-            let upload_req = SlackApiFilesUploadViaUrlRequest::new(
-                upload_url_resp.upload_url,
-                content,
-                content_type,
-            );
-            session
-                .files_upload_via_url(&upload_req)
-                .await
-                .map_err(|e| ChannelError::channel("slack", format!("Failed to upload file content: {}", e)))?;
+            let file_id = match resumed {
+                Some(file_id) => file_id,
+                None => {
+                    // Step 1: Get upload URL
+                    let upload_url_req = SlackApiFilesGetUploadUrlExternalRequest::new(
+                        filename.clone(),
+                        content.len(),
+                    );
+                    let upload_url_resp = session
+                        .get_upload_url_external(&upload_url_req)
+                        .await
+                        .map_err(|e| {
+                            let message = format!("Failed to get upload URL: {}", e);
+                            ChannelError::channel("slack", message)
+                        })?;
+
+                    debug!(
+                        "Got upload URL for file '{}': file_id={}",
+                        filename, upload_url_resp.file_id
+                    );
+
+                    // Step 2: Upload file content to the URL
+                    if chunked {
+                        let upload = UploadSession::new(
+                            upload_url_resp.upload_url.0.to_string(),
+                            content.len() as u64,
+                        )
+                        .with_file_id(upload_url_resp.file_id.to_string());
+                        self.uploader.upload(&key, upload, &content, &content_type).await?;
+                    } else {
+                        let upload_req = SlackApiFilesUploadViaUrlRequest::new(
+                            upload_url_resp.upload_url,
+                            content,
+                            content_type,
+                        );
+                        session
+                            .files_upload_via_url(&upload_req)
+                            .await
+                            .map_err(|e| {
+                                ChannelError::channel(
+                                    "slack",
+                                    format!("Failed to upload file content: {}", e),
+                                )
+                            })?;
+                    }
+                    upload_url_resp.file_id
+                }
+            };
 
             debug!("Uploaded file content for '{}'", filename);
 
             // Track file for completion
             uploaded_files.push(
-                SlackApiFilesComplete::new(upload_url_resp.file_id)
+                SlackApiFilesComplete::new(file_id)
                     .with_title(filename)
             );
         }
@@ -950,6 +1068,8 @@ impl Clone for SlackChannel {
             message_rx: Arc::new(RwLock::new(rx)),
             handler: self.handler.clone(),
             shutdown: self.shutdown.clone(),
+            uploader: self.uploader.clone(),
+            chunked_uploads: self.chunked_uploads,
         }
     }
 }
@@ -976,4 +1096,14 @@ mod tests {
         assert!(caps.chat_types.contains(&ChatType::Direct));
         assert!(caps.chat_types.contains(&ChatType::Channel));
     }
+
+    #[test]
+    fn test_upload_key_depends_on_content() {
+        let channel = SlackChannel::new("xoxb-test", None, "test_workspace");
+        let target = SlackChannelId::new("C1".to_string());
+        let key = channel.upload_key(&target, "report.bin", b"first");
+        assert_eq!(key, channel.upload_key(&target, "report.bin", b"first"));
+        assert_ne!(key, channel.upload_key(&target, "report.bin", b"other"));
+        assert!(!channel.chunked_uploads);
+    }
 }
@@ -4,6 +4,7 @@
 
 use crate::attachment::{Attachment, AttachmentType};
 use crate::error::ChannelError;
+use crate::offset::{FileOffsetStore, MemoryOffsetStore, OffsetStore, PollOffset};
 use crate::traits::{
     Channel, ChannelConfig, ChannelLifecycle, ChannelReceiver, ChannelSender, MessageHandler,
     MessageRef, SendResult,
@@ -18,10 +19,20 @@ use smartassist_core::types::{
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use teloxide::prelude::*;
-use teloxide::types::{ChatId, InputFile, MediaKind, MessageKind, ParseMode};
+use teloxide::types::{
+    AllowedUpdate, ChatId, InputFile, MediaKind, MessageKind, ParseMode, Recipient, Update,
+    UpdateKind,
+};
+use teloxide::RequestError;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, info, warn};
 
+/// Long-polling timeout for `getUpdates`, in seconds.
+const POLL_TIMEOUT_SECS: u32 = 30;
+
+/// Pause before polling again after a failed `getUpdates`.
+const POLL_RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Telegram channel implementation.
 pub struct TelegramChannel {
     /// Bot instance.
@@ -45,6 +56,12 @@ pub struct TelegramChannel {
 
     /// Shutdown signal.
     shutdown: Arc<RwLock<Option<tokio::sync::oneshot::Sender<()>>>>,
+
+    /// Where the polling offset is saved between runs.
+    offset_store: Arc<dyn OffsetStore>,
+
+    /// Update types requested from Telegram.
+    allowed_updates: Vec<AllowedUpdate>,
 }
 
 impl std::fmt::Debug for TelegramChannel {
@@ -70,12 +87,108 @@ impl TelegramChannel {
             message_rx: Arc::new(RwLock::new(rx)),
             handler: Arc::new(RwLock::new(None)),
             shutdown: Arc::new(RwLock::new(None)),
+            offset_store: Arc::new(MemoryOffsetStore::default()),
+            allowed_updates: vec![AllowedUpdate::Message],
         }
     }
 
     /// Create from configuration.
-    pub fn from_config(config: ChannelConfig, bot_token: String) -> Self {
-        Self::new(bot_token, config.instance_id)
+    ///
+    /// The polling offset is saved under the `offset_dir` option, or the
+    /// SmartAssist channels directory if unset. `allowed_updates` lists the
+    /// update types to receive, e.g. `["message", "edited_message"]`.
+    pub fn from_config(config: ChannelConfig, bot_token: String) -> Result<Self> {
+        let store = match config.options.get("offset_dir").and_then(|v| v.as_str()) {
+            Some(dir) => FileOffsetStore::new(dir),
+            None => FileOffsetStore::default_location()?,
+        };
+        let mut channel =
+            Self::new(bot_token, config.instance_id).with_offset_store(Arc::new(store));
+
+        if let Some(updates) = config.options.get("allowed_updates") {
+            let updates = serde_json::from_value(updates.clone()).map_err(|e| {
+                ChannelError::Config(format!("Invalid Telegram allowed_updates: {}", e))
+            })?;
+            channel = channel.with_allowed_updates(updates);
+        }
+        Ok(channel)
+    }
+
+    /// Save the polling offset in `store`, so a restart neither repeats
+    /// nor skips updates.
+    ///
+    /// Offsets are kept in memory by default.
+    pub fn with_offset_store(mut self, store: Arc<dyn OffsetStore>) -> Self {
+        self.offset_store = store;
+        self
+    }
+
+    /// Set the update types to receive. Only messages by default.
+    pub fn with_allowed_updates(mut self, updates: Vec<AllowedUpdate>) -> Self {
+        self.allowed_updates = updates;
+        self
+    }
+
+    /// Long-poll for updates until `shutdown` fires.
+    ///
+    /// The offset is committed after each batch has been forwarded.
+    async fn poll_updates(
+        self: Arc<Self>,
+        mut offset: PollOffset,
+        tx: mpsc::Sender<InboundMessage>,
+        mut shutdown: tokio::sync::oneshot::Receiver<()>,
+    ) {
+        loop {
+            let mut request = self
+                .bot
+                .get_updates()
+                .timeout(POLL_TIMEOUT_SECS)
+                .allowed_updates(self.allowed_updates.clone());
+            if let Some(next) = offset.next().and_then(|next| i32::try_from(next).ok()) {
+                request = request.offset(next);
+            }
+
+            let result = tokio::select! {
+                _ = &mut shutdown => break,
+                result = request.send() => result,
+            };
+            let updates = match result {
+                Ok(updates) => updates,
+                Err(e) => {
+                    warn!("Telegram getUpdates failed for {}: {}", self.instance_id, e);
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = tokio::time::sleep(POLL_RETRY_DELAY) => continue,
+                    }
+                }
+            };
+
+            let Some(last_id) = updates.iter().map(|update| update.id).max() else {
+                continue;
+            };
+            for update in updates {
+                if !offset.is_pending(update.id.into()) {
+                    continue;
+                }
+                if let Some(inbound) = self.convert_update(update).await {
+                    let _ = tx.send(inbound).await;
+                }
+            }
+            if let Err(e) = offset.commit(last_id.into()).await {
+                warn!("Failed to save Telegram offset for {}: {}", self.instance_id, e);
+            }
+        }
+        debug!("Stopped polling Telegram for {}", self.instance_id);
+    }
+
+    /// Convert an update carrying a message to an InboundMessage.
+    async fn convert_update(&self, update: Update) -> Option<InboundMessage> {
+        match update.kind {
+            UpdateKind::Message(msg) | UpdateKind::EditedMessage(msg) => {
+                self.convert_message(&msg).await
+            }
+            _ => None,
+        }
     }
 
     /// Convert Telegram message to InboundMessage.
@@ -346,6 +459,240 @@ impl Channel for TelegramChannel {
     }
 }
 
+impl TelegramChannel {
+    /// Send a text message, already formatted for `parse_mode`.
+    async fn send_text(
+        &self,
+        chat_id: ChatId,
+        text: &str,
+        parse_mode: Option<ParseMode>,
+        reply_to: Option<&str>,
+    ) -> std::result::Result<teloxide::types::Message, RequestError> {
+        let mut request = self.bot.send_message(chat_id, text);
+
+        if let Some(parse_mode) = parse_mode {
+            request = request.parse_mode(parse_mode);
+        }
+
+        // Set reply
+        if let Some(id) = reply_to.and_then(|id| id.parse::<i32>().ok()) {
+            request = request.reply_to_message_id(teloxide::types::MessageId(id));
+        }
+
+        request.await
+    }
+}
+
+/// Prepare text for the Bot API in the given mode.
+///
+/// Returns the text to send and the Telegram parse mode to send it with;
+/// plain text is sent without one.
+fn format_text(text: &str, mode: Option<CoreParseMode>) -> (String, Option<ParseMode>) {
+    match mode {
+        Some(CoreParseMode::MarkdownV2) => (to_markdown_v2(text), Some(ParseMode::MarkdownV2)),
+        Some(CoreParseMode::Html) => (to_telegram_html(text), Some(ParseMode::Html)),
+        Some(CoreParseMode::Plain) | None => (text.to_string(), None),
+    }
+}
+
+/// Whether `name` is a valid Telegram username: 5 to 32 letters, digits
+/// or underscores.
+fn is_username(name: &str) -> bool {
+    (5..=32).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether the Bot API refused a message because its markup did not parse.
+fn is_parse_error(err: &RequestError) -> bool {
+    // The API appends details ("... can't parse entities: Can't find end of
+    // the entity starting at byte offset 5"), which teloxide reports as an
+    // unknown error rather than `CantParseEntities`.
+    err.to_string().contains("can't parse entities")
+}
+
+/// Characters MarkdownV2 reserves outside code.
+const MARKDOWN_V2_RESERVED: &str = "_*[]()~`>#+-=|{}.!\\";
+
+/// Escape text so MarkdownV2 displays it literally.
+pub fn escape_markdown_v2(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_V2_RESERVED.contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escape text inside a MarkdownV2 code span or block.
+fn escape_markdown_v2_code(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`")
+}
+
+/// Convert Markdown to Telegram MarkdownV2.
+///
+/// Bold, italic, strikethrough, inline code, code blocks and links are kept
+/// as formatting; every other reserved character is escaped so the API
+/// accepts the message. A backslash before a reserved character keeps it
+/// literal, as in Markdown.
+pub fn to_markdown_v2(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 8);
+    let mut rest = text;
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((inner, after)) = delimited(rest, "```") {
+            out.push_str("```");
+            out.push_str(&escape_markdown_v2_code(inner));
+            out.push_str("```");
+            rest = after;
+        } else if let Some((inner, after)) = delimited(rest, "`") {
+            out.push('`');
+            out.push_str(&escape_markdown_v2_code(inner));
+            out.push('`');
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            out.push('[');
+            out.push_str(&to_markdown_v2(label));
+            out.push_str("](");
+            out.push_str(&url.replace('\\', "\\\\").replace(')', "\\)"));
+            out.push(')');
+            rest = after;
+        } else if let Some((inner, after, marker)) = emphasis(rest, prev) {
+            out.push_str(marker);
+            out.push_str(&to_markdown_v2(inner));
+            out.push_str(marker);
+            rest = after;
+        } else {
+            let mut chars = rest.chars();
+            chars.next();
+            match (c, chars.clone().next()) {
+                ('\\', Some(next)) if MARKDOWN_V2_RESERVED.contains(next) => {
+                    out.push('\\');
+                    out.push(next);
+                    chars.next();
+                }
+                _ => out.push_str(&escape_markdown_v2(c.encode_utf8(&mut [0; 4]))),
+            }
+            rest = chars.as_str();
+            prev = Some(c);
+            continue;
+        }
+        prev = None;
+    }
+    out
+}
+
+/// Split `text` starting with `marker` into the text up to the next
+/// `marker` and what follows it. Empty spans do not count.
+fn delimited<'a>(text: &'a str, marker: &str) -> Option<(&'a str, &'a str)> {
+    let body = text.strip_prefix(marker)?;
+    let end = body.find(marker)?;
+    (end > 0).then(|| (&body[..end], &body[end + marker.len()..]))
+}
+
+/// Parse a `[label](url)` link at the start of `text`.
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let body = text.strip_prefix('[')?;
+    let label_end = body.find("](")?;
+    let label = &body[..label_end];
+    let target = &body[label_end + 2..];
+    let url_end = target.find(')')?;
+    let url = &target[..url_end];
+    let valid = !label.is_empty() && !label.contains('\n') && !url.is_empty();
+    (valid && !url.contains(char::is_whitespace)).then(|| (label, url, &target[url_end + 1..]))
+}
+
+/// Parse emphasis at the start of `text`, returning the inner text, what
+/// follows, and the MarkdownV2 marker to wrap it in.
+///
+/// `prev` is the character before `text`; `_` inside a word (`snake_case`)
+/// is not emphasis.
+fn emphasis(text: &str, prev: Option<char>) -> Option<(&str, &str, &'static str)> {
+    const MARKERS: [(&str, &str); 5] =
+        [("**", "*"), ("__", "*"), ("~~", "~"), ("*", "_"), ("_", "_")];
+
+    for (marker, replacement) in MARKERS {
+        if marker.starts_with('_') && prev.is_some_and(char::is_alphanumeric) {
+            continue;
+        }
+        let Some((inner, after)) = delimited(text, marker) else {
+            continue;
+        };
+        let padded = inner.starts_with(char::is_whitespace) || inner.ends_with(char::is_whitespace);
+        let intraword = marker.starts_with('_') && after.starts_with(char::is_alphanumeric);
+        if !padded && !intraword && !inner.contains('\n') {
+            return Some((inner, after, replacement));
+        }
+    }
+    None
+}
+
+/// Tags the Bot API accepts in HTML mode.
+const TELEGRAM_HTML_TAGS: &[&str] = &[
+    "a", "b", "strong", "i", "em", "u", "ins", "s", "strike", "del", "span", "tg-spoiler",
+    "tg-emoji", "code", "pre", "blockquote",
+];
+
+/// Escape text so HTML mode displays it literally.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Prepare HTML for Telegram.
+///
+/// Supported tags and entities are kept; anything else that would break
+/// the parser, such as `a < b`, unsupported tags or a bare `&`, is escaped.
+pub fn to_telegram_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let keep = match c {
+            '<' => rest.find('>').map(|end| end + 1).filter(|&len| is_supported_tag(&rest[..len])),
+            '&' => rest.find(';').map(|end| end + 1).filter(|&len| is_entity(&rest[..len])),
+            _ => None,
+        };
+        match (keep, c) {
+            (Some(len), _) => {
+                out.push_str(&rest[..len]);
+                rest = &rest[len..];
+                continue;
+            }
+            (None, '<') => out.push_str("&lt;"),
+            (None, '>') => out.push_str("&gt;"),
+            (None, '&') => out.push_str("&amp;"),
+            (None, c) => out.push(c),
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Whether `tag` (`<...>`) opens or closes a tag Telegram supports.
+fn is_supported_tag(tag: &str) -> bool {
+    let body = tag[1..tag.len() - 1].strip_prefix('/').unwrap_or(&tag[1..tag.len() - 1]);
+    let name = body
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()
+        .unwrap_or_default();
+    !body.contains('<') && TELEGRAM_HTML_TAGS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// Whether `entity` (`&...;`) is a named or numeric character reference.
+fn is_entity(entity: &str) -> bool {
+    let body = &entity[1..entity.len() - 1];
+    match body.strip_prefix('#') {
+        Some(num) => match num.strip_prefix(['x', 'X']) {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !num.is_empty() && num.chars().all(|c| c.is_ascii_digit()),
+        },
+        None => !body.is_empty() && body.chars().all(|c| c.is_ascii_alphanumeric()),
+    }
+}
+
 #[async_trait]
 impl ChannelSender for TelegramChannel {
     async fn send(&self, message: OutboundMessage) -> Result<SendResult> {
@@ -357,27 +704,19 @@ impl ChannelSender for TelegramChannel {
                 .map_err(|e| ChannelError::InvalidMessage(e.to_string()))?,
         );
 
-        let mut request = self.bot.send_message(chat_id, &message.text);
+        let reply_to = message.reply_to.as_deref();
+        let (text, parse_mode) = format_text(&message.text, message.options.parse_mode);
 
-        // Set parse mode
-        if let Some(ref parse_mode) = message.options.parse_mode {
-            match parse_mode {
-                CoreParseMode::Html => request = request.parse_mode(ParseMode::Html),
-                CoreParseMode::Markdown => request = request.parse_mode(ParseMode::MarkdownV2),
-                CoreParseMode::Plain => {} // No parse mode for plain text
+        let sent = match self.send_text(chat_id, &text, parse_mode, reply_to).await {
+            // Telegram rejects the whole message if the markup is malformed;
+            // deliver the original text rather than nothing.
+            Err(e) if parse_mode.is_some() && is_parse_error(&e) => {
+                warn!("Telegram rejected formatted message, sending as plain text: {}", e);
+                self.send_text(chat_id, &message.text, None, reply_to).await
             }
-        }
-
-        // Set reply
-        if let Some(ref reply_to) = message.reply_to {
-            if let Ok(id) = reply_to.parse::<i32>() {
-                request = request.reply_to_message_id(teloxide::types::MessageId(id));
-            }
-        }
-
-        let sent = request
-            .await
-            .map_err(|e| ChannelError::channel("telegram", e.to_string()))?;
+            result => result,
+        };
+        let sent = sent.map_err(|e| ChannelError::channel("telegram", e.to_string()))?;
 
         Ok(SendResult::new(sent.id.to_string()))
     }
@@ -550,6 +889,29 @@ impl ChannelSender for TelegramChannel {
         Ok(())
     }
 
+    /// Resolve a numeric chat ID as is, and `@username` (or a bare
+    /// username) of a public group or channel with `getChat`.
+    async fn resolve_target(&self, identifier: &str) -> Result<MessageTarget> {
+        if identifier.parse::<i64>().is_ok() {
+            return Ok(MessageTarget::new(identifier));
+        }
+        let username = identifier.strip_prefix('@').unwrap_or(identifier);
+        if !is_username(username) {
+            return Err(ChannelError::unresolved(
+                &self.instance_id,
+                identifier,
+                "expected a chat ID or @username",
+            ));
+        }
+
+        let chat = self
+            .bot
+            .get_chat(Recipient::ChannelUsername(format!("@{}", username)))
+            .await
+            .map_err(|e| ChannelError::unresolved(&self.instance_id, identifier, e.to_string()))?;
+        Ok(MessageTarget::new(chat.id.0.to_string()))
+    }
+
     fn max_message_length(&self) -> usize {
         4096
     }
@@ -558,33 +920,17 @@ impl ChannelSender for TelegramChannel {
 #[async_trait]
 impl ChannelReceiver for TelegramChannel {
     async fn start_receiving(&self) -> Result<()> {
-        let (shutdown_tx, _shutdown_rx) = tokio::sync::oneshot::channel();
+        let offset = PollOffset::resume(self.offset_store.clone(), self.instance_id.clone()).await?;
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
 
         {
             let mut shutdown = self.shutdown.write().await;
             *shutdown = Some(shutdown_tx);
         }
 
-        let bot = self.bot.clone();
         let tx = self.message_tx.clone();
         let channel = Arc::new(self.clone());
-
-        tokio::spawn(async move {
-            let handler = Update::filter_message().endpoint(
-                move |_bot: Bot, msg: teloxide::types::Message| {
-                    let tx = tx.clone();
-                    let channel = channel.clone();
-                    async move {
-                        if let Some(inbound) = channel.convert_message(&msg).await {
-                            let _ = tx.send(inbound).await;
-                        }
-                        respond(())
-                    }
-                },
-            );
-
-            Dispatcher::builder(bot, handler).build().dispatch().await;
-        });
+        tokio::spawn(channel.poll_updates(offset, tx, shutdown_rx));
 
         info!("Started receiving messages for Telegram bot: {}", self.instance_id);
         Ok(())
@@ -692,6 +1038,8 @@ impl Clone for TelegramChannel {
             message_rx: Arc::new(RwLock::new(rx)),
             handler: self.handler.clone(),
             shutdown: self.shutdown.clone(),
+            offset_store: self.offset_store.clone(),
+            allowed_updates: self.allowed_updates.clone(),
         }
     }
 }
@@ -707,6 +1055,73 @@ mod tests {
         assert_eq!(channel.instance_id(), "test_bot");
     }
 
+    #[test]
+    fn test_from_config_options() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = ChannelConfig::new("telegram", "main", "main")
+            .with_option("offset_dir", serde_json::json!(tmp.path()))
+            .with_option("allowed_updates", serde_json::json!(["message", "edited_message"]));
+
+        let channel = TelegramChannel::from_config(config, "123:abc".to_string()).unwrap();
+        assert_eq!(
+            channel.allowed_updates,
+            vec![AllowedUpdate::Message, AllowedUpdate::EditedMessage]
+        );
+
+        let config = ChannelConfig::new("telegram", "main", "main")
+            .with_option("offset_dir", serde_json::json!(tmp.path()))
+            .with_option("allowed_updates", serde_json::json!(["messages"]));
+        let err = TelegramChannel::from_config(config, "123:abc".to_string()).unwrap_err();
+        assert!(err.to_string().contains("allowed_updates"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_convert_update_kinds() {
+        let channel = TelegramChannel::new("123:abc", "test");
+        // Parsed from text: teloxide can't read an `UpdateKind` from a `Value`.
+        let update = |kind: &str, body: serde_json::Value| -> Update {
+            let json = serde_json::json!({ "update_id": 7, (kind): body });
+            serde_json::from_str(&json.to_string()).unwrap()
+        };
+        let message = serde_json::json!({
+            "message_id": 1,
+            "date": 1_700_000_000,
+            "chat": {"id": 42, "type": "private", "first_name": "Ada"},
+            "from": {"id": 42, "is_bot": false, "first_name": "Ada"},
+            "text": "hello"
+        });
+
+        let inbound = channel.convert_update(update("message", message.clone())).await.unwrap();
+        assert_eq!(inbound.text, "hello");
+        let edited = {
+            let mut edited = message;
+            edited["edit_date"] = serde_json::json!(1_700_000_100);
+            edited
+        };
+        assert!(channel.convert_update(update("edited_message", edited)).await.is_some());
+
+        let poll_answer = serde_json::json!({
+            "poll_id": "p",
+            "user": {"id": 42, "is_bot": false, "first_name": "Ada"},
+            "option_ids": [0]
+        });
+        assert!(channel.convert_update(update("poll_answer", poll_answer)).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_target_without_lookup() {
+        let channel = TelegramChannel::new("test_token", "tg");
+
+        let target = channel.resolve_target("-1001234567890").await.unwrap();
+        assert_eq!(target.chat_id, "-1001234567890");
+
+        for bad in ["@ab", "@has space", "not-a-name"] {
+            let err = channel.resolve_target(bad).await.unwrap_err();
+            assert!(matches!(err, ChannelError::UnresolvedTarget { .. }), "{}: {:?}", bad, err);
+        }
+        assert!(is_username("ops_team"));
+    }
+
     #[test]
     fn test_capabilities() {
         let channel = TelegramChannel::new("test_token", "test_bot");
@@ -719,6 +1134,78 @@ mod tests {
         assert!(caps.chat_types.contains(&ChatType::Group));
     }
 
+    #[test]
+    fn test_escape_markdown_v2() {
+        assert_eq!(
+            escape_markdown_v2("a_b*c[d](e)"),
+            "a\\_b\\*c\\[d\\]\\(e\\)"
+        );
+        assert_eq!(escape_markdown_v2("1.5 + 2 = 3.5!"), "1\\.5 \\+ 2 \\= 3\\.5\\!");
+        assert_eq!(escape_markdown_v2("C:\\dir"), "C:\\\\dir");
+        assert_eq!(escape_markdown_v2("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_markdown_v2_keeps_formatting() {
+        assert_eq!(
+            to_markdown_v2("**Done.** See _notes_ (v1.2) or [the docs](https://x.io/a_b)"),
+            "*Done\\.* See _notes_ \\(v1\\.2\\) or [the docs](https://x.io/a_b)"
+        );
+        assert_eq!(to_markdown_v2("~~old~~ *new*"), "~old~ _new_");
+        assert_eq!(
+            to_markdown_v2("run `a_b(1)` then\n```\nlet x = \"`\";\n```"),
+            "run `a_b(1)` then\n```\nlet x = \"\\`\";\n```"
+        );
+    }
+
+    #[test]
+    fn test_markdown_v2_escapes_stray_markers() {
+        assert_eq!(to_markdown_v2("my_var_name"), "my\\_var\\_name");
+        assert_eq!(to_markdown_v2("2 * 3 * 4"), "2 \\* 3 \\* 4");
+        assert_eq!(to_markdown_v2("[1] (a) [x]()"), "\\[1\\] \\(a\\) \\[x\\]\\(\\)");
+        assert_eq!(to_markdown_v2("a \\*b\\* c"), "a \\*b\\* c");
+        assert_eq!(to_markdown_v2("- item #1"), "\\- item \\#1");
+    }
+
+    #[test]
+    fn test_telegram_html() {
+        assert_eq!(
+            to_telegram_html("<b>5 < 6</b> & <i>ok</i> &amp; <a href=\"https://x.io\">x</a>"),
+            "<b>5 &lt; 6</b> &amp; <i>ok</i> &amp; <a href=\"https://x.io\">x</a>"
+        );
+        assert_eq!(
+            to_telegram_html("<script>alert(1)</script> <br/>"),
+            "&lt;script&gt;alert(1)&lt;/script&gt; &lt;br/&gt;"
+        );
+        assert_eq!(to_telegram_html("&#39; &#x27; &copy; & ;"), "&#39; &#x27; &copy; &amp; ;");
+        assert_eq!(escape_html("<b>&</b>"), "&lt;b&gt;&amp;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_format_text_modes() {
+        let (text, mode) = format_text("a.b", Some(CoreParseMode::MarkdownV2));
+        assert_eq!((text.as_str(), mode), ("a\\.b", Some(ParseMode::MarkdownV2)));
+
+        let (text, mode) = format_text("a<b", Some(CoreParseMode::Html));
+        assert_eq!((text.as_str(), mode), ("a&lt;b", Some(ParseMode::Html)));
+
+        for plain in [Some(CoreParseMode::Plain), None] {
+            assert_eq!(format_text("*a.b*", plain), ("*a.b*".to_string(), None));
+        }
+    }
+
+    #[test]
+    fn test_is_parse_error() {
+        use teloxide::ApiError;
+
+        let detailed = ApiError::Unknown(
+            "Bad Request: can't parse entities: Can't find end of the entity".to_string(),
+        );
+        assert!(is_parse_error(&RequestError::Api(detailed)));
+        assert!(is_parse_error(&RequestError::Api(ApiError::CantParseEntities)));
+        assert!(!is_parse_error(&RequestError::Api(ApiError::MessageTextIsEmpty)));
+    }
+
     #[test]
     fn test_reaction_type_serialization() {
         // Test emoji reaction
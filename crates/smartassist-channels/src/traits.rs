@@ -61,6 +61,34 @@ pub enum ChannelFeature {
     Polls,
 }
 
+impl ChannelFeature {
+    /// Human-readable name, e.g. `typing indicators`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Images => "images",
+            Self::Audio => "audio",
+            Self::Video => "video",
+            Self::Files => "files",
+            Self::Stickers => "stickers",
+            Self::VoiceNotes => "voice notes",
+            Self::Reactions => "reactions",
+            Self::Threads => "threads",
+            Self::Edits => "edits",
+            Self::Deletes => "deletes",
+            Self::TypingIndicators => "typing indicators",
+            Self::ReadReceipts => "read receipts",
+            Self::Mentions => "mentions",
+            Self::Polls => "polls",
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Trait for sending messages through a channel.
 #[async_trait]
 pub trait ChannelSender: Send + Sync {
@@ -93,6 +121,14 @@ pub trait ChannelSender: Send + Sync {
     /// Send a typing indicator.
     async fn send_typing(&self, target: &MessageTarget) -> Result<()>;
 
+    /// Translate a human-friendly identifier, such as a username, phone
+    /// number or channel name, into the target to send to.
+    ///
+    /// The default treats the identifier as a raw chat ID.
+    async fn resolve_target(&self, identifier: &str) -> Result<MessageTarget> {
+        Ok(MessageTarget::new(identifier))
+    }
+
     /// Get the maximum message length for this channel.
     fn max_message_length(&self) -> usize {
         4096 // Default
@@ -0,0 +1,564 @@
+//! Resumable chunked uploads for large attachments.
+//!
+//! Some channel APIs hand out an upload URL that takes a file in pieces,
+//! each sent with a `Content-Range` header, as Slack's external upload
+//! flow does. [`ChunkedUploader`] sends files this way and saves the upload
+//! session after every accepted chunk, so an upload cut off by a dropped
+//! connection or a restart continues from the last accepted byte instead of
+//! starting over. A server that answers with a `Range` header is taken at
+//! its word about how much it holds, and one that rejects a chunk with a
+//! client error has dropped the upload, so its session is discarded.
+
+use crate::error::ChannelError;
+use crate::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::debug;
+
+/// Bytes sent per request by default.
+pub const DEFAULT_UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Time allowed for sending one chunk and reading the reply.
+pub const UPLOAD_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// An upload in progress.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadSession {
+    /// URL the content is sent to.
+    pub url: String,
+
+    /// File ID the channel assigned to the upload, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+
+    /// Total size of the file in bytes.
+    pub size: u64,
+
+    /// Bytes the server has accepted so far.
+    pub offset: u64,
+}
+
+impl UploadSession {
+    /// Start an upload of `size` bytes to `url`.
+    pub fn new(url: impl Into<String>, size: u64) -> Self {
+        Self {
+            url: url.into(),
+            file_id: None,
+            size,
+            offset: 0,
+        }
+    }
+
+    /// Set the file ID.
+    pub fn with_file_id(mut self, file_id: impl Into<String>) -> Self {
+        self.file_id = Some(file_id.into());
+        self
+    }
+
+    /// Check whether every byte has been accepted.
+    pub fn is_complete(&self) -> bool {
+        self.offset >= self.size
+    }
+}
+
+/// Storage for upload sessions, keyed by upload.
+#[async_trait]
+pub trait UploadStore: Send + Sync {
+    /// Load the saved session for `key`, if any.
+    async fn load(&self, key: &str) -> Result<Option<UploadSession>>;
+
+    /// Save the session for `key`.
+    async fn save(&self, key: &str, session: &UploadSession) -> Result<()>;
+
+    /// Forget the session for `key`.
+    async fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// Upload sessions kept in memory only, lost on restart.
+#[derive(Debug, Default)]
+pub struct MemoryUploadStore {
+    sessions: Mutex<HashMap<String, UploadSession>>,
+}
+
+#[async_trait]
+impl UploadStore for MemoryUploadStore {
+    async fn load(&self, key: &str) -> Result<Option<UploadSession>> {
+        Ok(self.sessions.lock().unwrap().get(key).cloned())
+    }
+
+    async fn save(&self, key: &str, session: &UploadSession) -> Result<()> {
+        self.sessions.lock().unwrap().insert(key.to_string(), session.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        self.sessions.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Upload sessions stored as one small JSON file per key in a directory.
+#[derive(Debug, Clone)]
+pub struct FileUploadStore {
+    dir: PathBuf,
+}
+
+impl FileUploadStore {
+    /// Store sessions in `dir`, created on first save.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Store sessions in the `uploads` folder of the SmartAssist channels
+    /// directory.
+    pub fn default_location() -> Result<Self> {
+        let dir = smartassist_core::paths::channels_dir()
+            .map_err(|e| ChannelError::Config(e.to_string()))?;
+        Ok(Self::new(dir.join("uploads")))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.upload", name))
+    }
+}
+
+#[async_trait]
+impl UploadStore for FileUploadStore {
+    async fn load(&self, key: &str) -> Result<Option<UploadSession>> {
+        let path = self.path(key);
+        let text = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&text).map(Some).map_err(|e| {
+            ChannelError::Internal(format!("Invalid upload session in {}: {}", path.display(), e))
+        })
+    }
+
+    async fn save(&self, key: &str, session: &UploadSession) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Write then rename, so a crash never leaves a truncated file.
+        let path = self.path(key);
+        let tmp = path.with_extension("upload.tmp");
+        tokio::fs::write(&tmp, serde_json::to_vec(session)?).await?;
+        tokio::fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Sends files in chunks, saving progress so interrupted uploads resume.
+///
+/// Each upload is identified by a key chosen by the caller. It should stay
+/// the same when the same file is sent to the same place again, so a retry
+/// finds the session an earlier attempt left behind.
+#[derive(Clone)]
+pub struct ChunkedUploader {
+    client: reqwest::Client,
+    store: Arc<dyn UploadStore>,
+    chunk_size: usize,
+}
+
+impl ChunkedUploader {
+    /// Create an uploader saving sessions in `store`.
+    pub fn new(store: Arc<dyn UploadStore>) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(UPLOAD_REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            store,
+            chunk_size: DEFAULT_UPLOAD_CHUNK_SIZE,
+        }
+    }
+
+    /// Set the bytes sent per request.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Set the HTTP client.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Get the bytes sent per request.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Find an unfinished upload of `size` bytes saved under `key`.
+    ///
+    /// A saved session for a different size belongs to another file and is
+    /// discarded.
+    pub async fn pending(&self, key: &str, size: u64) -> Result<Option<UploadSession>> {
+        match self.store.load(key).await? {
+            Some(session) if session.size == size && !session.is_complete() => Ok(Some(session)),
+            Some(_) => {
+                self.store.remove(key).await?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Send `content` from `session.offset` on, one chunk per request.
+    ///
+    /// The session is saved before the first chunk and after each accepted
+    /// one. On failure the saved session records what the server has, and
+    /// calling this again with the session from [`pending`](Self::pending)
+    /// carries on from there. Once the upload is complete the saved session
+    /// is removed and the finished session returned.
+    ///
+    /// A chunk rejected with a 4xx status means the server will not take
+    /// the upload, so the saved session is removed before the error is
+    /// returned.
+    pub async fn upload(
+        &self,
+        key: &str,
+        session: UploadSession,
+        content: &[u8],
+        content_type: &str,
+    ) -> Result<UploadSession> {
+        match self.send(key, session, content, content_type).await? {
+            Sent::Complete(session) => Ok(session),
+            Sent::Rejected(message) => {
+                self.store.remove(key).await?;
+                Err(ChannelError::Delivery(message))
+            }
+        }
+    }
+
+    /// Continue a session from [`pending`](Self::pending).
+    ///
+    /// Like [`upload`](Self::upload), except that a server rejecting the
+    /// session with a 4xx status, as happens once its upload URL expires,
+    /// gives `Ok(None)` after removing the session, so the caller can start
+    /// the upload over with a new URL.
+    pub async fn resume(
+        &self,
+        key: &str,
+        session: UploadSession,
+        content: &[u8],
+        content_type: &str,
+    ) -> Result<Option<UploadSession>> {
+        match self.send(key, session, content, content_type).await? {
+            Sent::Complete(session) => Ok(Some(session)),
+            Sent::Rejected(message) => {
+                debug!("Discarding upload '{}': {}", key, message);
+                self.store.remove(key).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        key: &str,
+        mut session: UploadSession,
+        content: &[u8],
+        content_type: &str,
+    ) -> Result<Sent> {
+        if session.size != content.len() as u64 {
+            return Err(ChannelError::Attachment(format!(
+                "Upload session is for {} bytes but the file has {}",
+                session.size,
+                content.len()
+            )));
+        }
+        if session.offset > 0 {
+            debug!("Resuming upload '{}' at byte {} of {}", key, session.offset, session.size);
+        }
+        self.store.save(key, &session).await?;
+
+        while !session.is_complete() {
+            let start = session.offset as usize;
+            let end = (start + self.chunk_size).min(content.len());
+            let response = self
+                .client
+                .post(&session.url)
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .header(
+                    reqwest::header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", start, end - 1, content.len()),
+                )
+                .body(content[start..end].to_vec())
+                .send()
+                .await?;
+
+            let status = response.status();
+            // 308 is how resumable upload servers acknowledge a partial file.
+            if !status.is_success() && status.as_u16() != 308 {
+                let body = response.text().await.unwrap_or_default();
+                let message = format!(
+                    "Upload of bytes {}-{} failed ({}): {}",
+                    start,
+                    end - 1,
+                    status,
+                    body
+                );
+                if status.is_client_error() {
+                    return Ok(Sent::Rejected(message));
+                }
+                return Err(ChannelError::Delivery(message));
+            }
+
+            let accepted = response
+                .headers()
+                .get(reqwest::header::RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_range_end)
+                .map(|last| last + 1)
+                .unwrap_or(end as u64);
+            session.offset = accepted.min(session.size);
+            self.store.save(key, &session).await?;
+        }
+
+        self.store.remove(key).await?;
+        Ok(Sent::Complete(session))
+    }
+}
+
+/// How an upload attempt ended, short of a transport or server error.
+enum Sent {
+    /// Every byte was accepted.
+    Complete(UploadSession),
+    /// The server refused a chunk with a client error.
+    Rejected(String),
+}
+
+impl std::fmt::Debug for ChunkedUploader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChunkedUploader")
+            .field("chunk_size", &self.chunk_size)
+            .finish()
+    }
+}
+
+/// Last byte held by the server, from a `Range: bytes=0-N` header.
+fn parse_range_end(range: &str) -> Option<u64> {
+    let (first, last) = range.trim().strip_prefix("bytes=")?.split_once('-')?;
+    if first.trim() != "0" {
+        return None;
+    }
+    last.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Chunks received by the mock endpoint, as (range start, bytes).
+    type Received = Arc<Mutex<Vec<(u64, Vec<u8>)>>>;
+
+    /// Read a request's headers, returning where its `Content-Range`
+    /// starts and the length of its body.
+    async fn read_head(stream: &mut tokio::net::TcpStream) -> (u64, usize) {
+        let mut buf = Vec::new();
+        let mut byte = [0u8; 1];
+        while !buf.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).await.unwrap();
+            buf.push(byte[0]);
+        }
+        let head = String::from_utf8_lossy(&buf).to_lowercase();
+        let header = |name: &str| {
+            head.lines()
+                .find_map(|l| l.strip_prefix(name))
+                .map(|v| v.trim().to_string())
+                .unwrap()
+        };
+        let range = header("content-range: bytes ");
+        let start = range.split('-').next().unwrap().parse().unwrap();
+        (start, header("content-length:").parse().unwrap())
+    }
+
+    /// Start an upload endpoint that stores each chunk it is sent and
+    /// drops the connection halfway through request number `drop_at`, as a
+    /// failing network would.
+    async fn mock_endpoint(drop_at: Option<usize>) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/upload/abc", listener.local_addr().unwrap());
+        let received: Received = Arc::default();
+        let chunks = received.clone();
+
+        tokio::spawn(async move {
+            let mut requests = 0;
+            while let Ok((mut stream, _)) = listener.accept().await {
+                requests += 1;
+                let (start, length) = read_head(&mut stream).await;
+                if Some(requests) == drop_at {
+                    let mut partial = vec![0u8; length / 2];
+                    let _ = stream.read_exact(&mut partial).await;
+                    continue;
+                }
+                let mut body = vec![0u8; length];
+                stream.read_exact(&mut body).await.unwrap();
+                chunks.lock().unwrap().push((start, body));
+                let _ = stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .await;
+            }
+        });
+
+        (url, received)
+    }
+
+    fn content(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_upload_resumes_at_offset() {
+        let tmp = TempDir::new().unwrap();
+        let store: Arc<dyn UploadStore> = Arc::new(FileUploadStore::new(tmp.path()));
+        let (url, received) = mock_endpoint(Some(3)).await;
+        let file = content(10_000);
+        let uploader = ChunkedUploader::new(store.clone()).with_chunk_size(3_000);
+
+        let session = UploadSession::new(&url, file.len() as u64).with_file_id("F123");
+        let err = uploader
+            .upload("slack:C1:report.bin", session, &file, "application/octet-stream")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChannelError::Http(_)), "{}", err);
+
+        // Two chunks made it before the connection dropped.
+        let saved = store.load("slack:C1:report.bin").await.unwrap().unwrap();
+        assert_eq!(saved.offset, 6_000);
+        assert_eq!(saved.file_id.as_deref(), Some("F123"));
+
+        // A fresh uploader, as after a restart, finds the session and goes on.
+        let uploader = ChunkedUploader::new(store.clone()).with_chunk_size(3_000);
+        let pending = uploader
+            .pending("slack:C1:report.bin", file.len() as u64)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending, saved);
+        let done = uploader
+            .upload("slack:C1:report.bin", pending, &file, "application/octet-stream")
+            .await
+            .unwrap();
+
+        assert!(done.is_complete());
+        assert_eq!(store.load("slack:C1:report.bin").await.unwrap(), None);
+        let chunks = received.lock().unwrap();
+        let starts: Vec<u64> = chunks.iter().map(|(start, _)| *start).collect();
+        assert_eq!(starts, vec![0, 3_000, 6_000, 9_000]);
+        let assembled: Vec<u8> = chunks.iter().flat_map(|(_, body)| body.clone()).collect();
+        assert_eq!(assembled, file);
+    }
+
+    #[tokio::test]
+    async fn test_server_range_header_sets_offset() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let starts = Arc::new(Mutex::new(Vec::new()));
+        let seen = starts.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (start, length) = read_head(&mut stream).await;
+                let mut body = vec![0u8; length];
+                stream.read_exact(&mut body).await.unwrap();
+                seen.lock().unwrap().push(start);
+                // Only ever keep the first 100 bytes of a chunk.
+                let response = format!(
+                    "HTTP/1.1 308 Resume Incomplete\r\nRange: bytes=0-{}\r\n\
+                     Content-Length: 0\r\nConnection: close\r\n\r\n",
+                    (start + 100).min(249) - 1
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let store: Arc<dyn UploadStore> = Arc::new(MemoryUploadStore::default());
+        let uploader = ChunkedUploader::new(store).with_chunk_size(150);
+        let file = content(249);
+        let done = uploader
+            .upload("k", UploadSession::new(url, 249), &file, "text/plain")
+            .await
+            .unwrap();
+
+        assert_eq!(done.offset, 249);
+        assert_eq!(*starts.lock().unwrap(), vec![0, 100, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_pending_discards_session_for_other_file() {
+        let store: Arc<dyn UploadStore> = Arc::new(MemoryUploadStore::default());
+        let mut session = UploadSession::new("http://127.0.0.1:9/", 500);
+        session.offset = 200;
+        store.save("k", &session).await.unwrap();
+
+        let uploader = ChunkedUploader::new(store.clone());
+        assert_eq!(uploader.pending("k", 500).await.unwrap(), Some(session));
+        assert_eq!(uploader.pending("k", 501).await.unwrap(), None);
+        assert_eq!(store.load("k").await.unwrap(), None);
+
+        let err = uploader
+            .upload("k", UploadSession::new("http://127.0.0.1:9/", 10), b"short", "text/plain")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChannelError::Attachment(_)));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_resume_discards_session() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (_, length) = read_head(&mut stream).await;
+                let mut body = vec![0u8; length];
+                stream.read_exact(&mut body).await.unwrap();
+                let response = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\
+                                Connection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let store: Arc<dyn UploadStore> = Arc::new(MemoryUploadStore::default());
+        let mut session = UploadSession::new(url, 300);
+        session.offset = 100;
+        store.save("k", &session).await.unwrap();
+
+        let uploader = ChunkedUploader::new(store.clone()).with_chunk_size(100);
+        let file = content(300);
+        let resumed = uploader.resume("k", session.clone(), &file, "text/plain").await.unwrap();
+        assert_eq!(resumed, None);
+        assert_eq!(store.load("k").await.unwrap(), None);
+
+        let err = uploader.upload("k", session, &file, "text/plain").await.unwrap_err();
+        assert!(matches!(err, ChannelError::Delivery(_)), "{}", err);
+        assert_eq!(store.load("k").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_range_end() {
+        assert_eq!(parse_range_end("bytes=0-1023"), Some(1023));
+        assert_eq!(parse_range_end(" bytes=0-7 "), Some(7));
+        assert_eq!(parse_range_end("bytes=100-200"), None);
+        assert_eq!(parse_range_end("items=0-5"), None);
+    }
+}
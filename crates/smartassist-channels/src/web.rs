@@ -6,6 +6,13 @@
 //! - Session/authentication support
 //! - Broadcast to all connected clients
 //! - Individual client messaging
+//! - Server-sent events (`GET /events`) for streaming assistant output to
+//!   browsers without a socket
+//!
+//! The SSE endpoint serves one chat per connection and requires the
+//! channel's `auth_token`, passed as a `token` query parameter (browsers
+//! cannot set headers on an `EventSource`) or a bearer `Authorization`
+//! header. Without a configured token the endpoint is closed.
 
 #![cfg(feature = "web")]
 
@@ -19,6 +26,7 @@ use crate::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use futures::{SinkExt, StreamExt};
+use smartassist_core::SecretString;
 use smartassist_core::types::{
     ChannelCapabilities, ChannelFeatures, ChannelHealth, ChannelLimits, ChatInfo, ChatType,
     HealthStatus, InboundMessage, MediaCapabilities, MessageId, MessageTarget, OutboundMessage,
@@ -28,11 +36,38 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
 use tracing::{debug, error, info, warn};
 
+/// Path of the server-sent events endpoint.
+pub const SSE_PATH: &str = "/events";
+
+/// Comment frame sent to idle SSE clients so proxies keep the connection open.
+pub const SSE_HEARTBEAT_FRAME: &str = ": keep-alive\n\n";
+
+/// Interval between SSE heartbeats.
+const SSE_HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// Longest HTTP request head accepted on the SSE endpoint.
+const MAX_REQUEST_HEAD: usize = 8192;
+
+/// How long a new connection has to send its request head.
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Response head for SSE connections.
+const SSE_RESPONSE_HEAD: &str = concat!(
+    "HTTP/1.1 200 OK\r\n",
+    "Content-Type: text/event-stream\r\n",
+    "Cache-Control: no-cache\r\n",
+    "Connection: keep-alive\r\n",
+    "X-Accel-Buffering: no\r\n",
+    "\r\n",
+);
+
 /// Web channel implementation using WebSocket.
 pub struct WebChannel {
     /// Channel instance ID.
@@ -41,6 +76,9 @@ pub struct WebChannel {
     /// Bind address for WebSocket server.
     bind_address: String,
 
+    /// Token SSE clients must present; the SSE endpoint is closed without one.
+    auth_token: Option<SecretString>,
+
     /// Connection state.
     connected: Arc<RwLock<bool>>,
 
@@ -55,6 +93,9 @@ pub struct WebChannel {
     /// Broadcast channel for outgoing messages.
     broadcast_tx: broadcast::Sender<String>,
 
+    /// Broadcast channel for SSE stream frames.
+    sse_tx: broadcast::Sender<SseFrame>,
+
     /// Message handler.
     handler: Arc<RwLock<Option<Box<dyn MessageHandler>>>>,
 
@@ -105,6 +146,82 @@ pub enum WebSocketMessage {
     Pong,
 }
 
+/// Assistant streaming events delivered to SSE clients.
+///
+/// Mirrors the agent runtime's stream events, so a response can be forwarded
+/// to the browser token by token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebStreamEvent {
+    /// Response started.
+    Start { message_id: String },
+    /// Text delta.
+    Text { delta: String },
+    /// Thinking delta.
+    Thinking { delta: String },
+    /// Tool invoked.
+    ToolUse { id: String, name: String },
+    /// Token usage update.
+    Usage {
+        input_tokens: usize,
+        output_tokens: usize,
+    },
+    /// Response completed.
+    Done { message_id: String },
+    /// Error occurred.
+    Error { message: String },
+}
+
+impl WebStreamEvent {
+    /// SSE event name, matching the JSON `type` tag.
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            Self::Start { .. } => "start",
+            Self::Text { .. } => "text",
+            Self::Thinking { .. } => "thinking",
+            Self::ToolUse { .. } => "tool_use",
+            Self::Usage { .. } => "usage",
+            Self::Done { .. } => "done",
+            Self::Error { .. } => "error",
+        }
+    }
+
+    /// Format the event as an SSE frame with a JSON payload.
+    pub fn to_sse_frame(&self) -> Result<String> {
+        let data =
+            serde_json::to_string(self).map_err(|e| ChannelError::Internal(e.to_string()))?;
+        Ok(sse_frame(self.event_name(), &data))
+    }
+}
+
+/// Format an SSE frame, putting each line of `data` on its own `data:` field.
+pub fn sse_frame(event: &str, data: &str) -> String {
+    let mut frame = format!("event: {}\n", event);
+    for line in data.split('\n') {
+        frame.push_str("data: ");
+        frame.push_str(line);
+        frame.push('\n');
+    }
+    frame.push('\n');
+    frame
+}
+
+/// A formatted SSE frame addressed to one chat.
+#[derive(Debug, Clone)]
+struct SseFrame {
+    chat_id: String,
+    data: String,
+}
+
+/// A parsed request for the SSE endpoint.
+#[derive(Debug, PartialEq)]
+struct SseRequest {
+    /// The chat whose events to forward.
+    chat_id: Option<String>,
+    /// The token from the query or an `Authorization: Bearer` header.
+    token: Option<String>,
+}
+
 /// Outbound message types sent to clients.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -176,15 +293,18 @@ impl WebChannel {
     pub fn new(instance_id: impl Into<String>, bind_address: impl Into<String>) -> Self {
         let (message_tx, message_rx) = mpsc::channel(1000);
         let (broadcast_tx, _) = broadcast::channel(1000);
+        let (sse_tx, _) = broadcast::channel(1000);
 
         Self {
             instance_id: instance_id.into(),
             bind_address: bind_address.into(),
+            auth_token: None,
             connected: Arc::new(RwLock::new(false)),
             clients: Arc::new(RwLock::new(HashMap::new())),
             message_tx,
             message_rx: Arc::new(RwLock::new(message_rx)),
             broadcast_tx,
+            sse_tx,
             handler: Arc::new(RwLock::new(None)),
             shutdown: Arc::new(RwLock::new(None)),
         }
@@ -199,7 +319,17 @@ impl WebChannel {
             .unwrap_or("127.0.0.1:8080")
             .to_string();
 
-        Self::new(config.instance_id, bind_address)
+        let mut channel = Self::new(config.instance_id, bind_address);
+        if let Some(token) = config.options.get("auth_token").and_then(|v| v.as_str()) {
+            channel = channel.with_auth_token(token);
+        }
+        channel
+    }
+
+    /// Set the token SSE clients must present.
+    pub fn with_auth_token(mut self, token: impl Into<SecretString>) -> Self {
+        self.auth_token = Some(token.into()).filter(|t: &SecretString| !t.is_empty());
+        self
     }
 
     /// Get the number of connected clients.
@@ -211,6 +341,30 @@ impl WebChannel {
     pub fn broadcast(&self, message: &str) -> std::result::Result<usize, broadcast::error::SendError<String>> {
         self.broadcast_tx.send(message.to_string())
     }
+
+    /// Publish a streaming event to SSE clients following `chat_id`.
+    ///
+    /// Returns the number of SSE connections that received it.
+    pub fn publish_stream_event(&self, chat_id: &str, event: &WebStreamEvent) -> Result<usize> {
+        let frame = SseFrame {
+            chat_id: chat_id.to_string(),
+            data: event.to_sse_frame()?,
+        };
+        // No subscribers is not an error; nobody is watching yet.
+        Ok(self.sse_tx.send(frame).unwrap_or(0))
+    }
+
+    /// Forward a stream of events to SSE clients following `chat_id`.
+    pub async fn stream_to_sse<S>(&self, chat_id: &str, events: S) -> Result<()>
+    where
+        S: futures::Stream<Item = WebStreamEvent>,
+    {
+        let mut events = std::pin::pin!(events);
+        while let Some(event) = events.next().await {
+            self.publish_stream_event(chat_id, &event)?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -397,6 +551,8 @@ impl ChannelReceiver for WebChannel {
         let clients = self.clients.clone();
         let message_tx = self.message_tx.clone();
         let broadcast_tx = self.broadcast_tx.clone();
+        let sse_tx = self.sse_tx.clone();
+        let auth_token = self.auth_token.clone().map(Arc::new);
         let handler = self.handler.clone();
         let instance_id = self.instance_id.clone();
         let connected = self.connected.clone();
@@ -407,6 +563,8 @@ impl ChannelReceiver for WebChannel {
                 clients,
                 message_tx,
                 broadcast_tx,
+                sse_tx,
+                auth_token,
                 handler,
                 instance_id,
                 connected,
@@ -515,11 +673,13 @@ impl Clone for WebChannel {
         Self {
             instance_id: self.instance_id.clone(),
             bind_address: self.bind_address.clone(),
+            auth_token: self.auth_token.clone(),
             connected: self.connected.clone(),
             clients: self.clients.clone(),
             message_tx,
             message_rx: Arc::new(RwLock::new(message_rx)),
             broadcast_tx: self.broadcast_tx.clone(),
+            sse_tx: self.sse_tx.clone(),
             handler: self.handler.clone(),
             shutdown: self.shutdown.clone(),
         }
@@ -529,11 +689,17 @@ impl Clone for WebChannel {
 // --- WebSocket Server Implementation ---
 
 /// Run the WebSocket server.
+///
+/// Requests for [`SSE_PATH`] are served as server-sent events on the same
+/// listener; everything else goes through the WebSocket handshake.
+#[allow(clippy::too_many_arguments)]
 async fn run_websocket_server(
     listener: TcpListener,
     clients: Arc<RwLock<HashMap<String, WebClient>>>,
     message_tx: mpsc::Sender<InboundMessage>,
     broadcast_tx: broadcast::Sender<String>,
+    sse_tx: broadcast::Sender<SseFrame>,
+    auth_token: Option<Arc<SecretString>>,
     handler: Arc<RwLock<Option<Box<dyn MessageHandler>>>>,
     instance_id: String,
     connected: Arc<RwLock<bool>>,
@@ -548,19 +714,33 @@ async fn run_websocket_server(
                         let clients = clients.clone();
                         let message_tx = message_tx.clone();
                         let broadcast_rx = broadcast_tx.subscribe();
+                        let sse_rx = sse_tx.subscribe();
+                        let auth_token = auth_token.clone();
                         let handler = handler.clone();
                         let instance_id = instance_id.clone();
 
                         tokio::spawn(async move {
-                            if let Err(e) = handle_connection(
-                                stream,
-                                peer_addr,
-                                clients,
-                                message_tx,
-                                broadcast_rx,
-                                handler,
-                                instance_id,
-                            ).await {
+                            let result = if is_sse_request(&stream).await {
+                                handle_sse_connection(
+                                    stream,
+                                    auth_token.as_deref(),
+                                    sse_rx,
+                                    SSE_HEARTBEAT,
+                                )
+                                .await
+                            } else {
+                                handle_connection(
+                                    stream,
+                                    peer_addr,
+                                    clients,
+                                    message_tx,
+                                    broadcast_rx,
+                                    handler,
+                                    instance_id,
+                                )
+                                .await
+                            };
+                            if let Err(e) = result {
                                 warn!("Connection error from {}: {}", peer_addr, e);
                             }
                         });
@@ -582,6 +762,179 @@ async fn run_websocket_server(
     *connected = false;
 }
 
+/// Peek at a new connection's request line to see if it targets the SSE
+/// endpoint, leaving the bytes in place for the WebSocket handshake.
+///
+/// The request line can arrive over several packets, so this peeks until
+/// it has enough of it to decide.
+async fn is_sse_request(stream: &TcpStream) -> bool {
+    let deadline = tokio::time::Instant::now() + REQUEST_HEAD_TIMEOUT;
+    let mut buf = [0u8; 1024];
+    let mut seen = 0;
+    loop {
+        let n = match tokio::time::timeout_at(deadline, stream.peek(&mut buf)).await {
+            Ok(Ok(n)) if n > 0 => n,
+            _ => return false,
+        };
+        match is_sse_request_line(&buf[..n]) {
+            Some(answer) => return answer,
+            None if n == buf.len() => return false,
+            None => {}
+        }
+        // Peeking again returns the same bytes at once, so wait for more.
+        if n == seen {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        seen = n;
+    }
+}
+
+/// Decide from the start of a request whether it is a `GET` for
+/// [`SSE_PATH`], or `None` if more bytes are needed.
+fn is_sse_request_line(head: &[u8]) -> Option<bool> {
+    let prefix = format!("GET {}", SSE_PATH);
+    let prefix = prefix.as_bytes();
+    let len = head.len().min(prefix.len());
+    if head[..len] != prefix[..len] {
+        return Some(false);
+    }
+    head.get(prefix.len()).map(|b| matches!(b, b'?' | b' '))
+}
+
+/// Read the request head of an SSE connection, up to the blank line.
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read = tokio::time::timeout(REQUEST_HEAD_TIMEOUT, async {
+        while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+            if head.len() > MAX_REQUEST_HEAD {
+                return Ok(false);
+            }
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                return Ok(false);
+            }
+            head.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(true)
+    });
+    match read.await {
+        Ok(Ok(true)) => Ok(Some(String::from_utf8_lossy(&head).into_owned())),
+        Ok(Ok(false)) | Err(_) => Ok(None),
+        Ok(Err(e)) => Err(e),
+    }
+}
+
+/// Parse an HTTP request head, returning the SSE request if it is a
+/// `GET` for [`SSE_PATH`].
+fn parse_sse_request(head: &str) -> Option<SseRequest> {
+    let mut lines = head.lines();
+    let mut parts = lines.next()?.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != SSE_PATH {
+        return None;
+    }
+
+    let param = |name: &str| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty())
+    };
+    let bearer = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    Some(SseRequest {
+        chat_id: param("chat_id"),
+        token: param("token").or(bearer),
+    })
+}
+
+/// Check an SSE request against the channel token, returning the chat to
+/// follow or the status line to refuse it with.
+fn authorize_sse(
+    request: Option<SseRequest>,
+    auth_token: Option<&SecretString>,
+) -> std::result::Result<String, &'static str> {
+    let Some(expected) = auth_token else {
+        return Err("403 Forbidden");
+    };
+    let request = request.ok_or("400 Bad Request")?;
+    if request.token.map(SecretString::new).as_ref() != Some(expected) {
+        return Err("401 Unauthorized");
+    }
+    request.chat_id.ok_or("400 Bad Request")
+}
+
+/// Serve a server-sent events connection until the client goes away.
+async fn handle_sse_connection(
+    mut stream: TcpStream,
+    auth_token: Option<&SecretString>,
+    mut sse_rx: broadcast::Receiver<SseFrame>,
+    heartbeat: Duration,
+) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let request = read_request_head(&mut stream)
+        .await?
+        .and_then(|head| parse_sse_request(&head));
+    let chat_id = match authorize_sse(request, auth_token) {
+        Ok(chat_id) => chat_id,
+        Err(status) => {
+            debug!("SSE request refused: {}", status);
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+    };
+
+    let (mut reader, mut writer) = stream.into_split();
+    writer.write_all(SSE_RESPONSE_HEAD.as_bytes()).await?;
+    debug!("SSE client connected (chat: {})", chat_id);
+
+    let mut ticker = tokio::time::interval(heartbeat);
+    ticker.tick().await;
+    // Anything after the request head is discarded; a zero-length read
+    // means the client closed the connection.
+    let mut discard = [0u8; 1024];
+
+    loop {
+        tokio::select! {
+            result = sse_rx.recv() => {
+                match result {
+                    Ok(frame) => {
+                        if frame.chat_id == chat_id {
+                            writer.write_all(frame.data.as_bytes()).await?;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("SSE client lagged {} events", n);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            _ = ticker.tick() => {
+                writer.write_all(SSE_HEARTBEAT_FRAME.as_bytes()).await?;
+            }
+            read = reader.read(&mut discard) => {
+                if read? == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    debug!("SSE client disconnected");
+    Ok(())
+}
+
 /// Handle a single WebSocket connection.
 async fn handle_connection(
     stream: TcpStream,
@@ -874,4 +1227,148 @@ mod tests {
         let json = serde_json::to_string(&edit).unwrap();
         assert!(json.contains("\"type\":\"edit\""));
     }
+
+    #[test]
+    fn test_sse_framing() {
+        let events = [
+            WebStreamEvent::Text {
+                delta: "Hel".to_string(),
+            },
+            WebStreamEvent::Text {
+                delta: "lo".to_string(),
+            },
+            WebStreamEvent::Done {
+                message_id: "msg1".to_string(),
+            },
+        ];
+        let frames: Vec<_> = events.iter().map(|e| e.to_sse_frame().unwrap()).collect();
+
+        assert_eq!(
+            frames,
+            [
+                "event: text\ndata: {\"type\":\"text\",\"delta\":\"Hel\"}\n\n",
+                "event: text\ndata: {\"type\":\"text\",\"delta\":\"lo\"}\n\n",
+                "event: done\ndata: {\"type\":\"done\",\"message_id\":\"msg1\"}\n\n",
+            ]
+        );
+
+        // Multi-line data must not end the frame early.
+        assert_eq!(sse_frame("note", "a\nb"), "event: note\ndata: a\ndata: b\n\n");
+    }
+
+    #[test]
+    fn test_parse_sse_request() {
+        let head = "GET /events?chat_id=chat%201&token=s3cret HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            parse_sse_request(head),
+            Some(SseRequest {
+                chat_id: Some("chat 1".to_string()),
+                token: Some("s3cret".to_string()),
+            })
+        );
+        let head = "GET /events?chat_id=c HTTP/1.1\r\nauthorization: Bearer s3cret\r\n\r\n";
+        assert_eq!(parse_sse_request(head).unwrap().token.as_deref(), Some("s3cret"));
+        assert_eq!(
+            parse_sse_request("GET /events HTTP/1.1\r\n"),
+            Some(SseRequest {
+                chat_id: None,
+                token: None,
+            })
+        );
+        assert_eq!(parse_sse_request("GET / HTTP/1.1\r\nUpgrade: websocket\r\n"), None);
+        assert_eq!(parse_sse_request("POST /events HTTP/1.1\r\n"), None);
+
+        assert_eq!(is_sse_request_line(b"GET /eve"), None);
+        assert_eq!(is_sse_request_line(b"GET /events?chat_id=1"), Some(true));
+        assert_eq!(is_sse_request_line(b"GET /events2 "), Some(false));
+        assert_eq!(is_sse_request_line(b"GET / HTTP/1.1"), Some(false));
+    }
+
+    #[test]
+    fn test_sse_requires_token_and_chat() {
+        let token = SecretString::new("s3cret");
+        let request = |chat_id: Option<&str>, token: Option<&str>| {
+            Some(SseRequest {
+                chat_id: chat_id.map(str::to_string),
+                token: token.map(str::to_string),
+            })
+        };
+
+        assert_eq!(
+            authorize_sse(request(Some("c"), Some("s3cret")), Some(&token)),
+            Ok("c".to_string())
+        );
+        assert_eq!(
+            authorize_sse(request(Some("c"), Some("guess")), Some(&token)),
+            Err("401 Unauthorized")
+        );
+        assert_eq!(authorize_sse(request(Some("c"), None), Some(&token)), Err("401 Unauthorized"));
+        assert_eq!(
+            authorize_sse(request(None, Some("s3cret")), Some(&token)),
+            Err("400 Bad Request")
+        );
+        // Without a configured token the endpoint is closed.
+        assert_eq!(authorize_sse(request(Some("c"), Some("x")), None), Err("403 Forbidden"));
+    }
+
+    #[tokio::test]
+    async fn test_sse_connection_streams_chat_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        // The request line arrives in two packets.
+        client.write_all(b"GET /ev").await.unwrap();
+        let detect = tokio::spawn(async move { (is_sse_request(&server).await, server) });
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        client
+            .write_all(b"ents?chat_id=chat1&token=s3cret HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let (is_sse, server) = detect.await.unwrap();
+        assert!(is_sse);
+
+        let channel = WebChannel::new("test_web", "127.0.0.1:0");
+        let sse_rx = channel.sse_tx.subscribe();
+        tokio::spawn(async move {
+            let token = SecretString::new("s3cret");
+            handle_sse_connection(server, Some(&token), sse_rx, Duration::from_millis(20)).await
+        });
+
+        let other = WebStreamEvent::Text {
+            delta: "elsewhere".to_string(),
+        };
+        channel.publish_stream_event("chat2", &other).unwrap();
+        let events = vec![
+            WebStreamEvent::Text {
+                delta: "Hi".to_string(),
+            },
+            WebStreamEvent::Done {
+                message_id: "msg1".to_string(),
+            },
+        ];
+        channel
+            .stream_to_sse("chat1", futures::stream::iter(events))
+            .await
+            .unwrap();
+
+        let mut received = String::new();
+        let mut buf = [0u8; 1024];
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !(received.contains("event: done") && received.contains(SSE_HEARTBEAT_FRAME)) {
+                let n = client.read(&mut buf).await.unwrap();
+                assert!(n > 0, "connection closed early");
+                received.push_str(&String::from_utf8_lossy(&buf[..n]));
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(received.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received.contains("Content-Type: text/event-stream\r\n"));
+        assert!(!received.contains("Access-Control-Allow-Origin"));
+        let text = received.find("event: text\n").unwrap();
+        assert!(text < received.find("event: done\n").unwrap());
+        assert!(!received.contains("elsewhere"));
+    }
 }
@@ -92,6 +92,10 @@ struct WhatsAppMessagePayload {
     document: Option<WhatsAppDocument>,
     #[serde(skip_serializing_if = "Option::is_none")]
     context: Option<WhatsAppContext>,
+    /// Opaque tag echoed back in status webhooks; carries the idempotency
+    /// key so deliveries can be matched to the send that caused them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    biz_opaque_callback_data: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -621,6 +625,7 @@ impl ChannelSender for WhatsAppChannel {
             audio: None,
             document: None,
             context: message.reply_to.map(|id| WhatsAppContext { message_id: id }),
+            biz_opaque_callback_data: message.idempotency_key,
         };
 
         let response = self
@@ -712,6 +717,7 @@ impl ChannelSender for WhatsAppChannel {
                         audio: None,
                         document: None,
                         context: None,
+                        biz_opaque_callback_data: message.idempotency_key.clone(),
                     };
 
                     match media_field {
@@ -796,6 +802,7 @@ impl ChannelSender for WhatsAppChannel {
                 audio: None,
                 document: None,
                 context: None,
+                biz_opaque_callback_data: message.idempotency_key.clone(),
             };
 
             match media_field {
@@ -954,6 +961,24 @@ impl ChannelSender for WhatsAppChannel {
         Ok(())
     }
 
+    /// Resolve a phone number in international format to its WhatsApp ID,
+    /// the digits of the number including the country code.
+    async fn resolve_target(&self, identifier: &str) -> Result<MessageTarget> {
+        let phone_chars = identifier
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '(' | ')' | '.' | ' '));
+        let wa_id = self.normalize_phone(identifier);
+        // E.164 numbers have at most 15 digits.
+        if !phone_chars || !(7..=15).contains(&wa_id.len()) {
+            return Err(ChannelError::unresolved(
+                &self.instance_id,
+                identifier,
+                "expected a phone number with country code",
+            ));
+        }
+        Ok(MessageTarget::new(wa_id))
+    }
+
     fn max_message_length(&self) -> usize {
         4096
     }
@@ -1143,6 +1168,32 @@ mod tests {
         assert!(caps.chat_types.contains(&ChatType::Direct));
     }
 
+    #[test]
+    fn test_payload_carries_idempotency_key() {
+        let payload = WhatsAppMessagePayload {
+            messaging_product: "whatsapp",
+            recipient_type: "individual",
+            to: "15551234567".to_string(),
+            message_type: "text".to_string(),
+            text: None,
+            image: None,
+            video: None,
+            audio: None,
+            document: None,
+            context: None,
+            biz_opaque_callback_data: Some("reply-1".to_string()),
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+        assert_eq!(json["biz_opaque_callback_data"], "reply-1");
+
+        let json = serde_json::to_value(WhatsAppMessagePayload {
+            biz_opaque_callback_data: None,
+            ..payload
+        })
+        .unwrap();
+        assert!(json.get("biz_opaque_callback_data").is_none());
+    }
+
     #[test]
     fn test_normalize_phone() {
         let channel = WhatsAppChannel::new("123456789", "access_token", "test");
@@ -1152,6 +1203,19 @@ mod tests {
         assert_eq!(channel.normalize_phone("15551234567"), "15551234567");
     }
 
+    #[tokio::test]
+    async fn test_resolve_target_from_phone() {
+        let channel = WhatsAppChannel::new("123456789", "access_token", "wa");
+
+        let target = channel.resolve_target("+1 (555) 010-0199").await.unwrap();
+        assert_eq!(target.chat_id, "15550100199");
+
+        for bad in ["@alice", "12345", "+1 555 010 0199 0199 01"] {
+            let err = channel.resolve_target(bad).await.unwrap_err();
+            assert!(matches!(err, ChannelError::UnresolvedTarget { .. }), "{}: {:?}", bad, err);
+        }
+    }
+
     #[test]
     fn test_verify_webhook_success() {
         let channel = WhatsAppChannel::new("123456789", "access_token", "test")
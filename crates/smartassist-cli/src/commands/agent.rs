@@ -6,7 +6,9 @@ use smartassist_agent::providers::anthropic::AnthropicProvider;
 use smartassist_agent::runtime::AgentRuntime;
 use smartassist_agent::session::SessionManager;
 use smartassist_agent::tools::ToolRegistry;
+use smartassist_agent::PromptLibrary;
 use smartassist_core::config::Config;
+use smartassist_core::template::TemplateVars;
 use smartassist_core::types::{AgentConfig, AgentId, SessionKey};
 use std::sync::Arc;
 
@@ -69,9 +71,22 @@ pub enum AgentCommand {
         model: Option<String>,
 
         /// System prompt
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "preset")]
         system: Option<String>,
 
+        /// Use the named system-prompt preset from `agents.prompts`
+        #[arg(long)]
+        preset: Option<String>,
+
+        /// Preset variable as KEY=VALUE (repeatable)
+        #[arg(
+            long = "var",
+            value_name = "KEY=VALUE",
+            value_parser = parse_var,
+            requires = "preset"
+        )]
+        vars: Vec<(String, String)>,
+
         /// Resume session ID
         #[arg(long)]
         session: Option<String>,
@@ -157,7 +172,7 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
         }
 
         AgentCommand::Message { agent, message } => {
-            let config = Config::load_or_default();
+            let config = super::load_resolved_config().await?;
             let agent_id = agent.unwrap_or_else(|| "default".to_string());
             let port = config.gateway.port;
 
@@ -199,6 +214,8 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
             agent,
             model,
             system,
+            preset,
+            vars,
             session,
             provider: _,
         } => {
@@ -231,9 +248,18 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
             let session_manager = Arc::new(SessionManager::new(sessions_dir));
 
             // Create runtime
-            let runtime = Arc::new(
-                AgentRuntime::new(config, provider, tool_registry, session_manager)
-            );
+            let mut runtime = AgentRuntime::new(config, provider, tool_registry, session_manager);
+            if let Some(name) = preset {
+                let library = prompt_library(&super::load_resolved_config().await?)?;
+                let vars: TemplateVars = vars
+                    .into_iter()
+                    .map(|(key, value)| (key, serde_json::Value::String(value)))
+                    .collect();
+                runtime = runtime
+                    .with_prompt_library(library)
+                    .with_system_prompt_preset(&name, &vars)?;
+            }
+            let runtime = Arc::new(runtime);
 
             // Create or resume session
             let session_key = match session {
@@ -254,9 +280,26 @@ pub async fn run(args: AgentArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Parse a `KEY=VALUE` preset variable.
+fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", arg)),
+    }
+}
+
+/// Build the prompt presets configured under `agents.prompts`.
+fn prompt_library(config: &Config) -> anyhow::Result<PromptLibrary> {
+    let presets = serde_json::to_value(&config.agents.prompts)?;
+    serde_json::from_value(presets)
+        .map_err(|e| anyhow::anyhow!("Invalid prompt preset in agents.prompts: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{parse_var, prompt_library};
     use smartassist_core::config::Config;
+    use smartassist_core::template::TemplateVars;
     use smartassist_core::types::{AgentConfig, AgentId};
 
     /// Test agent create: insert a new agent into Config and verify it exists.
@@ -389,4 +432,33 @@ mod tests {
             Some("You are helpful.")
         );
     }
+
+    /// Test that presets are read from `agents.prompts` and selected by name.
+    #[test]
+    fn test_prompt_library_from_config() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "agents": {
+                "prompts": {
+                    "support": {
+                        "prompt": "You support {{product}} users.",
+                        "defaults": { "product": "SmartAssist" }
+                    },
+                    "plain": { "prompt": "You are helpful." }
+                }
+            }
+        }))
+        .unwrap();
+
+        let library = prompt_library(&config).unwrap();
+        assert_eq!(library.names().collect::<Vec<_>>(), vec!["plain", "support"]);
+
+        let (key, value) = parse_var("product=Acme").unwrap();
+        let vars: TemplateVars = [(key, serde_json::Value::String(value))].into_iter().collect();
+        assert_eq!(
+            library.resolve("support", &vars).unwrap(),
+            "You support Acme users."
+        );
+        assert!(parse_var("product").is_err());
+        assert!(parse_var("=Acme").is_err());
+    }
 }
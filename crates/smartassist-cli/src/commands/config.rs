@@ -126,12 +126,19 @@ pub async fn run(args: ConfigArgs) -> anyhow::Result<()> {
         }
 
         ConfigCommand::Validate => {
-            match Config::load_default() {
+            let path = paths::config_file()?;
+            match Config::load(&path) {
                 Ok(config) => {
-                    match config.validate() {
-                        Ok(_) => println!("Configuration is valid"),
-                        Err(e) => anyhow::bail!("Configuration error: {}", e),
+                    if let Err(e) = config.validate() {
+                        anyhow::bail!("Configuration error: {}", e);
+                    }
+                    // Every secret reference must resolve from the store.
+                    if let Some(store) = super::open_secret_store(&config)? {
+                        smartassist_secrets::load_config(&path, &store)
+                            .await
+                            .map_err(|e| anyhow::anyhow!("Configuration error: {}", e))?;
                     }
+                    println!("Configuration is valid");
                 }
                 Err(e) => anyhow::bail!("Failed to load config: {}", e),
             }
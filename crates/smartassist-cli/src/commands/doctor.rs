@@ -17,14 +17,211 @@ pub struct DoctorArgs {
     /// Run all checks including slow ones
     #[arg(long)]
     pub full: bool,
+
+    /// Check sandbox support on this machine (included in --full)
+    #[arg(long)]
+    pub sandbox: bool,
+}
+
+/// Oldest kernel with Landlock filesystem rules.
+const LANDLOCK_MIN_KERNEL: (u32, u32) = (5, 13);
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Result of a single check, with a remediation hint when it did not pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Sandbox capabilities of the host.
+#[derive(Debug, Clone, Default)]
+pub struct SandboxProbe {
+    /// Operating system, as in [`std::env::consts::OS`].
+    pub os: &'static str,
+    /// Kernel release (Linux).
+    pub kernel_release: Option<String>,
+    /// Whether the kernel supports seccomp (Linux).
+    pub seccomp: bool,
+    /// Whether Landlock is an active LSM, if that could be read (Linux).
+    pub landlock: Option<bool>,
+    /// Whether `sandbox-exec` is installed (macOS).
+    pub sandbox_exec: bool,
+    /// Whether processes can be placed in Job Objects (Windows).
+    pub job_objects: bool,
+}
+
+impl SandboxProbe {
+    /// Probe the current machine.
+    pub fn detect() -> Self {
+        let read = |path: &str| std::fs::read_to_string(path).ok();
+        Self {
+            os: std::env::consts::OS,
+            kernel_release: read("/proc/sys/kernel/osrelease").map(|r| r.trim().to_string()),
+            seccomp: read("/proc/self/status")
+                .is_some_and(|status| status.lines().any(|l| l.starts_with("Seccomp:"))),
+            landlock: read("/sys/kernel/security/lsm")
+                .map(|lsm| lsm.trim().split(',').any(|m| m == "landlock")),
+            sandbox_exec: std::path::Path::new("/usr/bin/sandbox-exec").exists(),
+            // Job Objects are part of every supported Windows release.
+            job_objects: cfg!(windows),
+        }
+    }
+}
+
+/// Parse the major and minor version from a kernel release like `6.1.0-18-amd64`.
+fn kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+fn check_kernel(release: Option<&str>) -> CheckResult {
+    let Some(release) = release else {
+        return CheckResult::warn(
+            "Kernel version unknown",
+            "Could not read /proc/sys/kernel/osrelease; is /proc mounted?",
+        );
+    };
+    match kernel_version(release) {
+        Some(version) if version >= LANDLOCK_MIN_KERNEL => {
+            CheckResult::pass(format!("Kernel {}", release))
+        }
+        Some(_) => CheckResult::warn(
+            format!("Kernel {} predates Landlock", release),
+            "Upgrade to Linux 5.13 or newer for filesystem sandboxing",
+        ),
+        None => CheckResult::warn(
+            format!("Kernel version '{}' not recognized", release),
+            "Filesystem sandboxing needs Linux 5.13 or newer",
+        ),
+    }
+}
+
+fn check_seccomp(available: bool) -> CheckResult {
+    if available {
+        CheckResult::pass("seccomp syscall filtering available")
+    } else {
+        CheckResult::fail(
+            "seccomp not supported by this kernel",
+            "Use a kernel built with CONFIG_SECCOMP_FILTER; commands will not be syscall-filtered",
+        )
+    }
+}
+
+fn check_landlock(enabled: Option<bool>) -> CheckResult {
+    match enabled {
+        Some(true) => CheckResult::pass("Landlock filesystem sandboxing enabled"),
+        Some(false) => CheckResult::warn(
+            "Landlock is not an active LSM",
+            "Add 'landlock' to the lsm= kernel boot parameter",
+        ),
+        None => CheckResult::warn(
+            "Landlock status unknown",
+            "Mount securityfs at /sys/kernel/security to check the active LSMs",
+        ),
+    }
+}
+
+fn check_sandbox_exec(present: bool) -> CheckResult {
+    if present {
+        CheckResult::pass("sandbox-exec available")
+    } else {
+        CheckResult::fail(
+            "sandbox-exec not found at /usr/bin/sandbox-exec",
+            "sandbox-exec ships with macOS; restore it or commands will run unsandboxed",
+        )
+    }
+}
+
+fn check_job_objects(supported: bool) -> CheckResult {
+    if supported {
+        CheckResult::pass("Job Objects available for process limits")
+    } else {
+        CheckResult::fail(
+            "Job Objects unavailable",
+            "Process limits need Windows Job Objects; run on a supported Windows release",
+        )
+    }
+}
+
+/// Run the sandbox checks that apply to the probed platform.
+pub fn sandbox_checks(probe: &SandboxProbe) -> Vec<CheckResult> {
+    match probe.os {
+        "linux" => vec![
+            check_kernel(probe.kernel_release.as_deref()),
+            check_seccomp(probe.seccomp),
+            check_landlock(probe.landlock),
+        ],
+        "macos" => vec![check_sandbox_exec(probe.sandbox_exec)],
+        "windows" => vec![check_job_objects(probe.job_objects)],
+        os => vec![CheckResult::warn(
+            format!("No sandbox support on {}", os),
+            "Commands will run with resource limits only",
+        )],
+    }
+}
+
+/// Print a check result and count it.
+fn report(result: &CheckResult, errors: &mut usize, warnings: &mut usize) {
+    let icon = match result.status {
+        CheckStatus::Pass => style(CHECK).green(),
+        CheckStatus::Warn => {
+            *warnings += 1;
+            style(WARN).yellow()
+        }
+        CheckStatus::Fail => {
+            *errors += 1;
+            style(CROSS).red()
+        }
+    };
+    println!("  {} {}", icon, result.message);
+    if let Some(hint) = &result.hint {
+        println!("    {}", hint);
+    }
 }
 
 /// Run the doctor command.
 pub async fn run(args: DoctorArgs) -> anyhow::Result<()> {
     println!("SmartAssist Doctor\n");
 
-    let mut errors = 0;
-    let mut warnings = 0;
+    let mut errors: usize = 0;
+    let mut warnings: usize = 0;
 
     // Check directories
     println!("Checking directories...");
@@ -83,6 +280,14 @@ pub async fn run(args: DoctorArgs) -> anyhow::Result<()> {
         warnings += 1;
     }
 
+    // Sandbox checks
+    if args.full || args.sandbox {
+        println!("\nChecking sandbox support...");
+        for result in sandbox_checks(&SandboxProbe::detect()) {
+            report(&result, &mut errors, &mut warnings);
+        }
+    }
+
     // Full checks: gateway connectivity, additional API keys, secrets store, plugins dir
     if args.full {
         // Check gateway connectivity
@@ -159,3 +364,79 @@ pub async fn run(args: DoctorArgs) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(results: &[CheckResult]) -> Vec<CheckStatus> {
+        results.iter().map(|r| r.status).collect()
+    }
+
+    #[test]
+    fn test_kernel_version() {
+        assert_eq!(kernel_version("6.1.0-18-amd64"), Some((6, 1)));
+        assert_eq!(kernel_version("5.13"), Some((5, 13)));
+        assert_eq!(kernel_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_linux_checks() {
+        let probe = SandboxProbe {
+            os: "linux",
+            kernel_release: Some("6.5.0".to_string()),
+            seccomp: true,
+            landlock: Some(true),
+            ..Default::default()
+        };
+        let pass = CheckStatus::Pass;
+        assert_eq!(statuses(&sandbox_checks(&probe)), [pass, pass, pass]);
+
+        let probe = SandboxProbe {
+            os: "linux",
+            kernel_release: Some("4.19.0".to_string()),
+            seccomp: false,
+            landlock: None,
+            ..Default::default()
+        };
+        let results = sandbox_checks(&probe);
+        assert_eq!(
+            statuses(&results),
+            [CheckStatus::Warn, CheckStatus::Fail, CheckStatus::Warn]
+        );
+        assert!(results.iter().all(|r| r.hint.is_some()));
+        assert!(results[0].message.contains("4.19.0"));
+    }
+
+    #[test]
+    fn test_macos_checks() {
+        let probe = SandboxProbe {
+            os: "macos",
+            sandbox_exec: true,
+            ..Default::default()
+        };
+        assert_eq!(statuses(&sandbox_checks(&probe)), [CheckStatus::Pass]);
+
+        let probe = SandboxProbe {
+            os: "macos",
+            ..Default::default()
+        };
+        assert_eq!(statuses(&sandbox_checks(&probe)), [CheckStatus::Fail]);
+    }
+
+    #[test]
+    fn test_windows_and_other_checks() {
+        let probe = SandboxProbe {
+            os: "windows",
+            job_objects: true,
+            ..Default::default()
+        };
+        assert_eq!(statuses(&sandbox_checks(&probe)), [CheckStatus::Pass]);
+
+        let probe = SandboxProbe {
+            os: "freebsd",
+            ..Default::default()
+        };
+        assert_eq!(statuses(&sandbox_checks(&probe)), [CheckStatus::Warn]);
+    }
+}
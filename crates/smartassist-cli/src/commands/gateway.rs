@@ -1,10 +1,12 @@
 //! Gateway command.
 
 use clap::Args;
+use smartassist_channels::ChannelManager;
 use smartassist_core::config::{self, BindMode};
 use smartassist_gateway::{Gateway, GatewayConfig};
 use smartassist_providers::{
-    anthropic::AnthropicProvider, google::GoogleProvider, openai::OpenAIProvider, Provider,
+    anthropic::AnthropicProvider, deepseek::DeepSeekProvider, google::GoogleProvider,
+    moonshot::MoonshotProvider, openai::OpenAIProvider, Provider,
 };
 use std::net::TcpStream;
 use std::sync::Arc;
@@ -34,11 +36,12 @@ pub enum GatewayCommand {
         #[arg(short, long)]
         force: bool,
 
-        /// Model provider (anthropic, openai, google)
+        /// Model providers, comma-separated; the first serves chat
+        /// (anthropic, openai, google, deepseek, moonshot)
         #[arg(long, env = "SMARTASSIST_PROVIDER", default_value = "anthropic")]
         provider: String,
 
-        /// Default model to use
+        /// Default model of the first provider
         #[arg(long, env = "SMARTASSIST_MODEL")]
         model: Option<String>,
 
@@ -83,82 +86,44 @@ pub async fn run(args: GatewayArgs) -> anyhow::Result<()> {
                 port,
                 auth_token,
                 require_auth,
+                cron_store: smartassist_core::paths::cron_file().ok(),
+                config_file: smartassist_core::paths::config_file().ok(),
+                channel_manager: Some(Arc::new(ChannelManager::new())),
                 ..Default::default()
             };
 
-            // Try to create provider from environment
-            let provider_instance: Option<Arc<dyn Provider>> = match provider.as_str() {
-                "anthropic" => {
-                    match AnthropicProvider::from_env() {
-                        Ok(p) => {
-                            let p = if let Some(ref m) = model {
-                                p.with_default_model(m.clone())
-                            } else {
-                                p
-                            };
-                            info!("Using Anthropic provider");
-                            Some(Arc::new(p))
-                        }
-                        Err(e) => {
-                            info!("Anthropic provider not configured: {}", e);
-                            None
-                        }
-                    }
-                }
-                "openai" => {
-                    match OpenAIProvider::from_env() {
-                        Ok(p) => {
-                            let p = if let Some(ref m) = model {
-                                p.with_default_model(m.clone())
-                            } else {
-                                p
-                            };
-                            info!("Using OpenAI provider");
-                            Some(Arc::new(p))
-                        }
-                        Err(e) => {
-                            info!("OpenAI provider not configured: {}", e);
-                            None
-                        }
-                    }
-                }
-                "google" => {
-                    match GoogleProvider::from_env() {
-                        Ok(p) => {
-                            let p = if let Some(ref m) = model {
-                                p.with_default_model(m.clone())
-                            } else {
-                                p
-                            };
-                            info!("Using Google provider");
-                            Some(Arc::new(p))
-                        }
-                        Err(e) => {
-                            info!("Google provider not configured: {}", e);
-                            None
-                        }
-                    }
-                }
-                other => {
-                    anyhow::bail!("Unknown provider: {}. Valid options: anthropic, openai, google", other);
+            // Try to create the providers from the environment. The first one
+            // serves chat; the rest only add their models to `models.list`.
+            let mut providers: Vec<Arc<dyn Provider>> = Vec::new();
+            for (i, name) in provider.split(',').map(str::trim).enumerate() {
+                let model = if i == 0 { model.clone() } else { None };
+                if let Some(p) = provider_from_env(name, model)? {
+                    providers.push(p);
                 }
-            };
+            }
 
             info!("Starting gateway on port {} with 54 RPC methods", port);
 
-            // Create gateway with provider if available
-            let gateway = if let Some(provider) = provider_instance {
-                Gateway::with_provider(config, provider).await
-            } else {
+            // Create gateway with providers if available
+            let mut gateway = if providers.is_empty() {
                 info!("No provider configured, chat will return echo responses");
                 Gateway::with_default_handlers(config).await
+            } else {
+                Gateway::with_providers(config, providers).await
             };
+            // Fail now if a secret the config file refers to is missing,
+            // and check the references again whenever the file reloads.
+            let file_config = config::Config::load_or_default();
+            if let Some(store) = super::open_secret_store(&file_config)? {
+                smartassist_secrets::resolve_config(&file_config, &store).await?;
+                gateway = gateway.with_secret_store(Arc::new(store));
+            }
 
             gateway.run().await?;
         }
 
         GatewayCommand::Stop => {
-            let cfg = config::Config::load_or_default();
+            let cfg = super::load_resolved_config().await?;
             let port = cfg.gateway.port;
 
             match TcpStream::connect(format!("127.0.0.1:{}", port)) {
@@ -177,7 +142,7 @@ pub async fn run(args: GatewayArgs) -> anyhow::Result<()> {
         }
 
         GatewayCommand::Status => {
-            let cfg = config::Config::load_or_default();
+            let cfg = super::load_resolved_config().await?;
             let port = cfg.gateway.port;
 
             match TcpStream::connect(format!("127.0.0.1:{}", port)) {
@@ -193,3 +158,103 @@ pub async fn run(args: GatewayArgs) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Create the provider `name` from its environment variables.
+///
+/// Returns `None` when the provider is known but not configured.
+fn provider_from_env(
+    name: &str,
+    model: Option<String>,
+) -> anyhow::Result<Option<Arc<dyn Provider>>> {
+    let provider: Option<Arc<dyn Provider>> = match name {
+        "anthropic" => {
+            match AnthropicProvider::from_env() {
+                Ok(p) => {
+                    let p = if let Some(m) = model {
+                        p.with_default_model(m)
+                    } else {
+                        p
+                    };
+                    info!("Using Anthropic provider");
+                    Some(Arc::new(p))
+                }
+                Err(e) => {
+                    info!("Anthropic provider not configured: {}", e);
+                    None
+                }
+            }
+        }
+        "openai" => {
+            match OpenAIProvider::from_env() {
+                Ok(p) => {
+                    let p = if let Some(m) = model {
+                        p.with_default_model(m)
+                    } else {
+                        p
+                    };
+                    info!("Using OpenAI provider");
+                    Some(Arc::new(p))
+                }
+                Err(e) => {
+                    info!("OpenAI provider not configured: {}", e);
+                    None
+                }
+            }
+        }
+        "google" => {
+            match GoogleProvider::from_env() {
+                Ok(p) => {
+                    let p = if let Some(m) = model {
+                        p.with_default_model(m)
+                    } else {
+                        p
+                    };
+                    info!("Using Google provider");
+                    Some(Arc::new(p))
+                }
+                Err(e) => {
+                    info!("Google provider not configured: {}", e);
+                    None
+                }
+            }
+        }
+        "deepseek" => {
+            match DeepSeekProvider::from_env() {
+                Ok(p) => {
+                    let p = if let Some(m) = model {
+                        p.with_default_model(m)
+                    } else {
+                        p
+                    };
+                    info!("Using DeepSeek provider");
+                    Some(Arc::new(p))
+                }
+                Err(e) => {
+                    info!("DeepSeek provider not configured: {}", e);
+                    None
+                }
+            }
+        }
+        "moonshot" => {
+            match MoonshotProvider::from_env() {
+                Ok(p) => {
+                    let p = if let Some(m) = model {
+                        p.with_default_model(m)
+                    } else {
+                        p
+                    };
+                    info!("Using Moonshot provider");
+                    Some(Arc::new(p))
+                }
+                Err(e) => {
+                    info!("Moonshot provider not configured: {}", e);
+                    None
+                }
+            }
+        }
+        other => {
+            anyhow::bail!("Unknown provider: {}. Valid options: anthropic, openai, google, deepseek, moonshot", other);
+        }
+    };
+    Ok(provider)
+}
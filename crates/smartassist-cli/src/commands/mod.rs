@@ -7,3 +7,29 @@ pub mod config;
 pub mod doctor;
 pub mod plugins;
 pub mod secrets;
+
+use smartassist_core::config::Config;
+use smartassist_secrets::FileSecretStore;
+
+/// Load the config like [`Config::load_or_default`], with its `secret://`
+/// references resolved from the secret store.
+///
+/// Use this for config a command acts on. Commands that edit and save the
+/// config load it unresolved, so secrets never end up in the file.
+pub async fn load_resolved_config() -> anyhow::Result<Config> {
+    let config = Config::load_or_default();
+    match open_secret_store(&config)? {
+        Some(store) => Ok(smartassist_secrets::resolve_config(&config, &store).await?),
+        None => Ok(config),
+    }
+}
+
+/// Open the secret store if `config` refers to any secrets.
+pub fn open_secret_store(config: &Config) -> anyhow::Result<Option<FileSecretStore>> {
+    if config.secret_refs()?.is_empty() {
+        return Ok(None);
+    }
+    FileSecretStore::from_default_dir()
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Failed to initialize secret store: {}", e))
+}
@@ -0,0 +1,256 @@
+//! Syntax highlighting for fenced code blocks.
+//!
+//! This is a lexical highlighter, not a parser: it colors keywords, strings,
+//! comments and numbers from per-language word lists, which is enough to make
+//! code in responses easier to scan. Unknown languages are left uncolored.
+
+use console::Style;
+
+/// Lexical rules for one language.
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+    ],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "do",
+        "else", "enum", "export", "extends", "false", "finally", "for", "from", "function", "if",
+        "import", "instanceof", "interface", "let", "new", "null", "return", "switch", "this",
+        "throw", "true", "try", "type", "typeof", "undefined", "var", "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\'', '`'],
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "false", "for",
+        "func", "go", "if", "import", "interface", "map", "nil", "package", "range", "return",
+        "select", "struct", "switch", "true", "type", "var",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '`'],
+};
+
+const C_LIKE: Syntax = Syntax {
+    keywords: &[
+        "break", "case", "char", "class", "const", "continue", "default", "do", "double", "else",
+        "enum", "extends", "false", "final", "float", "for", "if", "import", "int", "long", "new",
+        "null", "private", "protected", "public", "return", "static", "struct", "switch", "this",
+        "true", "void", "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "echo", "elif", "else", "esac", "export", "fi", "for", "function",
+        "if", "in", "local", "return", "then", "while",
+    ],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+};
+
+const JSON: Syntax = Syntax {
+    keywords: &["false", "null", "true"],
+    line_comments: &[],
+    block_comment: None,
+    quotes: &['"'],
+};
+
+const CONFIG: Syntax = Syntax {
+    keywords: &["false", "true"],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+};
+
+/// Look up the rules for a fence info string such as `rust` or `py`.
+fn syntax_for(language: &str) -> Option<&'static Syntax> {
+    match language.to_ascii_lowercase().as_str() {
+        "rust" | "rs" => Some(&RUST),
+        "python" | "py" => Some(&PYTHON),
+        "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => Some(&JAVASCRIPT),
+        "go" | "golang" => Some(&GO),
+        "c" | "h" | "cpp" | "c++" | "java" | "cs" | "csharp" | "kotlin" => Some(&C_LIKE),
+        "sh" | "bash" | "zsh" | "shell" | "console" => Some(&SHELL),
+        "json" | "jsonc" => Some(&JSON),
+        "toml" | "yaml" | "yml" | "ini" => Some(&CONFIG),
+        _ => None,
+    }
+}
+
+/// Highlights a code block line by line.
+///
+/// Block comment state carries over between lines, so feed the lines of a
+/// block in order to one highlighter.
+pub struct Highlighter {
+    syntax: Option<&'static Syntax>,
+    in_block_comment: bool,
+}
+
+impl Highlighter {
+    /// Create a highlighter for a fence info string (e.g. `rust`).
+    pub fn new(language: &str) -> Self {
+        Self {
+            syntax: syntax_for(language),
+            in_block_comment: false,
+        }
+    }
+
+    /// Whether the language is known; unknown languages pass through as is.
+    pub fn is_supported(&self) -> bool {
+        self.syntax.is_some()
+    }
+
+    /// Highlight one line of code with ANSI styles.
+    pub fn highlight_line(&mut self, line: &str) -> String {
+        let Some(syntax) = self.syntax else {
+            return line.to_string();
+        };
+
+        let keyword = Style::new().magenta().force_styling(true);
+        let string = Style::new().green().force_styling(true);
+        let comment = Style::new().dim().force_styling(true);
+        let number = Style::new().yellow().force_styling(true);
+
+        let mut out = String::with_capacity(line.len());
+        let mut rest = line;
+
+        while let Some(c) = rest.chars().next() {
+            let len = if self.in_block_comment {
+                let (_, end) = syntax.block_comment.unwrap_or_default();
+                match rest.find(end) {
+                    Some(i) => {
+                        self.in_block_comment = false;
+                        i + end.len()
+                    }
+                    None => rest.len(),
+                }
+            } else if syntax.line_comments.iter().any(|p| rest.starts_with(p)) {
+                rest.len()
+            } else if let Some((start, _)) =
+                syntax.block_comment.filter(|(start, _)| rest.starts_with(start))
+            {
+                self.in_block_comment = true;
+                start.len()
+            } else {
+                let len = token_len(rest, c, syntax);
+                let token = &rest[..len];
+                if syntax.quotes.contains(&c) {
+                    out.push_str(&string.apply_to(token).to_string());
+                } else if c.is_ascii_digit() {
+                    out.push_str(&number.apply_to(token).to_string());
+                } else if syntax.keywords.contains(&token) {
+                    out.push_str(&keyword.apply_to(token).to_string());
+                } else {
+                    out.push_str(token);
+                }
+                rest = &rest[len..];
+                continue;
+            };
+
+            out.push_str(&comment.apply_to(&rest[..len]).to_string());
+            rest = &rest[len..];
+        }
+
+        out
+    }
+}
+
+/// Length in bytes of the token starting with `c` at the start of `rest`.
+fn token_len(rest: &str, c: char, syntax: &Syntax) -> usize {
+    if syntax.quotes.contains(&c) {
+        // Up to and including the closing quote, skipping escapes.
+        let mut escaped = false;
+        for (i, ch) in rest.char_indices().skip(1) {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if ch == c => return i + ch.len_utf8(),
+                _ => {}
+            }
+        }
+        return rest.len();
+    }
+
+    let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+    if c.is_ascii_digit() {
+        rest.find(|ch: char| !(is_word(ch) || ch == '.'))
+            .unwrap_or(rest.len())
+    } else if is_word(c) {
+        rest.find(|ch: char| !is_word(ch)).unwrap_or(rest.len())
+    } else {
+        c.len_utf8()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_tokens() {
+        let mut highlighter = Highlighter::new("rust");
+        let line = highlighter.highlight_line(r#"let name = "a \" b"; // done"#);
+
+        let keyword = Style::new().magenta().force_styling(true).apply_to("let");
+        assert!(line.contains(&keyword.to_string()));
+        let string = Style::new().green().force_styling(true).apply_to(r#""a \" b""#);
+        assert!(line.contains(&string.to_string()));
+        let comment = Style::new().dim().force_styling(true).apply_to("// done");
+        assert!(line.ends_with(&comment.to_string()));
+        assert_eq!(console::strip_ansi_codes(&line), r#"let name = "a \" b"; // done"#);
+    }
+
+    #[test]
+    fn test_block_comment_spans_lines() {
+        let mut highlighter = Highlighter::new("js");
+        highlighter.highlight_line("const x = 1; /* start");
+        assert!(highlighter.in_block_comment);
+
+        let line = highlighter.highlight_line("still comment */ let");
+        assert!(!highlighter.in_block_comment);
+        let comment = Style::new().dim().force_styling(true).apply_to("still comment */");
+        assert!(line.starts_with(&comment.to_string()));
+    }
+
+    #[test]
+    fn test_unknown_language_is_plain() {
+        let mut highlighter = Highlighter::new("brainfuck");
+        assert!(!highlighter.is_supported());
+        assert_eq!(highlighter.highlight_line("+[->+<]"), "+[->+<]");
+    }
+}
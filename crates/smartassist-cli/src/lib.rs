@@ -1,6 +1,7 @@
 //! SmartAssist command-line interface.
 
 pub mod commands;
+pub mod highlight;
 pub mod onboard;
 pub mod render;
 pub mod repl;
@@ -13,6 +13,7 @@ async fn main() -> anyhow::Result<()> {
                 .unwrap_or_else(|_| "smartassist=info".into()),
         )
         .with(tracing_subscriber::fmt::layer())
+        .with(smartassist_gateway::LogLayer::global())
         .init();
 
     // Parse CLI arguments
@@ -2,14 +2,272 @@
 //!
 //! Provides markdown rendering, tool status display, and token usage formatting.
 
-use console::style;
+use crate::highlight::Highlighter;
+use console::{style, Alignment, Style};
 use smartassist_core::types::TokenUsage;
 
+/// Width assumed when stdout is not a terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+/// Narrowest a table column is squeezed to when fitting the terminal.
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// How markdown is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Emit ANSI colors and styles.
+    pub color: bool,
+    /// Terminal width in columns.
+    pub width: usize,
+}
+
+impl RenderOptions {
+    /// Detect options for stdout.
+    ///
+    /// Color is enabled only for a terminal, and `NO_COLOR`/`CLICOLOR` are
+    /// respected.
+    pub fn detect() -> Self {
+        let width = console::Term::stdout()
+            .size_checked()
+            .map_or(DEFAULT_WIDTH, |(_, cols)| cols as usize);
+        Self {
+            color: console::colors_enabled(),
+            width,
+        }
+    }
+}
+
 /// Render markdown text to the terminal.
 pub fn render_markdown(text: &str) {
-    // Use termimad for markdown rendering
-    let skin = termimad::MadSkin::default();
-    skin.print_text(text);
+    print!("{}", format_markdown(text, RenderOptions::detect()));
+}
+
+/// Format markdown for the terminal.
+///
+/// Fenced code blocks are syntax-highlighted and tables are laid out with
+/// aligned columns that fit the width. With color enabled the remaining
+/// text is styled by termimad; without it, text and code pass through
+/// unchanged so piped output stays plain markdown.
+pub fn format_markdown(text: &str, options: RenderOptions) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::new();
+    let mut prose: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        if let Some(fence) = opening_fence(lines[i]) {
+            out.push_str(&format_prose(&prose, options));
+            prose.clear();
+
+            let end = lines[i + 1..]
+                .iter()
+                .position(|line| closes_fence(line, fence))
+                .map_or(lines.len(), |offset| i + 1 + offset);
+            let language = lines[i].trim_start()[fence.len()..].trim();
+            out.push_str(&format_code_block(language, &lines[i + 1..end], options, &lines[i..]));
+            i = end + 1;
+        } else if is_table_start(&lines[i..]) {
+            out.push_str(&format_prose(&prose, options));
+            prose.clear();
+
+            let end = lines[i..]
+                .iter()
+                .position(|line| line.trim().is_empty() || !line.contains('|'))
+                .map_or(lines.len(), |offset| i + offset);
+            out.push_str(&format_table(&lines[i..end], options));
+            i = end;
+        } else {
+            prose.push(lines[i]);
+            i += 1;
+        }
+    }
+
+    out.push_str(&format_prose(&prose, options));
+    out
+}
+
+fn format_prose(lines: &[&str], options: RenderOptions) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+    let text = lines.join("\n");
+    if options.color {
+        termimad::MadSkin::default()
+            .text(&text, Some(options.width))
+            .to_string()
+    } else {
+        text + "\n"
+    }
+}
+
+/// The fence (e.g. "```") if the line opens a fenced code block.
+fn opening_fence(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ['`', '~'].into_iter().find_map(|c| {
+        let len = trimmed.len() - trimmed.trim_start_matches(c).len();
+        (len >= 3).then(|| &trimmed[..len])
+    })
+}
+
+fn closes_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    opening_fence(trimmed).is_some_and(|f| f.starts_with(fence) && f.len() == trimmed.len())
+}
+
+/// Format a code block. `source` starts at the opening fence and is used
+/// verbatim when color is off.
+fn format_code_block(
+    language: &str,
+    code: &[&str],
+    options: RenderOptions,
+    source: &[&str],
+) -> String {
+    if !options.color {
+        let fenced = source.len().min(code.len() + 2);
+        return source[..fenced].iter().map(|line| format!("{}\n", line)).collect();
+    }
+
+    let border = Style::new().dim().force_styling(true);
+    let mut highlighter = Highlighter::new(language);
+    let mut out = format!("{}\n", border.apply_to(format!("┌ {}", language).trim_end()));
+    for line in code {
+        out.push_str(&format!(
+            "{} {}\n",
+            border.apply_to("│"),
+            highlighter.highlight_line(line)
+        ));
+    }
+    out.push_str(&format!("{}\n", border.apply_to("└")));
+    out
+}
+
+/// Whether a table (header row then delimiter row) starts here.
+fn is_table_start(lines: &[&str]) -> bool {
+    let [header, delimiter, ..] = lines else {
+        return false;
+    };
+    let delimiter = delimiter.trim();
+    header.contains('|')
+        && delimiter.contains('|')
+        && delimiter.contains('-')
+        && delimiter.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Split a table row into trimmed cells, honoring `\|` escapes.
+fn split_row(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = if line.ends_with('|') && !line.ends_with("\\|") {
+        &line[..line.len() - 1]
+    } else {
+        line
+    };
+
+    let mut cells = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cells.last_mut().unwrap().push('|');
+                chars.next();
+            }
+            '|' => cells.push(String::new()),
+            _ => cells.last_mut().unwrap().push(c),
+        }
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+fn column_alignment(delimiter: &str) -> Alignment {
+    match (delimiter.starts_with(':'), delimiter.ends_with(':')) {
+        (true, true) => Alignment::Center,
+        (false, true) => Alignment::Right,
+        _ => Alignment::Left,
+    }
+}
+
+/// Shrink the widest columns until the table fits in `width`.
+fn fit_columns(widths: &mut [usize], width: usize) {
+    // "| " + " | " between cells + " |"
+    let overhead = 3 * widths.len() + 1;
+    let available = width.saturating_sub(overhead);
+    while widths.iter().sum::<usize>() > available {
+        let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN_WIDTH).max() else {
+            break;
+        };
+        *widest -= 1;
+    }
+}
+
+/// Pad a cell to `width`, or cut it short with an ellipsis if too wide.
+fn fit_cell(cell: &str, width: usize, align: Alignment) -> String {
+    if console::measure_text_width(cell) > width {
+        console::truncate_str(cell, width, "…").into_owned()
+    } else {
+        console::pad_str(cell, width, align, None).into_owned()
+    }
+}
+
+fn format_table(lines: &[&str], options: RenderOptions) -> String {
+    let header = split_row(lines[0]);
+    let alignments: Vec<Alignment> = split_row(lines[1])
+        .iter()
+        .map(|d| column_alignment(d))
+        .collect();
+    let rows: Vec<Vec<String>> = lines[2..].iter().map(|line| split_row(line)).collect();
+
+    let columns = rows.iter().map(Vec::len).chain([header.len()]).max().unwrap_or(0);
+    let mut widths = vec![MIN_COLUMN_WIDTH; columns];
+    for row in rows.iter().chain([&header]) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(console::measure_text_width(cell));
+        }
+    }
+    fit_columns(&mut widths, options.width);
+
+    let cells = |row: &[String]| -> Vec<String> {
+        (0..columns)
+            .map(|c| {
+                let cell = row.get(c).map_or("", String::as_str);
+                let align = alignments.get(c).copied().unwrap_or(Alignment::Left);
+                fit_cell(cell, widths[c], align)
+            })
+            .collect()
+    };
+
+    let mut out = String::new();
+    if options.color {
+        let border = Style::new().dim().force_styling(true);
+        let bold = Style::new().bold().force_styling(true);
+        let separator = border.apply_to(" │ ").to_string();
+
+        let header: Vec<String> = cells(&header)
+            .iter()
+            .map(|cell| bold.apply_to(cell).to_string())
+            .collect();
+        out.push_str(&format!(" {}\n", header.join(&separator)));
+        let rule: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        out.push_str(&format!("{}\n", border.apply_to(rule.join("┼"))));
+        for row in &rows {
+            out.push_str(&format!(" {}\n", cells(row).join(&separator)));
+        }
+    } else {
+        out.push_str(&format!("| {} |\n", cells(&header).join(" | ")));
+        let rule: Vec<String> = widths
+            .iter()
+            .zip(alignments.iter().chain(std::iter::repeat(&Alignment::Left)))
+            .map(|(&w, align)| match align {
+                Alignment::Left => "-".repeat(w),
+                Alignment::Right => format!("{}:", "-".repeat(w - 1)),
+                Alignment::Center => format!(":{}:", "-".repeat(w - 2)),
+            })
+            .collect();
+        out.push_str(&format!("| {} |\n", rule.join(" | ")));
+        for row in &rows {
+            out.push_str(&format!("| {} |\n", cells(row).join(" | ")));
+        }
+    }
+    out
 }
 
 /// Tool execution status.
@@ -103,3 +361,76 @@ pub fn render_help() {
     eprintln!("  {} - Trigger context compaction", style("/compact").cyan());
     eprintln!();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: RenderOptions = RenderOptions {
+        color: true,
+        width: 80,
+    };
+    const PLAIN: RenderOptions = RenderOptions {
+        color: false,
+        width: 80,
+    };
+
+    #[test]
+    fn test_code_block_highlighted_in_tty_mode() {
+        let text = "Try this:\n\n```rust\nfn main() {\n    let x = 42;\n}\n```\n";
+        let out = format_markdown(text, COLOR);
+
+        assert!(out.contains("\x1b["));
+        let keyword = Style::new().magenta().force_styling(true).apply_to("fn");
+        assert!(out.contains(&keyword.to_string()));
+        let plain = console::strip_ansi_codes(&out);
+        assert!(plain.contains("┌ rust"));
+        assert!(plain.contains("│     let x = 42;"));
+        assert!(!plain.contains("```"));
+    }
+
+    #[test]
+    fn test_no_color_is_plain() {
+        let text = "Try this:\n\n```rust\nfn main() {}\n```\nDone.";
+        let out = format_markdown(text, PLAIN);
+
+        assert!(!out.contains('\x1b'));
+        assert_eq!(out, "Try this:\n\n```rust\nfn main() {}\n```\nDone.\n");
+    }
+
+    #[test]
+    fn test_table_columns_aligned() {
+        let text = "| Name | Qty | Note |\n|---|--:|:-:|\n| apple | 3 | ripe |\n| kiwi | 12 |\n";
+        let out = format_markdown(text, PLAIN);
+
+        assert_eq!(
+            out,
+            "| Name  | Qty | Note |\n\
+             | ----- | --: | :--: |\n\
+             | apple |   3 | ripe |\n\
+             | kiwi  |  12 |      |\n"
+        );
+    }
+
+    #[test]
+    fn test_table_fits_width() {
+        let long = "word ".repeat(30);
+        let text = format!("| Key | Description |\n| --- | --- |\n| a | {} |\n", long.trim());
+        let options = RenderOptions {
+            color: true,
+            width: 40,
+        };
+
+        let out = format_markdown(&text, options);
+        for line in out.lines() {
+            assert!(console::measure_text_width(line) <= 40, "too wide: {:?}", line);
+        }
+        assert!(out.contains('…'));
+    }
+
+    #[test]
+    fn test_split_row_escapes() {
+        assert_eq!(split_row(r"| a \| b | c |"), ["a | b", "c"]);
+        assert_eq!(split_row("x|y"), ["x", "y"]);
+    }
+}
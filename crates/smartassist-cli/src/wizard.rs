@@ -2,7 +2,7 @@
 //!
 //! Multi-step interactive wizard for configuring SmartAssist. Steps:
 //! 1. Provider selection
-//! 2. API key setup
+//! 2. API key setup (verified against the provider before it is stored)
 //! 3. Model selection
 //! 4. Agent configuration (name, thinking level)
 //! 5. Channel setup (optional, skipped with `--quick`)
@@ -13,6 +13,7 @@ use console::style;
 use smartassist_core::config::{self, ConfigBuilder};
 use smartassist_core::paths;
 use smartassist_core::types::{AgentConfig, AgentId, ThinkingLevel};
+use smartassist_providers::{ChatOptions, Message, Provider as ModelProvider};
 use smartassist_secrets::{FileSecretStore, SecretStore};
 use std::io::{self, Write};
 
@@ -107,6 +108,39 @@ impl Provider {
     fn model_in_config_format(&self, model: &str) -> String {
         format!("{}/{}", self.config_key(), model)
     }
+
+    /// Build a client for this provider with the given API key.
+    fn connect(&self, api_key: &str) -> anyhow::Result<Box<dyn ModelProvider>> {
+        use smartassist_providers::{anthropic, google, openai};
+
+        let client: Box<dyn ModelProvider> = match self {
+            Self::Anthropic => Box::new(anthropic::AnthropicProvider::new(api_key)?),
+            Self::OpenAI => Box::new(openai::OpenAIProvider::new(api_key)?),
+            Self::Google => Box::new(google::GoogleProvider::new(api_key)?),
+            Self::Ollama => anyhow::bail!("Ollama does not use an API key"),
+        };
+        Ok(client)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Key verification
+// ---------------------------------------------------------------------------
+
+/// Confirm that a provider accepts its credentials.
+///
+/// Sends a one-token completion, since some providers (Anthropic) serve
+/// their model list without authenticating.
+async fn verify_provider(client: &dyn ModelProvider, model: &str) -> anyhow::Result<()> {
+    let options = ChatOptions {
+        max_tokens: Some(1),
+        ..Default::default()
+    };
+    client
+        .chat(model, &[Message::user("ping")], Some(options))
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("{} rejected the request: {}", client.name(), e))
 }
 
 // ---------------------------------------------------------------------------
@@ -354,24 +388,50 @@ impl SetupWizard {
             }
         }
 
-        // Prompt for API key
+        // Prompt for API key until the provider accepts it
         let prompt_msg = format!("Enter your {} API key: ", provider.config_key());
-        let api_key = prompt_secret(&prompt_msg)?;
+        let (model, _) = provider.models()[0];
+        let api_key = loop {
+            let api_key = prompt_secret(&prompt_msg)?;
 
-        if api_key.is_empty() {
-            anyhow::bail!("API key must not be empty");
-        }
+            if api_key.is_empty() {
+                anyhow::bail!("API key must not be empty");
+            }
 
-        // Validate prefix
-        if let Some(prefix) = provider.api_key_prefix() {
-            if !api_key.starts_with(prefix) {
-                eprintln!(
-                    "  {} Key doesn't start with '{}'. It may be invalid.",
-                    style("!").yellow(),
-                    prefix,
-                );
+            // Validate prefix
+            if let Some(prefix) = provider.api_key_prefix() {
+                if !api_key.starts_with(prefix) {
+                    eprintln!(
+                        "  {} Key doesn't start with '{}'. It may be invalid.",
+                        style("!").yellow(),
+                        prefix,
+                    );
+                }
             }
-        }
+
+            eprint!("  Verifying key... ");
+            io::stderr().flush()?;
+            let verified = match provider.connect(&api_key) {
+                Ok(client) => verify_provider(client.as_ref(), model).await,
+                Err(e) => Err(e),
+            };
+            match verified {
+                Ok(()) => {
+                    eprintln!("{}", style("ok").green());
+                    break api_key;
+                }
+                Err(e) => {
+                    eprintln!("{} {}", style("failed:").red(), e);
+                    if !prompt_yes_no("Try another key?", true)? {
+                        eprintln!(
+                            "  {} Keeping the unverified key.",
+                            style("!").yellow()
+                        );
+                        break api_key;
+                    }
+                }
+            }
+        };
 
         // Store via smartassist-secrets
         let store = FileSecretStore::from_default_dir()
@@ -817,7 +877,123 @@ impl SetupWizard {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
     use smartassist_core::config::Config;
+    use smartassist_providers::{
+        ChatResponse, CompletionStream, ModelInfo, ProviderCapabilities, ProviderError,
+        StopReason, TokenCount, Usage,
+    };
+    use std::sync::Mutex;
+
+    /// Provider that accepts a single API key.
+    struct MockProvider {
+        api_key: String,
+        valid_key: &'static str,
+        requests: Mutex<Vec<(String, Option<usize>)>>,
+    }
+
+    impl MockProvider {
+        fn new(api_key: &str) -> Self {
+            Self {
+                api_key: api_key.to_string(),
+                valid_key: "sk-ant-good",
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ModelProvider for MockProvider {
+        fn name(&self) -> &str {
+            "mock"
+        }
+
+        async fn list_models(&self) -> smartassist_providers::Result<Vec<ModelInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn chat(
+            &self,
+            model: &str,
+            _messages: &[Message],
+            options: Option<ChatOptions>,
+        ) -> smartassist_providers::Result<ChatResponse> {
+            let max_tokens = options.and_then(|o| o.max_tokens);
+            self.requests
+                .lock()
+                .unwrap()
+                .push((model.to_string(), max_tokens));
+            if self.api_key != self.valid_key {
+                return Err(ProviderError::auth("invalid x-api-key"));
+            }
+            Ok(ChatResponse {
+                id: "msg_1".to_string(),
+                model: model.to_string(),
+                content: String::new(),
+                tool_calls: Vec::new(),
+                stop_reason: StopReason::MaxTokens,
+                usage: Usage::default(),
+                metadata: Default::default(),
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _model: &str,
+            _messages: &[Message],
+            _options: Option<ChatOptions>,
+        ) -> smartassist_providers::Result<CompletionStream> {
+            Err(ProviderError::unsupported("chat_stream"))
+        }
+
+        async fn count_tokens(
+            &self,
+            model: &str,
+            _messages: &[Message],
+        ) -> smartassist_providers::Result<TokenCount> {
+            Ok(TokenCount {
+                count: 0,
+                model: model.to_string(),
+            })
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_provider_accepts_valid_key() {
+        let client = MockProvider::new("sk-ant-good");
+        verify_provider(&client, "claude-haiku-4-5-20251001")
+            .await
+            .unwrap();
+
+        let requests = client.requests.lock().unwrap();
+        assert_eq!(
+            *requests,
+            [("claude-haiku-4-5-20251001".to_string(), Some(1))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_provider_reports_rejected_key() {
+        let client = MockProvider::new("sk-ant-bad");
+        let err = verify_provider(&client, "claude-haiku-4-5-20251001")
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("mock rejected the request"), "{}", err);
+        assert!(err.contains("invalid x-api-key"), "{}", err);
+    }
+
+    #[test]
+    fn test_provider_connect() {
+        assert!(Provider::Anthropic.connect("sk-ant-test").is_ok());
+        assert!(Provider::OpenAI.connect("").is_err());
+        assert!(Provider::Ollama.connect("anything").is_err());
+    }
 
     #[test]
     fn test_provider_model_config_format() {
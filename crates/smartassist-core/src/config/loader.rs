@@ -4,7 +4,7 @@ use super::Config;
 use crate::error::ConfigError;
 use crate::paths;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 impl Config {
     /// Load configuration from the default path.
@@ -428,6 +428,59 @@ impl ConfigBuilder {
     }
 }
 
+/// Watches a config file for edits by polling it.
+///
+/// Each [`poll`](Self::poll) reads the file and, when its contents differ
+/// from the previous read, parses and validates them. Contents that fail to
+/// load are reported once; the next report comes after the file changes
+/// again.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last: Option<String>,
+}
+
+impl ConfigWatcher {
+    /// Watch the file at `path`. The first poll loads it.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            last: None,
+        }
+    }
+
+    /// Get the watched path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check the file for changes.
+    ///
+    /// Returns the new config if the file changed and is valid, `None` if it
+    /// is unchanged, and an error if it changed but cannot be used.
+    pub fn poll(&mut self) -> Result<Option<Config>, ConfigError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if self.last.take().is_some() {
+                    return Err(ConfigError::NotFound(self.path.clone()));
+                }
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if self.last.as_deref() == Some(content.as_str()) {
+            return Ok(None);
+        }
+
+        let config = Config::parse(&content);
+        self.last = Some(content);
+        let config = config?;
+        config.validate()?;
+        Ok(Some(config))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -848,4 +901,43 @@ mod tests {
         assert_eq!(config.routing.bindings.len(), 1);
         assert_eq!(config.routing.bindings[0].agent_id, "main");
     }
+
+    #[test]
+    fn test_watcher_reports_valid_edits_once() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json5");
+        fs::write(&path, r#"{ gateway: { port: 7000 } }"#).unwrap();
+        let mut watcher = ConfigWatcher::new(&path);
+
+        let config = watcher.poll().unwrap().unwrap();
+        assert_eq!(config.gateway.port, 7000);
+        assert!(watcher.poll().unwrap().is_none());
+
+        fs::write(&path, r#"{ gateway: { port: 7001 } }"#).unwrap();
+        let config = watcher.poll().unwrap().unwrap();
+        assert_eq!(config.gateway.port, 7001);
+        assert!(watcher.poll().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_watcher_rejects_invalid_edits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json5");
+        fs::write(&path, r#"{ gateway: { port: 7000 } }"#).unwrap();
+        let mut watcher = ConfigWatcher::new(&path);
+        watcher.poll().unwrap().unwrap();
+
+        fs::write(&path, r#"{ gateway: { port: 0 } }"#).unwrap();
+        let err = watcher.poll().unwrap_err();
+        assert!(matches!(err, ConfigError::Validation(_)), "{}", err);
+        // The same broken contents are not reported again.
+        assert!(watcher.poll().unwrap().is_none());
+
+        fs::write(&path, "{ gateway: ").unwrap();
+        assert!(matches!(watcher.poll(), Err(ConfigError::Json5(_))));
+
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(watcher.poll(), Err(ConfigError::NotFound(_))));
+        assert!(watcher.poll().unwrap().is_none());
+    }
 }
@@ -2,6 +2,8 @@
 
 mod schema;
 mod loader;
+mod secret_refs;
 
 pub use schema::*;
 pub use loader::*;
+pub use secret_refs::*;
@@ -59,6 +59,11 @@ pub struct AgentsConfig {
     /// Default settings for all agents.
     #[serde(default)]
     pub defaults: AgentDefaults,
+
+    /// Named system-prompt presets, in the format of the agent crate's
+    /// `PromptLibrary`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prompts: HashMap<String, serde_json::Value>,
 }
 
 /// Default agent settings.
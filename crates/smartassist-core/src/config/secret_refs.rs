@@ -0,0 +1,240 @@
+//! `secret://` references in configuration values.
+//!
+//! Any string value in the config may be written as `secret://namespace/name`
+//! to stand for a secret held in the secret store, so the file on disk never
+//! contains the secret itself. The reference names the store entry
+//! `namespace_name`, matching the names the setup wizard stores tokens under
+//! (`secret://telegram/bot_token` is `telegram_bot_token`).
+//!
+//! This module only finds and substitutes references; reading the store is
+//! done by `smartassist-secrets`, which builds on this crate.
+
+use super::Config;
+use crate::error::ConfigError;
+use crate::secret::SecretString;
+use serde_json::Value;
+use std::fmt;
+
+/// Prefix marking a config value as a secret reference.
+pub const SECRET_REF_SCHEME: &str = "secret://";
+
+/// A parsed `secret://namespace/name` reference.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecretUri {
+    /// Namespace, usually the channel or provider the secret belongs to.
+    pub namespace: String,
+
+    /// Name within the namespace.
+    pub name: String,
+}
+
+impl SecretUri {
+    /// Parse a reference.
+    ///
+    /// Returns `None` for values without the `secret://` prefix and an error
+    /// for ones that have it but are malformed.
+    pub fn parse(value: &str) -> Option<Result<Self, String>> {
+        let rest = value.strip_prefix(SECRET_REF_SCHEME)?;
+        let valid = |part: &str| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        };
+        Some(match rest.split_once('/') {
+            Some((namespace, name)) if valid(namespace) && valid(name) => Ok(Self {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+            }),
+            _ => Err(format!(
+                "invalid secret reference '{}': expected {}namespace/name",
+                value, SECRET_REF_SCHEME
+            )),
+        })
+    }
+
+    /// Name of the secret in the store.
+    pub fn store_name(&self) -> String {
+        format!("{}_{}", self.namespace, self.name)
+    }
+}
+
+impl fmt::Display for SecretUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/{}", SECRET_REF_SCHEME, self.namespace, self.name)
+    }
+}
+
+/// A secret reference and where it appears in the config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigSecretRef {
+    /// Dotted path to the value, e.g. `channels.telegram.accounts.main.bot_token`.
+    pub path: String,
+
+    /// The reference.
+    pub uri: SecretUri,
+}
+
+impl Config {
+    /// List every secret reference in the config, in document order.
+    pub fn secret_refs(&self) -> Result<Vec<ConfigSecretRef>, ConfigError> {
+        let mut value = self.to_value()?;
+        let mut refs = Vec::new();
+        visit_strings(&mut value, String::new(), &mut |path, text| {
+            if let Some(uri) = parse_at(path, text)? {
+                refs.push(ConfigSecretRef {
+                    path: path.to_string(),
+                    uri,
+                });
+            }
+            Ok(())
+        })?;
+        Ok(refs)
+    }
+
+    /// Return a copy of the config with every secret reference replaced by
+    /// the secret `lookup` returns for it.
+    ///
+    /// A reference `lookup` has no secret for is an error naming its path.
+    pub fn resolve_secret_refs(
+        &self,
+        mut lookup: impl FnMut(&SecretUri) -> Option<SecretString>,
+    ) -> Result<Config, ConfigError> {
+        let mut value = self.to_value()?;
+        visit_strings(&mut value, String::new(), &mut |path, text| {
+            if let Some(uri) = parse_at(path, text)? {
+                let secret = lookup(&uri).ok_or_else(|| {
+                    ConfigError::Validation(format!(
+                        "{}: secret '{}' not found (looked for '{}')",
+                        path,
+                        uri,
+                        uri.store_name()
+                    ))
+                })?;
+                *text = secret.expose_secret().to_string();
+            }
+            Ok(())
+        })?;
+        serde_json::from_value(value).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    fn to_value(&self) -> Result<Value, ConfigError> {
+        serde_json::to_value(self).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+}
+
+/// Parse `text` as a secret reference found at `path`.
+fn parse_at(path: &str, text: &str) -> Result<Option<SecretUri>, ConfigError> {
+    SecretUri::parse(text)
+        .transpose()
+        .map_err(|e| ConfigError::Validation(format!("{}: {}", path, e)))
+}
+
+/// Call `f` with the path and value of every string in `value`.
+fn visit_strings(
+    value: &mut Value,
+    path: String,
+    f: &mut dyn FnMut(&str, &mut String) -> Result<(), ConfigError>,
+) -> Result<(), ConfigError> {
+    match value {
+        Value::String(text) => f(&path, text),
+        Value::Array(items) => items
+            .iter_mut()
+            .enumerate()
+            .try_for_each(|(i, item)| visit_strings(item, format!("{}[{}]", path, i), f)),
+        Value::Object(map) => map.iter_mut().try_for_each(|(key, item)| {
+            let path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            visit_strings(item, path, f)
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        channels: {
+            telegram: {
+                accounts: {
+                    main: { bot_token: "secret://telegram/bot_token", username: "bot" },
+                },
+            },
+        },
+        gateway: { control_ui: { auth: { token: "secret://gateway/token" } } },
+    }"#;
+
+    #[test]
+    fn test_parse_secret_uri() {
+        let uri = SecretUri::parse("secret://telegram/bot_token").unwrap().unwrap();
+        assert_eq!(uri.namespace, "telegram");
+        assert_eq!(uri.name, "bot_token");
+        assert_eq!(uri.store_name(), "telegram_bot_token");
+        assert_eq!(uri.to_string(), "secret://telegram/bot_token");
+
+        assert!(SecretUri::parse("plain-token").is_none());
+        for bad in ["secret://telegram", "secret:///x", "secret://a/b/c", "secret://a/b c"] {
+            assert!(SecretUri::parse(bad).unwrap().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_secret_refs_lists_paths() {
+        let config = Config::parse(CONFIG).unwrap();
+        let mut refs = config.secret_refs().unwrap();
+        refs.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let paths: Vec<_> = refs.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["channels.telegram.accounts.main.bot_token", "gateway.control_ui.auth.token"]
+        );
+        assert_eq!(refs[0].uri.store_name(), "telegram_bot_token");
+    }
+
+    #[test]
+    fn test_resolve_secret_refs() {
+        let config = Config::parse(CONFIG).unwrap();
+        let resolved = config
+            .resolve_secret_refs(|uri| Some(SecretString::new(format!("value-of-{}", uri.name))))
+            .unwrap();
+
+        let account = &resolved.channels.telegram.as_ref().unwrap().accounts["main"];
+        assert_eq!(account.bot_token.expose_secret(), "value-of-bot_token");
+        assert_eq!(account.username.as_deref(), Some("bot"));
+        let token = resolved.gateway.control_ui.auth.token.as_ref().unwrap();
+        assert_eq!(token.expose_secret(), "value-of-token");
+
+        // The loaded config keeps the reference, not the secret.
+        assert_eq!(config.secret_refs().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_missing_secret_names_path() {
+        let config = Config::parse(CONFIG).unwrap();
+        let err = config
+            .resolve_secret_refs(|uri| {
+                (uri.namespace == "gateway").then(|| SecretString::new("t"))
+            })
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("channels.telegram.accounts.main.bot_token"), "{}", err);
+        assert!(err.contains("secret://telegram/bot_token"), "{}", err);
+    }
+
+    #[test]
+    fn test_malformed_ref_is_an_error() {
+        let config = Config::parse(
+            r#"{ channels: { telegram: { accounts: { main: { bot_token: "secret://oops" } } } } }"#,
+        )
+        .unwrap();
+        let err = config.secret_refs().unwrap_err().to_string();
+        assert!(err.contains("channels.telegram.accounts.main.bot_token"), "{}", err);
+    }
+}
@@ -1,5 +1,6 @@
 //! ID generation utilities.
 
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
@@ -8,6 +9,27 @@ pub fn uuid() -> String {
     Uuid::new_v4().to_string()
 }
 
+/// Generate a new time-ordered UUID v7.
+///
+/// The id starts with the current Unix time in milliseconds, so ids sort
+/// in creation order. Ids generated by the same process are strictly
+/// increasing, even within one millisecond.
+pub fn uuid_v7() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// Get the creation time encoded in a UUID v7.
+///
+/// Returns `None` if `id` is not a valid UUID or not version 7.
+pub fn uuid_v7_timestamp(id: &str) -> Option<DateTime<Utc>> {
+    let uuid = Uuid::parse_str(id).ok()?;
+    if uuid.get_version() != Some(uuid::Version::SortRand) {
+        return None;
+    }
+    let (secs, nanos) = uuid.get_timestamp()?.to_unix();
+    DateTime::from_timestamp(secs as i64, nanos)
+}
+
 /// Generate a short random ID (8 characters).
 pub fn short_id() -> String {
     let bytes: [u8; 4] = rand::random();
@@ -70,6 +92,26 @@ mod tests {
         assert!(id.contains('-'));
     }
 
+    #[test]
+    fn test_uuid_v7_is_monotonic() {
+        let ids: Vec<String> = (0..1000).map(|_| uuid_v7()).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+        assert!(ids.iter().all(|id| id.len() == 36));
+    }
+
+    #[test]
+    fn test_uuid_v7_timestamp() {
+        let before = Utc::now().timestamp_millis();
+        let id = uuid_v7();
+        let after = Utc::now().timestamp_millis();
+
+        let ts = uuid_v7_timestamp(&id).unwrap().timestamp_millis();
+        assert!(before <= ts && ts <= after, "{} not in {}..={}", ts, before, after);
+
+        assert_eq!(uuid_v7_timestamp(&uuid()), None);
+        assert_eq!(uuid_v7_timestamp("not-a-uuid"), None);
+    }
+
     #[test]
     fn test_short_id() {
         let id = short_id();
@@ -17,6 +17,7 @@ pub mod id;
 pub mod secret;
 pub mod safety;
 pub mod context;
+pub mod template;
 
 // Re-exports for convenience
 pub use config::Config;
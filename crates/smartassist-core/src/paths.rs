@@ -26,6 +26,11 @@ pub fn models_file() -> Result<PathBuf, ConfigError> {
     Ok(base_dir()?.join("models.json"))
 }
 
+/// Get the cron jobs file path (~/.smartassist/cron.json).
+pub fn cron_file() -> Result<PathBuf, ConfigError> {
+    Ok(base_dir()?.join("cron.json"))
+}
+
 /// Get the sessions directory (~/.smartassist/sessions).
 pub fn sessions_dir() -> Result<PathBuf, ConfigError> {
     Ok(base_dir()?.join("sessions"))
@@ -51,6 +56,16 @@ pub fn plugins_dir() -> Result<PathBuf, ConfigError> {
     Ok(base_dir()?.join("plugins"))
 }
 
+/// Get the installed skills directory (~/.smartassist/skills).
+pub fn skills_dir() -> Result<PathBuf, ConfigError> {
+    Ok(base_dir()?.join("skills"))
+}
+
+/// Get the channel state directory (~/.smartassist/channels).
+pub fn channels_dir() -> Result<PathBuf, ConfigError> {
+    Ok(base_dir()?.join("channels"))
+}
+
 /// Get an agent's directory (~/.smartassist/agents/{agent_id}).
 pub fn agent_dir(agent_id: &str) -> Result<PathBuf, ConfigError> {
     Ok(agents_dir()?.join(agent_id))
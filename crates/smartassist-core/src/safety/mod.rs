@@ -6,6 +6,7 @@
 //! - **LeakDetector**: Secret and credential leak scanning
 //! - **Validator**: Input validation (length, null bytes, whitespace, repetition)
 //! - **SafetyPolicy**: Rule-based policy engine for content analysis
+//! - **StreamingLeakScanner**: Leak scanning for output that arrives in chunks
 //!
 //! These components are orchestrated by [`SafetyLayer`], which runs all checks
 //! on tool inputs and outputs.
@@ -13,15 +14,18 @@
 pub mod leak_detector;
 pub mod policy;
 pub mod sanitizer;
+pub mod streaming;
 pub mod validator;
 
 // Re-export public types from sub-modules
 pub use leak_detector::{LeakAction, LeakDetector, LeakMatch};
 pub use policy::{PolicyMatch, PolicyRule, SafetyPolicy};
 pub use sanitizer::{InjectionMatch, Sanitizer};
+pub use streaming::StreamingLeakScanner;
 pub use validator::{Validator, ValidatorConfig};
 
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 use crate::error::SecurityError;
 
@@ -47,6 +51,35 @@ pub enum PolicyAction {
     Sanitize,
 }
 
+/// How checked tool output is wrapped before it reaches the model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OutputWrapMode {
+    /// Pass the output through unwrapped.
+    None,
+    /// Wrap the output in `<tool_output tool="...">` boundary tags.
+    Xml,
+    /// Wrap the output in an object `{ "tool": name, <key>: output }`.
+    Json {
+        /// Key holding the output.
+        #[serde(default = "default_json_key")]
+        key: String,
+    },
+}
+
+fn default_json_key() -> String {
+    "output".to_string()
+}
+
+impl OutputWrapMode {
+    /// JSON wrapping under the default `output` key.
+    pub fn json() -> Self {
+        Self::Json {
+            key: default_json_key(),
+        }
+    }
+}
+
 /// Configuration for the safety layer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafetyConfig {
@@ -56,8 +89,8 @@ pub struct SafetyConfig {
     pub max_output_length: usize,
     /// Maximum input length in bytes.
     pub max_input_length: usize,
-    /// Whether to wrap output in XML boundary tags.
-    pub wrap_output_xml: bool,
+    /// How to wrap output, after leak cleaning and truncation.
+    pub wrap_output: OutputWrapMode,
     /// Whether to run injection detection on inputs.
     pub injection_detection: bool,
     /// Whether to run leak detection on inputs/outputs.
@@ -70,7 +103,7 @@ impl Default for SafetyConfig {
             enabled: true,
             max_output_length: 100 * 1024, // 100KB
             max_input_length: 100 * 1024,  // 100KB
-            wrap_output_xml: true,
+            wrap_output: OutputWrapMode::Xml,
             injection_detection: true,
             leak_detection: true,
         }
@@ -84,7 +117,7 @@ impl Default for SafetyConfig {
 pub struct SafetyLayer {
     config: SafetyConfig,
     sanitizer: Sanitizer,
-    leak_detector: LeakDetector,
+    leak_detector: Arc<LeakDetector>,
     validator: Validator,
     policy: SafetyPolicy,
 }
@@ -100,7 +133,7 @@ impl SafetyLayer {
         Self {
             config,
             sanitizer: Sanitizer::new(),
-            leak_detector: LeakDetector::new(),
+            leak_detector: Arc::new(LeakDetector::new()),
             validator: Validator::new(validator_config),
             policy: SafetyPolicy::default(),
         }
@@ -181,8 +214,8 @@ impl SafetyLayer {
 
     /// Check tool output for safety violations.
     ///
-    /// Runs leak detection on output, optionally wraps in XML boundary tags,
-    /// and truncates if over the configured max length.
+    /// Runs leak detection on output, truncates it if over the configured
+    /// max length, then wraps it as configured by [`SafetyConfig::wrap_output`].
     pub fn check_output(
         &self,
         tool_name: &str,
@@ -213,13 +246,29 @@ impl SafetyLayer {
             });
         }
 
-        // Step 3: Optionally wrap in XML boundary tags
-        if self.config.wrap_output_xml {
-            result = wrap_in_xml_boundary(tool_name, &result);
-        }
+        // Step 3: Wrap for the model
+        result = match &self.config.wrap_output {
+            OutputWrapMode::None => result,
+            OutputWrapMode::Xml => wrap_in_xml_boundary(tool_name, &result),
+            OutputWrapMode::Json { key } => {
+                let mut wrapped = serde_json::Map::new();
+                wrapped.insert("tool".to_string(), serde_json::json!(tool_name));
+                wrapped.insert(key.clone(), result);
+                serde_json::Value::Object(wrapped)
+            }
+        };
 
         Ok(result)
     }
+
+    /// Create a scanner that redacts leaks from streamed output.
+    ///
+    /// The streaming counterpart of the leak step of [`Self::check_output`].
+    /// Returns `None` if the layer or its leak detection is disabled.
+    pub fn output_scanner(&self) -> Option<StreamingLeakScanner> {
+        (self.config.enabled && self.config.leak_detection)
+            .then(|| StreamingLeakScanner::new(self.leak_detector.clone()))
+    }
 }
 
 impl Default for SafetyLayer {
@@ -425,7 +474,7 @@ mod tests {
     fn test_check_output_truncation() {
         let config = SafetyConfig {
             max_output_length: 50,
-            wrap_output_xml: false,
+            wrap_output: OutputWrapMode::None,
             ..Default::default()
         };
         let layer = SafetyLayer::new(config);
@@ -440,7 +489,7 @@ mod tests {
     #[test]
     fn test_check_output_xml_wrapping() {
         let config = SafetyConfig {
-            wrap_output_xml: true,
+            wrap_output: OutputWrapMode::Xml,
             ..Default::default()
         };
         let layer = SafetyLayer::new(config);
@@ -454,14 +503,90 @@ mod tests {
     #[test]
     fn test_check_output_no_xml_wrapping() {
         let config = SafetyConfig {
-            wrap_output_xml: false,
+            wrap_output: OutputWrapMode::None,
             ..Default::default()
         };
         let layer = SafetyLayer::new(config);
         let output = serde_json::json!("Hello, world!");
         let result = layer.check_output("my_tool", &output).unwrap();
-        let result_str = result.as_str().unwrap();
-        assert!(!result_str.contains("<tool_output"));
+        assert_eq!(result, serde_json::json!("Hello, world!"));
+    }
+
+    #[test]
+    fn test_check_output_json_wrapping() {
+        let layer = SafetyLayer::new(SafetyConfig {
+            wrap_output: OutputWrapMode::json(),
+            ..Default::default()
+        });
+        let output = serde_json::json!({"files": ["a.txt", "b.txt"]});
+        let result = layer.check_output("list_files", &output).unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({"tool": "list_files", "output": {"files": ["a.txt", "b.txt"]}})
+        );
+
+        let layer = SafetyLayer::new(SafetyConfig {
+            wrap_output: OutputWrapMode::Json {
+                key: "result".to_string(),
+            },
+            ..Default::default()
+        });
+        let result = layer.check_output("echo", &serde_json::json!("hi")).unwrap();
+        assert_eq!(result, serde_json::json!({"tool": "echo", "result": "hi"}));
+    }
+
+    #[test]
+    fn test_check_output_cleans_and_truncates_before_wrapping() {
+        for mode in [OutputWrapMode::None, OutputWrapMode::Xml, OutputWrapMode::json()] {
+            let layer = SafetyLayer::new(SafetyConfig {
+                max_output_length: 50,
+                wrap_output: mode.clone(),
+                ..Default::default()
+            });
+            let output = serde_json::json!(format!(
+                "key sk-abcdefghijklmnopqrstuvwx {}",
+                "a".repeat(200)
+            ));
+            let result = layer.check_output("my_tool", &output).unwrap();
+            let text = serde_json::to_string(&result).unwrap();
+
+            assert!(!text.contains("sk-abcdefghijklmnopqrstuvwx"), "{:?}", mode);
+            assert!(text.contains("truncated"), "{:?}", mode);
+            // The wrapper itself is not cut off.
+            match mode {
+                OutputWrapMode::None => {}
+                OutputWrapMode::Xml => assert!(text.contains("</tool_output>")),
+                OutputWrapMode::Json { .. } => assert_eq!(result["tool"], "my_tool"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_wrap_mode_serde() {
+        let mode: OutputWrapMode = serde_json::from_str(r#"{"mode": "json"}"#).unwrap();
+        assert_eq!(mode, OutputWrapMode::json());
+        let mode: OutputWrapMode = serde_json::from_str(r#"{"mode": "xml"}"#).unwrap();
+        assert_eq!(mode, OutputWrapMode::Xml);
+        assert_eq!(
+            serde_json::to_value(OutputWrapMode::None).unwrap(),
+            serde_json::json!({"mode": "none"})
+        );
+    }
+
+    #[test]
+    fn test_output_scanner() {
+        let layer = SafetyLayer::default();
+        let mut scanner = layer.output_scanner().unwrap();
+        let mut output = scanner.push("key: sk-abcdefghij");
+        output.push_str(&scanner.push("klmnopqrstuvwx\n"));
+        output.push_str(&scanner.finish());
+        assert_eq!(output, "key: [BLOCKED: openai_api_key]\n");
+
+        let layer = SafetyLayer::new(SafetyConfig {
+            leak_detection: false,
+            ..Default::default()
+        });
+        assert!(layer.output_scanner().is_none());
     }
 
     #[test]
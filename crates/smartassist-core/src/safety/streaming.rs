@@ -0,0 +1,222 @@
+//! Incremental leak scanning for streamed output.
+//!
+//! [`LeakDetector::scan_and_clean`] needs the whole text, so a secret split
+//! across two chunks of a stream would slip through if each chunk were
+//! cleaned on its own. [`StreamingLeakScanner`] holds back the tail of the
+//! stream that could still be the start of a secret and only releases text
+//! once no match can span past it.
+
+use std::sync::Arc;
+
+use super::leak_detector::{LeakDetector, LeakMatch};
+
+/// Bytes held back from the end of the buffer by default.
+///
+/// Longer than any fixed-length secret pattern, including the separators
+/// in multi-word ones such as `Bearer <token>`.
+const DEFAULT_HOLDBACK: usize = 256;
+
+/// Buffer size past which text is released even inside an unbroken run
+/// of token characters.
+const MAX_PENDING: usize = 64 * 1024;
+
+/// Redacts secrets from a stream of text chunks.
+///
+/// Feed chunks to [`push`](Self::push) and forward what it returns, then
+/// call [`finish`](Self::finish) at the end of the stream to release the
+/// held-back remainder.
+///
+/// While the buffer stays under 64 KiB the concatenated output equals what
+/// [`LeakDetector::scan_and_clean`] would produce for the whole text. An
+/// unbroken run of token characters longer than that is released in
+/// pieces, so a secret inside it can be split and missed.
+pub struct StreamingLeakScanner {
+    detector: Arc<LeakDetector>,
+    pending: String,
+    holdback: usize,
+    matches: Vec<LeakMatch>,
+}
+
+impl StreamingLeakScanner {
+    /// Create a scanner using `detector`.
+    pub fn new(detector: Arc<LeakDetector>) -> Self {
+        Self {
+            detector,
+            pending: String::new(),
+            holdback: DEFAULT_HOLDBACK,
+            matches: Vec::new(),
+        }
+    }
+
+    /// Set how many bytes at the end of the stream are held back.
+    pub fn with_holdback(mut self, bytes: usize) -> Self {
+        self.holdback = bytes;
+        self
+    }
+
+    /// Add a chunk, returning the cleaned text that is safe to forward now.
+    ///
+    /// The result may be empty while a possible secret is being buffered.
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.pending.push_str(chunk);
+        if self.pending.len() <= self.holdback {
+            return String::new();
+        }
+
+        let cut = self.safe_cut();
+        let released: String = self.pending.drain(..cut).collect();
+        self.clean(&released)
+    }
+
+    /// Release and clean everything still buffered.
+    pub fn finish(&mut self) -> String {
+        let released = std::mem::take(&mut self.pending);
+        self.clean(&released)
+    }
+
+    /// Leaks found so far, including warn-only ones left in the text.
+    pub fn matches(&self) -> &[LeakMatch] {
+        &self.matches
+    }
+
+    /// Find how much of the buffer can be released.
+    fn safe_cut(&self) -> usize {
+        let text = self.pending.as_str();
+        let mut cut = floor_char_boundary(text, text.len() - self.holdback);
+
+        // Don't split a run of token characters: it may be a secret that
+        // is still arriving. Very long runs are released anyway.
+        let run_start = text[..cut]
+            .char_indices()
+            .rfind(|(_, c)| !is_token_char(*c))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let continues = text[cut..].starts_with(is_token_char);
+        if continues && (run_start > 0 || text.len() <= MAX_PENDING) {
+            cut = run_start;
+        }
+
+        // Don't split a secret the detector already sees.
+        for leak in self.detector.scan(text) {
+            for (start, _) in text.match_indices(&leak.matched_text) {
+                if start < cut && cut < start + leak.matched_text.len() {
+                    cut = start;
+                }
+            }
+        }
+        cut
+    }
+
+    fn clean(&mut self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        let (cleaned, matches) = self.detector.scan_and_clean(text);
+        self.matches.extend(matches);
+        cleaned
+    }
+}
+
+impl Default for StreamingLeakScanner {
+    fn default() -> Self {
+        Self::new(Arc::new(LeakDetector::new()))
+    }
+}
+
+/// Characters that can appear inside a secret token.
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Largest char boundary in `text` at or before `index`.
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPENAI_KEY: &str = "sk-abcdefghijklmnopqrstuvwx";
+
+    /// Feed `chunks` through a scanner and return the full output.
+    fn scan_chunks(scanner: &mut StreamingLeakScanner, chunks: &[&str]) -> String {
+        let mut output: String = chunks.iter().map(|chunk| scanner.push(chunk)).collect();
+        output.push_str(&scanner.finish());
+        output
+    }
+
+    #[test]
+    fn test_secret_split_across_chunks_is_blocked() {
+        let mut scanner = StreamingLeakScanner::default();
+        let (head, tail) = OPENAI_KEY.split_at(10);
+        let before = format!("{}key: {}", "log line\n".repeat(40), head);
+
+        let output = scan_chunks(&mut scanner, &[&before, tail, " done\n"]);
+
+        assert!(!output.contains(head), "{}", output);
+        assert!(!output.contains(tail));
+        assert!(output.contains("[BLOCKED: openai_api_key] done"));
+        assert!(output.starts_with("log line\n"));
+        assert!(scanner.matches().iter().any(|m| m.pattern_name == "openai_api_key"));
+    }
+
+    #[test]
+    fn test_secret_split_at_every_offset_is_caught() {
+        let text = format!("{}Authorization: Bearer {} end", "x ".repeat(60), "t".repeat(30));
+        let expected = LeakDetector::new().scan_and_clean(&text).0;
+        assert!(expected.contains("[REDACTED]"));
+
+        for split in 1..text.len() {
+            let mut scanner = StreamingLeakScanner::default().with_holdback(64);
+            let (a, b) = text.split_at(split);
+            assert_eq!(scan_chunks(&mut scanner, &[a, b]), expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_secret_in_single_byte_chunks() {
+        let text = format!("{}{}\n{}", "y ".repeat(200), OPENAI_KEY, "z ".repeat(200));
+        let chunks: Vec<String> = text.chars().map(String::from).collect();
+        let chunks: Vec<&str> = chunks.iter().map(String::as_str).collect();
+
+        let mut scanner = StreamingLeakScanner::default();
+        let output = scan_chunks(&mut scanner, &chunks);
+
+        assert!(!output.contains(OPENAI_KEY));
+        assert_eq!(output, LeakDetector::new().scan_and_clean(&text).0);
+    }
+
+    #[test]
+    fn test_clean_text_is_released_incrementally() {
+        let mut scanner = StreamingLeakScanner::default();
+        let mut released = String::new();
+        for _ in 0..10 {
+            released.push_str(&scanner.push(&"hello world ".repeat(10)));
+        }
+
+        // Only the held-back tail is still buffered.
+        assert!(released.len() >= 1200 - DEFAULT_HOLDBACK - "hello ".len());
+        released.push_str(&scanner.finish());
+        assert_eq!(released, "hello world ".repeat(100));
+        assert!(scanner.matches().is_empty());
+    }
+
+    #[test]
+    fn test_long_token_run_is_eventually_released() {
+        let mut scanner = StreamingLeakScanner::default();
+        let run = "a".repeat(MAX_PENDING + 1000);
+        let released = scanner.push(&run);
+        assert!(!released.is_empty());
+        assert_eq!(released.len() + scanner.finish().len(), run.len());
+    }
+
+    #[test]
+    fn test_multibyte_text_is_not_split() {
+        let mut scanner = StreamingLeakScanner::default().with_holdback(5);
+        let output = scan_chunks(&mut scanner, &["héllo wörld ", "ünïcödé ", "ßtraße"]);
+        assert_eq!(output, "héllo wörld ünïcödé ßtraße");
+    }
+}
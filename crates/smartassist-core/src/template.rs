@@ -0,0 +1,131 @@
+//! `{{variable}}` placeholder substitution.
+//!
+//! Shared by the template tool and by providers rendering templated
+//! messages, so both read placeholders the same way. A placeholder is a
+//! name of letters, digits and underscores between double braces, with
+//! optional spaces inside; anything else in braces is left as is.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Values for `{{name}}` placeholders, keyed by name.
+pub type TemplateVars = HashMap<String, Value>;
+
+/// Outcome of substituting variables into a template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilledTemplate {
+    /// Text with every defined placeholder replaced.
+    pub text: String,
+
+    /// Names substituted, once per occurrence.
+    pub substituted: Vec<String>,
+
+    /// Names without a value, once per occurrence; left in place in `text`.
+    pub missing: Vec<String>,
+}
+
+/// Substitute the placeholders of `template` that have a value in `vars`.
+///
+/// Strings are inserted as is and other values as JSON. Undefined
+/// placeholders are kept and reported in [`FilledTemplate::missing`].
+pub fn fill_template(template: &str, vars: &TemplateVars) -> FilledTemplate {
+    let mut filled = FilledTemplate::default();
+    let mut rest = template;
+
+    while let Some(open) = rest.find("{{") {
+        let (before, from_open) = rest.split_at(open);
+        filled.text.push_str(before);
+
+        let Some(close) = from_open[2..].find("}}") else {
+            rest = from_open;
+            break;
+        };
+        let placeholder = &from_open[..close + 4];
+        let name = placeholder[2..close + 2].trim();
+        if !is_variable_name(name) {
+            filled.text.push_str("{{");
+            rest = &from_open[2..];
+            continue;
+        }
+
+        match vars.get(name) {
+            Some(Value::String(value)) => filled.text.push_str(value),
+            Some(value) => filled.text.push_str(&value.to_string()),
+            None => {
+                filled.text.push_str(placeholder);
+                filled.missing.push(name.to_string());
+                rest = &from_open[placeholder.len()..];
+                continue;
+            }
+        }
+        filled.substituted.push(name.to_string());
+        rest = &from_open[placeholder.len()..];
+    }
+    filled.text.push_str(rest);
+    filled
+}
+
+/// Placeholders [`render`] found without a value, sorted and without repeats.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndefinedVariables(pub Vec<String>);
+
+impl std::fmt::Display for UndefinedVariables {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Undefined template variable{}: {}",
+            if self.0.len() == 1 { "" } else { "s" },
+            self.0.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UndefinedVariables {}
+
+/// Substitute every placeholder of `template`, failing if one is undefined.
+pub fn render(template: &str, vars: &TemplateVars) -> Result<String, UndefinedVariables> {
+    let filled = fill_template(template, vars);
+    if filled.missing.is_empty() {
+        return Ok(filled.text);
+    }
+    let mut missing = filled.missing;
+    missing.sort();
+    missing.dedup();
+    Err(UndefinedVariables(missing))
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(value: Value) -> TemplateVars {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_fill_template_reports_missing_and_skips_non_placeholders() {
+        let filled = fill_template(
+            "{{a}} {{ }} {{a-b}} {{b}} {{unclosed",
+            &vars(json!({"a": {"x": 1}})),
+        );
+        assert_eq!(filled.text, r#"{"x":1} {{ }} {{a-b}} {{b}} {{unclosed"#);
+        assert_eq!(filled.substituted, vec!["a"]);
+        assert_eq!(filled.missing, vec!["b"]);
+    }
+
+    #[test]
+    fn test_render_lists_each_undefined_variable_once() {
+        assert_eq!(render("{{a}}!", &vars(json!({"a": "hi"}))).unwrap(), "hi!");
+
+        let err = render("{{b}} {{a}} {{b}}", &TemplateVars::new()).unwrap_err();
+        assert_eq!(err, UndefinedVariables(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(err.to_string(), "Undefined template variables: a, b");
+        let err = render("{{a}}", &TemplateVars::new()).unwrap_err();
+        assert_eq!(err.to_string(), "Undefined template variable: a");
+    }
+}
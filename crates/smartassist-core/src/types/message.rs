@@ -205,6 +205,13 @@ pub struct OutboundMessage {
     /// Send options.
     #[serde(default)]
     pub options: SendOptions,
+
+    /// Caller-chosen key identifying this send across retries.
+    ///
+    /// Sends with the same key are delivered once; repeats get the first
+    /// send's result back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
 /// A media payload for outbound messages.
@@ -277,11 +284,18 @@ pub struct SendOptions {
 }
 
 /// Parse mode for message formatting.
+///
+/// The mode says what the text is written in; channels escape or convert it
+/// for their own markup rules before sending.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ParseMode {
-    Markdown,
+    /// Markdown (`**bold**`, `_italic_`, `` `code` ``, `[text](url)`).
+    #[serde(alias = "markdown")]
+    MarkdownV2,
+    /// HTML using Telegram's supported tags (`<b>`, `<i>`, `<code>`, `<a>`, ...).
     Html,
+    /// Literal text with no formatting.
     Plain,
 }
 
@@ -353,12 +367,18 @@ mod tests {
 
     #[test]
     fn test_parse_mode_serde_roundtrip() {
-        let modes = [ParseMode::Markdown, ParseMode::Html, ParseMode::Plain];
+        let modes = [ParseMode::MarkdownV2, ParseMode::Html, ParseMode::Plain];
         for mode in &modes {
             let json = serde_json::to_string(mode).unwrap();
             let parsed: ParseMode = serde_json::from_str(&json).unwrap();
             assert_eq!(*mode, parsed);
         }
+        assert_eq!(
+            serde_json::to_string(&ParseMode::MarkdownV2).unwrap(),
+            "\"markdownv2\""
+        );
+        let legacy: ParseMode = serde_json::from_str("\"markdown\"").unwrap();
+        assert_eq!(legacy, ParseMode::MarkdownV2);
     }
 
     #[test]
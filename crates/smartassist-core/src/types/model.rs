@@ -1,5 +1,6 @@
 //! Model reference and metadata types.
 
+use super::{CostUsage, TokenUsage};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -144,6 +145,28 @@ pub struct ModelPricing {
     pub cache_read_per_1m: Option<f64>,
 }
 
+impl ModelPricing {
+    /// Compute the cost of a token usage.
+    ///
+    /// Cache tokens count as input and are billed at the input price unless
+    /// a cache price is set.
+    pub fn cost(&self, usage: &TokenUsage) -> CostUsage {
+        let per_token = |price_per_1m: f64| price_per_1m / 1_000_000.0;
+        let input_usd = usage.input as f64 * per_token(self.input_per_1m)
+            + usage.cache_creation as f64
+                * per_token(self.cache_creation_per_1m.unwrap_or(self.input_per_1m))
+            + usage.cache_read as f64
+                * per_token(self.cache_read_per_1m.unwrap_or(self.input_per_1m));
+        let output_usd = usage.output as f64 * per_token(self.output_per_1m);
+
+        CostUsage {
+            input_usd,
+            output_usd,
+            total_usd: input_usd + output_usd,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,6 +182,27 @@ mod tests {
         assert_eq!(ref2.model_id, "gpt-4-turbo");
     }
 
+    #[test]
+    fn test_pricing_cost() {
+        let pricing = ModelPricing {
+            input_per_1m: 3.0,
+            output_per_1m: 15.0,
+            cache_creation_per_1m: None,
+            cache_read_per_1m: Some(0.3),
+        };
+        let usage = TokenUsage {
+            input: 1_000_000,
+            output: 200_000,
+            cache_creation: 100_000,
+            cache_read: 1_000_000,
+        };
+
+        let cost = pricing.cost(&usage);
+        assert!((cost.input_usd - 3.6).abs() < 1e-9);
+        assert!((cost.output_usd - 3.0).abs() < 1e-9);
+        assert!((cost.total_usd - 6.6).abs() < 1e-9);
+    }
+
     #[test]
     fn test_model_ref_parse_invalid() {
         assert!(ModelRef::parse("invalid").is_err());
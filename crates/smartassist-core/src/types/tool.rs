@@ -48,11 +48,141 @@ pub struct ToolDefinition {
     /// JSON Schema for input validation.
     pub input_schema: Value,
 
+    /// JSON Schema describing the output of a successful call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+
     /// Execution settings.
     #[serde(default)]
     pub execution: ToolExecutionConfig,
 }
 
+impl ToolDefinition {
+    /// Description shown to the model, with the output schema appended
+    /// when one is declared so the shape of results can be anticipated.
+    pub fn model_description(&self) -> String {
+        match &self.output_schema {
+            Some(schema) => format!("{}\n\nReturns JSON matching: {}", self.description, schema),
+            None => self.description.clone(),
+        }
+    }
+
+    /// Check an output against the declared output schema.
+    ///
+    /// Returns one message per mismatch; always empty when no schema is
+    /// declared. See [`schema_violations`] for the supported keywords.
+    pub fn output_violations(&self, output: &Value) -> Vec<String> {
+        self.output_schema
+            .as_ref()
+            .map_or_else(Vec::new, |schema| schema_violations(schema, output))
+    }
+}
+
+/// Check a value against a JSON Schema.
+///
+/// Covers the subset tool schemas use: `type` (a name or a list of names),
+/// `enum`, `const`, `properties`, `required`, `additionalProperties` and
+/// `items`. Other keywords are ignored. Each mismatch is reported with the
+/// path of the offending value, e.g. `$.items[2]: expected string, got number`.
+pub fn schema_violations(schema: &Value, value: &Value) -> Vec<String> {
+    let mut violations = Vec::new();
+    check_schema(schema, value, "$", &mut violations);
+    violations
+}
+
+fn check_schema(schema: &Value, value: &Value, path: &str, out: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // `true`, `{}` and anything else that is not an object accept all values.
+        if schema == &Value::Bool(false) {
+            out.push(format!("{}: no value is allowed here", path));
+        }
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let names: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| has_type(value, name)) {
+            out.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                names.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            let allowed = Value::Array(allowed.clone());
+            out.push(format!("{}: {} is not one of {}", path, value, allowed));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            out.push(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    out.push(format!("{}: missing required property '{}'", path, name));
+                }
+            }
+        }
+
+        for (name, field) in object {
+            let field_path = format!("{}.{}", path, name);
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => check_schema(field_schema, field, &field_path, out),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        out.push(format!("{}: unexpected property", field_path))
+                    }
+                    Some(extra) => check_schema(extra, field, &field_path, out),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            check_schema(item_schema, item, &format!("{}[{}]", path, i), out);
+        }
+    }
+}
+
+/// Whether a value is an instance of a JSON Schema type name.
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        // JSON Schema counts any number with a zero fractional part, such
+        // as `3.0`, as an integer.
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
 /// Tool execution configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolExecutionConfig {
@@ -64,9 +194,15 @@ pub struct ToolExecutionConfig {
     #[serde(default)]
     pub requires_approval: bool,
 
-    /// Sandbox profile to use.
+    /// Sandbox profile this tool's commands run under, instead of the
+    /// executor's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_profile: Option<super::SandboxProfile>,
+
+    /// Whether results may be cached (pure tools only; never set this on
+    /// side-effecting tools).
     #[serde(default)]
-    pub sandbox_profile: super::SandboxProfile,
+    pub cacheable: bool,
 }
 
 /// Where a tool should be executed.
@@ -262,6 +398,85 @@ mod tests {
         assert_eq!(result.duration_ms, Some(150));
     }
 
+    fn definition(output_schema: Option<Value>) -> ToolDefinition {
+        ToolDefinition {
+            name: "lookup".to_string(),
+            description: "Look something up.".to_string(),
+            input_schema: serde_json::json!({"type": "object"}),
+            output_schema,
+            execution: ToolExecutionConfig::default(),
+        }
+    }
+
+    #[test]
+    fn test_output_schema_accepts_conforming_output() {
+        let tool = definition(Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "unit": {"enum": ["ms", "s"]},
+                "note": {"type": ["string", "null"]}
+            },
+            "required": ["count", "tags"],
+            "additionalProperties": false
+        })));
+
+        let output =
+            serde_json::json!({"count": 3, "tags": ["a", "b"], "unit": "ms", "note": null});
+        assert!(tool.output_violations(&output).is_empty());
+        assert!(definition(None).output_violations(&output).is_empty());
+
+        // A whole number written with a fraction is still an integer.
+        let output = serde_json::json!({"count": 3.0, "tags": []});
+        assert!(tool.output_violations(&output).is_empty());
+    }
+
+    #[test]
+    fn test_output_schema_reports_mismatches() {
+        let tool = definition(Some(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "count": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "unit": {"enum": ["ms", "s"]}
+            },
+            "required": ["count", "tags"],
+            "additionalProperties": false
+        })));
+
+        let output = serde_json::json!({"count": 1.5, "tags": ["a", 2], "unit": "h", "extra": 1});
+        let mut violations = tool.output_violations(&output);
+        violations.sort();
+        assert_eq!(
+            violations,
+            [
+                "$.count: expected integer, got number",
+                "$.extra: unexpected property",
+                "$.tags[1]: expected string, got number",
+                "$.unit: \"h\" is not one of [\"ms\",\"s\"]",
+            ]
+        );
+
+        let violations = tool.output_violations(&serde_json::json!({"tags": []}));
+        assert_eq!(violations, ["$: missing required property 'count'"]);
+        assert_eq!(
+            tool.output_violations(&Value::String("oops".into())),
+            ["$: expected object, got string"]
+        );
+    }
+
+    #[test]
+    fn test_model_description_includes_output_schema() {
+        assert_eq!(definition(None).model_description(), "Look something up.");
+
+        let tool = definition(Some(serde_json::json!({"type": "string"})));
+        assert_eq!(
+            tool.model_description(),
+            "Look something up.\n\nReturns JSON matching: {\"type\":\"string\"}"
+        );
+    }
+
     #[test]
     fn test_tool_group_default_is_custom() {
         assert_eq!(ToolGroup::default(), ToolGroup::Custom);
@@ -1,5 +1,6 @@
 //! Gateway error types.
 
+use crate::rpc::JsonRpcError;
 use thiserror::Error;
 
 /// Errors that can occur in the gateway.
@@ -25,6 +26,10 @@ pub enum GatewayError {
     #[error("Method not found: {0}")]
     MethodNotFound(String),
 
+    /// A method with this name is already registered.
+    #[error("Method already registered: {0}")]
+    MethodConflict(String),
+
     /// Invalid parameters.
     #[error("Invalid parameters: {0}")]
     InvalidParams(String),
@@ -45,6 +50,14 @@ pub enum GatewayError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// Rate limit exceeded.
+    #[error("Rate limit exceeded: {0}")]
+    RateLimited(String),
+
+    /// Upstream model provider error.
+    #[error("Provider error: {0}")]
+    Provider(String),
+
     /// Internal error.
     #[error("Internal error: {0}")]
     Internal(String),
@@ -52,14 +65,120 @@ pub enum GatewayError {
 
 impl GatewayError {
     /// Get the JSON-RPC error code.
+    ///
+    /// Standard JSON-RPC codes are used where they apply; gateway-specific
+    /// errors use the server-defined range starting at -32001.
     pub fn code(&self) -> i32 {
         match self {
+            Self::Json(_) => -32700,
             Self::MethodNotFound(_) => -32601,
             Self::InvalidParams(_) => -32602,
-            Self::Json(_) => -32700,
             Self::Auth(_) => -32001,
             Self::NotFound(_) => -32002,
-            _ => -32603,
+            Self::RateLimited(_) => -32003,
+            Self::Provider(_) => -32004,
+            Self::Agent(_) => -32005,
+            Self::Session(_) => -32006,
+            Self::Io(_)
+            | Self::WebSocket(_)
+            | Self::Rpc(_)
+            | Self::MethodConflict(_)
+            | Self::Internal(_) => -32603,
+        }
+    }
+
+    /// Get the machine-readable error code sent as `data.code`.
+    ///
+    /// Unlike the numeric code this is distinct for every variant.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io_error",
+            Self::Json(_) => "parse_error",
+            Self::WebSocket(_) => "websocket_error",
+            Self::Rpc(_) => "rpc_error",
+            Self::MethodNotFound(_) => "method_not_found",
+            Self::MethodConflict(_) => "method_conflict",
+            Self::InvalidParams(_) => "invalid_params",
+            Self::Auth(_) => "unauthorized",
+            Self::Agent(_) => "agent_error",
+            Self::Session(_) => "session_error",
+            Self::NotFound(_) => "not_found",
+            Self::RateLimited(_) => "rate_limited",
+            Self::Provider(_) => "upstream_provider_error",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl From<&GatewayError> for JsonRpcError {
+    fn from(error: &GatewayError) -> Self {
+        JsonRpcError::new(error.code(), error.to_string())
+            .with_data(serde_json::json!({ "code": error.error_code() }))
+    }
+}
+
+impl From<GatewayError> for JsonRpcError {
+    fn from(error: GatewayError) -> Self {
+        Self::from(&error)
+    }
+}
+
+impl From<smartassist_providers::ProviderError> for GatewayError {
+    fn from(error: smartassist_providers::ProviderError) -> Self {
+        if error.is_rate_limited().is_some() {
+            Self::RateLimited(error.to_string())
+        } else {
+            Self::Provider(error.to_string())
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let cases = [
+            (GatewayError::Json(serde_json::from_str::<()>("{").unwrap_err()), -32700, "parse_error"),
+            (GatewayError::MethodNotFound("x".into()), -32601, "method_not_found"),
+            (GatewayError::InvalidParams("x".into()), -32602, "invalid_params"),
+            (GatewayError::Auth("x".into()), -32001, "unauthorized"),
+            (GatewayError::NotFound("x".into()), -32002, "not_found"),
+            (GatewayError::RateLimited("x".into()), -32003, "rate_limited"),
+            (GatewayError::Provider("x".into()), -32004, "upstream_provider_error"),
+            (GatewayError::Agent("x".into()), -32005, "agent_error"),
+            (GatewayError::Session("x".into()), -32006, "session_error"),
+            (GatewayError::Io(std::io::Error::other("x")), -32603, "io_error"),
+            (GatewayError::WebSocket("x".into()), -32603, "websocket_error"),
+            (GatewayError::Rpc("x".into()), -32603, "rpc_error"),
+            (GatewayError::MethodConflict("x".into()), -32603, "method_conflict"),
+            (GatewayError::Internal("x".into()), -32603, "internal_error"),
+        ];
+
+        let mut data_codes = std::collections::HashSet::new();
+        for (error, code, data_code) in cases {
+            let message = error.to_string();
+            let json = serde_json::to_value(JsonRpcError::from(error)).unwrap();
+
+            assert_eq!(json["code"], code, "{}", message);
+            assert_eq!(json["message"], message);
+            assert_eq!(json["data"], serde_json::json!({ "code": data_code }));
+            assert!(data_codes.insert(data_code), "duplicate data code {}", data_code);
+        }
+    }
+
+    #[test]
+    fn test_provider_error_conversion() {
+        use smartassist_providers::ProviderError;
+
+        let error = GatewayError::from(ProviderError::rate_limit("slow down", Some(30)));
+        assert!(matches!(error, GatewayError::RateLimited(_)));
+        let error = GatewayError::from(ProviderError::server_error(429, "busy"));
+        assert!(matches!(error, GatewayError::RateLimited(_)));
+
+        let error = GatewayError::from(ProviderError::auth("bad key"));
+        assert_eq!(error.code(), -32004);
+        assert!(error.to_string().contains("bad key"));
+    }
+}
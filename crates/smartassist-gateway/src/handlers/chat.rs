@@ -2,12 +2,19 @@
 
 use super::{HandlerContext, SessionData};
 use crate::error::GatewayError;
-use crate::methods::MethodHandler;
+use crate::methods::{MethodHandler, MethodSchema};
 use crate::Result;
 use async_trait::async_trait;
-use smartassist_providers::{ChatOptions, Message as ProviderMessage};
+use futures::StreamExt;
+use smartassist_core::safety::StreamingLeakScanner;
+use smartassist_providers::{
+    cancellable, CancellationToken, ChatOptions, CompletionStream, Message as ProviderMessage,
+    StreamAccumulator, StreamEvent, StreamStats,
+};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::{debug, warn};
 
 /// Parameters for chat method.
@@ -43,6 +50,33 @@ pub struct ChatResponse {
 
     /// Message ID.
     pub message_id: Option<String>,
+
+    /// Whether `chat.abort` stopped the reply; `message` holds whatever
+    /// arrived before that.
+    pub aborted: bool,
+
+    /// Timing of a streamed reply.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<StreamTiming>,
+}
+
+/// Timing of a streamed reply.
+#[derive(Debug, Serialize)]
+pub struct StreamTiming {
+    /// Milliseconds from the request to the first token.
+    pub first_token_ms: Option<u64>,
+
+    /// Average output rate between the first and the last token.
+    pub tokens_per_second: Option<f64>,
+}
+
+impl From<StreamStats> for StreamTiming {
+    fn from(stats: StreamStats) -> Self {
+        Self {
+            first_token_ms: stats.first_token_latency.map(|d| d.as_millis() as u64),
+            tokens_per_second: stats.tokens_per_second(),
+        }
+    }
 }
 
 /// Token usage statistics.
@@ -52,6 +86,77 @@ pub struct TokenUsage {
     pub output: u64,
 }
 
+/// In-flight chat requests, keyed by session, so `chat.abort` can cancel
+/// them.
+#[derive(Debug, Default)]
+pub struct ActiveChats {
+    chats: Mutex<HashMap<String, (u64, CancellationToken)>>,
+    next_id: AtomicU64,
+}
+
+impl ActiveChats {
+    /// Register a request for `session_key`.
+    ///
+    /// The request stays abortable until the returned guard is dropped. A
+    /// newer request for the same session takes over the abort slot.
+    pub fn start(self: &Arc<Self>, session_key: &str) -> ActiveChat {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        self.chats
+            .lock()
+            .unwrap()
+            .insert(session_key.to_string(), (id, token.clone()));
+        ActiveChat {
+            chats: self.clone(),
+            session_key: session_key.to_string(),
+            id,
+            token,
+        }
+    }
+
+    /// Cancel the request running for `session_key`.
+    ///
+    /// Returns `false` if none is running.
+    pub fn abort(&self, session_key: &str) -> bool {
+        match self.chats.lock().unwrap().remove(session_key) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether a request is running for `session_key`.
+    pub fn is_active(&self, session_key: &str) -> bool {
+        self.chats.lock().unwrap().contains_key(session_key)
+    }
+}
+
+/// Registration of one in-flight chat request, removed on drop.
+pub struct ActiveChat {
+    chats: Arc<ActiveChats>,
+    session_key: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl ActiveChat {
+    /// Token cancelled when the request is aborted.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for ActiveChat {
+    fn drop(&mut self) {
+        let mut chats = self.chats.chats.lock().unwrap();
+        if chats.get(&self.session_key).is_some_and(|(id, _)| *id == self.id) {
+            chats.remove(&self.session_key);
+        }
+    }
+}
+
 /// Chat method handler.
 pub struct ChatHandler {
     context: Arc<HandlerContext>,
@@ -111,12 +216,58 @@ impl MethodHandler for ChatHandler {
         };
 
         // Try to use the provider if available
+        let mut aborted = false;
+        let mut timing = None;
         let (response_message, usage) = if let Some(provider) = &self.context.provider {
             let model = params.model.as_deref().unwrap_or(&self.context.default_model);
             let options = ChatOptions::with_max_tokens(4096);
 
-            match provider.chat(model, &messages, Some(options)).await {
-                Ok(response) => {
+            // Aborting drops the provider future or stream, which closes the
+            // upstream HTTP request.
+            let chat = self.context.active_chats.start(&session_key);
+            let cancel = chat.token().clone();
+            let scanner = self.context.safety.output_scanner();
+            let result = if params.stream.unwrap_or(false) {
+                let request = tokio::select! {
+                    _ = cancel.cancelled() => Ok(None),
+                    request = provider.chat_stream_with_stats(model, &messages, Some(options)) => {
+                        request.map(Some)
+                    }
+                };
+                match request {
+                    Ok(Some((stream, stats))) => {
+                        let stream = cancellable(stream, cancel.clone());
+                        let stream = match scanner {
+                            Some(scanner) => redact_stream(stream, scanner),
+                            None => stream,
+                        };
+                        let response = StreamAccumulator::collect(stream).await.map(Some);
+                        // Sent once the collected stream has been dropped.
+                        timing = stats.await.ok().map(StreamTiming::from);
+                        response
+                    }
+                    Ok(None) => Ok(None),
+                    Err(e) => Err(e),
+                }
+            } else {
+                let result = tokio::select! {
+                    _ = cancel.cancelled() => Ok(None),
+                    result = provider.chat(model, &messages, Some(options)) => result.map(Some),
+                };
+                result.map(|response| {
+                    response.map(|mut response| {
+                        if let Some(mut scanner) = scanner {
+                            response.content = scanner.push(&response.content) + &scanner.finish();
+                        }
+                        response
+                    })
+                })
+            };
+            drop(chat);
+            aborted = cancel.is_cancelled();
+
+            match result {
+                Ok(Some(response)) if !aborted => {
                     // Store assistant message in session
                     {
                         let mut sessions = self.context.sessions.write().await;
@@ -136,9 +287,14 @@ impl MethodHandler for ChatHandler {
                         }),
                     )
                 }
+                // Aborted: keep what streamed in, but not in the session.
+                Ok(response) => {
+                    debug!("Chat aborted for session: {}", session_key);
+                    (response.map(|r| r.content).unwrap_or_default(), None)
+                }
                 Err(e) => {
                     warn!("Provider error: {}", e);
-                    (format!("Error: {}", e), None)
+                    return Err(e.into());
                 }
             }
         } else {
@@ -151,12 +307,42 @@ impl MethodHandler for ChatHandler {
             message: response_message,
             usage,
             message_id: Some(uuid::Uuid::new_v4().to_string()),
+            aborted,
+            timing,
         };
 
         serde_json::to_value(response).map_err(|e| GatewayError::Internal(e.to_string()))
     }
 }
 
+/// Redact leaked secrets from the text deltas of `stream`.
+///
+/// The scanner holds text back while it could still be part of a secret;
+/// whatever it holds when the stream ends is sent as a last delta.
+fn redact_stream(stream: CompletionStream, scanner: StreamingLeakScanner) -> CompletionStream {
+    let state = Some((stream, scanner));
+    Box::pin(futures::stream::unfold(state, |state| async move {
+        let (mut stream, mut scanner) = state?;
+        loop {
+            match stream.next().await {
+                Some(Ok(StreamEvent::ContentDelta { delta })) => {
+                    let delta = scanner.push(&delta);
+                    if !delta.is_empty() {
+                        let event = Ok(StreamEvent::ContentDelta { delta });
+                        return Some((event, Some((stream, scanner))));
+                    }
+                }
+                Some(event) => return Some((event, Some((stream, scanner)))),
+                None => {
+                    let delta = scanner.finish();
+                    return (!delta.is_empty())
+                        .then(|| (Ok(StreamEvent::ContentDelta { delta }), None));
+                }
+            }
+        }
+    }))
+}
+
 /// Parameters for chat.history method.
 #[derive(Debug, Deserialize)]
 pub struct ChatHistoryParams {
@@ -212,6 +398,30 @@ impl MethodHandler for ChatHistoryHandler {
             "total": session.messages.len(),
         }))
     }
+
+    fn schema(&self) -> Option<MethodSchema> {
+        let schema = MethodSchema::new(
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "session_key": { "type": "string" },
+                    "limit": { "type": "integer", "minimum": 0, "default": 100 },
+                    "offset": { "type": "integer", "minimum": 0, "default": 0 },
+                },
+                "required": ["session_key"],
+            }),
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "session_key": { "type": "string" },
+                    "messages": { "type": "array", "items": { "type": "object" } },
+                    "total": { "type": "integer" },
+                },
+                "required": ["session_key", "messages", "total"],
+            }),
+        );
+        Some(schema.with_summary("Page through a session's messages"))
+    }
 }
 
 /// Parameters for chat.abort method.
@@ -223,12 +433,12 @@ pub struct ChatAbortParams {
 
 /// Chat abort method handler.
 pub struct ChatAbortHandler {
-    _context: Arc<HandlerContext>,
+    context: Arc<HandlerContext>,
 }
 
 impl ChatAbortHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
-        Self { _context: context }
+        Self { context }
     }
 }
 
@@ -242,12 +452,11 @@ impl MethodHandler for ChatAbortHandler {
 
         debug!("Chat abort request for session: {}", params.session_key);
 
-        // TODO: Actually abort the running agent
-        // For now, just acknowledge the request
+        let aborted = self.context.active_chats.abort(&params.session_key);
 
         Ok(serde_json::json!({
             "session_key": params.session_key,
-            "aborted": true,
+            "aborted": aborted,
         }))
     }
 }
@@ -279,6 +488,7 @@ impl TryFrom<serde_json::Value> for ChatAbortParams {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::AtomicBool;
 
     #[test]
     fn test_chat_params_deserialize() {
@@ -291,4 +501,125 @@ mod tests {
         assert_eq!(params.message, "Hello, world!");
         assert_eq!(params.session_key, Some("test-session".to_string()));
     }
+
+    /// Start a chat against a stalling provider, abort it, and return the
+    /// chat response.
+    async fn abort_stalled_chat(stream: bool, dropped: Arc<AtomicBool>) -> serde_json::Value {
+        use super::super::mock_provider::MockProvider;
+
+        let provider = Arc::new(MockProvider::stalling("mock", dropped));
+        let context = Arc::new(HandlerContext::new().with_provider(provider));
+        let chat = ChatHandler::new(context.clone());
+        let call = tokio::spawn(async move {
+            let params =
+                serde_json::json!({"message": "hi", "session_key": "s1", "stream": stream});
+            chat.call(Some(params)).await
+        });
+
+        while !context.active_chats.is_active("s1") {
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // Let a streamed reply deliver its first delta.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let abort = ChatAbortHandler::new(context.clone());
+        let params = serde_json::json!({"session_key": "s1"});
+        let result = abort.call(Some(params.clone())).await.unwrap();
+        assert_eq!(result["aborted"], true);
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(2), call)
+            .await
+            .expect("chat should end promptly after abort")
+            .unwrap()
+            .unwrap();
+
+        // Nothing is left to abort, and the partial reply isn't stored.
+        assert_eq!(abort.call(Some(params)).await.unwrap()["aborted"], false);
+        let sessions = context.sessions.read().await;
+        assert_eq!(sessions["s1"].messages.len(), 1);
+        response
+    }
+
+    #[tokio::test]
+    async fn test_abort_cancels_streaming_chat() {
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        let response = abort_stalled_chat(true, dropped.clone()).await;
+
+        assert_eq!(response["aborted"], true);
+        assert_eq!(response["message"], "partial");
+        assert!(dropped.load(Ordering::SeqCst), "upstream stream was not dropped");
+    }
+
+    #[tokio::test]
+    async fn test_abort_cancels_pending_chat() {
+        let dropped = Arc::new(AtomicBool::new(false));
+
+        let response = abort_stalled_chat(false, dropped.clone()).await;
+
+        assert_eq!(response["aborted"], true);
+        assert_eq!(response["message"], "");
+        assert!(dropped.load(Ordering::SeqCst), "upstream request was not dropped");
+    }
+
+    #[tokio::test]
+    async fn test_chat_redacts_leaked_secrets() {
+        use super::super::mock_provider::MockProvider;
+
+        // The key arrives split across two deltas.
+        let deltas = ["Your key is sk-abcdefghij", "klmnopqrstuvwx, keep it safe."];
+        let provider = Arc::new(MockProvider::replying("mock", &deltas));
+        let context = Arc::new(HandlerContext::new().with_provider(provider));
+        let chat = ChatHandler::new(context.clone());
+
+        for stream in [true, false] {
+            let params = serde_json::json!({"message": "hi", "stream": stream});
+            let response = chat.call(Some(params)).await.unwrap();
+            let message = response["message"].as_str().unwrap();
+            assert!(message.starts_with("Your key is "), "{}", message);
+            assert!(message.ends_with(", keep it safe."), "{}", message);
+            assert!(!message.contains("abcdefghij"), "{}", message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_chat_reports_timing() {
+        use super::super::mock_provider::MockProvider;
+
+        let provider = Arc::new(MockProvider::replying("mock", &["Hello", " there"]));
+        let chat = ChatHandler::new(Arc::new(HandlerContext::new().with_provider(provider)));
+
+        let params = serde_json::json!({"message": "hi", "stream": true});
+        let response = chat.call(Some(params)).await.unwrap();
+        assert_eq!(response["message"], "Hello there");
+        assert!(response["timing"]["first_token_ms"].is_u64(), "{}", response);
+
+        let params = serde_json::json!({"message": "hi", "stream": false});
+        let response = chat.call(Some(params)).await.unwrap();
+        assert!(response.get("timing").is_none(), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_abort_without_running_chat() {
+        let abort = ChatAbortHandler::new(Arc::new(HandlerContext::new()));
+        let result = abort
+            .call(Some(serde_json::json!({"session_key": "idle"})))
+            .await
+            .unwrap();
+        assert_eq!(result["aborted"], false);
+    }
+
+    #[test]
+    fn test_finished_chat_releases_abort_slot() {
+        let chats = Arc::new(ActiveChats::default());
+        let first = chats.start("s");
+        let second = chats.start("s");
+
+        // The older request finishing leaves the newer one abortable.
+        drop(first);
+        assert!(chats.is_active("s"));
+        drop(second);
+        assert!(!chats.is_active("s"));
+        assert!(!chats.abort("s"));
+    }
 }
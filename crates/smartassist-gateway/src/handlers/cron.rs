@@ -1,8 +1,14 @@
 //! Cron job RPC method handlers.
 //!
 //! Handles scheduling and management of cron jobs.
-//! Includes an in-memory [`CronScheduler`] that validates cron expressions
-//! and tracks job metadata (last run, run count, next fire time).
+//! Includes a [`CronScheduler`] that validates cron expressions, tracks job
+//! metadata (last run, run count, next fire time) and can save jobs to a
+//! JSON file so they survive restarts.
+//!
+//! Schedules accept standard 5-field cron syntax (`min hour day month
+//! weekday`), the same with a leading seconds field, the 7-field form with a
+//! trailing year, and `@daily`-style shorthands. In the 5- and 6-field forms
+//! weekdays are numbered the Unix way, with 0 and 7 both meaning Sunday.
 
 use super::HandlerContext;
 use crate::error::GatewayError;
@@ -12,10 +18,11 @@ use async_trait::async_trait;
 use cron::Schedule;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::debug;
+use tracing::{debug, warn};
 
 // ---------------------------------------------------------------------------
 // CronJobInfo (wire type returned by list/status endpoints)
@@ -60,6 +67,9 @@ pub struct CronJob {
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_run: Option<chrono::DateTime<chrono::Utc>>,
     pub run_count: u64,
+    /// Next fire time, as of the last change or load.
+    #[serde(default)]
+    pub next_run: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl CronJob {
@@ -80,35 +90,164 @@ impl CronJob {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Schedule parsing
+// ---------------------------------------------------------------------------
+
+/// Default number of upcoming run times previewed by `cron.add`.
+const DEFAULT_PREVIEW_RUNS: usize = 5;
+
+/// Maximum number of upcoming run times previewed by `cron.add`.
+const MAX_PREVIEW_RUNS: usize = 100;
+
+/// Parse a cron expression into a [`Schedule`].
+///
+/// The error message names the expression and what is wrong with it.
+pub fn parse_schedule(expression: &str) -> std::result::Result<Schedule, String> {
+    let invalid = |reason: String| format!("Invalid cron expression '{}': {}", expression, reason);
+
+    let normalized = if expression.trim_start().starts_with('@') {
+        expression.trim().to_string()
+    } else {
+        let mut fields: Vec<String> = expression.split_whitespace().map(str::to_string).collect();
+        match fields.len() {
+            5 => {
+                fields[4] = unix_day_of_week(&fields[4]).map_err(invalid)?;
+                format!("0 {}", fields.join(" "))
+            }
+            6 => {
+                fields[5] = unix_day_of_week(&fields[5]).map_err(invalid)?;
+                fields.join(" ")
+            }
+            7 => fields.join(" "),
+            n => {
+                return Err(invalid(format!(
+                    "expected 5 fields (min hour day month weekday) or 6 with leading seconds, got {}",
+                    n
+                )))
+            }
+        }
+    };
+
+    Schedule::from_str(&normalized).map_err(|e| invalid(e.to_string()))
+}
+
+/// Translate a Unix day-of-week field (0-7, Sunday = 0 or 7) to the
+/// 1-7 numbering (Sunday = 1) used by the `cron` crate.
+fn unix_day_of_week(field: &str) -> std::result::Result<String, String> {
+    let items = field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, Some(step)),
+                None => (item, None),
+            };
+
+            let range = match range.split_once('-') {
+                // Sunday at both ends: the whole week, not just Sunday.
+                Some(("0", "7")) => "1-7".to_string(),
+                Some((start, end)) => {
+                    let (start, end) = (shift_day(start)?, shift_day(end)?);
+                    match (start.parse::<u8>(), end.parse::<u8>()) {
+                        // Ranges ending on Sunday (e.g. 5-7) wrap around.
+                        (Ok(s), Ok(e)) if s > e && step.is_none() => format!("{}-7,1-{}", s, e),
+                        (Ok(s), Ok(e)) if s > e => {
+                            return Err(format!("day-of-week range {} wraps past Sunday", item))
+                        }
+                        _ => format!("{}-{}", start, end),
+                    }
+                }
+                None => shift_day(range)?,
+            };
+
+            Ok(match step {
+                Some(step) => format!("{}/{}", range, step),
+                None => range,
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, String>>()?;
+
+    Ok(items.join(","))
+}
+
+/// Shift a single numeric weekday by one; names and wildcards pass through.
+fn shift_day(day: &str) -> std::result::Result<String, String> {
+    match day.parse::<u8>() {
+        Ok(7) => Ok("1".to_string()),
+        Ok(n) if n < 7 => Ok((n + 1).to_string()),
+        Ok(n) => Err(format!("day of week {} is out of range 0-7", n)),
+        Err(_) => Ok(day.to_string()),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // CronScheduler
 // ---------------------------------------------------------------------------
 
-/// In-memory cron job scheduler.
+/// Cron job scheduler.
+///
+/// Jobs are kept in memory. A scheduler created with [`CronScheduler::load`]
+/// also saves them to its store after every change, replacing the file
+/// atomically. Save failures are logged and leave the in-memory state as is.
 pub struct CronScheduler {
     jobs: RwLock<HashMap<String, CronJob>>,
+    /// JSON file jobs are saved to, if any.
+    store: Option<PathBuf>,
 }
 
 impl CronScheduler {
     pub fn new() -> Self {
         Self {
             jobs: RwLock::new(HashMap::new()),
+            store: None,
         }
     }
 
+    /// Load saved jobs from `path` and keep saving changes there.
+    ///
+    /// A missing file starts an empty store. Next run times are recomputed,
+    /// since the saved ones may have passed while the gateway was down.
+    pub async fn load(path: impl Into<PathBuf>) -> std::result::Result<Self, String> {
+        let path = path.into();
+        let saved: Vec<CronJob> = match tokio::fs::read_to_string(&path).await {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid cron store {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("Failed to read cron store {}: {}", path.display(), e)),
+        };
+
+        let jobs = saved
+            .into_iter()
+            .map(|mut job| {
+                job.next_run = Self::next_run(&job.schedule);
+                (job.id.clone(), job)
+            })
+            .collect();
+
+        Ok(Self {
+            jobs: RwLock::new(jobs),
+            store: Some(path),
+        })
+    }
+
     /// Add a new job. Validates the cron expression before inserting.
-    pub async fn add(&self, job: CronJob) -> std::result::Result<(), String> {
-        Schedule::from_str(&job.schedule)
-            .map_err(|e| format!("Invalid cron expression: {}", e))?;
+    pub async fn add(&self, mut job: CronJob) -> std::result::Result<(), String> {
+        parse_schedule(&job.schedule)?;
+        job.next_run = Self::next_run(&job.schedule);
         let mut jobs = self.jobs.write().await;
         jobs.insert(job.id.clone(), job);
+        self.save(&jobs).await;
         Ok(())
     }
 
     /// Remove a job by ID.
     pub async fn remove(&self, id: &str) -> Option<CronJob> {
         let mut jobs = self.jobs.write().await;
-        jobs.remove(id)
+        let removed = jobs.remove(id);
+        if removed.is_some() {
+            self.save(&jobs).await;
+        }
+        removed
     }
 
     /// Update fields on an existing job. Only non-None fields are applied.
@@ -126,7 +265,8 @@ impl CronScheduler {
             .ok_or_else(|| format!("Job not found: {}", id))?;
 
         if let Some(s) = schedule {
-            Schedule::from_str(&s).map_err(|e| format!("Invalid cron expression: {}", e))?;
+            parse_schedule(&s)?;
+            job.next_run = Self::next_run(&s);
             job.schedule = s;
         }
         if let Some(d) = description {
@@ -138,6 +278,7 @@ impl CronScheduler {
         if let Some(e) = enabled {
             job.enabled = e;
         }
+        self.save(&jobs).await;
         Ok(())
     }
 
@@ -161,15 +302,34 @@ impl CronScheduler {
             .ok_or_else(|| format!("Job not found: {}", id))?;
         job.last_run = Some(chrono::Utc::now());
         job.run_count += 1;
-        Ok(job.clone())
+        job.next_run = Self::next_run(&job.schedule);
+        let job = job.clone();
+        self.save(&jobs).await;
+        Ok(job)
+    }
+
+    /// Save `jobs` to the store, if this scheduler has one.
+    async fn save(&self, jobs: &HashMap<String, CronJob>) {
+        let Some(path) = &self.store else {
+            return;
+        };
+        if let Err(e) = write_store(path, jobs).await {
+            warn!("Failed to save cron jobs to {}: {}", path.display(), e);
+        }
     }
 
     /// Compute the next run time for a given cron expression.
     pub fn next_run(schedule: &str) -> Option<chrono::DateTime<chrono::Utc>> {
-        Schedule::from_str(schedule)
-            .ok()?
-            .upcoming(chrono::Utc)
-            .next()
+        parse_schedule(schedule).ok()?.upcoming(chrono::Utc).next()
+    }
+
+    /// Compute the next `count` run times after `after` for a cron expression.
+    pub fn upcoming_runs(
+        schedule: &str,
+        after: chrono::DateTime<chrono::Utc>,
+        count: usize,
+    ) -> std::result::Result<Vec<chrono::DateTime<chrono::Utc>>, String> {
+        Ok(parse_schedule(schedule)?.after(&after).take(count).collect())
     }
 }
 
@@ -179,6 +339,20 @@ impl Default for CronScheduler {
     }
 }
 
+/// Write jobs to `path` in creation order (write-to-tmp then rename).
+async fn write_store(path: &Path, jobs: &HashMap<String, CronJob>) -> std::io::Result<()> {
+    let mut jobs: Vec<&CronJob> = jobs.values().collect();
+    jobs.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    let json = serde_json::to_vec_pretty(&jobs)?;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, json).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
 // ---------------------------------------------------------------------------
 // CronListHandler
 // ---------------------------------------------------------------------------
@@ -272,6 +446,8 @@ pub struct CronAddParams {
     pub prompt: String,
     /// Whether to enable immediately.
     pub enabled: Option<bool>,
+    /// Number of upcoming run times to return (default 5, at most 100).
+    pub preview: Option<usize>,
 }
 
 /// Cron add handler.
@@ -295,6 +471,17 @@ impl MethodHandler for CronAddHandler {
 
         debug!("Cron add: schedule={}", params.schedule);
 
+        let preview = params
+            .preview
+            .unwrap_or(DEFAULT_PREVIEW_RUNS)
+            .min(MAX_PREVIEW_RUNS);
+        let next_runs: Vec<String> =
+            CronScheduler::upcoming_runs(&params.schedule, chrono::Utc::now(), preview)
+                .map_err(GatewayError::InvalidParams)?
+                .iter()
+                .map(|t| t.to_rfc3339())
+                .collect();
+
         let job_id = uuid::Uuid::new_v4().to_string();
         let enabled = params.enabled.unwrap_or(true);
 
@@ -308,13 +495,14 @@ impl MethodHandler for CronAddHandler {
             created_at: chrono::Utc::now(),
             last_run: None,
             run_count: 0,
+            next_run: None,
         };
 
         self.context
             .cron_scheduler
             .add(job)
             .await
-            .map_err(|e| GatewayError::InvalidParams(e))?;
+            .map_err(GatewayError::InvalidParams)?;
 
         Ok(serde_json::json!({
             "id": job_id,
@@ -322,6 +510,7 @@ impl MethodHandler for CronAddHandler {
             "agent_id": params.agent_id,
             "enabled": enabled,
             "created": true,
+            "next_runs": next_runs,
         }))
     }
 }
@@ -646,6 +835,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             last_run: None,
             run_count: 0,
+            next_run: None,
         };
 
         scheduler.add(job).await.unwrap();
@@ -673,6 +863,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             last_run: None,
             run_count: 0,
+            next_run: None,
         };
 
         let result = scheduler.add(job).await;
@@ -693,6 +884,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             last_run: None,
             run_count: 0,
+            next_run: None,
         };
 
         scheduler.add(job).await.unwrap();
@@ -719,6 +911,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             last_run: None,
             run_count: 0,
+            next_run: None,
         };
 
         scheduler.add(job).await.unwrap();
@@ -732,4 +925,188 @@ mod tests {
         assert_eq!(j.prompt, "new prompt");
         assert!(!j.enabled);
     }
+
+    #[tokio::test]
+    async fn test_scheduler_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cron.json");
+
+        let scheduler = CronScheduler::load(&path).await.unwrap();
+        assert!(scheduler.list().await.is_empty());
+        for id in ["j1", "j2"] {
+            let job = CronJob {
+                id: id.to_string(),
+                schedule: "0 9 * * 1-5".to_string(),
+                description: Some("Standup reminder".to_string()),
+                agent_id: "agent".to_string(),
+                prompt: "Post the standup thread".to_string(),
+                enabled: true,
+                created_at: chrono::Utc::now(),
+                last_run: None,
+                run_count: 0,
+                next_run: None,
+            };
+            scheduler.add(job).await.unwrap();
+        }
+        scheduler.record_run("j1").await.unwrap();
+        scheduler.remove("j2").await.unwrap();
+        drop(scheduler);
+
+        // Simulate downtime: the saved next run is now in the past.
+        let mut saved: Vec<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 1);
+        saved[0]["next_run"] = serde_json::json!("2000-01-03T09:00:00Z");
+        std::fs::write(&path, serde_json::to_string(&saved).unwrap()).unwrap();
+
+        let scheduler = CronScheduler::load(&path).await.unwrap();
+        let jobs = scheduler.list().await;
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert_eq!(job.id, "j1");
+        assert_eq!(job.run_count, 1);
+        assert!(job.last_run.is_some());
+        assert_eq!(job.description.as_deref(), Some("Standup reminder"));
+        assert!(job.next_run.unwrap() > chrono::Utc::now());
+        assert_eq!(job.next_run, CronScheduler::next_run("0 9 * * 1-5"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_rejects_corrupt_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cron.json");
+        std::fs::write(&path, "{not json").unwrap();
+
+        let err = CronScheduler::load(&path).await.err().unwrap();
+        assert!(err.starts_with("Invalid cron store"));
+    }
+
+    #[test]
+    fn test_parse_schedule_field_counts() {
+        assert!(parse_schedule("*/15 * * * *").is_ok());
+        assert!(parse_schedule("30 */15 * * * *").is_ok());
+        assert!(parse_schedule("0 0 * * * * *").is_ok());
+        assert!(parse_schedule("@daily").is_ok());
+
+        let err = parse_schedule("* * *").unwrap_err();
+        assert!(err.contains("'* * *'"));
+        assert!(err.contains("got 3"));
+    }
+
+    #[test]
+    fn test_parse_schedule_unix_weekdays() {
+        let after = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let weekday = |expr: &str| {
+            use chrono::Datelike;
+            CronScheduler::upcoming_runs(expr, after, 1).unwrap()[0].weekday()
+        };
+
+        assert_eq!(weekday("0 12 * * 0"), chrono::Weekday::Sun);
+        assert_eq!(weekday("0 12 * * 7"), chrono::Weekday::Sun);
+        assert_eq!(weekday("0 12 * * 1"), chrono::Weekday::Mon);
+        assert_eq!(weekday("0 0 12 * * 6"), chrono::Weekday::Sat);
+
+        // 2026-01-01 is a Thursday; Fri-Sun wraps past the end of the week.
+        let runs = CronScheduler::upcoming_runs("0 12 * * 5-7", after, 3).unwrap();
+        let days: Vec<_> = runs.iter().map(chrono::Datelike::weekday).collect();
+        assert_eq!(
+            days,
+            [chrono::Weekday::Fri, chrono::Weekday::Sat, chrono::Weekday::Sun]
+        );
+
+        // 0-7 is every day, as is 0-7 with a step of one.
+        for expr in ["0 12 * * 0-7", "0 12 * * 0-7/1"] {
+            let runs = CronScheduler::upcoming_runs(expr, after, 7).unwrap();
+            let mut days: Vec<_> = runs.iter().map(chrono::Datelike::weekday).collect();
+            days.dedup();
+            assert_eq!(days.len(), 7, "{}", expr);
+        }
+
+        assert!(parse_schedule("0 12 * * 8").is_err());
+    }
+
+    #[test]
+    fn test_upcoming_runs() {
+        // Friday 2026-01-02, after the 09:30 run.
+        let after = chrono::DateTime::parse_from_rfc3339("2026-01-02T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let runs: Vec<String> = CronScheduler::upcoming_runs("30 9 * * 1-5", after, 5)
+            .unwrap()
+            .iter()
+            .map(|t| t.to_rfc3339())
+            .collect();
+
+        assert_eq!(
+            runs,
+            [
+                "2026-01-05T09:30:00+00:00",
+                "2026-01-06T09:30:00+00:00",
+                "2026-01-07T09:30:00+00:00",
+                "2026-01-08T09:30:00+00:00",
+                "2026-01-09T09:30:00+00:00",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cron_add_rejects_invalid_expression() {
+        let handler = CronAddHandler::new(Arc::new(HandlerContext::new()));
+
+        let err = handler
+            .call(Some(serde_json::json!({
+                "schedule": "61 * * * *",
+                "agent_id": "agent",
+                "prompt": "hello",
+            })))
+            .await
+            .unwrap_err();
+
+        match err {
+            GatewayError::InvalidParams(message) => {
+                assert!(message.contains("Invalid cron expression '61 * * * *'"))
+            }
+            other => panic!("expected InvalidParams, got {:?}", other),
+        }
+        assert!(handler.context.cron_scheduler.list().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cron_add_returns_next_runs() {
+        let handler = CronAddHandler::new(Arc::new(HandlerContext::new()));
+
+        let response = handler
+            .call(Some(serde_json::json!({
+                "schedule": "0 * * * *",
+                "agent_id": "agent",
+                "prompt": "hello",
+                "preview": 3,
+            })))
+            .await
+            .unwrap();
+
+        let runs: Vec<chrono::DateTime<chrono::Utc>> = response["next_runs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| {
+                chrono::DateTime::parse_from_rfc3339(t.as_str().unwrap())
+                    .unwrap()
+                    .with_timezone(&chrono::Utc)
+            })
+            .collect();
+
+        assert_eq!(runs.len(), 3);
+        assert!(runs[0] > chrono::Utc::now() - chrono::Duration::seconds(1));
+        for pair in runs.windows(2) {
+            assert_eq!(pair[1] - pair[0], chrono::Duration::hours(1));
+        }
+        assert!(runs
+            .iter()
+            .all(|t| chrono::Timelike::minute(t) == 0 && chrono::Timelike::second(t) == 0));
+        assert_eq!(handler.context.cron_scheduler.list().await.len(), 1);
+    }
 }
@@ -6,7 +6,9 @@ use crate::methods::MethodHandler;
 use crate::Result;
 use async_trait::async_trait;
 use serde::Serialize;
+use smartassist_providers::Provider;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::debug;
 
 /// Health check response.
@@ -36,6 +38,36 @@ pub struct ComponentHealth {
 
     /// Agent runtime status.
     pub agent: ComponentStatus,
+
+    /// Model provider status.
+    pub provider: ComponentStatus,
+
+    /// Cron scheduler status.
+    pub cron: ComponentStatus,
+}
+
+impl ComponentHealth {
+    /// Roll the component statuses up into an overall status.
+    ///
+    /// Any failing component makes the gateway `degraded`: it is still
+    /// answering requests. Unconfigured components do not count.
+    fn overall(&self) -> &'static str {
+        let components = [
+            &self.sessions,
+            &self.channels,
+            &self.agent,
+            &self.provider,
+            &self.cron,
+        ];
+        if components
+            .iter()
+            .all(|c| c.status == "ok" || c.status == "not_configured")
+        {
+            "ok"
+        } else {
+            "degraded"
+        }
+    }
 }
 
 /// Individual component status.
@@ -46,6 +78,10 @@ pub struct ComponentStatus {
 
     /// Optional message.
     pub message: Option<String>,
+
+    /// Time the component took to answer its check, if measured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
 }
 
 impl ComponentStatus {
@@ -53,6 +89,15 @@ impl ComponentStatus {
         Self {
             status: "ok".to_string(),
             message: None,
+            latency_ms: None,
+        }
+    }
+
+    pub fn ok_with(msg: impl Into<String>) -> Self {
+        Self {
+            status: "ok".to_string(),
+            message: Some(msg.into()),
+            latency_ms: None,
         }
     }
 
@@ -60,6 +105,7 @@ impl ComponentStatus {
         Self {
             status: "not_configured".to_string(),
             message: Some("Component not configured".to_string()),
+            latency_ms: None,
         }
     }
 
@@ -67,23 +113,146 @@ impl ComponentStatus {
         Self {
             status: "error".to_string(),
             message: Some(msg.into()),
+            latency_ms: None,
+        }
+    }
+}
+
+/// Reachability of a model provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderStatus {
+    /// Provider name.
+    pub name: String,
+
+    /// Whether the provider answered its health check.
+    pub reachable: bool,
+
+    /// Latency the provider's health check measured, or the time until the
+    /// check failed or gave up.
+    pub latency_ms: u64,
+
+    /// Number of models the provider listed, if known.
+    pub models: Option<usize>,
+
+    /// Why the provider is unreachable.
+    pub error: Option<String>,
+}
+
+impl ProviderStatus {
+    /// Run the provider's health check, giving up after `timeout`.
+    pub async fn check(provider: &dyn Provider, timeout: Duration) -> Self {
+        let start = Instant::now();
+        let result = tokio::time::timeout(timeout, provider.health_check()).await;
+        let elapsed = start.elapsed();
+
+        let (latency, models, error) = match result {
+            Ok(Ok(health)) => (health.latency, health.models, None),
+            Ok(Err(e)) => (elapsed, None, Some(e.to_string())),
+            Err(_) => (
+                elapsed,
+                None,
+                Some(format!("Health check timed out after {}ms", timeout.as_millis())),
+            ),
+        };
+        Self {
+            name: provider.name().to_string(),
+            reachable: error.is_none(),
+            latency_ms: latency.as_millis() as u64,
+            models,
+            error,
         }
     }
+
+    /// Summarize the check as a component status.
+    fn to_component(&self) -> ComponentStatus {
+        let mut status = match (&self.error, self.models) {
+            (Some(error), _) => ComponentStatus::error(format!("{}: {}", self.name, error)),
+            (None, Some(models)) => {
+                ComponentStatus::ok_with(format!("{}: {} models", self.name, models))
+            }
+            (None, None) => ComponentStatus::ok_with(format!("{}: reachable", self.name)),
+        };
+        status.latency_ms = Some(self.latency_ms);
+        status
+    }
 }
 
+/// Default time limit for each health sub-check.
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default time `status` reuses a provider health check for.
+const DEFAULT_STATUS_CACHE_TTL: Duration = Duration::from_secs(30);
+
 /// Health method handler.
+///
+/// Sub-checks run concurrently, each under its own timeout, so a hung
+/// component shows up as an error instead of stalling the health call.
 pub struct HealthHandler {
-    _context: Arc<HandlerContext>,
+    context: Arc<HandlerContext>,
     start_time: std::time::Instant,
+    check_timeout: Duration,
 }
 
 impl HealthHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
         Self {
-            _context: context,
+            context,
             start_time: std::time::Instant::now(),
+            check_timeout: DEFAULT_CHECK_TIMEOUT,
         }
     }
+
+    /// Set the time limit for each sub-check.
+    pub fn with_check_timeout(mut self, timeout: Duration) -> Self {
+        self.check_timeout = timeout;
+        self
+    }
+
+    /// Run a sub-check, turning a timeout into an error status.
+    async fn timed(
+        &self,
+        check: impl std::future::Future<Output = ComponentStatus>,
+    ) -> ComponentStatus {
+        tokio::time::timeout(self.check_timeout, check)
+            .await
+            .unwrap_or_else(|_| {
+                ComponentStatus::error(format!(
+                    "Check timed out after {}ms",
+                    self.check_timeout.as_millis()
+                ))
+            })
+    }
+
+    /// Sessions are healthy if the session store can be read.
+    async fn check_sessions(&self) -> ComponentStatus {
+        let count = self.context.sessions.read().await.len();
+        ComponentStatus::ok_with(format!("{} sessions", count))
+    }
+
+    /// Report the number of active channels.
+    fn check_channels(&self) -> ComponentStatus {
+        let count = self
+            .context
+            .active_channels
+            .load(std::sync::atomic::Ordering::Relaxed);
+        ComponentStatus::ok_with(format!("{} active channels", count))
+    }
+
+    /// The provider is healthy if it passes its health check.
+    async fn check_provider(&self) -> ComponentStatus {
+        let Some(provider) = &self.context.provider else {
+            return ComponentStatus::not_configured();
+        };
+        ProviderStatus::check(provider.as_ref(), self.check_timeout)
+            .await
+            .to_component()
+    }
+
+    /// The scheduler is live if its job table can be read.
+    async fn check_cron(&self) -> ComponentStatus {
+        let jobs = self.context.cron_scheduler.list().await;
+        ComponentStatus::ok_with(format!("{} jobs", jobs.len()))
+    }
 }
 
 #[async_trait]
@@ -91,21 +260,25 @@ impl MethodHandler for HealthHandler {
     async fn call(&self, _params: Option<serde_json::Value>) -> Result<serde_json::Value> {
         debug!("Health check request");
 
-        // Sessions are always available (in-memory storage)
-        let sessions_status = ComponentStatus::ok();
-
-        // Channels status based on active count
-        let channels_status = ComponentStatus::ok();
+        let (sessions, provider, cron) = tokio::join!(
+            self.timed(self.check_sessions()),
+            self.check_provider(),
+            self.timed(self.check_cron()),
+        );
+
+        let components = ComponentHealth {
+            sessions,
+            channels: self.check_channels(),
+            agent: ComponentStatus::ok(),
+            provider,
+            cron,
+        };
 
         let response = HealthResponse {
-            status: "ok".to_string(),
+            status: components.overall().to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime_seconds: self.start_time.elapsed().as_secs(),
-            components: ComponentHealth {
-                sessions: sessions_status,
-                channels: channels_status,
-                agent: ComponentStatus::ok(),
-            },
+            components,
         };
 
         serde_json::to_value(response).map_err(|e| GatewayError::Internal(e.to_string()))
@@ -133,21 +306,66 @@ pub struct StatusResponse {
     /// Active channels count.
     pub active_channels: usize,
 
+    /// Open WebSocket connections count.
+    pub active_connections: usize,
+
     /// Memory usage (if available).
     pub memory_mb: Option<f64>,
 
     /// CPU usage (if available).
     pub cpu_percent: Option<f64>,
+
+    /// Model provider reachability (if a provider is configured).
+    pub provider: Option<ProviderStatus>,
 }
 
 /// Status method handler.
+///
+/// The provider health check is cached, so clients polling `status` do not
+/// send a request to the provider on every call.
 pub struct StatusHandler {
     context: Arc<HandlerContext>,
+    check_timeout: Duration,
+    cache_ttl: Duration,
+    /// Last provider check and when it finished.
+    provider_cache: tokio::sync::Mutex<Option<(Instant, ProviderStatus)>>,
 }
 
 impl StatusHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
-        Self { context }
+        Self {
+            context,
+            check_timeout: DEFAULT_CHECK_TIMEOUT,
+            cache_ttl: DEFAULT_STATUS_CACHE_TTL,
+            provider_cache: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Set the time limit for the provider health check.
+    pub fn with_check_timeout(mut self, timeout: Duration) -> Self {
+        self.check_timeout = timeout;
+        self
+    }
+
+    /// Set how long a provider health check is reused; zero checks on every
+    /// call.
+    pub fn with_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = ttl;
+        self
+    }
+
+    /// Check the provider, reusing a check younger than the cache TTL.
+    async fn provider_status(&self, provider: &dyn Provider) -> ProviderStatus {
+        // Holding the lock through the check lets concurrent calls share it.
+        let mut cache = self.provider_cache.lock().await;
+        if let Some((checked, status)) = cache.as_ref() {
+            if checked.elapsed() < self.cache_ttl {
+                return status.clone();
+            }
+        }
+        let status = ProviderStatus::check(provider, self.check_timeout).await;
+        *cache = Some((Instant::now(), status.clone()));
+        status
     }
 }
 
@@ -161,6 +379,14 @@ impl MethodHandler for StatusHandler {
             .context
             .active_channels
             .load(std::sync::atomic::Ordering::Relaxed);
+        let active_connections = self
+            .context
+            .active_connections
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let provider = match &self.context.provider {
+            Some(provider) => Some(self.provider_status(provider.as_ref()).await),
+            None => None,
+        };
 
         let response = StatusResponse {
             name: "smartassist-gateway".to_string(),
@@ -169,8 +395,10 @@ impl MethodHandler for StatusHandler {
             arch: std::env::consts::ARCH.to_string(),
             active_sessions,
             active_channels,
+            active_connections,
             memory_mb: None, // TODO: Get actual memory usage
             cpu_percent: None, // TODO: Get actual CPU usage
+            provider,
         };
 
         serde_json::to_value(response).map_err(|e| GatewayError::Internal(e.to_string()))
@@ -180,6 +408,7 @@ impl MethodHandler for StatusHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::handlers::mock_provider::MockProvider;
 
     #[test]
     fn test_component_status_ok() {
@@ -194,4 +423,147 @@ mod tests {
         assert_eq!(status.status, "error");
         assert_eq!(status.message, Some("Something went wrong".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_health_without_provider_is_ok() {
+        let handler = HealthHandler::new(Arc::new(HandlerContext::new()));
+        let response = handler.call(None).await.unwrap();
+
+        assert_eq!(response["status"], "ok");
+        assert_eq!(response["components"]["provider"]["status"], "not_configured");
+        assert_eq!(response["components"]["cron"]["status"], "ok");
+        assert_eq!(response["components"]["channels"]["message"], "0 active channels");
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_provider_latency() {
+        let provider = MockProvider::new("mock", &["small", "large"]);
+        let context = HandlerContext::new().with_provider(Arc::new(provider));
+        let handler = HealthHandler::new(Arc::new(context));
+        let response = handler.call(None).await.unwrap();
+
+        assert_eq!(response["status"], "ok");
+        let provider = &response["components"]["provider"];
+        assert_eq!(provider["status"], "ok");
+        assert_eq!(provider["message"], "mock: 2 models");
+        assert!(provider["latency_ms"].is_u64());
+        // Components without a measured latency leave it out.
+        assert!(response["components"]["cron"].get("latency_ms").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_health_failing_provider_is_degraded() {
+        let context = HandlerContext::new().with_provider(Arc::new(MockProvider::failing("mock")));
+        let handler = HealthHandler::new(Arc::new(context));
+        let response = handler.call(None).await.unwrap();
+
+        assert_eq!(response["status"], "degraded");
+        let provider = &response["components"]["provider"];
+        assert_eq!(provider["status"], "error");
+        assert!(provider["message"].as_str().unwrap().contains("invalid API key"));
+        assert_eq!(response["components"]["sessions"]["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_health_hanging_provider_times_out() {
+        let context = HandlerContext::new().with_provider(Arc::new(MockProvider::hanging("mock")));
+        let handler = HealthHandler::new(Arc::new(context))
+            .with_check_timeout(Duration::from_millis(50));
+
+        let response = tokio::time::timeout(Duration::from_secs(2), handler.call(None))
+            .await
+            .expect("health call should not hang")
+            .unwrap();
+
+        assert_eq!(response["status"], "degraded");
+        let provider = &response["components"]["provider"];
+        assert_eq!(provider["status"], "error");
+        assert!(provider["message"].as_str().unwrap().contains("timed out"));
+        assert_eq!(response["components"]["cron"]["status"], "ok");
+    }
+
+    async fn status(provider: Option<MockProvider>) -> serde_json::Value {
+        let mut context = HandlerContext::new();
+        if let Some(provider) = provider {
+            context = context.with_provider(Arc::new(provider));
+        }
+        let handler = StatusHandler::new(Arc::new(context))
+            .with_check_timeout(Duration::from_millis(50));
+        tokio::time::timeout(Duration::from_secs(2), handler.call(None))
+            .await
+            .expect("status call should not hang")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_status_healthy_provider() {
+        let response = status(Some(MockProvider::new("mock", &["small"]))).await;
+
+        let provider = &response["provider"];
+        assert_eq!(provider["name"], "mock");
+        assert_eq!(provider["reachable"], true);
+        assert_eq!(provider["models"], 1);
+        assert!(provider["error"].is_null());
+        assert!(provider["latency_ms"].as_u64().unwrap() < 50);
+    }
+
+    #[tokio::test]
+    async fn test_status_slow_provider_times_out() {
+        let slow = MockProvider::slow("mock", Duration::from_secs(1));
+        let response = status(Some(slow)).await;
+
+        let provider = &response["provider"];
+        assert_eq!(provider["reachable"], false);
+        assert!(provider["error"].as_str().unwrap().contains("timed out after 50ms"));
+        assert!(provider["latency_ms"].as_u64().unwrap() < 1000);
+    }
+
+    #[tokio::test]
+    async fn test_status_failing_provider() {
+        let response = status(Some(MockProvider::failing("mock"))).await;
+
+        let provider = &response["provider"];
+        assert_eq!(provider["reachable"], false);
+        assert!(provider["error"].as_str().unwrap().contains("invalid API key"));
+        assert!(provider["models"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_provider_measured_latency() {
+        let provider = MockProvider::reporting_latency("mock", Duration::from_millis(1234));
+        let response = status(Some(provider)).await;
+
+        let provider = &response["provider"];
+        assert_eq!(provider["reachable"], true);
+        assert_eq!(provider["latency_ms"], 1234);
+    }
+
+    #[tokio::test]
+    async fn test_status_caches_provider_check() {
+        let provider = Arc::new(MockProvider::new("mock", &["small"]));
+        let context = HandlerContext::new().with_provider(provider.clone());
+        let handler = StatusHandler::new(Arc::new(context));
+
+        for _ in 0..3 {
+            let response = handler.call(None).await.unwrap();
+            assert_eq!(response["provider"]["reachable"], true);
+        }
+        assert_eq!(provider.health_checks(), 1);
+
+        // Once the check is older than the TTL it runs again.
+        let handler = StatusHandler::new(Arc::new(
+            HandlerContext::new().with_provider(provider.clone()),
+        ))
+        .with_cache_ttl(Duration::ZERO);
+        handler.call(None).await.unwrap();
+        handler.call(None).await.unwrap();
+        assert_eq!(provider.health_checks(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_status_without_provider() {
+        let response = status(None).await;
+        assert!(response["provider"].is_null());
+        assert_eq!(response["name"], "smartassist-gateway");
+    }
 }
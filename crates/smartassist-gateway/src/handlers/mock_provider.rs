@@ -0,0 +1,228 @@
+//! Mock model provider for handler tests.
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use smartassist_providers::{
+    ChatOptions, ChatResponse, CompletionStream, Message, ModelInfo, Provider,
+    ProviderCapabilities, ProviderError, ProviderHealth, Result, StreamAccumulator, StreamEvent,
+    TokenCount,
+};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// How the mock answers `list_models`.
+enum Behavior {
+    Models(Vec<&'static str>),
+    Fail,
+    Slow(std::time::Duration),
+    Hang,
+    Stall(Arc<AtomicBool>),
+    Reply(Vec<&'static str>),
+    Latency(std::time::Duration),
+}
+
+/// Sets its flag when dropped, to observe a request being cancelled.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Provider whose `list_models` returns fixed models, fails, or never
+/// returns, and whose chats stall or give a fixed reply.
+pub(crate) struct MockProvider {
+    name: &'static str,
+    behavior: Behavior,
+    health_checks: AtomicUsize,
+}
+
+impl MockProvider {
+    /// A provider listing `models`.
+    pub(crate) fn new(name: &'static str, models: &[&'static str]) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Models(models.to_vec()),
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose calls fail with an authentication error.
+    pub(crate) fn failing(name: &'static str) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Fail,
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider that lists no models after `delay`.
+    pub(crate) fn slow(name: &'static str, delay: std::time::Duration) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Slow(delay),
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose `list_models` never completes.
+    pub(crate) fn hanging(name: &'static str) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Hang,
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose chats never finish: `chat` never returns and
+    /// `chat_stream` sends "partial" then stalls. `dropped` is set once the
+    /// pending request or stream is dropped.
+    pub(crate) fn stalling(name: &'static str, dropped: Arc<AtomicBool>) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Stall(dropped),
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose chats answer with `deltas`, one stream event each.
+    pub(crate) fn replying(name: &'static str, deltas: &[&'static str]) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Reply(deltas.to_vec()),
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// A provider whose health check answers at once, reporting `latency`.
+    pub(crate) fn reporting_latency(name: &'static str, latency: std::time::Duration) -> Self {
+        Self {
+            name,
+            behavior: Behavior::Latency(latency),
+            health_checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of health checks run so far.
+    pub(crate) fn health_checks(&self) -> usize {
+        self.health_checks.load(Ordering::SeqCst)
+    }
+
+    fn reply_stream(deltas: &[&'static str]) -> CompletionStream {
+        let events: Vec<Result<StreamEvent>> = deltas
+            .iter()
+            .map(|delta| {
+                Ok(StreamEvent::ContentDelta {
+                    delta: delta.to_string(),
+                })
+            })
+            .collect();
+        Box::pin(futures::stream::iter(events))
+    }
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        let models = match &self.behavior {
+            Behavior::Models(models) => models,
+            Behavior::Fail => return Err(ProviderError::auth("invalid API key")),
+            Behavior::Slow(delay) => {
+                tokio::time::sleep(*delay).await;
+                return Ok(Vec::new());
+            }
+            Behavior::Hang => return futures::future::pending().await,
+            Behavior::Stall(_) | Behavior::Reply(_) | Behavior::Latency(_) => {
+                return Ok(Vec::new())
+            }
+        };
+        Ok(models
+            .iter()
+            .map(|id| {
+                ModelInfo::new(*id, *id)
+                    .with_limits(8_192, 1_024)
+                    .with_capabilities(&["tools"])
+            })
+            .collect())
+    }
+
+    async fn health_check(&self) -> Result<ProviderHealth> {
+        self.health_checks.fetch_add(1, Ordering::SeqCst);
+        if let Behavior::Latency(latency) = self.behavior {
+            return Ok(ProviderHealth {
+                latency,
+                models: None,
+            });
+        }
+        let start = std::time::Instant::now();
+        let models = self.list_models().await?;
+        Ok(ProviderHealth {
+            latency: start.elapsed(),
+            models: Some(models.len()),
+        })
+    }
+
+    async fn chat(
+        &self,
+        _model: &str,
+        _messages: &[Message],
+        _options: Option<ChatOptions>,
+    ) -> Result<ChatResponse> {
+        if let Behavior::Reply(deltas) = &self.behavior {
+            return StreamAccumulator::collect(Self::reply_stream(deltas)).await;
+        }
+        let Behavior::Stall(dropped) = &self.behavior else {
+            return Err(ProviderError::unsupported("chat"));
+        };
+        let _request = DropFlag(dropped.clone());
+        futures::future::pending().await
+    }
+
+    async fn chat_stream(
+        &self,
+        _model: &str,
+        _messages: &[Message],
+        _options: Option<ChatOptions>,
+    ) -> Result<CompletionStream> {
+        if let Behavior::Reply(deltas) = &self.behavior {
+            return Ok(Self::reply_stream(deltas));
+        }
+        let Behavior::Stall(dropped) = &self.behavior else {
+            return Err(ProviderError::unsupported("chat_stream"));
+        };
+        let request = DropFlag(dropped.clone());
+        let partial = Ok(StreamEvent::ContentDelta {
+            delta: "partial".to_string(),
+        });
+        let stream = futures::stream::iter([partial])
+            .chain(futures::stream::pending())
+            .map(move |event| {
+                let _ = &request;
+                event
+            });
+        Ok(Box::pin(stream))
+    }
+
+    async fn count_tokens(&self, model: &str, _messages: &[Message]) -> Result<TokenCount> {
+        Ok(TokenCount {
+            count: 0,
+            model: model.to_string(),
+        })
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: false,
+            tools: true,
+            vision: false,
+            system_messages: true,
+            max_context: Some(8_192),
+            max_output: Some(1_024),
+        }
+    }
+}
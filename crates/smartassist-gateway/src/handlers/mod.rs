@@ -17,12 +17,15 @@ pub mod skills;
 pub mod system;
 pub mod wizard;
 
-use crate::methods::MethodRegistry;
+#[cfg(test)]
+mod mock_provider;
+
+use crate::methods::{DiscoverHandler, MethodRegistry};
 use smartassist_providers::Provider;
 use std::sync::Arc;
 
 pub use agent::{AgentHandler, AgentStreamHandler};
-pub use chat::{ChatAbortHandler, ChatHandler, ChatHistoryHandler};
+pub use chat::{ActiveChat, ActiveChats, ChatAbortHandler, ChatHandler, ChatHistoryHandler};
 pub use config::{ConfigGetHandler, ConfigPatchHandler, ConfigSchemaHandler, ConfigSetHandler};
 pub use cron::{
     CronAddHandler, CronListHandler, CronRemoveHandler, CronRunHandler, CronRunsHandler,
@@ -47,17 +50,25 @@ pub use send::{SendMessageHandler, SendPollHandler};
 pub use sessions::{
     SessionsDeleteHandler, SessionsListHandler, SessionsPatchHandler, SessionsResolveHandler,
 };
-pub use skills::{SkillsBinsHandler, SkillsInstallHandler, SkillsStatusHandler, SkillsUpdateHandler};
+pub use skills::{
+    HttpSkillSource, SkillManifest, SkillSource, SkillsBinsHandler, SkillsInstallHandler,
+    SkillsStatusHandler, SkillsUpdateHandler,
+};
 pub use system::{
-    LastHeartbeatHandler, LogsTailHandler, SetHeartbeatsHandler, SystemEventHandler,
+    LastHeartbeatHandler, LogsTailHandler, LogsUnfollowHandler, SetHeartbeatsHandler, SystemEventHandler,
     SystemPresenceHandler,
 };
 pub use wizard::{WizardCancelHandler, WizardNextHandler, WizardStartHandler, WizardStatusHandler};
 
 /// Register all built-in method handlers.
-pub async fn register_all(registry: &MethodRegistry, context: HandlerContext) {
+pub async fn register_all(registry: &Arc<MethodRegistry>, context: HandlerContext) {
     let ctx = Arc::new(context);
 
+    // Introspection
+    registry
+        .register("rpc.discover", Arc::new(DiscoverHandler::new(registry)))
+        .await;
+
     // Chat methods
     registry
         .register("chat", Arc::new(ChatHandler::new(ctx.clone())))
@@ -223,6 +234,9 @@ pub async fn register_all(registry: &MethodRegistry, context: HandlerContext) {
     registry
         .register("logs.tail", Arc::new(LogsTailHandler::new(ctx.clone())))
         .await;
+    registry
+        .register("logs.unfollow", Arc::new(LogsUnfollowHandler))
+        .await;
 
     // Agent methods
     registry
@@ -273,9 +287,15 @@ pub struct HandlerContext {
     /// Active channels count.
     pub active_channels: Arc<std::sync::atomic::AtomicUsize>,
 
+    /// Open WebSocket connections count.
+    pub active_connections: Arc<std::sync::atomic::AtomicUsize>,
+
     /// Model provider (optional, for chat completions).
     pub provider: Option<Arc<dyn Provider>>,
 
+    /// All configured model providers, including the chat provider.
+    pub providers: Vec<Arc<dyn Provider>>,
+
     /// Default model to use.
     pub default_model: String,
 
@@ -287,6 +307,18 @@ pub struct HandlerContext {
 
     /// Path to config file for persistence.
     pub config_path: Option<std::path::PathBuf>,
+
+    /// Captured log lines served by `logs.tail`.
+    pub logs: Arc<crate::logs::LogBuffer>,
+
+    /// Channel manager, for reporting channel presence.
+    pub channel_manager: Option<Arc<smartassist_channels::ChannelManager>>,
+
+    /// Chat requests in flight, for `chat.abort`.
+    pub active_chats: Arc<ActiveChats>,
+
+    /// Safety layer whose leak detection cleans chat replies.
+    pub safety: Arc<smartassist_core::safety::SafetyLayer>,
 }
 
 impl Default for HandlerContext {
@@ -295,11 +327,17 @@ impl Default for HandlerContext {
             config: None,
             sessions: Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new())),
             active_channels: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            active_connections: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
             provider: None,
+            providers: Vec::new(),
             default_model: "claude-sonnet-4-20250514".to_string(),
             approval_queue: Arc::new(ApprovalQueue::new()),
             cron_scheduler: Arc::new(CronScheduler::new()),
             config_path: None,
+            logs: crate::logs::LogBuffer::global(),
+            channel_manager: None,
+            active_chats: Arc::new(ActiveChats::default()),
+            safety: Arc::new(smartassist_core::safety::SafetyLayer::default()),
         }
     }
 }
@@ -329,16 +367,67 @@ impl HandlerContext {
 
     /// Set the model provider.
     pub fn with_provider(mut self, provider: Arc<dyn Provider>) -> Self {
+        if !self.providers.iter().any(|p| Arc::ptr_eq(p, &provider)) {
+            self.providers.push(provider.clone());
+        }
         self.provider = Some(provider);
         self
     }
 
+    /// Add a model provider.
+    ///
+    /// The first provider added also serves chat completions unless one
+    /// was already set with [`HandlerContext::with_provider`].
+    pub fn add_provider(mut self, provider: Arc<dyn Provider>) -> Self {
+        if self.provider.is_none() {
+            self.provider = Some(provider.clone());
+        }
+        self.providers.push(provider);
+        self
+    }
+
     /// Set the default model.
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = model.into();
         self
     }
 
+    /// Set the log buffer served by `logs.tail`.
+    pub fn with_logs(mut self, logs: Arc<crate::logs::LogBuffer>) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// Set the safety layer used to clean chat replies.
+    pub fn with_safety(mut self, safety: Arc<smartassist_core::safety::SafetyLayer>) -> Self {
+        self.safety = safety;
+        self
+    }
+
+    /// Set the channel manager used for presence reporting.
+    pub fn with_channel_manager(
+        mut self,
+        manager: Arc<smartassist_channels::ChannelManager>,
+    ) -> Self {
+        self.channel_manager = Some(manager);
+        self
+    }
+
+    /// Set the counter of open WebSocket connections reported by `status`.
+    pub fn with_active_connections(
+        mut self,
+        connections: Arc<std::sync::atomic::AtomicUsize>,
+    ) -> Self {
+        self.active_connections = connections;
+        self
+    }
+
+    /// Set the cron scheduler.
+    pub fn with_cron_scheduler(mut self, scheduler: Arc<CronScheduler>) -> Self {
+        self.cron_scheduler = scheduler;
+        self
+    }
+
     /// Set the config file path for persistence.
     pub fn with_config_path(mut self, path: std::path::PathBuf) -> Self {
         self.config_path = Some(path);
@@ -6,8 +6,10 @@ use crate::methods::MethodHandler;
 use crate::Result;
 use async_trait::async_trait;
 use serde::Serialize;
+use smartassist_providers::Provider;
+use std::collections::HashSet;
 use std::sync::Arc;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Model information.
 #[derive(Debug, Serialize)]
@@ -37,6 +39,33 @@ pub struct ModelInfo {
     pub supports_tools: bool,
 }
 
+impl ModelInfo {
+    /// Convert a model reported by a provider, tagging it with the provider name.
+    fn from_provider(provider: &dyn Provider, model: smartassist_providers::ModelInfo) -> Self {
+        let capabilities = provider.model_capabilities(&model.id);
+        Self {
+            provider: provider.name().to_string(),
+            description: (!model.description.is_empty()).then_some(model.description),
+            context_window: model.context_window.and_then(|w| u32::try_from(w).ok()),
+            max_output_tokens: model.max_output.and_then(|n| u32::try_from(n).ok()),
+            supports_vision: model.supports_vision.unwrap_or(capabilities.vision),
+            supports_tools: model.supports_tools.unwrap_or(capabilities.tools),
+            id: model.id,
+            name: model.name,
+        }
+    }
+}
+
+/// A provider whose models could not be listed.
+#[derive(Debug, Serialize)]
+pub struct ModelsListError {
+    /// Provider name.
+    pub provider: String,
+
+    /// Error message.
+    pub error: String,
+}
+
 /// Models list response.
 #[derive(Debug, Serialize)]
 pub struct ModelsListResponse {
@@ -45,16 +74,59 @@ pub struct ModelsListResponse {
 
     /// Default model ID.
     pub default_model: Option<String>,
+
+    /// Providers that failed to list their models.
+    pub errors: Vec<ModelsListError>,
 }
 
 /// Models list method handler.
 pub struct ModelsListHandler {
-    _context: Arc<HandlerContext>,
+    context: Arc<HandlerContext>,
 }
 
 impl ModelsListHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
-        Self { _context: context }
+        Self { context }
+    }
+
+    /// List the models of all configured providers.
+    ///
+    /// Providers are queried concurrently. A provider that fails is reported
+    /// in the errors list and does not affect the others; models reported
+    /// twice by the same provider are listed once.
+    async fn list_provider_models(&self) -> (Vec<ModelInfo>, Vec<ModelsListError>) {
+        let results = futures::future::join_all(
+            self.context
+                .providers
+                .iter()
+                .map(|provider| async move { (provider, provider.list_models().await) }),
+        )
+        .await;
+
+        let mut models = Vec::new();
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (provider, result) in results {
+            match result {
+                Ok(list) => {
+                    for model in list {
+                        if seen.insert((provider.name().to_string(), model.id.clone())) {
+                            models.push(ModelInfo::from_provider(provider.as_ref(), model));
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to list models for provider {}: {}", provider.name(), e);
+                    errors.push(ModelsListError {
+                        provider: provider.name().to_string(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        (models, errors)
     }
 
     /// Get list of available models.
@@ -206,9 +278,20 @@ impl MethodHandler for ModelsListHandler {
     async fn call(&self, _params: Option<serde_json::Value>) -> Result<serde_json::Value> {
         debug!("Models list request");
 
-        let response = ModelsListResponse {
-            models: Self::get_available_models(),
-            default_model: Some("claude-3-5-sonnet-20241022".to_string()),
+        // Without configured providers, fall back to the built-in catalog.
+        let response = if self.context.providers.is_empty() {
+            ModelsListResponse {
+                models: Self::get_available_models(),
+                default_model: Some("claude-3-5-sonnet-20241022".to_string()),
+                errors: Vec::new(),
+            }
+        } else {
+            let (models, errors) = self.list_provider_models().await;
+            ModelsListResponse {
+                models,
+                default_model: Some(self.context.default_model.clone()),
+                errors,
+            }
         };
 
         serde_json::to_value(response).map_err(|e| GatewayError::Internal(e.to_string()))
@@ -218,6 +301,7 @@ impl MethodHandler for ModelsListHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::handlers::mock_provider::MockProvider;
 
     #[test]
     fn test_models_list() {
@@ -230,4 +314,38 @@ mod tests {
         assert!(providers.contains(&"openai"));
         assert!(providers.contains(&"deepseek"));
     }
+
+    #[tokio::test]
+    async fn test_models_list_merges_providers() {
+        let context = HandlerContext::new()
+            .add_provider(Arc::new(MockProvider::new(
+                "alpha",
+                &["alpha-large", "alpha-small", "alpha-large"],
+            )))
+            .add_provider(Arc::new(MockProvider::failing("broken")))
+            .add_provider(Arc::new(MockProvider::new("beta", &["alpha-large"])));
+        let handler = ModelsListHandler::new(Arc::new(context));
+
+        let response = handler.call(None).await.unwrap();
+
+        let models: Vec<_> = response["models"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| (m["provider"].as_str().unwrap(), m["id"].as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            models,
+            [
+                ("alpha", "alpha-large"),
+                ("alpha", "alpha-small"),
+                ("beta", "alpha-large"),
+            ]
+        );
+
+        let errors = response["errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["provider"], "broken");
+        assert!(errors[0]["error"].as_str().unwrap().contains("invalid API key"));
+    }
 }
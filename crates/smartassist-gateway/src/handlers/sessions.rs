@@ -1,6 +1,6 @@
 //! Session RPC method handlers.
 
-use super::HandlerContext;
+use super::{HandlerContext, SessionData};
 use crate::error::GatewayError;
 use crate::methods::MethodHandler;
 use crate::Result;
@@ -9,6 +9,12 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tracing::debug;
 
+/// Default number of sessions per page.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Maximum number of sessions per page.
+const MAX_PAGE_SIZE: usize = 1000;
+
 /// Parameters for sessions.list method.
 #[derive(Debug, Default, Deserialize)]
 pub struct SessionsListParams {
@@ -18,10 +24,16 @@ pub struct SessionsListParams {
     /// Filter by status.
     pub status: Option<String>,
 
+    /// Only sessions created after this time (RFC 3339).
+    pub created_after: Option<String>,
+
     /// Maximum sessions to return.
     pub limit: Option<usize>,
 
-    /// Offset for pagination.
+    /// Cursor from a previous page's `next_cursor`.
+    pub cursor: Option<String>,
+
+    /// Offset for pagination (ignored when a cursor is given).
     pub offset: Option<usize>,
 }
 
@@ -47,7 +59,56 @@ pub struct SessionInfo {
     pub message_count: usize,
 }
 
+/// Position of a session in the listing order.
+///
+/// Sessions are listed by last activity (falling back to creation time),
+/// most recent first, with ties broken by key so the order is total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SessionCursor {
+    activity_micros: i64,
+    key: String,
+}
+
+impl SessionCursor {
+    fn of(session: &SessionData) -> Self {
+        Self {
+            activity_micros: session
+                .last_activity
+                .unwrap_or(session.created_at)
+                .timestamp_micros(),
+            key: session.key.clone(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!("{}:{}", self.activity_micros, self.key)
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        cursor
+            .split_once(':')
+            .and_then(|(micros, key)| {
+                Some(Self {
+                    activity_micros: micros.parse().ok()?,
+                    key: key.to_string(),
+                })
+            })
+            .ok_or_else(|| GatewayError::InvalidParams(format!("Invalid cursor: {}", cursor)))
+    }
+
+    /// Listing order: most recent activity first, then by key.
+    fn cmp_listing(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .activity_micros
+            .cmp(&self.activity_micros)
+            .then_with(|| self.key.cmp(&other.key))
+    }
+}
+
 /// Sessions list method handler.
+///
+/// Pages are addressed by cursor rather than offset, so sessions created
+/// while a client pages through the list do not shift later pages.
 pub struct SessionsListHandler {
     context: Arc<HandlerContext>,
 }
@@ -62,34 +123,53 @@ impl SessionsListHandler {
 impl MethodHandler for SessionsListHandler {
     async fn call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
         let params: SessionsListParams = params
-            .map(|v| serde_json::from_value(v).unwrap_or_default())
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| GatewayError::InvalidParams(e.to_string()))?
             .unwrap_or_default();
 
         debug!("Sessions list request");
 
+        let limit = params
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let created_after = params
+            .created_after
+            .as_deref()
+            .map(|t| {
+                chrono::DateTime::parse_from_rfc3339(t)
+                    .map(|t| t.with_timezone(&chrono::Utc))
+                    .map_err(|e| GatewayError::InvalidParams(format!("Invalid created_after: {}", e)))
+            })
+            .transpose()?;
+        let cursor = params.cursor.as_deref().map(SessionCursor::decode).transpose()?;
+
         let sessions = self.context.sessions.read().await;
-        let limit = params.limit.unwrap_or(100);
-        let offset = params.offset.unwrap_or(0);
 
-        let mut session_infos: Vec<SessionInfo> = sessions
+        let mut matching: Vec<(SessionCursor, &SessionData)> = sessions
             .values()
             .filter(|s| {
-                // Apply filters
-                if let Some(ref agent_id) = params.agent_id {
-                    if s.agent_id.as_ref() != Some(agent_id) {
-                        return false;
-                    }
-                }
-                if let Some(ref status) = params.status {
-                    if &s.status != status {
-                        return false;
-                    }
-                }
-                true
+                params.agent_id.as_ref().is_none_or(|a| s.agent_id.as_ref() == Some(a))
+                    && params.status.as_ref().is_none_or(|status| &s.status == status)
+                    && created_after.is_none_or(|t| s.created_at > t)
             })
-            .skip(offset)
-            .take(limit)
-            .map(|s| SessionInfo {
+            .map(|s| (SessionCursor::of(s), s))
+            .collect();
+        matching.sort_by(|(a, _), (b, _)| a.cmp_listing(b));
+
+        let total = matching.len();
+        let start = match &cursor {
+            Some(cursor) => matching
+                .partition_point(|(position, _)| position.cmp_listing(cursor).is_le()),
+            None => params.offset.unwrap_or(0).min(total),
+        };
+        let end = (start + limit).min(total);
+
+        let next_cursor = (end < total).then(|| matching[end - 1].0.encode());
+        let session_infos: Vec<SessionInfo> = matching[start..end]
+            .iter()
+            .map(|(_, s)| SessionInfo {
                 key: s.key.clone(),
                 agent_id: s.agent_id.clone(),
                 status: s.status.clone(),
@@ -99,11 +179,10 @@ impl MethodHandler for SessionsListHandler {
             })
             .collect();
 
-        session_infos.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-
         Ok(serde_json::json!({
             "sessions": session_infos,
-            "total": session_infos.len(),
+            "total": total,
+            "next_cursor": next_cursor,
         }))
     }
 }
@@ -298,4 +377,144 @@ mod tests {
         assert!(params.agent_id.is_none());
         assert!(params.limit.is_none());
     }
+
+    fn session(key: &str, agent_id: &str, minutes_ago: i64) -> SessionData {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        SessionData {
+            key: key.to_string(),
+            agent_id: Some(agent_id.to_string()),
+            status: "active".to_string(),
+            created_at: now - chrono::Duration::days(1) - chrono::Duration::minutes(minutes_ago),
+            last_activity: Some(now - chrono::Duration::minutes(minutes_ago)),
+            ..Default::default()
+        }
+    }
+
+    async fn handler(sessions: Vec<SessionData>) -> SessionsListHandler {
+        let context = HandlerContext::new();
+        {
+            let mut map = context.sessions.write().await;
+            for s in sessions {
+                map.insert(s.key.clone(), s);
+            }
+        }
+        SessionsListHandler::new(Arc::new(context))
+    }
+
+    fn keys(response: &serde_json::Value) -> Vec<String> {
+        response["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["key"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sessions_list_orders_by_last_activity() {
+        // "b" and "c" share a timestamp and are ordered by key.
+        let handler = handler(vec![
+            session("a", "main", 30),
+            session("c", "main", 10),
+            session("b", "main", 10),
+            session("d", "main", 0),
+        ])
+        .await;
+
+        let response = handler.call(None).await.unwrap();
+        assert_eq!(keys(&response), ["d", "b", "c", "a"]);
+        assert_eq!(response["total"], 4);
+        assert!(response["next_cursor"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_list_page_boundaries() {
+        let handler = handler((0..4).map(|i| session(&format!("s{}", i), "main", i)).collect()).await;
+
+        // Exactly one full page leaves no cursor.
+        let response = handler.call(Some(serde_json::json!({"limit": 4}))).await.unwrap();
+        assert_eq!(keys(&response).len(), 4);
+        assert!(response["next_cursor"].is_null());
+
+        let first = handler.call(Some(serde_json::json!({"limit": 3}))).await.unwrap();
+        assert_eq!(keys(&first), ["s0", "s1", "s2"]);
+        let last = handler
+            .call(Some(serde_json::json!({"limit": 3, "cursor": first["next_cursor"]})))
+            .await
+            .unwrap();
+        assert_eq!(keys(&last), ["s3"]);
+        assert!(last["next_cursor"].is_null());
+
+        let err = handler
+            .call(Some(serde_json::json!({"cursor": "garbage"})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GatewayError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn test_sessions_list_filters() {
+        let handler = handler(vec![
+            session("a1", "alpha", 5),
+            session("b1", "beta", 4),
+            session("a2", "alpha", 3),
+            session("a3", "alpha", 120),
+        ])
+        .await;
+
+        let response = handler
+            .call(Some(serde_json::json!({"agent_id": "alpha"})))
+            .await
+            .unwrap();
+        assert_eq!(keys(&response), ["a2", "a1", "a3"]);
+
+        // a3 was created two hours before the others.
+        let response = handler
+            .call(Some(serde_json::json!({
+                "agent_id": "alpha",
+                "created_after": "2026-02-28T11:00:00Z",
+            })))
+            .await
+            .unwrap();
+        assert_eq!(keys(&response), ["a2", "a1"]);
+
+        let response = handler
+            .call(Some(serde_json::json!({"status": "closed"})))
+            .await
+            .unwrap();
+        assert_eq!(response["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_list_cursor_is_stable() {
+        let handler = handler((0..10).map(|i| session(&format!("s{:02}", i), "main", i % 4)).collect()).await;
+
+        let first = handler.call(Some(serde_json::json!({"limit": 5}))).await.unwrap();
+        let cursor = first["next_cursor"].as_str().unwrap().to_string();
+
+        // New activity on an unseen session must not shift the next page.
+        handler
+            .context
+            .sessions
+            .write()
+            .await
+            .insert("new".to_string(), session("new", "main", -5));
+
+        let second = handler
+            .call(Some(serde_json::json!({"limit": 5, "cursor": cursor})))
+            .await
+            .unwrap();
+
+        let mut seen = keys(&first);
+        seen.extend(keys(&second));
+        let mut expected: Vec<String> = (0..10).map(|i| format!("s{:02}", i)).collect();
+        expected.sort_by_key(|k| {
+            let i: i64 = k[1..].parse().unwrap();
+            (i % 4, k.clone())
+        });
+        assert_eq!(seen, expected);
+        assert!(second["next_cursor"].is_null());
+    }
 }
@@ -1,15 +1,41 @@
 //! Skills RPC method handlers.
 //!
 //! Handles skill/plugin installation and management.
+//!
+//! Installing a skill downloads the artifact named in its [`SkillManifest`]
+//! and checks it against the manifest's SHA-256 before putting it in place.
+//! Downloads go to a `.part` file first; an interrupted download is resumed
+//! from where it stopped, both on the next attempt and on the next install.
+//! A `.part` file that already has the expected checksum is used as is, and
+//! one the server cannot resume from is downloaded again from the start.
+//! Manifests are only fetched over HTTPS, since they carry the checksum.
 
 use super::HandlerContext;
 use crate::error::GatewayError;
 use crate::methods::MethodHandler;
 use crate::Result;
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::sync::Arc;
-use tracing::debug;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, info, warn};
+
+/// Download attempts per install before giving up.
+const MAX_DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled for each further one.
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Time allowed for one download request; a slower one is resumed.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// File the manifest is saved as next to an installed skill.
+const MANIFEST_FILE: &str = "skill.json";
 
 /// Skill info.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,23 +124,310 @@ impl MethodHandler for SkillsBinsHandler {
     }
 }
 
+/// Description of an installable skill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillManifest {
+    /// Skill ID, also the name of its install directory.
+    pub id: String,
+    /// Skill name.
+    pub name: String,
+    /// Version.
+    pub version: String,
+    /// Description.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The file to download.
+    pub artifact: SkillArtifact,
+}
+
+/// Downloadable file of a skill.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillArtifact {
+    /// Download URL.
+    pub url: String,
+    /// Hex-encoded SHA-256 of the file.
+    pub sha256: String,
+    /// Name to install the file as, by default the last segment of the URL.
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+/// Chunks of a download.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>> + Send>>;
+
+/// A download started by [`SkillSource::fetch`].
+pub struct Download {
+    /// Byte offset the chunks start at: the requested one, or 0 if the
+    /// source cannot resume and sends the whole file.
+    pub offset: u64,
+    /// The file contents from `offset` on.
+    pub chunks: ByteStream,
+}
+
+/// Where skill manifests and artifacts are downloaded from.
+#[async_trait]
+pub trait SkillSource: Send + Sync {
+    /// Fetch `url`, starting at byte `offset` if the source supports it.
+    async fn fetch(&self, url: &str, offset: u64) -> Result<Download>;
+}
+
+/// Downloads over HTTP, resuming with range requests.
+pub struct HttpSkillSource {
+    client: reqwest::Client,
+}
+
+impl HttpSkillSource {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(DOWNLOAD_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    async fn get(&self, url: &str, offset: u64) -> Result<reqwest::Response> {
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| GatewayError::Internal(format!("Failed to fetch {}: {}", url, e)))
+    }
+}
+
+impl Default for HttpSkillSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SkillSource for HttpSkillSource {
+    async fn fetch(&self, url: &str, offset: u64) -> Result<Download> {
+        let mut response = self.get(url, offset).await?;
+        if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+            // What we hold is not a prefix of the file; start over.
+            debug!("Cannot resume {} at byte {}, downloading it again", url, offset);
+            response = self.get(url, 0).await?;
+        }
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GatewayError::Internal(format!(
+                "Failed to fetch {}: HTTP {}",
+                url, status
+            )));
+        }
+
+        let offset = if status == reqwest::StatusCode::PARTIAL_CONTENT {
+            offset
+        } else {
+            0
+        };
+        let url = url.to_string();
+        let chunks = response.bytes_stream().map(move |chunk| {
+            chunk.map(|bytes| bytes.to_vec()).map_err(|e| {
+                GatewayError::Internal(format!("Download of {} interrupted: {}", url, e))
+            })
+        });
+        Ok(Download {
+            offset,
+            chunks: Box::pin(chunks),
+        })
+    }
+}
+
 /// Parameters for skills.install method.
 #[derive(Debug, Deserialize)]
 pub struct SkillsInstallParams {
-    /// Skill package name or URL.
+    /// Skill package name or URL of its manifest.
     pub package: String,
     /// Version constraint.
     pub version: Option<String>,
+    /// Manifest of the skill, used instead of fetching one from `package`.
+    #[serde(default)]
+    pub manifest: Option<SkillManifest>,
 }
 
 /// Skills install handler.
 pub struct SkillsInstallHandler {
     _context: Arc<HandlerContext>,
+    source: Arc<dyn SkillSource>,
+    install_dir: Option<PathBuf>,
 }
 
 impl SkillsInstallHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
-        Self { _context: context }
+        Self {
+            _context: context,
+            source: Arc::new(HttpSkillSource::new()),
+            install_dir: None,
+        }
+    }
+
+    /// Set where manifests and artifacts are downloaded from.
+    pub fn with_source(mut self, source: Arc<dyn SkillSource>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Set the directory skills are installed to (`~/.smartassist/skills`
+    /// by default).
+    pub fn with_install_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.install_dir = Some(dir.into());
+        self
+    }
+
+    async fn manifest(&self, params: &SkillsInstallParams) -> Result<SkillManifest> {
+        if let Some(manifest) = &params.manifest {
+            return Ok(manifest.clone());
+        }
+        if params.package.starts_with("http://") {
+            return Err(GatewayError::InvalidParams(
+                "Skill manifests must be fetched over https".to_string(),
+            ));
+        }
+        if !params.package.starts_with("https://") {
+            return Err(GatewayError::InvalidParams(
+                "Pass the skill manifest, or its URL as the package".to_string(),
+            ));
+        }
+
+        let mut download = self.source.fetch(&params.package, 0).await?;
+        let mut body = Vec::new();
+        while let Some(chunk) = download.chunks.next().await {
+            body.extend(chunk?);
+        }
+        serde_json::from_slice(&body)
+            .map_err(|e| GatewayError::InvalidParams(format!("Invalid skill manifest: {}", e)))
+    }
+
+    /// Download the artifact to `part`, resuming a partial download and
+    /// retrying interrupted ones. Returns the offset the download resumed at.
+    async fn download(&self, url: &str, part: &Path) -> Result<u64> {
+        let mut resumed_at = None;
+        let mut last_error = None;
+
+        for attempt in 0..MAX_DOWNLOAD_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(RETRY_DELAY * 2u32.pow(attempt - 1)).await;
+            }
+            let offset = match tokio::fs::metadata(part).await {
+                Ok(metadata) => metadata.len(),
+                Err(_) => 0,
+            };
+            match self.download_from(url, part, offset).await {
+                Ok(start) => return Ok(*resumed_at.get_or_insert(start)),
+                Err(e) => {
+                    warn!("Download of {} failed (attempt {}): {}", url, attempt + 1, e);
+                    resumed_at.get_or_insert(offset);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(GatewayError::Internal(format!(
+            "Download of {} failed after {} attempts: {}",
+            url,
+            MAX_DOWNLOAD_ATTEMPTS,
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        )))
+    }
+
+    /// Append `url` from `offset` on to `part`, returning where it started.
+    async fn download_from(&self, url: &str, part: &Path, offset: u64) -> Result<u64> {
+        let mut download = self.source.fetch(url, offset).await?;
+        let mut file = if download.offset == 0 {
+            tokio::fs::File::create(part).await?
+        } else if download.offset == offset {
+            tokio::fs::OpenOptions::new().append(true).open(part).await?
+        } else {
+            return Err(GatewayError::Internal(format!(
+                "Source resumed {} at byte {} instead of {}",
+                url, download.offset, offset
+            )));
+        };
+
+        while let Some(chunk) = download.chunks.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.flush().await?;
+        Ok(download.offset)
+    }
+
+    async fn install(&self, manifest: &SkillManifest) -> Result<serde_json::Value> {
+        check_name("skill id", &manifest.id)?;
+        check_name("skill version", &manifest.version)?;
+        let expected = manifest.artifact.sha256.to_ascii_lowercase();
+        if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(GatewayError::InvalidParams(format!(
+                "Invalid sha256 in manifest: {}",
+                manifest.artifact.sha256
+            )));
+        }
+        let file_name = match &manifest.artifact.file_name {
+            Some(name) => name.clone(),
+            None => url_file_name(&manifest.artifact.url),
+        };
+        check_name("artifact file name", &file_name)?;
+
+        let root = match &self.install_dir {
+            Some(dir) => dir.clone(),
+            None => smartassist_core::paths::skills_dir()
+                .map_err(|e| GatewayError::Internal(e.to_string()))?,
+        };
+        let downloads = root.join(".downloads");
+        tokio::fs::create_dir_all(&downloads).await?;
+        let part = downloads.join(format!("{}-{}.part", manifest.id, manifest.version));
+
+        // A download that finished before the install was cut short.
+        let finished = match tokio::fs::metadata(&part).await {
+            Ok(metadata) if metadata.is_file() && sha256_file(&part).await? == expected => {
+                Some(metadata.len())
+            }
+            _ => None,
+        };
+        let resumed_at = match finished {
+            Some(len) => len,
+            None => self.download(&manifest.artifact.url, &part).await?,
+        };
+
+        let actual = sha256_file(&part).await?;
+        if actual != expected {
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(GatewayError::Internal(format!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                manifest.artifact.url, expected, actual
+            )));
+        }
+
+        let skill_dir = root.join(&manifest.id);
+        tokio::fs::create_dir_all(&skill_dir).await?;
+        let path = skill_dir.join(&file_name);
+        tokio::fs::rename(&part, &path).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).await?;
+        }
+        tokio::fs::write(
+            skill_dir.join(MANIFEST_FILE),
+            serde_json::to_vec_pretty(manifest)?,
+        )
+        .await?;
+
+        info!("Installed skill {} {}", manifest.id, manifest.version);
+        Ok(serde_json::json!({
+            "id": manifest.id,
+            "version": manifest.version,
+            "path": path,
+            "sha256": actual,
+            "resumed_at": resumed_at,
+            "installed": true,
+        }))
     }
 }
 
@@ -128,14 +441,50 @@ impl MethodHandler for SkillsInstallHandler {
 
         debug!("Skills install: {}", params.package);
 
-        // TODO: Actually install the skill
+        let manifest = self.manifest(&params).await?;
+        if let Some(version) = &params.version {
+            if *version != manifest.version {
+                return Err(GatewayError::InvalidParams(format!(
+                    "Requested version {} but the manifest is for {}",
+                    version, manifest.version
+                )));
+            }
+        }
+        self.install(&manifest).await
+    }
+}
 
-        Ok(serde_json::json!({
-            "package": params.package,
-            "version": params.version,
-            "installed": true,
-        }))
+/// Reject names that could escape the install directory.
+fn check_name(what: &str, name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(GatewayError::InvalidParams(format!("Invalid {}: {:?}", what, name)));
     }
+    Ok(())
+}
+
+/// Last path segment of a URL, without query or fragment.
+fn url_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default().to_string()
+}
+
+async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
 }
 
 /// Parameters for skills.update method.
@@ -214,4 +563,182 @@ mod tests {
         assert_eq!(json["id"], "test-skill");
         assert_eq!(json["enabled"], true);
     }
+
+    /// Source serving files from memory, optionally failing mid-download.
+    #[derive(Default)]
+    struct MockSource {
+        files: std::collections::HashMap<String, Vec<u8>>,
+        /// Bytes to send before failing, for the next fetch only.
+        fail_after: std::sync::Mutex<Option<usize>>,
+        /// Offsets of the fetches made, in order.
+        requests: std::sync::Mutex<Vec<u64>>,
+    }
+
+    impl MockSource {
+        fn with_file(url: &str, data: &[u8]) -> Self {
+            let mut source = Self::default();
+            source.files.insert(url.to_string(), data.to_vec());
+            source
+        }
+    }
+
+    #[async_trait]
+    impl SkillSource for MockSource {
+        async fn fetch(&self, url: &str, offset: u64) -> Result<Download> {
+            self.requests.lock().unwrap().push(offset);
+            let data = self
+                .files
+                .get(url)
+                .ok_or_else(|| GatewayError::NotFound(url.to_string()))?;
+            let rest = data[offset as usize..].to_vec();
+
+            let chunks: Vec<Result<Vec<u8>>> = match self.fail_after.lock().unwrap().take() {
+                Some(n) => vec![
+                    Ok(rest[..n].to_vec()),
+                    Err(GatewayError::Internal("connection reset".to_string())),
+                ],
+                None => rest.chunks(3).map(|c| Ok(c.to_vec())).collect(),
+            };
+            Ok(Download {
+                offset,
+                chunks: Box::pin(futures::stream::iter(chunks)),
+            })
+        }
+    }
+
+    const URL: &str = "https://skills.example/weather/weather-bin";
+    const ARTIFACT: &[u8] = b"#!/bin/sh\necho sunny\n";
+
+    fn manifest(sha256: &str) -> serde_json::Value {
+        serde_json::json!({
+            "package": "weather",
+            "manifest": {
+                "id": "weather",
+                "name": "Weather",
+                "version": "1.2.0",
+                "artifact": { "url": URL, "sha256": sha256 },
+            },
+        })
+    }
+
+    fn handler(source: Arc<MockSource>, dir: &Path) -> SkillsInstallHandler {
+        SkillsInstallHandler::new(Arc::new(HandlerContext::new()))
+            .with_source(source)
+            .with_install_dir(dir)
+    }
+
+    fn artifact_sha256() -> String {
+        hex::encode(Sha256::digest(ARTIFACT))
+    }
+
+    #[tokio::test]
+    async fn test_install_with_matching_checksum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+
+        let result = handler(source, tmp.path())
+            .call(Some(manifest(&artifact_sha256())))
+            .await
+            .unwrap();
+
+        assert_eq!(result["installed"], true);
+        let installed = tmp.path().join("weather/weather-bin");
+        assert_eq!(std::fs::read(&installed).unwrap(), ARTIFACT);
+        assert!(tmp.path().join("weather").join(MANIFEST_FILE).exists());
+        assert!(!tmp.path().join(".downloads/weather-1.2.0.part").exists());
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_aborts_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+        let wrong = hex::encode(Sha256::digest(b"something else"));
+
+        let err = handler(source, tmp.path())
+            .call(Some(manifest(&wrong)))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        assert!(!tmp.path().join("weather").exists());
+        assert!(!tmp.path().join(".downloads/weather-1.2.0.part").exists());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_resumes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+        *source.fail_after.lock().unwrap() = Some(5);
+
+        let result = handler(source.clone(), tmp.path())
+            .call(Some(manifest(&artifact_sha256())))
+            .await
+            .unwrap();
+
+        assert_eq!(*source.requests.lock().unwrap(), vec![0, 5]);
+        assert_eq!(result["resumed_at"], 0);
+        assert_eq!(std::fs::read(tmp.path().join("weather/weather-bin")).unwrap(), ARTIFACT);
+    }
+
+    #[tokio::test]
+    async fn test_install_resumes_partial_download() {
+        let tmp = tempfile::tempdir().unwrap();
+        let downloads = tmp.path().join(".downloads");
+        std::fs::create_dir_all(&downloads).unwrap();
+        std::fs::write(downloads.join("weather-1.2.0.part"), &ARTIFACT[..7]).unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+
+        let result = handler(source.clone(), tmp.path())
+            .call(Some(manifest(&artifact_sha256())))
+            .await
+            .unwrap();
+
+        assert_eq!(*source.requests.lock().unwrap(), vec![7]);
+        assert_eq!(result["resumed_at"], 7);
+        assert_eq!(std::fs::read(tmp.path().join("weather/weather-bin")).unwrap(), ARTIFACT);
+    }
+
+    #[tokio::test]
+    async fn test_complete_partial_download_is_not_fetched_again() {
+        let tmp = tempfile::tempdir().unwrap();
+        let downloads = tmp.path().join(".downloads");
+        std::fs::create_dir_all(&downloads).unwrap();
+        std::fs::write(downloads.join("weather-1.2.0.part"), ARTIFACT).unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+
+        let result = handler(source.clone(), tmp.path())
+            .call(Some(manifest(&artifact_sha256())))
+            .await
+            .unwrap();
+
+        assert!(source.requests.lock().unwrap().is_empty());
+        assert_eq!(result["resumed_at"], ARTIFACT.len());
+        assert_eq!(std::fs::read(tmp.path().join("weather/weather-bin")).unwrap(), ARTIFACT);
+    }
+
+    #[tokio::test]
+    async fn test_plain_http_manifest_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+        let params = serde_json::json!({ "package": "http://skills.example/weather.json" });
+
+        let err = handler(source.clone(), tmp.path()).call(Some(params)).await.unwrap_err();
+        assert!(err.to_string().contains("https"), "{}", err);
+        assert!(source.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manifest_requires_checksum() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = Arc::new(MockSource::with_file(URL, ARTIFACT));
+        let mut params = manifest("");
+        params["manifest"]["artifact"]
+            .as_object_mut()
+            .unwrap()
+            .remove("sha256");
+
+        let err = handler(source.clone(), tmp.path()).call(Some(params)).await.unwrap_err();
+        assert!(matches!(err, GatewayError::InvalidParams(_)), "{}", err);
+        assert!(source.requests.lock().unwrap().is_empty());
+    }
 }
@@ -4,11 +4,16 @@
 
 use super::HandlerContext;
 use crate::error::GatewayError;
-use crate::methods::MethodHandler;
+use crate::logs::{LogLevel, LogLine};
+use crate::methods::{Connection, MethodHandler};
+use crate::rpc::JsonRpcNotification;
 use crate::Result;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use smartassist_core::types::{ChannelHealth, HealthStatus};
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
 use tracing::debug;
 
 /// System presence info.
@@ -24,6 +29,9 @@ pub struct SystemPresence {
     pub hostname: String,
     /// Active channels.
     pub channels: Vec<String>,
+    /// Health of every configured channel, by instance ID.
+    #[serde(default)]
+    pub channel_health: HashMap<String, ChannelHealth>,
     /// Connected devices.
     pub devices: Vec<String>,
     /// Uptime in seconds.
@@ -55,6 +63,19 @@ impl MethodHandler for SystemPresenceHandler {
             .active_channels
             .load(std::sync::atomic::Ordering::Relaxed);
 
+        let channel_health = match &self.context.channel_manager {
+            Some(manager) => manager.presence().await,
+            None => HashMap::new(),
+        };
+        let mut channels: Vec<String> = channel_health
+            .iter()
+            .filter(|(_, health)| {
+                matches!(health.status, HealthStatus::Healthy | HealthStatus::Degraded)
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        channels.sort();
+
         let presence = SystemPresence {
             version: env!("CARGO_PKG_VERSION").to_string(),
             platform: std::env::consts::OS.to_string(),
@@ -62,7 +83,8 @@ impl MethodHandler for SystemPresenceHandler {
             hostname: hostname::get()
                 .map(|h| h.to_string_lossy().to_string())
                 .unwrap_or_else(|_| "unknown".to_string()),
-            channels: vec![], // TODO: Get from channel manager
+            channels,
+            channel_health,
             devices: vec![],  // TODO: Get from device manager
             uptime_seconds: self.start_time.elapsed().as_secs(),
         };
@@ -176,37 +198,144 @@ impl MethodHandler for SetHeartbeatsHandler {
 pub struct LogsTailParams {
     /// Number of lines to return.
     pub lines: Option<usize>,
-    /// Log level filter.
-    pub level: Option<String>,
-    /// Component filter.
+    /// Minimum log level (trace, debug, info, warn, error).
+    #[serde(alias = "level")]
+    pub min_level: Option<String>,
+    /// Component filter (log target or module prefix).
     pub component: Option<String>,
+    /// Keep pushing new lines as `logs.line` notifications.
+    #[serde(default)]
+    pub follow: bool,
+}
+
+impl LogsTailParams {
+    /// Parse the minimum level, defaulting to trace.
+    fn min_level(&self) -> Result<LogLevel> {
+        self.min_level
+            .as_deref()
+            .map_or(Ok(LogLevel::Trace), str::parse)
+            .map_err(GatewayError::InvalidParams)
+    }
 }
 
 /// Logs tail handler.
+///
+/// Returns recent lines from the captured log buffer. With `follow`, new
+/// lines passing the filter are then pushed to the client as `logs.line`
+/// notifications until the connection closes or `logs.unfollow` is called.
+/// A client that cannot keep up loses its oldest pending lines and is sent
+/// a `logs.dropped` notification with the number lost.
 pub struct LogsTailHandler {
-    _context: Arc<HandlerContext>,
+    context: Arc<HandlerContext>,
 }
 
 impl LogsTailHandler {
     pub fn new(context: Arc<HandlerContext>) -> Self {
-        Self { _context: context }
+        Self { context }
+    }
+
+    fn parse_params(params: Option<serde_json::Value>) -> Result<LogsTailParams> {
+        params
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| GatewayError::InvalidParams(e.to_string()))
+            .map(Option::unwrap_or_default)
+    }
+
+    fn snapshot(&self, params: &LogsTailParams, min_level: LogLevel) -> serde_json::Value {
+        let logs = self.context.logs.recent(
+            params.lines.unwrap_or(100),
+            min_level,
+            params.component.as_deref(),
+        );
+        let count = logs.len();
+        serde_json::json!({
+            "logs": logs,
+            "count": count,
+        })
     }
 }
 
 #[async_trait]
 impl MethodHandler for LogsTailHandler {
     async fn call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
-        let params: LogsTailParams = params
-            .map(|v| serde_json::from_value(v).unwrap_or_default())
-            .unwrap_or_default();
-
+        let params = Self::parse_params(params)?;
         debug!("Logs tail: lines={:?}", params.lines);
 
-        // TODO: Actually read logs
-        Ok(serde_json::json!({
-            "logs": [],
-            "count": 0,
-        }))
+        if params.follow {
+            return Err(GatewayError::InvalidParams(
+                "follow requires a WebSocket connection".to_string(),
+            ));
+        }
+
+        let min_level = params.min_level()?;
+        Ok(self.snapshot(&params, min_level))
+    }
+
+    async fn call_with_connection(
+        &self,
+        params: Option<serde_json::Value>,
+        connection: &Connection,
+    ) -> Result<serde_json::Value> {
+        let params = Self::parse_params(params)?;
+        debug!("Logs tail: lines={:?} follow={}", params.lines, params.follow);
+
+        let min_level = params.min_level()?;
+        if !params.follow {
+            return Ok(self.snapshot(&params, min_level));
+        }
+
+        // Subscribe before taking the snapshot so no line falls in between.
+        let receiver = self.context.logs.subscribe();
+        let mut response = self.snapshot(&params, min_level);
+
+        let subscription = uuid::Uuid::new_v4().to_string();
+        let task = tokio::spawn(forward_log_lines(
+            receiver,
+            connection.notifier(),
+            subscription.clone(),
+            min_level,
+            params.component,
+        ));
+        connection.add_subscription(subscription.clone(), task.abort_handle());
+
+        response["subscription"] = serde_json::json!(subscription);
+        Ok(response)
+    }
+}
+
+/// Push log lines passing the filter to a client until it disconnects.
+///
+/// Nothing here logs: a forwarded line must not produce another one.
+async fn forward_log_lines(
+    mut receiver: broadcast::Receiver<LogLine>,
+    notifier: mpsc::Sender<String>,
+    subscription: String,
+    min_level: LogLevel,
+    component: Option<String>,
+) {
+    loop {
+        let notification = match receiver.recv().await {
+            Ok(line) if line.matches(min_level, component.as_deref()) => JsonRpcNotification::new(
+                "logs.line",
+                serde_json::json!({ "subscription": subscription, "line": line }),
+            ),
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(dropped)) => JsonRpcNotification::new(
+                "logs.dropped",
+                serde_json::json!({ "subscription": subscription, "count": dropped }),
+            ),
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+
+        let Ok(text) = serde_json::to_string(&notification) else {
+            continue;
+        };
+        // Waiting here lets the broadcast channel drop the oldest lines
+        // for this client instead of slowing down the code that logs.
+        if notifier.send(text).await.is_err() {
+            return;
+        }
     }
 }
 
@@ -214,12 +343,53 @@ impl Default for LogsTailParams {
     fn default() -> Self {
         Self {
             lines: Some(100),
-            level: None,
+            min_level: None,
             component: None,
+            follow: false,
         }
     }
 }
 
+/// Parameters for logs.unfollow method.
+#[derive(Debug, Deserialize)]
+pub struct LogsUnfollowParams {
+    /// Subscription ID returned by `logs.tail`.
+    pub subscription: String,
+}
+
+/// Logs unfollow handler.
+pub struct LogsUnfollowHandler;
+
+#[async_trait]
+impl MethodHandler for LogsUnfollowHandler {
+    async fn call(&self, _params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        Err(GatewayError::InvalidParams(
+            "unfollow requires a WebSocket connection".to_string(),
+        ))
+    }
+
+    async fn call_with_connection(
+        &self,
+        params: Option<serde_json::Value>,
+        connection: &Connection,
+    ) -> Result<serde_json::Value> {
+        let params: LogsUnfollowParams = params
+            .ok_or_else(|| GatewayError::InvalidParams("Missing parameters".to_string()))
+            .and_then(|v| {
+                serde_json::from_value(v).map_err(|e| GatewayError::InvalidParams(e.to_string()))
+            })?;
+
+        if !connection.remove_subscription(&params.subscription) {
+            return Err(GatewayError::NotFound(format!(
+                "Subscription '{}' not found",
+                params.subscription
+            )));
+        }
+
+        Ok(serde_json::json!({ "unfollowed": true }))
+    }
+}
+
 // TryFrom implementations
 
 impl TryFrom<serde_json::Value> for SystemEventParams {
@@ -248,6 +418,7 @@ mod tests {
             arch: "aarch64".to_string(),
             hostname: "test-host".to_string(),
             channels: vec!["telegram".to_string()],
+            channel_health: HashMap::new(),
             devices: vec![],
             uptime_seconds: 3600,
         };
@@ -256,4 +427,140 @@ mod tests {
         assert_eq!(json["version"], "0.1.0");
         assert_eq!(json["uptime_seconds"], 3600);
     }
+
+    #[tokio::test]
+    async fn test_system_presence_reports_channel_health() {
+        let handler = SystemPresenceHandler::new(Arc::new(HandlerContext::new()));
+        let json = handler.call(None).await.unwrap();
+        assert_eq!(json["channels"], serde_json::json!([]));
+        assert_eq!(json["channel_health"], serde_json::json!({}));
+
+        let manager = Arc::new(smartassist_channels::ChannelManager::new());
+        let context = HandlerContext::new().with_channel_manager(manager);
+        let handler = SystemPresenceHandler::new(Arc::new(context));
+        let presence: SystemPresence =
+            serde_json::from_value(handler.call(None).await.unwrap()).unwrap();
+        assert!(presence.channel_health.is_empty());
+    }
+
+    fn follow_context() -> (Arc<crate::logs::LogBuffer>, LogsTailHandler) {
+        let logs = Arc::new(crate::logs::LogBuffer::new(100));
+        let context = HandlerContext::new().with_logs(logs.clone());
+        (logs, LogsTailHandler::new(Arc::new(context)))
+    }
+
+    fn emit(logs: &Arc<crate::logs::LogBuffer>, f: impl FnOnce()) {
+        use tracing_subscriber::layer::SubscriberExt;
+        let subscriber =
+            tracing_subscriber::registry().with(crate::logs::LogLayer::new(logs.clone()));
+        tracing::subscriber::with_default(subscriber, f);
+    }
+
+    async fn next_notification(rx: &mut mpsc::Receiver<String>) -> Option<serde_json::Value> {
+        tokio::time::timeout(std::time::Duration::from_millis(200), rx.recv())
+            .await
+            .ok()
+            .flatten()
+            .map(|text| serde_json::from_str(&text).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_logs_tail_snapshot_filters_level() {
+        let (logs, handler) = follow_context();
+        emit(&logs, || {
+            tracing::debug!("noise");
+            tracing::error!("disk full");
+        });
+
+        let response = handler
+            .call(Some(serde_json::json!({ "min_level": "warn" })))
+            .await
+            .unwrap();
+
+        assert_eq!(response["count"], 1);
+        assert_eq!(response["logs"][0]["message"], "disk full");
+        assert_eq!(response["logs"][0]["level"], "error");
+
+        let err = handler
+            .call(Some(serde_json::json!({ "min_level": "loud" })))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GatewayError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn test_logs_tail_follow_pushes_new_lines_above_level() {
+        let (logs, handler) = follow_context();
+        let (tx, mut rx) = mpsc::channel(16);
+        let connection = Connection::new("client-1", tx);
+
+        let response = handler
+            .call_with_connection(
+                Some(serde_json::json!({ "follow": true, "min_level": "warn" })),
+                &connection,
+            )
+            .await
+            .unwrap();
+        let subscription = response["subscription"].as_str().unwrap().to_string();
+        assert_eq!(response["count"], 0);
+
+        emit(&logs, || {
+            tracing::info!("request handled");
+            tracing::warn!("slow request");
+            tracing::debug!("cache miss");
+            tracing::error!("request failed");
+        });
+
+        let first = next_notification(&mut rx).await.unwrap();
+        assert_eq!(first["method"], "logs.line");
+        assert_eq!(first["params"]["subscription"], subscription.as_str());
+        assert_eq!(first["params"]["line"]["message"], "slow request");
+
+        let second = next_notification(&mut rx).await.unwrap();
+        assert_eq!(second["params"]["line"]["message"], "request failed");
+
+        assert!(next_notification(&mut rx).await.is_none());
+
+        // After unfollowing, nothing more is pushed.
+        LogsUnfollowHandler
+            .call_with_connection(
+                Some(serde_json::json!({ "subscription": subscription })),
+                &connection,
+            )
+            .await
+            .unwrap();
+        emit(&logs, || tracing::error!("after unfollow"));
+        assert!(next_notification(&mut rx).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_logs_tail_follow_reports_dropped_lines() {
+        let (logs, handler) = follow_context();
+        let (tx, mut rx) = mpsc::channel(1);
+        let connection = Connection::new("slow-client", tx);
+
+        handler
+            .call_with_connection(Some(serde_json::json!({ "follow": true })), &connection)
+            .await
+            .unwrap();
+
+        // Far more lines than the follow buffer holds, without reading.
+        emit(&logs, || {
+            for i in 0..1000 {
+                tracing::info!("line {}", i);
+            }
+        });
+
+        let mut dropped = 0;
+        let mut last = String::new();
+        while let Some(notification) = next_notification(&mut rx).await {
+            match notification["method"].as_str().unwrap() {
+                "logs.dropped" => dropped += notification["params"]["count"].as_u64().unwrap(),
+                _ => last = notification["params"]["line"]["message"].as_str().unwrap().to_string(),
+            }
+        }
+
+        assert!(dropped > 0);
+        assert_eq!(last, "line 999");
+    }
 }
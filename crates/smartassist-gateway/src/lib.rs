@@ -8,14 +8,21 @@
 
 pub mod error;
 pub mod handlers;
+pub mod logs;
 pub mod methods;
+pub mod progress;
+pub mod reload;
 pub mod rpc;
 pub mod server;
 pub mod session;
 
 pub use error::GatewayError;
 pub use handlers::HandlerContext;
-pub use methods::{MethodHandler, MethodRegistry};
+pub use logs::{LogBuffer, LogLayer};
+pub use methods::{
+    Connection, DiscoverHandler, MethodHandler, MethodRegistry, MethodSchema, PluginMethodAdapter,
+    RpcPlugin,
+};
 pub use rpc::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
 pub use server::{Gateway, GatewayConfig};
 
@@ -0,0 +1,298 @@
+//! In-process log capture for the `logs.tail` method.
+//!
+//! [`LogLayer`] is a `tracing_subscriber` layer that records every event it
+//! sees into a [`LogBuffer`]: a fixed-size ring of recent lines for snapshots
+//! plus a broadcast channel for followers. Recording never blocks; a follower
+//! that falls behind loses its oldest unread lines instead.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Number of recent lines kept for snapshots.
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// Number of unread lines a follower may fall behind before losing lines.
+const FOLLOW_BUFFER: usize = 256;
+
+/// Log severity, ordered from most to least verbose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl std::str::FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(Self::Trace),
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" | "warning" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            _ => Err(format!(
+                "Invalid log level '{}': expected trace, debug, info, warn or error",
+                s
+            )),
+        }
+    }
+}
+
+impl From<&tracing::Level> for LogLevel {
+    fn from(level: &tracing::Level) -> Self {
+        match *level {
+            tracing::Level::TRACE => Self::Trace,
+            tracing::Level::DEBUG => Self::Debug,
+            tracing::Level::INFO => Self::Info,
+            tracing::Level::WARN => Self::Warn,
+            tracing::Level::ERROR => Self::Error,
+        }
+    }
+}
+
+/// A captured log line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    /// When the event was recorded.
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+
+    /// Event level.
+    pub level: LogLevel,
+
+    /// Event target (usually the module path).
+    pub target: String,
+
+    /// Message followed by any other fields as `key=value`.
+    pub message: String,
+}
+
+impl LogLine {
+    /// Check whether the line passes a level and component filter.
+    ///
+    /// The component matches the target or any of its module prefixes.
+    pub fn matches(&self, min_level: LogLevel, component: Option<&str>) -> bool {
+        self.level >= min_level
+            && component.is_none_or(|c| {
+                self.target == c
+                    || self
+                        .target
+                        .strip_prefix(c)
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+    }
+}
+
+/// Ring buffer of recent log lines with a broadcast feed of new ones.
+pub struct LogBuffer {
+    lines: Mutex<VecDeque<LogLine>>,
+    capacity: usize,
+    tx: broadcast::Sender<LogLine>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl LogBuffer {
+    /// Create a buffer keeping the last `capacity` lines.
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(FOLLOW_BUFFER);
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            tx,
+        }
+    }
+
+    /// The process-wide buffer fed by [`LogLayer::global`].
+    pub fn global() -> Arc<LogBuffer> {
+        static GLOBAL: OnceLock<Arc<LogBuffer>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(LogBuffer::default())).clone()
+    }
+
+    /// Record a line, evicting the oldest if the buffer is full.
+    pub fn push(&self, line: LogLine) {
+        if self.capacity > 0 {
+            let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            lines.push_back(line.clone());
+        }
+
+        // No followers is not an error.
+        let _ = self.tx.send(line);
+    }
+
+    /// Get up to `count` of the most recent lines passing the filter, oldest first.
+    pub fn recent(&self, count: usize, min_level: LogLevel, component: Option<&str>) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        let mut recent: Vec<LogLine> = lines
+            .iter()
+            .rev()
+            .filter(|line| line.matches(min_level, component))
+            .take(count)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Subscribe to lines recorded from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<LogLine> {
+        self.tx.subscribe()
+    }
+}
+
+/// Tracing layer that records events into a [`LogBuffer`].
+pub struct LogLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl LogLayer {
+    /// Create a layer recording into `buffer`.
+    pub fn new(buffer: Arc<LogBuffer>) -> Self {
+        Self { buffer }
+    }
+
+    /// Create a layer recording into [`LogBuffer::global`].
+    pub fn global() -> Self {
+        Self::new(LogBuffer::global())
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        self.buffer.push(LogLine {
+            timestamp: chrono::Utc::now(),
+            level: metadata.level().into(),
+            target: metadata.target().to_string(),
+            message: visitor.finish(),
+        });
+    }
+}
+
+/// Formats an event's fields into a single message string.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(mut self) -> String {
+        if self.message.is_empty() {
+            self.fields.trim_start().to_string()
+        } else {
+            self.message.push_str(&self.fields);
+            self.message
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn line(level: LogLevel, target: &str, message: &str) -> LogLine {
+        LogLine {
+            timestamp: chrono::Utc::now(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!("WARN".parse::<LogLevel>(), Ok(LogLevel::Warn));
+        assert!("loud".parse::<LogLevel>().is_err());
+        assert!(LogLevel::Error > LogLevel::Trace);
+    }
+
+    #[test]
+    fn test_ring_buffer_evicts_oldest() {
+        let buffer = LogBuffer::new(2);
+        for message in ["a", "b", "c"] {
+            buffer.push(line(LogLevel::Info, "app", message));
+        }
+
+        let messages: Vec<_> = buffer
+            .recent(10, LogLevel::Trace, None)
+            .into_iter()
+            .map(|l| l.message)
+            .collect();
+        assert_eq!(messages, ["b", "c"]);
+    }
+
+    #[test]
+    fn test_recent_filters() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(line(LogLevel::Debug, "app::db", "query"));
+        buffer.push(line(LogLevel::Warn, "app::db", "slow query"));
+        buffer.push(line(LogLevel::Error, "app_other", "boom"));
+        buffer.push(line(LogLevel::Error, "app::http", "500"));
+
+        let warn: Vec<_> = buffer
+            .recent(10, LogLevel::Warn, Some("app"))
+            .into_iter()
+            .map(|l| l.message)
+            .collect();
+        assert_eq!(warn, ["slow query", "500"]);
+
+        let last = buffer.recent(1, LogLevel::Trace, None);
+        assert_eq!(last[0].message, "500");
+    }
+
+    #[test]
+    fn test_layer_records_events() {
+        let buffer = Arc::new(LogBuffer::new(10));
+        let subscriber = tracing_subscriber::registry().with(LogLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(user = "alice", "login failed");
+        });
+
+        let lines = buffer.recent(10, LogLevel::Trace, None);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].level, LogLevel::Warn);
+        assert_eq!(lines[0].message, "login failed user=alice");
+        assert!(lines[0].target.ends_with("logs::tests"));
+    }
+}
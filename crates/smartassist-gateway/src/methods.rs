@@ -6,9 +6,10 @@ use async_trait::async_trait;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::debug;
+use std::sync::{Arc, Weak};
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::AbortHandle;
+use tracing::{debug, info, warn};
 
 /// Type alias for method handler futures.
 pub type MethodFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value>> + Send>>;
@@ -18,6 +19,217 @@ pub type MethodFuture = Pin<Box<dyn Future<Output = Result<serde_json::Value>> +
 pub trait MethodHandler: Send + Sync {
     /// Handle the method call.
     async fn call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value>;
+
+    /// Handle the method call on behalf of a connected client.
+    ///
+    /// Handlers that push notifications to the client override this; by
+    /// default the connection is ignored.
+    async fn call_with_connection(
+        &self,
+        params: Option<serde_json::Value>,
+        _connection: &Connection,
+    ) -> Result<serde_json::Value> {
+        self.call(params).await
+    }
+
+    /// Describe the method's parameters and result for `rpc.discover`.
+    ///
+    /// Handlers without a schema are still listed, by name only.
+    fn schema(&self) -> Option<MethodSchema> {
+        None
+    }
+}
+
+/// Parameter and result shapes of a method, as JSON Schemas.
+#[derive(Debug, Clone)]
+pub struct MethodSchema {
+    /// One-line description of the method.
+    pub summary: Option<String>,
+
+    /// Schema of the params object; each property is one named parameter.
+    pub params: serde_json::Value,
+
+    /// Schema of the result.
+    pub result: serde_json::Value,
+}
+
+impl MethodSchema {
+    /// Create a schema from a params object schema and a result schema.
+    pub fn new(params: serde_json::Value, result: serde_json::Value) -> Self {
+        Self {
+            summary: None,
+            params,
+            result,
+        }
+    }
+
+    /// Set the summary.
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Render as an OpenRPC method object.
+    fn to_openrpc(&self, name: &str) -> serde_json::Value {
+        let required: Vec<&str> = self.params["required"]
+            .as_array()
+            .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+            .unwrap_or_default();
+        let params: Vec<serde_json::Value> = self.params["properties"]
+            .as_object()
+            .map(|properties| {
+                properties
+                    .iter()
+                    .map(|(param, schema)| {
+                        serde_json::json!({
+                            "name": param,
+                            "required": required.contains(&param.as_str()),
+                            "schema": schema,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut method = serde_json::json!({
+            "name": name,
+            "paramStructure": "by-name",
+            "params": params,
+            "result": { "name": "result", "schema": self.result },
+        });
+        if let Some(summary) = &self.summary {
+            method["summary"] = serde_json::json!(summary);
+        }
+        method
+    }
+}
+
+/// A connected client, as seen by method handlers.
+///
+/// Handlers push notifications through [`Connection::notifier`] and register
+/// background tasks feeding them as subscriptions, which are aborted when
+/// the connection closes.
+pub struct Connection {
+    /// Client ID.
+    id: String,
+
+    /// Outgoing messages to the client.
+    outbound: mpsc::Sender<String>,
+
+    /// Active subscriptions by ID.
+    subscriptions: std::sync::Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl Connection {
+    /// Create a connection sending outgoing messages to `outbound`.
+    pub fn new(id: impl Into<String>, outbound: mpsc::Sender<String>) -> Self {
+        Self {
+            id: id.into(),
+            outbound,
+            subscriptions: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the client ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get a sender for messages to the client.
+    pub fn notifier(&self) -> mpsc::Sender<String> {
+        self.outbound.clone()
+    }
+
+    /// Tie a task to the connection, returning the subscription ID.
+    pub fn add_subscription(&self, id: impl Into<String>, task: AbortHandle) -> String {
+        let id = id.into();
+        self.subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), task);
+        id
+    }
+
+    /// Abort a subscription. Returns false if it does not exist.
+    pub fn remove_subscription(&self, id: &str) -> bool {
+        let task = self
+            .subscriptions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(id);
+        task.map(|task| task.abort()).is_some()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let subscriptions = self.subscriptions.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (_, task) in subscriptions.drain() {
+            task.abort();
+        }
+    }
+}
+
+/// A plugin that adds its own RPC methods to the gateway.
+///
+/// The methods are registered as `plugin.<name>.<method>`, so a plugin can
+/// neither shadow a built-in method nor another plugin's.
+pub trait RpcPlugin: Send + Sync {
+    /// Plugin name, used as the namespace of its methods.
+    fn name(&self) -> &str;
+
+    /// Methods the plugin provides, keyed by their name within the namespace.
+    fn rpc_methods(&self) -> Vec<(String, Arc<dyn MethodHandler>)> {
+        Vec::new()
+    }
+}
+
+/// Adapter that wraps a plugin SDK method to implement [`MethodHandler`].
+pub struct PluginMethodAdapter {
+    method: Arc<dyn smartassist_plugin_sdk::PluginRpcMethod>,
+}
+
+impl PluginMethodAdapter {
+    /// Create a new adapter for a plugin method.
+    pub fn new(method: Arc<dyn smartassist_plugin_sdk::PluginRpcMethod>) -> Self {
+        Self { method }
+    }
+}
+
+#[async_trait]
+impl MethodHandler for PluginMethodAdapter {
+    async fn call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        use smartassist_plugin_sdk::PluginError;
+
+        self.method.call(params).await.map_err(|e| match e {
+            PluginError::Config(msg) => GatewayError::InvalidParams(msg),
+            PluginError::NotFound(msg) => GatewayError::NotFound(msg),
+            other => GatewayError::Internal(other.to_string()),
+        })
+    }
+}
+
+/// A loaded SDK plugin seen through [`RpcPlugin`].
+struct LoadedRpcPlugin<'a> {
+    name: String,
+    plugin: &'a dyn smartassist_plugin_sdk::RpcMethodPlugin,
+}
+
+impl RpcPlugin for LoadedRpcPlugin<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn rpc_methods(&self) -> Vec<(String, Arc<dyn MethodHandler>)> {
+        self.plugin
+            .rpc_methods()
+            .into_iter()
+            .map(|(name, method)| {
+                let handler: Arc<dyn MethodHandler> = Arc::new(PluginMethodAdapter::new(method));
+                (name, handler)
+            })
+            .collect()
+    }
 }
 
 /// Registry for RPC methods.
@@ -51,6 +263,68 @@ impl MethodRegistry {
         methods.insert(name.into(), handler);
     }
 
+    /// Register the RPC methods a plugin provides, namespaced under its name.
+    ///
+    /// Returns the registered method names. If any of them is already taken,
+    /// or the plugin lists a method twice, nothing is registered.
+    pub async fn register_plugin(&self, plugin: &dyn RpcPlugin) -> Result<Vec<String>> {
+        let prefix = format!("plugin.{}.", plugin.name());
+        let plugin_methods: Vec<(String, Arc<dyn MethodHandler>)> = plugin
+            .rpc_methods()
+            .into_iter()
+            .map(|(name, handler)| (format!("{}{}", prefix, name), handler))
+            .collect();
+
+        let mut methods = self.methods.write().await;
+        let mut names: Vec<String> = Vec::with_capacity(plugin_methods.len());
+        for (name, _) in &plugin_methods {
+            if methods.contains_key(name) || names.contains(name) {
+                return Err(GatewayError::MethodConflict(name.clone()));
+            }
+            names.push(name.clone());
+        }
+
+        for (name, handler) in plugin_methods {
+            debug!("Registering plugin method: {}", name);
+            methods.insert(name, handler);
+        }
+        Ok(names)
+    }
+
+    /// Register the RPC methods of every loaded plugin that provides them.
+    ///
+    /// Each plugin goes through [`MethodRegistry::register_plugin`]; one
+    /// whose methods collide is skipped with a warning. Returns the names
+    /// of the registered methods.
+    pub async fn register_plugin_methods(
+        &self,
+        loader: &smartassist_plugin_sdk::PluginLoader,
+    ) -> Vec<String> {
+        let mut registered = Vec::new();
+        for plugin_meta in loader.list() {
+            let Some(plugin) = loader.get(&plugin_meta.name) else {
+                continue;
+            };
+            let Some(rpc_plugin) = plugin.as_rpc_plugin() else {
+                continue;
+            };
+            let plugin = LoadedRpcPlugin {
+                name: plugin_meta.name.clone(),
+                plugin: rpc_plugin,
+            };
+            match self.register_plugin(&plugin).await {
+                Ok(names) => {
+                    for name in &names {
+                        info!("Registered plugin method: {}", name);
+                    }
+                    registered.extend(names);
+                }
+                Err(e) => warn!("Skipping RPC methods of plugin {}: {}", plugin_meta.name, e),
+            }
+        }
+        registered
+    }
+
     /// Unregister a method.
     pub async fn unregister(&self, name: &str) {
         let mut methods = self.methods.write().await;
@@ -63,21 +337,68 @@ impl MethodRegistry {
         name: &str,
         params: Option<serde_json::Value>,
     ) -> Result<serde_json::Value> {
-        let methods = self.methods.read().await;
-
-        let handler = methods
-            .get(name)
-            .ok_or_else(|| GatewayError::MethodNotFound(name.to_string()))?;
+        let handler = self.handler(name).await?;
 
         debug!("Calling method: {}", name);
         handler.call(params).await
     }
 
+    /// Call a method on behalf of a connected client.
+    pub async fn call_with_connection(
+        &self,
+        name: &str,
+        params: Option<serde_json::Value>,
+        connection: &Connection,
+    ) -> Result<serde_json::Value> {
+        let handler = self.handler(name).await?;
+
+        debug!("Calling method: {} (client: {})", name, connection.id());
+        handler.call_with_connection(params, connection).await
+    }
+
+    /// Look up a method handler.
+    async fn handler(&self, name: &str) -> Result<Arc<dyn MethodHandler>> {
+        let methods = self.methods.read().await;
+        methods
+            .get(name)
+            .cloned()
+            .ok_or_else(|| GatewayError::MethodNotFound(name.to_string()))
+    }
+
     /// List registered methods.
     pub async fn list(&self) -> Vec<String> {
         let methods = self.methods.read().await;
         methods.keys().cloned().collect()
     }
+
+    /// Build an OpenRPC document describing every registered method,
+    /// sorted by name.
+    pub async fn discover(&self) -> serde_json::Value {
+        let methods = self.methods.read().await;
+        let mut names: Vec<&String> = methods.keys().collect();
+        names.sort();
+
+        let methods: Vec<serde_json::Value> = names
+            .into_iter()
+            .map(|name| match methods[name].schema() {
+                Some(schema) => schema.to_openrpc(name),
+                None => serde_json::json!({
+                    "name": name,
+                    "params": [],
+                    "result": { "name": "result", "schema": {} },
+                }),
+            })
+            .collect();
+
+        serde_json::json!({
+            "openrpc": "1.2.6",
+            "info": {
+                "title": "smartassist-gateway",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "methods": methods,
+        })
+    }
 }
 
 /// Helper macro for creating method handlers from closures.
@@ -116,6 +437,23 @@ impl MethodHandler for SystemInfoHandler {
             "arch": std::env::consts::ARCH,
         }))
     }
+
+    fn schema(&self) -> Option<MethodSchema> {
+        let schema = MethodSchema::new(
+            serde_json::json!({ "type": "object", "properties": {} }),
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "version": { "type": "string" },
+                    "platform": { "type": "string" },
+                    "arch": { "type": "string" },
+                },
+                "required": ["name", "version", "platform", "arch"],
+            }),
+        );
+        Some(schema.with_summary("Gateway name, version and host platform"))
+    }
 }
 
 /// Ping method.
@@ -129,6 +467,21 @@ impl MethodHandler for PingHandler {
             "timestamp": chrono::Utc::now().to_rfc3339(),
         }))
     }
+
+    fn schema(&self) -> Option<MethodSchema> {
+        let schema = MethodSchema::new(
+            serde_json::json!({ "type": "object", "properties": {} }),
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pong": { "type": "boolean" },
+                    "timestamp": { "type": "string", "format": "date-time" },
+                },
+                "required": ["pong", "timestamp"],
+            }),
+        );
+        Some(schema.with_summary("Check that the gateway is responding"))
+    }
 }
 
 /// List methods handler.
@@ -152,6 +505,48 @@ impl MethodHandler for ListMethodsHandler {
     }
 }
 
+/// `rpc.discover`: describe the registry's methods as an OpenRPC document.
+///
+/// Holds the registry weakly, since the registry owns this handler.
+pub struct DiscoverHandler {
+    registry: Weak<MethodRegistry>,
+}
+
+impl DiscoverHandler {
+    pub fn new(registry: &Arc<MethodRegistry>) -> Self {
+        Self {
+            registry: Arc::downgrade(registry),
+        }
+    }
+}
+
+#[async_trait]
+impl MethodHandler for DiscoverHandler {
+    async fn call(&self, _params: Option<serde_json::Value>) -> Result<serde_json::Value> {
+        let registry = self
+            .registry
+            .upgrade()
+            .ok_or_else(|| GatewayError::Internal("Method registry was dropped".to_string()))?;
+        Ok(registry.discover().await)
+    }
+
+    fn schema(&self) -> Option<MethodSchema> {
+        let schema = MethodSchema::new(
+            serde_json::json!({ "type": "object", "properties": {} }),
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "openrpc": { "type": "string" },
+                    "info": { "type": "object" },
+                    "methods": { "type": "array", "items": { "type": "object" } },
+                },
+                "required": ["openrpc", "info", "methods"],
+            }),
+        );
+        Some(schema.with_summary("List the gateway's methods and their schemas"))
+    }
+}
+
 /// Register built-in methods.
 pub async fn register_builtin(registry: &MethodRegistry) {
     registry
@@ -180,4 +575,257 @@ mod tests {
         let result = registry.call("nonexistent", None).await;
         assert!(matches!(result, Err(GatewayError::MethodNotFound(_))));
     }
+
+    /// Plugin exposing a `forecast` method, and `status` if `extra` is set.
+    struct WeatherPlugin {
+        extra: Option<&'static str>,
+    }
+
+    impl RpcPlugin for WeatherPlugin {
+        fn name(&self) -> &str {
+            "weather"
+        }
+
+        fn rpc_methods(&self) -> Vec<(String, Arc<dyn MethodHandler>)> {
+            let mut methods = vec![(
+                "forecast".to_string(),
+                crate::method_handler!(|params: Option<serde_json::Value>| async move {
+                    let city = params.unwrap_or_default()["city"].clone();
+                    Ok(serde_json::json!({ "city": city, "sky": "clear" }))
+                }),
+            )];
+            if let Some(name) = self.extra {
+                methods.push((
+                    name.to_string(),
+                    crate::method_handler!(|_params: Option<serde_json::Value>| async move {
+                        Ok(serde_json::json!("ok"))
+                    }),
+                ));
+            }
+            methods
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_plugin_methods() {
+        let registry = MethodRegistry::new();
+
+        let names = registry
+            .register_plugin(&WeatherPlugin { extra: None })
+            .await
+            .unwrap();
+        assert_eq!(names, ["plugin.weather.forecast"]);
+
+        let result = registry
+            .call("plugin.weather.forecast", Some(serde_json::json!({ "city": "Oslo" })))
+            .await
+            .unwrap();
+        assert_eq!(result, serde_json::json!({ "city": "Oslo", "sky": "clear" }));
+        assert!(registry.call("forecast", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_register_plugin_rejects_collisions() {
+        let registry = MethodRegistry::new();
+        registry
+            .register_plugin(&WeatherPlugin { extra: None })
+            .await
+            .unwrap();
+
+        // One taken name rejects the whole plugin.
+        let result = registry
+            .register_plugin(&WeatherPlugin { extra: Some("status") })
+            .await;
+        match result {
+            Err(GatewayError::MethodConflict(name)) => assert_eq!(name, "plugin.weather.forecast"),
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        assert!(registry.call("plugin.weather.status", None).await.is_err());
+
+        let registry = MethodRegistry::new();
+        let result = registry
+            .register_plugin(&WeatherPlugin { extra: Some("forecast") })
+            .await;
+        assert!(matches!(result, Err(GatewayError::MethodConflict(_))));
+        assert!(registry.list().await.is_empty());
+    }
+
+    /// SDK plugin whose `greet` method fails without a name.
+    struct GreeterPlugin;
+
+    struct GreetMethod;
+
+    #[async_trait]
+    impl smartassist_plugin_sdk::PluginRpcMethod for GreetMethod {
+        async fn call(
+            &self,
+            params: Option<serde_json::Value>,
+        ) -> smartassist_plugin_sdk::Result<serde_json::Value> {
+            let name = params
+                .as_ref()
+                .and_then(|p| p["name"].as_str())
+                .ok_or_else(|| smartassist_plugin_sdk::PluginError::config("name is required"))?;
+            Ok(serde_json::json!(format!("Hello, {}", name)))
+        }
+    }
+
+    impl smartassist_plugin_sdk::RpcMethodPlugin for GreeterPlugin {
+        fn rpc_methods(
+            &self,
+        ) -> Vec<(String, Arc<dyn smartassist_plugin_sdk::PluginRpcMethod>)> {
+            vec![("greet".to_string(), Arc::new(GreetMethod))]
+        }
+    }
+
+    #[async_trait]
+    impl smartassist_plugin_sdk::Plugin for GreeterPlugin {
+        fn metadata(&self) -> smartassist_plugin_sdk::PluginMetadata {
+            smartassist_plugin_sdk::PluginMetadata {
+                name: "greeter".to_string(),
+                version: smartassist_plugin_sdk::Version::new(0, 1, 0),
+                description: "Greets people".to_string(),
+                author: None,
+                homepage: None,
+                license: None,
+                capabilities: vec![smartassist_plugin_sdk::PluginCapability::Rpc],
+                min_smartassist_version: None,
+            }
+        }
+
+        async fn initialize(
+            &mut self,
+            _ctx: &smartassist_plugin_sdk::PluginContext,
+        ) -> smartassist_plugin_sdk::Result<()> {
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) -> smartassist_plugin_sdk::Result<()> {
+            Ok(())
+        }
+
+        fn as_rpc_plugin(&self) -> Option<&dyn smartassist_plugin_sdk::RpcMethodPlugin> {
+            Some(self)
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_methods_from_plugin_loader() {
+        let mut loader = smartassist_plugin_sdk::PluginLoader::new();
+        loader.register(Box::new(GreeterPlugin));
+
+        let registry = MethodRegistry::new();
+        let names = registry.register_plugin_methods(&loader).await;
+        assert_eq!(names, ["plugin.greeter.greet"]);
+
+        let result = registry
+            .call("plugin.greeter.greet", Some(serde_json::json!({ "name": "Ada" })))
+            .await
+            .unwrap();
+        assert_eq!(result, serde_json::json!("Hello, Ada"));
+        let err = registry.call("plugin.greeter.greet", None).await.unwrap_err();
+        assert!(matches!(err, GatewayError::InvalidParams(_)), "{:?}", err);
+
+        // Loading the same plugin again collides and registers nothing.
+        assert!(registry.register_plugin_methods(&loader).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_discover_lists_methods_with_schemas() {
+        let registry = Arc::new(MethodRegistry::new());
+        register_builtin(&registry).await;
+        registry
+            .register("rpc.discover", Arc::new(DiscoverHandler::new(&registry)))
+            .await;
+        registry
+            .register(
+                "echo",
+                crate::method_handler!(|params: Option<serde_json::Value>| async move {
+                    Ok(params.unwrap_or_default())
+                }),
+            )
+            .await;
+
+        let doc = registry.call("rpc.discover", None).await.unwrap();
+        assert_eq!(doc["openrpc"], "1.2.6");
+
+        let methods = doc["methods"].as_array().unwrap();
+        let names: Vec<&str> = methods.iter().map(|m| m["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["echo", "ping", "rpc.discover", "system.info"]);
+
+        // Handlers without a schema are listed by name.
+        assert_eq!(methods[0]["params"], serde_json::json!([]));
+
+        let ping = &methods[1];
+        assert_eq!(ping["summary"], "Check that the gateway is responding");
+        assert_eq!(ping["result"]["schema"]["properties"]["pong"]["type"], "boolean");
+    }
+
+    #[test]
+    fn test_schema_params_become_named_descriptors() {
+        let schema = MethodSchema::new(
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "session_key": { "type": "string" },
+                    "limit": { "type": "integer" },
+                },
+                "required": ["session_key"],
+            }),
+            serde_json::json!({ "type": "object" }),
+        );
+
+        let method = schema.to_openrpc("chat.history");
+        assert!(method.get("summary").is_none());
+        let mut params: Vec<(String, bool)> = method["params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| (p["name"].as_str().unwrap().to_string(), p["required"] == true))
+            .collect();
+        params.sort();
+        assert_eq!(
+            params,
+            [("limit".to_string(), false), ("session_key".to_string(), true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discover_covers_all_gateway_methods() {
+        let registry = Arc::new(MethodRegistry::new());
+        crate::handlers::register_all(&registry, crate::handlers::HandlerContext::default())
+            .await;
+
+        let doc = registry.call("rpc.discover", None).await.unwrap();
+        let mut listed: Vec<String> = doc["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap().to_string())
+            .collect();
+        let mut registered = registry.list().await;
+        registered.sort();
+        listed.sort();
+        assert_eq!(listed, registered);
+        assert!(listed.iter().any(|name| name == "chat.history"));
+
+        let history = doc["methods"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["name"] == "chat.history")
+            .unwrap();
+        assert!(history["params"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|p| p["name"] == "session_key" && p["required"] == true));
+    }
 }
@@ -0,0 +1,103 @@
+//! Tool progress notifications.
+//!
+//! Long-running tools such as `memory_index` report progress through the
+//! agent runtime's event stream. The gateway forwards those updates to its
+//! clients as `agent.progress` notifications so a UI can show how far a
+//! tool call has got before its result arrives.
+
+use crate::rpc::JsonRpcNotification;
+use smartassist_agent::runtime::AgentEvent;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Build the `agent.progress` notification for `event`, if it is a
+/// progress update.
+pub fn progress_notification(event: &AgentEvent) -> Option<JsonRpcNotification> {
+    match event {
+        AgentEvent::ToolProgress {
+            session,
+            id,
+            name,
+            progress,
+        } => Some(JsonRpcNotification::new(
+            "agent.progress",
+            serde_json::json!({
+                "session": session.as_str(),
+                "tool_use_id": id,
+                "tool": name,
+                "progress": progress,
+            }),
+        )),
+        _ => None,
+    }
+}
+
+/// Forward progress updates from `events` to `notify` until the runtime
+/// goes away or the task is aborted.
+pub fn spawn_progress_forwarder(
+    mut events: broadcast::Receiver<AgentEvent>,
+    notify: broadcast::Sender<String>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(dropped)) => {
+                    debug!("Dropped {} agent events while forwarding progress", dropped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            let Some(notification) = progress_notification(&event) else {
+                continue;
+            };
+            if let Ok(text) = serde_json::to_string(&notification) {
+                // No subscribers just means no clients are connected.
+                let _ = notify.send(text);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use smartassist_core::types::SessionKey;
+
+    #[tokio::test]
+    async fn test_progress_is_forwarded_to_clients() {
+        let (events, receiver) = broadcast::channel(8);
+        let (notify, mut notifications) = broadcast::channel(8);
+        let forwarder = spawn_progress_forwarder(receiver, notify);
+
+        let session = SessionKey::new("agent:indexing");
+        events
+            .send(AgentEvent::TurnStarted {
+                session: session.clone(),
+                user_message: "index ~/notes".to_string(),
+            })
+            .unwrap();
+        events
+            .send(AgentEvent::ToolProgress {
+                session,
+                id: "call_1".to_string(),
+                name: "memory_index".to_string(),
+                progress: serde_json::json!({ "files_processed": 1, "files_total": 3 }),
+            })
+            .unwrap();
+        drop(events);
+        forwarder.await.unwrap();
+
+        let notification: Value =
+            serde_json::from_str(&notifications.recv().await.unwrap()).unwrap();
+        assert_eq!(notification["method"], "agent.progress");
+        assert_eq!(notification["params"]["session"], "agent:indexing");
+        assert_eq!(notification["params"]["tool_use_id"], "call_1");
+        assert_eq!(notification["params"]["tool"], "memory_index");
+        assert_eq!(notification["params"]["progress"]["files_processed"], 1);
+
+        // Only progress is forwarded.
+        assert!(notifications.try_recv().is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! Config file hot reload.
+//!
+//! The gateway polls its config file and, when an edit loads and
+//! validates, swaps the config shared with the handlers and sends clients
+//! a `config.changed` notification naming the sections that changed. An
+//! edit that fails to load is logged and the previous config stays in use.
+//!
+//! With a secret store, an edit's `secret://` references must resolve from
+//! it, as in [`smartassist_secrets::resolve_config`], or the edit is
+//! rejected like an invalid one. The shared config keeps the references
+//! rather than the secrets, since `config.set` writes it back to disk.
+//!
+//! The stored config can still hold inline secrets such as channel tokens,
+//! so reading it back with `config.get` requires the admin scope and
+//! read-only clients are not sent `config.changed`.
+
+use crate::rpc::JsonRpcNotification;
+use serde_json::Value;
+use smartassist_core::config::ConfigWatcher;
+use smartassist_secrets::SecretStore;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, warn};
+
+/// How often the config file is checked for edits.
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Poll `watcher` once and apply a valid change to `config`.
+///
+/// A change whose secret references do not resolve from `secrets` is not
+/// applied. Returns the top-level sections that changed, empty when nothing
+/// did.
+pub async fn reload_config(
+    watcher: &mut ConfigWatcher,
+    config: &RwLock<Value>,
+    notify: &broadcast::Sender<String>,
+    secrets: Option<&dyn SecretStore>,
+) -> Vec<String> {
+    let new_config = match watcher.poll() {
+        Ok(Some(new_config)) => new_config,
+        Ok(None) => return Vec::new(),
+        Err(e) => {
+            warn!(
+                "Ignoring config change in {}: {}",
+                watcher.path().display(),
+                e
+            );
+            return Vec::new();
+        }
+    };
+    if let Some(store) = secrets {
+        if let Err(e) = smartassist_secrets::resolve_config(&new_config, store).await {
+            warn!(
+                "Ignoring config change in {}: {}",
+                watcher.path().display(),
+                e
+            );
+            return Vec::new();
+        }
+    }
+    let new_value = match serde_json::to_value(&new_config) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Failed to serialize reloaded config: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let changed = {
+        let mut current = config.write().await;
+        let changed = changed_sections(&current, &new_value);
+        if !changed.is_empty() {
+            *current = new_value;
+        }
+        changed
+    };
+    if changed.is_empty() {
+        return changed;
+    }
+
+    info!(
+        "Reloaded config from {} ({})",
+        watcher.path().display(),
+        changed.join(", ")
+    );
+    let notification = JsonRpcNotification::new(
+        "config.changed",
+        serde_json::json!({
+            "path": watcher.path(),
+            "changed": changed,
+        }),
+    );
+    if let Ok(text) = serde_json::to_string(&notification) {
+        // No subscribers just means no clients are connected.
+        let _ = notify.send(text);
+    }
+    changed
+}
+
+/// Reload `config` from `watcher` every `interval` until the task is aborted.
+///
+/// The first check runs immediately and loads the file.
+pub fn spawn_config_reloader(
+    mut watcher: ConfigWatcher,
+    config: Arc<RwLock<Value>>,
+    notify: broadcast::Sender<String>,
+    secrets: Option<Arc<dyn SecretStore>>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            reload_config(&mut watcher, &config, &notify, secrets.as_deref()).await;
+        }
+    })
+}
+
+/// Top-level keys whose values differ between two config objects.
+fn changed_sections(old: &Value, new: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .chain(old.keys().filter(|key| !new.contains_key(*key)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smartassist_core::config::Config;
+
+    fn write(path: &std::path::Path, content: &str) {
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_valid_edit_is_applied_and_announced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json5");
+        write(&path, "{ gateway: { port: 7000 } }");
+
+        let config = RwLock::new(serde_json::json!({}));
+        let (notify, mut notifications) = broadcast::channel(8);
+        let mut watcher = ConfigWatcher::new(&path);
+
+        reload_config(&mut watcher, &config, &notify, None).await;
+        assert_eq!(config.read().await["gateway"]["port"], 7000);
+        notifications.recv().await.unwrap();
+
+        write(&path, "{ gateway: { port: 7001 } }");
+        let changed = reload_config(&mut watcher, &config, &notify, None).await;
+        assert_eq!(changed, vec!["gateway"]);
+        assert_eq!(config.read().await["gateway"]["port"], 7001);
+
+        let notification: Value =
+            serde_json::from_str(&notifications.recv().await.unwrap()).unwrap();
+        assert_eq!(notification["method"], "config.changed");
+        assert_eq!(notification["params"]["changed"], serde_json::json!(["gateway"]));
+
+        // Nothing to announce when the file is untouched.
+        assert!(reload_config(&mut watcher, &config, &notify, None).await.is_empty());
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_edit_keeps_previous_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json5");
+        write(&path, "{ gateway: { port: 7000 } }");
+
+        let config = RwLock::new(serde_json::json!({}));
+        let (notify, mut notifications) = broadcast::channel(8);
+        let mut watcher = ConfigWatcher::new(&path);
+        reload_config(&mut watcher, &config, &notify, None).await;
+        notifications.recv().await.unwrap();
+        let before = config.read().await.clone();
+
+        // Fails validation, then fails to parse.
+        write(&path, "{ gateway: { port: 0 } }");
+        assert!(reload_config(&mut watcher, &config, &notify, None).await.is_empty());
+        write(&path, "{ gateway: ");
+        assert!(reload_config(&mut watcher, &config, &notify, None).await.is_empty());
+
+        assert_eq!(*config.read().await, before);
+        assert!(notifications.try_recv().is_err());
+        let kept: Config = serde_json::from_value(before).unwrap();
+        assert_eq!(kept.gateway.port, 7000);
+    }
+
+    #[tokio::test]
+    async fn test_edit_with_unknown_secret_is_rejected() {
+        use smartassist_secrets::{crypto, FileSecretStore};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let store = FileSecretStore::new(tmp.path().join("secrets"), crypto::generate_master_key());
+        store.set("telegram_bot_token", "123:abc").await.unwrap();
+        let path = tmp.path().join("config.json5");
+        let config = RwLock::new(serde_json::json!({}));
+        let (notify, _notifications) = broadcast::channel(8);
+        let mut watcher = ConfigWatcher::new(&path);
+
+        let telegram = |token: &str| {
+            let account = format!("{{ main: {{ bot_token: \"{}\" }} }}", token);
+            format!("{{ channels: {{ telegram: {{ accounts: {} }} }} }}", account)
+        };
+        write(&path, &telegram("secret://telegram/bot_token"));
+        let changed = reload_config(&mut watcher, &config, &notify, Some(&store)).await;
+        assert!(changed.contains(&"channels".to_string()), "{:?}", changed);
+        // The shared config keeps the reference, not the secret.
+        let stored = config.read().await.to_string();
+        assert!(stored.contains("secret://telegram/bot_token"), "{}", stored);
+        assert!(!stored.contains("123:abc"), "{}", stored);
+
+        write(&path, &telegram("secret://telegram/other_token"));
+        assert!(reload_config(&mut watcher, &config, &notify, Some(&store)).await.is_empty());
+        assert!(config.read().await.to_string().contains("secret://telegram/bot_token"));
+    }
+
+    #[tokio::test]
+    async fn test_reloader_task_picks_up_edits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.json5");
+        write(&path, "{ gateway: { port: 7000 } }");
+
+        let config = Arc::new(RwLock::new(serde_json::json!({})));
+        let (notify, mut notifications) = broadcast::channel(8);
+        let task = spawn_config_reloader(
+            ConfigWatcher::new(&path),
+            config.clone(),
+            notify,
+            None,
+            Duration::from_millis(10),
+        );
+        notifications.recv().await.unwrap();
+
+        write(&path, "{ gateway: { port: 7002 } }");
+        tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.read().await["gateway"]["port"], 7002);
+        task.abort();
+    }
+
+    #[test]
+    fn test_changed_sections() {
+        let old = serde_json::json!({"a": 1, "b": {"c": 2}, "gone": true});
+        let new = serde_json::json!({"a": 1, "b": {"c": 3}, "added": 0});
+        assert_eq!(changed_sections(&old, &new), vec!["added", "b", "gone"]);
+    }
+}
@@ -1,12 +1,12 @@
 //! WebSocket gateway server.
 
 use crate::error::GatewayError;
-use crate::methods::MethodRegistry;
-use crate::rpc::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
+use crate::methods::{Connection, MethodRegistry};
+use crate::rpc::{JsonRpcRequest, JsonRpcResponse};
 use crate::Result;
 use axum::{
     extract::{
-        ws::{Message, WebSocket},
+        ws::{close_code, CloseFrame, Message, WebSocket},
         ConnectInfo, State, WebSocketUpgrade,
     },
     http::{HeaderMap, HeaderValue, Method},
@@ -15,13 +15,14 @@ use axum::{
     Router,
 };
 use futures::{SinkExt, StreamExt};
+use smartassist_agent::runtime::AgentEvent;
 use smartassist_core::config::BindMode;
 use smartassist_core::types::{AuthContext, Scope};
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{debug, error, info, warn};
 
@@ -34,6 +35,9 @@ const MAX_CONNECTIONS_PER_SECOND: u64 = 10;
 /// Maximum messages per second per client.
 const MAX_MESSAGES_PER_SECOND: u64 = 60;
 
+/// Outgoing messages queued per client before senders wait.
+const OUTBOUND_BUFFER: usize = 256;
+
 /// Allowed origins for CORS and WebSocket origin validation.
 const ALLOWED_ORIGINS: &[&str] = &[
     "http://localhost",
@@ -56,7 +60,8 @@ pub struct GatewayConfig {
     /// Enable CORS.
     pub cors: bool,
 
-    /// Maximum connections.
+    /// Maximum open WebSocket connections; further clients are refused with
+    /// close code 1013 (try again later).
     pub max_connections: usize,
 
     /// Authentication token (required for non-loopback binds).
@@ -64,6 +69,16 @@ pub struct GatewayConfig {
 
     /// Whether to require authentication.
     pub require_auth: bool,
+
+    /// File cron jobs are saved to (kept in memory only if unset).
+    pub cron_store: Option<std::path::PathBuf>,
+
+    /// Config file to load and reload when it changes (none if unset).
+    pub config_file: Option<std::path::PathBuf>,
+
+    /// Channel manager whose channels `system-presence` reports (none if
+    /// unset).
+    pub channel_manager: Option<Arc<smartassist_channels::ChannelManager>>,
 }
 
 impl Default for GatewayConfig {
@@ -75,6 +90,9 @@ impl Default for GatewayConfig {
             max_connections: 100,
             auth_token: None,
             require_auth: false,
+            cron_store: None,
+            config_file: None,
+            channel_manager: None,
         }
     }
 }
@@ -96,6 +114,9 @@ pub struct GatewayState {
     /// Connection counter for rate limiting.
     connection_count: AtomicU64,
 
+    /// Open WebSocket connections, shared with the `status` handler.
+    active_connections: Arc<AtomicUsize>,
+
     /// Last rate limit reset timestamp (unix seconds).
     rate_limit_reset: AtomicU64,
 }
@@ -119,6 +140,19 @@ impl GatewayState {
         }
     }
 
+    /// Reserve a connection slot, or `None` if `max_connections` are open.
+    fn reserve_connection(&self) -> Option<ConnectionSlot> {
+        let max = self.config.max_connections;
+        let open = self
+            .active_connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < max).then_some(n + 1))
+            .ok()?;
+        Some(ConnectionSlot {
+            connections: self.active_connections.clone(),
+            open: open + 1,
+        })
+    }
+
     /// Validate an auth token against the configured token.
     fn validate_token(&self, token: &str) -> Option<AuthContext> {
         if let Some(ref expected) = self.config.auth_token {
@@ -188,6 +222,19 @@ impl GatewayState {
     }
 }
 
+/// A place among the gateway's open connections, released when dropped.
+struct ConnectionSlot {
+    connections: Arc<AtomicUsize>,
+    /// Connections open once this one was reserved.
+    open: usize,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
 /// Information about a connected client.
 #[derive(Debug, Clone)]
 pub struct ClientInfo {
@@ -214,6 +261,15 @@ pub struct ClientInfo {
 pub struct Gateway {
     /// Server state.
     state: Arc<GatewayState>,
+
+    /// Configuration shared with the handlers.
+    config: Arc<RwLock<serde_json::Value>>,
+
+    /// Store secret references in the config file must resolve from.
+    secret_store: Option<Arc<dyn smartassist_secrets::SecretStore>>,
+
+    /// Agent events whose tool progress is forwarded to clients.
+    agent_events: std::sync::Mutex<Option<broadcast::Receiver<AgentEvent>>>,
 }
 
 impl Gateway {
@@ -227,10 +283,34 @@ impl Gateway {
             broadcast_tx,
             config,
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         });
 
-        Self { state }
+        Self {
+            state,
+            config: Arc::new(RwLock::new(serde_json::json!({}))),
+            secret_store: None,
+            agent_events: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Check the `secret://` references of the config file against `store`
+    /// when it is loaded; edits whose references do not resolve are
+    /// rejected.
+    pub fn with_secret_store(mut self, store: Arc<dyn smartassist_secrets::SecretStore>) -> Self {
+        self.secret_store = Some(store);
+        self
+    }
+
+    /// Forward tool progress from an agent runtime's events, obtained with
+    /// [`AgentRuntime::subscribe`], to clients as `agent.progress`
+    /// notifications while the gateway runs.
+    ///
+    /// [`AgentRuntime::subscribe`]: smartassist_agent::runtime::AgentRuntime::subscribe
+    pub fn with_agent_events(self, events: broadcast::Receiver<AgentEvent>) -> Self {
+        *self.agent_events.lock().unwrap() = Some(events);
+        self
     }
 
     /// Create a new gateway with default handlers registered.
@@ -238,8 +318,7 @@ impl Gateway {
         let gateway = Self::new(config);
 
         // Create handler context with default config
-        let context = crate::handlers::HandlerContext::new()
-            .with_config(Arc::new(RwLock::new(serde_json::json!({}))));
+        let context = gateway.base_context().await;
 
         // Register all handlers
         crate::handlers::register_all(&gateway.state.methods, context).await;
@@ -255,9 +334,7 @@ impl Gateway {
         let gateway = Self::new(config);
 
         // Create handler context with provider
-        let context = crate::handlers::HandlerContext::new()
-            .with_config(Arc::new(RwLock::new(serde_json::json!({}))))
-            .with_provider(provider);
+        let context = gateway.base_context().await.with_provider(provider);
 
         // Register all handlers
         crate::handlers::register_all(&gateway.state.methods, context).await;
@@ -265,6 +342,53 @@ impl Gateway {
         gateway
     }
 
+    /// Create a new gateway with several model providers and default handlers.
+    ///
+    /// The first provider serves chat completions; `models.list` reports
+    /// the models of all of them.
+    pub async fn with_providers(
+        config: GatewayConfig,
+        providers: Vec<std::sync::Arc<dyn smartassist_providers::Provider>>,
+    ) -> Self {
+        let gateway = Self::new(config);
+
+        let context = providers
+            .into_iter()
+            .fold(gateway.base_context().await, |context, provider| {
+                context.add_provider(provider)
+            });
+
+        // Register all handlers
+        crate::handlers::register_all(&gateway.state.methods, context).await;
+
+        gateway
+    }
+
+    /// Build the handler context shared by the `with_*` constructors.
+    ///
+    /// Saved cron jobs are loaded from the configured store. If the store
+    /// cannot be read, jobs are kept in memory only so the file is not
+    /// overwritten.
+    async fn base_context(&self) -> crate::handlers::HandlerContext {
+        let mut context = crate::handlers::HandlerContext::new()
+            .with_config(self.config.clone())
+            .with_active_connections(self.state.active_connections.clone());
+        if let Some(manager) = &self.state.config.channel_manager {
+            context = context.with_channel_manager(manager.clone());
+        }
+
+        let Some(path) = &self.state.config.cron_store else {
+            return context;
+        };
+        match crate::handlers::CronScheduler::load(path).await {
+            Ok(scheduler) => context.with_cron_scheduler(Arc::new(scheduler)),
+            Err(e) => {
+                warn!("{}; cron jobs will not be saved", e);
+                context
+            }
+        }
+    }
+
     /// Get the method registry for registering handlers.
     pub fn methods(&self) -> &Arc<MethodRegistry> {
         &self.state.methods
@@ -294,12 +418,32 @@ impl Gateway {
             .await
             .map_err(GatewayError::Io)?;
 
-        axum::serve(
+        let reloader = self.state.config.config_file.as_ref().map(|path| {
+            crate::reload::spawn_config_reloader(
+                smartassist_core::config::ConfigWatcher::new(path),
+                self.config.clone(),
+                self.state.broadcast_tx.clone(),
+                self.secret_store.clone(),
+                crate::reload::CONFIG_POLL_INTERVAL,
+            )
+        });
+
+        let forwarder = self.agent_events.lock().unwrap().take().map(|events| {
+            crate::progress::spawn_progress_forwarder(events, self.state.broadcast_tx.clone())
+        });
+
+        let served = axum::serve(
             listener,
             app.into_make_service_with_connect_info::<SocketAddr>(),
         )
-        .await
-        .map_err(|e| GatewayError::Internal(e.to_string()))?;
+        .await;
+        if let Some(reloader) = reloader {
+            reloader.abort();
+        }
+        if let Some(forwarder) = forwarder {
+            forwarder.abort();
+        }
+        served.map_err(|e| GatewayError::Internal(e.to_string()))?;
 
         Ok(())
     }
@@ -375,6 +519,12 @@ impl Gateway {
     pub async fn client_count(&self) -> usize {
         self.state.clients.read().await.len()
     }
+
+    /// Get the number of open WebSocket connections, including those still
+    /// completing the handshake.
+    pub fn connection_count(&self) -> usize {
+        self.state.active_connections.load(Ordering::Acquire)
+    }
 }
 
 /// WebSocket upgrade handler with authentication and origin validation.
@@ -390,13 +540,6 @@ async fn ws_handler(
         return Err(axum::http::StatusCode::TOO_MANY_REQUESTS);
     }
 
-    // Max connections check
-    let client_count = state.clients.read().await.len();
-    if client_count >= state.config.max_connections {
-        warn!("Max connections ({}) reached, rejecting {}", state.config.max_connections, addr);
-        return Err(axum::http::StatusCode::SERVICE_UNAVAILABLE);
-    }
-
     // Origin validation (prevents CVE-2026-25253 - 1-click RCE via cross-origin token theft)
     if !state.validate_origin(&headers) {
         return Err(axum::http::StatusCode::FORBIDDEN);
@@ -411,7 +554,23 @@ async fn ws_handler(
         }
     };
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, auth, addr)))
+    // Max connections check; the slot is held until the socket closes.
+    let Some(slot) = state.reserve_connection() else {
+        let max = state.config.max_connections;
+        warn!("Max connections ({}) reached, rejecting {}", max, addr);
+        return Ok(ws.on_upgrade(move |socket| refuse_socket(socket, max)));
+    };
+
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, auth, addr, slot)))
+}
+
+/// Close a connection refused because the gateway is full.
+async fn refuse_socket(mut socket: WebSocket, max_connections: usize) {
+    let frame = CloseFrame {
+        code: close_code::AGAIN,
+        reason: format!("Too many connections (max {})", max_connections).into(),
+    };
+    let _ = socket.send(Message::Close(Some(frame))).await;
 }
 
 /// Handle a WebSocket connection.
@@ -420,8 +579,10 @@ async fn handle_socket(
     state: Arc<GatewayState>,
     auth: AuthContext,
     remote_addr: SocketAddr,
+    slot: ConnectionSlot,
 ) {
     let client_id = uuid::Uuid::new_v4().to_string();
+    let opened = std::time::Instant::now();
     let message_count = Arc::new(AtomicU64::new(0));
     let message_rate_reset = Arc::new(AtomicU64::new(0));
 
@@ -442,12 +603,49 @@ async fn handle_socket(
     }
 
     info!(
+        event = "connection.open",
+        connection_id = %client_id,
+        remote_addr = %remote_addr,
+        connections = slot.open,
         "Client connected: {} from {} (scopes: {:?})",
-        client_id, remote_addr, auth.scopes
+        client_id,
+        remote_addr,
+        auth.scopes
     );
 
     let (mut sender, mut receiver) = socket.split();
-    let _broadcast_rx = state.broadcast_tx.subscribe();
+    let mut broadcast_rx = state.broadcast_tx.subscribe();
+
+    // Responses and handler notifications share one outgoing queue.
+    let (outbound_tx, mut outbound_rx) = mpsc::channel::<String>(OUTBOUND_BUFFER);
+    let send_task = tokio::spawn(async move {
+        while let Some(text) = outbound_rx.recv().await {
+            if let Err(e) = sender.send(Message::Text(text)).await {
+                error!("Failed to send response: {}", e);
+                break;
+            }
+        }
+    });
+
+    // Gateway-wide notifications go out through the same queue.
+    let broadcast_outbound = outbound_tx.clone();
+    let broadcast_auth = auth.clone();
+    let broadcast_task = tokio::spawn(async move {
+        loop {
+            match broadcast_rx.recv().await {
+                Ok(text) => {
+                    if !notification_visible(&broadcast_auth, &text) {
+                        continue;
+                    }
+                    if broadcast_outbound.send(text).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
 
     // Handle incoming messages
     let state_clone = state.clone();
@@ -455,6 +653,7 @@ async fn handle_socket(
     let auth_clone = auth.clone();
     let msg_count = message_count.clone();
     let msg_reset = message_rate_reset.clone();
+    let connection = Connection::new(client_id.clone(), outbound_tx.clone());
 
     let recv_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
@@ -464,19 +663,18 @@ async fn handle_socket(
                     if !check_message_rate(&msg_count, &msg_reset) {
                         let err_resp = JsonRpcResponse::error(
                             None,
-                            JsonRpcError::new(-32000, "Rate limit exceeded".to_string()),
+                            GatewayError::RateLimited("too many messages".to_string()).into(),
                         );
                         let err_str = serde_json::to_string(&err_resp).unwrap_or_default();
-                        if sender.send(Message::Text(err_str)).await.is_err() {
+                        if outbound_tx.send(err_str).await.is_err() {
                             break;
                         }
                         continue;
                     }
 
                     let response =
-                        handle_message(&text, &state_clone, &auth_clone).await;
-                    if let Err(e) = sender.send(Message::Text(response)).await {
-                        error!("Failed to send response: {}", e);
+                        handle_message(&text, &state_clone, &auth_clone, &connection).await;
+                    if outbound_tx.send(response).await.is_err() {
                         break;
                     }
                 }
@@ -491,18 +689,29 @@ async fn handle_socket(
                 _ => {}
             }
         }
+        // Dropping the connection here ends its subscriptions.
     });
 
     // Wait for task to complete
     let _ = recv_task.await;
+    broadcast_task.abort();
+    send_task.abort();
 
     // Unregister client
     {
         let mut clients = state.clients.write().await;
         clients.remove(&client_id);
     }
+    drop(slot);
 
-    info!("Client disconnected: {}", client_id);
+    info!(
+        event = "connection.close",
+        connection_id = %client_id,
+        duration_ms = opened.elapsed().as_millis() as u64,
+        connections = state.active_connections.load(Ordering::Acquire),
+        "Client disconnected: {}",
+        client_id
+    );
 }
 
 /// Check per-client message rate limit.
@@ -524,15 +733,17 @@ fn check_message_rate(count: &AtomicU64, reset: &AtomicU64) -> bool {
 }
 
 /// Handle a JSON-RPC message with scope-based authorization.
-async fn handle_message(text: &str, state: &GatewayState, auth: &AuthContext) -> String {
+async fn handle_message(
+    text: &str,
+    state: &GatewayState,
+    auth: &AuthContext,
+    connection: &Connection,
+) -> String {
     // Parse request
     let request: JsonRpcRequest = match serde_json::from_str(text) {
         Ok(r) => r,
         Err(e) => {
-            let response = JsonRpcResponse::error(
-                None,
-                JsonRpcError::parse_error(e.to_string()),
-            );
+            let response = JsonRpcResponse::error(None, GatewayError::Json(e).into());
             return serde_json::to_string(&response).unwrap_or_default();
         }
     };
@@ -544,32 +755,46 @@ async fn handle_message(text: &str, state: &GatewayState, auth: &AuthContext) ->
         if !auth.has_scope(required_scope) {
             let response = JsonRpcResponse::error(
                 request.id,
-                JsonRpcError::new(
-                    -32001,
-                    format!(
-                        "Insufficient permissions: method '{}' requires scope '{:?}'",
-                        request.method, required_scope
-                    ),
-                ),
+                GatewayError::Auth(format!(
+                    "Insufficient permissions: method '{}' requires scope '{:?}'",
+                    request.method, required_scope
+                ))
+                .into(),
             );
             return serde_json::to_string(&response).unwrap_or_default();
         }
     }
 
     // Dispatch to method handler
-    let result = state.methods.call(&request.method, request.params.clone()).await;
+    let result = state
+        .methods
+        .call_with_connection(&request.method, request.params.clone(), connection)
+        .await;
 
     let response = match result {
         Ok(value) => JsonRpcResponse::success(request.id, value),
-        Err(e) => JsonRpcResponse::error(
-            request.id,
-            JsonRpcError::new(e.code(), e.to_string()),
-        ),
+        Err(e) => JsonRpcResponse::error(request.id, e.into()),
     };
 
     serde_json::to_string(&response).unwrap_or_default()
 }
 
+/// Check whether a gateway-wide notification may be sent to a client.
+///
+/// Config notifications only go to clients allowed to change the config.
+fn notification_visible(auth: &AuthContext, text: &str) -> bool {
+    if auth.has_scope(Scope::Write) {
+        return true;
+    }
+    let notification: serde_json::Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) => return true,
+    };
+    !notification["method"]
+        .as_str()
+        .is_some_and(|method| method.starts_with("config."))
+}
+
 /// Determine the required scope for an RPC method.
 fn required_scope_for_method(method: &str) -> Option<Scope> {
     // Read-only methods
@@ -578,10 +803,16 @@ fn required_scope_for_method(method: &str) -> Option<Scope> {
         || method == "ping"
         || method == "system.info"
         || method == "system.methods"
+        || method == "rpc.discover"
     {
         return Some(Scope::Read);
     }
 
+    // The full config holds channel tokens and passwords
+    if method == "config.get" {
+        return Some(Scope::Admin);
+    }
+
     // Write methods (chat, agent, config changes)
     if method.starts_with("chat.")
         || method.starts_with("agent.")
@@ -643,6 +874,7 @@ mod tests {
     fn test_required_scope_read_methods() {
         assert_eq!(required_scope_for_method("ping"), Some(Scope::Read));
         assert_eq!(required_scope_for_method("system.info"), Some(Scope::Read));
+        assert_eq!(required_scope_for_method("rpc.discover"), Some(Scope::Read));
         assert_eq!(required_scope_for_method("status.get"), Some(Scope::Read));
     }
 
@@ -657,6 +889,24 @@ mod tests {
     fn test_required_scope_admin_methods() {
         assert_eq!(required_scope_for_method("gateway.restart"), Some(Scope::Admin));
         assert_eq!(required_scope_for_method("node.invoke"), Some(Scope::Admin));
+        assert_eq!(required_scope_for_method("config.get"), Some(Scope::Admin));
+        assert_eq!(required_scope_for_method("config.set"), Some(Scope::Write));
+    }
+
+    #[test]
+    fn test_config_notifications_hidden_from_read_only_clients() {
+        let changed = r#"{"jsonrpc":"2.0","method":"config.changed","params":{}}"#;
+        let other = r#"{"jsonrpc":"2.0","method":"presence.changed","params":{}}"#;
+        let anonymous = AuthContext {
+            client_id: "anonymous".to_string(),
+            scopes: [Scope::Read].into_iter().collect(),
+            identity: None,
+            authenticated_at: chrono::Utc::now(),
+        };
+
+        assert!(!notification_visible(&anonymous, changed));
+        assert!(notification_visible(&anonymous, other));
+        assert!(notification_visible(&AuthContext::loopback(), changed));
     }
 
     #[test]
@@ -672,6 +922,7 @@ mod tests {
             broadcast_tx: broadcast::channel(10).0,
             config: GatewayConfig::default(), // Loopback
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         };
         let headers = HeaderMap::new();
@@ -689,6 +940,7 @@ mod tests {
                 ..Default::default()
             },
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         };
         let mut headers = HeaderMap::new();
@@ -707,6 +959,7 @@ mod tests {
                 ..Default::default()
             },
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         };
         let mut headers = HeaderMap::new();
@@ -722,6 +975,7 @@ mod tests {
             broadcast_tx: broadcast::channel(10).0,
             config: GatewayConfig::default(), // Loopback
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         };
         let headers = HeaderMap::new();
@@ -742,6 +996,7 @@ mod tests {
                 ..Default::default()
             },
             connection_count: AtomicU64::new(0),
+            active_connections: Arc::new(AtomicUsize::new(0)),
             rate_limit_reset: AtomicU64::new(0),
         };
         // No auth header → rejected
@@ -759,4 +1014,77 @@ mod tests {
         let auth = state.authenticate(&headers).unwrap();
         assert!(auth.has_scope(Scope::Admin));
     }
+
+    /// Serve `gateway` on an ephemeral loopback port.
+    async fn serve(gateway: &Gateway) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = gateway.create_router();
+        tokio::spawn(async move {
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+                .unwrap();
+        });
+        format!("ws://{}/ws", addr)
+    }
+
+    async fn wait_for_connections(gateway: &Gateway, expected: usize) {
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while gateway.connection_count() != expected {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("expected {} connections", expected));
+    }
+
+    #[tokio::test]
+    async fn test_connection_over_limit_is_refused() {
+        use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+        use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+        let gateway = Gateway::new(GatewayConfig {
+            max_connections: 2,
+            ..Default::default()
+        });
+        let url = serve(&gateway).await;
+
+        let (_first, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let (_second, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        assert_eq!(gateway.connection_count(), 2);
+
+        let (mut third, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        match third.next().await {
+            Some(Ok(WsMessage::Close(Some(frame)))) => {
+                assert_eq!(frame.code, CloseCode::Again);
+                assert!(frame.reason.contains("max 2"));
+            }
+            other => panic!("expected a close frame, got {:?}", other),
+        }
+        assert_eq!(gateway.connection_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_connection_count_follows_open_and_close() {
+        let gateway = Gateway::with_default_handlers(GatewayConfig::default()).await;
+        let url = serve(&gateway).await;
+        let active = || async {
+            let status = gateway.methods().call("status", None).await.unwrap();
+            status["active_connections"].as_u64().unwrap()
+        };
+        assert_eq!(active().await, 0);
+
+        let (mut first, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let (second, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        wait_for_connections(&gateway, 2).await;
+        assert_eq!(active().await, 2);
+
+        first.close(None).await.unwrap();
+        wait_for_connections(&gateway, 1).await;
+        assert_eq!(active().await, 1);
+
+        drop(second);
+        wait_for_connections(&gateway, 0).await;
+        assert_eq!(gateway.client_count().await, 0);
+    }
 }
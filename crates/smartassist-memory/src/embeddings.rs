@@ -109,6 +109,332 @@ impl EmbeddingProvider for OpenAIEmbeddings {
     }
 }
 
+/// Maximum number of texts per Gemini `batchEmbedContents` call.
+const GOOGLE_MAX_BATCH_SIZE: usize = 100;
+
+/// Task type hint for Gemini embeddings.
+///
+/// Documents and queries should be embedded with the matching task type so
+/// retrieval quality is not degraded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GoogleTaskType {
+    /// Text that will be stored and searched.
+    #[default]
+    RetrievalDocument,
+
+    /// A search query.
+    RetrievalQuery,
+
+    /// Text compared for semantic similarity.
+    SemanticSimilarity,
+
+    /// Text to classify.
+    Classification,
+
+    /// Text to cluster.
+    Clustering,
+}
+
+/// Google Gemini embeddings provider.
+pub struct GoogleEmbeddings {
+    client: Client,
+    api_key: String,
+    model: String,
+    base_url: String,
+    task_type: GoogleTaskType,
+    output_dimensionality: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleEmbedRequest {
+    model: String,
+    content: GoogleContent,
+    task_type: GoogleTaskType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimensionality: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+struct GoogleContent {
+    parts: Vec<GooglePart>,
+}
+
+#[derive(Debug, Serialize)]
+struct GooglePart {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GoogleBatchRequest {
+    requests: Vec<GoogleEmbedRequest>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleBatchResponse {
+    #[serde(default)]
+    embeddings: Vec<GoogleEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleEmbedding {
+    values: Vec<f32>,
+}
+
+impl GoogleEmbeddings {
+    /// Create a new Gemini embeddings provider.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key: api_key.into(),
+            model: "text-embedding-004".to_string(),
+            base_url: "https://generativelanguage.googleapis.com".to_string(),
+            task_type: GoogleTaskType::default(),
+            output_dimensionality: None,
+        }
+    }
+
+    /// Set the model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Set a custom base URL.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    /// Set the task type used by [`EmbeddingProvider::embed`].
+    pub fn with_task_type(mut self, task_type: GoogleTaskType) -> Self {
+        self.task_type = task_type;
+        self
+    }
+
+    /// Truncate embeddings to the given dimension (supported models only).
+    pub fn with_output_dimensionality(mut self, dimension: usize) -> Self {
+        self.output_dimensionality = Some(dimension);
+        self
+    }
+
+    /// Embed search queries (uses the `RETRIEVAL_QUERY` task type).
+    pub async fn embed_query(&self, text: &str) -> Result<Vec<f32>> {
+        let embeddings = self
+            .embed_with_task(&[text.to_string()], GoogleTaskType::RetrievalQuery)
+            .await?;
+        embeddings
+            .into_iter()
+            .next()
+            .ok_or_else(|| MemoryError::Embedding("No embedding returned".to_string()))
+    }
+
+    /// Generate embeddings with an explicit task type.
+    pub async fn embed_with_task(
+        &self,
+        texts: &[String],
+        task_type: GoogleTaskType,
+    ) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+
+        for batch in self.build_batches(texts, task_type) {
+            let expected = batch.requests.len();
+
+            let response = self
+                .client
+                .post(format!(
+                    "{}/v1beta/{}:batchEmbedContents",
+                    self.base_url,
+                    self.model_path()
+                ))
+                .header("x-goog-api-key", &self.api_key)
+                .header("Content-Type", "application/json")
+                .json(&batch)
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                let text = response.text().await.unwrap_or_default();
+                return Err(MemoryError::Embedding(format!("API error: {}", text)));
+            }
+
+            let response: GoogleBatchResponse = response.json().await?;
+            if response.embeddings.len() != expected {
+                return Err(MemoryError::Embedding(format!(
+                    "Expected {} embeddings, got {}",
+                    expected,
+                    response.embeddings.len()
+                )));
+            }
+
+            embeddings.extend(response.embeddings.into_iter().map(|e| e.values));
+        }
+
+        Ok(embeddings)
+    }
+
+    /// Fully-qualified model name (`models/<model>`).
+    fn model_path(&self) -> String {
+        if self.model.starts_with("models/") {
+            self.model.clone()
+        } else {
+            format!("models/{}", self.model)
+        }
+    }
+
+    /// Split texts into `batchEmbedContents` requests.
+    fn build_batches(&self, texts: &[String], task_type: GoogleTaskType) -> Vec<GoogleBatchRequest> {
+        texts
+            .chunks(GOOGLE_MAX_BATCH_SIZE)
+            .map(|chunk| GoogleBatchRequest {
+                requests: chunk
+                    .iter()
+                    .map(|text| GoogleEmbedRequest {
+                        model: self.model_path(),
+                        content: GoogleContent {
+                            parts: vec![GooglePart { text: text.clone() }],
+                        },
+                        task_type,
+                        output_dimensionality: self.output_dimensionality,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for GoogleEmbeddings {
+    fn dimension(&self) -> usize {
+        if let Some(dimension) = self.output_dimensionality {
+            return dimension;
+        }
+
+        match self.model.trim_start_matches("models/") {
+            "text-embedding-004" => 768,
+            "embedding-001" => 768,
+            "gemini-embedding-001" => 3072,
+            _ => 768,
+        }
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        self.embed_with_task(texts, self.task_type).await
+    }
+}
+
+/// Ollama embeddings provider for fully local memory.
+///
+/// Ollama's `/api/embeddings` endpoint embeds a single prompt per call, so
+/// batches are embedded one text at a time.
+pub struct OllamaEmbeddings {
+    client: Client,
+    model: String,
+    base_url: String,
+}
+
+impl Default for OllamaEmbeddings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OllamaEmbeddings {
+    /// Create a new Ollama embeddings provider using `nomic-embed-text`
+    /// on the default local endpoint.
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+            model: "nomic-embed-text".to_string(),
+            base_url: "http://localhost:11434".to_string(),
+        }
+    }
+
+    /// Set the model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// Set a custom base URL.
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Embed a single prompt.
+    async fn embed_prompt(&self, prompt: &str) -> Result<Vec<f32>> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            model: &'a str,
+            prompt: &'a str,
+        }
+
+        #[derive(Deserialize)]
+        struct Response {
+            embedding: Vec<f32>,
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/embeddings", self.base_url))
+            .json(&Request {
+                model: &self.model,
+                prompt,
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                if e.is_connect() {
+                    MemoryError::Connection(format!(
+                        "Could not connect to Ollama at {} (is `ollama serve` running?): {}",
+                        self.base_url, e
+                    ))
+                } else {
+                    MemoryError::Http(e)
+                }
+            })?;
+
+        if !response.status().is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(MemoryError::Embedding(format!("Ollama error: {}", text)));
+        }
+
+        let response: Response = response.json().await?;
+        if response.embedding.is_empty() {
+            return Err(MemoryError::Embedding(format!(
+                "Ollama returned an empty embedding for model '{}'",
+                self.model
+            )));
+        }
+
+        Ok(response.embedding)
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddings {
+    fn dimension(&self) -> usize {
+        let model = self.model.split(':').next().unwrap_or(&self.model);
+        match model {
+            "nomic-embed-text" => 768,
+            "mxbai-embed-large" => 1024,
+            "all-minilm" => 384,
+            "snowflake-arctic-embed" => 1024,
+            _ => 768,
+        }
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.embed_prompt(text).await?);
+        }
+        Ok(embeddings)
+    }
+}
+
 /// Compute cosine similarity between two vectors.
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() {
@@ -139,4 +465,136 @@ mod tests {
         let c = vec![0.0, 1.0, 0.0];
         assert!((cosine_similarity(&a, &c)).abs() < 0.001);
     }
+
+    #[test]
+    fn test_google_request_serialization() {
+        let provider = GoogleEmbeddings::new("key").with_output_dimensionality(256);
+        let batches = provider.build_batches(&["hello".to_string()], GoogleTaskType::RetrievalDocument);
+
+        let json = serde_json::to_value(&batches[0]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "requests": [{
+                    "model": "models/text-embedding-004",
+                    "content": {"parts": [{"text": "hello"}]},
+                    "taskType": "RETRIEVAL_DOCUMENT",
+                    "outputDimensionality": 256
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_google_batch_shaping() {
+        let provider = GoogleEmbeddings::new("key");
+        let texts: Vec<String> = (0..250).map(|i| format!("text {}", i)).collect();
+
+        let batches = provider.build_batches(&texts, GoogleTaskType::RetrievalDocument);
+        let sizes: Vec<usize> = batches.iter().map(|b| b.requests.len()).collect();
+        assert_eq!(sizes, vec![100, 100, 50]);
+        assert_eq!(batches[2].requests[49].content.parts[0].text, "text 249");
+
+        assert!(provider.build_batches(&[], GoogleTaskType::RetrievalDocument).is_empty());
+    }
+
+    #[test]
+    fn test_google_task_types() {
+        let provider = GoogleEmbeddings::new("key").with_model("models/gemini-embedding-001");
+        let texts = vec!["query".to_string()];
+
+        let query = provider.build_batches(&texts, GoogleTaskType::RetrievalQuery);
+        let document = provider.build_batches(&texts, GoogleTaskType::RetrievalDocument);
+
+        let query = serde_json::to_value(&query[0]).unwrap();
+        let document = serde_json::to_value(&document[0]).unwrap();
+        assert_eq!(query["requests"][0]["taskType"], "RETRIEVAL_QUERY");
+        assert_eq!(document["requests"][0]["taskType"], "RETRIEVAL_DOCUMENT");
+        assert_eq!(query["requests"][0]["model"], "models/gemini-embedding-001");
+        assert!(query["requests"][0].get("outputDimensionality").is_none());
+    }
+
+    #[test]
+    fn test_google_dimension() {
+        assert_eq!(GoogleEmbeddings::new("key").dimension(), 768);
+        assert_eq!(
+            GoogleEmbeddings::new("key")
+                .with_model("gemini-embedding-001")
+                .dimension(),
+            3072
+        );
+        assert_eq!(
+            GoogleEmbeddings::new("key")
+                .with_model("gemini-embedding-001")
+                .with_output_dimensionality(1536)
+                .dimension(),
+            1536
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ollama_request_response() {
+        use wiremock::matchers::{body_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/embeddings"))
+            .and(body_json(serde_json::json!({
+                "model": "nomic-embed-text",
+                "prompt": "hello"
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"embedding": [0.1, 0.2, 0.3]})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OllamaEmbeddings::new().with_base_url(server.uri());
+        let embedding = provider.embed_one("hello").await.unwrap();
+        assert_eq!(embedding, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[tokio::test]
+    async fn test_ollama_batch_loop() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/embeddings"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({"embedding": [1.0, 0.0]})),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let provider = OllamaEmbeddings::new()
+            .with_model("all-minilm")
+            .with_base_url(server.uri());
+        let texts = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let embeddings = provider.embed(&texts).await.unwrap();
+
+        assert_eq!(embeddings.len(), 3);
+        assert_eq!(provider.dimension(), 384);
+    }
+
+    #[tokio::test]
+    async fn test_ollama_connection_error() {
+        // Bind and drop a listener to get a port nothing is listening on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let provider = OllamaEmbeddings::new().with_base_url(format!("http://127.0.0.1:{}", port));
+        let err = provider.embed_one("hello").await.unwrap_err();
+
+        assert!(matches!(err, MemoryError::Connection(_)));
+        assert!(err.to_string().contains("Ollama"));
+    }
 }
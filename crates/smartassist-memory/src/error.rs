@@ -17,6 +17,10 @@ pub enum MemoryError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    /// Embedding backend could not be reached.
+    #[error("Connection error: {0}")]
+    Connection(String),
+
     /// Embedding generation failed.
     #[error("Embedding error: {0}")]
     Embedding(String),
@@ -1,7 +1,7 @@
 //! Vector memory and embeddings for SmartAssist.
 //!
 //! This crate provides:
-//! - Embedding generation via OpenAI/Google APIs
+//! - Embedding generation via OpenAI/Google APIs or a local Ollama server
 //! - Vector storage and retrieval
 //! - Semantic search capabilities
 
@@ -11,9 +11,11 @@ pub mod store;
 pub mod search;
 
 pub use error::MemoryError;
-pub use embeddings::{EmbeddingProvider, OpenAIEmbeddings};
+pub use embeddings::{
+    EmbeddingProvider, GoogleEmbeddings, GoogleTaskType, OllamaEmbeddings, OpenAIEmbeddings,
+};
 pub use store::{VectorStore, MemoryVectorStore, FileVectorStore};
-pub use search::{SearchQuery, SearchResult};
+pub use search::{NoopReRanker, ReRanker, SearchQuery, SearchResult};
 
 /// Result type for memory operations.
 pub type Result<T> = std::result::Result<T, MemoryError>;
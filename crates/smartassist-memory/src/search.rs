@@ -3,6 +3,7 @@
 use crate::embeddings::EmbeddingProvider;
 use crate::store::VectorStore;
 use crate::{MemoryEntry, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -69,6 +70,29 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Second-stage ranking applied to retrieved results.
+///
+/// Vector search retrieves candidates by embedding similarity; a re-ranker
+/// can then score each candidate against the query text directly (for
+/// example with a cross-encoder model) and reorder them.
+#[async_trait]
+pub trait ReRanker: Send + Sync {
+    /// Return `results` reordered for `query`, best first, with their
+    /// scores updated to the re-ranker's scores.
+    async fn rerank(&self, query: &str, results: Vec<SearchResult>) -> Result<Vec<SearchResult>>;
+}
+
+/// Re-ranker that keeps the retrieval order and scores.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopReRanker;
+
+#[async_trait]
+impl ReRanker for NoopReRanker {
+    async fn rerank(&self, _query: &str, results: Vec<SearchResult>) -> Result<Vec<SearchResult>> {
+        Ok(results)
+    }
+}
+
 /// Semantic search engine.
 pub struct SearchEngine {
     /// Embedding provider.
@@ -76,12 +100,25 @@ pub struct SearchEngine {
 
     /// Vector store.
     store: Arc<dyn VectorStore>,
+
+    /// Re-ranker applied after retrieval.
+    reranker: Arc<dyn ReRanker>,
 }
 
 impl SearchEngine {
     /// Create a new search engine.
     pub fn new(embeddings: Arc<dyn EmbeddingProvider>, store: Arc<dyn VectorStore>) -> Self {
-        Self { embeddings, store }
+        Self {
+            embeddings,
+            store,
+            reranker: Arc::new(NoopReRanker),
+        }
+    }
+
+    /// Set the re-ranker applied to retrieved results.
+    pub fn with_reranker(mut self, reranker: Arc<dyn ReRanker>) -> Self {
+        self.reranker = reranker;
+        self
     }
 
     /// Add content to the search index.
@@ -112,7 +149,7 @@ impl SearchEngine {
         // Generate embedding for query
         let query_embedding = self.embeddings.embed_one(&query.text).await?;
 
-        // Search the store
+        // Search the store, fetching extra candidates for filtering and re-ranking
         let results = self.store.search(&query_embedding, query.limit * 2).await?;
 
         // Filter and transform results
@@ -133,10 +170,12 @@ impl SearchEngine {
 
                 true
             })
-            .take(query.limit)
             .map(|(entry, score)| SearchResult { entry, score })
             .collect();
 
+        let mut results = self.reranker.rerank(&query.text, results).await?;
+        results.truncate(query.limit);
+
         Ok(results)
     }
 
@@ -154,6 +193,61 @@ impl SearchEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::MemoryVectorStore;
+
+    /// Embeds texts by their first letter so retrieval order is known.
+    struct LetterEmbeddings;
+
+    #[async_trait]
+    impl EmbeddingProvider for LetterEmbeddings {
+        fn dimension(&self) -> usize {
+            2
+        }
+
+        async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|text| match text.chars().next() {
+                    Some('a') => vec![1.0, 0.0],
+                    Some('b') => vec![0.8, 0.6],
+                    _ => vec![0.0, 1.0],
+                })
+                .collect())
+        }
+    }
+
+    /// Prefers longer content.
+    struct LengthReRanker;
+
+    #[async_trait]
+    impl ReRanker for LengthReRanker {
+        async fn rerank(
+            &self,
+            _query: &str,
+            mut results: Vec<SearchResult>,
+        ) -> Result<Vec<SearchResult>> {
+            for result in &mut results {
+                result.score = result.entry.content.len() as f32;
+            }
+            results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            Ok(results)
+        }
+    }
+
+    async fn engine() -> SearchEngine {
+        let engine = SearchEngine::new(
+            Arc::new(LetterEmbeddings),
+            Arc::new(MemoryVectorStore::new()),
+        );
+        for content in ["a", "bb", "ccc"] {
+            engine.index(content).await.unwrap();
+        }
+        engine
+    }
+
+    fn contents(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.entry.content.as_str()).collect()
+    }
 
     #[test]
     fn test_search_query() {
@@ -167,4 +261,23 @@ mod tests {
         assert_eq!(query.min_score, 0.5);
         assert!(query.filters.contains_key("type"));
     }
+
+    #[tokio::test]
+    async fn test_noop_reranker_preserves_order() {
+        let results = engine().await.search(SearchQuery::new("a")).await.unwrap();
+
+        assert_eq!(contents(&results), ["a", "bb", "ccc"]);
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_reranker_reorders_results() {
+        let engine = engine().await.with_reranker(Arc::new(LengthReRanker));
+
+        let results = engine.search(SearchQuery::new("a").with_limit(2)).await.unwrap();
+
+        // Re-ranking sees all candidates before the limit is applied.
+        assert_eq!(contents(&results), ["ccc", "bb"]);
+        assert_eq!(results[0].score, 3.0);
+    }
 }
@@ -250,52 +250,88 @@ impl HookRegistry {
         self.hooks.get(&hook_type).cloned().unwrap_or_default()
     }
 
+    /// Get the pipeline of hooks for a type.
+    pub fn pipeline(&self, hook_type: HookType) -> HookPipeline {
+        HookPipeline::new(self.get(hook_type))
+    }
+
     /// Execute all hooks of a type.
+    ///
+    /// See [`HookPipeline::run`] for the execution order and how results
+    /// are combined.
     pub async fn execute(&self, ctx: &HookContext) -> Result<HookResult> {
-        let hooks = self.get(ctx.hook_type);
-        let mut current_data = ctx.data.clone();
+        self.pipeline(ctx.hook_type).run(ctx).await
+    }
+
+    /// List all registered hooks.
+    pub fn list(&self) -> Vec<HookMetadata> {
+        self.hooks
+            .values()
+            .flat_map(|hooks| hooks.iter().map(|h| h.metadata()))
+            .collect()
+    }
+}
+
+/// An ordered chain of hooks run against one context.
+///
+/// Hooks run in priority order, highest first; hooks with the same priority
+/// run in the order they were added. Each hook sees the data as left by the
+/// hooks before it:
+///
+/// - [`HookResult::Continue`] with data merges that data into the context
+///   passed to the next hook; without data the context is passed on as is.
+/// - [`HookResult::Skip`] and [`HookResult::Abort`] stop the chain, and the
+///   pipeline returns that result. Later hooks do not run.
+/// - An error from a hook also stops the chain and is returned.
+pub struct HookPipeline {
+    hooks: Vec<Arc<dyn Hook>>,
+}
+
+impl HookPipeline {
+    /// Create a pipeline from hooks, ordering them by priority.
+    pub fn new(hooks: Vec<Arc<dyn Hook>>) -> Self {
+        let mut hooks: Vec<(HookPriority, Arc<dyn Hook>)> = hooks
+            .into_iter()
+            .map(|hook| (hook.metadata().priority, hook))
+            .collect();
+        hooks.sort_by_key(|(priority, _)| *priority);
 
-        for hook in hooks {
-            if !hook.should_run(ctx) {
+        Self {
+            hooks: hooks.into_iter().map(|(_, hook)| hook).collect(),
+        }
+    }
+
+    /// Get the hooks in the order they run.
+    pub fn hooks(&self) -> &[Arc<dyn Hook>] {
+        &self.hooks
+    }
+
+    /// Run the hooks that apply to `ctx`.
+    ///
+    /// Returns `Continue(Some(data))` with the final data if any hook changed
+    /// it, `Continue(None)` if none did, or the result of the hook that
+    /// stopped the chain.
+    pub async fn run(&self, ctx: &HookContext) -> Result<HookResult> {
+        let mut current = ctx.clone();
+
+        for hook in &self.hooks {
+            if !hook.should_run(&current) {
                 continue;
             }
 
-            let mut hook_ctx = ctx.clone();
-            hook_ctx.data = current_data.clone();
-
-            match hook.execute(&hook_ctx).await? {
-                HookResult::Continue(Some(new_data)) => {
-                    // Merge modified data
-                    for (k, v) in new_data {
-                        current_data.insert(k, v);
-                    }
-                }
-                HookResult::Continue(None) => {
-                    // Continue without modification
-                }
-                HookResult::Skip => {
-                    return Ok(HookResult::Skip);
-                }
-                HookResult::Abort(msg) => {
-                    return Ok(HookResult::Abort(msg));
-                }
+            match hook.execute(&current).await? {
+                HookResult::Continue(Some(new_data)) => current.data.extend(new_data),
+                HookResult::Continue(None) => {}
+                stop @ (HookResult::Skip | HookResult::Abort(_)) => return Ok(stop),
             }
         }
 
-        if current_data != ctx.data {
-            Ok(HookResult::Continue(Some(current_data)))
+        if current.data != ctx.data {
+            Ok(HookResult::Continue(Some(current.data)))
         } else {
             Ok(HookResult::Continue(None))
         }
     }
-
-    /// List all registered hooks.
-    pub fn list(&self) -> Vec<HookMetadata> {
-        self.hooks
-            .values()
-            .flat_map(|hooks| hooks.iter().map(|h| h.metadata()))
-            .collect()
-    }
 }
 
 #[cfg(test)]
@@ -378,4 +414,106 @@ mod tests {
 
         assert!(result.is_continue());
     }
+
+    type Action = fn(&HookContext) -> HookResult;
+
+    /// Hook that records when it runs and then applies `action`.
+    struct ScriptedHook {
+        name: &'static str,
+        priority: HookPriority,
+        log: Arc<std::sync::Mutex<Vec<&'static str>>>,
+        action: Action,
+    }
+
+    #[async_trait]
+    impl Hook for ScriptedHook {
+        fn metadata(&self) -> HookMetadata {
+            HookMetadata {
+                name: self.name.to_string(),
+                hook_type: HookType::PreSend,
+                priority: self.priority,
+                description: None,
+            }
+        }
+
+        async fn execute(&self, ctx: &HookContext) -> Result<HookResult> {
+            self.log.lock().unwrap().push(self.name);
+            Ok((self.action)(ctx))
+        }
+    }
+
+    fn pipeline(
+        hooks: &[(&'static str, HookPriority, Action)],
+    ) -> (HookPipeline, Arc<std::sync::Mutex<Vec<&'static str>>>) {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let hooks = hooks
+            .iter()
+            .map(|&(name, priority, action)| {
+                Arc::new(ScriptedHook {
+                    name,
+                    priority,
+                    log: log.clone(),
+                    action,
+                }) as Arc<dyn Hook>
+            })
+            .collect();
+        (HookPipeline::new(hooks), log)
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_runs_in_priority_order() {
+        let (pipeline, log) = pipeline(&[
+            ("low", HookPriority::Low, |_| HookResult::ok()),
+            ("normal-1", HookPriority::Normal, |_| HookResult::ok()),
+            ("highest", HookPriority::Highest, |_| HookResult::ok()),
+            ("normal-2", HookPriority::Normal, |_| HookResult::ok()),
+        ]);
+
+        let result = pipeline.run(&HookContext::new(HookType::PreSend)).await.unwrap();
+
+        assert!(matches!(result, HookResult::Continue(None)));
+        assert_eq!(*log.lock().unwrap(), ["highest", "normal-1", "normal-2", "low"]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_stops_at_abort() {
+        let (pipeline, log) = pipeline(&[
+            ("first", HookPriority::High, |_| HookResult::ok()),
+            ("reject", HookPriority::Normal, |_| HookResult::abort("blocked")),
+            ("never", HookPriority::Low, |_| HookResult::ok()),
+        ]);
+
+        let result = pipeline.run(&HookContext::new(HookType::PreSend)).await.unwrap();
+
+        assert!(matches!(result, HookResult::Abort(ref msg) if msg == "blocked"));
+        assert_eq!(*log.lock().unwrap(), ["first", "reject"]);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_passes_modified_data_downstream() {
+        fn text(ctx: &HookContext) -> String {
+            ctx.get_data::<String>("text").unwrap()
+        }
+        let (pipeline, _log) = pipeline(&[
+            ("exclaim", HookPriority::Low, |ctx| {
+                let data = HashMap::from([("text".to_string(), format!("{}!", text(ctx)).into())]);
+                HookResult::modified(data)
+            }),
+            ("upper", HookPriority::High, |ctx| {
+                let data = HashMap::from([("text".to_string(), text(ctx).to_uppercase().into())]);
+                HookResult::modified(data)
+            }),
+        ]);
+
+        let ctx = HookContext::new(HookType::PreSend)
+            .with_data("text", serde_json::json!("hello"))
+            .with_data("channel", serde_json::json!("telegram"));
+        let result = pipeline.run(&ctx).await.unwrap();
+
+        let HookResult::Continue(Some(data)) = result else {
+            panic!("expected modified data, got {:?}", result);
+        };
+        assert_eq!(data["text"], "HELLO!");
+        assert_eq!(data["channel"], "telegram");
+    }
 }
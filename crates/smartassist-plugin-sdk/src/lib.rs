@@ -5,6 +5,7 @@
 //! - Custom messaging channels (Telegram, Discord, etc.)
 //! - Custom tools for agents
 //! - Custom model providers
+//! - Custom gateway RPC methods
 //! - Custom hooks and middleware
 //!
 //! # Example Plugin
@@ -44,12 +45,17 @@ mod channel;
 mod error;
 mod hooks;
 mod provider;
+mod rpc;
 mod tool;
 
 pub use channel::{ChannelPlugin, ChannelPluginFactory};
 pub use error::{PluginError, Result};
-pub use hooks::{Hook, HookContext, HookResult, HookType};
+pub use hooks::{
+    Hook, HookContext, HookMetadata, HookPipeline, HookPriority, HookRegistry, HookResult,
+    HookType,
+};
 pub use provider::{ModelProviderPlugin, ProviderCapabilities};
+pub use rpc::{PluginRpcMethod, RpcMethodPlugin};
 pub use tool::{PluginTool, ToolExecutionContext, ToolPlugin, ToolPluginFactory};
 
 use async_trait::async_trait;
@@ -159,6 +165,8 @@ pub enum PluginCapability {
     Tool,
     /// Plugin provides a model provider.
     ModelProvider,
+    /// Plugin provides gateway RPC methods.
+    Rpc,
     /// Plugin provides hooks.
     Hook,
     /// Plugin provides storage backend.
@@ -322,6 +330,11 @@ pub trait Plugin: Send + Sync {
         None
     }
 
+    /// Get the plugin as an RPC method plugin, if applicable.
+    fn as_rpc_plugin(&self) -> Option<&dyn RpcMethodPlugin> {
+        None
+    }
+
     /// Get the plugin as Any for downcasting.
     fn as_any(&self) -> &dyn Any;
 
@@ -587,8 +600,8 @@ pub mod prelude {
         Channel, ChannelLifecycle, ChannelPlugin, ChannelPluginFactory, ChannelReceiver,
         ChannelSender, Hook, HookContext, HookResult, HookType, ModelProviderPlugin, Plugin,
         PluginCapability, PluginConfig, PluginContext, PluginError, PluginHealth, PluginLoader,
-        PluginMetadata, PluginState, PluginTool, ProviderCapabilities, Result, ToolExecutionContext,
-        ToolPlugin, ToolPluginFactory, Version,
+        PluginMetadata, PluginRpcMethod, PluginState, PluginTool, ProviderCapabilities, Result,
+        RpcMethodPlugin, ToolExecutionContext, ToolPlugin, ToolPluginFactory, Version,
     };
 
     pub use super::{
@@ -0,0 +1,25 @@
+//! RPC method plugin support.
+//!
+//! This module provides traits for plugins that add gateway RPC methods.
+//! The gateway registers each method as `plugin.<plugin name>.<method>`.
+
+use crate::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Trait for a single RPC method implementation.
+#[async_trait]
+pub trait PluginRpcMethod: Send + Sync {
+    /// Handle a call with its JSON parameters.
+    ///
+    /// A [`PluginError::Config`](crate::PluginError::Config) error is
+    /// reported to the caller as invalid parameters.
+    async fn call(&self, params: Option<serde_json::Value>) -> Result<serde_json::Value>;
+}
+
+/// Trait for plugins that provide RPC methods.
+pub trait RpcMethodPlugin: Send + Sync {
+    /// Get all methods provided by this plugin, keyed by their name within
+    /// the plugin's namespace.
+    fn rpc_methods(&self) -> Vec<(String, Arc<dyn PluginRpcMethod>)>;
+}
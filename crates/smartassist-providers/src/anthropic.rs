@@ -16,10 +16,12 @@
 //! ```
 
 use crate::{
-    ChatOptions, ChatResponse, CompletionStream, Message, MessageContent, MessageRole, ModelInfo,
-    Provider, ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount,
-    ToolUse, Usage,
+    assemble_tool_calls, render_messages, shared_client, ChatOptions, ChatResponse,
+    CompletionStream, Message, MessageContent, MessageRole, ModelDefaults, ModelInfo, Provider,
+    ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount, ToolCall,
+    Usage, WireTrace,
 };
+use crate::stream::decode_utf8;
 use async_trait::async_trait;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
@@ -35,6 +37,12 @@ const DEFAULT_API_BASE: &str = "https://api.anthropic.com";
 /// Current API version.
 const API_VERSION: &str = "2023-06-01";
 
+/// Default output token limit when the caller sets none.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
 /// Anthropic Claude provider.
 pub struct AnthropicProvider {
     /// HTTP client.
@@ -51,6 +59,12 @@ pub struct AnthropicProvider {
 
     /// Request timeout in seconds.
     timeout: u64,
+
+    /// Wire tracing settings.
+    wire_trace: WireTrace,
+
+    /// Sampling defaults by model.
+    model_defaults: ModelDefaults,
 }
 
 impl AnthropicProvider {
@@ -61,10 +75,7 @@ impl AnthropicProvider {
             return Err(ProviderError::config("API key is required"));
         }
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300))
-            .build()
-            .map_err(|e| ProviderError::config(format!("Failed to create HTTP client: {}", e)))?;
+        let client = shared_client()?;
 
         Ok(Self {
             client,
@@ -72,6 +83,8 @@ impl AnthropicProvider {
             api_base: DEFAULT_API_BASE.to_string(),
             default_model: "claude-sonnet-4-20250514".to_string(),
             timeout: 300,
+            wire_trace: WireTrace::default(),
+            model_defaults: ModelDefaults::default(),
         })
     }
 
@@ -88,6 +101,14 @@ impl AnthropicProvider {
         self
     }
 
+    /// Use `client` for requests, e.g. one built from an
+    /// [`HttpClientConfig`](crate::HttpClientConfig) and shared with other
+    /// providers.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Set the default model.
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = model.into();
@@ -100,6 +121,19 @@ impl AnthropicProvider {
         self
     }
 
+    /// Enable request and response tracing.
+    pub fn with_wire_trace(mut self, trace: WireTrace) -> Self {
+        self.wire_trace = trace;
+        self
+    }
+
+    /// Set per-model sampling defaults, used for options a request leaves
+    /// unset.
+    pub fn with_model_defaults(mut self, defaults: ModelDefaults) -> Self {
+        self.model_defaults = defaults;
+        self
+    }
+
     /// Convert messages to Anthropic format.
     fn convert_messages(
         &self,
@@ -158,22 +192,22 @@ impl AnthropicProvider {
                             converted.push(AnthropicContentPart::Text { text: s.clone() });
                         }
                         crate::ContentPart::Image(img) => {
-                            converted.push(AnthropicContentPart::Image {
-                                source: ImageSource {
-                                    source_type: match img.source_type {
-                                        crate::ImageSourceType::Base64 => "base64".to_string(),
-                                        crate::ImageSourceType::Url => "url".to_string(),
-                                    },
+                            let source = match img.source_type {
+                                crate::ImageSourceType::Base64 => ImageSource::Base64 {
                                     media_type: img.media_type.clone(),
                                     data: img.data.clone(),
                                 },
-                            });
+                                crate::ImageSourceType::Url => ImageSource::Url {
+                                    url: img.data.clone(),
+                                },
+                            };
+                            converted.push(AnthropicContentPart::Image { source });
                         }
                         crate::ContentPart::ToolUse(tool) => {
                             converted.push(AnthropicContentPart::ToolUse {
                                 id: tool.id.clone(),
                                 name: tool.name.clone(),
-                                input: tool.input.clone(),
+                                input: tool.arguments.clone(),
                             });
                         }
                         crate::ContentPart::ToolResult(result) => {
@@ -201,9 +235,63 @@ impl AnthropicProvider {
             .collect()
     }
 
+    /// Build a messages request.
+    fn build_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: ChatOptions,
+        stream: bool,
+    ) -> Result<AnthropicRequest> {
+        let max_tokens = options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS);
+        let thinking = options
+            .thinking_budget
+            .map(|budget_tokens| {
+                if budget_tokens < MIN_THINKING_BUDGET {
+                    return Err(ProviderError::invalid_request(format!(
+                        "Thinking budget must be at least {} tokens, got {}",
+                        MIN_THINKING_BUDGET, budget_tokens
+                    )));
+                }
+                if budget_tokens as usize >= max_tokens {
+                    return Err(ProviderError::invalid_request(format!(
+                        "Thinking budget ({}) must be below max_tokens ({})",
+                        budget_tokens, max_tokens
+                    )));
+                }
+                Ok(AnthropicThinking::Enabled { budget_tokens })
+            })
+            .transpose()?;
+
+        let (system, converted_messages) = self.convert_messages(messages)?;
+
+        Ok(AnthropicRequest {
+            model: model.to_string(),
+            messages: converted_messages,
+            max_tokens,
+            system,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            top_k: options.top_k,
+            stop_sequences: options.stop,
+            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
+            tool_choice: options.tool_choice.as_ref().map(|c| match c {
+                crate::ToolChoice::Auto => AnthropicToolChoice::Auto,
+                crate::ToolChoice::Any => AnthropicToolChoice::Any,
+                crate::ToolChoice::None => AnthropicToolChoice::None,
+                crate::ToolChoice::Tool { name } => AnthropicToolChoice::Tool {
+                    name: name.clone(),
+                },
+            }),
+            thinking,
+            stream,
+        })
+    }
+
     /// Parse Anthropic response.
     fn parse_response(&self, response: AnthropicResponse) -> ChatResponse {
         let mut content = String::new();
+        let mut thinking = String::new();
         let mut tool_calls = Vec::new();
 
         for block in &response.content {
@@ -212,15 +300,20 @@ impl AnthropicProvider {
                     content.push_str(text);
                 }
                 AnthropicContentBlock::ToolUse { id, name, input } => {
-                    tool_calls.push(ToolUse {
-                        id: id.clone(),
-                        name: name.clone(),
-                        input: input.clone(),
-                    });
+                    tool_calls.push(ToolCall::new(id, name, input.clone()));
+                }
+                AnthropicContentBlock::Thinking { thinking: text } => {
+                    thinking.push_str(text);
                 }
+                AnthropicContentBlock::RedactedThinking => {}
             }
         }
 
+        let mut metadata = HashMap::new();
+        if !thinking.is_empty() {
+            metadata.insert("thinking".to_string(), serde_json::Value::String(thinking));
+        }
+
         let stop_reason = match response.stop_reason.as_deref() {
             Some("end_turn") => StopReason::EndTurn,
             Some("stop_sequence") => StopReason::StopSequence,
@@ -241,7 +334,7 @@ impl AnthropicProvider {
                 cache_read_tokens: response.usage.cache_read_input_tokens.unwrap_or(0),
                 cache_creation_tokens: response.usage.cache_creation_input_tokens.unwrap_or(0),
             },
-            metadata: HashMap::new(),
+            metadata,
         }
     }
 }
@@ -255,44 +348,24 @@ impl Provider for AnthropicProvider {
     async fn list_models(&self) -> Result<Vec<ModelInfo>> {
         // Anthropic doesn't have a list models endpoint, so we return known models
         Ok(vec![
-            ModelInfo {
-                id: "claude-opus-4-20250514".to_string(),
-                name: "Claude Opus 4".to_string(),
-                description: "Most capable model for complex tasks".to_string(),
-                context_window: 200_000,
-                max_output: 32_000,
-                input_price: 15.0,
-                output_price: 75.0,
-                capabilities: vec![
-                    "vision".to_string(),
-                    "tools".to_string(),
-                    "computer_use".to_string(),
-                ],
-            },
-            ModelInfo {
-                id: "claude-sonnet-4-20250514".to_string(),
-                name: "Claude Sonnet 4".to_string(),
-                description: "Best balance of performance and speed".to_string(),
-                context_window: 200_000,
-                max_output: 64_000,
-                input_price: 3.0,
-                output_price: 15.0,
-                capabilities: vec![
-                    "vision".to_string(),
-                    "tools".to_string(),
-                    "computer_use".to_string(),
-                ],
-            },
-            ModelInfo {
-                id: "claude-3-5-haiku-20241022".to_string(),
-                name: "Claude 3.5 Haiku".to_string(),
-                description: "Fastest model for simple tasks".to_string(),
-                context_window: 200_000,
-                max_output: 8192,
-                input_price: 0.80,
-                output_price: 4.0,
-                capabilities: vec!["vision".to_string(), "tools".to_string()],
-            },
+            ModelInfo::new("claude-opus-4-20250514", "Claude Opus 4")
+                .with_description("Most capable model for complex tasks")
+                .with_limits(200_000, 32_000)
+                .with_support(true, true)
+                .with_pricing(15.0, 75.0)
+                .with_capabilities(&["vision", "tools", "computer_use"]),
+            ModelInfo::new("claude-sonnet-4-20250514", "Claude Sonnet 4")
+                .with_description("Best balance of performance and speed")
+                .with_limits(200_000, 64_000)
+                .with_support(true, true)
+                .with_pricing(3.0, 15.0)
+                .with_capabilities(&["vision", "tools", "computer_use"]),
+            ModelInfo::new("claude-3-5-haiku-20241022", "Claude 3.5 Haiku")
+                .with_description("Fastest model for simple tasks")
+                .with_limits(200_000, 8192)
+                .with_support(true, true)
+                .with_pricing(0.80, 4.0)
+                .with_capabilities(&["vision", "tools"]),
         ])
     }
 
@@ -302,36 +375,24 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<ChatResponse> {
-        let options = options.unwrap_or_default();
-        let (system, converted_messages) = self.convert_messages(messages)?;
-
-        let request = AnthropicRequest {
-            model: model.to_string(),
-            messages: converted_messages,
-            max_tokens: options.max_tokens.unwrap_or(4096),
-            system,
-            temperature: options.temperature,
-            top_p: options.top_p,
-            top_k: options.top_k,
-            stop_sequences: options.stop,
-            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
-            tool_choice: options.tool_choice.as_ref().map(|c| match c {
-                crate::ToolChoice::Auto => AnthropicToolChoice::Auto,
-                crate::ToolChoice::Any => AnthropicToolChoice::Any,
-                crate::ToolChoice::None => AnthropicToolChoice::None,
-                crate::ToolChoice::Tool { name } => AnthropicToolChoice::Tool {
-                    name: name.clone(),
-                },
-            }),
-            stream: false,
-        };
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        let request = self.build_request(model, messages, options.unwrap_or_default(), false)?;
 
         debug!("Sending request to Anthropic: model={}", model);
 
+        let url = format!("{}/v1/messages", self.api_base);
+        let api_key = self.api_key.expose_secret();
+        let started =
+            self.wire_trace.request("anthropic", &url, model, messages, &request, api_key);
+
         let response = self
             .client
-            .post(format!("{}/v1/messages", self.api_base))
-            .header("x-api-key", self.api_key.expose_secret())
+            .post(&url)
+            .header("x-api-key", api_key)
             .header("anthropic-version", API_VERSION)
             .header("content-type", "application/json")
             .json(&request)
@@ -340,6 +401,8 @@ impl Provider for AnthropicProvider {
 
         let status = response.status();
         if !status.is_success() {
+            self.wire_trace.response("anthropic", status.as_u16(), None, started);
+            let headers = response.headers().clone();
             let error_body: AnthropicError = response.json().await.unwrap_or_else(|_| AnthropicError {
                 error: AnthropicErrorDetail {
                     error_type: "unknown".to_string(),
@@ -347,19 +410,19 @@ impl Provider for AnthropicProvider {
                 },
             });
 
-            return match status.as_u16() {
-                401 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
         }
 
-        let response: AnthropicResponse = response.json().await?;
-        Ok(self.parse_response(response))
+        let body = response.text().await?;
+        self.wire_trace.response_body("anthropic", &body, api_key);
+        let response = self.parse_response(serde_json::from_str::<AnthropicResponse>(&body)?);
+        self.wire_trace
+            .response("anthropic", status.as_u16(), Some(&response.usage), started);
+        Ok(response)
     }
 
     async fn chat_stream(
@@ -368,34 +431,22 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<CompletionStream> {
-        let options = options.unwrap_or_default();
-        let (system, converted_messages) = self.convert_messages(messages)?;
-
-        let request = AnthropicRequest {
-            model: model.to_string(),
-            messages: converted_messages,
-            max_tokens: options.max_tokens.unwrap_or(4096),
-            system,
-            temperature: options.temperature,
-            top_p: options.top_p,
-            top_k: options.top_k,
-            stop_sequences: options.stop,
-            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
-            tool_choice: options.tool_choice.as_ref().map(|c| match c {
-                crate::ToolChoice::Auto => AnthropicToolChoice::Auto,
-                crate::ToolChoice::Any => AnthropicToolChoice::Any,
-                crate::ToolChoice::None => AnthropicToolChoice::None,
-                crate::ToolChoice::Tool { name } => AnthropicToolChoice::Tool {
-                    name: name.clone(),
-                },
-            }),
-            stream: true,
-        };
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        let request = self.build_request(model, messages, options.unwrap_or_default(), true)?;
+
+        let url = format!("{}/v1/messages", self.api_base);
+        let api_key = self.api_key.expose_secret();
+        let started =
+            self.wire_trace.request("anthropic", &url, model, messages, &request, api_key);
 
         let response = self
             .client
-            .post(format!("{}/v1/messages", self.api_base))
-            .header("x-api-key", self.api_key.expose_secret())
+            .post(&url)
+            .header("x-api-key", api_key)
             .header("anthropic-version", API_VERSION)
             .header("content-type", "application/json")
             .json(&request)
@@ -403,7 +454,9 @@ impl Provider for AnthropicProvider {
             .await?;
 
         let status = response.status();
+        self.wire_trace.response("anthropic", status.as_u16(), None, started);
         if !status.is_success() {
+            let headers = response.headers().clone();
             let error_body: AnthropicError = response.json().await.unwrap_or_else(|_| AnthropicError {
                 error: AnthropicErrorDetail {
                     error_type: "unknown".to_string(),
@@ -411,19 +464,15 @@ impl Provider for AnthropicProvider {
                 },
             });
 
-            return match status.as_u16() {
-                401 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
         }
 
         let model = model.to_string();
-        let byte_stream = response.bytes_stream();
+        let byte_stream = decode_utf8(response.bytes_stream());
 
         let event_stream = byte_stream.eventsource();
 
@@ -450,8 +499,12 @@ impl Provider for AnthropicProvider {
                                 AnthropicStreamEvent::ContentBlockDelta { delta, .. } => {
                                     if let Some(text) = delta.text {
                                         Some(Ok(StreamEvent::ContentDelta { delta: text }))
+                                    } else if let Some(thinking) = delta.thinking {
+                                        Some(Ok(StreamEvent::Thinking { delta: thinking }))
                                     } else {
-                                        None
+                                        delta
+                                            .partial_json
+                                            .map(|json| Ok(StreamEvent::ToolInputDelta { delta: json }))
                                     }
                                 }
                                 AnthropicStreamEvent::ContentBlockStart { content_block, .. } => {
@@ -495,7 +548,7 @@ impl Provider for AnthropicProvider {
             }
         });
 
-        Ok(Box::pin(stream))
+        Ok(assemble_tool_calls(Box::pin(stream)))
     }
 
     async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount> {
@@ -547,6 +600,14 @@ impl Provider for AnthropicProvider {
             max_output: Some(64_000),
         }
     }
+
+    fn model_capabilities(&self, model: &str) -> ProviderCapabilities {
+        ProviderCapabilities {
+            // Claude 2 and Instant predate image input.
+            vision: !(model.starts_with("claude-2") || model.starts_with("claude-instant")),
+            ..self.capabilities()
+        }
+    }
 }
 
 // Internal types for Anthropic API
@@ -570,9 +631,17 @@ struct AnthropicRequest {
     tools: Option<Vec<AnthropicTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<AnthropicToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<AnthropicThinking>,
     stream: bool,
 }
 
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicThinking {
+    Enabled { budget_tokens: u32 },
+}
+
 #[derive(Serialize)]
 struct AnthropicMessage {
     role: String,
@@ -596,11 +665,10 @@ enum AnthropicContentPart {
 }
 
 #[derive(Serialize)]
-struct ImageSource {
-    #[serde(rename = "type")]
-    source_type: String,
-    media_type: String,
-    data: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
 }
 
 #[derive(Serialize)]
@@ -633,6 +701,8 @@ struct AnthropicResponse {
 enum AnthropicContentBlock {
     Text { text: String },
     ToolUse { id: String, name: String, input: serde_json::Value },
+    Thinking { thinking: String },
+    RedactedThinking,
 }
 
 #[derive(Deserialize)]
@@ -693,6 +763,7 @@ struct AnthropicStreamMessage {
 #[derive(Deserialize)]
 struct ContentDelta {
     text: Option<String>,
+    thinking: Option<String>,
     partial_json: Option<String>,
 }
 
@@ -716,6 +787,24 @@ mod tests {
         assert_eq!(provider.name(), "anthropic");
     }
 
+    #[tokio::test]
+    async fn test_undefined_template_variable_fails_before_sending() {
+        // Nothing listens on the discard port, so reaching it would fail
+        // with a network error instead.
+        let provider = AnthropicProvider::new("test-key")
+            .unwrap()
+            .with_base_url("http://127.0.0.1:9");
+        let messages = [Message::system("You are {{name}}."), Message::user("Hi")];
+        let options = ChatOptions::with_max_tokens(16).variables(crate::TemplateVars::new());
+
+        let err = provider
+            .chat("claude-sonnet-4-20250514", &messages, Some(options))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProviderError::InvalidRequest(_)), "{}", err);
+        assert!(err.to_string().contains("name"));
+    }
+
     #[test]
     fn test_provider_empty_key() {
         let result = AnthropicProvider::new("");
@@ -740,5 +829,286 @@ mod tests {
 
         assert!(!models.is_empty());
         assert!(models.iter().any(|m| m.id.contains("claude")));
+
+        let sonnet = models
+            .iter()
+            .find(|m| m.id == "claude-sonnet-4-20250514")
+            .unwrap();
+        assert_eq!(sonnet.context_window, Some(200_000));
+        assert_eq!(sonnet.max_output, Some(64_000));
+        assert_eq!(sonnet.supports_tools, Some(true));
+        assert_eq!(sonnet.supports_vision, Some(true));
+        assert_eq!(sonnet.input_price, Some(3.0));
+        assert_eq!(sonnet.output_price, Some(15.0));
+    }
+
+    #[test]
+    fn test_image_message_serialization() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        let messages = vec![
+            Message::with_image(
+                MessageRole::User,
+                "What is in this image?",
+                crate::ImageContent::base64("image/png", "iVBORw0KGgo="),
+            ),
+            Message::with_image(
+                MessageRole::User,
+                "And this one?",
+                crate::ImageContent::url("https://example.com/cat.jpg"),
+            ),
+        ];
+
+        let (_, converted) = provider.convert_messages(&messages).unwrap();
+        let json = serde_json::to_value(&converted).unwrap();
+
+        assert_eq!(
+            json[0]["content"][1],
+            serde_json::json!({
+                "type": "image",
+                "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}
+            })
+        );
+        assert_eq!(
+            json[1]["content"][1],
+            serde_json::json!({
+                "type": "image",
+                "source": {"type": "url", "url": "https://example.com/cat.jpg"}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_image_rejected_for_non_vision_model() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        assert!(provider.model_capabilities("claude-sonnet-4-20250514").vision);
+        assert!(!provider.model_capabilities("claude-2.1").vision);
+
+        let messages = vec![Message::with_image(
+            MessageRole::User,
+            "What is this?",
+            crate::ImageContent::base64("image/png", "iVBORw0KGgo="),
+        )];
+        let result = provider.chat("claude-2.1", &messages, None).await;
+        assert!(matches!(result, Err(ProviderError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_parse_tool_use_response() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        let raw = serde_json::json!({
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-sonnet-4-20250514",
+            "content": [
+                {"type": "text", "text": "Let me check."},
+                {
+                    "type": "tool_use",
+                    "id": "toolu_01",
+                    "name": "get_weather",
+                    "input": {"city": "Paris"}
+                }
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 30, "output_tokens": 12}
+        });
+
+        let response = provider.parse_response(serde_json::from_value(raw).unwrap());
+
+        assert_eq!(response.content, "Let me check.");
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(
+            response.tool_calls,
+            vec![ToolCall::new(
+                "toolu_01",
+                "get_weather",
+                serde_json::json!({"city": "Paris"})
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_tool_use() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\"}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_01\",\"name\":\"get_weather\",\"input\":{}}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"city\\\": \"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"\\\"Paris\\\"}\"}}\n\n",
+            "event: content_block_stop\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":12}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = AnthropicProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let events: Vec<_> = provider
+            .chat_stream("claude-sonnet-4-20250514", &[Message::user("Weather?")], None)
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        let calls: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolCall(call) => Some(call.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![ToolCall::new(
+                "toolu_01",
+                "get_weather",
+                serde_json::json!({"city": "Paris"})
+            )]
+        );
+    }
+
+    #[test]
+    fn test_thinking_request_serialization() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        let model = "claude-sonnet-4-20250514";
+        let messages = [Message::user("Hi")];
+        let options = ChatOptions::with_max_tokens(16_000).thinking_budget(10_000);
+
+        let request = provider.build_request(model, &messages, options, false).unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(
+            json["thinking"],
+            serde_json::json!({"type": "enabled", "budget_tokens": 10_000})
+        );
+        assert_eq!(json["max_tokens"], 16_000);
+
+        let request = provider
+            .build_request(model, &messages, ChatOptions::default(), false)
+            .unwrap();
+        assert!(serde_json::to_value(&request).unwrap().get("thinking").is_none());
+    }
+
+    #[test]
+    fn test_thinking_budget_validated() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        let model = "claude-sonnet-4-20250514";
+        let messages = [Message::user("Hi")];
+
+        // The default max_tokens is 4096.
+        for budget in [4096, 512] {
+            let options = ChatOptions::default().thinking_budget(budget);
+            let result = provider.build_request(model, &messages, options, false);
+            assert!(matches!(result, Err(ProviderError::InvalidRequest(_))));
+        }
+    }
+
+    #[test]
+    fn test_parse_thinking_response() {
+        let provider = AnthropicProvider::new("test-key").unwrap();
+        let raw = serde_json::json!({
+            "id": "msg_01",
+            "model": "claude-sonnet-4-20250514",
+            "content": [
+                {"type": "thinking", "thinking": "The user greets me.", "signature": "sig"},
+                {"type": "redacted_thinking", "data": "opaque"},
+                {"type": "text", "text": "Hello!"}
+            ],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 10, "output_tokens": 20}
+        });
+
+        let response = provider.parse_response(serde_json::from_value(raw).unwrap());
+
+        assert_eq!(response.content, "Hello!");
+        assert_eq!(response.metadata["thinking"], "The user greets me.");
+    }
+
+    #[tokio::test]
+    async fn test_stream_thinking() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\"}}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"thinking\",\"thinking\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Let me think\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"signature_delta\",\"signature\":\"EqQBCgIYAhIM\"}}\n\n",
+            "event: content_block_stop\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":0}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"text_delta\",\"text\":\"42\"}}\n\n",
+            "event: content_block_stop\n",
+            "data: {\"type\":\"content_block_stop\",\"index\":1}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":30}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(body_partial_json(serde_json::json!({
+                "stream": true,
+                "thinking": {"type": "enabled", "budget_tokens": 2048}
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = AnthropicProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let options = ChatOptions::with_max_tokens(8192).thinking_budget(2048);
+        let events: Vec<_> = provider
+            .chat_stream("claude-sonnet-4-20250514", &[Message::user("Answer?")], Some(options))
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        let thinking: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::Thinking { delta } => Some(delta.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(thinking, ["Let me think"]);
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, StreamEvent::ContentDelta { delta } if delta == "42")));
     }
 }
@@ -0,0 +1,93 @@
+//! Cancellation support for streaming completions.
+//!
+//! A cancelled stream ends without yielding further events and drops the
+//! underlying provider stream, which closes the HTTP response body and the
+//! connection behind it. Events yielded before cancellation are unaffected.
+
+use crate::CompletionStream;
+use futures::future::{self, Either};
+use futures::StreamExt;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Wrap a completion stream so that it ends as soon as `token` is cancelled.
+pub fn cancellable(stream: CompletionStream, token: CancellationToken) -> CompletionStream {
+    let stream = futures::stream::unfold(Some(stream), move |state| {
+        let token = token.clone();
+        async move {
+            let mut inner = state?;
+            if token.is_cancelled() {
+                return None;
+            }
+
+            let next = {
+                let cancelled = std::pin::pin!(token.cancelled());
+                match future::select(cancelled, inner.next()).await {
+                    Either::Left(_) => None,
+                    Either::Right((item, _)) => Some(item),
+                }
+            };
+
+            // `inner` is dropped here on cancellation or exhaustion.
+            match next {
+                Some(Some(item)) if !token.is_cancelled() => Some((item, Some(inner))),
+                _ => None,
+            }
+        }
+    });
+
+    Box::pin(stream.fuse())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StreamEvent;
+
+    fn delta(text: &str) -> crate::Result<StreamEvent> {
+        Ok(StreamEvent::ContentDelta {
+            delta: text.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_uncancelled_stream_passes_through() {
+        let inner: CompletionStream = Box::pin(futures::stream::iter(vec![delta("a"), delta("b")]));
+        let events: Vec<_> = cancellable(inner, CancellationToken::new()).collect().await;
+        assert_eq!(events.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_before_poll_yields_nothing() {
+        let inner: CompletionStream = Box::pin(futures::stream::iter(vec![delta("a")]));
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let mut stream = cancellable(inner, token);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_pending_stream() {
+        let inner: CompletionStream =
+            Box::pin(futures::stream::iter(vec![delta("a")]).chain(futures::stream::pending()));
+        let token = CancellationToken::new();
+        let mut stream = cancellable(inner, token.clone());
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Ok(StreamEvent::ContentDelta { .. }))
+        ));
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+
+        let next = tokio::time::timeout(std::time::Duration::from_secs(2), stream.next())
+            .await
+            .expect("stream should end promptly after cancellation");
+        assert!(next.is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! DeepSeek provider implementation.
+//!
+//! DeepSeek serves an OpenAI-compatible chat completions API, so requests
+//! are built and parsed by the [`OpenAIProvider`] against DeepSeek's base URL.
+
+use crate::openai::OpenAIProvider;
+use crate::{
+    render_messages, ChatOptions, ChatResponse, CompletionStream, Message, ModelDefaults,
+    ModelInfo, Provider, ProviderCapabilities, ProviderError, Result, TokenCount, WireTrace,
+};
+use async_trait::async_trait;
+
+/// Default DeepSeek API base URL.
+const DEFAULT_API_BASE: &str = "https://api.deepseek.com";
+
+/// Context window shared by the DeepSeek chat models.
+const CONTEXT_WINDOW: usize = 64_000;
+
+/// Maximum output tokens for the DeepSeek chat models.
+const MAX_OUTPUT: usize = 8_192;
+
+/// DeepSeek provider.
+pub struct DeepSeekProvider {
+    /// OpenAI-compatible client pointed at the DeepSeek API.
+    inner: OpenAIProvider,
+}
+
+impl DeepSeekProvider {
+    /// Create a new DeepSeek provider with an API key.
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        let inner = OpenAIProvider::new(api_key)?
+            .with_base_url(DEFAULT_API_BASE)
+            .with_default_model("deepseek-chat");
+        Ok(Self { inner })
+    }
+
+    /// Create a new provider from environment variable.
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("DEEPSEEK_API_KEY")
+            .map_err(|_| ProviderError::config("DEEPSEEK_API_KEY environment variable not set"))?;
+        Self::new(api_key)
+    }
+
+    /// Set the API base URL (for proxies or self-hosted gateways).
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_base_url(url);
+        self
+    }
+
+    /// Use `client` for requests; see [`OpenAIProvider::with_http_client`].
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Set the default model.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Enable request and response tracing.
+    pub fn with_wire_trace(mut self, trace: WireTrace) -> Self {
+        self.inner = self.inner.with_wire_trace(trace);
+        self
+    }
+
+    /// Set per-model sampling defaults, used for options a request leaves
+    /// unset.
+    pub fn with_model_defaults(mut self, defaults: ModelDefaults) -> Self {
+        self.inner = self.inner.with_model_defaults(defaults);
+        self
+    }
+}
+
+#[async_trait]
+impl Provider for DeepSeekProvider {
+    fn name(&self) -> &str {
+        "deepseek"
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        // Prices in USD per million tokens; the legacy coder alias has none.
+        let models = [
+            ("deepseek-chat", "DeepSeek Chat", "General chat model", Some((0.27, 1.10))),
+            ("deepseek-coder", "DeepSeek Coder", "Code generation model", None),
+            ("deepseek-reasoner", "DeepSeek Reasoner", "Reasoning model", Some((0.55, 2.19))),
+        ];
+
+        Ok(models
+            .into_iter()
+            .map(|(id, name, description, pricing)| {
+                let tools = model_supports_tools(id);
+                let info = ModelInfo::new(id, name)
+                    .with_description(description)
+                    .with_limits(CONTEXT_WINDOW, MAX_OUTPUT)
+                    .with_support(tools, false)
+                    .with_capabilities(if tools { &["tools"] } else { &[] });
+                match pricing {
+                    Some((input, output)) => info.with_pricing(input, output),
+                    None => info,
+                }
+            })
+            .collect())
+    }
+
+    async fn chat(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<ChatResponse> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.inner.model_defaults().apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.inner.send_chat(model, messages, options).await
+    }
+
+    async fn chat_stream(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<CompletionStream> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.inner.model_defaults().apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.inner.send_chat_stream(model, messages, options).await
+    }
+
+    async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount> {
+        self.inner.count_tokens(model, messages).await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: true,
+            tools: true,
+            vision: false,
+            system_messages: true,
+            max_context: Some(CONTEXT_WINDOW),
+            max_output: Some(MAX_OUTPUT),
+        }
+    }
+
+    fn model_capabilities(&self, model: &str) -> ProviderCapabilities {
+        ProviderCapabilities {
+            tools: model_supports_tools(model),
+            ..self.capabilities()
+        }
+    }
+}
+
+/// Check whether a DeepSeek model supports function calling.
+fn model_supports_tools(model: &str) -> bool {
+    !model.starts_with("deepseek-reasoner")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = DeepSeekProvider::new("test-key").unwrap();
+        assert_eq!(provider.name(), "deepseek");
+        assert!(DeepSeekProvider::new("").is_err());
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let provider = DeepSeekProvider::new("test-key").unwrap();
+        let caps = provider.capabilities();
+
+        assert!(caps.streaming);
+        assert!(!caps.vision);
+        assert_eq!(caps.max_context, Some(64_000));
+        assert!(!provider.model_capabilities("deepseek-reasoner").tools);
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let provider = DeepSeekProvider::new("test-key").unwrap();
+        let models = provider.list_models().await.unwrap();
+
+        let ids: Vec<_> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["deepseek-chat", "deepseek-coder", "deepseek-reasoner"]);
+        assert!(provider.is_model_available("deepseek-chat").await.unwrap());
+
+        let chat = &models[0];
+        assert_eq!(chat.context_window, Some(64_000));
+        assert_eq!(chat.max_output, Some(8_192));
+        assert_eq!(chat.supports_tools, Some(true));
+        assert_eq!(chat.supports_vision, Some(false));
+        assert_eq!(chat.input_price, Some(0.27));
+        assert_eq!(models[1].input_price, None);
+        assert_eq!(models[2].supports_tools, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_chat_request_serialization() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", "Bearer test-key"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "deepseek-chat",
+                "stream": false,
+                "max_tokens": 256,
+                "messages": [
+                    {"role": "system", "content": "Be brief."},
+                    {"role": "user", "content": "Hello"}
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "model": "deepseek-chat",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Hi!"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 9, "completion_tokens": 2, "total_tokens": 11}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DeepSeekProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let options = ChatOptions {
+            max_tokens: Some(256),
+            ..Default::default()
+        };
+        let response = provider
+            .chat(
+                "deepseek-chat",
+                &[Message::system("Be brief."), Message::user("Hello")],
+                Some(options),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hi!");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_request() {
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "deepseek-coder",
+                "stream": true
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = DeepSeekProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let events: Vec<_> = provider
+            .chat_stream("deepseek-coder", &[Message::user("Hello")], None)
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, crate::StreamEvent::ContentDelta { delta } if delta == "Hi")));
+    }
+
+    #[tokio::test]
+    async fn test_image_rejected() {
+        let provider = DeepSeekProvider::new("test-key").unwrap();
+        let messages = [Message::with_image(
+            crate::MessageRole::User,
+            "What is this?",
+            crate::ImageContent::base64("image/png", "iVBORw0KGgo="),
+        )];
+
+        let err = provider.chat("deepseek-chat", &messages, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Unsupported(_)));
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for model providers.
 
+use std::time::Duration;
 use thiserror::Error;
 
 /// Result type for provider operations.
@@ -130,26 +131,91 @@ impl ProviderError {
         Self::Internal(message.into())
     }
 
+    /// Create the error for a failed HTTP response.
+    ///
+    /// For 429 responses the delay in the `Retry-After` header, if any, is
+    /// kept for [`is_rate_limited`](Self::is_rate_limited).
+    pub fn from_status(
+        status: u16,
+        message: impl Into<String>,
+        headers: &reqwest::header::HeaderMap,
+    ) -> Self {
+        match status {
+            400 => Self::invalid_request(message),
+            401 | 403 => Self::auth(message),
+            429 => {
+                let retry_after = headers
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_retry_after)
+                    // Round up, so callers never retry early.
+                    .map(|delay| delay.as_secs() + u64::from(delay.subsec_nanos() > 0));
+                Self::rate_limit(message, retry_after)
+            }
+            _ => Self::server_error(status, message),
+        }
+    }
+
     /// Check if this error is retryable.
+    ///
+    /// Rate limits, timeouts, connection failures, and HTTP 408, 409, 429
+    /// and 5xx responses are; invalid requests and authentication failures
+    /// are not.
     pub fn is_retryable(&self) -> bool {
         match self {
-            Self::RateLimit { .. } | Self::Timeout(_) | Self::Network(_) => true,
-            Self::ServerError { status, .. } => *status >= 500,
+            Self::RateLimit { .. } | Self::Timeout(_) => true,
+            // A request that couldn't be built fails the same way again.
+            Self::Network(e) => !e.is_builder(),
+            Self::ServerError { status, .. } => is_retryable_status(*status),
             _ => false,
         }
     }
 
+    /// If this is a rate-limit error, how long the provider asked callers
+    /// to wait.
+    ///
+    /// `Some(Duration::ZERO)` means rate limited without a requested delay;
+    /// callers should then fall back to their own backoff.
+    pub fn is_rate_limited(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimit { retry_after, .. } => {
+                Some(Duration::from_secs(retry_after.unwrap_or(0)))
+            }
+            Self::ServerError { status: 429, .. } => Some(Duration::ZERO),
+            _ => None,
+        }
+    }
+
     /// Get retry delay if applicable.
     pub fn retry_after(&self) -> Option<u64> {
         match self {
             Self::RateLimit { retry_after, .. } => *retry_after,
             Self::Timeout(_) => Some(1),
-            Self::ServerError { status, .. } if *status >= 500 => Some(5),
+            Self::ServerError { status, .. } if is_retryable_status(*status) => Some(5),
             _ => None,
         }
     }
 }
 
+/// HTTP statuses worth retrying: timeouts, conflicts, rate limits and
+/// server errors.
+fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 408 | 409 | 429 | 500..=599)
+}
+
+/// Parse a `Retry-After` value, either delay seconds or an HTTP date.
+///
+/// Dates in the past give a zero delay.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -176,4 +242,110 @@ mod tests {
         assert!(!ProviderError::invalid_request("").is_retryable());
         assert!(!ProviderError::server_error(400, "").is_retryable());
     }
+
+    #[test]
+    fn test_status_classification() {
+        let headers = reqwest::header::HeaderMap::new();
+        // (status, retryable, rate limited)
+        let cases = [
+            (400, false, false),
+            (401, false, false),
+            (403, false, false),
+            (404, false, false),
+            (408, true, false),
+            (409, true, false),
+            (422, false, false),
+            (429, true, true),
+            (500, true, false),
+            (502, true, false),
+            (503, true, false),
+            (504, true, false),
+        ];
+
+        for (status, retryable, rate_limited) in cases {
+            let err = ProviderError::from_status(status, "failed", &headers);
+            assert_eq!(err.is_retryable(), retryable, "status {}", status);
+            assert_eq!(err.is_rate_limited().is_some(), rate_limited, "status {}", status);
+
+            // Statuses reported without going through from_status agree.
+            let err = ProviderError::server_error(status, "failed");
+            assert_eq!(err.is_retryable(), retryable, "status {}", status);
+        }
+
+        assert!(matches!(
+            ProviderError::from_status(400, "", &headers),
+            ProviderError::InvalidRequest(_)
+        ));
+        assert!(matches!(
+            ProviderError::from_status(401, "", &headers),
+            ProviderError::Authentication(_)
+        ));
+        assert!(matches!(
+            ProviderError::from_status(403, "", &headers),
+            ProviderError::Authentication(_)
+        ));
+        assert!(matches!(
+            ProviderError::from_status(503, "", &headers),
+            ProviderError::ServerError { status: 503, .. }
+        ));
+    }
+
+    #[test]
+    fn test_other_errors_classification() {
+        assert!(ProviderError::Timeout(30).is_retryable());
+        assert_eq!(ProviderError::Timeout(30).is_rate_limited(), None);
+        for err in [
+            ProviderError::context_exceeded(10, 5),
+            ProviderError::content_filtered(""),
+            ProviderError::config(""),
+            ProviderError::model_not_found("m"),
+            ProviderError::stream(""),
+        ] {
+            assert!(!err.is_retryable(), "{}", err);
+        }
+
+        let builder = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert!(!ProviderError::Network(builder).is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_connection_error_is_retryable() {
+        // Nothing listens on port 1.
+        let err = reqwest::get("http://127.0.0.1:1/").await.unwrap_err();
+        assert!(ProviderError::Network(err).is_retryable());
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        assert_eq!(
+            ProviderError::from_status(429, "", &headers).is_rate_limited(),
+            Some(Duration::ZERO)
+        );
+
+        headers.insert("retry-after", "7".parse().unwrap());
+        assert_eq!(
+            ProviderError::from_status(429, "", &headers).is_rate_limited(),
+            Some(Duration::from_secs(7))
+        );
+
+        let date = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        headers.insert("retry-after", date.parse().unwrap());
+        let delay = ProviderError::from_status(429, "", &headers).is_rate_limited().unwrap();
+        assert!(delay > Duration::from_secs(85) && delay <= Duration::from_secs(90), "{:?}", delay);
+
+        headers.insert("retry-after", "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(
+            ProviderError::from_status(429, "", &headers).is_rate_limited(),
+            Some(Duration::ZERO)
+        );
+
+        headers.insert("retry-after", "soon".parse().unwrap());
+        assert_eq!(
+            ProviderError::from_status(429, "", &headers).is_rate_limited(),
+            Some(Duration::ZERO)
+        );
+        // Only rate limits carry the delay.
+        assert_eq!(ProviderError::from_status(503, "", &headers).is_rate_limited(), None);
+    }
 }
@@ -3,10 +3,12 @@
 //! This module provides integration with Google's Gemini models.
 
 use crate::{
-    ChatOptions, ChatResponse, CompletionStream, Message, MessageContent, MessageRole, ModelInfo,
-    Provider, ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount,
-    ToolUse, Usage,
+    assemble_tool_calls, render_messages, shared_client, ChatOptions, ChatResponse,
+    CompletionStream, Message, MessageContent, MessageRole, ModelDefaults, ModelInfo, Provider,
+    ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount, ToolCall,
+    Usage, WireTrace,
 };
+use crate::stream::decode_utf8;
 use async_trait::async_trait;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
@@ -32,6 +34,12 @@ pub struct GoogleProvider {
 
     /// Default model to use.
     default_model: String,
+
+    /// Wire tracing settings.
+    wire_trace: WireTrace,
+
+    /// Sampling defaults by model.
+    model_defaults: ModelDefaults,
 }
 
 impl GoogleProvider {
@@ -42,16 +50,15 @@ impl GoogleProvider {
             return Err(ProviderError::config("API key is required"));
         }
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300))
-            .build()
-            .map_err(|e| ProviderError::config(format!("Failed to create HTTP client: {}", e)))?;
+        let client = shared_client()?;
 
         Ok(Self {
             client,
             api_key: SecretString::new(api_key),
             api_base: DEFAULT_API_BASE.to_string(),
             default_model: "gemini-2.0-flash".to_string(),
+            wire_trace: WireTrace::default(),
+            model_defaults: ModelDefaults::default(),
         })
     }
 
@@ -73,12 +80,36 @@ impl GoogleProvider {
         self
     }
 
+    /// Use `client` for requests, e.g. one built from an
+    /// [`HttpClientConfig`](crate::HttpClientConfig) and shared with other
+    /// providers.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
     /// Set the default model.
     pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
         self.default_model = model.into();
         self
     }
 
+    /// Enable request and response tracing.
+    ///
+    /// Gemini takes the API key as a query parameter, so logged endpoints
+    /// have it redacted.
+    pub fn with_wire_trace(mut self, trace: WireTrace) -> Self {
+        self.wire_trace = trace;
+        self
+    }
+
+    /// Set per-model sampling defaults, used for options a request leaves
+    /// unset.
+    pub fn with_model_defaults(mut self, defaults: ModelDefaults) -> Self {
+        self.model_defaults = defaults;
+        self
+    }
+
     /// Convert messages to Gemini format.
     fn convert_messages(
         &self,
@@ -110,13 +141,13 @@ impl GoogleProvider {
                     });
                 }
                 MessageRole::Tool => {
-                    // Tool results in Gemini format
+                    // Tool results in Gemini format, keyed by function name
                     if let Some(tool_call_id) = &msg.tool_call_id {
                         contents.push(GeminiContent {
                             role: "user".to_string(),
                             parts: vec![GeminiPart::FunctionResponse {
                                 function_response: GeminiFunctionResponse {
-                                    name: tool_call_id.clone(),
+                                    name: msg.name.clone().unwrap_or_else(|| tool_call_id.clone()),
                                     response: serde_json::json!({
                                         "result": msg.text().unwrap_or("")
                                     }),
@@ -154,7 +185,7 @@ impl GoogleProvider {
                             gemini_parts.push(GeminiPart::FunctionCall {
                                 function_call: GeminiFunctionCall {
                                     name: tool.name.clone(),
-                                    args: tool.input.clone(),
+                                    args: tool.arguments.clone(),
                                 },
                             });
                         }
@@ -197,11 +228,7 @@ impl GoogleProvider {
                     content.push_str(&text);
                 }
                 GeminiPart::FunctionCall { function_call } => {
-                    tool_calls.push(ToolUse {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        name: function_call.name,
-                        input: function_call.args,
-                    });
+                    tool_calls.push(function_call.into_tool_call());
                 }
                 _ => {}
             }
@@ -264,6 +291,7 @@ impl Provider for GoogleProvider {
         }
 
         #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
         struct GeminiModel {
             name: String,
             #[serde(default)]
@@ -284,20 +312,20 @@ impl Provider for GoogleProvider {
             .filter(|m| m.name.contains("gemini"))
             .map(|m| {
                 let id = m.name.replace("models/", "");
-                ModelInfo {
-                    id: id.clone(),
-                    name: if m.display_name.is_empty() {
-                        id
-                    } else {
-                        m.display_name
-                    },
-                    description: m.description,
-                    context_window: m.input_token_limit,
-                    max_output: m.output_token_limit,
-                    input_price: 0.0,
-                    output_price: 0.0,
-                    capabilities: vec!["tools".to_string(), "vision".to_string()],
+                let mut info = known_model(&id)
+                    .with_description(m.description)
+                    .with_capabilities(&["tools", "vision"]);
+                if !m.display_name.is_empty() {
+                    info.name = m.display_name;
+                }
+                // Limits reported by the API win over the static table.
+                if m.input_token_limit > 0 {
+                    info.context_window = Some(m.input_token_limit);
                 }
+                if m.output_token_limit > 0 {
+                    info.max_output = Some(m.output_token_limit);
+                }
+                info
             })
             .collect();
 
@@ -310,6 +338,11 @@ impl Provider for GoogleProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<ChatResponse> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
         let options = options.unwrap_or_default();
         let (system_instruction, contents) = self.convert_messages(messages)?;
 
@@ -328,20 +361,19 @@ impl Provider for GoogleProvider {
 
         debug!("Sending request to Google: model={}", model);
 
-        let response = self
-            .client
-            .post(format!(
-                "{}/models/{}:generateContent?key={}",
-                self.api_base,
-                model,
-                self.api_key.expose_secret()
-            ))
-            .json(&request)
-            .send()
-            .await?;
+        let api_key = self.api_key.expose_secret();
+        let url = format!(
+            "{}/models/{}:generateContent?key={}",
+            self.api_base, model, api_key
+        );
+        let started = self.wire_trace.request("google", &url, model, messages, &request, api_key);
+
+        let response = self.client.post(&url).json(&request).send().await?;
 
         let status = response.status();
         if !status.is_success() {
+            self.wire_trace.response("google", status.as_u16(), None, started);
+            let headers = response.headers().clone();
             let error_body: GeminiError = response.json().await.unwrap_or_else(|_| GeminiError {
                 error: GeminiErrorDetail {
                     code: status.as_u16() as i32,
@@ -350,19 +382,19 @@ impl Provider for GoogleProvider {
                 },
             });
 
-            return match status.as_u16() {
-                401 | 403 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
         }
 
-        let response: GeminiResponse = response.json().await?;
-        self.parse_response(response, model)
+        let body = response.text().await?;
+        self.wire_trace.response_body("google", &body, api_key);
+        let response = self.parse_response(serde_json::from_str::<GeminiResponse>(&body)?, model)?;
+        self.wire_trace
+            .response("google", status.as_u16(), Some(&response.usage), started);
+        Ok(response)
     }
 
     async fn chat_stream(
@@ -371,6 +403,11 @@ impl Provider for GoogleProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<CompletionStream> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
         let options = options.unwrap_or_default();
         let (system_instruction, contents) = self.convert_messages(messages)?;
 
@@ -387,20 +424,19 @@ impl Provider for GoogleProvider {
             tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
         };
 
-        let response = self
-            .client
-            .post(format!(
-                "{}/models/{}:streamGenerateContent?key={}&alt=sse",
-                self.api_base,
-                model,
-                self.api_key.expose_secret()
-            ))
-            .json(&request)
-            .send()
-            .await?;
+        let api_key = self.api_key.expose_secret();
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?key={}&alt=sse",
+            self.api_base, model, api_key
+        );
+        let started = self.wire_trace.request("google", &url, model, messages, &request, api_key);
+
+        let response = self.client.post(&url).json(&request).send().await?;
 
         let status = response.status();
+        self.wire_trace.response("google", status.as_u16(), None, started);
         if !status.is_success() {
+            let headers = response.headers().clone();
             let error_body: GeminiError = response.json().await.unwrap_or_else(|_| GeminiError {
                 error: GeminiErrorDetail {
                     code: status.as_u16() as i32,
@@ -409,72 +445,36 @@ impl Provider for GoogleProvider {
                 },
             });
 
-            return match status.as_u16() {
-                401 | 403 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
         }
 
-        let model = model.to_string();
-        let byte_stream = response.bytes_stream();
+        let byte_stream = decode_utf8(response.bytes_stream());
         let event_stream = byte_stream.eventsource();
 
-        let stream = event_stream.filter_map(move |result| {
-            let _model = model.clone();
-            async move {
-                match result {
-                    Ok(event) => {
-                        if event.data.is_empty() {
-                            return None;
-                        }
+        let stream = event_stream
+            .map(|result| match result {
+                Ok(event) => {
+                    if event.data.is_empty() {
+                        return Vec::new();
+                    }
 
-                        let parsed: std::result::Result<GeminiStreamChunk, _> =
-                            serde_json::from_str(&event.data);
-
-                        match parsed {
-                            Ok(chunk) => {
-                                if let Some(candidate) = chunk.candidates.into_iter().next() {
-                                    for part in candidate.content.parts {
-                                        if let GeminiPart::Text { text } = part {
-                                            return Some(Ok(StreamEvent::ContentDelta {
-                                                delta: text,
-                                            }));
-                                        }
-                                    }
-
-                                    if let Some(finish_reason) = candidate.finish_reason {
-                                        let stop_reason = match finish_reason.as_str() {
-                                            "STOP" => StopReason::EndTurn,
-                                            "MAX_TOKENS" => StopReason::MaxTokens,
-                                            "SAFETY" => StopReason::ContentFilter,
-                                            _ => StopReason::Unknown,
-                                        };
-
-                                        return Some(Ok(StreamEvent::End {
-                                            stop_reason,
-                                            usage: Usage::default(),
-                                        }));
-                                    }
-                                }
-                                None
-                            }
-                            Err(e) => {
-                                warn!("Failed to parse SSE event: {}", e);
-                                None
-                            }
+                    match serde_json::from_str::<GeminiStreamChunk>(&event.data) {
+                        Ok(chunk) => stream_chunk_events(chunk).into_iter().map(Ok).collect(),
+                        Err(e) => {
+                            warn!("Failed to parse SSE event: {}", e);
+                            Vec::new()
                         }
                     }
-                    Err(e) => Some(Err(ProviderError::stream(e.to_string()))),
                 }
-            }
-        });
+                Err(e) => vec![Err(ProviderError::stream(e.to_string()))],
+            })
+            .flat_map(futures::stream::iter);
 
-        Ok(Box::pin(stream))
+        Ok(assemble_tool_calls(Box::pin(stream)))
     }
 
     async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount> {
@@ -529,6 +529,39 @@ impl Provider for GoogleProvider {
     }
 }
 
+/// Model info for a Gemini model, with published limits and pricing filled
+/// in for well-known models.
+fn known_model(id: &str) -> ModelInfo {
+    let info = ModelInfo::new(id, id);
+    match id {
+        "gemini-2.5-pro" => info
+            .with_limits(1_048_576, 65_536)
+            .with_support(true, true)
+            .with_pricing(1.25, 10.0),
+        "gemini-2.5-flash" => info
+            .with_limits(1_048_576, 65_536)
+            .with_support(true, true)
+            .with_pricing(0.30, 2.50),
+        "gemini-2.0-flash" | "gemini-2.0-flash-001" => info
+            .with_limits(1_048_576, 8_192)
+            .with_support(true, true)
+            .with_pricing(0.10, 0.40),
+        "gemini-2.0-flash-lite" | "gemini-2.0-flash-lite-001" => info
+            .with_limits(1_048_576, 8_192)
+            .with_support(true, true)
+            .with_pricing(0.075, 0.30),
+        "gemini-1.5-pro" | "gemini-1.5-pro-002" => info
+            .with_limits(2_097_152, 8_192)
+            .with_support(true, true)
+            .with_pricing(1.25, 5.0),
+        "gemini-1.5-flash" | "gemini-1.5-flash-002" => info
+            .with_limits(1_048_576, 8_192)
+            .with_support(true, true)
+            .with_pricing(0.075, 0.30),
+        _ => info,
+    }
+}
+
 // Internal types for Gemini API
 
 #[derive(Serialize)]
@@ -556,10 +589,21 @@ struct GeminiSystemInstruction {
 #[derive(Serialize, Deserialize)]
 #[serde(untagged)]
 enum GeminiPart {
-    Text { text: String },
-    InlineData { inline_data: InlineData },
-    FunctionCall { function_call: GeminiFunctionCall },
-    FunctionResponse { function_response: GeminiFunctionResponse },
+    Text {
+        text: String,
+    },
+    InlineData {
+        #[serde(alias = "inlineData")]
+        inline_data: InlineData,
+    },
+    FunctionCall {
+        #[serde(alias = "functionCall")]
+        function_call: GeminiFunctionCall,
+    },
+    FunctionResponse {
+        #[serde(alias = "functionResponse")]
+        function_response: GeminiFunctionResponse,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -574,6 +618,13 @@ struct GeminiFunctionCall {
     args: serde_json::Value,
 }
 
+impl GeminiFunctionCall {
+    /// Convert to a tool call; Gemini does not assign call IDs.
+    fn into_tool_call(self) -> ToolCall {
+        ToolCall::new(uuid::Uuid::new_v4().to_string(), self.name, self.args)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct GeminiFunctionResponse {
     name: String,
@@ -649,6 +700,42 @@ struct GeminiStreamChunk {
     candidates: Vec<GeminiCandidate>,
 }
 
+/// Convert a streamed chunk into provider-neutral events.
+///
+/// Gemini sends each function call whole, so calls are emitted directly.
+fn stream_chunk_events(chunk: GeminiStreamChunk) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let Some(candidate) = chunk.candidates.into_iter().next() else {
+        return events;
+    };
+
+    for part in candidate.content.parts {
+        match part {
+            GeminiPart::Text { text } => events.push(StreamEvent::ContentDelta { delta: text }),
+            GeminiPart::FunctionCall { function_call } => {
+                events.push(StreamEvent::ToolCall(function_call.into_tool_call()));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(finish_reason) = candidate.finish_reason {
+        let stop_reason = match finish_reason.as_str() {
+            "STOP" => StopReason::EndTurn,
+            "MAX_TOKENS" => StopReason::MaxTokens,
+            "SAFETY" => StopReason::ContentFilter,
+            _ => StopReason::Unknown,
+        };
+
+        events.push(StreamEvent::End {
+            stop_reason,
+            usage: Usage::default(),
+        });
+    }
+
+    events
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -675,4 +762,176 @@ mod tests {
         assert!(caps.vision);
         assert_eq!(caps.max_context, Some(2_000_000));
     }
+
+    #[tokio::test]
+    async fn test_list_models_metadata() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(query_param("key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "models": [
+                    {
+                        "name": "models/gemini-2.0-flash",
+                        "displayName": "Gemini 2.0 Flash",
+                        "inputTokenLimit": 1_048_576,
+                        "outputTokenLimit": 8_192
+                    },
+                    {"name": "models/gemini-exp-1206"},
+                    {"name": "models/embedding-001"}
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = GoogleProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(models.len(), 2);
+
+        let flash = &models[0];
+        assert_eq!(flash.id, "gemini-2.0-flash");
+        assert_eq!(flash.name, "Gemini 2.0 Flash");
+        assert_eq!(flash.context_window, Some(1_048_576));
+        assert_eq!(flash.max_output, Some(8_192));
+        assert_eq!(flash.supports_tools, Some(true));
+        assert_eq!(flash.supports_vision, Some(true));
+        assert_eq!(flash.input_price, Some(0.10));
+        assert_eq!(flash.output_price, Some(0.40));
+
+        let unknown = &models[1];
+        assert_eq!(unknown.name, "gemini-exp-1206");
+        assert_eq!(unknown.context_window, None);
+        assert_eq!(unknown.input_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_wire_trace_redacts_query_key() {
+        use crate::trace::LogCapture;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/models/gemini-2.0-flash:generateContent"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "candidates": [{
+                    "content": {"role": "model", "parts": [{"text": "Hi"}]},
+                    "finishReason": "STOP"
+                }],
+                "usageMetadata": {"promptTokenCount": 3, "candidatesTokenCount": 1}
+            })))
+            .mount(&server)
+            .await;
+
+        let api_key = "AIza-trace-secret-0123456789";
+        let logs = LogCapture::default();
+        let provider = GoogleProvider::new(api_key)
+            .unwrap()
+            .with_base_url(server.uri())
+            .with_wire_trace(WireTrace::metadata());
+        {
+            let _guard = logs.install();
+            provider
+                .chat("gemini-2.0-flash", &[Message::user("Hello")], None)
+                .await
+                .unwrap();
+        }
+
+        let output = logs.contents();
+        assert!(output.contains("key=[REDACTED]"), "{}", output);
+        assert!(output.contains("output_tokens=1"), "{}", output);
+        assert!(!output.contains(api_key), "{}", output);
+    }
+
+    #[test]
+    fn test_parse_function_call_response() {
+        let provider = GoogleProvider::new("test-key").unwrap();
+        let raw = serde_json::json!({
+            "candidates": [{
+                "content": {
+                    "role": "model",
+                    "parts": [{
+                        "functionCall": {
+                            "name": "get_weather",
+                            "args": {"city": "Paris"}
+                        }
+                    }]
+                },
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 12, "candidatesTokenCount": 5}
+        });
+
+        let response = provider
+            .parse_response(serde_json::from_value(raw).unwrap(), "gemini-2.0-flash")
+            .unwrap();
+
+        assert_eq!(response.tool_calls.len(), 1);
+        let call = &response.tool_calls[0];
+        assert!(!call.id.is_empty());
+        assert_eq!(call.name, "get_weather");
+        assert_eq!(call.arguments, serde_json::json!({"city": "Paris"}));
+    }
+
+    #[tokio::test]
+    async fn test_stream_function_call() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Checking.\"}]}}]}\n\n",
+            "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"functionCall\":{\"name\":\"get_weather\",\"args\":{\"city\":\"Paris\"}}}]},\"finishReason\":\"STOP\"}]}\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/models/gemini-2.0-flash:streamGenerateContent"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = GoogleProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let events: Vec<_> = provider
+            .chat_stream("gemini-2.0-flash", &[Message::user("Weather?")], None)
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        let calls: Vec<_> = events
+            .into_iter()
+            .filter_map(|e| match e.unwrap() {
+                StreamEvent::ToolCall(call) => Some(call),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "get_weather");
+        assert_eq!(calls[0].arguments, serde_json::json!({"city": "Paris"}));
+    }
+
+    #[test]
+    fn test_tool_result_uses_function_name() {
+        let provider = GoogleProvider::new("test-key").unwrap();
+        let call = ToolCall::new("call-1", "get_weather", serde_json::json!({"city": "Paris"}));
+        let messages = vec![
+            Message::assistant_tool_calls("", std::slice::from_ref(&call)),
+            call.result_message("sunny"),
+        ];
+
+        let (_, contents) = provider.convert_messages(&messages).unwrap();
+        let json = serde_json::to_value(&contents).unwrap();
+        assert_eq!(json[0]["parts"][0]["function_call"]["name"], "get_weather");
+        assert_eq!(json[1]["parts"][0]["function_response"]["name"], "get_weather");
+    }
 }
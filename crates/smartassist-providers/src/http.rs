@@ -0,0 +1,187 @@
+//! Shared HTTP client for providers.
+//!
+//! A [`reqwest::Client`] keeps a connection pool, and clones share it. Build
+//! one client from an [`HttpClientConfig`] and hand clones to each provider
+//! with `with_http_client`, so timeouts, proxy, and TLS settings apply to
+//! every provider alike. Providers created without one use
+//! [`shared_client`].
+
+use crate::{ProviderError, Result};
+use reqwest::Client;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Default time allowed for a whole request, including a streamed reply.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Default time allowed to establish a connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default `User-Agent` header.
+pub const DEFAULT_USER_AGENT: &str = concat!("smartassist/", env!("CARGO_PKG_VERSION"));
+
+/// Settings for the HTTP client providers use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    timeout: Duration,
+    connect_timeout: Duration,
+    proxy: Option<String>,
+    user_agent: String,
+    accept_invalid_certs: bool,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_REQUEST_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            proxy: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// Set the time allowed for a whole request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the time allowed to establish a connection.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Send all requests through a proxy, e.g. `http://proxy.internal:3128`.
+    pub fn with_proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Set the `User-Agent` header.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Accept TLS certificates that fail verification.
+    ///
+    /// Only for self-hosted servers with self-signed certificates; anyone
+    /// on the network path can read and alter the traffic.
+    pub fn danger_accept_invalid_certs(mut self) -> Self {
+        self.accept_invalid_certs = true;
+        self
+    }
+
+    /// Request timeout.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Proxy URL, if any.
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    /// Build a client with these settings.
+    pub fn build(&self) -> Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .user_agent(&self.user_agent)
+            .danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| ProviderError::config(format!("Invalid proxy '{}': {}", proxy, e)))?;
+            builder = builder.proxy(proxy);
+        }
+        builder
+            .build()
+            .map_err(|e| ProviderError::config(format!("Failed to create HTTP client: {}", e)))
+    }
+}
+
+/// Process-wide client with the default settings.
+///
+/// Providers created without an explicit client share this one, and with
+/// it their connection pool.
+pub fn shared_client() -> Result<Client> {
+    static SHARED: OnceLock<Client> = OnceLock::new();
+
+    if let Some(client) = SHARED.get() {
+        return Ok(client.clone());
+    }
+    let client = HttpClientConfig::default().build()?;
+    Ok(SHARED.get_or_init(|| client).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_user_agent_is_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/ping"))
+            .and(header("user-agent", "smartassist-test/1.0"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = HttpClientConfig::default()
+            .with_user_agent("smartassist-test/1.0")
+            .build()
+            .unwrap();
+        let response = client.get(format!("{}/ping", server.uri())).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_default_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", DEFAULT_USER_AGENT))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let response = shared_client().unwrap().get(server.uri()).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_applied() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let client = HttpClientConfig::default()
+            .with_timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let start = std::time::Instant::now();
+        let err = client.get(server.uri()).send().await.unwrap_err();
+
+        assert!(err.is_timeout(), "{}", err);
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_config_error() {
+        let err = HttpClientConfig::default().with_proxy("not a url").build().unwrap_err();
+        assert!(matches!(err, ProviderError::Config(_)), "{}", err);
+        assert!(HttpClientConfig::default()
+            .with_proxy("http://127.0.0.1:3128")
+            .build()
+            .is_ok());
+    }
+}
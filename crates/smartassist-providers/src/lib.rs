@@ -4,6 +4,8 @@
 //! - Anthropic (Claude models)
 //! - OpenAI (GPT models)
 //! - Google (Gemini models)
+//! - DeepSeek (OpenAI-compatible)
+//! - Moonshot (Kimi models, OpenAI-compatible)
 //!
 //! # Example
 //!
@@ -25,7 +27,13 @@
 //! }
 //! ```
 
+mod cancel;
 mod error;
+mod http;
+mod stats;
+mod stream;
+mod template;
+mod trace;
 mod types;
 
 #[cfg(feature = "anthropic")]
@@ -37,12 +45,30 @@ pub mod openai;
 #[cfg(feature = "google")]
 pub mod google;
 
+#[cfg(feature = "deepseek")]
+pub mod deepseek;
+
+#[cfg(feature = "moonshot")]
+pub mod moonshot;
+
+pub use cancel::{cancellable, CancellationToken};
 pub use error::{ProviderError, Result};
+pub use http::{
+    shared_client, HttpClientConfig, DEFAULT_CONNECT_TIMEOUT, DEFAULT_REQUEST_TIMEOUT,
+    DEFAULT_USER_AGENT,
+};
+pub use stats::{with_stats, StatsReceiver, StreamStats};
+pub use stream::{assemble_tool_calls, StreamAccumulator};
+pub use template::{
+    fill_template, render_messages, render_template, FilledTemplate, TemplateVars,
+};
+pub use trace::{redact_url, WireTrace, TRACE_TARGET};
 pub use types::*;
 
 use async_trait::async_trait;
 use futures::Stream;
 use std::pin::Pin;
+use std::time::{Duration, Instant};
 
 /// Stream of completion events for streaming responses.
 pub type CompletionStream = Pin<Box<dyn Stream<Item = Result<StreamEvent>> + Send>>;
@@ -56,6 +82,22 @@ pub trait Provider: Send + Sync {
     /// List available models.
     async fn list_models(&self) -> Result<Vec<ModelInfo>>;
 
+    /// Check that the provider is reachable.
+    ///
+    /// Defaults to listing models, failing with [`ProviderError::Timeout`]
+    /// after [`HEALTH_CHECK_TIMEOUT`]. Providers with a cheaper endpoint can
+    /// override this.
+    async fn health_check(&self) -> Result<ProviderHealth> {
+        let start = Instant::now();
+        let models = tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.list_models())
+            .await
+            .map_err(|_| ProviderError::Timeout(HEALTH_CHECK_TIMEOUT.as_secs()))??;
+        Ok(ProviderHealth {
+            latency: start.elapsed(),
+            models: Some(models.len()),
+        })
+    }
+
     /// Check if a model is available.
     async fn is_model_available(&self, model: &str) -> Result<bool> {
         let models = self.list_models().await?;
@@ -71,6 +113,9 @@ pub trait Provider: Send + Sync {
     ) -> Result<ChatResponse>;
 
     /// Generate a streaming chat completion.
+    ///
+    /// Tool calls are reported as [`StreamEvent::ToolCall`] once their
+    /// arguments are complete.
     async fn chat_stream(
         &self,
         model: &str,
@@ -78,11 +123,158 @@ pub trait Provider: Send + Sync {
         options: Option<ChatOptions>,
     ) -> Result<CompletionStream>;
 
+    /// Generate a streaming chat completion that can be aborted.
+    ///
+    /// Cancelling `cancel` ends the stream without further events and drops
+    /// the underlying HTTP request. If cancelled before the provider responds,
+    /// an empty stream is returned.
+    async fn chat_stream_with_cancel(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+        cancel: CancellationToken,
+    ) -> Result<CompletionStream> {
+        let cancelled = std::pin::pin!(cancel.cancelled());
+        let request = self.chat_stream(model, messages, options);
+        match futures::future::select(cancelled, request).await {
+            futures::future::Either::Left(_) => Ok(Box::pin(futures::stream::empty())),
+            futures::future::Either::Right((stream, _)) => Ok(cancellable(stream?, cancel.clone())),
+        }
+    }
+
+    /// Generate a streaming chat completion and measure its timing.
+    ///
+    /// The receiver gets the [`StreamStats`] once the stream ends or is
+    /// dropped. Latencies are measured from this call.
+    async fn chat_stream_with_stats(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<(CompletionStream, StatsReceiver)> {
+        let started = Instant::now();
+        let stream = self.chat_stream(model, messages, options).await?;
+        Ok(with_stats(stream, started))
+    }
+
     /// Count tokens in a message.
     async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount>;
 
     /// Get model capabilities.
     fn capabilities(&self) -> ProviderCapabilities;
+
+    /// Get capabilities for a specific model.
+    ///
+    /// Defaults to the provider-wide capabilities; providers override this
+    /// when some of their models lack a capability such as vision.
+    fn model_capabilities(&self, _model: &str) -> ProviderCapabilities {
+        self.capabilities()
+    }
+
+    /// Reject messages with image content if the model lacks vision support.
+    fn ensure_vision_supported(&self, model: &str, messages: &[Message]) -> Result<()> {
+        if !self.model_capabilities(model).vision && messages.iter().any(Message::has_images) {
+            return Err(ProviderError::unsupported(format!(
+                "model {} does not accept image input",
+                model
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check whether a request fits the model's context window.
+    ///
+    /// Counts the prompt with [`Provider::count_tokens`] and reserves
+    /// `options.max_tokens` for the response. Models without a known
+    /// `max_context` always fit.
+    async fn fits_context(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<&ChatOptions>,
+    ) -> Result<bool> {
+        let Some(limit) = self.model_capabilities(model).max_context else {
+            return Ok(true);
+        };
+        let reserved = options.and_then(|o| o.max_tokens).unwrap_or(0);
+        let used = self.count_tokens(model, messages).await?.count + reserved;
+        Ok(used <= limit)
+    }
+
+    /// Reject a request that would overflow the model's context window.
+    ///
+    /// Called by `chat` and `chat_stream` before sending. Requests whose text
+    /// is smaller than the limit in bytes cannot overflow it and skip the
+    /// token count; if counting fails the request is sent as is.
+    async fn ensure_fits_context(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<&ChatOptions>,
+    ) -> Result<()> {
+        let Some(limit) = self.model_capabilities(model).max_context else {
+            return Ok(());
+        };
+        let reserved = options.and_then(|o| o.max_tokens).unwrap_or(0);
+        if token_upper_bound(messages) + reserved <= limit {
+            return Ok(());
+        }
+
+        match self.count_tokens(model, messages).await {
+            Ok(count) if count.count + reserved > limit => {
+                Err(ProviderError::context_exceeded(count.count + reserved, limit))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::debug!("skipping context pre-flight check: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Tokens reserved per message for role and formatting overhead.
+const MESSAGE_TOKEN_OVERHEAD: usize = 8;
+
+/// Upper bound on the tokens needed for the text of `messages`.
+///
+/// Every token covers at least one byte of text, so the byte length bounds
+/// the token count from above.
+fn token_upper_bound(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|message| {
+            let text = match &message.content {
+                MessageContent::Text(text) => text.len(),
+                MessageContent::Parts(parts) => parts
+                    .iter()
+                    .map(|part| match part {
+                        ContentPart::Text(text) => text.len(),
+                        ContentPart::Image(_) => 0,
+                        ContentPart::ToolUse(call) => {
+                            call.name.len() + call.arguments.to_string().len()
+                        }
+                        ContentPart::ToolResult(result) => result.content.len(),
+                    })
+                    .sum(),
+            };
+            text + MESSAGE_TOKEN_OVERHEAD
+        })
+        .sum()
+}
+
+/// Time limit for the default [`Provider::health_check`].
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Result of a successful provider health check.
+#[derive(Debug, Clone)]
+pub struct ProviderHealth {
+    /// Time the provider took to answer.
+    pub latency: Duration,
+
+    /// Number of models the provider listed, if the check lists them.
+    pub models: Option<usize>,
 }
 
 /// Provider capabilities.
@@ -110,6 +302,251 @@ pub struct ProviderCapabilities {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Provider that turns each chunk of a chunked HTTP response into a text delta.
+    struct ChunkProvider {
+        url: String,
+    }
+
+    #[async_trait]
+    impl Provider for ChunkProvider {
+        fn name(&self) -> &str {
+            "chunk"
+        }
+
+        async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn chat(
+            &self,
+            _model: &str,
+            _messages: &[Message],
+            _options: Option<ChatOptions>,
+        ) -> Result<ChatResponse> {
+            Err(ProviderError::unsupported("chat"))
+        }
+
+        async fn chat_stream(
+            &self,
+            _model: &str,
+            _messages: &[Message],
+            _options: Option<ChatOptions>,
+        ) -> Result<CompletionStream> {
+            let response = reqwest::get(&self.url).await?;
+            let stream = response.bytes_stream().map(|chunk| {
+                let chunk = chunk?;
+                Ok(StreamEvent::ContentDelta {
+                    delta: String::from_utf8_lossy(&chunk).into_owned(),
+                })
+            });
+            Ok(Box::pin(stream))
+        }
+
+        async fn count_tokens(&self, model: &str, _messages: &[Message]) -> Result<TokenCount> {
+            Ok(TokenCount {
+                count: 0,
+                model: model.to_string(),
+            })
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities::default()
+        }
+    }
+
+    /// Serve an endless chunked response, reporting when the client hangs up.
+    async fn endless_server() -> (String, tokio::sync::oneshot::Receiver<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/stream", listener.local_addr().unwrap());
+        let (closed_tx, closed_rx) = tokio::sync::oneshot::channel();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+
+            let headers = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n";
+            socket.write_all(headers.as_bytes()).await.unwrap();
+
+            loop {
+                if socket.write_all(b"5\r\nhello\r\n").await.is_err() {
+                    break;
+                }
+                if socket.flush().await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            let _ = closed_tx.send(());
+        });
+
+        (url, closed_rx)
+    }
+
+    /// Provider with a tiny context window that counts one token per word.
+    struct TinyContextProvider {
+        count_calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl TinyContextProvider {
+        const LIMIT: usize = 50;
+
+        fn new() -> Self {
+            Self {
+                count_calls: std::sync::atomic::AtomicUsize::new(0),
+            }
+        }
+
+        fn count_calls(&self) -> usize {
+            self.count_calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for TinyContextProvider {
+        fn name(&self) -> &str {
+            "tiny"
+        }
+
+        async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+            Ok(Vec::new())
+        }
+
+        async fn chat(
+            &self,
+            model: &str,
+            messages: &[Message],
+            options: Option<ChatOptions>,
+        ) -> Result<ChatResponse> {
+            self.ensure_fits_context(model, messages, options.as_ref()).await?;
+            Ok(ChatResponse {
+                id: "resp".to_string(),
+                model: model.to_string(),
+                content: "ok".to_string(),
+                tool_calls: Vec::new(),
+                stop_reason: StopReason::EndTurn,
+                usage: Usage::default(),
+                metadata: Default::default(),
+            })
+        }
+
+        async fn chat_stream(
+            &self,
+            _model: &str,
+            _messages: &[Message],
+            _options: Option<ChatOptions>,
+        ) -> Result<CompletionStream> {
+            Err(ProviderError::unsupported("chat_stream"))
+        }
+
+        async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount> {
+            self.count_calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let count = messages
+                .iter()
+                .filter_map(|m| m.text())
+                .map(|t| t.split_whitespace().count())
+                .sum();
+            Ok(TokenCount {
+                count,
+                model: model.to_string(),
+            })
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            ProviderCapabilities {
+                max_context: Some(Self::LIMIT),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fits_context() {
+        let provider = TinyContextProvider::new();
+        let small = vec![Message::user("hello there")];
+        let oversized = vec![Message::user("word ".repeat(60))];
+
+        assert!(provider.fits_context("tiny", &small, None).await.unwrap());
+        assert!(!provider.fits_context("tiny", &oversized, None).await.unwrap());
+
+        // The requested output budget counts against the window.
+        let options = ChatOptions::with_max_tokens(49);
+        assert!(!provider
+            .fits_context("tiny", &small, Some(&options))
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_oversized_request() {
+        let provider = TinyContextProvider::new();
+        let oversized = vec![Message::user("word ".repeat(60))];
+
+        let result = provider.chat("tiny", &oversized, None).await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::ContextLengthExceeded { used: 60, max: 50 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_small_request_skips_token_count() {
+        let provider = TinyContextProvider::new();
+        let response = provider
+            .chat("tiny", &[Message::user("hi")], None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "ok");
+        assert_eq!(provider.count_calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_cancel_drops_connection() {
+        let (url, closed) = endless_server().await;
+        let provider = ChunkProvider { url };
+        let token = CancellationToken::new();
+
+        let mut stream = provider
+            .chat_stream_with_cancel("model", &[Message::user("hi")], None, token.clone())
+            .await
+            .unwrap();
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert!(matches!(first, StreamEvent::ContentDelta { ref delta } if delta == "hello"));
+
+        token.cancel();
+        assert!(stream.next().await.is_none());
+        assert!(stream.next().await.is_none());
+
+        // The stream itself is still alive, so the server noticing the hang-up
+        // proves the HTTP response was dropped on cancellation.
+        tokio::time::timeout(Duration::from_secs(5), closed)
+            .await
+            .expect("connection should be closed after cancellation")
+            .unwrap();
+        drop(stream);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_cancelled_before_response() {
+        let provider = ChunkProvider {
+            url: "http://127.0.0.1:9/unreachable".to_string(),
+        };
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let mut stream = provider
+            .chat_stream_with_cancel("model", &[Message::user("hi")], None, token)
+            .await
+            .unwrap();
+        assert!(stream.next().await.is_none());
+    }
 
     #[test]
     fn test_provider_capabilities() {
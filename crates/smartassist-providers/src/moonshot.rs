@@ -0,0 +1,279 @@
+//! Moonshot AI (Kimi) provider implementation.
+//!
+//! Moonshot serves an OpenAI-compatible chat completions API, so requests
+//! are built and parsed by the [`OpenAIProvider`] against Moonshot's base URL.
+
+use crate::openai::OpenAIProvider;
+use crate::{
+    render_messages, ChatOptions, ChatResponse, CompletionStream, Message, ModelDefaults,
+    ModelInfo, Provider, ProviderCapabilities, ProviderError, Result, TokenCount, WireTrace,
+};
+use async_trait::async_trait;
+
+/// Default Moonshot API base URL.
+const DEFAULT_API_BASE: &str = "https://api.moonshot.cn/v1";
+
+/// Models served by Moonshot with their context windows.
+const MODELS: [(&str, usize); 3] = [
+    ("moonshot-v1-8k", 8_192),
+    ("moonshot-v1-32k", 32_768),
+    ("moonshot-v1-128k", 131_072),
+];
+
+/// Maximum output tokens for the Moonshot models.
+const MAX_OUTPUT: usize = 4_096;
+
+/// Moonshot provider.
+pub struct MoonshotProvider {
+    /// OpenAI-compatible client pointed at the Moonshot API.
+    inner: OpenAIProvider,
+}
+
+impl MoonshotProvider {
+    /// Create a new Moonshot provider with an API key.
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        let inner = OpenAIProvider::new(api_key)?
+            .with_base_url(DEFAULT_API_BASE)
+            .with_default_model("moonshot-v1-8k");
+        Ok(Self { inner })
+    }
+
+    /// Create a new provider from environment variable.
+    pub fn from_env() -> Result<Self> {
+        let api_key = std::env::var("MOONSHOT_API_KEY")
+            .map_err(|_| ProviderError::config("MOONSHOT_API_KEY environment variable not set"))?;
+        Self::new(api_key)
+    }
+
+    /// Set the API base URL (e.g. `https://api.moonshot.ai/v1` outside China).
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.inner = self.inner.with_base_url(url);
+        self
+    }
+
+    /// Use `client` for requests; see [`OpenAIProvider::with_http_client`].
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.inner = self.inner.with_http_client(client);
+        self
+    }
+
+    /// Set the default model.
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.inner = self.inner.with_default_model(model);
+        self
+    }
+
+    /// Enable request and response tracing.
+    pub fn with_wire_trace(mut self, trace: WireTrace) -> Self {
+        self.inner = self.inner.with_wire_trace(trace);
+        self
+    }
+
+    /// Set per-model sampling defaults, used for options a request leaves
+    /// unset.
+    pub fn with_model_defaults(mut self, defaults: ModelDefaults) -> Self {
+        self.inner = self.inner.with_model_defaults(defaults);
+        self
+    }
+}
+
+#[async_trait]
+impl Provider for MoonshotProvider {
+    fn name(&self) -> &str {
+        "moonshot"
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        Ok(MODELS
+            .into_iter()
+            // Moonshot only publishes CNY pricing, so prices stay unknown.
+            .map(|(id, context_window)| {
+                ModelInfo::new(id, id)
+                    .with_limits(context_window, MAX_OUTPUT)
+                    .with_support(true, false)
+                    .with_capabilities(&["tools"])
+            })
+            .collect())
+    }
+
+    async fn chat(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<ChatResponse> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.inner.model_defaults().apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.inner.send_chat(model, messages, options).await
+    }
+
+    async fn chat_stream(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<CompletionStream> {
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.inner.model_defaults().apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.inner.send_chat_stream(model, messages, options).await
+    }
+
+    async fn count_tokens(&self, model: &str, messages: &[Message]) -> Result<TokenCount> {
+        self.inner.count_tokens(model, messages).await
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            streaming: true,
+            tools: true,
+            vision: false,
+            system_messages: true,
+            max_context: Some(131_072),
+            max_output: Some(MAX_OUTPUT),
+        }
+    }
+
+    fn model_capabilities(&self, model: &str) -> ProviderCapabilities {
+        let caps = self.capabilities();
+        match MODELS.iter().find(|(id, _)| *id == model) {
+            Some(&(_, context_window)) => ProviderCapabilities {
+                max_context: Some(context_window),
+                ..caps
+            },
+            None => caps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_provider_creation() {
+        let provider = MoonshotProvider::new("test-key").unwrap();
+        assert_eq!(provider.name(), "moonshot");
+        assert!(MoonshotProvider::new("").is_err());
+    }
+
+    #[test]
+    fn test_model_capabilities() {
+        let provider = MoonshotProvider::new("test-key").unwrap();
+
+        assert!(!provider.capabilities().vision);
+        assert_eq!(provider.model_capabilities("moonshot-v1-8k").max_context, Some(8_192));
+        assert_eq!(provider.model_capabilities("moonshot-v1-32k").max_context, Some(32_768));
+        assert_eq!(provider.model_capabilities("moonshot-v1-128k").max_context, Some(131_072));
+    }
+
+    #[tokio::test]
+    async fn test_list_models() {
+        let provider = MoonshotProvider::new("test-key").unwrap();
+        let models = provider.list_models().await.unwrap();
+
+        let ids: Vec<_> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["moonshot-v1-8k", "moonshot-v1-32k", "moonshot-v1-128k"]);
+        assert_eq!(models[2].context_window, Some(131_072));
+        assert_eq!(models[2].max_output, Some(4_096));
+        assert_eq!(models[2].supports_tools, Some(true));
+        assert_eq!(models[2].supports_vision, Some(false));
+        assert_eq!(models[2].input_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_chat_request_serialization() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer test-key"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "moonshot-v1-8k",
+                "stream": false,
+                "temperature": 0.3,
+                "messages": [{"role": "user", "content": "Hello"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "cmpl-1",
+                "model": "moonshot-v1-8k",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Hi!"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = MoonshotProvider::new("test-key")
+            .unwrap()
+            .with_base_url(format!("{}/v1", server.uri()));
+        let options = ChatOptions {
+            temperature: Some(0.3),
+            ..Default::default()
+        };
+        let response = provider
+            .chat("moonshot-v1-8k", &[Message::user("Hello")], Some(options))
+            .await
+            .unwrap();
+
+        assert_eq!(response.content, "Hi!");
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_request() {
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Hi\"},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "moonshot-v1-32k",
+                "stream": true
+            })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = MoonshotProvider::new("test-key")
+            .unwrap()
+            .with_base_url(format!("{}/v1", server.uri()));
+        let events: Vec<_> = provider
+            .chat_stream("moonshot-v1-32k", &[Message::user("Hello")], None)
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, crate::StreamEvent::ContentDelta { delta } if delta == "Hi")));
+    }
+
+    #[tokio::test]
+    async fn test_context_window_is_per_model() {
+        let provider = MoonshotProvider::new("test-key").unwrap();
+        let messages = [Message::user("word ".repeat(10_000))];
+
+        let err = provider.chat("moonshot-v1-8k", &messages, None).await.unwrap_err();
+        assert!(matches!(err, ProviderError::ContextLengthExceeded { .. }));
+    }
+}
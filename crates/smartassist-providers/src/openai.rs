@@ -1,19 +1,24 @@
 //! OpenAI GPT provider implementation.
 //!
-//! This module provides integration with OpenAI's GPT models.
+//! This module provides integration with OpenAI's GPT models. The same
+//! client also targets any OpenAI-compatible server (vLLM, LM Studio,
+//! Ollama, ...) through [`OpenAIProvider::compatible`] or
+//! [`OpenAIProvider::with_base_url`].
 
 use crate::{
-    ChatOptions, ChatResponse, CompletionStream, Message, MessageContent, MessageRole, ModelInfo,
-    Provider, ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount,
-    ToolUse, Usage,
+    assemble_tool_calls, render_messages, shared_client, ChatOptions, ChatResponse,
+    CompletionStream, Message, MessageContent, MessageRole, ModelDefaults, ModelInfo, Provider,
+    ProviderCapabilities, ProviderError, Result, StopReason, StreamEvent, TokenCount, ToolCall,
+    Usage, WireTrace,
 };
+use crate::stream::decode_utf8;
 use async_trait::async_trait;
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use tracing::{debug, warn};
 
 /// Default OpenAI API base URL.
@@ -24,7 +29,7 @@ pub struct OpenAIProvider {
     /// HTTP client.
     client: Client,
 
-    /// API key.
+    /// API key (empty for servers that need none).
     api_key: SecretString,
 
     /// API base URL.
@@ -35,6 +40,12 @@ pub struct OpenAIProvider {
 
     /// Default model to use.
     default_model: String,
+
+    /// Wire tracing settings.
+    wire_trace: WireTrace,
+
+    /// Sampling defaults by model.
+    model_defaults: ModelDefaults,
 }
 
 impl OpenAIProvider {
@@ -44,11 +55,18 @@ impl OpenAIProvider {
         if api_key.is_empty() {
             return Err(ProviderError::config("API key is required"));
         }
+        Self::build(api_key)
+    }
 
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(300))
-            .build()
-            .map_err(|e| ProviderError::config(format!("Failed to create HTTP client: {}", e)))?;
+    /// Create a provider for an OpenAI-compatible server.
+    ///
+    /// No API key is sent unless one is set with [`Self::with_api_key`].
+    pub fn compatible(base_url: impl Into<String>) -> Result<Self> {
+        Ok(Self::build(String::new())?.with_base_url(base_url))
+    }
+
+    fn build(api_key: String) -> Result<Self> {
+        let client = shared_client()?;
 
         Ok(Self {
             client,
@@ -56,22 +74,77 @@ impl OpenAIProvider {
             api_base: DEFAULT_API_BASE.to_string(),
             organization: None,
             default_model: "gpt-4o".to_string(),
+            wire_trace: WireTrace::default(),
+            model_defaults: ModelDefaults::default(),
         })
     }
 
-    /// Create a new provider from environment variable.
+    /// Create a new provider from environment variables.
+    ///
+    /// `OPENAI_BASE_URL` points the provider at an OpenAI-compatible server,
+    /// in which case `OPENAI_API_KEY` is optional.
     pub fn from_env() -> Result<Self> {
-        let api_key = std::env::var("OPENAI_API_KEY")
-            .map_err(|_| ProviderError::config("OPENAI_API_KEY environment variable not set"))?;
-        Self::new(api_key)
+        let api_key = std::env::var("OPENAI_API_KEY").ok().filter(|k| !k.is_empty());
+        let base_url = std::env::var("OPENAI_BASE_URL").ok().filter(|u| !u.is_empty());
+
+        match (api_key, base_url) {
+            (Some(key), Some(url)) => Ok(Self::new(key)?.with_base_url(url)),
+            (Some(key), None) => Self::new(key),
+            (None, Some(url)) => Self::compatible(url),
+            (None, None) => Err(ProviderError::config(
+                "OPENAI_API_KEY environment variable not set",
+            )),
+        }
     }
 
     /// Set the API base URL (for Azure OpenAI or compatible APIs).
     pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
-        self.api_base = url.into();
+        self.api_base = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use `client` for requests, e.g. one built from an
+    /// [`HttpClientConfig`](crate::HttpClientConfig) and shared with other
+    /// providers.
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the API key.
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = SecretString::new(api_key.into());
         self
     }
 
+    /// Get the API base URL.
+    pub fn base_url(&self) -> &str {
+        &self.api_base
+    }
+
+    /// Check whether requests go to the OpenAI API itself.
+    fn is_openai(&self) -> bool {
+        self.api_base == DEFAULT_API_BASE
+    }
+
+    /// Build the authentication headers for a request.
+    fn headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let api_key = self.api_key.expose_secret();
+        if !api_key.is_empty() {
+            headers.insert(
+                "Authorization",
+                format!("Bearer {}", api_key).parse().unwrap(),
+            );
+        }
+
+        if let Some(org) = &self.organization {
+            headers.insert("OpenAI-Organization", org.parse().unwrap());
+        }
+
+        headers
+    }
+
     /// Set the organization ID.
     pub fn with_organization(mut self, org: impl Into<String>) -> Self {
         self.organization = Some(org.into());
@@ -84,6 +157,188 @@ impl OpenAIProvider {
         self
     }
 
+    /// Enable request and response tracing.
+    pub fn with_wire_trace(mut self, trace: WireTrace) -> Self {
+        self.wire_trace = trace;
+        self
+    }
+
+    /// Set per-model sampling defaults, used for options a request leaves
+    /// unset.
+    pub fn with_model_defaults(mut self, defaults: ModelDefaults) -> Self {
+        self.model_defaults = defaults;
+        self
+    }
+
+    /// Sampling defaults by model, for wrappers that send through this client.
+    pub(crate) fn model_defaults(&self) -> &ModelDefaults {
+        &self.model_defaults
+    }
+
+    /// Send a chat completion request without pre-flight checks.
+    ///
+    /// Shared with the providers that speak the OpenAI-compatible API.
+    pub(crate) async fn send_chat(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<ChatResponse> {
+        let options = options.unwrap_or_default();
+        let converted_messages = self.convert_messages(messages)?;
+
+        let request = OpenAIRequest {
+            model: model.to_string(),
+            messages: converted_messages,
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop: options.stop,
+            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
+            tool_choice: options.tool_choice.as_ref().map(|c| match c {
+                crate::ToolChoice::Auto => OpenAIToolChoice::Auto,
+                crate::ToolChoice::Any => OpenAIToolChoice::Required,
+                crate::ToolChoice::None => OpenAIToolChoice::None,
+                crate::ToolChoice::Tool { name } => OpenAIToolChoice::Function {
+                    name: name.clone(),
+                },
+            }),
+            stream: false,
+            user: options.user,
+        };
+
+        debug!("Sending request to OpenAI: model={}", model);
+
+        let headers = self.headers();
+        let url = format!("{}/chat/completions", self.api_base);
+        let api_key = self.api_key.expose_secret();
+        let started = self.wire_trace.request("openai", &url, model, messages, &request, api_key);
+
+        let response = self
+            .client
+            .post(&url)
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            self.wire_trace.response("openai", status.as_u16(), None, started);
+            let headers = response.headers().clone();
+            let error_body: OpenAIError = response.json().await.unwrap_or_else(|_| OpenAIError {
+                error: OpenAIErrorDetail {
+                    message: "Unknown error".to_string(),
+                    error_type: "unknown".to_string(),
+                    code: None,
+                },
+            });
+
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
+        }
+
+        let body = response.text().await?;
+        self.wire_trace.response_body("openai", &body, api_key);
+        let response = self.parse_response(serde_json::from_str::<OpenAIResponse>(&body)?)?;
+        self.wire_trace
+            .response("openai", status.as_u16(), Some(&response.usage), started);
+        Ok(response)
+    }
+
+    /// Send a streaming chat completion request without pre-flight checks.
+    pub(crate) async fn send_chat_stream(
+        &self,
+        model: &str,
+        messages: &[Message],
+        options: Option<ChatOptions>,
+    ) -> Result<CompletionStream> {
+        let options = options.unwrap_or_default();
+        let converted_messages = self.convert_messages(messages)?;
+
+        let request = OpenAIRequest {
+            model: model.to_string(),
+            messages: converted_messages,
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop: options.stop,
+            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
+            tool_choice: options.tool_choice.as_ref().map(|c| match c {
+                crate::ToolChoice::Auto => OpenAIToolChoice::Auto,
+                crate::ToolChoice::Any => OpenAIToolChoice::Required,
+                crate::ToolChoice::None => OpenAIToolChoice::None,
+                crate::ToolChoice::Tool { name } => OpenAIToolChoice::Function {
+                    name: name.clone(),
+                },
+            }),
+            stream: true,
+            user: options.user,
+        };
+
+        let headers = self.headers();
+        let url = format!("{}/chat/completions", self.api_base);
+        let api_key = self.api_key.expose_secret();
+        let started = self.wire_trace.request("openai", &url, model, messages, &request, api_key);
+
+        let response = self
+            .client
+            .post(&url)
+            .headers(headers)
+            .json(&request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        self.wire_trace.response("openai", status.as_u16(), None, started);
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let error_body: OpenAIError = response.json().await.unwrap_or_else(|_| OpenAIError {
+                error: OpenAIErrorDetail {
+                    message: "Unknown error".to_string(),
+                    error_type: "unknown".to_string(),
+                    code: None,
+                },
+            });
+
+            return Err(ProviderError::from_status(
+                status.as_u16(),
+                error_body.error.message,
+                &headers,
+            ));
+        }
+
+        let byte_stream = decode_utf8(response.bytes_stream());
+        let event_stream = byte_stream.eventsource();
+
+        let stream = event_stream
+            .scan(StreamedToolCalls::default(), |calls, result| {
+                let events = match result {
+                    Ok(event) if event.data == "[DONE]" => {
+                        calls.drain().into_iter().map(Ok).collect()
+                    }
+                    Ok(event) if event.data.is_empty() => Vec::new(),
+                    Ok(event) => match serde_json::from_str::<OpenAIStreamChunk>(&event.data) {
+                        Ok(chunk) => {
+                            stream_chunk_events(chunk, calls).into_iter().map(Ok).collect()
+                        }
+                        Err(e) => {
+                            warn!("Failed to parse SSE event: {}", e);
+                            Vec::new()
+                        }
+                    },
+                    Err(e) => vec![Err(ProviderError::stream(e.to_string()))],
+                };
+                futures::future::ready(Some(events))
+            })
+            .flat_map(futures::stream::iter);
+
+        Ok(assemble_tool_calls(Box::pin(stream)))
+    }
+
     /// Convert messages to OpenAI format.
     fn convert_messages(&self, messages: &[Message]) -> Result<Vec<OpenAIMessage>> {
         let mut converted = Vec::new();
@@ -96,6 +351,7 @@ impl OpenAIProvider {
                 MessageRole::Tool => "tool",
             };
 
+            let mut tool_calls = Vec::new();
             let content = match &msg.content {
                 MessageContent::Text(s) => OpenAIContent::Text(s.clone()),
                 MessageContent::Parts(parts) => {
@@ -118,6 +374,16 @@ impl OpenAIProvider {
                                     image_url: ImageUrl { url },
                                 });
                             }
+                            crate::ContentPart::ToolUse(call) => {
+                                tool_calls.push(OpenAIToolCall {
+                                    id: call.id.clone(),
+                                    call_type: "function".to_string(),
+                                    function: OpenAIFunctionCall {
+                                        name: call.name.clone(),
+                                        arguments: call.arguments.to_string(),
+                                    },
+                                });
+                            }
                             _ => {}
                         }
                     }
@@ -125,12 +391,27 @@ impl OpenAIProvider {
                 }
             };
 
+            // Assistant turns that only requested tools carry no content.
+            let content = match content {
+                OpenAIContent::Parts(parts) if parts.is_empty() && !tool_calls.is_empty() => None,
+                content => Some(content),
+            };
+
             let message = OpenAIMessage {
                 role: role.to_string(),
-                content: Some(content),
-                name: msg.name.clone(),
+                content,
+                // The tool name is only needed by providers that key results by name.
+                name: if msg.role == MessageRole::Tool {
+                    None
+                } else {
+                    msg.name.clone()
+                },
                 tool_call_id: msg.tool_call_id.clone(),
-                tool_calls: None,
+                tool_calls: if tool_calls.is_empty() {
+                    None
+                } else {
+                    Some(tool_calls)
+                },
             };
 
             converted.push(message);
@@ -182,10 +463,12 @@ impl OpenAIProvider {
             .tool_calls
             .unwrap_or_default()
             .into_iter()
-            .map(|tc| ToolUse {
-                id: tc.id,
-                name: tc.function.name,
-                input: serde_json::from_str(&tc.function.arguments).unwrap_or_default(),
+            .map(|tc| {
+                ToolCall::new(
+                    tc.id,
+                    tc.function.name,
+                    ToolCall::parse_arguments(&tc.function.arguments),
+                )
             })
             .collect();
 
@@ -221,17 +504,7 @@ impl Provider for OpenAIProvider {
     }
 
     async fn list_models(&self) -> Result<Vec<ModelInfo>> {
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", self.api_key.expose_secret())
-                .parse()
-                .unwrap(),
-        );
-
-        if let Some(org) = &self.organization {
-            headers.insert("OpenAI-Organization", org.parse().unwrap());
-        }
+        let headers = self.headers();
 
         let response = self
             .client
@@ -259,34 +532,25 @@ impl Provider for OpenAIProvider {
 
         let result: ModelsResponse = response.json().await?;
 
-        // Filter to chat models and add metadata
+        // Filter to chat models and add metadata. Compatible servers only
+        // list what they serve, so everything there is kept.
+        let is_openai = self.is_openai();
         let chat_models: Vec<ModelInfo> = result
             .data
             .into_iter()
-            .filter(|m| m.id.starts_with("gpt-") || m.id.starts_with("o1") || m.id.starts_with("o3"))
+            .filter(|m| {
+                !is_openai
+                    || m.id.starts_with("gpt-")
+                    || m.id.starts_with("o1")
+                    || m.id.starts_with("o3")
+            })
             .map(|m| {
-                let (context_window, max_output) = match m.id.as_str() {
-                    "gpt-4o" | "gpt-4o-2024-08-06" => (128_000, 16_384),
-                    "gpt-4o-mini" | "gpt-4o-mini-2024-07-18" => (128_000, 16_384),
-                    "gpt-4-turbo" | "gpt-4-turbo-2024-04-09" => (128_000, 4_096),
-                    "gpt-4" | "gpt-4-0613" => (8_192, 4_096),
-                    "gpt-3.5-turbo" => (16_385, 4_096),
-                    "o1" | "o1-2024-12-17" => (200_000, 100_000),
-                    "o1-mini" | "o1-mini-2024-09-12" => (128_000, 65_536),
-                    "o3-mini" | "o3-mini-2025-01-31" => (200_000, 100_000),
-                    _ => (128_000, 4_096),
+                let capabilities: &[&str] = if model_supports_vision(&m.id) {
+                    &["tools", "vision"]
+                } else {
+                    &["tools"]
                 };
-
-                ModelInfo {
-                    id: m.id.clone(),
-                    name: m.id.clone(),
-                    description: String::new(),
-                    context_window,
-                    max_output,
-                    input_price: 0.0,
-                    output_price: 0.0,
-                    capabilities: vec!["tools".to_string()],
-                }
+                known_model(&m.id).with_capabilities(capabilities)
             })
             .collect();
 
@@ -299,74 +563,12 @@ impl Provider for OpenAIProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<ChatResponse> {
-        let options = options.unwrap_or_default();
-        let converted_messages = self.convert_messages(messages)?;
-
-        let request = OpenAIRequest {
-            model: model.to_string(),
-            messages: converted_messages,
-            max_tokens: options.max_tokens,
-            temperature: options.temperature,
-            top_p: options.top_p,
-            stop: options.stop,
-            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
-            tool_choice: options.tool_choice.as_ref().map(|c| match c {
-                crate::ToolChoice::Auto => OpenAIToolChoice::Auto,
-                crate::ToolChoice::Any => OpenAIToolChoice::Required,
-                crate::ToolChoice::None => OpenAIToolChoice::None,
-                crate::ToolChoice::Tool { name } => OpenAIToolChoice::Function {
-                    name: name.clone(),
-                },
-            }),
-            stream: false,
-            user: options.user,
-        };
-
-        debug!("Sending request to OpenAI: model={}", model);
-
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", self.api_key.expose_secret())
-                .parse()
-                .unwrap(),
-        );
-
-        if let Some(org) = &self.organization {
-            headers.insert("OpenAI-Organization", org.parse().unwrap());
-        }
-
-        let response = self
-            .client
-            .post(format!("{}/chat/completions", self.api_base))
-            .headers(headers)
-            .json(&request)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_body: OpenAIError = response.json().await.unwrap_or_else(|_| OpenAIError {
-                error: OpenAIErrorDetail {
-                    message: "Unknown error".to_string(),
-                    error_type: "unknown".to_string(),
-                    code: None,
-                },
-            });
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
-        }
-
-        let response: OpenAIResponse = response.json().await?;
-        self.parse_response(response)
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.send_chat(model, messages, options).await
     }
 
     async fn chat_stream(
@@ -375,122 +577,12 @@ impl Provider for OpenAIProvider {
         messages: &[Message],
         options: Option<ChatOptions>,
     ) -> Result<CompletionStream> {
-        let options = options.unwrap_or_default();
-        let converted_messages = self.convert_messages(messages)?;
-
-        let request = OpenAIRequest {
-            model: model.to_string(),
-            messages: converted_messages,
-            max_tokens: options.max_tokens,
-            temperature: options.temperature,
-            top_p: options.top_p,
-            stop: options.stop,
-            tools: options.tools.as_ref().map(|t| self.convert_tools(t)),
-            tool_choice: options.tool_choice.as_ref().map(|c| match c {
-                crate::ToolChoice::Auto => OpenAIToolChoice::Auto,
-                crate::ToolChoice::Any => OpenAIToolChoice::Required,
-                crate::ToolChoice::None => OpenAIToolChoice::None,
-                crate::ToolChoice::Tool { name } => OpenAIToolChoice::Function {
-                    name: name.clone(),
-                },
-            }),
-            stream: true,
-            user: options.user,
-        };
-
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "Authorization",
-            format!("Bearer {}", self.api_key.expose_secret())
-                .parse()
-                .unwrap(),
-        );
-
-        if let Some(org) = &self.organization {
-            headers.insert("OpenAI-Organization", org.parse().unwrap());
-        }
-
-        let response = self
-            .client
-            .post(format!("{}/chat/completions", self.api_base))
-            .headers(headers)
-            .json(&request)
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let error_body: OpenAIError = response.json().await.unwrap_or_else(|_| OpenAIError {
-                error: OpenAIErrorDetail {
-                    message: "Unknown error".to_string(),
-                    error_type: "unknown".to_string(),
-                    code: None,
-                },
-            });
-
-            return match status.as_u16() {
-                401 => Err(ProviderError::auth(error_body.error.message)),
-                429 => Err(ProviderError::rate_limit(error_body.error.message, None)),
-                400 => Err(ProviderError::invalid_request(error_body.error.message)),
-                _ => Err(ProviderError::server_error(
-                    status.as_u16(),
-                    error_body.error.message,
-                )),
-            };
-        }
-
-        let byte_stream = response.bytes_stream();
-        let event_stream = byte_stream.eventsource();
-
-        let stream = event_stream.filter_map(move |result| {
-            async move {
-                match result {
-                    Ok(event) => {
-                        if event.data.is_empty() || event.data == "[DONE]" {
-                            return None;
-                        }
-
-                        let parsed: std::result::Result<OpenAIStreamChunk, _> =
-                            serde_json::from_str(&event.data);
-
-                        match parsed {
-                            Ok(chunk) => {
-                                if let Some(choice) = chunk.choices.into_iter().next() {
-                                    if let Some(content) = choice.delta.content {
-                                        return Some(Ok(StreamEvent::ContentDelta {
-                                            delta: content,
-                                        }));
-                                    }
-
-                                    if let Some(finish_reason) = choice.finish_reason {
-                                        let stop_reason = match finish_reason.as_str() {
-                                            "stop" => StopReason::EndTurn,
-                                            "length" => StopReason::MaxTokens,
-                                            "tool_calls" => StopReason::ToolUse,
-                                            "content_filter" => StopReason::ContentFilter,
-                                            _ => StopReason::Unknown,
-                                        };
-
-                                        return Some(Ok(StreamEvent::End {
-                                            stop_reason,
-                                            usage: Usage::default(),
-                                        }));
-                                    }
-                                }
-                                None
-                            }
-                            Err(e) => {
-                                warn!("Failed to parse SSE event: {}", e);
-                                None
-                            }
-                        }
-                    }
-                    Err(e) => Some(Err(ProviderError::stream(e.to_string()))),
-                }
-            }
-        });
-
-        Ok(Box::pin(stream))
+        let rendered = render_messages(messages, options.as_ref())?;
+        let messages = rendered.as_ref();
+        self.ensure_vision_supported(model, messages)?;
+        let options = self.model_defaults.apply(model, options);
+        self.ensure_fits_context(model, messages, options.as_ref()).await?;
+        self.send_chat_stream(model, messages, options).await
     }
 
     async fn count_tokens(&self, _model: &str, messages: &[Message]) -> Result<TokenCount> {
@@ -518,6 +610,60 @@ impl Provider for OpenAIProvider {
             max_output: Some(16_384),
         }
     }
+
+    fn model_capabilities(&self, model: &str) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: model_supports_vision(model),
+            ..self.capabilities()
+        }
+    }
+}
+
+/// Model info for an OpenAI model, with published limits and pricing filled
+/// in for well-known models.
+fn known_model(id: &str) -> ModelInfo {
+    let info = ModelInfo::new(id, id);
+    match id {
+        "gpt-4o" | "gpt-4o-2024-08-06" => info
+            .with_limits(128_000, 16_384)
+            .with_support(true, true)
+            .with_pricing(2.50, 10.0),
+        "gpt-4o-mini" | "gpt-4o-mini-2024-07-18" => info
+            .with_limits(128_000, 16_384)
+            .with_support(true, true)
+            .with_pricing(0.15, 0.60),
+        "gpt-4-turbo" | "gpt-4-turbo-2024-04-09" => info
+            .with_limits(128_000, 4_096)
+            .with_support(true, true)
+            .with_pricing(10.0, 30.0),
+        "gpt-4" | "gpt-4-0613" => info
+            .with_limits(8_192, 4_096)
+            .with_support(true, false)
+            .with_pricing(30.0, 60.0),
+        "gpt-3.5-turbo" => info
+            .with_limits(16_385, 4_096)
+            .with_support(true, false)
+            .with_pricing(0.50, 1.50),
+        "o1" | "o1-2024-12-17" => info
+            .with_limits(200_000, 100_000)
+            .with_support(true, true)
+            .with_pricing(15.0, 60.0),
+        "o1-mini" | "o1-mini-2024-09-12" => info
+            .with_limits(128_000, 65_536)
+            .with_support(false, false)
+            .with_pricing(1.10, 4.40),
+        "o3-mini" | "o3-mini-2025-01-31" => info
+            .with_limits(200_000, 100_000)
+            .with_support(true, false)
+            .with_pricing(1.10, 4.40),
+        _ => info,
+    }
+}
+
+/// Check whether an OpenAI model accepts image input.
+fn model_supports_vision(model: &str) -> bool {
+    const TEXT_ONLY: [&str; 5] = ["gpt-3.5", "gpt-4-0", "gpt-4-32k", "o1-mini", "o3-mini"];
+    model != "gpt-4" && !TEXT_ONLY.iter().any(|prefix| model.starts_with(prefix))
 }
 
 // Internal types for OpenAI API
@@ -615,9 +761,15 @@ struct OpenAIChoice {
 #[derive(Serialize, Deserialize)]
 struct OpenAIToolCall {
     id: String,
+    #[serde(rename = "type", default = "function_type")]
+    call_type: String,
     function: OpenAIFunctionCall,
 }
 
+fn function_type() -> String {
+    "function".to_string()
+}
+
 #[derive(Serialize, Deserialize)]
 struct OpenAIFunctionCall {
     name: String,
@@ -645,6 +797,107 @@ struct OpenAIErrorDetail {
 
 // Streaming types
 
+/// Tool calls of a streamed response, keyed by their `index`.
+///
+/// Parallel tool calls can interleave their argument deltas, so calls are
+/// collected until the response finishes and then emitted one at a time.
+#[derive(Default)]
+struct StreamedToolCalls {
+    calls: BTreeMap<usize, StreamedToolCall>,
+}
+
+#[derive(Default)]
+struct StreamedToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl StreamedToolCalls {
+    fn push(&mut self, delta: OpenAIToolCallDelta) {
+        // Servers that leave out `index` send one call at a time, starting
+        // each with its ID.
+        let index = delta.index.unwrap_or_else(|| {
+            let last = self.calls.keys().next_back().copied();
+            match (last, &delta.id) {
+                (Some(last), None) => last,
+                (Some(last), Some(_)) => last + 1,
+                (None, _) => 0,
+            }
+        });
+        let call = self.calls.entry(index).or_default();
+        if let Some(id) = delta.id {
+            call.id = id;
+        }
+        let function = delta.function.unwrap_or_default();
+        if let Some(name) = function.name {
+            call.name.push_str(&name);
+        }
+        if let Some(arguments) = function.arguments {
+            call.arguments.push_str(&arguments);
+        }
+    }
+
+    /// Events for the collected calls in index order, leaving none behind.
+    fn drain(&mut self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        for call in std::mem::take(&mut self.calls).into_values() {
+            events.push(StreamEvent::ToolUseStart {
+                id: call.id,
+                name: call.name,
+            });
+            if !call.arguments.is_empty() {
+                events.push(StreamEvent::ToolInputDelta {
+                    delta: call.arguments,
+                });
+            }
+        }
+        events
+    }
+}
+
+/// Convert a streamed chunk into provider-neutral events.
+///
+/// Tool call deltas go to `calls` and are emitted when the chunk finishes
+/// the response.
+fn stream_chunk_events(
+    chunk: OpenAIStreamChunk,
+    calls: &mut StreamedToolCalls,
+) -> Vec<StreamEvent> {
+    let mut events = Vec::new();
+    let Some(choice) = chunk.choices.into_iter().next() else {
+        return events;
+    };
+
+    if let Some(content) = choice.delta.content {
+        if !content.is_empty() {
+            events.push(StreamEvent::ContentDelta { delta: content });
+        }
+    }
+
+    for call in choice.delta.tool_calls.unwrap_or_default() {
+        calls.push(call);
+    }
+
+    if let Some(finish_reason) = choice.finish_reason {
+        events.extend(calls.drain());
+        let stop_reason = match finish_reason.as_str() {
+            "stop" => StopReason::EndTurn,
+            "length" => StopReason::MaxTokens,
+            "tool_calls" => StopReason::ToolUse,
+            "content_filter" => StopReason::ContentFilter,
+            _ => StopReason::Unknown,
+        };
+
+        events.push(StreamEvent::End {
+            stop_reason,
+            usage: Usage::default(),
+        });
+    }
+
+    events
+}
+
 #[derive(Deserialize)]
 struct OpenAIStreamChunk {
     choices: Vec<OpenAIStreamChoice>,
@@ -664,12 +917,12 @@ struct OpenAIStreamDelta {
 
 #[derive(Deserialize)]
 struct OpenAIToolCallDelta {
-    index: usize,
+    index: Option<usize>,
     id: Option<String>,
     function: Option<OpenAIFunctionDelta>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct OpenAIFunctionDelta {
     name: Option<String>,
     arguments: Option<String>,
@@ -691,6 +944,314 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_default_base_url() {
+        let provider = OpenAIProvider::new("test-key").unwrap();
+        assert_eq!(provider.base_url(), "https://api.openai.com/v1");
+        assert!(provider.is_openai());
+
+        let provider = provider.with_base_url("http://localhost:8000/v1/");
+        assert_eq!(provider.base_url(), "http://localhost:8000/v1");
+        assert!(!provider.is_openai());
+    }
+
+    #[tokio::test]
+    async fn test_compatible_server_requests() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [
+                    {"id": "meta-llama/Llama-3.1-8B-Instruct", "object": "model"},
+                    {"id": "qwen2.5-coder-7b", "object": "model"}
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(serde_json::json!({"model": "qwen2.5-coder-7b"})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "model": "qwen2.5-coder-7b",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Hi!"},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::compatible(format!("{}/v1", server.uri())).unwrap();
+
+        let ids: Vec<_> = provider
+            .list_models()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, ["meta-llama/Llama-3.1-8B-Instruct", "qwen2.5-coder-7b"]);
+
+        let response = provider
+            .chat("qwen2.5-coder-7b", &[Message::user("Hello")], None)
+            .await
+            .unwrap();
+        assert_eq!(response.content, "Hi!");
+
+        // No key was configured, so none is sent.
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .flat_map(|r| r.headers.keys())
+            .all(|name| name.as_str() != "authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_compatible_server_with_key() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(header("authorization", "Bearer local-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "local-model"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::compatible(server.uri())
+            .unwrap()
+            .with_api_key("local-key");
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(models[0].id, "local-model");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_retry_after() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("retry-after", "12")
+                    .set_body_json(serde_json::json!({
+                        "error": {"message": "Slow down", "type": "rate_limit_error"}
+                    })),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::compatible(server.uri()).unwrap();
+        let err = provider
+            .chat("local-model", &[Message::user("hi")], None)
+            .await
+            .unwrap_err();
+
+        assert!(err.is_retryable());
+        assert_eq!(err.is_rate_limited(), Some(std::time::Duration::from_secs(12)));
+        assert!(err.to_string().contains("Slow down"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_shared_http_client_settings() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/fast/models"))
+            .and(header("user-agent", "smartassist-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "local-model"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/slow/models"))
+            .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let client = crate::HttpClientConfig::default()
+            .with_user_agent("smartassist-test")
+            .with_timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let fast = OpenAIProvider::compatible(format!("{}/fast", server.uri()))
+            .unwrap()
+            .with_http_client(client.clone());
+        let slow = OpenAIProvider::compatible(format!("{}/slow", server.uri()))
+            .unwrap()
+            .with_http_client(client);
+
+        assert_eq!(fast.list_models().await.unwrap()[0].id, "local-model");
+        match slow.list_models().await {
+            Err(ProviderError::Network(e)) => assert!(e.is_timeout(), "{}", e),
+            other => panic!("expected a timeout, got {:?}", other.map(|m| m.len())),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_models_metadata() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{"id": "gpt-4o"}, {"id": "o3-mini"}, {"id": "gpt-next"}]
+            })))
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let models = provider.list_models().await.unwrap();
+        assert_eq!(models.len(), 3);
+
+        let gpt4o = &models[0];
+        assert_eq!(gpt4o.context_window, Some(128_000));
+        assert_eq!(gpt4o.max_output, Some(16_384));
+        assert_eq!(gpt4o.supports_tools, Some(true));
+        assert_eq!(gpt4o.supports_vision, Some(true));
+        assert_eq!(gpt4o.input_price, Some(2.50));
+        assert_eq!(gpt4o.output_price, Some(10.0));
+
+        assert_eq!(models[1].context_window, Some(200_000));
+        assert_eq!(models[1].supports_vision, Some(false));
+
+        // Unknown models keep their metadata unset rather than guessed.
+        let unknown = &models[2];
+        assert_eq!(unknown.context_window, None);
+        assert_eq!(unknown.supports_tools, None);
+        assert_eq!(unknown.input_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_multibyte_split_across_chunks() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let body = concat!(
+            "data: {\"id\":\"1\",\"model\":\"m\",\"choices\":[{\"index\":0,",
+            "\"delta\":{\"content\":\"こんにちは 👋\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        )
+        .as_bytes();
+        // Cut inside "ん" and inside the emoji.
+        let first = body.iter().position(|&b| b == 0xe3).unwrap() + 4;
+        let second = body.iter().position(|&b| b == 0xf0).unwrap() + 2;
+        let chunks = [&body[..first], &body[first..second], &body[second..]];
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let chunks: Vec<Vec<u8>> = chunks.iter().map(|c| c.to_vec()).collect();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let headers = concat!(
+                "HTTP/1.1 200 OK\r\n",
+                "content-type: text/event-stream\r\n",
+                "transfer-encoding: chunked\r\n\r\n",
+            );
+            socket.write_all(headers.as_bytes()).await.unwrap();
+            for chunk in chunks {
+                socket
+                    .write_all(format!("{:x}\r\n", chunk.len()).as_bytes())
+                    .await
+                    .unwrap();
+                socket.write_all(&chunk).await.unwrap();
+                socket.write_all(b"\r\n").await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+            socket.write_all(b"0\r\n\r\n").await.unwrap();
+        });
+
+        let provider = OpenAIProvider::compatible(url).unwrap();
+        let stream = provider
+            .chat_stream("m", &[Message::user("Hi")], None)
+            .await
+            .unwrap();
+        let response = crate::StreamAccumulator::collect(stream).await.unwrap();
+        assert_eq!(response.content, "こんにちは 👋");
+    }
+
+    #[tokio::test]
+    async fn test_wire_trace_redacts_key_and_content() {
+        use crate::trace::LogCapture;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "model": "gpt-4o",
+                "choices": [{
+                    "message": {"role": "assistant", "content": "Noted."},
+                    "finish_reason": "stop"
+                }],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 2, "total_tokens": 14}
+            })))
+            .mount(&server)
+            .await;
+
+        let api_key = "sk-trace-secret-0123456789";
+        let messages = [Message::user("the vault code is 4711")];
+
+        let logs = LogCapture::default();
+        let provider = OpenAIProvider::new(api_key)
+            .unwrap()
+            .with_base_url(server.uri())
+            .with_wire_trace(WireTrace::metadata());
+        {
+            let _guard = logs.install();
+            provider.chat("gpt-4o", &messages, None).await.unwrap();
+        }
+        let output = logs.contents();
+        assert!(output.contains("provider request"), "{}", output);
+        assert!(output.contains("gpt-4o"), "{}", output);
+        assert!(output.contains("/chat/completions"), "{}", output);
+        assert!(output.contains("status=200"), "{}", output);
+        assert!(output.contains("input_tokens=12"), "{}", output);
+        assert!(!output.contains(api_key), "{}", output);
+        assert!(!output.contains("4711"), "{}", output);
+        assert!(!output.contains("Noted."), "{}", output);
+
+        // Opting into bodies logs content, but the key is still scrubbed.
+        let logs = LogCapture::default();
+        let provider = provider.with_wire_trace(WireTrace::metadata().include_bodies_unsafe());
+        {
+            let _guard = logs.install();
+            provider.chat("gpt-4o", &messages, None).await.unwrap();
+        }
+        let output = logs.contents();
+        assert!(output.contains("4711"), "{}", output);
+        assert!(output.contains("Noted."), "{}", output);
+        assert!(!output.contains(api_key), "{}", output);
+    }
+
     #[test]
     fn test_capabilities() {
         let provider = OpenAIProvider::new("test-key").unwrap();
@@ -700,4 +1261,262 @@ mod tests {
         assert!(caps.tools);
         assert!(caps.vision);
     }
+
+    #[test]
+    fn test_parse_tool_calls_response() {
+        let provider = OpenAIProvider::new("test-key").unwrap();
+        let raw = serde_json::json!({
+            "id": "chatcmpl-123",
+            "model": "gpt-4o",
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": {
+                            "name": "get_weather",
+                            "arguments": "{\"city\":\"Paris\"}"
+                        }
+                    }]
+                },
+                "finish_reason": "tool_calls"
+            }],
+            "usage": {"prompt_tokens": 20, "completion_tokens": 8}
+        });
+
+        let response = provider
+            .parse_response(serde_json::from_value(raw).unwrap())
+            .unwrap();
+
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(
+            response.tool_calls,
+            vec![ToolCall::new(
+                "call_abc",
+                "get_weather",
+                serde_json::json!({"city": "Paris"})
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_interleaved_parallel_tool_calls() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let chunk = |calls: serde_json::Value, finish: Option<&str>| {
+            let chunk = serde_json::json!({
+                "choices": [{"delta": {"tool_calls": calls}, "finish_reason": finish}]
+            });
+            format!("data: {}\n\n", chunk)
+        };
+        let start = |index: usize, id: &str, name: &str| {
+            serde_json::json!({
+                "index": index, "id": id, "type": "function",
+                "function": {"name": name, "arguments": ""}
+            })
+        };
+        let args = |index: usize, arguments: &str| {
+            serde_json::json!({"index": index, "function": {"arguments": arguments}})
+        };
+        let body = [
+            chunk(serde_json::json!([start(0, "call_a", "get_weather")]), None),
+            chunk(serde_json::json!([start(1, "call_b", "get_time")]), None),
+            chunk(serde_json::json!([args(0, "{\"city\":"), args(1, "{\"tz\":")]), None),
+            chunk(serde_json::json!([args(1, "\"UTC\"}")]), None),
+            chunk(serde_json::json!([args(0, "\"Paris\"}")]), Some("tool_calls")),
+            "data: [DONE]\n\n".to_string(),
+        ]
+        .concat();
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let events: Vec<_> = provider
+            .chat_stream("gpt-4o", &[Message::user("Weather and time?")], None)
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        let calls: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolCall(call) => Some(call.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ToolCall::new("call_a", "get_weather", serde_json::json!({"city": "Paris"})),
+                ToolCall::new("call_b", "get_time", serde_json::json!({"tz": "UTC"})),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_tool_calls() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"tool_calls\":[{\"index\":0,\"id\":\"call_abc\",\"type\":\"function\",\"function\":{\"name\":\"get_weather\",\"arguments\":\"\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"city\\\":\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"Paris\\\"}\"}}]},\"finish_reason\":null}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let provider = OpenAIProvider::new("test-key")
+            .unwrap()
+            .with_base_url(server.uri());
+        let events: Vec<_> = provider
+            .chat_stream("gpt-4o", &[Message::user("Weather?")], None)
+            .await
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        let calls: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolCall(call) => Some(call.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![ToolCall::new(
+                "call_abc",
+                "get_weather",
+                serde_json::json!({"city": "Paris"})
+            )]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::End {
+                stop_reason: StopReason::ToolUse,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_image_message_serialization() {
+        let provider = OpenAIProvider::new("test-key").unwrap();
+        let messages = vec![
+            Message::with_image(
+                MessageRole::User,
+                "What is in this image?",
+                crate::ImageContent::base64("image/png", "iVBORw0KGgo="),
+            ),
+            Message::with_image(
+                MessageRole::User,
+                "And this one?",
+                crate::ImageContent::url("https://example.com/cat.jpg"),
+            ),
+        ];
+
+        let converted = provider.convert_messages(&messages).unwrap();
+        let json = serde_json::to_value(&converted).unwrap();
+
+        assert_eq!(
+            json[0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "What is in this image?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+            ])
+        );
+        assert_eq!(
+            json[1]["content"][1]["image_url"]["url"],
+            "https://example.com/cat.jpg"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_image_rejected_for_non_vision_model() {
+        let provider = OpenAIProvider::new("test-key").unwrap();
+        assert!(provider.model_capabilities("gpt-4o").vision);
+        assert!(!provider.model_capabilities("gpt-3.5-turbo").vision);
+
+        let messages = vec![Message::with_image(
+            MessageRole::User,
+            "What is this?",
+            crate::ImageContent::url("https://example.com/cat.jpg"),
+        )];
+        let result = provider.chat_stream("gpt-3.5-turbo", &messages, None).await;
+        assert!(matches!(result, Err(ProviderError::Unsupported(_))));
+
+        // Text-only requests to the same model are not affected.
+        assert!(provider
+            .ensure_vision_supported("gpt-3.5-turbo", &[Message::user("Hi")])
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_chat_rejects_request_over_context_window() {
+        // Nothing listens here; the request must be rejected before sending.
+        let provider = OpenAIProvider::new("test-key")
+            .unwrap()
+            .with_base_url("http://127.0.0.1:9");
+        let messages = vec![Message::user("x".repeat(600_000))];
+
+        let result = provider.chat("gpt-4o", &messages, None).await;
+        assert!(matches!(
+            result,
+            Err(ProviderError::ContextLengthExceeded {
+                used: 150_000,
+                max: 128_000
+            })
+        ));
+    }
+
+    #[test]
+    fn test_tool_call_round_trip_messages() {
+        let provider = OpenAIProvider::new("test-key").unwrap();
+        let call = ToolCall::new("call_abc", "get_weather", serde_json::json!({"city": "Paris"}));
+        let messages = vec![
+            Message::assistant_tool_calls("", std::slice::from_ref(&call)),
+            call.result_message("sunny"),
+        ];
+
+        let converted = provider.convert_messages(&messages).unwrap();
+        let json = serde_json::to_value(&converted).unwrap();
+
+        assert!(json[0].get("content").is_none());
+        assert_eq!(json[0]["tool_calls"][0]["id"], "call_abc");
+        assert_eq!(json[0]["tool_calls"][0]["type"], "function");
+        assert_eq!(
+            json[0]["tool_calls"][0]["function"]["arguments"],
+            "{\"city\":\"Paris\"}"
+        );
+        assert_eq!(json[1]["role"], "tool");
+        assert_eq!(json[1]["tool_call_id"], "call_abc");
+        assert!(json[1].get("name").is_none());
+    }
 }
@@ -0,0 +1,254 @@
+//! Timing statistics for streaming completions.
+//!
+//! [`with_stats`] wraps a completion stream and measures how long the first
+//! token took to arrive and how quickly the rest followed. Events pass
+//! through untouched; the only per-event cost is reading the clock for
+//! deltas. The [`StreamStats`] are sent once the stream ends, or when it is
+//! dropped before that.
+
+use crate::{CompletionStream, StreamEvent};
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Number of most recent tokens the rolling rate is computed over.
+const RATE_WINDOW: usize = 16;
+
+/// Receives the [`StreamStats`] of a stream wrapped by [`with_stats`].
+pub type StatsReceiver = oneshot::Receiver<StreamStats>;
+
+/// Timing of a streaming completion.
+///
+/// Tokens are counted as text, thinking and tool input deltas, which
+/// providers send roughly one token at a time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamStats {
+    /// Time from the request to the first token.
+    pub first_token_latency: Option<Duration>,
+
+    /// Time from the request to the last token.
+    pub last_token_latency: Option<Duration>,
+
+    /// Time from the request until the stream ended or was dropped.
+    pub duration: Duration,
+
+    /// Number of token deltas received.
+    pub tokens: usize,
+
+    /// Output tokens reported by the provider when the stream completed.
+    pub output_tokens: Option<usize>,
+
+    /// Token rate over the last few tokens received.
+    pub recent_tokens_per_second: Option<f64>,
+}
+
+impl StreamStats {
+    /// Average token rate between the first and the last token.
+    ///
+    /// Uses the provider's output token count when it reported one; a
+    /// reported count of zero gives a rate of zero.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        if self.output_tokens == Some(0) {
+            return Some(0.0);
+        }
+        let elapsed = self
+            .last_token_latency?
+            .checked_sub(self.first_token_latency?)?
+            .as_secs_f64();
+        let tokens = self.output_tokens.unwrap_or(self.tokens);
+        if tokens < 2 || elapsed <= 0.0 {
+            return None;
+        }
+        Some((tokens - 1) as f64 / elapsed)
+    }
+}
+
+/// Wrap a completion stream to measure its timing.
+///
+/// Latencies are measured from `started`, which should be taken before the
+/// request was sent so that the first token latency includes it.
+pub fn with_stats(stream: CompletionStream, started: Instant) -> (CompletionStream, StatsReceiver) {
+    let (tx, rx) = oneshot::channel();
+    let recorder = StatsRecorder {
+        started,
+        stats: StreamStats::default(),
+        recent: VecDeque::with_capacity(RATE_WINDOW),
+        tx: Some(tx),
+    };
+
+    // The recorder sends the stats when it is dropped, which happens both
+    // when the inner stream ends and when the caller drops the stream.
+    let stream = futures::stream::unfold((stream, recorder), |(mut inner, mut recorder)| {
+        async move {
+            let item = inner.next().await?;
+            if let Ok(event) = &item {
+                recorder.observe(event);
+            }
+            Some((item, (inner, recorder)))
+        }
+    });
+
+    (Box::pin(stream.fuse()), rx)
+}
+
+/// Accumulates [`StreamStats`] and sends them when dropped.
+struct StatsRecorder {
+    started: Instant,
+    stats: StreamStats,
+    /// Arrival times of the most recent tokens, oldest first.
+    recent: VecDeque<Instant>,
+    tx: Option<oneshot::Sender<StreamStats>>,
+}
+
+impl StatsRecorder {
+    fn observe(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::ContentDelta { delta }
+            | StreamEvent::Thinking { delta }
+            | StreamEvent::ToolInputDelta { delta }
+                if !delta.is_empty() =>
+            {
+                let now = Instant::now();
+                let elapsed = now.duration_since(self.started);
+                self.stats.first_token_latency.get_or_insert(elapsed);
+                self.stats.last_token_latency = Some(elapsed);
+                self.stats.tokens += 1;
+
+                if self.recent.len() == RATE_WINDOW {
+                    self.recent.pop_front();
+                }
+                self.recent.push_back(now);
+            }
+            StreamEvent::End { usage, .. } => {
+                self.stats.output_tokens = Some(usage.output_tokens);
+            }
+            _ => {}
+        }
+    }
+
+    fn recent_rate(&self) -> Option<f64> {
+        let (first, last) = (self.recent.front()?, self.recent.back()?);
+        let elapsed = last.duration_since(*first).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some((self.recent.len() - 1) as f64 / elapsed)
+    }
+}
+
+impl Drop for StatsRecorder {
+    fn drop(&mut self) {
+        let Some(tx) = self.tx.take() else {
+            return;
+        };
+        self.stats.duration = self.started.elapsed();
+        self.stats.recent_tokens_per_second = self.recent_rate();
+        tracing::debug!(
+            first_token_ms = self.stats.first_token_latency.map(|d| d.as_millis() as u64),
+            tokens = self.stats.tokens,
+            duration_ms = self.stats.duration.as_millis() as u64,
+            "stream finished"
+        );
+        let _ = tx.send(std::mem::take(&mut self.stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StopReason, Usage};
+
+    fn delta(text: &str) -> StreamEvent {
+        StreamEvent::ContentDelta {
+            delta: text.to_string(),
+        }
+    }
+
+    /// Stream yielding each event after its delay in milliseconds.
+    fn timed(events: Vec<(u64, StreamEvent)>) -> CompletionStream {
+        Box::pin(futures::stream::iter(events).then(|(delay, event)| async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            Ok(event)
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_first_token_latency_and_count() {
+        let inner = timed(vec![
+            (0, StreamEvent::Start { id: "1".to_string(), model: "m".to_string() }),
+            (40, delta("Hel")),
+            (10, delta("lo")),
+            (10, delta("")),
+            (10, delta(" there")),
+            (
+                0,
+                StreamEvent::End {
+                    stop_reason: StopReason::EndTurn,
+                    usage: Usage { output_tokens: 3, ..Default::default() },
+                },
+            ),
+        ]);
+        let (stream, stats) = with_stats(inner, Instant::now());
+
+        let events: Vec<_> = stream.collect().await;
+        assert_eq!(events.len(), 6);
+
+        let stats = stats.await.unwrap();
+        let first = stats.first_token_latency.unwrap();
+        let last = stats.last_token_latency.unwrap();
+        assert!(first >= Duration::from_millis(40), "{:?}", first);
+        assert!(last >= first + Duration::from_millis(30), "{:?}", last);
+        assert!(stats.duration >= last);
+        assert_eq!(stats.tokens, 3);
+        assert_eq!(stats.output_tokens, Some(3));
+
+        // Two more tokens arrived at least 30ms after the first.
+        let rate = stats.tokens_per_second().unwrap();
+        assert!(rate <= 2.0 / 0.03 + 1.0, "{}", rate);
+        assert!(stats.recent_tokens_per_second.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stats_sent_when_stream_dropped() {
+        let inner = timed(vec![(0, delta("a")), (5, delta("b"))]);
+        let inner: CompletionStream = Box::pin(inner.chain(futures::stream::pending()));
+        let (mut stream, stats) = with_stats(inner, Instant::now());
+
+        stream.next().await.unwrap().unwrap();
+        stream.next().await.unwrap().unwrap();
+        drop(stream);
+
+        let stats = stats.await.unwrap();
+        assert_eq!(stats.tokens, 2);
+        assert_eq!(stats.output_tokens, None);
+        assert!(stats.first_token_latency.is_some());
+    }
+
+    #[test]
+    fn test_tokens_per_second_needs_two_tokens() {
+        let stats = StreamStats {
+            first_token_latency: Some(Duration::from_millis(100)),
+            last_token_latency: Some(Duration::from_millis(100)),
+            tokens: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.tokens_per_second(), None);
+
+        let stats = StreamStats {
+            last_token_latency: Some(Duration::from_millis(600)),
+            tokens: 11,
+            ..stats
+        };
+        assert!((stats.tokens_per_second().unwrap() - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tokens_per_second_is_zero_without_output() {
+        let stats = StreamStats {
+            output_tokens: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(stats.tokens_per_second(), Some(0.0));
+    }
+}
@@ -0,0 +1,535 @@
+//! Tool call assembly and accumulation for streaming completions.
+//!
+//! Providers stream tool calls as a [`StreamEvent::ToolUseStart`] followed by
+//! [`StreamEvent::ToolInputDelta`] fragments of JSON. The adapter here passes
+//! those events through unchanged and, once a call is complete, emits a
+//! [`StreamEvent::ToolCall`] carrying the assembled [`ToolCall`].
+//!
+//! [`StreamAccumulator`] goes one step further and folds a whole stream into
+//! the [`ChatResponse`] a non-streaming request would have returned.
+//!
+//! Below the event level, [`decode_utf8`] turns raw response chunks into text
+//! without splitting multi-byte characters that straddle chunk boundaries.
+
+use crate::{
+    ChatResponse, CompletionStream, ProviderError, Result, StopReason, StreamEvent, ToolCall,
+    Usage,
+};
+use futures::{Stream, StreamExt};
+use std::collections::{HashMap, VecDeque};
+
+/// A tool call whose arguments are still streaming in.
+struct PendingCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// Collects tool input deltas into complete tool calls.
+#[derive(Default)]
+struct ToolCallAssembler {
+    pending: Option<PendingCall>,
+}
+
+impl ToolCallAssembler {
+    /// Feed an event, returning the events to emit in order.
+    fn push(&mut self, event: StreamEvent) -> Vec<StreamEvent> {
+        let mut out = Vec::new();
+        match &event {
+            StreamEvent::ToolUseStart { id, name } => {
+                out.extend(self.flush());
+                self.pending = Some(PendingCall {
+                    id: id.clone(),
+                    name: name.clone(),
+                    arguments: String::new(),
+                });
+            }
+            StreamEvent::ToolInputDelta { delta } => {
+                if let Some(pending) = &mut self.pending {
+                    pending.arguments.push_str(delta);
+                }
+            }
+            // An already assembled copy of the pending call replaces it.
+            StreamEvent::ToolCall(call)
+                if self.pending.as_ref().is_some_and(|p| p.id == call.id) =>
+            {
+                self.pending = None;
+            }
+            StreamEvent::End { .. } | StreamEvent::ToolCall(_) => out.extend(self.flush()),
+            _ => {}
+        }
+        out.push(event);
+        out
+    }
+
+    /// Complete the pending call, if any.
+    fn flush(&mut self) -> Option<StreamEvent> {
+        self.pending.take().map(|call| {
+            StreamEvent::ToolCall(ToolCall {
+                id: call.id,
+                name: call.name,
+                arguments: ToolCall::parse_arguments(&call.arguments),
+            })
+        })
+    }
+}
+
+/// Emit a [`StreamEvent::ToolCall`] for every tool call in the stream.
+///
+/// A call is complete when the next tool call starts, the stream reports
+/// [`StreamEvent::End`], or the stream finishes.
+pub fn assemble_tool_calls(stream: CompletionStream) -> CompletionStream {
+    let state = (stream, ToolCallAssembler::default(), VecDeque::new(), false);
+
+    let stream = futures::stream::unfold(state, |(mut inner, mut assembler, mut queue, mut done)| async move {
+        loop {
+            if let Some(event) = queue.pop_front() {
+                return Some((event, (inner, assembler, queue, done)));
+            }
+            if done {
+                return None;
+            }
+
+            match inner.next().await {
+                Some(Ok(event)) => queue.extend(assembler.push(event).into_iter().map(Ok)),
+                Some(Err(e)) => return Some((Err(e), (inner, assembler, queue, done))),
+                None => {
+                    done = true;
+                    queue.extend(assembler.flush().map(Ok));
+                }
+            }
+        }
+    });
+
+    Box::pin(stream.fuse())
+}
+
+/// Incremental UTF-8 decoder for response byte chunks.
+///
+/// An incomplete multi-byte sequence at the end of a chunk is held back until
+/// the next chunk completes it. Invalid bytes become U+FFFD instead of
+/// stalling the stream.
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode a chunk, returning all text that is complete so far.
+    fn decode(&mut self, chunk: &[u8]) -> String {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+
+        let mut text = String::with_capacity(bytes.len());
+        let mut rest = bytes.as_slice();
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            // Truncated sequence: wait for the next chunk.
+                            self.pending = after.to_vec();
+                            rest = &[];
+                        }
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// Flush bytes left over when the stream ends.
+    fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let bytes = std::mem::take(&mut self.pending);
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Decode a stream of byte chunks into UTF-8 text chunks.
+///
+/// Multi-byte characters split across chunks are held back and rejoined, so a
+/// chunk boundary never corrupts valid text. Bytes that are not valid UTF-8,
+/// including a sequence still incomplete when the stream ends, are replaced
+/// with U+FFFD, so the result can be fed to an SSE parser safely.
+pub(crate) fn decode_utf8<S, B, E>(stream: S) -> impl Stream<Item = std::result::Result<String, E>>
+where
+    S: Stream<Item = std::result::Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let state = (Box::pin(stream), Utf8Decoder::default(), false);
+    futures::stream::unfold(state, |(mut inner, mut decoder, done)| async move {
+        if done {
+            return None;
+        }
+        match inner.next().await {
+            Some(Ok(chunk)) => {
+                let text = decoder.decode(chunk.as_ref());
+                Some((Ok(text), (inner, decoder, false)))
+            }
+            Some(Err(e)) => Some((Err(e), (inner, decoder, false))),
+            None => decoder.finish().map(|rest| (Ok(rest), (inner, decoder, true))),
+        }
+    })
+}
+
+/// Folds streamed events into a single [`ChatResponse`].
+///
+/// Text deltas are concatenated, tool calls are assembled from their input
+/// deltas, and usage is summed across [`StreamEvent::End`] events. Thinking
+/// is kept under the `thinking` metadata key, as in non-streaming responses.
+#[derive(Default)]
+pub struct StreamAccumulator {
+    id: String,
+    model: String,
+    content: String,
+    thinking: String,
+    tool_calls: Vec<ToolCall>,
+    assembler: ToolCallAssembler,
+    stop_reason: Option<StopReason>,
+    usage: Usage,
+}
+
+impl StreamAccumulator {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one event.
+    ///
+    /// Returns an error for [`StreamEvent::Error`].
+    pub fn push(&mut self, event: StreamEvent) -> Result<()> {
+        for event in self.assembler.push(event) {
+            match event {
+                StreamEvent::Start { id, model } => {
+                    self.id = id;
+                    self.model = model;
+                }
+                StreamEvent::ContentDelta { delta } => self.content.push_str(&delta),
+                StreamEvent::Thinking { delta } => self.thinking.push_str(&delta),
+                StreamEvent::ToolCall(call) => self.tool_calls.push(call),
+                StreamEvent::End { stop_reason, usage } => {
+                    self.stop_reason = Some(stop_reason);
+                    self.usage.input_tokens += usage.input_tokens;
+                    self.usage.output_tokens += usage.output_tokens;
+                    self.usage.cache_read_tokens += usage.cache_read_tokens;
+                    self.usage.cache_creation_tokens += usage.cache_creation_tokens;
+                }
+                StreamEvent::Error { message } => return Err(ProviderError::stream(message)),
+                StreamEvent::ToolUseStart { .. } | StreamEvent::ToolInputDelta { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the final response.
+    ///
+    /// Without an [`StreamEvent::End`], the stop reason is inferred from
+    /// whether any tool calls were made.
+    pub fn finish(mut self) -> ChatResponse {
+        if let Some(StreamEvent::ToolCall(call)) = self.assembler.flush() {
+            self.tool_calls.push(call);
+        }
+        let stop_reason = self.stop_reason.unwrap_or(if self.tool_calls.is_empty() {
+            StopReason::EndTurn
+        } else {
+            StopReason::ToolUse
+        });
+
+        let mut metadata = HashMap::new();
+        if !self.thinking.is_empty() {
+            metadata.insert(
+                "thinking".to_string(),
+                serde_json::Value::String(self.thinking),
+            );
+        }
+
+        ChatResponse {
+            id: self.id,
+            model: self.model,
+            content: self.content,
+            tool_calls: self.tool_calls,
+            stop_reason,
+            usage: self.usage,
+            metadata,
+        }
+    }
+
+    /// Consume a stream into a response.
+    pub async fn collect(stream: CompletionStream) -> Result<ChatResponse> {
+        Self::collect_with(stream, |_| {}).await
+    }
+
+    /// Consume a stream into a response, passing each event to `forward`
+    /// first so callers can render deltas as they arrive.
+    pub async fn collect_with(
+        mut stream: CompletionStream,
+        mut forward: impl FnMut(&StreamEvent),
+    ) -> Result<ChatResponse> {
+        let mut accumulator = Self::new();
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            forward(&event);
+            accumulator.push(event)?;
+        }
+        Ok(accumulator.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{StopReason, Usage};
+    use serde_json::json;
+
+    fn events(events: Vec<StreamEvent>) -> CompletionStream {
+        Box::pin(futures::stream::iter(events.into_iter().map(Ok)))
+    }
+
+    fn tool_calls(events: &[StreamEvent]) -> Vec<ToolCall> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolCall(call) => Some(call.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_assembles_deltas_before_end() {
+        let stream = assemble_tool_calls(events(vec![
+            StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "read".to_string(),
+            },
+            StreamEvent::ToolInputDelta {
+                delta: "{\"path\":".to_string(),
+            },
+            StreamEvent::ToolInputDelta {
+                delta: "\"a.txt\"}".to_string(),
+            },
+            StreamEvent::ToolUseStart {
+                id: "call_2".to_string(),
+                name: "list".to_string(),
+            },
+            StreamEvent::End {
+                stop_reason: StopReason::ToolUse,
+                usage: Usage::default(),
+            },
+        ]));
+
+        let out: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(
+            tool_calls(&out),
+            vec![
+                ToolCall::new("call_1", "read", json!({"path": "a.txt"})),
+                ToolCall::new("call_2", "list", json!({})),
+            ]
+        );
+        assert!(matches!(out.last(), Some(StreamEvent::End { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_flushes_at_stream_end() {
+        let stream = assemble_tool_calls(events(vec![
+            StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "read".to_string(),
+            },
+            StreamEvent::ToolInputDelta {
+                delta: "{}".to_string(),
+            },
+        ]));
+
+        let out: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(tool_calls(&out), vec![ToolCall::new("call_1", "read", json!({}))]);
+    }
+
+    #[tokio::test]
+    async fn test_complete_calls_pass_through() {
+        let call = ToolCall::new("call_1", "read", json!({"path": "a.txt"}));
+        let stream = assemble_tool_calls(events(vec![StreamEvent::ToolCall(call.clone())]));
+
+        let out: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(tool_calls(&out), vec![call]);
+    }
+
+    #[tokio::test]
+    async fn test_accumulated_call_not_duplicated() {
+        let call = ToolCall::new("call_1", "read", json!({"path": "a.txt"}));
+        let stream = assemble_tool_calls(events(vec![
+            StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "read".to_string(),
+            },
+            StreamEvent::ToolCall(call.clone()),
+        ]));
+
+        let out: Vec<_> = stream.map(|e| e.unwrap()).collect().await;
+        assert_eq!(tool_calls(&out), vec![call]);
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_reconstructs_response() {
+        let stream = events(vec![
+            StreamEvent::Start {
+                id: "msg_1".to_string(),
+                model: "test-model".to_string(),
+            },
+            StreamEvent::Thinking {
+                delta: "Need the ".to_string(),
+            },
+            StreamEvent::Thinking {
+                delta: "file.".to_string(),
+            },
+            StreamEvent::ContentDelta {
+                delta: "Let me ".to_string(),
+            },
+            StreamEvent::ContentDelta {
+                delta: "check.".to_string(),
+            },
+            StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "read".to_string(),
+            },
+            StreamEvent::ToolInputDelta {
+                delta: "{\"path\":".to_string(),
+            },
+            StreamEvent::ToolInputDelta {
+                delta: "\"a.txt\"}".to_string(),
+            },
+            StreamEvent::End {
+                stop_reason: StopReason::ToolUse,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 4,
+                    ..Default::default()
+                },
+            },
+            StreamEvent::End {
+                stop_reason: StopReason::ToolUse,
+                usage: Usage {
+                    input_tokens: 0,
+                    output_tokens: 3,
+                    ..Default::default()
+                },
+            },
+        ]);
+
+        let mut forwarded = String::new();
+        let response = StreamAccumulator::collect_with(stream, |event| {
+            if let StreamEvent::ContentDelta { delta } = event {
+                forwarded.push_str(delta);
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(forwarded, "Let me check.");
+        assert_eq!(response.id, "msg_1");
+        assert_eq!(response.model, "test-model");
+        assert_eq!(response.content, "Let me check.");
+        assert_eq!(
+            response.tool_calls,
+            vec![ToolCall::new("call_1", "read", json!({"path": "a.txt"}))]
+        );
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert_eq!(response.usage.input_tokens, 10);
+        assert_eq!(response.usage.output_tokens, 7);
+        assert_eq!(response.metadata["thinking"], "Need the file.");
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_without_end() {
+        let stream = events(vec![
+            StreamEvent::ContentDelta {
+                delta: "Hi".to_string(),
+            },
+            StreamEvent::ToolUseStart {
+                id: "call_1".to_string(),
+                name: "list".to_string(),
+            },
+        ]);
+
+        let response = StreamAccumulator::collect(stream).await.unwrap();
+        assert_eq!(response.content, "Hi");
+        assert_eq!(response.tool_calls, vec![ToolCall::new("call_1", "list", json!({}))]);
+        assert_eq!(response.stop_reason, StopReason::ToolUse);
+        assert!(response.metadata.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_accumulator_surfaces_errors() {
+        let stream = events(vec![
+            StreamEvent::ContentDelta {
+                delta: "Hi".to_string(),
+            },
+            StreamEvent::Error {
+                message: "overloaded".to_string(),
+            },
+        ]);
+
+        let err = StreamAccumulator::collect(stream).await.unwrap_err();
+        assert!(matches!(err, ProviderError::Stream(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_decode_utf8_rejoins_split_characters() {
+        let text = "héllo 👋 世界";
+        let bytes = text.as_bytes();
+        // Split inside "é", inside the emoji, and inside "世".
+        let chunks: Vec<std::result::Result<Vec<u8>, ()>> = vec![
+            Ok(bytes[..2].to_vec()),
+            Ok(bytes[2..9].to_vec()),
+            Ok(bytes[9..13].to_vec()),
+            Ok(bytes[13..].to_vec()),
+        ];
+
+        let decoded: Vec<String> = decode_utf8(futures::stream::iter(chunks))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(decoded.concat(), text);
+        assert!(decoded.iter().all(|chunk| !chunk.contains('\u{FFFD}')));
+    }
+
+    #[tokio::test]
+    async fn test_decode_utf8_replaces_invalid_bytes() {
+        // An invalid byte, a sequence broken by an ASCII byte, and a sequence
+        // the stream ends in the middle of.
+        let chunks: Vec<std::result::Result<Vec<u8>, ()>> = vec![
+            Ok(b"data: \xff ok".to_vec()),
+            Ok(b" \xe4\xb8".to_vec()),
+            Ok(b"x \xf0\x9f".to_vec()),
+        ];
+
+        let decoded: Vec<String> = decode_utf8(futures::stream::iter(chunks))
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(decoded.concat(), "data: \u{FFFD} ok \u{FFFD}x \u{FFFD}");
+    }
+
+    #[test]
+    fn test_utf8_decoder_invalid_and_truncated_bytes() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{FFFD}b");
+        assert_eq!(decoder.decode(b"c\xe4\xb8"), "c");
+        assert_eq!(decoder.finish().as_deref(), Some("\u{FFFD}"));
+        assert_eq!(decoder.finish(), None);
+    }
+}
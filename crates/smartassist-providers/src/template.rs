@@ -0,0 +1,122 @@
+//! `{{variable}}` substitution in message text.
+//!
+//! When [`ChatOptions::variables`] is set, providers render the text of
+//! system messages with [`render_messages`] before building the request, so
+//! a shared system prompt can be written once with placeholders. A
+//! placeholder without a value fails the request instead of sending literal
+//! braces. User, assistant and tool messages are never rendered: their text
+//! comes from people and tools, and braces in it are not placeholders.
+//! Text that only looks like a placeholder, such as `{{ }}` or `{{a-b}}`, is
+//! left as is; see [`smartassist_core::template`].
+
+use crate::{ChatOptions, ContentPart, Message, MessageContent, ProviderError, Result};
+use std::borrow::Cow;
+
+pub use smartassist_core::template::{fill_template, FilledTemplate, TemplateVars};
+
+/// Substitute every placeholder of `template`, failing if one is undefined.
+pub fn render_template(template: &str, vars: &TemplateVars) -> Result<String> {
+    smartassist_core::template::render(template, vars)
+        .map_err(|e| ProviderError::invalid_request(e.to_string()))
+}
+
+/// Render the system messages in `messages` with the variables in `options`.
+///
+/// Messages are borrowed unchanged when no variables are set, so text with
+/// braces is only interpreted when a caller opts in.
+pub fn render_messages<'a>(
+    messages: &'a [Message],
+    options: Option<&ChatOptions>,
+) -> Result<Cow<'a, [Message]>> {
+    match options.and_then(|o| o.variables.as_ref()) {
+        Some(vars) => messages
+            .iter()
+            .map(|message| {
+                if message.role.is_system() {
+                    message.render(vars)
+                } else {
+                    Ok(message.clone())
+                }
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Cow::Owned),
+        None => Ok(Cow::Borrowed(messages)),
+    }
+}
+
+impl Message {
+    /// Render the text of this message with `vars`.
+    ///
+    /// Images, tool calls and tool results are copied unchanged.
+    pub fn render(&self, vars: &TemplateVars) -> Result<Message> {
+        let content = match &self.content {
+            MessageContent::Text(text) => MessageContent::Text(render_template(text, vars)?),
+            MessageContent::Parts(parts) => MessageContent::Parts(
+                parts
+                    .iter()
+                    .map(|part| match part {
+                        ContentPart::Text(text) => {
+                            render_template(text, vars).map(ContentPart::Text)
+                        }
+                        other => Ok(other.clone()),
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+        };
+        Ok(Message {
+            content,
+            ..self.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vars(value: serde_json::Value) -> TemplateVars {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_render_templated_system_message() {
+        let messages = vec![
+            Message::system("You are {{name}}, helping {{ user }} in {{lang}}. Limit: {{max}}."),
+            Message::user("Hi {{user}}"),
+        ];
+        let options = ChatOptions::default().variables(vars(json!({
+            "name": "SmartAssist",
+            "user": "Ada",
+            "lang": "English",
+            "max": 3,
+        })));
+
+        let rendered = render_messages(&messages, Some(&options)).unwrap();
+        assert_eq!(
+            rendered[0].text(),
+            Some("You are SmartAssist, helping Ada in English. Limit: 3.")
+        );
+        assert!(rendered[0].role.is_system());
+        // Only system messages are templates.
+        assert_eq!(rendered[1].text(), Some("Hi {{user}}"));
+    }
+
+    #[test]
+    fn test_undefined_variable_fails() {
+        let messages = vec![Message::system("Hello {{name}}, today is {{day}} ({{day}})")];
+        let options = ChatOptions::default().variables(vars(json!({"name": "Ada"})));
+
+        let err = render_messages(&messages, Some(&options)).unwrap_err();
+        assert!(matches!(err, ProviderError::InvalidRequest(_)));
+        assert_eq!(err.to_string(), "Invalid request: Undefined template variable: day");
+    }
+
+    #[test]
+    fn test_messages_untouched_without_variables() {
+        let messages = vec![Message::user("Literal {{braces}} stay")];
+        let rendered = render_messages(&messages, None).unwrap();
+        assert!(matches!(rendered, Cow::Borrowed(_)));
+        assert_eq!(rendered[0].text(), Some("Literal {{braces}} stay"));
+    }
+}
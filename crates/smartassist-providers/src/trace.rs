@@ -0,0 +1,229 @@
+//! Opt-in tracing of provider HTTP traffic.
+//!
+//! Providers enable it with `with_wire_trace`. Requests and responses are
+//! logged at debug level under [`TRACE_TARGET`]. By default only metadata is
+//! recorded: credentials in endpoints are redacted and message content is
+//! never logged. [`WireTrace::include_bodies_unsafe`] adds the JSON bodies,
+//! with the API key still scrubbed from them.
+
+use crate::{Message, Usage};
+use serde::Serialize;
+use std::time::Instant;
+use tracing::debug;
+
+/// Tracing target for provider traffic.
+pub const TRACE_TARGET: &str = "smartassist_providers::wire";
+
+/// Replacement for redacted values.
+const REDACTED: &str = "[REDACTED]";
+
+/// Query parameters that carry credentials.
+const SECRET_PARAMS: [&str; 3] = ["key", "api_key", "access_token"];
+
+/// Wire tracing settings for a provider.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WireTrace {
+    enabled: bool,
+    include_bodies: bool,
+}
+
+impl WireTrace {
+    /// Trace request and response metadata only.
+    pub fn metadata() -> Self {
+        Self {
+            enabled: true,
+            include_bodies: false,
+        }
+    }
+
+    /// Also log request and response bodies.
+    ///
+    /// Bodies contain prompts, replies, and tool arguments, so this is unsafe
+    /// anywhere logs are shared. API keys are still redacted.
+    pub fn include_bodies_unsafe(mut self) -> Self {
+        self.enabled = true;
+        self.include_bodies = true;
+        self
+    }
+
+    /// Check whether tracing is on.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Log an outgoing request, returning when it started.
+    pub(crate) fn request(
+        &self,
+        provider: &str,
+        url: &str,
+        model: &str,
+        messages: &[Message],
+        body: &impl Serialize,
+        api_key: &str,
+    ) -> Instant {
+        if self.enabled {
+            debug!(
+                target: TRACE_TARGET,
+                provider,
+                endpoint = %redact_url(url),
+                model,
+                messages = messages.len(),
+                estimated_tokens = estimate_tokens(messages),
+                "provider request"
+            );
+            if self.include_bodies {
+                let body = serde_json::to_string(body).unwrap_or_default();
+                debug!(
+                    target: TRACE_TARGET,
+                    provider,
+                    body = %redact_secret(&body, api_key),
+                    "provider request body"
+                );
+            }
+        }
+        Instant::now()
+    }
+
+    /// Log a response status, with usage once it is known.
+    pub(crate) fn response(
+        &self,
+        provider: &str,
+        status: u16,
+        usage: Option<&Usage>,
+        started: Instant,
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match usage {
+            Some(usage) => debug!(
+                target: TRACE_TARGET,
+                provider,
+                status,
+                latency_ms,
+                input_tokens = usage.input_tokens,
+                output_tokens = usage.output_tokens,
+                "provider response"
+            ),
+            None => debug!(target: TRACE_TARGET, provider, status, latency_ms, "provider response"),
+        }
+    }
+
+    /// Log a response body when bodies are included.
+    pub(crate) fn response_body(&self, provider: &str, body: &str, api_key: &str) {
+        if self.enabled && self.include_bodies {
+            debug!(
+                target: TRACE_TARGET,
+                provider,
+                body = %redact_secret(body, api_key),
+                "provider response body"
+            );
+        }
+    }
+}
+
+/// Rough token estimate for logging (~4 characters per token).
+fn estimate_tokens(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .filter_map(|m| m.text())
+        .map(|text| text.len().div_ceil(4))
+        .sum()
+}
+
+/// Redact credential query parameters from a URL.
+pub fn redact_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name) => format!("{}={}", name, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{}?{}", base, query.join("&"))
+}
+
+/// Replace every occurrence of `secret` in `text`.
+fn redact_secret(text: &str, secret: &str) -> String {
+    if secret.is_empty() {
+        text.to_string()
+    } else {
+        text.replace(secret, REDACTED)
+    }
+}
+
+/// Captures formatted log output so tests can assert on what was logged.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct LogCapture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl LogCapture {
+    /// Route debug logs on the current thread into this buffer.
+    pub(crate) fn install(&self) -> tracing::subscriber::DefaultGuard {
+        let writer = self.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    /// Everything logged so far.
+    pub(crate) fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for LogCapture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("https://api.example.com/models/gemini:generateContent?key=AIza-secret"),
+            "https://api.example.com/models/gemini:generateContent?key=[REDACTED]"
+        );
+        assert_eq!(
+            redact_url("https://api.example.com/stream?key=AIza-secret&alt=sse"),
+            "https://api.example.com/stream?key=[REDACTED]&alt=sse"
+        );
+        assert_eq!(
+            redact_url("https://api.example.com/v1/messages"),
+            "https://api.example.com/v1/messages"
+        );
+    }
+
+    #[test]
+    fn test_redact_secret() {
+        assert_eq!(
+            redact_secret("{\"auth\":\"sk-abc\"}", "sk-abc"),
+            "{\"auth\":\"[REDACTED]\"}"
+        );
+        assert_eq!(redact_secret("unchanged", ""), "unchanged");
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        let messages = [Message::user("12345678"), Message::assistant("123")];
+        assert_eq!(estimate_tokens(&messages), 3);
+    }
+}
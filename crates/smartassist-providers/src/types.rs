@@ -93,6 +93,25 @@ impl Message {
         }
     }
 
+    /// Create an assistant message that requested tool calls.
+    ///
+    /// Used to replay the assistant turn before the tool results.
+    pub fn assistant_tool_calls(text: impl Into<String>, calls: &[ToolCall]) -> Self {
+        let text = text.into();
+        let mut parts = Vec::with_capacity(calls.len() + 1);
+        if !text.is_empty() {
+            parts.push(ContentPart::Text(text));
+        }
+        parts.extend(calls.iter().cloned().map(ContentPart::ToolUse));
+
+        Self {
+            role: MessageRole::Assistant,
+            content: MessageContent::Parts(parts),
+            name: None,
+            tool_call_id: None,
+        }
+    }
+
     /// Create a message with image content.
     pub fn with_image(role: MessageRole, text: impl Into<String>, image: ImageContent) -> Self {
         Self {
@@ -106,6 +125,16 @@ impl Message {
         }
     }
 
+    /// Check whether the message contains image content.
+    pub fn has_images(&self) -> bool {
+        match &self.content {
+            MessageContent::Text(_) => false,
+            MessageContent::Parts(parts) => {
+                parts.iter().any(|part| matches!(part, ContentPart::Image(_)))
+            }
+        }
+    }
+
     /// Get the text content of the message.
     pub fn text(&self) -> Option<&str> {
         match &self.content {
@@ -144,7 +173,7 @@ pub enum ContentPart {
     Image(ImageContent),
 
     /// Tool use request.
-    ToolUse(ToolUse),
+    ToolUse(ToolCall),
 
     /// Tool result.
     ToolResult(ToolResultContent),
@@ -194,17 +223,56 @@ pub enum ImageSourceType {
     Url,
 }
 
-/// Tool use request from the model.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolUse {
-    /// Unique ID for this tool use.
+/// A tool call requested by the model.
+///
+/// Every provider normalizes its native shape (Anthropic `tool_use` blocks,
+/// OpenAI `tool_calls`, Gemini `functionCall` parts) into this type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Unique ID for this tool call.
     pub id: String,
 
     /// Tool name.
     pub name: String,
 
     /// Tool arguments as JSON.
-    pub input: serde_json::Value,
+    pub arguments: serde_json::Value,
+}
+
+impl ToolCall {
+    /// Create a new tool call.
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        arguments: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            arguments,
+        }
+    }
+
+    /// Parse raw JSON arguments as sent by the provider.
+    ///
+    /// Empty input becomes an empty object; input that is not valid JSON is
+    /// kept as a string so it can still be reported back to the model.
+    pub fn parse_arguments(raw: &str) -> serde_json::Value {
+        if raw.trim().is_empty() {
+            return serde_json::Value::Object(Default::default());
+        }
+        serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+    }
+
+    /// Build the message that returns this call's result to the model.
+    ///
+    /// Each provider converts it into its own tool-result shape.
+    pub fn result_message(&self, content: impl Into<String>) -> Message {
+        Message {
+            name: Some(self.name.clone()),
+            ..Message::tool_result(&self.id, content)
+        }
+    }
 }
 
 /// Tool result content.
@@ -256,6 +324,20 @@ pub struct ChatOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<String>,
 
+    /// Token budget for extended thinking, where supported.
+    ///
+    /// Must be below `max_tokens`, which includes the thinking tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
+
+    /// Values for `{{name}}` placeholders in system messages.
+    ///
+    /// When set, system messages are rendered before the request is sent
+    /// and an undefined placeholder fails the request. Other messages are
+    /// sent as written. Not sent to the provider.
+    #[serde(skip)]
+    pub variables: Option<crate::TemplateVars>,
+
     /// Additional provider-specific options.
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
@@ -287,6 +369,95 @@ impl ChatOptions {
         self.tool_choice = Some(choice);
         self
     }
+
+    /// Enable extended thinking with a token budget.
+    pub fn thinking_budget(mut self, budget: u32) -> Self {
+        self.thinking_budget = Some(budget);
+        self
+    }
+
+    /// Set the values for `{{name}}` placeholders in system messages.
+    pub fn variables(mut self, variables: crate::TemplateVars) -> Self {
+        self.variables = Some(variables);
+        self
+    }
+
+    /// Fill the sampling parameters left unset with `defaults`.
+    pub fn or_defaults(mut self, defaults: &SamplingDefaults) -> Self {
+        self.max_tokens = self.max_tokens.or(defaults.max_tokens);
+        self.temperature = self.temperature.or(defaults.temperature);
+        self.top_p = self.top_p.or(defaults.top_p);
+        if self.stop.is_none() {
+            self.stop = defaults.stop.clone();
+        }
+        self
+    }
+}
+
+/// Sampling parameters a model uses when the caller leaves them unset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingDefaults {
+    /// Maximum tokens to generate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+
+    /// Temperature for sampling.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Top-p sampling parameter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+
+    /// Stop sequences.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+}
+
+/// Sampling defaults by model.
+///
+/// Providers merge these into the options of every `chat` and
+/// `chat_stream` call: a value set by the caller wins, then the model's
+/// default, then the provider's own. Keys are model IDs; a key ending in
+/// `*` covers every model starting with the rest of it, and the longest
+/// such match is used when no key matches exactly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ModelDefaults {
+    models: HashMap<String, SamplingDefaults>,
+}
+
+impl ModelDefaults {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the defaults for a model ID or `prefix*` pattern.
+    pub fn model(mut self, model: impl Into<String>, defaults: SamplingDefaults) -> Self {
+        self.models.insert(model.into(), defaults);
+        self
+    }
+
+    /// Get the defaults that apply to `model`.
+    pub fn get(&self, model: &str) -> Option<&SamplingDefaults> {
+        self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter_map(|(key, defaults)| Some((key.strip_suffix('*')?, defaults)))
+                .filter(|(prefix, _)| model.starts_with(prefix))
+                .max_by_key(|(prefix, _)| prefix.len())
+                .map(|(_, defaults)| defaults)
+        })
+    }
+
+    /// Merge the caller's options for a request to `model` with its defaults.
+    pub fn apply(&self, model: &str, options: Option<ChatOptions>) -> Option<ChatOptions> {
+        match self.get(model) {
+            Some(defaults) => Some(options.unwrap_or_default().or_defaults(defaults)),
+            None => options,
+        }
+    }
 }
 
 /// Tool definition for function calling.
@@ -330,7 +501,7 @@ pub struct ChatResponse {
 
     /// Tool calls requested by the model.
     #[serde(default)]
-    pub tool_calls: Vec<ToolUse>,
+    pub tool_calls: Vec<ToolCall>,
 
     /// Stop reason.
     pub stop_reason: StopReason,
@@ -404,6 +575,9 @@ pub struct TokenCount {
 }
 
 /// Model information.
+///
+/// Limits, capabilities, and pricing are `None` when the provider does not
+/// publish them for the model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelInfo {
     /// Model ID.
@@ -417,24 +591,85 @@ pub struct ModelInfo {
     pub description: String,
 
     /// Maximum context window.
-    pub context_window: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<usize>,
 
     /// Maximum output tokens.
-    pub max_output: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<usize>,
 
-    /// Input price per million tokens.
-    #[serde(default)]
-    pub input_price: f64,
+    /// Whether the model supports tool use.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_tools: Option<bool>,
 
-    /// Output price per million tokens.
-    #[serde(default)]
-    pub output_price: f64,
+    /// Whether the model accepts image input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supports_vision: Option<bool>,
+
+    /// Input price in USD per million tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_price: Option<f64>,
+
+    /// Output price in USD per million tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_price: Option<f64>,
 
     /// Model capabilities.
     #[serde(default)]
     pub capabilities: Vec<String>,
 }
 
+impl ModelInfo {
+    /// Create model info with no known metadata.
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: String::new(),
+            context_window: None,
+            max_output: None,
+            supports_tools: None,
+            supports_vision: None,
+            input_price: None,
+            output_price: None,
+            capabilities: Vec::new(),
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Set the context window and maximum output tokens.
+    pub fn with_limits(mut self, context_window: usize, max_output: usize) -> Self {
+        self.context_window = Some(context_window);
+        self.max_output = Some(max_output);
+        self
+    }
+
+    /// Set tool and vision support.
+    pub fn with_support(mut self, tools: bool, vision: bool) -> Self {
+        self.supports_tools = Some(tools);
+        self.supports_vision = Some(vision);
+        self
+    }
+
+    /// Set input and output prices in USD per million tokens.
+    pub fn with_pricing(mut self, input_price: f64, output_price: f64) -> Self {
+        self.input_price = Some(input_price);
+        self.output_price = Some(output_price);
+        self
+    }
+
+    /// Set the capability tags.
+    pub fn with_capabilities(mut self, capabilities: &[&str]) -> Self {
+        self.capabilities = capabilities.iter().map(|c| c.to_string()).collect();
+        self
+    }
+}
+
 /// Streaming event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -450,6 +685,11 @@ pub enum StreamEvent {
         delta: String,
     },
 
+    /// Extended thinking delta.
+    Thinking {
+        delta: String,
+    },
+
     /// Tool use started.
     ToolUseStart {
         id: String,
@@ -461,6 +701,9 @@ pub enum StreamEvent {
         delta: String,
     },
 
+    /// Tool call completed, with its arguments fully assembled.
+    ToolCall(ToolCall),
+
     /// Stream completed.
     End {
         stop_reason: StopReason,
@@ -502,6 +745,78 @@ mod tests {
         assert!(matches!(opts.tool_choice, Some(ToolChoice::Auto)));
     }
 
+    fn model_defaults() -> ModelDefaults {
+        ModelDefaults::new()
+            .model(
+                "gpt-4o",
+                SamplingDefaults {
+                    temperature: Some(0.2),
+                    top_p: Some(0.9),
+                    stop: Some(vec!["END".to_string()]),
+                    max_tokens: Some(2048),
+                },
+            )
+            .model(
+                "claude-*",
+                SamplingDefaults {
+                    temperature: Some(1.0),
+                    ..Default::default()
+                },
+            )
+    }
+
+    #[test]
+    fn test_model_defaults_caller_wins() {
+        let caller = ChatOptions::with_max_tokens(100).temperature(0.7);
+        let merged = model_defaults().apply("gpt-4o", Some(caller)).unwrap();
+
+        assert_eq!(merged.max_tokens, Some(100));
+        assert_eq!(merged.temperature, Some(0.7));
+        // Fields the caller left unset come from the model.
+        assert_eq!(merged.top_p, Some(0.9));
+        assert_eq!(merged.stop, Some(vec!["END".to_string()]));
+    }
+
+    #[test]
+    fn test_model_defaults_fill_unset_fields() {
+        let defaults = model_defaults();
+
+        let merged = defaults.apply("gpt-4o", None).unwrap();
+        assert_eq!(merged.max_tokens, Some(2048));
+        assert_eq!(merged.temperature, Some(0.2));
+
+        // Left unset by both, so the provider's own default applies.
+        let merged = defaults.apply("claude-sonnet-4", None).unwrap();
+        assert_eq!(merged.temperature, Some(1.0));
+        assert_eq!(merged.max_tokens, None);
+        assert_eq!(merged.stop, None);
+
+        assert!(defaults.apply("gemini-2.0-flash", None).is_none());
+        let caller = ChatOptions::default().temperature(0.5);
+        let merged = defaults.apply("gemini-2.0-flash", Some(caller)).unwrap();
+        assert_eq!(merged.temperature, Some(0.5));
+    }
+
+    #[test]
+    fn test_model_defaults_longest_prefix() {
+        let defaults = model_defaults().model(
+            "claude-opus-*",
+            SamplingDefaults {
+                temperature: Some(0.3),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(defaults.get("claude-opus-4").unwrap().temperature, Some(0.3));
+        assert_eq!(defaults.get("claude-haiku").unwrap().temperature, Some(1.0));
+        assert_eq!(defaults.get("gpt-4o").unwrap().temperature, Some(0.2));
+        assert!(defaults.get("gpt-4o-mini").is_none());
+
+        let json = serde_json::json!({ "gpt-4o-mini": { "temperature": 0.0 } });
+        let parsed: ModelDefaults = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.get("gpt-4o-mini").unwrap().temperature, Some(0.0));
+    }
+
     #[test]
     fn test_usage() {
         let usage = Usage {
@@ -522,5 +837,9 @@ mod tests {
 
         let url_img = ImageContent::url("https://example.com/img.png");
         assert_eq!(url_img.source_type, ImageSourceType::Url);
+
+        let msg = Message::with_image(MessageRole::User, "What is this?", img);
+        assert!(msg.has_images());
+        assert!(!Message::user("Hello!").has_images());
     }
 }
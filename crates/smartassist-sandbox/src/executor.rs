@@ -7,8 +7,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
 use tokio::time::timeout;
 use tracing::{debug, warn};
@@ -98,11 +99,62 @@ impl ExecutionContext {
     }
 }
 
+/// How a command finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ExitStatus {
+    /// Exited on its own with this status code.
+    Exited(i32),
+
+    /// Killed by this signal (Unix).
+    Signaled(i32),
+
+    /// Killed for running past the wall-clock timeout.
+    TimedOut,
+
+    /// Killed by a signal that dumped core (Unix).
+    CoreDumped,
+}
+
+impl ExitStatus {
+    /// Exit code, if the command exited on its own.
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Self::Exited(code) => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Check if the command exited with code 0.
+    pub fn success(&self) -> bool {
+        *self == Self::Exited(0)
+    }
+}
+
+impl From<std::process::ExitStatus> for ExitStatus {
+    fn from(status: std::process::ExitStatus) -> Self {
+        if let Some(code) = status.code() {
+            return Self::Exited(code);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if status.core_dumped() {
+                return Self::CoreDumped;
+            }
+            if let Some(signal) = status.signal() {
+                return Self::Signaled(signal);
+            }
+        }
+        Self::Exited(-1)
+    }
+}
+
 /// Output from command execution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionOutput {
-    /// Exit code (0 for success).
-    pub exit_code: i32,
+    /// How the command finished.
+    pub status: ExitStatus,
 
     /// Standard output.
     pub stdout: String,
@@ -124,12 +176,21 @@ pub struct ExecutionOutput {
 
     /// Signal that killed the process (if any).
     pub signal: Option<i32>,
+
+    /// Whether output beyond the size limit was dropped.
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 impl ExecutionOutput {
+    /// Exit code (0 for success), or -1 if the command did not exit on its own.
+    pub fn exit_code(&self) -> i32 {
+        self.status.code().unwrap_or(-1)
+    }
+
     /// Check if execution was successful.
     pub fn success(&self) -> bool {
-        self.exit_code == 0 && !self.timed_out && !self.resource_limited
+        self.status.success() && !self.resource_limited
     }
 
     /// Get output, preferring combined if available.
@@ -143,7 +204,7 @@ pub struct CommandExecutor {
     /// Execution context.
     context: ExecutionContext,
 
-    /// Maximum output size to capture.
+    /// Maximum bytes captured across stdout and stderr.
     max_output_size: usize,
 }
 
@@ -156,7 +217,9 @@ impl CommandExecutor {
         }
     }
 
-    /// Set maximum output size.
+    /// Set the maximum bytes captured across stdout and stderr.
+    ///
+    /// Output past the limit is dropped and a truncation marker appended.
     pub fn with_max_output_size(mut self, size: usize) -> Self {
         self.max_output_size = size;
         self
@@ -190,7 +253,7 @@ impl CommandExecutor {
             Err(_) => {
                 // Timeout occurred
                 Ok(ExecutionOutput {
-                    exit_code: -1,
+                    status: ExitStatus::TimedOut,
                     stdout: String::new(),
                     stderr: format!("Command timed out after {} seconds", timeout_duration.as_secs()),
                     combined: None,
@@ -198,6 +261,7 @@ impl CommandExecutor {
                     timed_out: true,
                     resource_limited: false,
                     signal: Some(9), // SIGKILL
+                    truncated: false,
                 })
             }
         }
@@ -220,9 +284,12 @@ impl CommandExecutor {
             .env_clear()
             .envs(&env);
 
-        // Apply platform-specific sandbox settings
+        // Apply platform-specific sandbox settings. The network namespace,
+        // if any, is torn down when this function returns.
+        #[cfg(target_os = "linux")]
+        let network = self.network_namespace().await?;
         #[cfg(target_os = "linux")]
-        self.apply_linux_sandbox(&mut cmd)?;
+        self.apply_linux_sandbox(&mut cmd, network.as_ref())?;
 
         #[cfg(target_os = "macos")]
         self.apply_macos_sandbox(&mut cmd)?;
@@ -234,18 +301,19 @@ impl CommandExecutor {
         let stdout_handle = child.stdout.take();
         let stderr_handle = child.stderr.take();
 
-        // Read stdout and stderr concurrently
+        // Read stdout and stderr concurrently, sharing one size budget
+        let budget = AtomicUsize::new(self.max_output_size);
         let (stdout, stderr) = tokio::join!(
-            read_stream(stdout_handle, self.max_output_size),
-            read_stream(stderr_handle, self.max_output_size),
+            read_stream(stdout_handle, &budget),
+            read_stream(stderr_handle, &budget),
         );
+        let truncated = stdout.1 || stderr.1;
 
         let status = child.wait().await.map_err(|e| {
             SandboxError::execution_failed(format!("Failed to wait for command: {}", e))
         })?;
 
-        let exit_code = status.code().unwrap_or(-1);
-        let signal = if !status.success() && status.code().is_none() {
+        let signal = if status.code().is_none() {
             #[cfg(unix)]
             {
                 use std::os::unix::process::ExitStatusExt;
@@ -260,14 +328,15 @@ impl CommandExecutor {
         };
 
         Ok(ExecutionOutput {
-            exit_code,
-            stdout: stdout.unwrap_or_default(),
-            stderr: stderr.unwrap_or_default(),
+            status: status.into(),
+            stdout: captured_text(stdout, self.max_output_size),
+            stderr: captured_text(stderr, self.max_output_size),
             combined: None,
             duration_ms: 0, // Set by caller
             timed_out: false,
             resource_limited: false,
             signal,
+            truncated,
         })
     }
 
@@ -305,10 +374,58 @@ impl CommandExecutor {
         env
     }
 
+    /// Whether the command gets a network namespace of its own: when it
+    /// has no network access, or CIDR egress rules to load into it.
     #[cfg(target_os = "linux")]
-    fn apply_linux_sandbox(&self, _cmd: &mut Command) -> Result<()> {
-        // Linux-specific sandbox setup would be done in a pre_exec hook
-        // This is a placeholder for the actual implementation
+    fn isolates_network(&self) -> bool {
+        let network = &self.context.profile.network;
+        !network.enabled || !network.egress_rules().is_empty()
+    }
+
+    /// Set up the network namespace the command joins, with its egress
+    /// rules loaded and an uplink if they allow traffic beyond localhost.
+    ///
+    /// If it cannot be set up the command fails to start rather than run
+    /// unconfined.
+    #[cfg(target_os = "linux")]
+    async fn network_namespace(&self) -> Result<Option<crate::linux::NetworkNamespace>> {
+        if !self.isolates_network() {
+            return Ok(None);
+        }
+        let rules = self.context.profile.network.clone();
+        tokio::task::spawn_blocking(move || crate::linux::NetworkNamespace::create(&rules))
+            .await
+            .map_err(|e| SandboxError::setup_failed(format!("Network setup failed: {}", e)))?
+            .map(Some)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply_linux_sandbox(
+        &self,
+        cmd: &mut Command,
+        network: Option<&crate::linux::NetworkNamespace>,
+    ) -> Result<()> {
+        if let Some(network) = network {
+            use caps::Capability;
+            let join = network.joiner()?;
+            // SAFETY: the closure only calls setns(2), capset(2) and
+            // prctl(2), which are safe to run between fork and exec.
+            unsafe {
+                cmd.pre_exec(move || {
+                    join()?;
+                    // Without CAP_NET_ADMIN the command cannot unload its
+                    // egress rules, and without CAP_SYS_ADMIN it cannot
+                    // setns(2) back into the host's network namespace.
+                    crate::linux::capabilities::drop_before_exec(&[
+                        Capability::CAP_NET_ADMIN,
+                        Capability::CAP_SYS_ADMIN,
+                    ])
+                    .map_err(std::io::Error::other)
+                });
+            }
+        }
+
+        // Other Linux-specific sandbox setup would be done in the same hook
         Ok(())
     }
 
@@ -330,50 +447,57 @@ impl CommandExecutor {
     }
 }
 
-/// Read from an async stream with size limit.
+/// Read a stream in chunks, keeping bytes while the shared budget lasts.
+///
+/// Returns the kept bytes and whether the budget ran out. Reading stops at
+/// that point, so a command still writing gets a broken pipe instead of
+/// having its output held in memory.
 async fn read_stream(
     handle: Option<impl tokio::io::AsyncRead + Unpin>,
-    max_size: usize,
-) -> Option<String> {
-    let handle = handle?;
-    let mut reader = BufReader::new(handle);
+    budget: &AtomicUsize,
+) -> (Vec<u8>, bool) {
+    let Some(mut handle) = handle else {
+        return (Vec::new(), false);
+    };
     let mut output = Vec::new();
-    let mut total_read = 0;
+    let mut chunk = [0u8; 8192];
 
     loop {
-        let mut line = String::new();
-        match reader.read_line(&mut line).await {
-            Ok(0) => break, // EOF
-            Ok(n) => {
-                total_read += n;
-                if total_read > max_size {
-                    output.extend_from_slice(b"\n[Output truncated]\n");
-                    break;
-                }
-                output.extend_from_slice(line.as_bytes());
-            }
+        let n = match handle.read(&mut chunk).await {
+            Ok(0) => return (output, false), // EOF
+            Ok(n) => n,
             Err(e) => {
                 warn!("Error reading stream: {}", e);
-                break;
+                return (output, false);
             }
+        };
+
+        let granted = budget
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                Some(left.saturating_sub(n))
+            })
+            .map_or(0, |left| left.min(n));
+        output.extend_from_slice(&chunk[..granted]);
+        if granted < n {
+            return (output, true);
         }
     }
+}
 
-    String::from_utf8(output).ok()
+/// Decode captured output, marking it if it was cut short.
+fn captured_text((bytes, truncated): (Vec<u8>, bool), max_size: usize) -> String {
+    let mut text = String::from_utf8_lossy(&bytes).into_owned();
+    if truncated {
+        text.push_str(&format!("\n[Output truncated at {} bytes]\n", max_size));
+    }
+    text
 }
 
 /// Execute a simple command without sandboxing.
 pub async fn execute_simple(command: &str, cwd: Option<&PathBuf>) -> Result<ExecutionOutput> {
     let context = ExecutionContext {
         cwd: cwd.cloned().unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"))),
-        profile: SandboxProfile {
-            name: "none".to_string(),
-            environment: crate::profile::EnvironmentRules {
-                inherit: true,
-                ..Default::default()
-            },
-            ..Default::default()
-        },
+        profile: SandboxProfile::none(),
         ..Default::default()
     };
 
@@ -401,11 +525,60 @@ mod tests {
         assert!(result.success());
     }
 
+    #[tokio::test]
+    async fn test_output_size_cap() {
+        let executor = CommandExecutor::new(ExecutionContext::default()).with_max_output_size(1000);
+
+        let result = executor
+            .execute("head -c 100000 /dev/zero | tr '\\0' 'a'; echo done >&2")
+            .await
+            .unwrap();
+        assert!(result.truncated);
+        assert!(result.stdout.starts_with(&"a".repeat(1000)));
+        assert!(result.stdout.ends_with("[Output truncated at 1000 bytes]\n"));
+        assert!(result.stdout.len() + result.stderr.len() < 1100);
+
+        let result = executor
+            .execute("head -c 1000 /dev/zero | tr '\\0' 'a'")
+            .await
+            .unwrap();
+        assert!(!result.truncated);
+        assert_eq!(result.stdout, "a".repeat(1000));
+    }
+
     #[tokio::test]
     async fn test_execution_failure() {
         let result = execute_simple("exit 1", None).await.unwrap();
         assert!(!result.success());
-        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.exit_code(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exit_status_nonzero_exit() {
+        let result = execute_simple("exit 2", None).await.unwrap();
+        assert_eq!(result.status, ExitStatus::Exited(2));
+        assert_eq!(result.exit_code(), 2);
+        assert_eq!(result.signal, None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exit_status_killed_by_signal() {
+        let result = execute_simple("kill -KILL $$", None).await.unwrap();
+        assert_eq!(result.status, ExitStatus::Signaled(9));
+        assert_eq!(result.exit_code(), -1);
+        assert_eq!(result.signal, Some(9));
+        assert!(!result.success());
+    }
+
+    #[tokio::test]
+    async fn test_exit_status_timed_out() {
+        let executor = CommandExecutor::new(ExecutionContext::default());
+        let result = executor.execute_with_timeout("sleep 5", Some(0)).await.unwrap();
+        assert_eq!(result.status, ExitStatus::TimedOut);
+        assert!(result.timed_out);
+        assert!(!result.success());
     }
 
     #[tokio::test]
@@ -428,4 +601,49 @@ mod tests {
         assert!(env.contains_key("PATH") || env.is_empty()); // PATH may or may not be set
         assert!(!env.contains_key("LD_PRELOAD"));
     }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    #[ignore = "requires nft and slirp4netns"]
+    async fn test_egress_rules_are_enforced() {
+        use crate::profile::NetworkRules;
+
+        // Any non-loopback address of this host is reachable over the uplink.
+        let probe = std::net::UdpSocket::bind("0.0.0.0:0").unwrap();
+        probe.connect("192.0.2.1:9").unwrap();
+        let host = probe.local_addr().unwrap().ip();
+        let listener = std::net::TcpListener::bind((host, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let host_cidr = format!("{}/32", host).parse().unwrap();
+
+        let run = |network: NetworkRules, command: String| async move {
+            let context = ExecutionContext {
+                shell: "/bin/bash".to_string(),
+                ..ExecutionContext::default().with_profile(SandboxProfile {
+                    network,
+                    ..SandboxProfile::standard()
+                })
+            };
+            CommandExecutor::new(context)
+                .execute_with_timeout(&command, Some(5))
+                .await
+                .unwrap()
+        };
+        let connect = format!("exec 3<>/dev/tcp/{}/{}", host, port);
+
+        let allowed = NetworkRules::builder().enabled(true).allow_cidr(host_cidr).build();
+        let result = run(allowed, connect.clone()).await;
+        assert!(result.success(), "allowed connect failed: {}", result.stderr);
+
+        let denied = NetworkRules::builder()
+            .enabled(true)
+            .allow_cidr("0.0.0.0/0".parse().unwrap())
+            .deny_cidr(host_cidr)
+            .build();
+        assert!(!run(denied.clone(), connect.clone()).await.success());
+
+        // The command cannot unload its own rules.
+        let result = run(denied, format!("nft flush ruleset; {}", connect)).await;
+        assert!(!result.success());
+    }
 }
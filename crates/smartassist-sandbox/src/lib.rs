@@ -18,7 +18,7 @@ pub mod linux;
 pub mod macos;
 
 pub use error::SandboxError;
-pub use executor::{CommandExecutor, ExecutionContext, ExecutionOutput};
+pub use executor::{CommandExecutor, ExecutionContext, ExecutionOutput, ExitStatus};
 pub use limits::ResourceLimits;
 pub use profile::{SandboxProfile, ProfileBuilder};
 pub use pty::{PtySession, PtyConfig};
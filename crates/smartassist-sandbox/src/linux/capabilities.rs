@@ -96,6 +96,63 @@ impl CapabilitySet {
         Ok(())
     }
 
+    /// Apply the capability set, then confirm from procfs that the kernel
+    /// made every change it was asked to.
+    ///
+    /// Returns an error naming any capability left behind or not kept,
+    /// which guards against partial drops.
+    pub fn apply_and_verify(&self) -> Result<()> {
+        self.apply()?;
+        self.verify()
+    }
+
+    /// Check the current thread's capabilities against this capability set.
+    ///
+    /// When dropping all, no set may hold a capability outside the kept
+    /// ones. Every kept capability must be in the ambient set, where
+    /// [`apply`](Self::apply) raises it for child processes.
+    pub fn verify(&self) -> Result<()> {
+        // Capabilities are per-thread, so prefer the calling thread's status.
+        let status = std::fs::read_to_string("/proc/thread-self/status")
+            .or_else(|_| std::fs::read_to_string("/proc/self/status"))?;
+        self.verify_status(&status)?;
+
+        debug!("Verified capability drop");
+        Ok(())
+    }
+
+    /// Check a `/proc/<pid>/status` listing against this capability set.
+    fn verify_status(&self, status: &str) -> Result<()> {
+        if self.drop_all {
+            for (field, set) in [
+                ("CapEff", "effective"),
+                ("CapPrm", "permitted"),
+                ("CapInh", "inheritable"),
+                ("CapBnd", "bounding"),
+                ("CapAmb", "ambient"),
+            ] {
+                let current = parse_capabilities(status, field)?;
+                let lingering = current.into_iter().filter(|cap| !self.keep.contains(cap));
+                if let Some(names) = capability_names(lingering) {
+                    return Err(SandboxError::Capability(format!(
+                        "Capabilities still in the {} set after dropping: {}",
+                        set, names
+                    )));
+                }
+            }
+        }
+
+        let ambient = parse_capabilities(status, "CapAmb")?;
+        let missing = self.keep.iter().copied().filter(|cap| !ambient.contains(cap));
+        if let Some(names) = capability_names(missing) {
+            return Err(SandboxError::Capability(format!(
+                "Kept capabilities missing from the ambient set: {}",
+                names
+            )));
+        }
+        Ok(())
+    }
+
     /// Drop all capabilities from all sets.
     fn drop_capabilities(&self) -> Result<()> {
         // Clear bounding set
@@ -166,6 +223,32 @@ impl CapabilitySet {
     }
 }
 
+/// Parse a capability mask line such as `CapEff:\t000001ffffffffff` from
+/// `/proc/<pid>/status`.
+fn parse_capabilities(status: &str, field: &str) -> Result<HashSet<Capability>> {
+    let mask = status
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))
+        .ok_or_else(|| SandboxError::Capability(format!("{} missing from status", field)))?;
+    let mask = u64::from_str_radix(mask.trim(), 16)
+        .map_err(|e| SandboxError::Capability(format!("Invalid {} mask: {}", field, e)))?;
+
+    Ok(caps::all()
+        .into_iter()
+        .filter(|cap| mask & cap.bitmask() != 0)
+        .collect())
+}
+
+/// Sorted, comma-separated names of `caps`, or `None` if there are none.
+fn capability_names(caps: impl Iterator<Item = Capability>) -> Option<String> {
+    let mut names: Vec<String> = caps.map(|cap| cap.to_string()).collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort();
+    Some(names.join(", "))
+}
+
 /// Common dangerous capabilities that should typically be dropped.
 pub const DANGEROUS_CAPS: &[Capability] = &[
     Capability::CAP_SYS_ADMIN,
@@ -202,6 +285,21 @@ pub fn drop_dangerous_capabilities() -> Result<()> {
     Ok(())
 }
 
+/// Keep the next `execve` from granting `caps`, even to root.
+///
+/// Drops them from the bounding set and clears the inheritable and ambient
+/// sets, so file capabilities cannot bring them back either. Only makes
+/// system calls when it succeeds, so it can run in a `pre_exec` hook.
+pub fn drop_before_exec(caps: &[Capability]) -> Result<()> {
+    let error = |e: caps::errors::CapsError| SandboxError::Capability(e.to_string());
+    for cap in caps {
+        caps::drop(None, CapSet::Bounding, *cap).map_err(error)?;
+    }
+    caps::clear(None, CapSet::Ambient).map_err(error)?;
+    caps::clear(None, CapSet::Inheritable).map_err(error)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,6 +328,102 @@ mod tests {
         assert!(!caps.keep.contains(&Capability::CAP_SYS_ADMIN));
     }
 
+    #[test]
+    fn test_parse_capabilities() {
+        let status = "Name:\tcat\nCapInh:\t0000000000000000\nCapEff:\t0000000000002001\n";
+        let caps = parse_capabilities(status, "CapEff").unwrap();
+        assert_eq!(
+            caps,
+            HashSet::from([Capability::CAP_CHOWN, Capability::CAP_NET_RAW])
+        );
+        assert!(parse_capabilities(status, "CapInh").unwrap().is_empty());
+        assert!(parse_capabilities(status, "CapPrm").is_err());
+    }
+
+    /// Status lines for the given masks of the effective, permitted,
+    /// inheritable, bounding and ambient sets.
+    fn status(eff: u64, prm: u64, inh: u64, bnd: u64, amb: u64) -> String {
+        format!(
+            "Name:\tcat\nCapInh:\t{:016x}\nCapPrm:\t{:016x}\nCapEff:\t{:016x}\n\
+             CapBnd:\t{:016x}\nCapAmb:\t{:016x}\n",
+            inh, prm, eff, bnd, amb
+        )
+    }
+
+    #[test]
+    fn test_verify_status_checks_every_set() {
+        let chown = Capability::CAP_CHOWN.bitmask();
+        let net_raw = Capability::CAP_NET_RAW.bitmask();
+        let set = CapabilitySet::with_capabilities([Capability::CAP_CHOWN]);
+
+        set.verify_status(&status(chown, chown, chown, chown, chown)).unwrap();
+
+        for (field, status) in [
+            ("effective", status(chown | net_raw, chown, chown, chown, chown)),
+            ("permitted", status(chown, chown | net_raw, chown, chown, chown)),
+            ("inheritable", status(chown, chown, chown | net_raw, chown, chown)),
+            ("bounding", status(chown, chown, chown, chown | net_raw, chown)),
+            ("ambient", status(chown, chown, chown, chown, chown | net_raw)),
+        ] {
+            let err = set.verify_status(&status).unwrap_err().to_string();
+            assert!(err.contains(field), "{}", err);
+            assert!(err.contains("CAP_NET_RAW"), "{}", err);
+        }
+
+        let err = set
+            .verify_status(&status(chown, chown, chown, chown, 0))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("missing from the ambient set: CAP_CHOWN"), "{}", err);
+
+        // Without drop_all, only the kept capabilities are checked.
+        let set = set.drop_all(false);
+        set.verify_status(&status(!0, !0, 0, !0, chown)).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs CAP_SETPCAP; run as root with --ignored"]
+    fn test_drop_net_raw_and_verify() {
+        // Capabilities are per-thread; drop them on a throwaway thread so the
+        // rest of the test process keeps its own.
+        std::thread::spawn(|| {
+            assert!(
+                CapabilitySet::has_capability(Capability::CAP_SETPCAP).unwrap(),
+                "dropping capabilities needs CAP_SETPCAP"
+            );
+
+            let mut keep = CapabilitySet::current_permitted().unwrap();
+            keep.remove(&Capability::CAP_NET_RAW);
+            let set = CapabilitySet::with_capabilities(keep);
+            set.apply_and_verify().unwrap();
+
+            assert!(!CapabilitySet::has_capability(Capability::CAP_NET_RAW).unwrap());
+            assert!(!CapabilitySet::current_permitted()
+                .unwrap()
+                .contains(&Capability::CAP_NET_RAW));
+            assert!(CapabilitySet::has_capability(Capability::CAP_SETPCAP).unwrap());
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs CAP_NET_RAW; run as root with --ignored"]
+    fn test_verify_reports_lingering_capabilities() {
+        std::thread::spawn(|| {
+            assert!(
+                CapabilitySet::has_capability(Capability::CAP_NET_RAW).unwrap(),
+                "CAP_NET_RAW is not held"
+            );
+
+            // Nothing was dropped, so verifying a drop-all set must fail.
+            let err = CapabilitySet::new().verify().unwrap_err().to_string();
+            assert!(err.contains("CAP_NET_RAW"), "{}", err);
+        })
+        .join()
+        .unwrap();
+    }
+
     #[test]
     fn test_builder_pattern() {
         let caps = CapabilitySet::new()
@@ -6,15 +6,17 @@ use crate::error::SandboxError;
 use crate::profile::FilesystemRules;
 use crate::Result;
 use landlock::{
-    Access, AccessFs, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
+    Access, AccessFs, BitFlags, PathBeneath, PathFd, Ruleset, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr, RulesetStatus, ABI,
 };
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::{debug, warn};
 
 /// Landlock ruleset builder and applier.
 pub struct LandlockRuleset {
     rules: FilesystemRules,
-    workspace: Option<std::path::PathBuf>,
+    workspace: Option<PathBuf>,
 }
 
 impl LandlockRuleset {
@@ -26,8 +28,13 @@ impl LandlockRuleset {
         }
     }
 
+    /// Create a ruleset enforcing a profile's filesystem rules.
+    pub fn from_filesystem_rules(rules: &FilesystemRules) -> Self {
+        Self::new(rules.clone())
+    }
+
     /// Set the workspace directory (will be granted write access if allowed).
-    pub fn with_workspace(mut self, workspace: impl Into<std::path::PathBuf>) -> Self {
+    pub fn with_workspace(mut self, workspace: impl Into<PathBuf>) -> Self {
         self.workspace = Some(workspace.into());
         self
     }
@@ -40,126 +47,108 @@ impl LandlockRuleset {
             .is_ok()
     }
 
-    /// Build and apply the landlock ruleset.
-    pub fn apply(&self) -> Result<()> {
-        if !Self::is_available() {
-            warn!("Landlock is not available on this system");
-            return Ok(());
-        }
-
+    /// Build and apply the landlock ruleset to the calling thread.
+    ///
+    /// Returns whether the ruleset is enforced. On kernels without Landlock
+    /// this logs a warning and returns `false` without restricting anything.
+    pub fn apply(&self) -> Result<bool> {
         let abi = ABI::V3; // Use latest ABI
 
-        // Create ruleset with all possible access rights
+        // Handle every access right so anything not granted below is denied
         let mut ruleset = Ruleset::new()
             .handle_access(AccessFs::from_all(abi))
+            .map_err(|e| SandboxError::Landlock(e.to_string()))?
+            .create()
             .map_err(|e| SandboxError::Landlock(e.to_string()))?;
 
-        // Add read paths
-        for path in &self.rules.read_paths {
-            if let Err(e) = self.add_read_rule(&mut ruleset, path) {
-                warn!("Failed to add read rule for {:?}: {}", path, e);
+        for (path, access) in self.path_rules(abi) {
+            if let Err(e) = Self::add_rule(&mut ruleset, &path, access, abi) {
+                warn!("Failed to add landlock rule for {:?}: {}", path, e);
             }
         }
 
-        // Add write paths
-        for path in &self.rules.write_paths {
-            if let Err(e) = self.add_write_rule(&mut ruleset, path) {
-                warn!("Failed to add write rule for {:?}: {}", path, e);
+        for blocked in &self.rules.blocked_paths {
+            if let Some((allowed, _)) = self
+                .path_rules(abi)
+                .into_iter()
+                .find(|(path, _)| blocked.starts_with(path))
+            {
+                warn!(
+                    "Landlock cannot block {:?} beneath allowed path {:?}",
+                    blocked, allowed
+                );
             }
         }
 
-        // Add exec paths
-        for path in &self.rules.exec_paths {
-            if let Err(e) = self.add_exec_rule(&mut ruleset, path) {
-                warn!("Failed to add exec rule for {:?}: {}", path, e);
+        let status = ruleset
+            .set_no_new_privs(true)
+            .restrict_self()
+            .map_err(|e| SandboxError::Landlock(e.to_string()))?;
+
+        match status.ruleset {
+            RulesetStatus::FullyEnforced => debug!("Landlock ruleset applied successfully"),
+            RulesetStatus::PartiallyEnforced => {
+                warn!("Landlock ruleset only partially enforced by this kernel")
+            }
+            RulesetStatus::NotEnforced => {
+                warn!("Landlock is not available on this system; filesystem rules not enforced");
+                return Ok(false);
             }
         }
+        Ok(true)
+    }
 
-        // Add tmp access if allowed
+    /// Paths to grant and their access rights, merged per path.
+    ///
+    /// Read paths may be read and listed, exec paths may also be executed,
+    /// and write paths (plus `/tmp` and the workspace, when allowed) may be
+    /// read and modified.
+    fn path_rules(&self, abi: ABI) -> BTreeMap<PathBuf, BitFlags<AccessFs>> {
+        let read = AccessFs::ReadFile | AccessFs::ReadDir;
+        let exec = AccessFs::ReadFile | AccessFs::Execute;
+        let write = read | AccessFs::from_write(abi);
+
+        let mut writable = self.rules.write_paths.clone();
         if self.rules.allow_tmp {
-            if let Err(e) = self.add_write_rule(&mut ruleset, Path::new("/tmp")) {
-                warn!("Failed to add /tmp rule: {}", e);
-            }
+            writable.push(PathBuf::from("/tmp"));
         }
-
-        // Add workspace access if allowed
         if self.rules.allow_workspace {
-            if let Some(ref workspace) = self.workspace {
-                if let Err(e) = self.add_write_rule(&mut ruleset, workspace) {
-                    warn!("Failed to add workspace rule: {}", e);
-                }
-            }
+            writable.extend(self.workspace.clone());
         }
 
-        // Create and enforce the ruleset
-        let ruleset = ruleset
-            .create()
-            .map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        ruleset
-            .set_no_new_privs(true)
-            .restrict_self()
-            .map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        debug!("Landlock ruleset applied successfully");
-        Ok(())
-    }
-
-    /// Add a read-only rule for a path.
-    fn add_read_rule(&self, ruleset: &mut Ruleset, path: &Path) -> Result<()> {
-        if !path.exists() {
-            return Ok(()); // Skip non-existent paths
+        let mut paths: BTreeMap<PathBuf, BitFlags<AccessFs>> = BTreeMap::new();
+        let grants = [
+            (&self.rules.read_paths, read),
+            (&self.rules.exec_paths, exec),
+            (&writable, write),
+        ];
+        for (list, access) in grants {
+            for path in list {
+                *paths.entry(path.clone()).or_insert_with(BitFlags::empty) |= access;
+            }
         }
-
-        let fd = PathFd::new(path).map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        let access = AccessFs::ReadFile | AccessFs::ReadDir;
-
-        ruleset
-            .add_rule(PathBeneath::new(fd, access))
-            .map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        Ok(())
+        paths
     }
 
-    /// Add a read-write rule for a path.
-    fn add_write_rule(&self, ruleset: &mut Ruleset, path: &Path) -> Result<()> {
+    /// Grant `access` beneath `path`.
+    fn add_rule(
+        ruleset: &mut RulesetCreated,
+        path: &Path,
+        access: BitFlags<AccessFs>,
+        abi: ABI,
+    ) -> Result<()> {
         if !path.exists() {
             return Ok(()); // Skip non-existent paths
         }
 
-        let fd = PathFd::new(path).map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        let access = AccessFs::ReadFile
-            | AccessFs::ReadDir
-            | AccessFs::WriteFile
-            | AccessFs::RemoveFile
-            | AccessFs::RemoveDir
-            | AccessFs::MakeChar
-            | AccessFs::MakeDir
-            | AccessFs::MakeReg
-            | AccessFs::MakeSock
-            | AccessFs::MakeFifo
-            | AccessFs::MakeBlock
-            | AccessFs::MakeSym;
-
-        ruleset
-            .add_rule(PathBeneath::new(fd, access))
-            .map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        Ok(())
-    }
-
-    /// Add an execute rule for a path.
-    fn add_exec_rule(&self, ruleset: &mut Ruleset, path: &Path) -> Result<()> {
-        if !path.exists() {
-            return Ok(()); // Skip non-existent paths
-        }
+        // Directory-only rights are invalid on a file
+        let access = if path.is_dir() {
+            access
+        } else {
+            access & AccessFs::from_file(abi)
+        };
 
         let fd = PathFd::new(path).map_err(|e| SandboxError::Landlock(e.to_string()))?;
-
-        let access = AccessFs::ReadFile | AccessFs::Execute;
-
         ruleset
             .add_rule(PathBeneath::new(fd, access))
             .map_err(|e| SandboxError::Landlock(e.to_string()))?;
@@ -178,7 +167,7 @@ pub mod profiles {
     }
 
     /// Create a standard landlock ruleset.
-    pub fn standard(workspace: impl Into<std::path::PathBuf>) -> LandlockRuleset {
+    pub fn standard(workspace: impl Into<PathBuf>) -> LandlockRuleset {
         LandlockRuleset::new(FilesystemRules::workspace_write()).with_workspace(workspace)
     }
 }
@@ -199,6 +188,56 @@ mod tests {
         assert!(ruleset.workspace.is_none());
     }
 
+    #[test]
+    fn test_path_rules_map_access() {
+        let rules = FilesystemRules {
+            read_paths: vec![PathBuf::from("/usr")],
+            write_paths: vec![PathBuf::from("/data")],
+            exec_paths: vec![PathBuf::from("/usr")],
+            blocked_paths: vec![],
+            allow_tmp: true,
+            allow_workspace: true,
+        };
+        let ruleset = LandlockRuleset::from_filesystem_rules(&rules).with_workspace("/work");
+        let paths = ruleset.path_rules(ABI::V3);
+
+        let usr = paths[Path::new("/usr")];
+        assert!(usr.contains(AccessFs::ReadDir | AccessFs::Execute));
+        assert!(!usr.contains(AccessFs::WriteFile));
+        for writable in ["/data", "/tmp", "/work"] {
+            let access = paths[Path::new(writable)];
+            assert!(access.contains(AccessFs::WriteFile | AccessFs::MakeReg | AccessFs::ReadFile));
+            assert!(!access.contains(AccessFs::Execute));
+        }
+    }
+
+    #[test]
+    #[ignore = "requires landlock-enforcing kernel; run with --ignored"]
+    fn test_write_outside_allowed_path_denied() {
+        let allowed = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let rules = FilesystemRules {
+            read_paths: vec![],
+            write_paths: vec![allowed.path().to_path_buf()],
+            exec_paths: vec![],
+            blocked_paths: vec![],
+            allow_tmp: false,
+            allow_workspace: false,
+        };
+
+        // Landlock restricts only the calling thread.
+        let (inside, outside) = (allowed.path().join("ok"), outside.path().join("denied"));
+        std::thread::spawn(move || {
+            assert!(LandlockRuleset::from_filesystem_rules(&rules).apply().unwrap());
+
+            std::fs::write(&inside, "allowed").unwrap();
+            let err = std::fs::write(&outside, "denied").unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        })
+        .join()
+        .unwrap();
+    }
+
     #[test]
     fn test_ruleset_with_workspace() {
         let ruleset = LandlockRuleset::new(FilesystemRules::workspace_write())
@@ -206,7 +245,7 @@ mod tests {
 
         assert_eq!(
             ruleset.workspace,
-            Some(std::path::PathBuf::from("/tmp/test"))
+            Some(PathBuf::from("/tmp/test"))
         );
     }
 }
@@ -4,6 +4,7 @@
 //! - seccomp: System call filtering
 //! - landlock: Filesystem sandboxing
 //! - namespaces: Process/network/mount isolation
+//! - netfilter: CIDR egress rules inside a network namespace
 //! - capabilities: Privilege dropping
 
 #[cfg(target_os = "linux")]
@@ -15,17 +16,23 @@ pub mod landlock;
 #[cfg(target_os = "linux")]
 pub mod namespace;
 
+#[cfg(target_os = "linux")]
+pub mod netfilter;
+
 #[cfg(target_os = "linux")]
 pub mod capabilities;
 
 #[cfg(target_os = "linux")]
-pub use self::seccomp::SeccompFilter;
+pub use self::seccomp::{DenyAction, SeccompBase, SeccompFilter, SeccompProfile};
 
 #[cfg(target_os = "linux")]
 pub use self::landlock::LandlockRuleset;
 
 #[cfg(target_os = "linux")]
-pub use self::namespace::NamespaceConfig;
+pub use self::namespace::{NamespaceConfig, NetworkNamespace};
+
+#[cfg(target_os = "linux")]
+pub use self::netfilter::NetfilterRuleset;
 
 #[cfg(target_os = "linux")]
 pub use self::capabilities::CapabilitySet;
@@ -3,10 +3,17 @@
 #![cfg(target_os = "linux")]
 
 use crate::error::SandboxError;
+use crate::linux::NetfilterRuleset;
+use crate::profile::NetworkRules;
 use crate::Result;
-use nix::sched::{unshare, CloneFlags};
+use nix::sched::{setns, unshare, CloneFlags};
 use nix::unistd::{Gid, Uid};
+use std::fs::File;
+use std::io::Read;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::process::CommandExt;
 use std::path::Path;
+use std::process::{Child, Command, Stdio};
 use tracing::{debug, warn};
 
 /// Namespace configuration.
@@ -210,6 +217,193 @@ impl NamespaceConfig {
     }
 }
 
+/// A network namespace set up from outside for sandboxed commands to join.
+///
+/// A holder process creates the namespace and brings its loopback interface
+/// up. The ruleset for the [`NetworkRules`] is then loaded with `nft`, and
+/// if the rules allow traffic beyond localhost, `slirp4netns` gives the
+/// namespace a NAT uplink (without access to the host's loopback). Commands
+/// join with [`NetworkNamespace::joiner`] and should drop `CAP_NET_ADMIN`
+/// before they run, so they cannot change the rules.
+///
+/// Without `CAP_SYS_ADMIN` the namespace belongs to a new user namespace
+/// in which the current user is mapped to root.
+pub struct NetworkNamespace {
+    /// Process that created the namespace; `slirp4netns` attaches to it.
+    holder: Child,
+
+    /// The holder's user namespace, if it had to create one.
+    user: Option<File>,
+
+    /// The holder's network namespace.
+    net: File,
+
+    /// The `slirp4netns` process providing the uplink, if any.
+    uplink: Option<Child>,
+}
+
+impl NetworkNamespace {
+    /// Create a namespace and set it up for `rules`.
+    pub fn create(rules: &NetworkRules) -> Result<Self> {
+        let mut holder = Command::new("cat");
+        holder
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // SAFETY: the closure only makes system calls.
+        unsafe {
+            holder.pre_exec(|| {
+                unshare(CloneFlags::CLONE_NEWNET)
+                    .or_else(|_| unshare(CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNET))?;
+                set_loopback_up()
+            });
+        }
+        let holder = holder.spawn().map_err(|e| {
+            SandboxError::Namespace(format!("Failed to create network namespace: {}", e))
+        })?;
+
+        let ns = format!("/proc/{}/ns", holder.id());
+        let mut namespace = Self {
+            net: File::open(format!("{}/net", ns))?,
+            holder,
+            user: None,
+            uplink: None,
+        };
+        let user = format!("{}/user", ns);
+        if std::fs::read_link(&user)? != std::fs::read_link("/proc/self/ns/user")? {
+            namespace.map_user_to_root()?;
+            namespace.user = Some(File::open(&user)?);
+        }
+
+        if rules.enabled {
+            NetfilterRuleset::new(rules.clone()).apply_in(&namespace)?;
+            if !rules.localhost_only {
+                namespace.uplink = Some(namespace.start_uplink()?);
+            }
+        }
+        debug!(
+            "Network namespace ready (user namespace: {}, uplink: {})",
+            namespace.user.is_some(),
+            namespace.uplink.is_some()
+        );
+        Ok(namespace)
+    }
+
+    /// A `pre_exec` hook that moves the calling process into the namespace.
+    ///
+    /// The hook holds its own close-on-exec copies of the namespace
+    /// descriptors, so it stays valid however long the command is kept.
+    pub fn joiner(&self) -> Result<impl Fn() -> std::io::Result<()> + Send + Sync + 'static> {
+        let user = self.user.as_ref().map(File::try_clone).transpose()?;
+        let net = self.net.try_clone()?;
+        Ok(move || {
+            if let Some(user) = &user {
+                setns(user, CloneFlags::CLONE_NEWUSER)?;
+            }
+            setns(&net, CloneFlags::CLONE_NEWNET)?;
+            Ok(())
+        })
+    }
+
+    /// Map the current user and group to root in the holder's user namespace.
+    fn map_user_to_root(&self) -> Result<()> {
+        let proc = format!("/proc/{}", self.holder.id());
+        let write = |file: &str, contents: String| {
+            std::fs::write(format!("{}/{}", proc, file), contents).map_err(|e| {
+                SandboxError::Namespace(format!("Failed to write {}: {}", file, e))
+            })
+        };
+        write("uid_map", format!("0 {} 1\n", Uid::effective()))?;
+        write("setgroups", "deny\n".to_string())?;
+        write("gid_map", format!("0 {} 1\n", Gid::effective()))
+    }
+
+    /// Start `slirp4netns` on the holder and wait until the uplink is up.
+    fn start_uplink(&self) -> Result<Child> {
+        let mut fds = [0; 2];
+        // SAFETY: pipe2 fills in two new descriptors on success.
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        // SAFETY: both descriptors are new and owned by nothing else.
+        let (mut ready, writer) =
+            unsafe { (File::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        let ready_fd = writer.as_raw_fd();
+
+        let mut command = Command::new("slirp4netns");
+        command
+            .args(["--configure", "--disable-host-loopback", "--mtu=65520"])
+            .arg(format!("--ready-fd={}", ready_fd))
+            .arg(self.holder.id().to_string())
+            .arg("tap0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // SAFETY: fcntl is async-signal-safe. The write end is kept open
+        // across exec in slirp4netns only.
+        unsafe {
+            command.pre_exec(move || {
+                if libc::fcntl(ready_fd, libc::F_SETFD, 0) < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let mut uplink = command.spawn().map_err(|e| {
+            SandboxError::Namespace(format!("Failed to start slirp4netns: {}", e))
+        })?;
+        drop(writer);
+
+        // slirp4netns writes one byte once configured; EOF means it quit.
+        let mut byte = [0u8; 1];
+        if !matches!(ready.read(&mut byte), Ok(1)) {
+            let _ = uplink.kill();
+            let _ = uplink.wait();
+            return Err(SandboxError::Namespace(
+                "slirp4netns exited before the uplink was ready".to_string(),
+            ));
+        }
+        Ok(uplink)
+    }
+}
+
+impl Drop for NetworkNamespace {
+    fn drop(&mut self) {
+        for child in self.uplink.iter_mut().chain(std::iter::once(&mut self.holder)) {
+            if let Err(e) = child.kill() {
+                warn!("Failed to stop network namespace helper: {}", e);
+            }
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Bring up the loopback interface of the current network namespace.
+fn set_loopback_up() -> std::io::Result<()> {
+    // SAFETY: a plain socket and two ioctls on a zeroed, NUL-terminated ifreq.
+    unsafe {
+        let fd = libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0);
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let mut request: libc::ifreq = std::mem::zeroed();
+        request.ifr_name[0] = b'l' as libc::c_char;
+        request.ifr_name[1] = b'o' as libc::c_char;
+
+        let mut result = libc::ioctl(fd, libc::SIOCGIFFLAGS as _, &mut request);
+        if result >= 0 {
+            request.ifr_ifru.ifru_flags |= libc::IFF_UP as libc::c_short;
+            result = libc::ioctl(fd, libc::SIOCSIFFLAGS as _, &request);
+        }
+        let error = std::io::Error::last_os_error();
+        libc::close(fd);
+        if result < 0 {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
 /// Check if user namespaces are available.
 pub fn user_namespaces_available() -> bool {
     // Try to create a user namespace and immediately exit
@@ -0,0 +1,178 @@
+//! nftables egress filtering for Linux.
+//!
+//! Landlock and seccomp cannot match destination addresses, so CIDR rules
+//! are enforced by an nftables output chain loaded into the sandbox's
+//! network namespace before the sandboxed command joins it.
+
+#![cfg(target_os = "linux")]
+
+use crate::error::SandboxError;
+use crate::linux::NetworkNamespace;
+use crate::profile::{Cidr, EgressAction, EgressRule, NetworkRules};
+use crate::Result;
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use tracing::debug;
+
+/// Name of the nftables table holding the sandbox rules.
+const TABLE: &str = "smartassist_sandbox";
+
+/// nftables ruleset builder and applier.
+pub struct NetfilterRuleset {
+    rules: NetworkRules,
+}
+
+impl NetfilterRuleset {
+    /// Create a new ruleset from network rules.
+    pub fn new(rules: NetworkRules) -> Self {
+        Self { rules }
+    }
+
+    /// Render the ruleset as an `nft -f` script.
+    ///
+    /// nftables stops at the first matching verdict, so denied networks and
+    /// blocked ports come before anything that is accepted.
+    pub fn render(&self) -> String {
+        let rules = &self.rules;
+        let allowlist = !rules.enabled || rules.localhost_only || !rules.allowed_cidrs.is_empty();
+        let policy = if allowlist { "drop" } else { "accept" };
+
+        let mut script = format!("table inet {} {{\n", TABLE);
+        script.push_str("    chain output {\n");
+        script.push_str(&format!(
+            "        type filter hook output priority 0; policy {};\n",
+            policy
+        ));
+
+        if rules.enabled {
+            let egress = rules.egress_rules();
+            let (denied, allowed): (Vec<&EgressRule>, Vec<&EgressRule>) = egress
+                .iter()
+                .partition(|rule| rule.action == EgressAction::Deny);
+
+            for rule in &denied {
+                script.push_str(&format!("        {} drop\n", daddr(rule.cidr)));
+            }
+            for port in &rules.blocked_ports {
+                script.push_str(&format!("        tcp dport {} drop\n", port));
+            }
+            if rules.localhost_only {
+                script.push_str("        oif \"lo\" accept\n");
+            }
+            for rule in &allowed {
+                script.push_str(&format!("        {} accept\n", daddr(rule.cidr)));
+            }
+        }
+
+        script.push_str("    }\n}\n");
+        script
+    }
+
+    /// Load the ruleset with `nft`.
+    ///
+    /// Must run inside the sandbox's network namespace with `CAP_NET_ADMIN`,
+    /// or it will replace the host's rules.
+    pub fn apply(&self) -> Result<()> {
+        self.load(Command::new("nft"))
+    }
+
+    /// Load the ruleset with `nft` run inside `namespace`.
+    pub fn apply_in(&self, namespace: &NetworkNamespace) -> Result<()> {
+        let mut command = Command::new("nft");
+        // SAFETY: the hook only calls setns(2).
+        unsafe {
+            command.pre_exec(namespace.joiner()?);
+        }
+        self.load(command)
+    }
+
+    fn load(&self, mut command: Command) -> Result<()> {
+        let script = self.render();
+        debug!("Applying nftables ruleset:\n{}", script);
+
+        let mut child = command
+            .args(["-f", "-"])
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| SandboxError::setup_failed(format!("Failed to run nft: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(script.as_bytes())?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(SandboxError::setup_failed(format!(
+                "nft rejected the ruleset: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// nftables destination match for a network.
+fn daddr(cidr: Cidr) -> String {
+    let family = if cidr.network().is_ipv4() {
+        "ip"
+    } else {
+        "ip6"
+    };
+    format!("{} daddr {}", family, cidr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_index(script: &str, line: &str) -> usize {
+        script
+            .lines()
+            .position(|l| l.trim() == line)
+            .unwrap_or_else(|| panic!("missing {:?} in:\n{}", line, script))
+    }
+
+    #[test]
+    fn test_cidr_rules_deny_first() {
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .allow_cidr("10.0.0.0/8".parse().unwrap())
+            .allow_cidr("fd00::/8".parse().unwrap())
+            .deny_cidr("10.1.0.0/16".parse().unwrap())
+            .block_port(22)
+            .build();
+        let script = NetfilterRuleset::new(rules).render();
+
+        assert!(script.contains("policy drop;"));
+        let deny = line_index(&script, "ip daddr 10.1.0.0/16 drop");
+        let port = line_index(&script, "tcp dport 22 drop");
+        let allow = line_index(&script, "ip daddr 10.0.0.0/8 accept");
+        line_index(&script, "ip6 daddr fd00::/8 accept");
+        assert!(deny < allow && port < allow);
+    }
+
+    #[test]
+    fn test_open_network_accepts_by_default() {
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .deny_cidr("169.254.169.254".parse().unwrap())
+            .build();
+        let script = NetfilterRuleset::new(rules).render();
+
+        assert!(script.contains("policy accept;"));
+        line_index(&script, "ip daddr 169.254.169.254/32 drop");
+    }
+
+    #[test]
+    fn test_localhost_and_disabled() {
+        let script = NetfilterRuleset::new(NetworkRules::localhost_only()).render();
+        assert!(script.contains("policy drop;"));
+        let port = line_index(&script, "tcp dport 18789 drop");
+        assert!(port < line_index(&script, "oif \"lo\" accept"));
+
+        let script = NetfilterRuleset::new(NetworkRules::disabled()).render();
+        assert!(script.contains("policy drop;"));
+        assert!(!script.contains("accept\n"));
+    }
+}
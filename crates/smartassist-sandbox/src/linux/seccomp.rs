@@ -8,30 +8,174 @@ use crate::Result;
 use seccompiler::{
     BpfMap, SeccompAction, SeccompFilter as SeccompilerFilter, SeccompRule, TargetArch,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use tracing::{debug, warn};
 
+/// Syscalls allowed by [`SeccompBase::Strict`]: memory, signals, time and
+/// I/O on already-open descriptors. No process creation, exec or sockets.
+const STRICT_SYSCALLS: &[&str] = &[
+    "read", "write", "readv", "writev", "pread64", "pwrite64", "openat", "close", "fstat",
+    "newfstatat", "statx", "lseek", "fcntl", "dup", "dup3", "pipe2", "ppoll", "getdents64",
+    "readlinkat", "getcwd", "mmap", "mprotect", "munmap", "mremap", "madvise", "brk",
+    "rt_sigaction", "rt_sigprocmask", "rt_sigreturn", "sigaltstack", "futex", "getrandom",
+    "clock_gettime", "clock_nanosleep", "nanosleep", "sched_yield", "getpid", "gettid", "uname",
+    "prlimit64", "set_robust_list", "set_tid_address", "rseq", "exit", "exit_group",
+];
+
+/// Base syscall set for a [`SeccompProfile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeccompBase {
+    /// Allow only computation and I/O on open files; deny everything else.
+    Strict,
+    /// Allow everything except syscalls that can escape or damage the host.
+    Standard,
+    /// Allow everything; only syscalls removed from the profile are denied.
+    Permissive,
+}
+
+/// What happens when a process makes a denied syscall.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DenyAction {
+    /// The syscall fails with `EPERM`.
+    #[default]
+    Errno,
+    /// The kernel kills the process, which dies from `SIGSYS`.
+    Kill,
+}
+
+impl DenyAction {
+    fn seccomp_action(self) -> SeccompAction {
+        match self {
+            Self::Errno => SeccompAction::Errno(libc::EPERM as u32),
+            Self::Kill => SeccompAction::KillProcess,
+        }
+    }
+}
+
+/// A syscall filter built from a named base with per-syscall overrides.
+///
+/// ```rust,ignore
+/// let filter = SeccompProfile::new(SeccompBase::Strict)
+///     .allow("socket")
+///     .deny("getdents64")
+///     .deny_action(DenyAction::Kill)
+///     .filter()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeccompProfile {
+    base: SeccompBase,
+    allow: BTreeSet<String>,
+    deny: BTreeSet<String>,
+    deny_action: DenyAction,
+}
+
+impl SeccompProfile {
+    /// Create a profile from a base syscall set.
+    pub fn new(base: SeccompBase) -> Self {
+        Self {
+            base,
+            allow: BTreeSet::new(),
+            deny: BTreeSet::new(),
+            deny_action: DenyAction::default(),
+        }
+    }
+
+    /// Allow a syscall the base would deny.
+    pub fn allow(mut self, syscall: impl Into<String>) -> Self {
+        let syscall = syscall.into();
+        self.deny.remove(&syscall);
+        self.allow.insert(syscall);
+        self
+    }
+
+    /// Deny a syscall the base would allow.
+    pub fn deny(mut self, syscall: impl Into<String>) -> Self {
+        let syscall = syscall.into();
+        self.allow.remove(&syscall);
+        self.deny.insert(syscall);
+        self
+    }
+
+    /// Set what happens on a denied syscall.
+    pub fn deny_action(mut self, action: DenyAction) -> Self {
+        self.deny_action = action;
+        self
+    }
+
+    /// Resolve the base and overrides into syscall rules.
+    pub fn rules(&self) -> SyscallRules {
+        let without_denied = |names: HashSet<String>| -> HashSet<String> {
+            names
+                .into_iter()
+                .filter(|name| !self.deny.contains(name))
+                .chain(self.allow.iter().cloned())
+                .collect()
+        };
+        let with_denied = |names: HashSet<String>| -> HashSet<String> {
+            names
+                .into_iter()
+                .filter(|name| !self.allow.contains(name))
+                .chain(self.deny.iter().cloned())
+                .collect()
+        };
+
+        match self.base {
+            SeccompBase::Strict => SyscallRules {
+                mode: SyscallMode::Allowlist,
+                allowed: without_denied(STRICT_SYSCALLS.iter().map(|s| s.to_string()).collect()),
+                blocked: HashSet::new(),
+            },
+            SeccompBase::Standard => SyscallRules {
+                blocked: with_denied(SyscallRules::standard().blocked),
+                ..SyscallRules::standard()
+            },
+            SeccompBase::Permissive => SyscallRules {
+                mode: SyscallMode::Blocklist,
+                allowed: HashSet::new(),
+                blocked: with_denied(HashSet::new()),
+            },
+        }
+    }
+
+    /// Build the filter, rejecting overrides that name unknown syscalls.
+    pub fn filter(&self) -> Result<SeccompFilter> {
+        let arch = SeccompFilter::detect_arch()?;
+        if let Some(unknown) = self
+            .allow
+            .iter()
+            .chain(&self.deny)
+            .find(|name| SeccompFilter::syscall_number(name, arch).is_none())
+        {
+            return Err(SandboxError::Seccomp(format!("Unknown syscall: {}", unknown)));
+        }
+
+        Ok(SeccompFilter::new(self.rules()).with_deny_action(self.deny_action))
+    }
+}
+
 /// Seccomp filter builder and applier.
 pub struct SeccompFilter {
     rules: SyscallRules,
-    default_action: SeccompAction,
+    deny_action: SeccompAction,
 }
 
 impl SeccompFilter {
     /// Create a new seccomp filter from rules.
+    ///
+    /// Denied syscalls fail with `EPERM`.
     pub fn new(rules: SyscallRules) -> Self {
-        let default_action = match rules.mode {
-            SyscallMode::Disabled => SeccompAction::Allow,
-            SyscallMode::Blocklist => SeccompAction::Allow,
-            SyscallMode::Allowlist => SeccompAction::Errno(libc::EPERM as u32),
-        };
-
         Self {
             rules,
-            default_action,
+            deny_action: DenyAction::default().seccomp_action(),
         }
     }
 
+    /// Set what happens on a denied syscall.
+    pub fn with_deny_action(mut self, action: DenyAction) -> Self {
+        self.deny_action = action.seccomp_action();
+        self
+    }
+
     /// Build the seccomp BPF filter.
     pub fn build(&self) -> Result<BpfMap> {
         if self.rules.mode == SyscallMode::Disabled {
@@ -49,7 +193,10 @@ impl SeccompFilter {
         Ok(map)
     }
 
-    /// Apply the seccomp filter to the current process.
+    /// Apply the seccomp filter to the calling thread.
+    ///
+    /// Also sets `PR_SET_NO_NEW_PRIVS`. The filter is inherited by child
+    /// processes and cannot be removed.
     pub fn apply(&self) -> Result<()> {
         if self.rules.mode == SyscallMode::Disabled {
             debug!("Seccomp filtering disabled, skipping");
@@ -63,9 +210,7 @@ impl SeccompFilter {
             .get("main")
             .ok_or_else(|| SandboxError::Seccomp("No filter found".to_string()))?;
 
-        // Apply the filter using prctl
-        // Note: This is a simplified version; real implementation would use
-        // seccompiler::apply_filter or direct syscall
+        seccompiler::apply_filter(bpf_prog).map_err(|e| SandboxError::Seccomp(e.to_string()))?;
 
         debug!("Seccomp filter applied successfully");
         Ok(())
@@ -85,49 +230,35 @@ impl SeccompFilter {
 
     /// Create the seccomp filter.
     fn create_filter(&self, arch: TargetArch) -> Result<Vec<seccompiler::sock_filter>> {
-        let mut rules_map: HashMap<i64, Vec<SeccompRule>> = HashMap::new();
+        let mut rules_map: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
-        match self.rules.mode {
+        let (listed, mismatch_action, match_action) = match self.rules.mode {
+            // Listed syscalls are denied, everything else allowed
             SyscallMode::Blocklist => {
-                // Block specific syscalls
-                for syscall_name in &self.rules.blocked {
-                    if let Some(nr) = Self::syscall_number(syscall_name, arch) {
-                        rules_map.insert(nr, vec![SeccompRule::new(vec![]).unwrap()]);
-                    } else {
-                        warn!("Unknown syscall: {}", syscall_name);
-                    }
-                }
+                (&self.rules.blocked, SeccompAction::Allow, self.deny_action.clone())
             }
+            // Listed syscalls are allowed, everything else denied
             SyscallMode::Allowlist => {
-                // Allow specific syscalls (everything else blocked by default)
-                for syscall_name in &self.rules.allowed {
-                    if let Some(nr) = Self::syscall_number(syscall_name, arch) {
-                        rules_map.insert(nr, vec![SeccompRule::new(vec![]).unwrap()]);
-                    } else {
-                        warn!("Unknown syscall: {}", syscall_name);
-                    }
-                }
+                (&self.rules.allowed, self.deny_action.clone(), SeccompAction::Allow)
             }
             SyscallMode::Disabled => unreachable!(),
-        }
-
-        let filter_action = match self.rules.mode {
-            SyscallMode::Blocklist => SeccompAction::Errno(libc::EPERM as u32),
-            SyscallMode::Allowlist => SeccompAction::Allow,
-            SyscallMode::Disabled => unreachable!(),
         };
 
-        let filter = SeccompilerFilter::new(
-            rules_map,
-            filter_action,
-            self.default_action,
-            arch,
-        )
-        .map_err(|e| SandboxError::Seccomp(e.to_string()))?;
+        for syscall_name in listed {
+            if let Some(nr) = Self::syscall_number(syscall_name, arch) {
+                // An empty rule list matches the syscall unconditionally
+                rules_map.insert(nr, vec![]);
+            } else {
+                warn!("Unknown syscall: {}", syscall_name);
+            }
+        }
+
+        let filter = SeccompilerFilter::new(rules_map, mismatch_action, match_action, arch)
+            .map_err(|e| SandboxError::Seccomp(e.to_string()))?;
 
         filter
             .try_into()
-            .map_err(|e: seccompiler::Error| SandboxError::Seccomp(e.to_string()))
+            .map_err(|e: seccompiler::BackendError| SandboxError::Seccomp(e.to_string()))
     }
 
     /// Get syscall number by name for the given architecture.
@@ -149,72 +280,185 @@ impl SeccompFilter {
                 ("mprotect", 10),
                 ("munmap", 11),
                 ("brk", 12),
+                ("rt_sigaction", 13),
+                ("rt_sigprocmask", 14),
+                ("rt_sigreturn", 15),
                 ("ioctl", 16),
+                ("pread64", 17),
+                ("pwrite64", 18),
+                ("readv", 19),
+                ("writev", 20),
                 ("access", 21),
                 ("pipe", 22),
                 ("select", 23),
+                ("sched_yield", 24),
+                ("mremap", 25),
+                ("madvise", 28),
                 ("dup", 32),
                 ("dup2", 33),
                 ("nanosleep", 35),
                 ("getpid", 39),
+                ("socket", 41),
+                ("connect", 42),
+                ("accept", 43),
+                ("sendto", 44),
+                ("recvfrom", 45),
+                ("bind", 49),
+                ("listen", 50),
+                ("clone", 56),
                 ("fork", 57),
                 ("vfork", 58),
                 ("execve", 59),
                 ("exit", 60),
+                ("wait4", 61),
                 ("kill", 62),
+                ("uname", 63),
+                ("fcntl", 72),
+                ("getcwd", 79),
+                ("chdir", 80),
+                ("readlink", 89),
                 ("ptrace", 101),
                 ("getuid", 102),
                 ("getgid", 104),
                 ("setuid", 105),
                 ("setgid", 106),
+                ("geteuid", 107),
+                ("getegid", 108),
+                ("getppid", 110),
+                ("sigaltstack", 131),
+                ("pivot_root", 155),
+                ("arch_prctl", 158),
+                ("adjtimex", 159),
                 ("chroot", 161),
+                ("acct", 163),
+                ("settimeofday", 164),
                 ("mount", 165),
                 ("umount2", 166),
+                ("swapon", 167),
+                ("swapoff", 168),
                 ("reboot", 169),
                 ("init_module", 175),
                 ("delete_module", 176),
-                ("kexec_load", 246),
+                ("gettid", 186),
+                ("futex", 202),
+                ("getdents64", 217),
+                ("set_tid_address", 218),
+                ("clock_gettime", 228),
+                ("clock_nanosleep", 230),
                 ("exit_group", 231),
+                ("tgkill", 234),
+                ("kexec_load", 246),
+                ("openat", 257),
+                ("newfstatat", 262),
+                ("readlinkat", 267),
+                ("faccessat", 269),
+                ("ppoll", 271),
+                ("set_robust_list", 273),
+                ("accept4", 288),
+                ("dup3", 292),
+                ("pipe2", 293),
+                ("prlimit64", 302),
                 ("process_vm_readv", 310),
                 ("process_vm_writev", 311),
+                ("finit_module", 313),
+                ("getrandom", 318),
+                ("kexec_file_load", 320),
+                ("execveat", 322),
+                ("statx", 332),
+                ("rseq", 334),
+                ("clone3", 435),
             ]
             .into_iter()
             .collect(),
             TargetArch::aarch64 => [
-                ("read", 63),
-                ("write", 64),
-                ("openat", 56),
-                ("close", 57),
-                ("fstat", 80),
-                ("lseek", 62),
-                ("mmap", 222),
-                ("mprotect", 226),
-                ("munmap", 215),
-                ("brk", 214),
+                ("getcwd", 17),
+                ("dup", 23),
+                ("dup3", 24),
+                ("fcntl", 25),
                 ("ioctl", 29),
+                ("umount2", 39),
+                ("mount", 40),
+                ("pivot_root", 41),
                 ("faccessat", 48),
+                ("chdir", 49),
+                ("chroot", 51),
+                ("openat", 56),
+                ("close", 57),
                 ("pipe2", 59),
+                ("getdents64", 61),
+                ("lseek", 62),
+                ("read", 63),
+                ("write", 64),
+                ("readv", 65),
+                ("writev", 66),
+                ("pread64", 67),
+                ("pwrite64", 68),
                 ("ppoll", 73),
-                ("dup", 23),
-                ("dup3", 24),
-                ("nanosleep", 101),
-                ("getpid", 172),
-                ("clone", 220),
-                ("execve", 221),
+                ("readlinkat", 78),
+                ("newfstatat", 79),
+                ("fstat", 80),
+                ("acct", 89),
                 ("exit", 93),
-                ("kill", 129),
+                ("exit_group", 94),
+                ("set_tid_address", 96),
+                ("futex", 98),
+                ("set_robust_list", 99),
+                ("nanosleep", 101),
+                ("kexec_load", 104),
+                ("init_module", 105),
+                ("delete_module", 106),
+                ("clock_gettime", 113),
+                ("clock_nanosleep", 115),
                 ("ptrace", 117),
+                ("sched_yield", 124),
+                ("kill", 129),
+                ("tgkill", 131),
+                ("sigaltstack", 132),
+                ("rt_sigaction", 134),
+                ("rt_sigprocmask", 135),
+                ("rt_sigreturn", 139),
+                ("reboot", 142),
+                ("setgid", 144),
+                ("setuid", 146),
+                ("uname", 160),
+                ("settimeofday", 170),
+                ("adjtimex", 171),
+                ("getpid", 172),
+                ("getppid", 173),
                 ("getuid", 174),
+                ("geteuid", 175),
                 ("getgid", 176),
-                ("setuid", 146),
-                ("setgid", 144),
-                ("chroot", 51),
-                ("mount", 40),
-                ("umount2", 39),
-                ("reboot", 142),
-                ("init_module", 105),
-                ("delete_module", 106),
-                ("exit_group", 94),
+                ("getegid", 177),
+                ("gettid", 178),
+                ("socket", 198),
+                ("bind", 200),
+                ("listen", 201),
+                ("accept", 202),
+                ("connect", 203),
+                ("sendto", 206),
+                ("recvfrom", 207),
+                ("brk", 214),
+                ("munmap", 215),
+                ("mremap", 216),
+                ("clone", 220),
+                ("execve", 221),
+                ("mmap", 222),
+                ("swapon", 224),
+                ("swapoff", 225),
+                ("mprotect", 226),
+                ("madvise", 233),
+                ("accept4", 242),
+                ("wait4", 260),
+                ("prlimit64", 261),
+                ("process_vm_readv", 270),
+                ("process_vm_writev", 271),
+                ("finit_module", 273),
+                ("getrandom", 278),
+                ("execveat", 281),
+                ("statx", 291),
+                ("rseq", 293),
+                ("kexec_file_load", 294),
+                ("clone3", 435),
             ]
             .into_iter()
             .collect(),
@@ -243,6 +487,51 @@ pub mod profiles {
 mod tests {
     use super::*;
 
+    /// How a forked child that tried to create a socket ended.
+    #[derive(Debug, PartialEq, Eq)]
+    enum Outcome {
+        Allowed,
+        Eperm,
+        Killed(i32),
+    }
+
+    /// Fork, apply the filter in the child, then try `socket(2)`.
+    ///
+    /// The BPF program is compiled before forking so the child only makes
+    /// raw syscalls.
+    fn socket_under(filter: &SeccompFilter) -> Outcome {
+        let map = filter.build().unwrap();
+        let prog = &map["main"];
+
+        unsafe {
+            let pid = libc::fork();
+            assert!(pid >= 0, "fork failed");
+            if pid == 0 {
+                if seccompiler::apply_filter(prog).is_err() {
+                    libc::_exit(3);
+                }
+                let fd = libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0);
+                let errno = *libc::__errno_location();
+                libc::_exit(match fd {
+                    fd if fd >= 0 => 0,
+                    _ if errno == libc::EPERM => 1,
+                    _ => 2,
+                });
+            }
+
+            let mut status = 0;
+            assert_eq!(libc::waitpid(pid, &mut status, 0), pid);
+            if libc::WIFSIGNALED(status) {
+                return Outcome::Killed(libc::WTERMSIG(status));
+            }
+            match libc::WEXITSTATUS(status) {
+                0 => Outcome::Allowed,
+                1 => Outcome::Eperm,
+                code => panic!("child exited with {}", code),
+            }
+        }
+    }
+
     #[test]
     fn test_seccomp_filter_creation() {
         let rules = SyscallRules::standard();
@@ -258,4 +547,65 @@ mod tests {
         let nr = SeccompFilter::syscall_number("ptrace", TargetArch::x86_64);
         assert_eq!(nr, Some(101));
     }
+
+    #[test]
+    fn test_profile_overrides() {
+        let rules = SeccompProfile::new(SeccompBase::Strict)
+            .allow("socket")
+            .deny("getdents64")
+            .rules();
+        assert_eq!(rules.mode, SyscallMode::Allowlist);
+        assert!(rules.allowed.contains("socket"));
+        assert!(rules.allowed.contains("read"));
+        assert!(!rules.allowed.contains("getdents64"));
+
+        let rules = SeccompProfile::new(SeccompBase::Standard)
+            .allow("ptrace")
+            .deny("socket")
+            .rules();
+        assert_eq!(rules.mode, SyscallMode::Blocklist);
+        assert!(!rules.blocked.contains("ptrace"));
+        assert!(rules.blocked.contains("socket"));
+        assert!(rules.blocked.contains("mount"));
+
+        let rules = SeccompProfile::new(SeccompBase::Permissive).rules();
+        assert!(rules.blocked.is_empty());
+    }
+
+    #[test]
+    fn test_profile_rejects_unknown_syscall() {
+        let err = SeccompProfile::new(SeccompBase::Strict)
+            .allow("not_a_syscall")
+            .filter()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not_a_syscall"));
+    }
+
+    #[test]
+    fn test_strict_profile_denies_with_eperm() {
+        let filter = SeccompProfile::new(SeccompBase::Strict).filter().unwrap();
+        assert_eq!(socket_under(&filter), Outcome::Eperm);
+    }
+
+    #[test]
+    fn test_permissive_profile_allows() {
+        let filter = SeccompProfile::new(SeccompBase::Permissive).filter().unwrap();
+        assert_eq!(socket_under(&filter), Outcome::Allowed);
+
+        let filter = SeccompProfile::new(SeccompBase::Permissive)
+            .deny("socket")
+            .filter()
+            .unwrap();
+        assert_eq!(socket_under(&filter), Outcome::Eperm);
+    }
+
+    #[test]
+    fn test_kill_action() {
+        let filter = SeccompProfile::new(SeccompBase::Strict)
+            .deny_action(DenyAction::Kill)
+            .filter()
+            .unwrap();
+        assert_eq!(socket_under(&filter), Outcome::Killed(libc::SIGSYS));
+    }
 }
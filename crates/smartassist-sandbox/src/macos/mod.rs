@@ -14,7 +14,7 @@ use crate::profile::{FilesystemRules, NetworkRules};
 use crate::Result;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// macOS sandbox profile generator.
 pub struct MacOsSandbox {
@@ -178,7 +178,17 @@ impl MacOsSandbox {
             return;
         }
 
-        if self.network.localhost_only {
+        // SBPL can only name `*` or `localhost` as a network host, so CIDR
+        // rules cannot be expressed. An allowlist is narrowed to localhost,
+        // and denied networks stay reachable when the network is otherwise
+        // open.
+        if !self.network.denied_cidrs.is_empty() && self.network.allowed_cidrs.is_empty() {
+            warn!(
+                "Sandbox profile '{}': denied networks cannot be enforced on macOS",
+                self.name
+            );
+        }
+        if self.network.localhost_only || !self.network.allowed_cidrs.is_empty() {
             profile.push_str("(allow network* (local ip \"localhost:*\"))\n");
             profile.push_str("(allow network* (local ip \"127.0.0.1:*\"))\n");
             profile.push_str("(allow network* (local ip \"::1:*\"))\n");
@@ -282,6 +292,29 @@ mod tests {
         assert!(profile.contains("127.0.0.1"));
     }
 
+    #[test]
+    fn test_cidr_rules() {
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .allow_cidr("10.0.0.0/8".parse().unwrap())
+            .deny_cidr("10.1.0.0/16".parse().unwrap())
+            .build();
+        let profile = MacOsSandbox::new("test").with_network(rules).generate_profile();
+
+        // An allowlist narrows to localhost, since SBPL cannot name networks.
+        assert!(!profile.contains("remote ip"));
+        assert!(profile.contains("(allow network* (local ip \"localhost:*\"))"));
+        assert!(!profile.contains("(allow network*)\n"));
+
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .deny_cidr("169.254.169.254".parse().unwrap())
+            .build();
+        let profile = MacOsSandbox::new("test").with_network(rules).generate_profile();
+        assert!(!profile.contains("remote ip"));
+        assert!(profile.contains("(allow network*)\n"));
+    }
+
     #[test]
     fn test_workspace_access() {
         let sandbox = MacOsSandbox::new("test")
@@ -1,9 +1,13 @@
 //! Sandbox profile definitions.
 
+use crate::error::SandboxError;
 use crate::limits::ResourceLimits;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::fmt;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 /// A sandbox profile defining security constraints.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +113,32 @@ impl SandboxProfile {
             drop_capabilities: false,
         }
     }
+
+    /// Create a profile that applies no restrictions beyond the defaults
+    /// and passes the environment through.
+    pub fn none() -> Self {
+        Self {
+            name: "none".to_string(),
+            environment: EnvironmentRules {
+                inherit: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl From<smartassist_core::types::SandboxProfile> for SandboxProfile {
+    /// Map a configured isolation level to its profile.
+    fn from(level: smartassist_core::types::SandboxProfile) -> Self {
+        use smartassist_core::types::SandboxProfile as Level;
+        match level {
+            Level::Strict => Self::minimal(),
+            Level::Standard => Self::standard(),
+            Level::Trusted => Self::relaxed(),
+            Level::None => Self::none(),
+        }
+    }
 }
 
 /// Filesystem access rules.
@@ -212,9 +242,25 @@ pub struct NetworkRules {
     /// Blocked ports.
     #[serde(default)]
     pub blocked_ports: Vec<u16>,
+
+    /// Networks that may be reached. When non-empty, egress to any other
+    /// address (besides localhost, if allowed) is denied. macOS profiles
+    /// cannot name networks and allow only localhost instead.
+    #[serde(default)]
+    pub allowed_cidrs: Vec<Cidr>,
+
+    /// Networks that may never be reached. Takes precedence over
+    /// `allowed_cidrs` where the two overlap. Not enforced on macOS.
+    #[serde(default)]
+    pub denied_cidrs: Vec<Cidr>,
 }
 
 impl NetworkRules {
+    /// Create a builder starting from disabled network rules.
+    pub fn builder() -> NetworkRulesBuilder {
+        NetworkRulesBuilder::new()
+    }
+
     /// Create disabled network rules.
     pub fn disabled() -> Self {
         Self {
@@ -223,6 +269,8 @@ impl NetworkRules {
             allowed_hosts: vec![],
             allowed_ports: vec![],
             blocked_ports: vec![],
+            allowed_cidrs: vec![],
+            denied_cidrs: vec![],
         }
     }
 
@@ -235,6 +283,8 @@ impl NetworkRules {
             allowed_hosts: vec!["localhost".to_string(), "127.0.0.1".to_string()],
             allowed_ports: vec![],
             blocked_ports: vec![18789], // Block gateway port
+            allowed_cidrs: vec![],
+            denied_cidrs: vec![],
         }
     }
 
@@ -246,7 +296,217 @@ impl NetworkRules {
             allowed_hosts: vec![],
             allowed_ports: vec![],
             blocked_ports: vec![22, 23, 25, 18789], // SSH, Telnet, SMTP, gateway
+            allowed_cidrs: vec![],
+            denied_cidrs: vec![],
+        }
+    }
+
+    /// CIDR rules in evaluation order: the first rule containing an address
+    /// decides. Denies come first, so an address in both lists is denied.
+    pub fn egress_rules(&self) -> Vec<EgressRule> {
+        let denied = self.denied_cidrs.iter().map(|&cidr| EgressRule {
+            action: EgressAction::Deny,
+            cidr,
+        });
+        let allowed = self.allowed_cidrs.iter().map(|&cidr| EgressRule {
+            action: EgressAction::Allow,
+            cidr,
+        });
+        denied.chain(allowed).collect()
+    }
+
+    /// Whether egress to `addr` is permitted, ignoring hostnames and ports.
+    pub fn allows_address(&self, addr: IpAddr) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if let Some(rule) = self.egress_rules().iter().find(|r| r.cidr.contains(addr)) {
+            return rule.action == EgressAction::Allow;
+        }
+        if self.localhost_only {
+            addr.is_loopback()
+        } else {
+            self.allowed_cidrs.is_empty()
+        }
+    }
+}
+
+/// Whether an egress rule permits or blocks traffic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EgressAction {
+    Allow,
+    Deny,
+}
+
+/// An allow or deny rule for a destination network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EgressRule {
+    pub action: EgressAction,
+    pub cidr: Cidr,
+}
+
+/// An IPv4 or IPv6 network in CIDR notation, such as `10.0.0.0/8`.
+///
+/// A bare address is a single-host network. Host bits are cleared, so
+/// `10.1.2.3/8` is stored as `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Create a network from an address and prefix length.
+    pub fn new(addr: IpAddr, prefix: u8) -> crate::Result<Self> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(SandboxError::Config(format!(
+                "Invalid CIDR prefix /{} for {}",
+                prefix, addr
+            )));
         }
+        Ok(Self {
+            network: mask(addr, prefix),
+            prefix,
+        })
+    }
+
+    /// The network address.
+    pub fn network(&self) -> IpAddr {
+        self.network
+    }
+
+    /// The prefix length in bits.
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether `addr` is inside this network.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        addr.is_ipv4() == self.network.is_ipv4() && mask(addr, self.prefix) == self.network
+    }
+}
+
+/// Clear the host bits of `addr`.
+fn mask(addr: IpAddr, prefix: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(v4) => {
+            let bits = u32::from(v4);
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            IpAddr::V4((bits & mask).into())
+        }
+        IpAddr::V6(v6) => {
+            let bits = u128::from(v6);
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            IpAddr::V6((bits & mask).into())
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = SandboxError;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        let invalid = || SandboxError::Config(format!("Invalid CIDR: {}", s));
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.trim().parse().map_err(|_| invalid())?;
+        let prefix = match prefix {
+            Some(prefix) => prefix.trim().parse().map_err(|_| invalid())?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+        Self::new(addr, prefix)
+    }
+}
+
+impl TryFrom<String> for Cidr {
+    type Error = SandboxError;
+
+    fn try_from(s: String) -> crate::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Cidr> for String {
+    fn from(cidr: Cidr) -> Self {
+        cidr.to_string()
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Builder for network rules.
+#[derive(Debug, Default)]
+pub struct NetworkRulesBuilder {
+    rules: NetworkRules,
+}
+
+impl NetworkRulesBuilder {
+    /// Create a builder starting from disabled network rules.
+    pub fn new() -> Self {
+        Self {
+            rules: NetworkRules::disabled(),
+        }
+    }
+
+    /// Create a builder starting from existing rules.
+    pub fn from_rules(rules: NetworkRules) -> Self {
+        Self { rules }
+    }
+
+    /// Enable or disable network access.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.rules.enabled = enabled;
+        self
+    }
+
+    /// Restrict network access to localhost.
+    pub fn localhost_only(mut self, localhost_only: bool) -> Self {
+        self.rules.localhost_only = localhost_only;
+        self
+    }
+
+    /// Allow a hostname or IP.
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.rules.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Allow a port.
+    pub fn allow_port(mut self, port: u16) -> Self {
+        self.rules.allowed_ports.push(port);
+        self
+    }
+
+    /// Block a port.
+    pub fn block_port(mut self, port: u16) -> Self {
+        self.rules.blocked_ports.push(port);
+        self
+    }
+
+    /// Allow egress to a network.
+    pub fn allow_cidr(mut self, cidr: Cidr) -> Self {
+        self.rules.allowed_cidrs.push(cidr);
+        self
+    }
+
+    /// Deny egress to a network, even if an allowed network contains it.
+    pub fn deny_cidr(mut self, cidr: Cidr) -> Self {
+        self.rules.denied_cidrs.push(cidr);
+        self
+    }
+
+    /// Build the rules.
+    pub fn build(self) -> NetworkRules {
+        self.rules
     }
 }
 
@@ -565,4 +825,89 @@ mod tests {
         let rules = NetworkRules::enabled();
         assert!(rules.blocked_ports.contains(&18789));
     }
+
+    fn cidr(s: &str) -> Cidr {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cidr_parsing() {
+        assert_eq!(cidr("10.1.2.3/8").to_string(), "10.0.0.0/8");
+        assert_eq!(cidr("192.168.1.7").to_string(), "192.168.1.7/32");
+        assert_eq!(cidr("fd00::1/8").to_string(), "fd00::/8");
+        assert_eq!(cidr("0.0.0.0/0").to_string(), "0.0.0.0/0");
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("example.com/24".parse::<Cidr>().is_err());
+
+        let all = cidr("0.0.0.0/0");
+        assert!(all.contains(ip("203.0.113.9")));
+        assert!(!all.contains(ip("::1")));
+        assert!(cidr("fd00::/8").contains(ip("fd12::3")));
+    }
+
+    #[test]
+    fn test_cidr_serde_as_string() {
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .allow_cidr(cidr("10.0.0.0/8"))
+            .build();
+        let json = serde_json::to_value(&rules).unwrap();
+        assert_eq!(json["allowed_cidrs"], serde_json::json!(["10.0.0.0/8"]));
+
+        let parsed: NetworkRules = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.allowed_cidrs, [cidr("10.0.0.0/8")]);
+        let bad = serde_json::json!({ "denied_cidrs": ["10.0.0.0/40"] });
+        assert!(serde_json::from_value::<NetworkRules>(bad).is_err());
+    }
+
+    #[test]
+    fn test_deny_wins_over_overlapping_allow() {
+        let rules = NetworkRules::builder()
+            .enabled(true)
+            .allow_cidr(cidr("10.0.0.0/8"))
+            .deny_cidr(cidr("10.1.0.0/16"))
+            .build();
+
+        let order: Vec<EgressAction> = rules.egress_rules().iter().map(|r| r.action).collect();
+        assert_eq!(order, [EgressAction::Deny, EgressAction::Allow]);
+
+        assert!(rules.allows_address(ip("10.2.0.1")));
+        assert!(!rules.allows_address(ip("10.1.0.1")));
+        // An allowlist blocks everything outside it.
+        assert!(!rules.allows_address(ip("8.8.8.8")));
+    }
+
+    #[test]
+    fn test_denied_cidrs_without_allowlist() {
+        let rules = NetworkRulesBuilder::from_rules(NetworkRules::enabled())
+            .deny_cidr(cidr("169.254.169.254"))
+            .build();
+        assert!(rules.allows_address(ip("8.8.8.8")));
+        assert!(!rules.allows_address(ip("169.254.169.254")));
+
+        let local = NetworkRulesBuilder::from_rules(NetworkRules::localhost_only())
+            .allow_cidr(cidr("192.168.0.0/16"))
+            .build();
+        assert!(local.allows_address(ip("127.0.0.1")));
+        assert!(local.allows_address(ip("192.168.4.4")));
+        assert!(!local.allows_address(ip("8.8.8.8")));
+        assert!(!NetworkRules::disabled().allows_address(ip("127.0.0.1")));
+    }
+
+    #[test]
+    fn test_from_core_profile_level() {
+        use smartassist_core::types::SandboxProfile as Level;
+
+        let strict = SandboxProfile::from(Level::Strict);
+        assert_eq!(strict.name, "minimal");
+        assert!(!strict.network.enabled);
+        assert_eq!(SandboxProfile::from(Level::Standard).name, "standard");
+        assert_eq!(SandboxProfile::from(Level::Trusted).name, "relaxed");
+        let none = SandboxProfile::from(Level::None);
+        assert!(none.environment.inherit);
+    }
 }
@@ -0,0 +1,103 @@
+//! Resolving `secret://` references in configuration.
+//!
+//! Config files can refer to secrets as `secret://namespace/name` instead of
+//! holding them inline; see [`smartassist_core::config::SecretUri`]. The
+//! functions here read those secrets from a [`SecretStore`] when the config
+//! is loaded, leaving the file itself untouched.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use smartassist_core::config::{Config, SecretUri};
+use smartassist_core::error::ConfigError;
+use smartassist_core::SecretString;
+
+use crate::error::{Result, SecretError};
+use crate::store::SecretStore;
+
+/// Load a config file and resolve its secret references from `store`.
+pub async fn load_config(path: &Path, store: &dyn SecretStore) -> Result<Config> {
+    let config = Config::load(path)?;
+    resolve_config(&config, store).await
+}
+
+/// Return a copy of `config` with every secret reference replaced by its
+/// decrypted value from `store`.
+///
+/// A reference to a secret the store does not have is an error naming the
+/// config path it appears at.
+pub async fn resolve_config(config: &Config, store: &dyn SecretStore) -> Result<Config> {
+    let mut secrets: HashMap<SecretUri, SecretString> = HashMap::new();
+
+    for secret_ref in config.secret_refs()? {
+        if secrets.contains_key(&secret_ref.uri) {
+            continue;
+        }
+        match store.get(&secret_ref.uri.store_name()).await {
+            Ok(secret) => {
+                secrets.insert(secret_ref.uri, SecretString::new(secret.expose()));
+            }
+            // Reported with its path when the config is resolved below.
+            Err(SecretError::NotFound(_)) => {}
+            Err(e) => {
+                return Err(ConfigError::Validation(format!(
+                    "{}: failed to read secret '{}': {}",
+                    secret_ref.path, secret_ref.uri, e
+                ))
+                .into())
+            }
+        }
+    }
+
+    Ok(config.resolve_secret_refs(|uri| secrets.get(uri).cloned())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+    use crate::store::FileSecretStore;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"{
+        channels: {
+            telegram: { accounts: { main: { bot_token: "secret://telegram/bot_token" } } },
+        },
+    }"#;
+
+    fn test_store() -> (FileSecretStore, TempDir) {
+        let tmp = TempDir::new().unwrap();
+        let store = FileSecretStore::new(tmp.path().to_path_buf(), crypto::generate_master_key());
+        (store, tmp)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_config_from_store() {
+        let (store, tmp) = test_store();
+        store.set("telegram_bot_token", "123:abc").await.unwrap();
+        let path = tmp.path().join("config.json5");
+        std::fs::write(&path, CONFIG).unwrap();
+
+        let config = load_config(&path, &store).await.unwrap();
+
+        let account = &config.channels.telegram.as_ref().unwrap().accounts["main"];
+        assert_eq!(account.bot_token.expose_secret(), "123:abc");
+        // The file still holds only the reference.
+        let on_disk = std::fs::read_to_string(&path).unwrap();
+        assert!(on_disk.contains("secret://telegram/bot_token"));
+        assert!(!on_disk.contains("123:abc"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_config_missing_secret() {
+        let (store, _tmp) = test_store();
+        let config = Config::parse(CONFIG).unwrap();
+
+        let err = resolve_config(&config, &store).await.unwrap_err();
+
+        assert!(matches!(err, SecretError::Config(_)));
+        let message = err.to_string();
+        assert!(message.contains("channels.telegram.accounts.main.bot_token"), "{}", message);
+        assert!(message.contains("telegram_bot_token"), "{}", message);
+    }
+}
@@ -34,8 +34,7 @@ fn derive_key(master_key: &[u8], salt: &[u8]) -> [u8; KEY_SIZE] {
 /// Returns `(nonce || ciphertext_with_tag, salt)`. The salt is randomly
 /// generated so the same plaintext encrypted twice produces different output.
 pub fn encrypt(master_key: &[u8], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
-    let mut salt = vec![0u8; SALT_SIZE];
-    rand::thread_rng().fill_bytes(&mut salt);
+    let salt = generate_salt();
 
     let mut nonce_bytes = [0u8; NONCE_SIZE];
     rand::thread_rng().fill_bytes(&mut nonce_bytes);
@@ -81,6 +80,20 @@ pub fn decrypt(master_key: &[u8], encrypted: &[u8], salt: &[u8]) -> Result<Vec<u
         .map_err(|e| SecretError::DecryptionFailed(e.to_string()))
 }
 
+/// Derive a 256-bit key from a passphrase via PBKDF2-HMAC-SHA256.
+pub fn derive_key_from_passphrase(passphrase: &[u8], salt: &[u8], iterations: u32) -> Vec<u8> {
+    let mut key = vec![0u8; KEY_SIZE];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase, salt, iterations, &mut key);
+    key
+}
+
+/// Generate a random salt suitable for [`encrypt`] or passphrase derivation.
+pub fn generate_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_SIZE];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
 /// Generate a new random 256-bit master key.
 pub fn generate_master_key() -> Vec<u8> {
     let mut key = vec![0u8; KEY_SIZE];
@@ -146,6 +159,18 @@ mod tests {
         assert_ne!(enc_a, enc_b);
     }
 
+    #[test]
+    fn test_passphrase_derivation_is_deterministic() {
+        let salt = generate_salt();
+        let a = derive_key_from_passphrase(b"correct horse", &salt, 1_000);
+        let b = derive_key_from_passphrase(b"correct horse", &salt, 1_000);
+        let c = derive_key_from_passphrase(b"battery staple", &salt, 1_000);
+
+        assert_eq!(a.len(), KEY_SIZE);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
     #[test]
     fn test_empty_plaintext_works() {
         let master_key = generate_master_key();
@@ -34,6 +34,9 @@ pub enum SecretError {
 
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+
+    #[error("Config error: {0}")]
+    Config(#[from] smartassist_core::error::ConfigError),
 }
 
 /// Convenience result alias for secret operations.
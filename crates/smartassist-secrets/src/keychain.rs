@@ -7,18 +7,104 @@
 //!
 //! On Linux and other platforms the keychain path is not yet implemented;
 //! only the environment variable fallback is used.
+//!
+//! Keychain access goes through the [`KeychainBackend`] trait so that entries
+//! can be migrated between identities (see [`migrate`]) and tested without
+//! touching the real OS keychain. For systems without a usable keychain, see
+//! [`crate::master_key`].
 
-use crate::crypto;
 use crate::error::{Result, SecretError};
-use tracing::debug;
+use crate::master_key::{self, EnvKeyProvider, KeychainKeyProvider, MasterKeyProvider};
+use tracing::{debug, info};
 #[cfg(not(target_os = "macos"))]
-use tracing::warn;
+use {crate::master_key::DEFAULT_ENV_VAR as ENV_VAR, tracing::warn};
 
 const SERVICE_NAME: &str = "smartassist";
 const ACCOUNT_NAME: &str = "master_key";
 
-/// Environment variable name for the master key (hex-encoded).
-const ENV_VAR: &str = "SMARTASSIST_MASTER_KEY";
+/// Service and account name identifying a keychain entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeychainIdentity {
+    /// Keychain service name.
+    pub service: String,
+    /// Keychain account name.
+    pub account: String,
+}
+
+impl KeychainIdentity {
+    /// Create a new identity.
+    pub fn new(service: impl Into<String>, account: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            account: account.into(),
+        }
+    }
+}
+
+impl Default for KeychainIdentity {
+    fn default() -> Self {
+        Self::new(SERVICE_NAME, ACCOUNT_NAME)
+    }
+}
+
+/// Storage backend for keychain entries.
+///
+/// Entries hold the raw stored bytes (the hex-encoded master key).
+pub trait KeychainBackend: Send + Sync {
+    /// Read an entry, returning `None` if it does not exist.
+    fn get(&self, service: &str, account: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Create or overwrite an entry.
+    fn set(&self, service: &str, account: &str, data: &[u8]) -> Result<()>;
+
+    /// Delete an entry. Deleting a missing entry is not an error.
+    fn delete(&self, service: &str, account: &str) -> Result<()>;
+}
+
+impl<B: KeychainBackend + ?Sized> KeychainBackend for &B {
+    fn get(&self, service: &str, account: &str) -> Result<Option<Vec<u8>>> {
+        (**self).get(service, account)
+    }
+
+    fn set(&self, service: &str, account: &str, data: &[u8]) -> Result<()> {
+        (**self).set(service, account, data)
+    }
+
+    fn delete(&self, service: &str, account: &str) -> Result<()> {
+        (**self).delete(service, account)
+    }
+}
+
+/// The platform keychain.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsKeychain;
+
+impl KeychainBackend for OsKeychain {
+    fn get(&self, service: &str, account: &str) -> Result<Option<Vec<u8>>> {
+        get_from_keychain(service, account)
+    }
+
+    fn set(&self, service: &str, account: &str, data: &[u8]) -> Result<()> {
+        store_in_keychain(service, account, data)
+    }
+
+    fn delete(&self, service: &str, account: &str) -> Result<()> {
+        delete_from_keychain(service, account)
+    }
+}
+
+/// How the master key is located in the keychain.
+#[derive(Debug, Clone, Default)]
+pub struct MasterKeyConfig {
+    /// Identity of the master key entry.
+    pub identity: KeychainIdentity,
+
+    /// Identity used by older releases; migrated to `identity` when found.
+    pub legacy_identity: Option<KeychainIdentity>,
+
+    /// Delete the legacy entry after a successful migration.
+    pub delete_legacy: bool,
+}
 
 /// Retrieve the master key, creating one if it does not exist yet.
 ///
@@ -27,37 +113,110 @@ const ENV_VAR: &str = "SMARTASSIST_MASTER_KEY";
 /// 2. OS keychain lookup
 /// 3. Generate + persist to keychain
 pub fn get_or_create_master_key() -> Result<Vec<u8>> {
+    get_or_create_master_key_with(&OsKeychain, &KeychainIdentity::default())
+}
+
+/// Retrieve or create the master key using a specific backend and identity.
+pub fn get_or_create_master_key_with(
+    backend: &dyn KeychainBackend,
+    identity: &KeychainIdentity,
+) -> Result<Vec<u8>> {
     // 1. Try environment variable first.
-    if let Ok(hex_key) = std::env::var(ENV_VAR) {
+    if let Some(key) = EnvKeyProvider::default().load()? {
         debug!("using master key from environment variable");
-        let key = hex::decode(hex_key.trim()).map_err(|e| {
-            SecretError::KeychainError(format!("invalid hex in {ENV_VAR}: {e}"))
-        })?;
-        if key.len() != 32 {
-            return Err(SecretError::KeychainError(format!(
-                "{ENV_VAR} must decode to exactly 32 bytes, got {}",
-                key.len()
-            )));
-        }
-        return Ok(key);
-    }
-
-    // 2. Try OS keychain.
-    if let Some(key) = get_from_keychain()? {
-        debug!("using master key from OS keychain");
         return Ok(key);
     }
 
-    // 3. Generate a new key and store it.
-    debug!("generating new master key and storing in keychain");
-    let key = crypto::generate_master_key();
-    store_in_keychain(&key)?;
-    Ok(key)
+    // 2. Try OS keychain, 3. generate + persist.
+    KeychainKeyProvider::with_backend(identity.clone(), backend).load_or_create()
 }
 
 /// Delete the master key from the OS keychain (for reset workflows).
 pub fn delete_master_key() -> Result<()> {
-    delete_from_keychain()
+    delete_from_keychain(SERVICE_NAME, ACCOUNT_NAME)
+}
+
+/// Move the master key entry to a new service/account name in the OS keychain.
+///
+/// Copies the old entry to the new one and deletes the old entry. Returns
+/// `true` if an entry was copied and `false` if there was nothing to do,
+/// either because the old entry does not exist or because the new entry is
+/// already present, so calling it on every start is safe.
+pub fn migrate(
+    old_service: &str,
+    old_account: &str,
+    new_service: &str,
+    new_account: &str,
+) -> Result<bool> {
+    migrate_with(
+        &OsKeychain,
+        &KeychainIdentity::new(old_service, old_account),
+        &KeychainIdentity::new(new_service, new_account),
+        true,
+    )
+}
+
+/// Move a keychain entry between identities using a specific backend.
+///
+/// An existing new entry is never overwritten. If it already holds the same
+/// key as the old entry (an earlier migration that did not get to delete the
+/// old entry), the old entry is still deleted when `delete_old` is set.
+pub fn migrate_with(
+    backend: &dyn KeychainBackend,
+    old: &KeychainIdentity,
+    new: &KeychainIdentity,
+    delete_old: bool,
+) -> Result<bool> {
+    if old == new {
+        return Ok(false);
+    }
+
+    let Some(data) = backend.get(&old.service, &old.account)? else {
+        return Ok(false);
+    };
+    // Refuse to carry over an entry that is not a usable master key.
+    decode_stored_key(&data)?;
+
+    if let Some(existing) = backend.get(&new.service, &new.account)? {
+        if delete_old && existing == data {
+            backend.delete(&old.service, &old.account)?;
+        }
+        debug!(
+            service = %new.service,
+            account = %new.account,
+            "keychain entry already migrated"
+        );
+        return Ok(false);
+    }
+
+    backend.set(&new.service, &new.account, &data)?;
+
+    // Only drop the old entry once the copy is readable.
+    if backend.get(&new.service, &new.account)?.as_deref() != Some(data.as_slice()) {
+        return Err(SecretError::KeychainError(format!(
+            "migrated entry {}/{} could not be read back",
+            new.service, new.account
+        )));
+    }
+    if delete_old {
+        backend.delete(&old.service, &old.account)?;
+    }
+
+    info!(
+        from = %format!("{}/{}", old.service, old.account),
+        to = %format!("{}/{}", new.service, new.account),
+        "migrated master key keychain entry"
+    );
+    Ok(true)
+}
+
+/// Decode a keychain entry into a 32-byte master key.
+fn decode_stored_key(data: &[u8]) -> Result<Vec<u8>> {
+    // The key is stored as a hex string in the keychain.
+    let hex_str = std::str::from_utf8(data).map_err(|e| {
+        SecretError::KeychainError(format!("keychain data is not valid UTF-8: {e}"))
+    })?;
+    master_key::decode_hex_key(hex_str, "keychain entry")
 }
 
 // ---------------------------------------------------------------------------
@@ -65,26 +224,11 @@ pub fn delete_master_key() -> Result<()> {
 // ---------------------------------------------------------------------------
 
 #[cfg(target_os = "macos")]
-fn get_from_keychain() -> Result<Option<Vec<u8>>> {
+fn get_from_keychain(service: &str, account: &str) -> Result<Option<Vec<u8>>> {
     use security_framework::passwords::get_generic_password;
 
-    match get_generic_password(SERVICE_NAME, ACCOUNT_NAME) {
-        Ok(data) => {
-            // The key is stored as a hex string in the keychain.
-            let hex_str = String::from_utf8(data.to_vec()).map_err(|e| {
-                SecretError::KeychainError(format!("keychain data is not valid UTF-8: {e}"))
-            })?;
-            let key = hex::decode(hex_str.trim()).map_err(|e| {
-                SecretError::KeychainError(format!("keychain data is not valid hex: {e}"))
-            })?;
-            if key.len() != 32 {
-                return Err(SecretError::KeychainError(format!(
-                    "keychain key has wrong length: {} (expected 32)",
-                    key.len()
-                )));
-            }
-            Ok(Some(key))
-        }
+    match get_generic_password(service, account) {
+        Ok(data) => Ok(Some(data.to_vec())),
         Err(e) => {
             // errSecItemNotFound is the expected "not stored yet" case.
             let msg = e.to_string();
@@ -100,20 +244,19 @@ fn get_from_keychain() -> Result<Option<Vec<u8>>> {
 }
 
 #[cfg(target_os = "macos")]
-fn store_in_keychain(key: &[u8]) -> Result<()> {
+fn store_in_keychain(service: &str, account: &str, data: &[u8]) -> Result<()> {
     use security_framework::passwords::set_generic_password;
 
-    let hex_key = hex::encode(key);
-    set_generic_password(SERVICE_NAME, ACCOUNT_NAME, hex_key.as_bytes()).map_err(|e| {
+    set_generic_password(service, account, data).map_err(|e| {
         SecretError::KeychainError(format!("keychain write failed: {e}"))
     })
 }
 
 #[cfg(target_os = "macos")]
-fn delete_from_keychain() -> Result<()> {
+fn delete_from_keychain(service: &str, account: &str) -> Result<()> {
     use security_framework::passwords::delete_generic_password;
 
-    match delete_generic_password(SERVICE_NAME, ACCOUNT_NAME) {
+    match delete_generic_password(service, account) {
         Ok(()) => Ok(()),
         Err(e) => {
             let msg = e.to_string();
@@ -134,7 +277,7 @@ fn delete_from_keychain() -> Result<()> {
 // ---------------------------------------------------------------------------
 
 #[cfg(target_os = "linux")]
-fn get_from_keychain() -> Result<Option<Vec<u8>>> {
+fn get_from_keychain(_service: &str, _account: &str) -> Result<Option<Vec<u8>>> {
     // TODO: Implement secret-service (D-Bus) integration for Linux desktops.
     // For now only the SMARTASSIST_MASTER_KEY env var is supported on Linux.
     warn!("OS keychain not implemented on Linux; use {ENV_VAR} env var");
@@ -142,17 +285,17 @@ fn get_from_keychain() -> Result<Option<Vec<u8>>> {
 }
 
 #[cfg(target_os = "linux")]
-fn store_in_keychain(key: &[u8]) -> Result<()> {
+fn store_in_keychain(_service: &str, _account: &str, data: &[u8]) -> Result<()> {
     warn!(
         "OS keychain not implemented on Linux; master key cannot be persisted. \
          Set {ENV_VAR}={} to reuse this key.",
-        hex::encode(key)
+        String::from_utf8_lossy(data)
     );
     Ok(())
 }
 
 #[cfg(target_os = "linux")]
-fn delete_from_keychain() -> Result<()> {
+fn delete_from_keychain(_service: &str, _account: &str) -> Result<()> {
     // Nothing stored, nothing to delete.
     Ok(())
 }
@@ -162,33 +305,145 @@ fn delete_from_keychain() -> Result<()> {
 // ---------------------------------------------------------------------------
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn get_from_keychain() -> Result<Option<Vec<u8>>> {
+fn get_from_keychain(_service: &str, _account: &str) -> Result<Option<Vec<u8>>> {
     warn!("OS keychain not available on this platform; use {ENV_VAR} env var");
     Ok(None)
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn store_in_keychain(key: &[u8]) -> Result<()> {
+fn store_in_keychain(_service: &str, _account: &str, data: &[u8]) -> Result<()> {
     warn!(
         "OS keychain not available on this platform; master key cannot be persisted. \
          Set {ENV_VAR}={} to reuse this key.",
-        hex::encode(key)
+        String::from_utf8_lossy(data)
     );
     Ok(())
 }
 
 #[cfg(not(any(target_os = "macos", target_os = "linux")))]
-fn delete_from_keychain() -> Result<()> {
+fn delete_from_keychain(_service: &str, _account: &str) -> Result<()> {
     Ok(())
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
+    use crate::crypto;
+    use crate::master_key::DEFAULT_ENV_VAR as ENV_VAR;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Serializes tests that read or modify `SMARTASSIST_MASTER_KEY`.
+    pub(crate) static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// In-memory keychain for tests.
+    #[derive(Default)]
+    pub(crate) struct MockKeychain {
+        entries: Mutex<HashMap<(String, String), Vec<u8>>>,
+    }
+
+    impl MockKeychain {
+        pub(crate) fn contains(&self, identity: &KeychainIdentity) -> bool {
+            self.get(&identity.service, &identity.account).unwrap().is_some()
+        }
+
+        pub(crate) fn insert_key(&self, identity: &KeychainIdentity, key: &[u8]) {
+            self.set(&identity.service, &identity.account, hex::encode(key).as_bytes())
+                .unwrap();
+        }
+    }
+
+    impl KeychainBackend for MockKeychain {
+        fn get(&self, service: &str, account: &str) -> Result<Option<Vec<u8>>> {
+            let entries = self.entries.lock().unwrap();
+            Ok(entries.get(&(service.to_string(), account.to_string())).cloned())
+        }
+
+        fn set(&self, service: &str, account: &str, data: &[u8]) -> Result<()> {
+            let mut entries = self.entries.lock().unwrap();
+            entries.insert((service.to_string(), account.to_string()), data.to_vec());
+            Ok(())
+        }
+
+        fn delete(&self, service: &str, account: &str) -> Result<()> {
+            let mut entries = self.entries.lock().unwrap();
+            entries.remove(&(service.to_string(), account.to_string()));
+            Ok(())
+        }
+    }
+
+    fn legacy() -> KeychainIdentity {
+        KeychainIdentity::new("oldapp", "master_key")
+    }
+
+    #[test]
+    fn test_migrate_copies_and_deletes() {
+        let backend = MockKeychain::default();
+        let key = crypto::generate_master_key();
+        backend.insert_key(&legacy(), &key);
+
+        let new = KeychainIdentity::default();
+        assert!(migrate_with(&backend, &legacy(), &new, true).unwrap());
+
+        assert!(!backend.contains(&legacy()));
+        let stored = backend.get(&new.service, &new.account).unwrap().unwrap();
+        assert_eq!(decode_stored_key(&stored).unwrap(), key);
+    }
+
+    #[test]
+    fn test_migrate_keeps_old_when_asked() {
+        let backend = MockKeychain::default();
+        backend.insert_key(&legacy(), &crypto::generate_master_key());
+
+        let new = KeychainIdentity::default();
+        assert!(migrate_with(&backend, &legacy(), &new, false).unwrap());
+        assert!(backend.contains(&legacy()));
+        assert!(backend.contains(&new));
+    }
+
+    #[test]
+    fn test_migrate_already_migrated_is_noop() {
+        let backend = MockKeychain::default();
+        let new = KeychainIdentity::default();
+        let current = crypto::generate_master_key();
+        backend.insert_key(&new, &current);
+
+        // Nothing left at the old identity.
+        assert!(!migrate_with(&backend, &legacy(), &new, true).unwrap());
+
+        // A different old key never overwrites the current one.
+        backend.insert_key(&legacy(), &crypto::generate_master_key());
+        assert!(!migrate_with(&backend, &legacy(), &new, true).unwrap());
+        assert!(backend.contains(&legacy()));
+        let stored = backend.get(&new.service, &new.account).unwrap().unwrap();
+        assert_eq!(decode_stored_key(&stored).unwrap(), current);
+    }
+
+    #[test]
+    fn test_migrate_finishes_interrupted_cleanup() {
+        let backend = MockKeychain::default();
+        let key = crypto::generate_master_key();
+        backend.insert_key(&legacy(), &key);
+        backend.insert_key(&KeychainIdentity::default(), &key);
+
+        assert!(!migrate_with(&backend, &legacy(), &KeychainIdentity::default(), true).unwrap());
+        assert!(!backend.contains(&legacy()));
+    }
+
+    #[test]
+    fn test_migrate_rejects_invalid_entry() {
+        let backend = MockKeychain::default();
+        backend.set("oldapp", "master_key", b"not-a-key").unwrap();
+
+        let result = migrate_with(&backend, &legacy(), &KeychainIdentity::default(), true);
+        assert!(matches!(result, Err(SecretError::KeychainError(_))));
+        assert!(!backend.contains(&KeychainIdentity::default()));
+    }
 
     /// Test the env-var path, which works on all platforms (including CI).
     #[test]
     fn test_master_key_from_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let key = crypto::generate_master_key();
         let hex_key = hex::encode(&key);
 
@@ -203,6 +458,7 @@ mod tests {
 
     #[test]
     fn test_invalid_hex_in_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         std::env::set_var(ENV_VAR, "not-valid-hex!");
         let result = get_or_create_master_key();
         assert!(result.is_err());
@@ -211,6 +467,7 @@ mod tests {
 
     #[test]
     fn test_wrong_length_key_in_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         // 16 bytes instead of 32.
         std::env::set_var(ENV_VAR, hex::encode([0u8; 16]));
         let result = get_or_create_master_key();
@@ -3,12 +3,19 @@
 //! Provides AES-256-GCM encrypted storage with OS keychain integration
 //! for master key management.
 
+pub mod config;
 pub mod crypto;
 pub mod error;
 pub mod keychain;
+pub mod master_key;
 pub mod store;
 pub mod types;
 
+pub use config::{load_config, resolve_config};
 pub use error::{Result, SecretError};
-pub use store::{FileSecretStore, SecretStore};
+pub use keychain::{KeychainBackend, KeychainIdentity, MasterKeyConfig, OsKeychain};
+pub use master_key::{
+    EnvKeyProvider, KeySource, KeychainKeyProvider, MasterKeyProvider, PassphraseFileKeyProvider,
+};
+pub use store::{CompactionStats, FileSecretStore, SecretStore};
 pub use types::{CreateSecretParams, DecryptedSecret, Secret, SecretRef};
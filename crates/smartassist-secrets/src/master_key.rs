@@ -0,0 +1,580 @@
+//! Pluggable master key storage.
+//!
+//! [`MasterKeyProvider`] abstracts where the master key lives. Three
+//! implementations are provided:
+//!
+//! - [`KeychainKeyProvider`]: the OS keychain (the default)
+//! - [`PassphraseFileKeyProvider`]: a file encrypted with a passphrase, for
+//!   systems without a usable keychain
+//! - [`EnvKeyProvider`]: a hex-encoded environment variable, for containers
+//!
+//! # Container mode
+//!
+//! Containers usually have neither a keychain nor anyone to type a
+//! passphrase. [`KeySource`] reads an existing master key from a mounted
+//! secret file or an environment variable and never generates or stores
+//! one, so a misconfigured container fails instead of silently creating a
+//! new key that cannot decrypt the existing secrets. Open the store with
+//! [`crate::FileSecretStore::open_with_key_source`]:
+//!
+//! ```no_run
+//! use smartassist_secrets::{FileSecretStore, KeySource};
+//!
+//! // `SMARTASSIST_MASTER_KEY_FILE` if set, else `SMARTASSIST_MASTER_KEY`.
+//! let source = KeySource::from_env();
+//! let store = FileSecretStore::open_with_key_source("/data/secrets".into(), &source)?;
+//! # Ok::<(), smartassist_secrets::SecretError>(())
+//! ```
+//!
+//! A key file holds either the 32 raw key bytes or their hex encoding
+//! (trailing whitespace is ignored), e.g. a Docker secret created with
+//! `openssl rand -hex 32`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use zeroize::Zeroizing;
+
+use crate::crypto;
+use crate::error::{Result, SecretError};
+use crate::keychain::{KeychainBackend, KeychainIdentity, OsKeychain};
+
+/// Length of a master key in bytes.
+const MASTER_KEY_LEN: usize = 32;
+
+/// Default environment variable holding the master key (hex-encoded).
+pub const DEFAULT_ENV_VAR: &str = "SMARTASSIST_MASTER_KEY";
+
+/// Environment variable naming a file that holds the master key.
+pub const KEY_FILE_ENV_VAR: &str = "SMARTASSIST_MASTER_KEY_FILE";
+
+/// Default PBKDF2 iteration count for passphrase-protected key files.
+const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// A place the master key can be loaded from and stored to.
+pub trait MasterKeyProvider: Send + Sync {
+    /// Short name for logging.
+    fn name(&self) -> &str;
+
+    /// Load the master key, returning `None` if none is stored.
+    fn load(&self) -> Result<Option<Vec<u8>>>;
+
+    /// Store the master key, replacing any existing one.
+    fn store(&self, key: &[u8]) -> Result<()>;
+
+    /// Delete the stored master key. Deleting a missing key is not an error.
+    fn delete(&self) -> Result<()>;
+
+    /// Load the master key, generating and storing a new one if needed.
+    fn load_or_create(&self) -> Result<Vec<u8>> {
+        if let Some(key) = self.load()? {
+            debug!(provider = self.name(), "loaded master key");
+            return Ok(key);
+        }
+
+        debug!(provider = self.name(), "generating new master key");
+        let key = crypto::generate_master_key();
+        self.store(&key)?;
+        Ok(key)
+    }
+}
+
+/// Decode a hex-encoded master key, naming `source` in errors.
+pub(crate) fn decode_hex_key(hex_key: &str, source: &str) -> Result<Vec<u8>> {
+    let key = hex::decode(hex_key.trim())
+        .map_err(|e| SecretError::KeychainError(format!("invalid hex in {source}: {e}")))?;
+    check_key_len(Zeroizing::new(key), source)
+}
+
+fn is_hex(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Check a decoded key's length, wiping it if the length is wrong.
+fn check_key_len(mut key: Zeroizing<Vec<u8>>, source: &str) -> Result<Vec<u8>> {
+    if key.len() != MASTER_KEY_LEN {
+        return Err(SecretError::KeychainError(format!(
+            "{source} must decode to exactly {MASTER_KEY_LEN} bytes, got {}",
+            key.len()
+        )));
+    }
+    Ok(std::mem::take(&mut *key))
+}
+
+// ---------------------------------------------------------------------------
+// OS keychain
+// ---------------------------------------------------------------------------
+
+/// Master key stored in the OS keychain.
+pub struct KeychainKeyProvider<B = OsKeychain> {
+    backend: B,
+    identity: KeychainIdentity,
+}
+
+impl KeychainKeyProvider {
+    /// Use the OS keychain entry with the given identity.
+    pub fn new(identity: KeychainIdentity) -> Self {
+        Self::with_backend(identity, OsKeychain)
+    }
+}
+
+impl Default for KeychainKeyProvider {
+    fn default() -> Self {
+        Self::new(KeychainIdentity::default())
+    }
+}
+
+impl<B: KeychainBackend> KeychainKeyProvider<B> {
+    /// Use a specific keychain backend.
+    pub fn with_backend(identity: KeychainIdentity, backend: B) -> Self {
+        Self { backend, identity }
+    }
+
+    /// The keychain entry this provider uses.
+    pub fn identity(&self) -> &KeychainIdentity {
+        &self.identity
+    }
+}
+
+impl<B: KeychainBackend> MasterKeyProvider for KeychainKeyProvider<B> {
+    fn name(&self) -> &str {
+        "keychain"
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        let Some(data) = self.backend.get(&self.identity.service, &self.identity.account)? else {
+            return Ok(None);
+        };
+        // The key is stored as a hex string in the keychain.
+        let hex_str = std::str::from_utf8(&data).map_err(|e| {
+            SecretError::KeychainError(format!("keychain data is not valid UTF-8: {e}"))
+        })?;
+        decode_hex_key(hex_str, "keychain entry").map(Some)
+    }
+
+    fn store(&self, key: &[u8]) -> Result<()> {
+        let hex_key = Zeroizing::new(hex::encode(key));
+        self.backend
+            .set(&self.identity.service, &self.identity.account, hex_key.as_bytes())
+    }
+
+    fn delete(&self) -> Result<()> {
+        self.backend.delete(&self.identity.service, &self.identity.account)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Environment variable
+// ---------------------------------------------------------------------------
+
+/// Master key read from a hex-encoded environment variable.
+///
+/// Storing or deleting only changes the current process environment, and
+/// the key itself is never logged. To keep a key across restarts, generate
+/// it yourself (e.g. `openssl rand -hex 32`) and export it before starting.
+#[derive(Debug, Clone)]
+pub struct EnvKeyProvider {
+    var: String,
+}
+
+impl EnvKeyProvider {
+    /// Read the master key from `var`.
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+
+    /// The environment variable name.
+    pub fn var(&self) -> &str {
+        &self.var
+    }
+}
+
+impl Default for EnvKeyProvider {
+    fn default() -> Self {
+        Self::new(DEFAULT_ENV_VAR)
+    }
+}
+
+impl MasterKeyProvider for EnvKeyProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        match std::env::var(&self.var) {
+            Ok(hex_key) => decode_hex_key(&hex_key, &self.var).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn store(&self, key: &[u8]) -> Result<()> {
+        warn!(
+            var = %self.var,
+            "master key set for this process only; export {} to persist it",
+            self.var
+        );
+        std::env::set_var(&self.var, hex::encode(key));
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<()> {
+        std::env::remove_var(&self.var);
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Container key source
+// ---------------------------------------------------------------------------
+
+/// Where container mode reads an existing master key from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// A hex-encoded key in this environment variable.
+    Env(String),
+
+    /// A file holding the raw key bytes or their hex encoding.
+    File(PathBuf),
+}
+
+impl KeySource {
+    /// The file named by `SMARTASSIST_MASTER_KEY_FILE` if it is set,
+    /// otherwise the `SMARTASSIST_MASTER_KEY` variable.
+    pub fn from_env() -> Self {
+        match std::env::var_os(KEY_FILE_ENV_VAR) {
+            Some(path) => Self::File(path.into()),
+            None => Self::Env(DEFAULT_ENV_VAR.to_string()),
+        }
+    }
+
+    /// Read and validate the key.
+    ///
+    /// Fails if the key is missing, malformed or not exactly 32 bytes. The
+    /// key and any intermediate copies made here are zeroized on drop;
+    /// the process environment keeps its own copy of an `Env` key.
+    pub fn read(&self) -> Result<Zeroizing<Vec<u8>>> {
+        match self {
+            Self::Env(var) => {
+                let hex_key = Zeroizing::new(std::env::var(var).map_err(|_| {
+                    SecretError::KeychainError(format!("master key variable {var} is not set"))
+                })?);
+                decode_hex_key(&hex_key, var).map(Zeroizing::new)
+            }
+            Self::File(path) => {
+                let data = Zeroizing::new(std::fs::read(path).map_err(|e| {
+                    SecretError::KeychainError(format!(
+                        "cannot read master key file {}: {e}",
+                        path.display()
+                    ))
+                })?);
+                let source = format!("master key file {}", path.display());
+                // Text that looks like hex is decoded, so a short hex key is
+                // reported as such rather than taken as raw bytes.
+                match std::str::from_utf8(&data) {
+                    Ok(text) if is_hex(text.trim()) => {
+                        decode_hex_key(text, &source).map(Zeroizing::new)
+                    }
+                    _ if data.len() == MASTER_KEY_LEN => Ok(Zeroizing::new(data.to_vec())),
+                    _ => Err(SecretError::KeychainError(format!(
+                        "{source} must hold {MASTER_KEY_LEN} raw bytes or their hex encoding, \
+                         got {} bytes",
+                        data.len()
+                    ))),
+                }
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Passphrase-protected file
+// ---------------------------------------------------------------------------
+
+/// On-disk format of a passphrase-protected master key.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyFile {
+    /// Format version.
+    version: u32,
+    /// PBKDF2-HMAC-SHA256 iteration count.
+    iterations: u32,
+    /// PBKDF2 salt, hex-encoded.
+    kdf_salt: String,
+    /// Encrypted master key, base64-encoded.
+    encrypted_key: String,
+    /// HKDF salt used by [`crypto::encrypt`], hex-encoded.
+    salt: String,
+}
+
+/// Master key stored in a file, encrypted with a key derived from a passphrase.
+pub struct PassphraseFileKeyProvider {
+    path: PathBuf,
+    passphrase: Zeroizing<String>,
+    iterations: u32,
+}
+
+impl PassphraseFileKeyProvider {
+    /// Store the master key at `path`, protected by `passphrase`.
+    pub fn new(path: impl Into<PathBuf>, passphrase: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            passphrase: Zeroizing::new(passphrase.into()),
+            iterations: DEFAULT_PBKDF2_ITERATIONS,
+        }
+    }
+
+    /// Set the PBKDF2 iteration count used when storing a key.
+    ///
+    /// Loading always uses the count recorded in the file.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// The key file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl MasterKeyProvider for PassphraseFileKeyProvider {
+    fn name(&self) -> &str {
+        "passphrase-file"
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        let data = match std::fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let file: KeyFile = serde_json::from_str(&data)?;
+        if file.version != 1 {
+            return Err(SecretError::StorageError(format!(
+                "unsupported key file version {}",
+                file.version
+            )));
+        }
+
+        let kdf_salt = hex::decode(&file.kdf_salt)
+            .map_err(|e| SecretError::DecryptionFailed(format!("hex decode failed: {e}")))?;
+        let salt = hex::decode(&file.salt)
+            .map_err(|e| SecretError::DecryptionFailed(format!("hex decode failed: {e}")))?;
+        let encrypted = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &file.encrypted_key,
+        )
+        .map_err(|e| SecretError::DecryptionFailed(format!("base64 decode failed: {e}")))?;
+
+        let wrapping_key = Zeroizing::new(crypto::derive_key_from_passphrase(
+            self.passphrase.as_bytes(),
+            &kdf_salt,
+            file.iterations,
+        ));
+        let key = crypto::decrypt(&wrapping_key, &encrypted, &salt).map_err(|_| {
+            SecretError::DecryptionFailed("wrong passphrase or corrupted key file".to_string())
+        })?;
+        check_key_len(Zeroizing::new(key), "key file").map(Some)
+    }
+
+    fn store(&self, key: &[u8]) -> Result<()> {
+        let kdf_salt = crypto::generate_salt();
+        let wrapping_key = Zeroizing::new(crypto::derive_key_from_passphrase(
+            self.passphrase.as_bytes(),
+            &kdf_salt,
+            self.iterations,
+        ));
+        let (encrypted, salt) = crypto::encrypt(&wrapping_key, key)?;
+
+        let file = KeyFile {
+            version: 1,
+            iterations: self.iterations,
+            kdf_salt: hex::encode(&kdf_salt),
+            encrypted_key: base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                &encrypted,
+            ),
+            salt: hex::encode(&salt),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(&file)?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut out = options.open(&self.path)?;
+        #[cfg(unix)]
+        {
+            // The mode only applies to a new file; tighten an existing one
+            // before writing the key into it.
+            use std::os::unix::fs::PermissionsExt;
+            out.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        out.write_all(contents.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keychain::tests::MockKeychain;
+    use tempfile::TempDir;
+
+    /// Behaviour every provider must share.
+    fn conformance(provider: &dyn MasterKeyProvider) {
+        assert!(provider.load().unwrap().is_none(), "starts empty");
+
+        let key = crypto::generate_master_key();
+        provider.store(&key).unwrap();
+        assert_eq!(provider.load().unwrap(), Some(key.clone()));
+
+        let replacement = crypto::generate_master_key();
+        provider.store(&replacement).unwrap();
+        assert_eq!(provider.load().unwrap(), Some(replacement));
+
+        provider.delete().unwrap();
+        assert!(provider.load().unwrap().is_none(), "empty after delete");
+        provider.delete().unwrap();
+
+        let created = provider.load_or_create().unwrap();
+        assert_eq!(created.len(), MASTER_KEY_LEN);
+        assert_eq!(provider.load_or_create().unwrap(), created);
+
+        provider.delete().unwrap();
+    }
+
+    #[test]
+    fn test_keychain_provider_conformance() {
+        let provider = KeychainKeyProvider::with_backend(
+            KeychainIdentity::new("smartassist-test", "master_key"),
+            MockKeychain::default(),
+        );
+        conformance(&provider);
+    }
+
+    #[test]
+    fn test_env_provider_conformance() {
+        // A dedicated variable keeps this independent of other tests.
+        let provider = EnvKeyProvider::new("SMARTASSIST_TEST_MASTER_KEY_CONFORMANCE");
+        conformance(&provider);
+    }
+
+    #[test]
+    fn test_passphrase_file_provider_conformance() {
+        let tmp = TempDir::new().unwrap();
+        let provider = PassphraseFileKeyProvider::new(tmp.path().join("master.key"), "hunter2")
+            .with_iterations(1_000);
+        conformance(&provider);
+    }
+
+    #[test]
+    fn test_passphrase_file_wrong_passphrase() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("master.key");
+        PassphraseFileKeyProvider::new(&path, "hunter2")
+            .with_iterations(1_000)
+            .store(&crypto::generate_master_key())
+            .unwrap();
+
+        let result = PassphraseFileKeyProvider::new(&path, "wrong").load();
+        assert!(matches!(result, Err(SecretError::DecryptionFailed(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_passphrase_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        let provider = PassphraseFileKeyProvider::new(tmp.path().join("master.key"), "hunter2")
+            .with_iterations(1_000);
+        provider.load_or_create().unwrap();
+
+        let mode = std::fs::metadata(provider.path()).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+
+        // Overwriting a file left readable by others makes it private too.
+        let perms = std::fs::Permissions::from_mode(0o644);
+        std::fs::set_permissions(provider.path(), perms).unwrap();
+        provider.store(&[7u8; 32]).unwrap();
+        let mode = std::fs::metadata(provider.path()).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn test_key_source_env() {
+        let var = "SMARTASSIST_TEST_MASTER_KEY_SOURCE";
+        let key = crypto::generate_master_key();
+        std::env::set_var(var, hex::encode(&key));
+
+        let read = KeySource::Env(var.to_string()).read().unwrap();
+        assert_eq!(*read, key);
+
+        std::env::remove_var(var);
+        let err = KeySource::Env(var.to_string()).read().unwrap_err();
+        assert!(err.to_string().contains("is not set"), "{err}");
+    }
+
+    #[test]
+    fn test_key_source_file_raw_and_hex() {
+        let tmp = TempDir::new().unwrap();
+        let key = crypto::generate_master_key();
+
+        let raw = tmp.path().join("raw.key");
+        std::fs::write(&raw, &key).unwrap();
+        assert_eq!(*KeySource::File(raw).read().unwrap(), key);
+
+        let hex_file = tmp.path().join("hex.key");
+        std::fs::write(&hex_file, format!("{}\n", hex::encode(&key))).unwrap();
+        assert_eq!(*KeySource::File(hex_file).read().unwrap(), key);
+
+        let missing = KeySource::File(tmp.path().join("missing.key")).read();
+        assert!(matches!(missing, Err(SecretError::KeychainError(_))));
+    }
+
+    #[test]
+    fn test_key_source_rejects_wrong_length() {
+        let tmp = TempDir::new().unwrap();
+
+        let short = tmp.path().join("short.key");
+        std::fs::write(&short, hex::encode([7u8; 16])).unwrap();
+        let err = KeySource::File(short).read().unwrap_err().to_string();
+        assert!(err.contains("exactly 32 bytes, got 16"), "{err}");
+
+        let binary = tmp.path().join("binary.key");
+        std::fs::write(&binary, [0xffu8; 31]).unwrap();
+        assert!(KeySource::File(binary).read().is_err());
+
+        let var = "SMARTASSIST_TEST_MASTER_KEY_SOURCE_SHORT";
+        std::env::set_var(var, hex::encode([7u8; 33]));
+        let err = KeySource::Env(var.to_string()).read().unwrap_err().to_string();
+        assert!(err.contains("got 33"), "{err}");
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_env_provider_rejects_bad_key() {
+        let provider = EnvKeyProvider::new("SMARTASSIST_TEST_MASTER_KEY_BAD");
+        std::env::set_var(provider.var(), hex::encode([0u8; 16]));
+        assert!(matches!(provider.load(), Err(SecretError::KeychainError(_))));
+        provider.delete().unwrap();
+    }
+}
@@ -5,14 +5,19 @@
 //! under `~/.smartassist/secrets/`.
 
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
 use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
+use zeroize::Zeroizing;
 
 use crate::crypto;
 use crate::error::{Result, SecretError};
+use crate::keychain::{self, KeychainBackend, MasterKeyConfig, OsKeychain};
+use crate::master_key::{KeySource, MasterKeyProvider};
 use crate::types::{DecryptedSecret, SecretRef};
 
 /// Maximum allowed length for a secret name.
@@ -52,13 +57,29 @@ struct StoredSecret {
     usage_count: u64,
 }
 
+/// Result of [`FileSecretStore::compact`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Live secrets kept in the store.
+    pub entries_kept: usize,
+    /// Dead entries deleted.
+    pub entries_removed: usize,
+    /// Unreadable files left in place.
+    pub entries_skipped: usize,
+    /// Bytes freed on disk.
+    pub bytes_reclaimed: u64,
+}
+
 /// A file-system-backed secret store.
 ///
 /// Each secret is stored as an individual JSON file at
 /// `{base_dir}/{name}.json`. Files are created with mode `0600` on Unix.
 pub struct FileSecretStore {
     base_dir: PathBuf,
-    master_key: Vec<u8>,
+    master_key: Zeroizing<Vec<u8>>,
+    /// Serializes writes so [`FileSecretStore::compact`] never rewrites a
+    /// secret with a value read before a concurrent update.
+    write_lock: tokio::sync::Mutex<()>,
 }
 
 impl FileSecretStore {
@@ -66,17 +87,60 @@ impl FileSecretStore {
     pub fn new(base_dir: PathBuf, master_key: Vec<u8>) -> Self {
         Self {
             base_dir,
-            master_key,
+            master_key: Zeroizing::new(master_key),
+            write_lock: tokio::sync::Mutex::new(()),
         }
     }
 
     /// Create a store using the default directory (`~/.smartassist/secrets/`) and
     /// the master key resolved via [`crate::keychain::get_or_create_master_key`].
     pub fn from_default_dir() -> Result<Self> {
-        let base_dir = smartassist_core::paths::base_dir()
-            .map_err(|e| SecretError::StorageError(e.to_string()))?
-            .join("secrets");
-        let master_key = crate::keychain::get_or_create_master_key()?;
+        Self::open(default_dir()?, &MasterKeyConfig::default())
+    }
+
+    /// Create a store whose master key comes from `provider`.
+    ///
+    /// A new master key is generated and stored if the provider has none.
+    pub fn with_key_provider(base_dir: PathBuf, provider: &dyn MasterKeyProvider) -> Result<Self> {
+        let master_key = provider.load_or_create()?;
+        Ok(Self::new(base_dir, master_key))
+    }
+
+    /// Open a store rooted at `base_dir`, resolving the master key from the
+    /// OS keychain as described by `config`.
+    ///
+    /// If `config.legacy_identity` is set, the master key entry is migrated
+    /// from it before the key is resolved, so stores created under an older
+    /// service/account name keep working.
+    pub fn open(base_dir: PathBuf, config: &MasterKeyConfig) -> Result<Self> {
+        Self::open_with_backend(base_dir, config, &OsKeychain)
+    }
+
+    /// Open a store in container mode, with the master key read from a
+    /// mounted file or an environment variable instead of the keychain.
+    ///
+    /// The key must already exist; none is generated. See
+    /// [`crate::master_key`] for the accepted formats.
+    pub fn open_with_key_source(base_dir: PathBuf, source: &KeySource) -> Result<Self> {
+        let master_key = source.read()?;
+        debug!(?source, "using master key from container key source");
+        Ok(Self {
+            base_dir,
+            master_key,
+            write_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Open a store using a specific keychain backend.
+    pub fn open_with_backend(
+        base_dir: PathBuf,
+        config: &MasterKeyConfig,
+        backend: &dyn KeychainBackend,
+    ) -> Result<Self> {
+        if let Some(legacy) = &config.legacy_identity {
+            keychain::migrate_with(backend, legacy, &config.identity, config.delete_legacy)?;
+        }
+        let master_key = keychain::get_or_create_master_key_with(backend, &config.identity)?;
         Ok(Self::new(base_dir, master_key))
     }
 
@@ -94,12 +158,100 @@ impl FileSecretStore {
         Ok(())
     }
 
+    /// Remove dead entries from the store directory.
+    ///
+    /// Dead entries are temporary files left behind by writes that were
+    /// interrupted before their atomic rename. Only temporary files older
+    /// than [`STALE_TEMP_AGE`] are removed, so a write still in progress in
+    /// another process is not disturbed. Live secrets are rewritten in
+    /// place (again via atomic rename), so their current values are never at
+    /// risk. Files that cannot be parsed as secrets are left untouched and
+    /// only counted, since they may still be recoverable.
+    ///
+    /// Each secret is a single file holding only its current value, so there
+    /// are no superseded versions to prune here.
+    pub async fn compact(&self) -> Result<CompactionStats> {
+        let mut stats = CompactionStats::default();
+        if !self.base_dir.exists() {
+            return Ok(stats);
+        }
+        let _guard = self.write_lock.lock().await;
+
+        // Sorted so the outcome does not depend on directory iteration order.
+        let mut paths = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.base_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+
+            if file_name.ends_with(TEMP_SUFFIX) {
+                let metadata = tokio::fs::metadata(&path).await?;
+                let age = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .unwrap_or_default();
+                if age < STALE_TEMP_AGE {
+                    continue;
+                }
+                let size = metadata.len();
+                debug!(path = %path.display(), "removing leftover temporary file");
+                tokio::fs::remove_file(&path).await?;
+                stats.entries_removed += 1;
+                stats.bytes_reclaimed += size;
+                continue;
+            }
+
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let data = tokio::fs::read(&path).await?;
+            let Ok(stored) = serde_json::from_slice::<StoredSecret>(&data) else {
+                tracing::warn!(path = %path.display(), "keeping unreadable secret file");
+                stats.entries_skipped += 1;
+                continue;
+            };
+
+            let rewritten = serde_json::to_vec_pretty(&stored)?;
+            if rewritten != data {
+                write_secret_file(&path, &rewritten).await?;
+                stats.bytes_reclaimed += (data.len() as u64).saturating_sub(rewritten.len() as u64);
+            }
+            stats.entries_kept += 1;
+        }
+
+        debug!(
+            kept = stats.entries_kept,
+            removed = stats.entries_removed,
+            bytes = stats.bytes_reclaimed,
+            "compacted secret store"
+        );
+        Ok(stats)
+    }
+
     /// Resolve the path for a secret file.
     fn secret_path(&self, name: &str) -> PathBuf {
         self.base_dir.join(format!("{name}.json"))
     }
 }
 
+/// The default secrets directory (`~/.smartassist/secrets/`).
+fn default_dir() -> Result<PathBuf> {
+    Ok(smartassist_core::paths::base_dir()
+        .map_err(|e| SecretError::StorageError(e.to_string()))?
+        .join("secrets"))
+}
+
 /// Validate that a secret name contains only safe characters.
 ///
 /// Allowed: ASCII alphanumeric, underscore, hyphen. Max length 128.
@@ -125,17 +277,43 @@ fn validate_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Suffix of the temporary file a secret is written to before being renamed
+/// into place.
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// How old a temporary file must be before [`FileSecretStore::compact`]
+/// treats it as left behind by an interrupted write.
+const STALE_TEMP_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Distinguishes temporary files written concurrently by one process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
 /// Write `data` to `path` with mode 0600 on Unix.
+///
+/// The data is written to a temporary sibling file and renamed over `path`,
+/// so a crash mid-write leaves either the old or the new contents, never a
+/// truncated file. Each write gets its own temporary file, named after the
+/// process id and a per-process counter, so concurrent writes of the same
+/// secret do not collide.
 async fn write_secret_file(path: &std::path::Path, data: &[u8]) -> Result<()> {
-    tokio::fs::write(path, data).await?;
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(format!(
+        ".{}.{}{TEMP_SUFFIX}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = PathBuf::from(temp);
+
+    tokio::fs::write(&temp, data).await?;
 
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
         let perms = std::fs::Permissions::from_mode(0o600);
-        tokio::fs::set_permissions(path, perms).await?;
+        tokio::fs::set_permissions(&temp, perms).await?;
     }
 
+    tokio::fs::rename(&temp, path).await?;
     Ok(())
 }
 
@@ -162,6 +340,7 @@ impl SecretStore for FileSecretStore {
         let json = serde_json::to_string_pretty(&stored)?;
         let path = self.secret_path(name);
         debug!(name, path = %path.display(), "writing secret");
+        let _guard = self.write_lock.lock().await;
         write_secret_file(&path, json.as_bytes()).await?;
         Ok(())
     }
@@ -192,6 +371,7 @@ impl SecretStore for FileSecretStore {
         // Update usage count and persist.
         stored.usage_count += 1;
         let json = serde_json::to_string_pretty(&stored)?;
+        let _guard = self.write_lock.lock().await;
         write_secret_file(&path, json.as_bytes()).await?;
 
         debug!(name, "read secret (usage_count={})", stored.usage_count);
@@ -256,6 +436,7 @@ impl SecretStore for FileSecretStore {
         }
 
         debug!(name, path = %path.display(), "deleting secret");
+        let _guard = self.write_lock.lock().await;
         tokio::fs::remove_file(&path).await?;
         Ok(())
     }
@@ -392,6 +573,159 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_open_migrates_legacy_identity() {
+        use crate::keychain::tests::{MockKeychain, ENV_LOCK};
+        use crate::keychain::KeychainIdentity;
+
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let tmp = TempDir::new().unwrap();
+        let backend = MockKeychain::default();
+        let legacy = KeychainIdentity::new("oldapp", "master_key");
+        backend.insert_key(&legacy, &crypto::generate_master_key());
+
+        let config = MasterKeyConfig {
+            legacy_identity: Some(legacy.clone()),
+            delete_legacy: true,
+            ..Default::default()
+        };
+        FileSecretStore::open_with_backend(tmp.path().to_path_buf(), &config, &backend).unwrap();
+
+        assert!(!backend.contains(&legacy));
+        assert!(backend.contains(&config.identity));
+
+        // Opening again is a no-op for the keychain.
+        FileSecretStore::open_with_backend(tmp.path().to_path_buf(), &config, &backend).unwrap();
+        assert!(backend.contains(&config.identity));
+    }
+
+    #[tokio::test]
+    async fn test_with_key_provider_reuses_key() {
+        use crate::master_key::PassphraseFileKeyProvider;
+
+        let tmp = TempDir::new().unwrap();
+        let provider = PassphraseFileKeyProvider::new(tmp.path().join("master.key"), "hunter2")
+            .with_iterations(1_000);
+        let dir = tmp.path().join("secrets");
+
+        let store = FileSecretStore::with_key_provider(dir.clone(), &provider).unwrap();
+        store.set("api_key", "sk-abc123").await.unwrap();
+
+        let reopened = FileSecretStore::with_key_provider(dir, &provider).unwrap();
+        assert_eq!(reopened.get("api_key").await.unwrap().expose(), "sk-abc123");
+    }
+
+    #[tokio::test]
+    async fn test_open_with_env_key_source() {
+        let var = "SMARTASSIST_TEST_CONTAINER_KEY";
+        std::env::set_var(var, hex::encode(crypto::generate_master_key()));
+        let tmp = TempDir::new().unwrap();
+        let source = KeySource::Env(var.to_string());
+
+        let store = FileSecretStore::open_with_key_source(tmp.path().to_path_buf(), &source)
+            .unwrap();
+        store.set("api_key", "sk-abc123").await.unwrap();
+
+        let reopened = FileSecretStore::open_with_key_source(tmp.path().to_path_buf(), &source)
+            .unwrap();
+        assert_eq!(reopened.get("api_key").await.unwrap().expose(), "sk-abc123");
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_open_with_key_source_rejects_wrong_length() {
+        let var = "SMARTASSIST_TEST_CONTAINER_KEY_SHORT";
+        std::env::set_var(var, hex::encode([1u8; 24]));
+        let tmp = TempDir::new().unwrap();
+
+        let source = KeySource::Env(var.to_string());
+        let result = FileSecretStore::open_with_key_source(tmp.path().to_path_buf(), &source);
+        assert!(matches!(result, Err(SecretError::KeychainError(msg)) if msg.contains("got 24")));
+        std::env::remove_var(var);
+    }
+
+    #[tokio::test]
+    async fn test_writes_leave_no_temp_files() {
+        let (store, tmp) = test_store();
+        store.set("api_key", "v1").await.unwrap();
+        store.set("api_key", "v2").await.unwrap();
+        let _ = store.get("api_key").await.unwrap();
+
+        let names: Vec<_> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(names, vec!["api_key.json"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_writes_of_same_secret() {
+        let (store, tmp) = test_store();
+        let store = std::sync::Arc::new(store);
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let store = store.clone();
+                tokio::spawn(async move { store.set("api_key", &format!("v{i}")).await })
+            })
+            .collect();
+        for writer in writers {
+            writer.await.unwrap().unwrap();
+        }
+        let compacted = store.compact().await.unwrap();
+        assert_eq!(compacted.entries_kept, 1);
+
+        assert!(store.get("api_key").await.unwrap().expose().starts_with('v'));
+        let names: Vec<_> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(names, vec!["api_key.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_compact_drops_dead_entries() {
+        let (store, tmp) = test_store();
+        store.set("api_key", "old").await.unwrap();
+        store.set("api_key", "current").await.unwrap();
+        store.set("other", "value").await.unwrap();
+        store.set("removed", "gone").await.unwrap();
+        store.delete("removed").await.unwrap();
+
+        // Leftovers from writes interrupted before their rename.
+        let stale = std::time::SystemTime::now() - STALE_TEMP_AGE - Duration::from_secs(1);
+        for (name, data) in [
+            ("api_key.json.1.0.tmp", &b"{\"partial\":"[..]),
+            ("removed.json.1.1.tmp", &b"stale"[..]),
+        ] {
+            let file = std::fs::File::create(tmp.path().join(name)).unwrap();
+            std::io::Write::write_all(&mut &file, data).unwrap();
+            file.set_modified(stale).unwrap();
+        }
+        // A write still in flight is left alone.
+        std::fs::write(tmp.path().join("other.json.2.0.tmp"), b"{").unwrap();
+        // Unreadable files are kept, not deleted.
+        std::fs::write(tmp.path().join("corrupt.json"), b"not json").unwrap();
+
+        let stats = store.compact().await.unwrap();
+        assert_eq!(stats.entries_kept, 2);
+        assert_eq!(stats.entries_removed, 2);
+        assert_eq!(stats.entries_skipped, 1);
+        assert_eq!(stats.bytes_reclaimed, 16);
+
+        assert!(!tmp.path().join("api_key.json.1.0.tmp").exists());
+        assert!(!tmp.path().join("removed.json.1.1.tmp").exists());
+        assert!(tmp.path().join("other.json.2.0.tmp").exists());
+        assert!(tmp.path().join("corrupt.json").exists());
+        assert_eq!(store.get("api_key").await.unwrap().expose(), "current");
+        assert_eq!(store.get("other").await.unwrap().expose(), "value");
+        assert!(!store.exists("removed").await.unwrap());
+
+        // A second pass has nothing left to do.
+        let again = store.compact().await.unwrap();
+        assert_eq!(again.entries_removed, 0);
+        assert_eq!(again.bytes_reclaimed, 0);
+    }
+
     #[cfg(unix)]
     #[tokio::test]
     async fn test_file_permissions() {
@@ -166,6 +166,7 @@ impl PluginTool for HelloTool {
                     }
                 }
             }),
+            output_schema: None,
             execution: ToolExecutionConfig::default(),
         }
     }